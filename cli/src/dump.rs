@@ -102,5 +102,13 @@ pub fn handle(
         terminal::render_summaries(model, &annotations, options)?;
     }
 
+    if sub_matches.is_present("memory") {
+        let model = params
+            .tract_model
+            .downcast_ref::<TypedModel>()
+            .context("Can only plan memory for typed models")?;
+        println!("{}", SimplePlan::new(model)?.memory_plan()?);
+    }
+
     Ok(())
 }
@@ -60,6 +60,7 @@ pub const STAGES: &'static [&'static str] = &[
     "analyse",
     "incorporate",
     "type",
+    "set",
     "declutter",
     "concretize-stream-dim",
     "concretize-stream-dim-declutter",
@@ -91,7 +92,7 @@ fn main() -> tract_core::anyhow::Result<()> {
     (@arg model: +takes_value "Sets the model to use")
 
     (@arg format: -f +takes_value
-     "Hint the model format ('kaldi', 'onnx' or 'tf') instead of guess from extension.")
+     "Hint the model format ('kaldi', 'onnx', 'nnef', 'tf' or 'tflite') instead of guess from extension.")
 
     (@arg input: -i --input +takes_value +multiple number_of_values(1)
      "Set input shape and type (@file.pb or @file.npz:thing.npy or 3x4xi32).")
@@ -106,7 +107,7 @@ fn main() -> tract_core::anyhow::Result<()> {
      "Adjust value of final offset in network (for reproducibility)")
 
     (@arg kaldi_downsample: --("kaldi-downsample") +takes_value
-     "Add a subsampling to output on axis 0")
+     "Frame subsampling factor: outputs are computed every N frames")
 
     (@arg kaldi_left_context: --("kaldi-left-context") +takes_value
      "Add lines of left context to input (dupping first time frame)")
@@ -123,6 +124,12 @@ fn main() -> tract_core::anyhow::Result<()> {
     (@arg tf_initializer_output_node: --("tf-initializer-output-node") +takes_value +multiple number_of_values(1)
      "Set an initializer node")
 
+    (@arg tf_saved_model_tag: --("tf-saved-model-tag") +takes_value +multiple number_of_values(1)
+     "Meta graph tag to pick in a SavedModel directory (defaults to serve)")
+
+    (@arg tf_signature: --("tf-signature") +takes_value
+     "Signature to use in a SavedModel directory (defaults to serving_default)")
+
     (@arg output_node: --("output-node") +takes_value +multiple number_of_values(1)
      "Override output nodes name (auto-detects otherwise).")
 
@@ -144,10 +151,14 @@ fn main() -> tract_core::anyhow::Result<()> {
     (@arg nnef_tract_core: --("nnef-tract-core") "Allow usage of tract-core extension in NNEF dump and load")
     (@arg nnef_tract_onnx: --("nnef-tract-onnx") "Allow usage of tract-onnx extension in NNEF dump and load")
     (@arg nnef_tract_pulse: --("nnef-tract-pulse") "Allow usage of tract-pulse extension in NNEF dump and load")
+    (@arg nnef_compound_fragments: --("nnef-compound-fragments") "Dump repeated subgraphs as NNEF fragments")
+    (@arg nnef_mmap: --("nnef-mmap") "Memory map NNEF directories and uncompressed tars instead of reading them")
 
     (@arg optimize: -O --optimize "Optimize before running")
-    (@arg pulse: --pulse +takes_value "Translate to pulse network")
+    (@arg pulse: --pulse +takes_value "Translate to pulse network (PULSE for stream S, or S=PULSE,T=PULSE for several streams)")
     (@arg concretize_stream_dim: --("concretize-stream-dim") +takes_value "Replace streaming dim by a concrete value")
+    (@arg assert: --assert +takes_value +multiple number_of_values(1) "Assert a fact about symbols (--assert S>=1, --assert T%4==0)")
+    (@arg set: --set +takes_value +multiple number_of_values(1) "Set a symbol to a concrete value (--set batch_size=1)")
 
     (@arg verbosity: -v ... "Sets the level of verbosity.")
 
@@ -218,6 +229,7 @@ fn main() -> tract_core::anyhow::Result<()> {
         .long_about("Dumps the Tensorflow graph in human readable form.")
         .arg(Arg::with_name("cost").long("cost").help("Include const information"))
         .arg(Arg::with_name("profile").long("profile").help("Include results for profile run"))
+        .arg(Arg::with_name("memory").long("memory").help("Show the memory plan (typed models only)"))
         .arg(
             Arg::with_name("assert-cost")
             .takes_value(true)
@@ -583,5 +595,11 @@ fn nnef(matches: &clap::ArgMatches) -> tract_nnef::internal::Nnef {
     if matches.is_present("nnef_tract_core") {
         fw = fw.with_tract_core();
     }
+    if matches.is_present("nnef_compound_fragments") {
+        fw = fw.with_compound_fragments(true);
+    }
+    if matches.is_present("nnef_mmap") {
+        fw = fw.with_mmap(true);
+    }
     fw
 }
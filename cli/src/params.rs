@@ -108,8 +108,12 @@ impl Parameters {
         let format = matches.value_of("format").unwrap_or(
             if filename.extension().map(|s| s == "onnx").unwrap_or(false) {
                 "onnx"
+            } else if filename.extension().map(|s| s == "tflite").unwrap_or(false) {
+                "tflite"
             } else if filename.extension().map(|s| s == "raw" || s == "txt").unwrap_or(false) {
                 "kaldi"
+            } else if filename.is_dir() && filename.join("saved_model.pb").exists() {
+                "tf"
             } else if filename.is_dir()
                 || filename.to_string_lossy().ends_with(".tar")
                 || filename.to_string_lossy().ends_with(".tar.gz")
@@ -130,6 +134,9 @@ impl Parameters {
                 if let Some(i) = matches.value_of("kaldi_adjust_final_offset") {
                     graph.adjust_final_offset = i.parse()?;
                 }
+                if let Some(factor) = matches.value_of("kaldi_downsample") {
+                    graph.frame_subsampling_factor = factor.parse()?;
+                }
                 let parsed = kaldi.model_for_proto_model(&graph)?;
                 if need_graph {
                     (SomeGraphDef::Kaldi(graph), Box::new(parsed), Option::<TfExt>::None)
@@ -139,11 +146,11 @@ impl Parameters {
             }
             "nnef" => {
                 let nnef = super::nnef(&matches);
-                let mut file = std::fs::File::open(&filename)?;
                 let proto_model = if filename.to_string_lossy().ends_with("gz") {
+                    let file = std::fs::File::open(&filename)?;
                     nnef.proto_model_for_read(&mut flate2::read::GzDecoder::new(file))?
                 } else {
-                    nnef.proto_model_for_read(&mut file)?
+                    nnef.proto_model_for_path(&filename)?
                 };
                 info_usage("proto model loaded", probe);
                 if need_graph {
@@ -183,16 +190,40 @@ impl Parameters {
                     (SomeGraphDef::NoGraphDef, Box::new(parsed.model), Option::<TfExt>::None)
                 }
             }
+            #[cfg(feature = "tflite")]
+            "tflite" => {
+                let tflite = tract_tflite::tflite();
+                info_usage("loaded framework (tflite)", probe);
+                let proto = tflite.proto_model_for_path(&filename)?;
+                info_usage("proto model loaded", probe);
+                let model = tflite.model_for_proto_model(&proto)?;
+                (SomeGraphDef::NoGraphDef, Box::new(model), Option::<TfExt>::None)
+            }
             #[cfg(feature = "tf")]
             "tf" => {
                 let tf = tract_tensorflow::tensorflow();
                 info_usage("loaded framework (tf)", probe);
-                let mut graph = tf.proto_model_for_path(&filename)?;
+                let (mut graph, signature) = if filename.is_dir() {
+                    let tags = matches
+                        .values_of("tf_saved_model_tag")
+                        .map(|tags| tags.collect())
+                        .unwrap_or(vec!["serve"]);
+                    let signature = matches.value_of("tf_signature").unwrap_or("serving_default");
+                    tf.read_saved_model_dir(&filename, &tags, signature)?
+                } else {
+                    (tf.proto_model_for_path(&filename)?, None)
+                };
                 info_usage("proto model loaded", probe);
                 if matches.is_present("determinize") {
                     tract_tensorflow::Tensorflow::determinize(&mut graph)?;
                 }
                 let mut model_and_ext = tf.parse_graph(&graph)?;
+                if let Some(signature) = signature {
+                    tract_tensorflow::Tensorflow::apply_signature(
+                        &mut model_and_ext.0,
+                        &signature,
+                    )?;
+                }
                 model_and_ext.1.initializing_nodes = matches
                     .values_of("tf_initializer_output_node")
                     .map(|values| {
@@ -216,28 +247,6 @@ impl Parameters {
         Ok(triplet)
     }
 
-    fn kaldi_downsample<F, O>(raw_model: &mut Graph<F, O>, period: isize) -> CliResult<()>
-    where
-        F: std::fmt::Debug + Clone + Hash + Fact,
-        O: std::fmt::Debug + std::fmt::Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + Hash,
-        Graph<F, O>: SpecialOps<F, O>,
-        tract_core::ops::Downsample: Into<O>,
-    {
-        if period != 1 {
-            let mut outputs = raw_model.output_outlets()?.to_vec();
-            let output_name = raw_model.node(outputs[0].node).name.clone();
-            raw_model.node_mut(outputs[0].node).name = format!("{}-old", output_name);
-            let id = raw_model.wire_node(
-                output_name,
-                tract_core::ops::Downsample::new(0, period as _, 0),
-                &outputs[0..1],
-            )?[0];
-            outputs[0] = id;
-            raw_model.set_output_outlets(&*outputs)?;
-        }
-        Ok(())
-    }
-
     fn kaldi_context<F, O>(raw_model: &mut Graph<F, O>, left: usize, right: usize) -> CliResult<()>
     where
         F: std::fmt::Debug + Clone + Hash + Fact,
@@ -292,7 +301,10 @@ impl Parameters {
                         .nth(0)
                         .unwrap()
                         .parse::<usize>()?;
-                    let (name, tensor) = tensor::for_data(file.path().to_str().unwrap())?;
+                    let (name, tensor) = tensor::for_data(
+                        &mut raw_model.symbol_table,
+                        file.path().to_str().unwrap(),
+                    )?;
                     Ok(Some((ix, filename.starts_with("input_"), filename, name.unwrap(), tensor)))
                 } else {
                     Ok(None)
@@ -350,7 +362,7 @@ impl Parameters {
 
         if let Some(inputs) = matches.values_of("input") {
             for (ix, v) in inputs.enumerate() {
-                let (name, t) = tensor::for_string(v)?;
+                let (name, t) = tensor::for_string(&mut raw_model.symbol_table, v)?;
                 let fact = t.clone().without_value();
                 let fact: F = (&fact).try_into().unwrap();
                 let outlet = if let Some(name) = name.filter(|s| s.len() > 0) {
@@ -451,12 +463,17 @@ impl Parameters {
     )> {
         let keep_last = matches.is_present("verbose");
         #[cfg(feature = "pulse")]
-        let pulse: Option<usize> =
-            matches.value_of("pulse").map(|s| s.parse::<usize>()).transpose()?;
+        let pulse: Option<&str> = matches.value_of("pulse");
         #[cfg(feature = "pulse")]
         let concretize_stream_dim: Option<usize> =
             matches.value_of("concretize_stream_dim").map(|s| s.parse()).transpose()?;
 
+        let symbol_values: Vec<(String, i64)> = matches
+            .values_of("set")
+            .map(|values| values.map(parse_symbol_value).collect::<CliResult<_>>())
+            .transpose()?
+            .unwrap_or_default();
+
         let stop_at = matches.value_of("pass").unwrap_or(if matches.is_present("optimize") {
             "optimize"
         } else {
@@ -532,7 +549,20 @@ impl Parameters {
             stage!("tf-preproc", inference_model -> inference_model, |m:InferenceModel| Ok(ext.preproc(m)?));
         }
         stage!("incorporate", inference_model -> inference_model, |m:InferenceModel| { Ok(m.incorporate()?)});
-        stage!("type", inference_model -> typed_model, |m:InferenceModel| Ok(m.into_typed()?));
+        stage!("type", inference_model -> typed_model, |m:InferenceModel| {
+            // command line facts have been parsed in the model symbol table
+            let mut m = m.into_typed()?;
+            for a in matches.values_of("assert").into_iter().flatten() {
+                let assertion = m.symbol_table.parse_assertion(a)?;
+                m.symbol_table.add_assertion(assertion);
+            }
+            Ok(m)
+        });
+        if symbol_values.len() > 0 {
+            stage!("set", typed_model -> typed_model, |m:TypedModel| {
+                Ok(m.concretize_named_dims(symbol_values.iter().map(|(n, v)| (&**n, *v)))?)
+            });
+        }
         stage!("declutter", typed_model -> typed_model, |m:TypedModel| { let mut dec = tract_core::optim::Optimizer::declutter();
             if let Some(steps) = matches.value_of("declutter_step") {
                 dec = dec.stopping_at(steps.parse()?);
@@ -542,10 +572,16 @@ impl Parameters {
         #[cfg(feature = "pulse")]
         {
             if let Some(dim) = concretize_stream_dim {
-                stage!("concretize-stream-dim", typed_model -> typed_model, |m:TypedModel| Ok(m.concretize_dims(&SymbolValues::default().with(stream_symbol(), dim as _))?));
+                stage!("concretize-stream-dim", typed_model -> typed_model, |m:TypedModel| {
+                    let symbol = m.symbol_table.get("S").unwrap_or_else(stream_symbol);
+                    Ok(m.concretize_dims(&SymbolValues::default().with(symbol, dim as _))?)
+                });
                 stage!("concretize-stream-dim-declutter", typed_model -> typed_model, |m:TypedModel| Ok(m.declutter()?));
             } else if let Some(pulse) = pulse {
-                stage!("pulse", typed_model -> pulsed_model, |m:TypedModel| Ok(PulsedModel::new(&m, pulse)?));
+                stage!("pulse", typed_model -> pulsed_model, |m:TypedModel| {
+                    let pulses = parse_pulses(pulse, &m.symbol_table)?;
+                    Ok(PulsedModel::new_with_pulses(&m, &pulses)?)
+                });
                 stage!("pulse-to-type", pulsed_model -> typed_model, |m:PulsedModel| Ok(m.into_typed()?));
                 stage!("pulse-declutter", typed_model -> typed_model, |m:TypedModel| Ok(m.declutter()?));
             }
@@ -609,8 +645,10 @@ impl Parameters {
         }
 
         if let Some(inputs) = matches.values_of("input") {
+            // only the names are used here, facts are parsed again later
+            let mut scratch = SymbolTable::default();
             let names = inputs
-                .map(|t| Ok(tensor::for_string(t)?.0))
+                .map(|t| Ok(tensor::for_string(&mut scratch, t)?.0))
                 .collect::<CliResult<Vec<Option<String>>>>()?;
             if names.iter().all(|s| s.is_some() && s.as_ref().unwrap().len() > 0) {
                 let names: Vec<&str> = names.iter().map(|s| &**s.as_ref().unwrap()).collect();
@@ -630,11 +668,13 @@ impl Parameters {
 
         if let Some(override_facts) = matches.values_of("override_fact") {
             for fact in override_facts {
-                let (name, fact) = tensor::for_string(fact)?;
-                let node = raw_model.node_id_by_name(&name.unwrap())?;
                 if let Some(inf) = raw_model.downcast_mut::<InferenceModel>() {
+                    let (name, fact) = tensor::for_string(&mut inf.symbol_table, fact)?;
+                    let node = inf.node_id_by_name(&name.unwrap())?;
                     inf.set_outlet_fact(OutletId::new(node, 0), fact)?;
                 } else if let Some(typ) = raw_model.downcast_mut::<TypedModel>() {
+                    let (name, fact) = tensor::for_string(&mut typ.symbol_table, fact)?;
+                    let node = typ.node_id_by_name(&name.unwrap())?;
                     typ.set_outlet_fact(OutletId::new(node, 0), (&fact).try_into()?)?;
                 }
             }
@@ -652,11 +692,11 @@ impl Parameters {
             })
             .collect();
 
-        let mut assertions = Assertions::from_clap(matches, &*output_names_and_labels)?;
-
-        if let Some(sub) = matches.value_of("kaldi_downsample") {
-            dispatch_model_mut_no_pulse!(raw_model, |m| Self::kaldi_downsample(m, sub.parse()?))?;
-        }
+        let mut assertions = dispatch_model_mut_no_pulse!(raw_model, |m| Assertions::from_clap(
+            matches,
+            &*output_names_and_labels,
+            &mut m.symbol_table
+        ))?;
 
         if matches.value_of("kaldi_left_context").is_some()
             || matches.value_of("kaldi_right_context").is_some()
@@ -707,6 +747,41 @@ impl Parameters {
     }
 }
 
+/// Parse a symbol value assignment, as in `--set batch_size=1`.
+fn parse_symbol_value(spec: &str) -> CliResult<(String, i64)> {
+    let mut tokens = spec.splitn(2, '=');
+    let name = tokens.next().unwrap().trim();
+    let value = tokens.next().ok_or_else(|| format_err!("Expected NAME=VALUE, got {}", spec))?;
+    if name.is_empty() {
+        bail!("Missing symbol name in {}", spec)
+    }
+    Ok((name.to_string(), value.trim().parse()?))
+}
+
+/// Parse a pulse specification: either a single size for the default stream
+/// symbol `S` (`--pulse 8`), or comma-separated sizes for independent streams
+/// (`--pulse S=8,T=4`). Symbols are looked up in the model symbol table.
+#[cfg(feature = "pulse")]
+fn parse_pulses(spec: &str, table: &SymbolTable) -> CliResult<Pulses> {
+    if let Ok(pulse) = spec.parse::<usize>() {
+        let symbol = table.get("S").unwrap_or_else(stream_symbol);
+        return Ok(std::iter::once((symbol, pulse)).collect());
+    }
+    spec.split(',')
+        .map(|s| {
+            let mut tokens = s.splitn(2, '=');
+            let sym = tokens.next().unwrap().trim();
+            let pulse =
+                tokens.next().ok_or_else(|| format_err!("Expected SYMBOL=PULSE, got {}", s))?;
+            if sym.is_empty() {
+                bail!("Missing symbol in {}", s)
+            }
+            let sym = table.get(sym).ok_or_else(|| format_err!("Unknown symbol {}", sym))?;
+            Ok((sym, pulse.trim().parse::<usize>()?))
+        })
+        .collect()
+}
+
 pub struct BenchLimits {
     pub max_iters: usize,
     pub max_time: std::time::Duration,
@@ -770,15 +845,22 @@ impl Assertions {
     fn from_clap(
         matches: &clap::ArgMatches,
         output_names: &[Vec<String>],
+        symbol_table: &mut SymbolTable,
     ) -> CliResult<Assertions> {
         let mut assert_outputs: Vec<Option<Arc<Tensor>>> = vec![None; output_names.len()];
         if let Some(values) = matches.values_of("assert-output") {
             for (ix, o) in values.enumerate() {
-                assert_outputs[ix] = tensor::for_string(o).unwrap().1.value.concretize();
+                assert_outputs[ix] =
+                    tensor::for_string(symbol_table, o).unwrap().1.value.concretize();
             }
         }
 
-        if let Some(bundles) = matches.subcommand.as_ref().map(|sub| &sub.matches).and_then(|s| s.values_of("assert-output-bundle")) {
+        if let Some(bundles) = matches
+            .subcommand
+            .as_ref()
+            .map(|sub| &sub.matches)
+            .and_then(|s| s.values_of("assert-output-bundle"))
+        {
             for bundle in bundles {
                 let mut npz = ndarray_npy::NpzReader::new(std::fs::File::open(bundle)?)?;
                 for (ix, labels) in output_names.iter().enumerate() {
@@ -797,7 +879,7 @@ impl Assertions {
 
         let assert_output_facts: Option<Vec<InferenceFact>> = matches
             .values_of("assert-output-fact")
-            .map(|vs| vs.map(|v| tensor::for_string(v).unwrap().1).collect());
+            .map(|vs| vs.map(|v| tensor::for_string(symbol_table, v).unwrap().1).collect());
         Ok(Assertions { assert_outputs, assert_output_facts })
     }
 }
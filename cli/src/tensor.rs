@@ -25,19 +25,19 @@ fn parse_dt(dt: &str) -> CliResult<DatumType> {
     })
 }
 
-pub fn parse_spec(size: &str) -> CliResult<InferenceFact> {
+pub fn parse_spec(symbol_table: &mut SymbolTable, size: &str) -> CliResult<InferenceFact> {
     if size.len() == 0 {
         return Ok(InferenceFact::default());
     }
     if size.contains("x") && !size.contains(",") {
-        parse_x_spec(size)
+        parse_x_spec(symbol_table, size)
     } else {
-        parse_coma_spec(size)
+        parse_coma_spec(symbol_table, size)
     }
 }
 
-pub fn parse_coma_spec(size: &str) -> CliResult<InferenceFact> {
-    let splits = size.split(",").collect::<Vec<_>>();
+pub fn parse_coma_spec(symbol_table: &mut SymbolTable, size: &str) -> CliResult<InferenceFact> {
+    let splits = split_spec(size);
 
     if splits.len() < 1 {
         bail!("The <size> argument should be formatted as {size},{...},{type}.");
@@ -54,7 +54,11 @@ pub fn parse_coma_spec(size: &str) -> CliResult<InferenceFact> {
         shape
             .iter()
             .map(|&s| {
-                Ok(if s == "_" { GenericFactoid::Any } else { GenericFactoid::Only(parse_dim(s)?) })
+                Ok(if s == "_" {
+                    GenericFactoid::Any
+                } else {
+                    GenericFactoid::Only(parse_dim(symbol_table, s)?)
+                })
             })
             .collect::<CliResult<TVec<DimFact>>>()?,
     );
@@ -66,29 +70,37 @@ pub fn parse_coma_spec(size: &str) -> CliResult<InferenceFact> {
     }
 }
 
-pub fn parse_dim(i: &str) -> CliResult<TDim> {
-    // ensure the magic S is pre-registered
-    #[cfg(feature = "pulse")]
-    let _ = tract_pulse::internal::stream_symbol();
-
+/// Parse a dimension expression, resolving its symbols in the model symbol
+/// table.
+pub fn parse_dim(symbol_table: &mut SymbolTable, i: &str) -> CliResult<TDim> {
     if i.len() == 0 {
         bail!("Can not parse empty string as Dim")
     }
-    let number_len = i.chars().take_while(|c| c.is_digit(10)).count();
-    let symbol_len = i.len() - number_len;
-    if symbol_len > 1 {
-        bail!("Can not parse {} as Dim", i)
-    }
-    let number: i64 = if number_len > 0 { i[..number_len].parse()? } else { 1 };
-    if symbol_len == 0 {
-        return Ok(number.to_dim());
+    Ok(symbol_table.parse_tdim(i)?)
+}
+
+/// Split a shape specification on commas, ignoring the ones nested in
+/// dimension expressions like `min(S,4)`.
+fn split_spec(size: &str) -> Vec<&str> {
+    let mut splits = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (ix, c) in size.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                splits.push(&size[start..ix]);
+                start = ix + 1;
+            }
+            _ => (),
+        }
     }
-    let symbol = i.chars().last().unwrap();
-    let symbol = Symbol::from(symbol);
-    Ok(symbol.to_dim() * number)
+    splits.push(&size[start..]);
+    splits
 }
 
-pub fn parse_x_spec(size: &str) -> CliResult<InferenceFact> {
+pub fn parse_x_spec(symbol_table: &mut SymbolTable, size: &str) -> CliResult<InferenceFact> {
     warn!(
         "Deprecated \"x\" syntax for shape : please use the comma as separator, x is now a symbol."
     );
@@ -113,7 +125,7 @@ pub fn parse_x_spec(size: &str) -> CliResult<InferenceFact> {
                 Ok(if s == "_" {
                     GenericFactoid::Any
                 } else {
-                    GenericFactoid::Only(parse_dim_stream(s)?)
+                    GenericFactoid::Only(parse_dim_stream(symbol_table, s)?)
                 })
             })
             .collect::<CliResult<TVec<DimFact>>>()?,
@@ -134,14 +146,14 @@ fn parse_values<'a, T: Datum + FromStr>(shape: &[usize], it: Vec<&'a str>) -> Cl
     Ok(tract_ndarray::Array::from_shape_vec(shape, values)?.into())
 }
 
-fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
+fn tensor_for_text_data(symbol_table: &mut SymbolTable, filename: &str) -> CliResult<Tensor> {
     let mut file = fs::File::open(filename)
         .map_err(|e| format_err!("Reading tensor from {}, {:?}", filename, e))?;
     let mut data = String::new();
     file.read_to_string(&mut data)?;
 
     let mut lines = data.lines();
-    let proto = parse_spec(lines.next().context("Empty data file")?)?;
+    let proto = parse_spec(symbol_table, lines.next().context("Empty data file")?)?;
     let shape = proto.shape.concretize().unwrap();
 
     let values = lines.flat_map(|l| l.split_whitespace()).collect::<Vec<&str>>();
@@ -156,7 +168,10 @@ fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
 }
 
 /// Parses the `data` command-line argument.
-pub fn for_data(filename: &str) -> CliResult<(Option<String>, InferenceFact)> {
+pub fn for_data(
+    symbol_table: &mut SymbolTable,
+    filename: &str,
+) -> CliResult<(Option<String>, InferenceFact)> {
     #[allow(unused_imports)]
     use std::convert::TryFrom;
     if filename.ends_with(".pb") {
@@ -177,7 +192,7 @@ pub fn for_data(filename: &str) -> CliResult<(Option<String>, InferenceFact)> {
         let mut npz = ndarray_npy::NpzReader::new(std::fs::File::open(filename)?)?;
         Ok((None, for_npz(&mut npz, inner)?.into()))
     } else {
-        Ok((None, tensor_for_text_data(filename)?.into()))
+        Ok((None, tensor_for_text_data(symbol_table, filename)?.into()))
     }
 }
 
@@ -222,9 +237,12 @@ pub fn for_npz(npz: &mut ndarray_npy::NpzReader<fs::File>, name: &str) -> CliRes
     bail!("Can not extract tensor from {}", name);
 }
 
-pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
+pub fn for_string(
+    symbol_table: &mut SymbolTable,
+    value: &str,
+) -> CliResult<(Option<String>, InferenceFact)> {
     if value.starts_with("@") {
-        for_data(&value[1..])
+        for_data(symbol_table, &value[1..])
     } else {
         let (name, value) = if value.contains(":") {
             let mut splits = value.split(":");
@@ -234,7 +252,7 @@ pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
         };
         if value.contains("=") {
             let mut split = value.split("=");
-            let spec = parse_spec(split.next().unwrap())?;
+            let spec = parse_spec(symbol_table, split.next().unwrap())?;
             let value = split.next().unwrap().split(",");
             let dt = spec
                 .datum_type
@@ -247,27 +265,27 @@ pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
             let tensor = dispatch_datum!(parse_values(dt)(&*shape, value.collect()))?;
             Ok((name, tensor.into()))
         } else {
-            Ok((name, parse_spec(value)?))
+            Ok((name, parse_spec(symbol_table, value)?))
         }
     }
 }
 
 #[cfg(feature = "pulse")]
-fn parse_dim_stream(s: &str) -> CliResult<TDim> {
-    use tract_pulse::internal::stream_dim;
+fn parse_dim_stream(symbol_table: &mut SymbolTable, s: &str) -> CliResult<TDim> {
+    let stream_dim = TDim::from(symbol_table.sym("S"));
     if s == "S" {
-        Ok(stream_dim())
+        Ok(stream_dim)
     } else if s.ends_with("S") {
         let number: String = s.chars().take_while(|c| c.is_digit(10)).collect();
         let number: i64 = number.parse::<i64>().map(|i| i.into())?;
-        Ok(stream_dim() * number)
+        Ok(stream_dim * number)
     } else {
         Ok(s.parse::<i64>().map(|i| i.into())?)
     }
 }
 
 #[cfg(not(feature = "pulse"))]
-fn parse_dim_stream(s: &str) -> CliResult<TDim> {
+fn parse_dim_stream(_symbol_table: &mut SymbolTable, s: &str) -> CliResult<TDim> {
     Ok(s.parse::<i64>().map(|i| i.into())?)
 }
 
@@ -74,6 +74,7 @@ pub mod ops;
 pub mod broadcast;
 pub mod framework;
 mod hash;
+pub mod memory;
 pub mod model;
 pub mod optim;
 pub mod plan;
@@ -87,7 +88,7 @@ pub type TractResult<T> = Result<T, anyhow::Error>;
 pub mod prelude {
     pub use crate::framework::Framework;
     pub use crate::model::*;
-    pub use crate::plan::{SimplePlan, SimpleState};
+    pub use crate::plan::{PlanOptions, SimplePlan, SimpleState};
     pub use crate::{TractError, TractResult};
     pub use std::sync::Arc;
     pub use tract_data::prelude::*;
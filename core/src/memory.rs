@@ -0,0 +1,458 @@
+//! Plan-time memory planning.
+//!
+//! Using the typed facts of a model and the evaluation order of a plan, the
+//! planner assigns each intermediate value an offset in a single arena. Two
+//! values can share memory if their lifetimes (from the step computing them
+//! to the last step using them) do not overlap. Element-wise operators
+//! running in place reuse the buffer of their input.
+//!
+//! Values with a symbolic shape can not be planned, they are left to regular
+//! allocation.
+//!
+//! Plans built with `PlanOptions::memory_arena` compute the memory plan and
+//! allocate the arena once. The planned storage of each node outputs is
+//! offered to its operator (see `tract_data::internal::with_storage_offers`),
+//! and used by the operators allocating their outputs with
+//! `Tensor::uninitialized_output_dt`. A region is only offered when no tensor
+//! still uses memory it overlaps: tensors escaping their planned lifetime
+//! (model outputs, values kept by stateful operators) make the
+//! overlapping values fall back to regular allocation.
+use std::alloc::{alloc, dealloc, Layout};
+use std::fmt;
+use std::sync::Weak;
+
+use crate::internal::*;
+use crate::model::{Fact, Graph, OutletId};
+use crate::ops::element_wise::ElementWiseOp;
+
+const ALIGNMENT: usize = 16;
+
+/// A region of the arena.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Allocation {
+    pub offset: usize,
+    pub size: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MemoryPlan {
+    /// arena region for each planned value
+    pub allocations: HashMap<OutletId, Allocation>,
+    /// values computed in place, with the value whose buffer they reuse
+    pub in_place: HashMap<OutletId, OutletId>,
+    /// values with a symbolic shape, left to regular allocation
+    pub dynamic: Vec<OutletId>,
+    /// arena size, in bytes
+    pub arena_size: usize,
+    /// total size of planned values without any reuse, in bytes
+    pub naive_size: usize,
+    /// peak size of simultaneously live values, in bytes
+    pub peak_live_size: usize,
+}
+
+#[derive(Debug)]
+struct Buffer {
+    outlets: Vec<OutletId>,
+    size: usize,
+    start: usize,
+    end: usize,
+}
+
+impl Buffer {
+    fn overlaps(&self, other: &Buffer) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+impl Allocation {
+    fn overlaps(&self, other: &Allocation) -> bool {
+        self.offset < other.offset + other.size && other.offset < self.offset + self.size
+    }
+}
+
+impl MemoryPlan {
+    /// Plan memory for the model, evaluated in `order`, computing `outputs`.
+    ///
+    /// Model inputs and constants are not part of the plan.
+    pub fn new<F, O>(
+        model: &Graph<F, O>,
+        order: &[usize],
+        outputs: &[OutletId],
+    ) -> TractResult<MemoryPlan>
+    where
+        F: Fact + Hash + Clone + 'static,
+        O: fmt::Debug + fmt::Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+    {
+        let mut step_of = vec![None; model.nodes().len()];
+        for (step, &n) in order.iter().enumerate() {
+            step_of[n] = Some(step);
+        }
+        let mut plan = MemoryPlan::default();
+        let mut buffers: Vec<Buffer> = vec![];
+        let mut buffer_of: HashMap<OutletId, usize> = HashMap::new();
+        let inputs = model.input_outlets()?;
+        for (step, &n) in order.iter().enumerate() {
+            let node = model.node(n);
+            for slot in 0..node.outputs.len() {
+                let outlet = OutletId::new(n, slot);
+                if inputs.contains(&outlet) {
+                    continue;
+                }
+                let fact = model.outlet_fact(outlet)?.to_typed_fact()?;
+                if fact.konst.is_some() {
+                    continue;
+                }
+                let size = if let Some(shape) = fact.shape.as_concrete() {
+                    shape.iter().product::<usize>() * fact.datum_type.size_of()
+                } else {
+                    plan.dynamic.push(outlet);
+                    continue;
+                };
+                let uses = &node.outputs[slot].successors;
+                let mut end = uses.iter().filter_map(|i| step_of[i.node]).max().unwrap_or(step);
+                if outputs.contains(&outlet) {
+                    end = order.len();
+                }
+                plan.naive_size += size;
+                if let Some(input) = Self::in_place_input(model, node, step, &step_of, size) {
+                    if let Some(&buffer) = buffer_of.get(&input) {
+                        buffers[buffer].outlets.push(outlet);
+                        buffers[buffer].end = end;
+                        buffer_of.insert(outlet, buffer);
+                        plan.in_place.insert(outlet, input);
+                        continue;
+                    }
+                }
+                buffer_of.insert(outlet, buffers.len());
+                buffers.push(Buffer { outlets: vec![outlet], size, start: step, end });
+            }
+        }
+        plan.peak_live_size = (0..=order.len())
+            .map(|step| {
+                buffers.iter().filter(|b| b.start <= step && step <= b.end).map(|b| b.size).sum()
+            })
+            .max()
+            .unwrap_or(0);
+        let mut placing: Vec<usize> = (0..buffers.len()).collect();
+        placing.sort_by_key(|&b| (std::cmp::Reverse(buffers[b].size), buffers[b].start));
+        let mut placed: Vec<(usize, Allocation)> = vec![];
+        for b in placing {
+            let size = buffers[b].size;
+            let mut busy: Vec<Allocation> = placed
+                .iter()
+                .filter(|(other, _)| buffers[*other].overlaps(&buffers[b]))
+                .map(|(_, alloc)| *alloc)
+                .collect();
+            busy.sort_by_key(|a| a.offset);
+            let mut offset = 0;
+            for alloc in busy {
+                if offset + size <= alloc.offset {
+                    break;
+                }
+                offset = offset.max(align(alloc.offset + alloc.size));
+            }
+            let alloc = Allocation { offset, size };
+            plan.arena_size = plan.arena_size.max(offset + size);
+            for &outlet in &buffers[b].outlets {
+                plan.allocations.insert(outlet, alloc);
+            }
+            placed.push((b, alloc));
+        }
+        Ok(plan)
+    }
+
+    /// Input whose buffer can be reused by an in-place element-wise operator.
+    fn in_place_input<F, O>(
+        model: &Graph<F, O>,
+        node: &Node<F, O>,
+        step: usize,
+        step_of: &[Option<usize>],
+        size: usize,
+    ) -> Option<OutletId>
+    where
+        F: Fact + Hash + Clone + 'static,
+        O: fmt::Debug + fmt::Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+    {
+        let op = node.op().downcast_ref::<ElementWiseOp>()?;
+        let input = node.inputs[0];
+        let fact = model.outlet_fact(input).ok()?.to_typed_fact().ok()?;
+        if op.0.output_type(fact.datum_type).is_some() {
+            return None;
+        }
+        let uses = &model.node(input.node).outputs[input.slot].successors;
+        if uses.len() != 1 || step_of[uses[0].node] != Some(step) {
+            return None;
+        }
+        if model.output_outlets().ok()?.contains(&input) {
+            return None;
+        }
+        let input_size =
+            fact.shape.as_concrete()?.iter().product::<usize>() * fact.datum_type.size_of();
+        if input_size == size {
+            Some(input)
+        } else {
+            None
+        }
+    }
+}
+
+fn align(offset: usize) -> usize {
+    (offset + ALIGNMENT - 1) / ALIGNMENT * ALIGNMENT
+}
+
+impl fmt::Display for MemoryPlan {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(fmt, "Memory plan:")?;
+        writeln!(fmt, " * arena size: {} bytes", self.arena_size)?;
+        writeln!(fmt, " * peak live size: {} bytes", self.peak_live_size)?;
+        writeln!(fmt, " * without reuse: {} bytes", self.naive_size)?;
+        writeln!(fmt, " * planned values: {}", self.allocations.len())?;
+        writeln!(fmt, " * computed in place: {}", self.in_place.len())?;
+        write!(fmt, " * symbolic values (not planned): {}", self.dynamic.len())
+    }
+}
+
+struct Arena {
+    data: *mut u8,
+    layout: Layout,
+}
+
+unsafe impl Send for Arena {}
+unsafe impl Sync for Arena {}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.data, self.layout) }
+    }
+}
+
+/// Storage of a tensor in the arena. Keeps the arena alive, and the region
+/// busy.
+#[allow(dead_code)]
+struct Lease(Arc<Arena>);
+
+/// Arena serving the planned values of a running plan.
+#[derive(Default)]
+pub struct ArenaState {
+    arena: Option<Arc<Arena>>,
+    leases: Vec<(Allocation, Weak<Lease>)>,
+}
+
+impl ArenaState {
+    /// Storage offers for the outputs of `node`, skipping `escaping` values
+    /// and values computed in place.
+    pub fn offers<F, O>(
+        &mut self,
+        plan: &MemoryPlan,
+        model: &Graph<F, O>,
+        node: usize,
+        escaping: &[OutletId],
+    ) -> TractResult<Vec<StorageOffer>>
+    where
+        F: Fact + Hash + Clone + 'static,
+        O: fmt::Debug + fmt::Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static + Hash,
+    {
+        if plan.arena_size == 0 {
+            return Ok(vec![]);
+        }
+        if self.arena.is_none() {
+            let layout = Layout::from_size_align(plan.arena_size, ALIGNMENT)?;
+            let data = unsafe { alloc(layout) };
+            if data.is_null() {
+                bail!("Failed to allocate a {} bytes arena", plan.arena_size)
+            }
+            self.arena = Some(Arc::new(Arena { data, layout }));
+        }
+        let arena = self.arena.as_ref().unwrap();
+        self.leases.retain(|(_, lease)| lease.strong_count() > 0);
+        let mut offers = vec![];
+        for slot in 0..model.node(node).outputs.len() {
+            let outlet = OutletId::new(node, slot);
+            if escaping.contains(&outlet) || plan.in_place.contains_key(&outlet) {
+                continue;
+            }
+            let alloc = if let Some(alloc) = plan.allocations.get(&outlet) {
+                *alloc
+            } else {
+                continue;
+            };
+            if self.leases.iter().any(|(other, _)| other.overlaps(&alloc)) {
+                continue;
+            }
+            let fact = model.outlet_fact(outlet)?.to_typed_fact()?;
+            let shape = if let Some(shape) = fact.shape.as_concrete() {
+                shape.into()
+            } else {
+                continue;
+            };
+            let lease = Arc::new(Lease(arena.clone()));
+            self.leases.push((alloc, Arc::downgrade(&lease)));
+            let data = unsafe { arena.data.add(alloc.offset) };
+            offers.push(StorageOffer { dt: fact.datum_type, shape, data, owner: lease });
+        }
+        Ok(offers)
+    }
+}
+
+/// A cloned state gets its own arena.
+impl Clone for ArenaState {
+    fn clone(&self) -> ArenaState {
+        ArenaState::default()
+    }
+}
+
+impl fmt::Debug for ArenaState {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let size = self.arena.as_ref().map(|a| a.layout.size()).unwrap_or(0);
+        write!(fmt, "ArenaState({} bytes, {} leases)", size, self.leases.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::math;
+
+    fn chain(len: usize) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let mut wire = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[256]))?;
+        for i in 0..len {
+            let add = crate::ops::binary::TypedBinOp(Box::new(math::Add));
+            wire = model.wire_node(format!("add-{}", i), add, &[wire, wire])?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+        Ok(model)
+    }
+
+    #[test]
+    fn reuse_in_chain() -> TractResult<()> {
+        let plan = SimplePlan::new(chain(6)?)?;
+        let memory = plan.memory_plan()?;
+        assert_eq!(memory.naive_size, 6 * 1024);
+        assert_eq!(memory.peak_live_size, 2 * 1024);
+        assert_eq!(memory.arena_size, 2 * 1024);
+        Ok(())
+    }
+
+    #[test]
+    fn in_place() -> TractResult<()> {
+        let mut model = chain(1)?;
+        let wire = model.output_outlets()?[0];
+        let wire = model.wire_node("abs", math::abs(), &[wire])?;
+        model.set_output_outlets(&wire)?;
+        let memory = SimplePlan::new(model)?.memory_plan()?;
+        assert_eq!(memory.in_place.len(), 1);
+        assert_eq!(memory.arena_size, 1024);
+        Ok(())
+    }
+
+    #[test]
+    fn symbolic() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let wire = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[s]))?;
+        let wire = model.wire_node("abs", math::abs(), &[wire])?;
+        model.set_output_outlets(&wire)?;
+        let memory = SimplePlan::new(model)?.memory_plan()?;
+        assert_eq!(memory.dynamic.len(), 1);
+        assert_eq!(memory.arena_size, 0);
+        Ok(())
+    }
+
+    fn arena() -> PlanOptions {
+        PlanOptions { memory_arena: true }
+    }
+
+    fn in_arena(state: &ArenaState, address: usize) -> bool {
+        state.arena.as_ref().map_or(false, |arena| {
+            address >= arena.data as usize && address < arena.data as usize + arena.layout.size()
+        })
+    }
+
+    /// Run the plan, recording the storage address of every node output.
+    fn run_recording(
+        state: &mut TypedSimpleState<TypedModel, &TypedSimplePlan<TypedModel>>,
+        input: Tensor,
+    ) -> TractResult<(HashMap<usize, usize>, Arc<Tensor>)> {
+        let mut addresses = HashMap::new();
+        let output =
+            state.run_plan_with_eval(tvec!(input), |session, op_state, node, inputs| {
+                let outputs = crate::plan::eval(session, op_state, node, inputs)?;
+                addresses.insert(node.id, outputs[0].as_ptr::<f32>()? as usize);
+                TractResult::Ok(outputs)
+            })?;
+        Ok((addresses, output[0].clone()))
+    }
+
+    #[test]
+    fn arena_reused_across_steps_and_runs() -> TractResult<()> {
+        let model = chain(6)?;
+        let output = model.output_outlets()?[0].node;
+        let plan = SimplePlan::new_with_options(model, &arena())?;
+        let mut state = SimpleState::new(&plan)?;
+        let input = tensor1(&[1.0f32; 256]);
+        let (first, result) = run_recording(&mut state, input.clone())?;
+        assert_eq!(result.as_slice::<f32>()?, &[64.0f32; 256][..]);
+        assert!(!in_arena(&state.arena, result.as_ptr::<f32>()? as usize));
+        let intermediates: std::collections::HashSet<usize> =
+            first.iter().filter(|(n, _)| **n != 0 && **n != output).map(|(_, a)| *a).collect();
+        assert_eq!(intermediates.len(), 2);
+        assert!(intermediates.iter().all(|a| in_arena(&state.arena, *a)));
+        drop(result);
+        let (second, result) = run_recording(&mut state, input)?;
+        assert_eq!(result.as_slice::<f32>()?, &[64.0f32; 256][..]);
+        for (node, address) in first {
+            if node != 0 && node != output {
+                assert_eq!(second[&node], address);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn arena_is_opt_in() -> TractResult<()> {
+        let plan = SimplePlan::new(chain(3)?)?;
+        assert!(plan.memory.is_none());
+        let mut state = SimpleState::new(&plan)?;
+        let (_, result) = run_recording(&mut state, tensor1(&[1.0f32; 256]))?;
+        assert_eq!(result.as_slice::<f32>()?, &[8.0f32; 256][..]);
+        assert!(state.arena.arena.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn arena_in_place() -> TractResult<()> {
+        let mut model = chain(1)?;
+        let add = model.output_outlets()?[0];
+        let abs = model.wire_node("abs", math::abs(), &[add])?[0];
+        let double = crate::ops::binary::TypedBinOp(Box::new(math::Add));
+        let wire = model.wire_node("double", double, &[abs, abs])?;
+        model.set_output_outlets(&wire)?;
+        let plan = SimplePlan::new_with_options(model, &arena())?;
+        let mut state = SimpleState::new(&plan)?;
+        let (addresses, result) = run_recording(&mut state, tensor1(&[-1.0f32; 256]))?;
+        assert_eq!(result.as_slice::<f32>()?, &[4.0f32; 256][..]);
+        assert!(in_arena(&state.arena, addresses[&add.node]));
+        assert_eq!(addresses[&add.node], addresses[&abs.node]);
+        Ok(())
+    }
+
+    #[test]
+    fn arena_falls_back_on_symbols() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let wire = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[s]))?;
+        let add = crate::ops::binary::TypedBinOp(Box::new(math::Add));
+        let wire = model.wire_node("add", add, &[wire, wire])?;
+        let wire = model.wire_node("abs", math::abs(), &wire)?;
+        model.set_output_outlets(&wire)?;
+        let plan = SimplePlan::new_with_options(model, &arena())?;
+        let mut state = SimpleState::new(&plan)?;
+        for len in &[3, 5] {
+            let (_, result) = run_recording(&mut state, tensor1(&vec![-1.0f32; *len]))?;
+            assert_eq!(result.as_slice::<f32>()?, &*vec![2.0f32; *len]);
+            assert!(state.arena.arena.is_none());
+        }
+        Ok(())
+    }
+}
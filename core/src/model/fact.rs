@@ -12,6 +12,12 @@ pub trait Fact: std::fmt::Debug + Downcast + dyn_clone::DynClone + Send + Sync +
         self.to_typed_fact()?.matches(t)
     }
 
+    /// Same as `matches`, with the symbols of `symbols` substituted by their
+    /// value.
+    fn matches_with_symbols(&self, t: &Tensor, _symbols: &SymbolValues) -> TractResult<bool> {
+        self.matches(t)
+    }
+
     fn same_as(&self, _other: &dyn Fact) -> bool;
 }
 
@@ -132,6 +138,60 @@ impl<T: AsRef<[usize]>> std::cmp::PartialEq<T> for ShapeFact {
     }
 }
 
+/// Linear quantization of a tensor: how its integer values map to reals.
+#[derive(Clone, Copy, Debug, PartialEq, Educe)]
+#[educe(Hash)]
+pub enum QuantFormat {
+    /// The `2^bits` integer values span `[min, max]` uniformly.
+    Linear {
+        #[educe(Hash(method = "hash_f32"))]
+        min: f32,
+        #[educe(Hash(method = "hash_f32"))]
+        max: f32,
+        bits: usize,
+    },
+    /// `real = scale * (q - zero_point)`.
+    ZeroPointLinear {
+        zero_point: i32,
+        #[educe(Hash(method = "hash_f32"))]
+        scale: f32,
+        bits: usize,
+        signed: bool,
+        symmetric: bool,
+    },
+}
+
+impl QuantFormat {
+    /// Zero point and scale equivalent to this format.
+    pub fn zp_scale(&self) -> (i32, f32) {
+        match *self {
+            QuantFormat::Linear { min, max, bits } => {
+                let scale = (max - min) / ((1u64 << bits) - 1) as f32;
+                let zero_point = if scale == 0.0 { 0 } else { (-min / scale).round() as i32 };
+                (zero_point, scale)
+            }
+            QuantFormat::ZeroPointLinear { zero_point, scale, .. } => (zero_point, scale),
+        }
+    }
+
+    /// Integer type of the quantized values.
+    pub fn datum_type(&self) -> TractResult<DatumType> {
+        let (bits, signed) = match *self {
+            QuantFormat::Linear { bits, .. } => (bits, false),
+            QuantFormat::ZeroPointLinear { bits, signed, .. } => (bits, signed),
+        };
+        Ok(match (bits, signed) {
+            (8, false) => DatumType::U8,
+            (8, true) => DatumType::I8,
+            (16, false) => DatumType::U16,
+            (16, true) => DatumType::I16,
+            (32, false) => DatumType::U32,
+            (32, true) => DatumType::I32,
+            _ => bail!("No integer type for {} bits quantization (signed: {})", bits, signed),
+        })
+    }
+}
+
 /// Fully determined tensor information for TypedModel.
 #[derive(Clone, PartialEq, Hash)]
 pub struct TypedFact {
@@ -141,6 +201,8 @@ pub struct TypedFact {
     pub shape: ShapeFact,
     /// optional constant value
     pub konst: Option<Arc<Tensor>>,
+    /// optional quantization of the tensor values
+    pub quant: Option<QuantFormat>,
 }
 
 impl_dyn_hash!(TypedFact);
@@ -158,7 +220,7 @@ impl TypedFact {
     where
         S: Into<ShapeFact>,
     {
-        TypedFact { datum_type, shape: shape.into(), konst: None }
+        TypedFact { datum_type, shape: shape.into(), konst: None, quant: None }
     }
 
     pub fn rank(&self) -> usize {
@@ -193,7 +255,11 @@ impl TypedFact {
     }
 
     pub fn without_value(&self) -> Self {
-        Self::dt_shape(self.datum_type, &*self.shape)
+        TypedFact { quant: self.quant, ..Self::dt_shape(self.datum_type, &*self.shape) }
+    }
+
+    pub fn with_quant(self, quant: Option<QuantFormat>) -> Self {
+        TypedFact { quant, ..self }
     }
 }
 
@@ -209,6 +275,12 @@ impl Fact for TypedFact {
         Ok(self.datum_type == t.datum_type() && self.shape == t.shape())
     }
 
+    fn matches_with_symbols(&self, t: &Tensor, symbols: &SymbolValues) -> TractResult<bool> {
+        Ok(self.datum_type == t.datum_type()
+            && self.rank() == t.rank()
+            && self.shape.iter().zip(t.shape().iter()).all(|(d, &v)| d.eval(symbols) == v.to_dim()))
+    }
+
     fn same_as(&self, other: &dyn Fact) -> bool {
         if cfg!(debug_assertions) {
             self.consistent().unwrap()
@@ -217,7 +289,11 @@ impl Fact for TypedFact {
             if cfg!(debug_assertions) {
                 other.consistent().unwrap()
             }
-            self == other
+            // quantization is an annotation, it does not change what the
+            // values are
+            self.datum_type == other.datum_type
+                && self.shape == other.shape
+                && self.konst == other.konst
         } else {
             false
         }
@@ -242,6 +318,7 @@ impl From<Arc<Tensor>> for TypedFact {
             datum_type: t.datum_type(),
             shape: ShapeFact::from_dims(t.shape().iter().map(TDim::from)),
             konst: Some(t),
+            quant: None,
         }
     }
 }
@@ -255,9 +332,14 @@ impl<'a> From<&'a TypedFact> for TypedFact {
 impl fmt::Debug for TypedFact {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self.konst {
-            Some(ref k) => write!(fmt, "{:?}", k),
-            None if self.rank() > 0 => write!(fmt, "{:?},{:?}", self.shape, self.datum_type),
-            None => write!(fmt, "{:?}", self.datum_type),
+            Some(ref k) => write!(fmt, "{:?}", k)?,
+            None if self.rank() > 0 => write!(fmt, "{:?},{:?}", self.shape, self.datum_type)?,
+            None => write!(fmt, "{:?}", self.datum_type)?,
         }
+        if let Some(q) = self.quant {
+            let (zero_point, scale) = q.zp_scale();
+            write!(fmt, " q(zp={},scale={:?})", zero_point, scale)?;
+        }
+        Ok(())
     }
 }
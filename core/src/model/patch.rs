@@ -125,7 +125,9 @@ where
     ) -> TractResult<()> {
         let original_fact = model.outlet_fact(outlet)?;
         let new_fact = self.model.outlet_fact(by)?;
-        if !original_fact.same_as(new_fact) {
+        if !original_fact.same_as(new_fact)
+            && !same_under_assertions(&model.symbol_table, original_fact, new_fact)
+        {
             bail!("Trying to substitute a {:?} by {:?}.\n{:?}", original_fact, new_fact, self);
         }
         self.shunt_outlet_by.insert(outlet, by);
@@ -299,3 +301,22 @@ where
         Ok(())
     }
 }
+
+/// Typed facts whose dimensions are equal once simplified with the model
+/// symbol assertions.
+fn same_under_assertions(table: &SymbolTable, a: &dyn Fact, b: &dyn Fact) -> bool {
+    if table.assertions().len() == 0 {
+        return false;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<TypedFact>(), b.downcast_ref::<TypedFact>()) {
+        a.datum_type == b.datum_type
+            && a.konst == b.konst
+            && a.shape.rank() == b.shape.rank()
+            && a.shape
+                .iter()
+                .zip(b.shape.iter())
+                .all(|(a, b)| a.simplify_with(table) == b.simplify_with(table))
+    } else {
+        false
+    }
+}
@@ -136,6 +136,15 @@ impl TypedModel {
         values.translate_model(&self)
     }
 
+    /// Concretize dimensions using symbol names, as found in the model symbol
+    /// table (e.g. `batch_size` from an ONNX `dim_param`).
+    pub fn concretize_named_dims<'n>(
+        &self,
+        values: impl IntoIterator<Item = (&'n str, i64)>,
+    ) -> TractResult<TypedModel> {
+        self.concretize_dims(&self.symbol_table.values(values)?)
+    }
+
     /// Translate the graph to locally optimized operators (LIR or MIR ops).
     pub fn optimize(self) -> TractResult<TypedModel> {
         crate::optim::Optimizer::codegen().optimize(&self)
@@ -155,4 +164,81 @@ mod test {
         fn is_sync<T: Sync>() {}
         is_sync::<TypedModel>();
     }
+
+    fn sliced_model(assertions: &[&str]) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        for a in assertions {
+            let a = model.symbol_table.parse_assertion(a)?;
+            model.symbol_table.add_assertion(a);
+        }
+        let source = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[s]))?;
+        let slice = crate::ops::array::Slice::new(0, 0, TDim::from(s).mini(10.into()));
+        let output = model.wire_node("slice", slice, &[source])?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn slice_simplified_by_assertion() -> TractResult<()> {
+        assert_eq!(sliced_model(&[])?.declutter()?.nodes().len(), 2);
+        assert_eq!(sliced_model(&["S<=10"])?.declutter()?.nodes().len(), 1);
+        Ok(())
+    }
+
+    fn padded_and_cropped_model(assertions: &[&str]) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        for a in assertions {
+            let a = model.symbol_table.parse_assertion(a)?;
+            model.symbol_table.add_assertion(a);
+        }
+        let source = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[s]))?;
+        let pad = crate::ops::array::Pad::new(vec![(1, 1)], Default::default());
+        let padded = model.wire_node("pad", pad, &[source])?;
+        let end = (TDim::from(s) + 1).mini(33.into());
+        let slice = crate::ops::array::Slice::new(0, 1, end);
+        let output = model.wire_node("slice", slice, &padded)?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn pad_simplified_by_assertion() -> TractResult<()> {
+        assert_eq!(padded_and_cropped_model(&[])?.declutter()?.nodes().len(), 3);
+        assert_eq!(padded_and_cropped_model(&["S<=32"])?.declutter()?.nodes().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn partial_crop_folded_in_pad() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[4]))?;
+        let pad = crate::ops::array::Pad::new(vec![(2, 2)], crate::ops::array::PadMode::Reflect);
+        let padded = model.wire_node("pad", pad, &[source])?;
+        let slice = crate::ops::array::Slice::new(0, 1, 7);
+        let output = model.wire_node("slice", slice, &padded)?;
+        model.set_output_outlets(&output)?;
+        let decluttered = model.declutter()?;
+        assert_eq!(decluttered.nodes().len(), 2);
+        let input = tvec!(tensor1(&[0f32, 1., 2., 3.]));
+        let expected = model.into_runnable()?.run(input.clone())?;
+        let found = decluttered.into_runnable()?.run(input)?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn assertions_checked_at_runtime() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let a = model.symbol_table.parse_assertion("S>=2")?;
+        model.symbol_table.add_assertion(a);
+        let source = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[s]))?;
+        model.set_output_outlets(&[source])?;
+        let model = model.into_runnable()?;
+        assert!(model.run(tvec!(tensor1(&[0f32, 1.0]))).is_ok());
+        assert!(model.run(tvec!(tensor1(&[0f32]))).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,109 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Cumulative sum along an axis.
+///
+/// With `exclusive`, the element itself is not part of its sum, with `reverse`
+/// the sum runs from the end of the axis.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct CumSum {
+    pub axis: usize,
+    pub exclusive: bool,
+    pub reverse: bool,
+}
+
+impl_dyn_hash!(CumSum);
+
+impl CumSum {
+    fn eval_t<T: Datum + Copy + num_traits::Zero>(&self, input: &Tensor) -> TractResult<Tensor> {
+        let mut output = input.to_array_view::<T>()?.to_owned();
+        for mut lane in output.lanes_mut(Axis(self.axis)) {
+            let len = lane.len();
+            let mut acc = T::zero();
+            for i in 0..len {
+                let ix = if self.reverse { len - 1 - i } else { i };
+                let x = lane[ix];
+                if self.exclusive {
+                    lane[ix] = acc;
+                    acc = acc + x;
+                } else {
+                    acc = acc + x;
+                    lane[ix] = acc;
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for CumSum {
+    fn name(&self) -> Cow<str> {
+        "CumSum".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "axis: {} exclusive: {} reverse: {}",
+            self.axis, self.exclusive, self.reverse
+        )])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for CumSum {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = dispatch_numbers!(Self::eval_t(input.datum_type())(self, &input))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for CumSum {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if self.axis >= inputs[0].rank() {
+            bail!("CumSum axis is {}, but input has rank {}", self.axis, inputs[0].rank())
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let rank = model.outlet_fact(node.inputs[0])?.rank();
+        Ok((0..rank).filter(|&ax| ax != self.axis).map(|ax| AxisInfo::simple(ax)).collect())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(op: CumSum, input: Tensor) -> Tensor {
+        let mut output = op.eval(tvec!(input.into_arc_tensor())).unwrap();
+        output.remove(0).into_tensor()
+    }
+
+    #[test]
+    fn cumsum_variants() {
+        let input = tensor1(&[1i32, 2, 3, 4]);
+        assert_eq!(run(CumSum::new(0, false, false), input.clone()), tensor1(&[1i32, 3, 6, 10]));
+        assert_eq!(run(CumSum::new(0, true, false), input.clone()), tensor1(&[0i32, 1, 3, 6]));
+        assert_eq!(run(CumSum::new(0, false, true), input.clone()), tensor1(&[10i32, 9, 7, 4]));
+        assert_eq!(run(CumSum::new(0, true, true), input), tensor1(&[9i32, 7, 4, 0]));
+    }
+
+    #[test]
+    fn cumsum_inner_axis() {
+        let input = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        assert_eq!(
+            run(CumSum::new(1, false, false), input),
+            tensor2(&[[1f32, 3., 6.], [4., 9., 15.]])
+        );
+    }
+}
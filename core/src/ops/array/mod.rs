@@ -2,19 +2,33 @@
 mod broadcast;
 pub(crate) mod concat;
 mod constant_of_shape;
+mod cumsum;
 mod gather;
+mod non_zero;
 mod one_hot;
 mod pad;
+mod range;
 mod reshape;
+mod reverse_sequence;
 mod slice;
 mod tile;
+mod topk;
+mod trilu;
+mod unique;
 
 pub use self::broadcast::MultiBroadcastTo;
 pub use self::concat::{ConcatSlice, TypedConcat};
 pub use self::constant_of_shape::ConstantOfShape;
+pub use self::cumsum::CumSum;
 pub use self::gather::Gather;
+pub use self::non_zero::NonZero;
 pub use self::one_hot::OneHot;
 pub use self::pad::{Pad, PadMode};
+pub use self::range::Range;
 pub use self::reshape::FiniteReshape;
+pub use self::reverse_sequence::ReverseSequence;
 pub use self::slice::Slice;
 pub use self::tile::Tile;
+pub use self::topk::TopK;
+pub use self::trilu::Trilu;
+pub use self::unique::Unique;
@@ -0,0 +1,124 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Coordinates of the non-zero (or true) elements of the input, as a
+/// `[count, rank]` i64 tensor, in row-major order.
+///
+/// The number of non-zero elements depends on the input values: it is the
+/// `count` symbol, resolved when the op is evaluated.
+#[derive(Debug, Clone, new, Hash)]
+pub struct NonZero {
+    pub count: Symbol,
+}
+
+impl_dyn_hash!(NonZero);
+
+impl NonZero {
+    unsafe fn eval_t<T: Datum + num_traits::Zero>(input: &Tensor) -> TractResult<Tensor> {
+        let view = input.to_array_view_unchecked::<T>();
+        let count = view.iter().filter(|d| !d.is_zero()).count();
+        let mut output = Array2::<i64>::zeros((count, input.rank()));
+        for (mut row, (coords, _)) in
+            output.outer_iter_mut().zip(view.indexed_iter().filter(|(_, value)| !value.is_zero()))
+        {
+            row.assign(&coords.as_array_view().map(|d| *d as i64));
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for NonZero {
+    fn name(&self) -> Cow<str> {
+        "NonZero".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("count: {}", self.count)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for NonZero {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = unsafe {
+            if input.datum_type() == bool::datum_type() {
+                Self::eval_t::<u8>(&input)?
+            } else {
+                dispatch_numbers!(Self::eval_t(input.datum_type())(&input))?
+            }
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for NonZero {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(TypedFact::dt_shape(
+            i64::datum_type(),
+            &[self.count.to_dim(), inputs[0].rank().to_dim()]
+        )))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::array::Gather;
+
+    #[test]
+    fn non_zero() {
+        let op = NonZero::new(Symbol::new("count"));
+        let output = op.eval(tvec!(rctensor2(&[[0f32, 1.], [2., 0.]]))).unwrap();
+        assert_eq!(*output[0], tensor2(&[[0i64, 1], [1, 0]]));
+        let output = op.eval(tvec!(rctensor1(&[false, false]))).unwrap();
+        assert_eq!(output[0].shape(), &[0, 1]);
+    }
+
+    #[test]
+    fn count_is_resolved() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let count = model.symbol_table.new_symbol("count");
+        let input = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[4]))?;
+        let indices = model.wire_node("non_zero", NonZero::new(count), &[input])?;
+        let indices = model.wire_node("rm", AxisOp::Rm(1), &indices)?;
+        let gathered = model.wire_node("gather", Gather::new(0), &[input, indices[0]])?;
+        assert_eq!(model.outlet_fact(gathered[0])?.shape.to_tvec(), tvec!(count.to_dim()));
+        model.set_output_outlets(&gathered)?;
+        let plan = SimplePlan::new(model.into_optimized()?)?;
+        let mut state = SimpleState::new(&plan)?;
+        let output = state.run(tvec!(tensor1(&[0f32, 1., 0., 2.])))?;
+        assert_eq!(*output[0], tensor1(&[1f32, 2.]));
+        assert_eq!(state.session_state.resolved_symbols[count], Some(2));
+        let output = state.run(tvec!(tensor1(&[0f32, 0., 0., 3.])))?;
+        assert_eq!(*output[0], tensor1(&[3f32]));
+        assert_eq!(state.session_state.resolved_symbols[count], Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn conflicting_count_fails() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let count = model.symbol_table.new_symbol("count");
+        let a = model.add_source("a", TypedFact::dt_shape(f32::datum_type(), &[4]))?;
+        let b = model.add_source("b", TypedFact::dt_shape(f32::datum_type(), &[4]))?;
+        let a = model.wire_node("non_zero_a", NonZero::new(count), &[a])?;
+        let b = model.wire_node("non_zero_b", NonZero::new(count), &[b])?;
+        model.set_output_outlets(&[a[0], b[0]])?;
+        let plan = SimplePlan::new(model)?;
+        let mut state = SimpleState::new(&plan)?;
+        state.run(tvec!(tensor1(&[1f32, 0., 0., 2.]), tensor1(&[0f32, 3., 4., 0.])))?;
+        assert!(state
+            .run(tvec!(tensor1(&[1f32, 0., 0., 2.]), tensor1(&[0f32, 0., 4., 0.])))
+            .is_err());
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@ use ndarray::*;
 pub enum PadMode {
     Constant(Arc<Tensor>),
     Reflect,
+    /// Like Reflect, but the edge value is repeated (TensorFlow's SYMMETRIC).
+    Symmetric,
     Edge,
 }
 
@@ -45,7 +47,7 @@ impl Pad {
             .collect();
         let slice_info = SliceInfo::<_, IxDyn>::new(slice_spec).unwrap();
         output.slice_mut(slice_info.as_ref()).assign(&input);
-        if self.mode == PadMode::Reflect || self.mode == PadMode::Edge {
+        if let PadMode::Reflect | PadMode::Symmetric | PadMode::Edge = self.mode {
             for (ax, &(bef, aft)) in self.pads.iter().enumerate() {
                 let axis = Axis(ax);
                 let dim = output.shape()[ax];
@@ -56,6 +58,7 @@ impl Pad {
                         let source_slice = match self.mode {
                             PadMode::Edge => 0,
                             PadMode::Reflect => bef - i,
+                            PadMode::Symmetric => bef - 1 - i,
                             _ => panic!(),
                         };
                         let source =
@@ -70,6 +73,7 @@ impl Pad {
                         let source_slice = match self.mode {
                             PadMode::Edge => dim - aft - 1,
                             PadMode::Reflect => dim - aft - 2 - i,
+                            PadMode::Symmetric => dim - aft - 1 - i,
                             _ => panic!(),
                         };
                         let source =
@@ -129,7 +133,47 @@ impl TypedOp for Pad {
         if self.pads.iter().all(|p| p.0 == 0 && p.1 == 0) {
             Ok(Some(TypedModelPatch::shunt_one_op(model, node)?))
         } else {
-            Ok(None)
+            self.declutter_crop(model, node)
         }
     }
 }
+
+impl Pad {
+    /// Fold a Slice cropping some of the padding away into the pads. Bounds
+    /// are compared under the model assertions, like Slice does.
+    fn declutter_crop(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let succ = if let Some(succ) = model.single_succ(node.id)? {
+            succ
+        } else {
+            return Ok(None);
+        };
+        let slice = if let Some(slice) = succ.op_as::<super::Slice>() {
+            slice
+        } else {
+            return Ok(None);
+        };
+        let (before, after) = self.pads[slice.axis];
+        let dim = model.outlet_fact(node.inputs[0])?.shape[slice.axis].clone();
+        let start = slice.start.clone().simplify_with(&model.symbol_table).to_i64();
+        let end = (slice.end.clone() - dim - before).simplify_with(&model.symbol_table).to_i64();
+        let (start, end) = if let (Ok(start), Ok(end)) = (start, end) {
+            (start, end)
+        } else {
+            return Ok(None);
+        };
+        if start < 0 || start as usize > before || end < 0 || end as usize > after {
+            return Ok(None);
+        }
+        let mut pads = self.pads.clone();
+        pads[slice.axis] = (before - start as usize, end as usize);
+        let mut patch = TypedModelPatch::default();
+        let wire = patch.tap_model(model, node.inputs[0])?;
+        let wire = patch.wire_node(&node.name, Pad { pads, mode: self.mode.clone() }, &[wire])?;
+        patch.shunt_outside(model, succ.id.into(), wire[0])?;
+        Ok(Some(patch.with_context("fold crop")))
+    }
+}
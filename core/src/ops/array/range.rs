@@ -0,0 +1,179 @@
+use crate::internal::*;
+use ndarray::*;
+use num_traits::AsPrimitive;
+use std::ops::{Add, Mul};
+
+/// The 1D tensor `start`, `start + step`, ... up to `end` (excluded), from
+/// three scalar inputs.
+///
+/// `len` is the output length. It is computed from the inputs when they are
+/// known at wiring time (as an expression when they come from symbolic
+/// shapes), or is a fresh symbol resolved when the op is evaluated.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Range {
+    pub len: TDim,
+}
+
+impl_dyn_hash!(Range);
+
+impl Range {
+    /// A Range with the length of the inputs if they are constants, or a
+    /// fresh symbol from the model symbol table.
+    pub fn for_inputs(model: &mut TypedModel, inputs: &[OutletId]) -> TractResult<Range> {
+        let konsts = inputs
+            .iter()
+            .map(|i| Ok(model.outlet_fact(*i)?.konst.clone()))
+            .collect::<TractResult<TVec<_>>>()?;
+        if let (Some(start), Some(end), Some(step)) = (&konsts[0], &konsts[1], &konsts[2]) {
+            return Ok(Range::new(Self::len(start, end, step)?));
+        }
+        Ok(Range::new(model.symbol_table.new_symbol("range").to_dim()))
+    }
+
+    fn len(start: &Tensor, end: &Tensor, step: &Tensor) -> TractResult<TDim> {
+        let symbolic = [start, end, step].iter().any(|t| t.datum_type() == TDim::datum_type());
+        if symbolic {
+            let step = step.cast_to_scalar::<i64>()?;
+            let start = start.cast_to::<TDim>()?.to_scalar::<TDim>()?.clone();
+            let end = end.cast_to::<TDim>()?.to_scalar::<TDim>()?.clone();
+            let diff = if step > 0 { end - start } else { start - end };
+            if step == 0 {
+                bail!("Range step can not be zero")
+            } else if let Ok(diff) = diff.to_i64() {
+                Ok(((diff.max(0) + step.abs() - 1) / step.abs()).to_dim())
+            } else {
+                Ok(diff.div_ceil(step.abs() as u64))
+            }
+        } else {
+            let start = start.cast_to_scalar::<f64>()?;
+            let end = end.cast_to_scalar::<f64>()?;
+            let step = step.cast_to_scalar::<f64>()?;
+            if step == 0.0 {
+                bail!("Range step can not be zero")
+            }
+            Ok((((end - start) / step).ceil().max(0.0) as usize).to_dim())
+        }
+    }
+
+    fn eval_t<T>(start: &Tensor, step: &Tensor, len: usize) -> TractResult<Tensor>
+    where
+        T: Datum + Copy + Add<T, Output = T> + Mul<T, Output = T>,
+        usize: AsPrimitive<T>,
+    {
+        let start = start.cast_to_scalar::<T>()?;
+        let step = step.cast_to_scalar::<T>()?;
+        Ok(Array1::from_shape_fn(len, |ix| ix.as_() * step + start).into_tensor())
+    }
+
+    fn eval_with_symbols(
+        &self,
+        inputs: TVec<Arc<Tensor>>,
+        symbols: &SymbolValues,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let dt = inputs[0].datum_type();
+        let values = inputs
+            .iter()
+            .map(|t| {
+                if t.datum_type() == TDim::datum_type() {
+                    Ok(tensor0(t.to_scalar::<TDim>()?.eval(symbols).to_i64()?))
+                } else {
+                    Ok(t.as_ref().clone())
+                }
+            })
+            .collect::<TractResult<TVec<Tensor>>>()?;
+        let len = Self::len(&values[0], &values[1], &values[2])?.to_usize()?;
+        let output =
+            dispatch_numbers!(Self::eval_t(values[0].datum_type())(&values[0], &values[2], len))?;
+        Ok(tvec!(output.cast_to_dt(dt)?.into_owned().into_arc_tensor()))
+    }
+}
+
+impl Op for Range {
+    fn name(&self) -> Cow<str> {
+        "Range".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("len: {}", self.len)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Range {
+    fn is_stateless(&self) -> bool {
+        self.len.to_usize().is_ok()
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        self.eval_with_symbols(inputs, &SymbolValues::default())
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(RangeState)))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RangeState;
+
+impl OpState for RangeState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<Range>().unwrap();
+        op.eval_with_symbols(inputs, &session.resolved_symbols)
+    }
+}
+
+impl TypedOp for Range {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs.iter().any(|i| i.rank() != 0) {
+            bail!("Range inputs must be scalars")
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &[self.len.clone()])))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range() -> TractResult<()> {
+        let op = Range::new(3.to_dim());
+        let output = op.eval(tvec!(rctensor0(1i64), rctensor0(7i64), rctensor0(2i64)))?;
+        assert_eq!(*output[0], tensor1(&[1i64, 3, 5]));
+        let output = op.eval(tvec!(rctensor0(1f32), rctensor0(-0.5f32), rctensor0(-0.5f32)))?;
+        assert_eq!(*output[0], tensor1(&[1f32, 0.5, 0.]));
+        Ok(())
+    }
+
+    #[test]
+    fn symbolic_range() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[s.to_dim()]))?;
+        let start = model.add_const("start", tensor0(TDim::from(1)))?;
+        let end = model.add_const("end", tensor0(s.to_dim()))?;
+        let step = model.add_const("step", tensor0(TDim::from(1)))?;
+        let op = Range::for_inputs(&mut model, &[start, end, step])?;
+        assert_eq!(op.len, s.to_dim() - 1);
+        let range = model.wire_node("range", op, &[start, end, step])?;
+        let range = model.wire_node("cast", crate::ops::cast::cast(f32::datum_type()), &range)?;
+        model.set_output_outlets(&range)?;
+        let output = model.into_runnable()?.run(tvec!(tensor1(&[0f32; 4])))?;
+        assert_eq!(*output[0], tensor1(&[1f32, 2., 3.]));
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Reverse the first `sequence_lens[b]` elements along `time_axis`, for each
+/// index `b` of `batch_axis`.
+///
+/// The second input is the integer vector `sequence_lens`. Elements past the
+/// sequence length are copied as is.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct ReverseSequence {
+    pub batch_axis: usize,
+    pub time_axis: usize,
+}
+
+impl_dyn_hash!(ReverseSequence);
+
+impl ReverseSequence {
+    fn eval_t<T: Datum>(&self, input: &Tensor, lens: &[i64]) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let mut output = input.to_owned();
+        let time_axis = Axis(self.time_axis - (self.time_axis > self.batch_axis) as usize);
+        for (b, &len) in lens.iter().enumerate() {
+            let len = len.max(0) as usize;
+            let input = input.index_axis(Axis(self.batch_axis), b);
+            let mut output = output.index_axis_mut(Axis(self.batch_axis), b);
+            if len > input.len_of(time_axis) {
+                bail!("Sequence length {} is greater than time axis size", len)
+            }
+            for t in 0..len {
+                output
+                    .index_axis_mut(time_axis, t)
+                    .assign(&input.index_axis(time_axis, len - 1 - t));
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for ReverseSequence {
+    fn name(&self) -> Cow<str> {
+        "ReverseSequence".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("batch_axis: {} time_axis: {}", self.batch_axis, self.time_axis)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for ReverseSequence {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, lens) = args_2!(inputs);
+        let lens = lens.cast_to::<i64>()?;
+        let lens = lens.as_slice::<i64>()?;
+        if lens.len() != input.shape()[self.batch_axis] {
+            bail!("Expected {} sequence lengths, got {:?}", input.shape()[self.batch_axis], lens)
+        }
+        let output = dispatch_datum!(Self::eval_t(input.datum_type())(self, &input, lens))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for ReverseSequence {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let rank = inputs[0].rank();
+        if self.batch_axis >= rank || self.time_axis >= rank || self.batch_axis == self.time_axis {
+            bail!(
+                "Invalid batch and time axes ({} and {}) for input {:?}",
+                self.batch_axis,
+                self.time_axis,
+                inputs[0]
+            )
+        }
+        if inputs[1].rank() != 1 {
+            bail!("Sequence lengths must be a vector, got {:?}", inputs[1])
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let rank = model.outlet_fact(node.inputs[0])?.rank();
+        Ok((0..rank)
+            .filter(|&ax| ax != self.batch_axis && ax != self.time_axis)
+            .map(|ax| AxisInfo::simple(ax))
+            .collect())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_sequence() {
+        let input = tensor2(&[[0i32, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11]]);
+        let op = ReverseSequence::new(0, 1);
+        let output = op.eval(tvec!(input.clone().into(), rctensor1(&[4i64, 2, 1]))).unwrap();
+        assert_eq!(*output[0], tensor2(&[[3i32, 2, 1, 0], [5, 4, 6, 7], [8, 9, 10, 11]]));
+        let op = ReverseSequence::new(1, 0);
+        let output = op.eval(tvec!(input.into(), rctensor1(&[3i64, 2, 1, 0]))).unwrap();
+        assert_eq!(*output[0], tensor2(&[[8i32, 5, 2, 3], [4, 1, 6, 7], [0, 9, 10, 11]]));
+    }
+}
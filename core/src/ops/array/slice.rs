@@ -104,8 +104,23 @@ impl TypedOp for Slice {
         model: &TypedModel,
         node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
+        if model.symbol_table.assertions().len() > 0 {
+            let start = self.start.clone().simplify_with(&model.symbol_table);
+            let end = self.end.clone().simplify_with(&model.symbol_table);
+            if start != self.start || end != self.end {
+                let op = Slice { axis: self.axis, start, end };
+                return Ok(Some(
+                    TypedModelPatch::replace_single_op(model, node, &node.inputs, op)?
+                        .with_context("simplify bounds"),
+                ));
+            }
+        }
         let prec = model.node(node.inputs[0].node);
-        if self.start.is_zero() && (self.end == model.outlet_fact(node.inputs[0])?.shape[self.axis])
+        let dim = model.outlet_fact(node.inputs[0])?.shape[self.axis].clone();
+        if self.start.is_zero()
+            && (self.end == dim
+                || self.end.clone().simplify_with(&model.symbol_table)
+                    == dim.simplify_with(&model.symbol_table))
         {
             return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?.with_context("noop")));
         }
@@ -0,0 +1,130 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// The `k` largest (or smallest) values along an axis, and their indices.
+///
+/// Values are sorted, ties are resolved by picking the lowest index first.
+/// Indices are produced as i64.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct TopK {
+    pub axis: usize,
+    pub k: usize,
+    pub largest: bool,
+}
+
+impl_dyn_hash!(TopK);
+
+impl TopK {
+    fn eval_t<T: Datum + Copy + PartialOrd>(
+        &self,
+        input: &Tensor,
+    ) -> TractResult<(Tensor, Tensor)> {
+        let input = input.to_array_view::<T>()?;
+        let mut shape = input.shape().to_vec();
+        shape[self.axis] = self.k;
+        let mut values = ArrayD::<T>::from_elem(&*shape, T::default());
+        let mut indices = ArrayD::<i64>::zeros(&*shape);
+        for ((input, mut values), mut indices) in input
+            .lanes(Axis(self.axis))
+            .into_iter()
+            .zip(values.lanes_mut(Axis(self.axis)))
+            .zip(indices.lanes_mut(Axis(self.axis)))
+        {
+            let mut sorted: Vec<(usize, T)> = input.iter().cloned().enumerate().collect();
+            let largest = self.largest;
+            sorted.sort_by(|a, b| {
+                let order = a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal);
+                if largest {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+            for (ix, (pos, v)) in sorted.into_iter().take(self.k).enumerate() {
+                values[ix] = v;
+                indices[ix] = pos as i64;
+            }
+        }
+        Ok((values.into_tensor(), indices.into_tensor()))
+    }
+}
+
+impl Op for TopK {
+    fn name(&self) -> Cow<str> {
+        "TopK".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} k: {} largest: {}", self.axis, self.k, self.largest)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for TopK {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        if input.shape()[self.axis] < self.k {
+            bail!("TopK with k={} on axis of size {}", self.k, input.shape()[self.axis])
+        }
+        let (values, indices) = dispatch_numbers!(Self::eval_t(input.datum_type())(self, &input))?;
+        Ok(tvec!(values.into_arc_tensor(), indices.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for TopK {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if self.axis >= inputs[0].rank() {
+            bail!("TopK axis is {}, but input has rank {}", self.axis, inputs[0].rank())
+        }
+        let mut shape = inputs[0].shape.to_tvec();
+        shape[self.axis] = self.k.to_dim();
+        Ok(tvec!(
+            TypedFact::dt_shape(inputs[0].datum_type, &*shape),
+            TypedFact::dt_shape(i64::datum_type(), &*shape)
+        ))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let rank = model.outlet_fact(node.inputs[0])?.rank();
+        Ok((0..rank)
+            .filter(|&ax| ax != self.axis)
+            .map(|ax| AxisInfo {
+                inputs: tvec!(Some(ax)),
+                outputs: tvec!(Some(ax), Some(ax)),
+                period: 1,
+                disposable: true,
+            })
+            .collect())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topk_largest() {
+        let op = TopK::new(1, 2, true);
+        let input = tensor2(&[[1f32, 4., 3., 4.], [0., -1., 2., 1.]]);
+        let output = op.eval(tvec!(input.into_arc_tensor())).unwrap();
+        assert_eq!(*output[0], tensor2(&[[4f32, 4.], [2., 1.]]));
+        assert_eq!(*output[1], tensor2(&[[1i64, 3], [2, 3]]));
+    }
+
+    #[test]
+    fn topk_smallest() {
+        let op = TopK::new(0, 1, false);
+        let input = tensor2(&[[1i32, 4], [0, 5]]);
+        let output = op.eval(tvec!(input.into_arc_tensor())).unwrap();
+        assert_eq!(*output[0], tensor2(&[[0i32, 4]]));
+        assert_eq!(*output[1], tensor2(&[[1i64, 0]]));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Upper (or lower) triangular part of the matrices formed by the two inner
+/// axes of the first input, other elements are zeroed.
+///
+/// The second input is the scalar diagonal offset `k`: the upper triangle
+/// keeps elements on and above the k-th diagonal, the lower triangle on and
+/// below it.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct Trilu {
+    pub upper: bool,
+}
+
+impl_dyn_hash!(Trilu);
+
+impl Trilu {
+    fn eval_t<T: Datum>(&self, input: &Tensor, k: i64) -> TractResult<Tensor> {
+        let mut output = input.to_array_view::<T>()?.to_owned();
+        let rank = output.ndim();
+        let rows = output.shape()[rank - 2];
+        for (ix, mut lane) in output.lanes_mut(Axis(rank - 1)).into_iter().enumerate() {
+            let row = (ix % rows) as i64;
+            for (col, value) in lane.iter_mut().enumerate() {
+                let keep = if self.upper { col as i64 - row >= k } else { col as i64 - row <= k };
+                if !keep {
+                    *value = T::default();
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for Trilu {
+    fn name(&self) -> Cow<str> {
+        "Trilu".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("upper: {}", self.upper)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Trilu {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, k) = args_2!(inputs);
+        let k = k.cast_to_scalar::<i64>()?;
+        let output = dispatch_datum!(Self::eval_t(input.datum_type())(self, &input, k))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for Trilu {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].rank() < 2 {
+            bail!("Trilu input must have a rank of at least 2, got {:?}", inputs[0])
+        }
+        if inputs[1].rank() != 0 {
+            bail!("Trilu k must be a scalar, got {:?}", inputs[1])
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let rank = model.outlet_fact(node.inputs[0])?.rank();
+        Ok((0..rank - 2).map(|axis| AxisInfo::simple(axis)).collect())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(upper: bool, k: i64) -> Tensor {
+        let input = tensor2(&[[1i32, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        let mut output = Trilu::new(upper).eval(tvec!(input.into(), rctensor0(k))).unwrap();
+        output.remove(0).into_tensor()
+    }
+
+    #[test]
+    fn upper() {
+        assert_eq!(run(true, 0), tensor2(&[[1i32, 2, 3], [0, 5, 6], [0, 0, 9]]));
+        assert_eq!(run(true, 1), tensor2(&[[0i32, 2, 3], [0, 0, 6], [0, 0, 0]]));
+    }
+
+    #[test]
+    fn lower() {
+        assert_eq!(run(false, 0), tensor2(&[[1i32, 0, 0], [4, 5, 0], [7, 8, 9]]));
+        assert_eq!(run(false, -1), tensor2(&[[0i32, 0, 0], [4, 0, 0], [7, 8, 0]]));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::internal::*;
+use ndarray::*;
+use std::cmp::Ordering;
+
+/// Unique elements of the input, or unique slices along `axis`.
+///
+/// Outputs are the unique values, the index of their first occurrence in the
+/// input, the index in the unique values of each input element (or slice),
+/// and the number of occurrences of each unique value. Without `axis`, the
+/// input is flattened. Unique values are sorted if `sorted` is set, in order
+/// of first occurrence otherwise.
+///
+/// The number of unique values is the `count` symbol, resolved when the op is
+/// evaluated.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Unique {
+    pub axis: Option<usize>,
+    pub sorted: bool,
+    pub count: Symbol,
+}
+
+impl_dyn_hash!(Unique);
+
+impl Unique {
+    fn eval_t<T: Datum + Copy + PartialOrd>(
+        &self,
+        input: &Tensor,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let len = input.len();
+        let input = input.to_array_view::<T>()?;
+        let (view, axis) = if let Some(axis) = self.axis {
+            (input, Axis(axis))
+        } else {
+            (input.into_shape(IxDyn(&[len]))?, Axis(0))
+        };
+        let compare = |a: usize, b: usize| {
+            view.index_axis(axis, a)
+                .iter()
+                .zip(view.index_axis(axis, b).iter())
+                .map(|(x, y)| x.partial_cmp(y).unwrap_or(Ordering::Equal))
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        };
+        let mut order: Vec<usize> = (0..view.len_of(axis)).collect();
+        order.sort_by(|&a, &b| compare(a, b));
+        let mut groups: Vec<Vec<usize>> = vec![];
+        for ix in order {
+            match groups.last_mut() {
+                Some(group) if compare(group[0], ix) == Ordering::Equal => group.push(ix),
+                _ => groups.push(vec![ix]),
+            }
+        }
+        if !self.sorted {
+            groups.sort_by_key(|group| group[0]);
+        }
+        let indices: Vec<usize> = groups.iter().map(|group| group[0]).collect();
+        let mut inverse = vec![0i64; view.len_of(axis)];
+        for (ix, group) in groups.iter().enumerate() {
+            for &member in group {
+                inverse[member] = ix as i64;
+            }
+        }
+        let counts: Vec<i64> = groups.iter().map(|group| group.len() as i64).collect();
+        Ok(tvec!(
+            view.select(axis, &indices).into_arc_tensor(),
+            tensor1(&indices.iter().map(|&i| i as i64).collect::<Vec<_>>()).into_arc_tensor(),
+            tensor1(&inverse).into_arc_tensor(),
+            tensor1(&counts).into_arc_tensor(),
+        ))
+    }
+}
+
+impl Op for Unique {
+    fn name(&self) -> Cow<str> {
+        "Unique".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {:?} sorted: {} count: {}", self.axis, self.sorted, self.count)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Unique {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        dispatch_numbers!(Self::eval_t(input.datum_type())(self, &input))
+    }
+}
+
+impl TypedOp for Unique {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let count = self.count.to_dim();
+        let (values, len) = if let Some(axis) = self.axis {
+            if axis >= inputs[0].rank() {
+                bail!("Unique axis is {}, but input has rank {}", axis, inputs[0].rank())
+            }
+            let mut shape = inputs[0].shape.to_tvec();
+            let len = std::mem::replace(&mut shape[axis], count.clone());
+            (shape, len)
+        } else {
+            let len = inputs[0].shape.iter().fold(1.to_dim(), |acc, d| acc * d);
+            (tvec!(count.clone()), len)
+        };
+        Ok(tvec!(
+            TypedFact::dt_shape(inputs[0].datum_type, &*values),
+            TypedFact::dt_shape(i64::datum_type(), &[count.clone()]),
+            TypedFact::dt_shape(i64::datum_type(), &[len]),
+            TypedFact::dt_shape(i64::datum_type(), &[count]),
+        ))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_flat() {
+        let op = Unique::new(None, true, Symbol::new("unique"));
+        let outputs = op.eval(tvec!(rctensor2(&[[2f32, 1.], [1., 3.]]))).unwrap();
+        assert_eq!(*outputs[0], tensor1(&[1f32, 2., 3.]));
+        assert_eq!(*outputs[1], tensor1(&[1i64, 0, 3]));
+        assert_eq!(*outputs[2], tensor1(&[1i64, 0, 0, 2]));
+        assert_eq!(*outputs[3], tensor1(&[2i64, 1, 1]));
+        let op = Unique::new(None, false, Symbol::new("unique"));
+        let outputs = op.eval(tvec!(rctensor1(&[2i32, 1, 1, 3]))).unwrap();
+        assert_eq!(*outputs[0], tensor1(&[2i32, 1, 3]));
+        assert_eq!(*outputs[2], tensor1(&[0i64, 1, 1, 2]));
+    }
+
+    #[test]
+    fn unique_axis() {
+        let op = Unique::new(Some(0), true, Symbol::new("unique"));
+        let outputs = op.eval(tvec!(rctensor2(&[[1i32, 1], [0, 1], [2, 1], [0, 1]]))).unwrap();
+        assert_eq!(*outputs[0], tensor2(&[[0i32, 1], [1, 1], [2, 1]]));
+        assert_eq!(*outputs[1], tensor1(&[1i64, 0, 2]));
+        assert_eq!(*outputs[2], tensor1(&[1i64, 0, 2, 0]));
+        assert_eq!(*outputs[3], tensor1(&[2i64, 1, 1]));
+    }
+}
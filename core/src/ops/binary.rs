@@ -25,7 +25,7 @@ pub trait BinMiniOp:
         let a = a.cast_to_dt(op_type)?;
         let b = b.cast_to_dt(op_type)?;
         let c_dt = self.result_datum_type(a.datum_type(), b.datum_type())?;
-        let mut c = unsafe { Tensor::uninitialized_dt(c_dt, &*c_shape)? };
+        let mut c = unsafe { Tensor::uninitialized_output_dt(c_dt, &*c_shape)? };
         self.eval_out_of_place(&mut c, a.as_ref(), b.as_ref())?;
         Ok(tvec!(c.into_arc_tensor()))
     }
@@ -34,7 +34,7 @@ pub trait BinMiniOp:
         let c_shape = crate::broadcast::multi_broadcast(&[a.shape(), b.shape()])
             .ok_or_else(|| format_err!("Can not compute resulting shape"))?;
         let c_dt = self.result_datum_type(a.datum_type(), b.datum_type())?;
-        let mut c = unsafe { Tensor::uninitialized_dt(c_dt, &*c_shape)? };
+        let mut c = unsafe { Tensor::uninitialized_output_dt(c_dt, &*c_shape)? };
         self.eval_out_of_place(&mut c, a.as_ref(), b.as_ref())?;
         Ok(tvec!(c.into_arc_tensor()))
     }
@@ -58,6 +58,10 @@ impl Im2Col {
             Patcher::Padded2d
         } else if !patch.padded && patch.rank() == 1 {
             Patcher::Valid1d
+        } else if !patch.padded && patch.rank() == 3 {
+            Patcher::Valid3d
+        } else if patch.rank() == 3 {
+            Patcher::Padded3d
         } else {
             Patcher::Generic
         };
@@ -180,6 +184,8 @@ enum Patcher {
     Valid1d,
     Valid2d,
     Padded2d,
+    Valid3d,
+    Padded3d,
 }
 
 impl Patcher {
@@ -195,6 +201,8 @@ impl Patcher {
             Patcher::Valid1d => Self::valid_1d::<T>(im2col, input, input_shape, pack, g),
             Patcher::Valid2d => Self::valid_2d::<T>(im2col, input, input_shape, pack, g),
             Patcher::Padded2d => Self::padded_2d::<T>(im2col, input, input_shape, pack, g),
+            Patcher::Valid3d => Self::valid_3d::<T>(im2col, input, input_shape, pack, g),
+            Patcher::Padded3d => Self::padded_3d::<T>(im2col, input, input_shape, pack, g),
             _ => Self::generic::<T>(im2col, input, input_shape, pack, g),
         }
     }
@@ -345,4 +353,112 @@ impl Patcher {
             Ok(())
         }
     }
+    #[inline(never)]
+    fn padded_3d<'i, 'p, T: Copy + Datum>(
+        im2col: &'i Im2Col,
+        input: &'i TensorView,
+        shape: &DataShape,
+        pack: &'p mut TensorView,
+        g: usize,
+    ) -> TractResult<()> {
+        unsafe {
+            let pad_value = *im2col.pad_value.to_scalar_unchecked::<T>();
+            let pack = pack.as_slice_mut_unchecked::<T>();
+            let z_stride = im2col.patch.spec.strides[0] as isize;
+            let y_stride = im2col.patch.spec.strides[1] as isize;
+            let x_stride = im2col.patch.spec.strides[2] as isize;
+            let z_stride_ptr = z_stride * shape.hw_strides()[0] as isize;
+            let y_stride_ptr = y_stride * shape.hw_strides()[1] as isize;
+            let x_stride_ptr = x_stride * *shape.w_stride() as isize;
+            let c_stride_ptr = *shape.c_stride() as isize;
+            let input_depth = shape.hw_dims()[0] as isize;
+            let input_heigth = shape.hw_dims()[1] as isize;
+            let input_width = shape.hw_dims()[2] as isize;
+            let output_depth = *im2col.patch.output_shape.get_unchecked(0);
+            let output_heigth = *im2col.patch.output_shape.get_unchecked(1);
+            let output_width = *im2col.patch.output_shape.get_unchecked(2);
+            let kernel_len = im2col.patch.standard_layout_data_field.len();
+            let mut writer = im2col.b_pack.write_with_k_outer(pack, im2col.n);
+            let iptr = input.as_ptr_unchecked::<T>();
+            let iptr = iptr.offset((g * im2col.ci_per_group * shape.c_stride()) as isize);
+            for ci in 0..im2col.ci_per_group {
+                let iptr = iptr.offset(ci as isize * c_stride_ptr);
+                for kitem in 0..kernel_len {
+                    let dz = *im2col.patch.data_field.as_ptr().offset(kitem as isize * 3);
+                    let dy = *im2col.patch.data_field.as_ptr().offset(1 + kitem as isize * 3);
+                    let dx = *im2col.patch.data_field.as_ptr().offset(2 + kitem as isize * 3);
+                    let iptr =
+                        iptr.offset(*im2col.patch.standard_layout_data_field.get_unchecked(kitem));
+                    for zo in 0..output_depth {
+                        let z = zo as isize * z_stride + dz;
+                        let iptr = iptr.offset(zo as isize * z_stride_ptr);
+                        if z < 0 || z >= input_depth {
+                            for _ in 0..output_heigth * output_width {
+                                writer.write(pad_value);
+                            }
+                            continue;
+                        }
+                        for yo in 0..output_heigth {
+                            let y = yo as isize * y_stride + dy;
+                            let iptr = iptr.offset(yo as isize * y_stride_ptr);
+                            if y >= 0 && y < input_heigth {
+                                for xo in 0..output_width {
+                                    let x = xo as isize * x_stride + dx;
+                                    if x >= 0 && x < input_width {
+                                        writer.write(*iptr.offset(xo as isize * x_stride_ptr));
+                                    } else {
+                                        writer.write(pad_value);
+                                    }
+                                }
+                            } else {
+                                for _x in 0..output_width {
+                                    writer.write(pad_value);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[inline(never)]
+    fn valid_3d<'i, 'p, T: Copy + Datum>(
+        im2col: &'i Im2Col,
+        input: &'i TensorView,
+        shape: &DataShape,
+        pack: &'p mut TensorView,
+        g: usize,
+    ) -> TractResult<()> {
+        unsafe {
+            let pack = pack.as_slice_mut_unchecked::<T>();
+            let z_stride = im2col.patch.spec.strides[0] as isize;
+            let y_stride = im2col.patch.spec.strides[1] as isize;
+            let x_stride = im2col.patch.spec.strides[2] as isize;
+            let z_stride_ptr = z_stride * shape.hw_strides()[0] as isize;
+            let y_stride_ptr = y_stride * shape.hw_strides()[1] as isize;
+            let x_stride_ptr = x_stride * *shape.w_stride() as isize;
+            let c_stride_ptr = *shape.c_stride() as isize;
+            let mut writer = im2col.b_pack.write_with_k_outer(pack, im2col.n);
+            let iptr = input.as_ptr_unchecked::<T>();
+            let iptr = iptr.offset((g * im2col.ci_per_group * shape.c_stride()) as isize);
+            for ci in 0..im2col.ci_per_group {
+                let iptr = iptr.offset(ci as isize * c_stride_ptr);
+                for koffset in &im2col.patch.standard_layout_data_field {
+                    let iptr = iptr.offset(*koffset);
+                    for z in 0..*im2col.patch.output_shape.get_unchecked(0) {
+                        let iptr = iptr.offset(z as isize * z_stride_ptr);
+                        for y in 0..*im2col.patch.output_shape.get_unchecked(1) {
+                            let iptr = iptr.offset(y as isize * y_stride_ptr);
+                            for x in 0..*im2col.patch.output_shape.get_unchecked(2) {
+                                writer.write(*iptr.offset(x as isize * x_stride_ptr));
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
 }
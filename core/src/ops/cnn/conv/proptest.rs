@@ -15,6 +15,9 @@ struct ConvProblem {
     data: ArrayD<f32>,
     kernel: ArrayD<f32>,
     bias: Option<ArrayD<f32>>,
+    strides: TVec<usize>,
+    dilations: TVec<usize>,
+    padding: PaddingSpec,
 }
 
 impl ConvProblem {
@@ -28,6 +31,12 @@ impl ConvProblem {
         let n = *self.shape_in.n().clone().unwrap_or(&1);
         let ci_per_g = self.shape_in.c() / self.group;
         let co_per_g = self.shape_out.c() / self.group;
+        let pad_before: TVec<isize> = self
+            .padding
+            .compute(self.shape_in.hw_dims(), self.geo_ker(), &self.dilations, &self.strides)
+            .iter()
+            .map(|d| d.pad_before as isize)
+            .collect();
         for n in 0..n {
             for g in 0..self.group {
                 for geo_out in tract_ndarray::indices(self.shape_out.hw_dims()) {
@@ -37,8 +46,20 @@ impl ConvProblem {
                     }
                     output_coords.insert(self.shape_out.c_axis(), 0);
                     for geo_ker in tract_ndarray::indices(self.geo_ker()) {
+                        let input_geo: TVec<isize> = (0..self.shape_in.hw_rank())
+                            .map(|ax| {
+                                (geo_out[ax] * self.strides[ax] + geo_ker[ax] * self.dilations[ax])
+                                    as isize
+                                    - pad_before[ax]
+                            })
+                            .collect();
+                        if izip!(&input_geo, self.shape_in.hw_dims())
+                            .any(|(&x, &dim)| x < 0 || x >= dim as isize)
+                        {
+                            continue;
+                        }
                         let mut input_coords: TVec<usize> =
-                            izip!(geo_out.slice(), geo_ker.slice()).map(|(a, b)| a + b).collect();
+                            input_geo.iter().map(|&x| x as usize).collect();
                         if self.shape_in.fmt.has_n() {
                             input_coords.insert(0, n);
                         }
@@ -84,9 +105,9 @@ impl ConvProblem {
             PoolSpec::new(
                 self.shape_in.fmt,
                 self.geo_ker().into(),
-                PaddingSpec::Valid,
-                None,
-                None,
+                self.padding.clone(),
+                Some(self.dilations.clone()),
+                Some(self.strides.clone()),
                 Some(*self.shape_out.c()),
             ),
             self.kernel_format.clone(),
@@ -103,10 +124,8 @@ impl ConvProblem {
     }
 }
 
-impl Arbitrary for ConvProblem {
-    type Parameters = ();
-    type Strategy = BoxedStrategy<ConvProblem>;
-    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+impl ConvProblem {
+    fn strategy(ranks: std::ops::RangeInclusive<usize>) -> BoxedStrategy<ConvProblem> {
         (
             any::<DataFormat>(),
             any::<KernelFormat>(),
@@ -114,16 +133,25 @@ impl Arbitrary for ConvProblem {
             1usize..=4,
             1usize..=4,
             1usize..=3,
-            (1usize..=3).prop_flat_map(|r| shapes(r)),
+            ranks.prop_flat_map(|r| shapes(r)),
+            prop_oneof!(
+                Just(PaddingSpec::Valid),
+                Just(PaddingSpec::SameUpper),
+                Just(PaddingSpec::SameLower)
+            ),
         )
-            .prop_flat_map(|(df, kf, n, mut ci0, co0, group, (mut ker_shape, data_shape))| {
+            .prop_flat_map(|(df, kf, n, mut ci0, co0, group, geo, padding)| {
+                let (mut ker_shape, data_shape, strides, dilations) = geo;
                 // FIXME in HWIO order, only regular and depthwise are supported
                 if kf == KernelFormat::HWIO && group > 1 {
                     ci0 = 1;
                 }
                 let shape_in = df.from_n_c_hw(n, ci0 * group, &data_shape).unwrap();
-                let shape_out: TVec<_> =
-                    izip!(&ker_shape, data_shape).map(|(k, d)| d - k + 1).collect();
+                let shape_out: TVec<_> = padding
+                    .compute(&data_shape, &ker_shape, &dilations, &strides)
+                    .iter()
+                    .map(|d| d.output)
+                    .collect();
                 let shape_out = df.from_n_c_hw(n, co0 * group, &shape_out).unwrap();
                 let data_in = tensor(shape_in.shape.iter().cloned().collect());
                 match kf {
@@ -138,15 +166,36 @@ impl Arbitrary for ConvProblem {
                 };
                 let kernel = tensor(ker_shape);
                 let bias = proptest::option::of(tensor(vec![co0 * group]));
-                (Just((kf, shape_in, shape_out, group)), data_in, kernel, bias)
+                let geo = (strides.into(), dilations.into(), padding);
+                (Just((kf, shape_in, shape_out, group, geo)), data_in, kernel, bias)
             })
-            .prop_map(|((kernel_format, shape_in, shape_out, group), data, kernel, bias)| {
-                ConvProblem { shape_in, shape_out, kernel_format, group, data, kernel, bias }
+            .prop_map(|((kernel_format, shape_in, shape_out, group, geo), data, kernel, bias)| {
+                let (strides, dilations, padding) = geo;
+                ConvProblem {
+                    shape_in,
+                    shape_out,
+                    kernel_format,
+                    group,
+                    data,
+                    kernel,
+                    bias,
+                    strides,
+                    dilations,
+                    padding,
+                }
             })
             .boxed()
     }
 }
 
+impl Arbitrary for ConvProblem {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<ConvProblem>;
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        ConvProblem::strategy(1..=3)
+    }
+}
+
 impl Arbitrary for KernelFormat {
     type Parameters = ();
     type Strategy = BoxedStrategy<KernelFormat>;
@@ -176,9 +225,20 @@ fn tensor(shape: Vec<usize>) -> BoxedStrategy<ArrayD<f32>> {
         .boxed()
 }
 
-fn shapes(rank: usize) -> BoxedStrategy<(Vec<usize>, Vec<usize>)> {
-    vec((1usize..3, 0usize..3).prop_map(|(k, exceed)| (k, k + exceed)), rank..=rank)
-        .prop_map(|v| v.into_iter().unzip())
+/// Kernel, data, strides and dilations shapes of a `rank` dimensional
+/// convolution, with data at least as big as the dilated kernel.
+fn shapes(rank: usize) -> BoxedStrategy<(Vec<usize>, Vec<usize>, Vec<usize>, Vec<usize>)> {
+    vec((1usize..3, 0usize..3, 1usize..3, 1usize..3), rank..=rank)
+        .prop_map(|v| {
+            let mut shapes = (vec![], vec![], vec![], vec![]);
+            for (k, exceed, stride, dilation) in v {
+                shapes.0.push(k);
+                shapes.1.push((k - 1) * dilation + 1 + exceed);
+                shapes.2.push(stride);
+                shapes.3.push(dilation);
+            }
+            shapes
+        })
         .boxed()
 }
 
@@ -187,6 +247,11 @@ proptest::proptest! {
     fn prop(pb in any::<ConvProblem>()) {
         prop_assert_eq!(pb.tract().unwrap(), pb.reference());
     }
+
+    #[test]
+    fn prop_3d(pb in ConvProblem::strategy(3..=3)) {
+        prop_assert_eq!(pb.tract().unwrap(), pb.reference());
+    }
 }
 
 #[test]
@@ -199,6 +264,9 @@ fn trivial_1() -> anyhow::Result<()> {
         data: ndarray::arr3(&[[[1.0f32]]]).into_dyn(),
         kernel: ndarray::arr3(&[[[1.0f32]]]).into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract()?, pb.reference());
     Ok(())
@@ -214,6 +282,9 @@ fn trivial_2() -> anyhow::Result<()> {
         data: ndarray::arr3(&[[[1.0f32], [0.0]]]).into_dyn(),
         kernel: ndarray::arr3(&[[[1.0f32]]]).into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract()?, pb.reference());
     Ok(())
@@ -229,6 +300,9 @@ fn trivial_3() -> anyhow::Result<()> {
         data: ndarray::arr3(&[[[0.0f32, 1.0]]]).into_dyn(),
         kernel: ndarray::arr3(&[[[0.0f32], [1.0]]]).into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract()?, pb.reference());
     Ok(())
@@ -244,6 +318,9 @@ fn group_1() -> anyhow::Result<()> {
         data: ndarray::arr2(&[[0.0f32, 1.0]]).into_dyn(),
         kernel: ndarray::arr3(&[[[0.0f32]], [[1.0]]]).into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract()?, pb.reference());
     Ok(())
@@ -276,6 +353,9 @@ fn group_3() -> anyhow::Result<()> {
         data: ndarray::arr2(&[[0.0f32, 1.0]]).into_dyn(),
         kernel: ndarray::arr3(&[[[0.0f32]], [[1.0]]]).into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -291,6 +371,9 @@ fn group_4() -> anyhow::Result<()> {
         data: ndarray::arr2(&[[0.0f32, 1.0]]).into_dyn(),
         kernel: ndarray::arr3(&[[[0.0f32]], [[0.0]], [[0.0]], [[1.0]]]).into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -309,6 +392,9 @@ fn group_5() -> anyhow::Result<()> {
             .unwrap()
             .into_dyn(),
         bias: None,
+        strides: tvec!(1; 2),
+        dilations: tvec!(1; 2),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -327,6 +413,9 @@ fn group_6() -> anyhow::Result<()> {
             .unwrap()
             .into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -345,6 +434,9 @@ fn group_7() -> anyhow::Result<()> {
             .unwrap()
             .into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -363,6 +455,9 @@ fn group_8() -> anyhow::Result<()> {
             .unwrap()
             .into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -381,6 +476,9 @@ fn group_9() -> anyhow::Result<()> {
             .unwrap()
             .into_dyn(),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -396,6 +494,9 @@ fn group_10() -> anyhow::Result<()> {
         data: ndarray::ArrayD::<f32>::zeros(vec![2, 2, 1, 4]),
         kernel: ndarray::ArrayD::from_elem(vec![4, 1, 1, 1, 2], 1.0f32),
         bias: None,
+        strides: tvec!(1; 3),
+        dilations: tvec!(1; 3),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -411,6 +512,9 @@ fn group_bias_0() -> anyhow::Result<()> {
         data: ndarray::ArrayD::<f32>::zeros(vec![1, 1, 2]),
         kernel: ndarray::ArrayD::<f32>::zeros(vec![4, 1, 1]),
         bias: Some(ndarray::ArrayD::<f32>::zeros(vec![4])),
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -426,6 +530,9 @@ fn bias_0() -> anyhow::Result<()> {
         data: ndarray::ArrayD::<f32>::zeros(vec![2, 1]),
         kernel: ndarray::ArrayD::<f32>::zeros(vec![1, 1, 2]),
         bias: Some(ndarray::ArrayD::<f32>::zeros(vec![1])),
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
@@ -441,6 +548,9 @@ fn batch_0() -> anyhow::Result<()> {
         data: ndarray::ArrayD::<f32>::zeros(vec![2, 2, 1]),
         kernel: ndarray::ArrayD::<f32>::zeros(vec![1, 1, 2]),
         bias: None,
+        strides: tvec!(1; 1),
+        dilations: tvec!(1; 1),
+        padding: PaddingSpec::Valid,
     };
     assert_eq!(pb.tract().unwrap(), pb.reference());
     Ok(())
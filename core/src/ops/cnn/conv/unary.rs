@@ -284,7 +284,7 @@ impl ConvUnary {
                 .data_format
                 .shape(input_fact.shape.iter().collect::<TVec<TDim>>())?;
             let down = patch.wire_node(
-                format!("{}.downsample", node.name),
+                format!("{}.downsample-{}", node.name, axis),
                 crate::ops::Downsample::new(axis + shape.h_axis(), downsample_factor as isize, 0),
                 &[tap],
             )?;
@@ -0,0 +1,336 @@
+use crate::internal::*;
+use ndarray::*;
+
+use crate::ops::cnn::{KernelFormat, PaddingSpec};
+use crate::ops::nn::DataFormat;
+
+/// Transposed convolution (aka deconvolution, or convolution gradient wrt
+/// its input).
+///
+/// The kernel is given in the format of the matching forward convolution:
+/// its "output" channels are the input channels of the deconvolution, and its
+/// "input" channels are the output channels of the deconvolution.
+///
+/// For each spatial axis, the output size is `(i - 1) * s + (k - 1) * d + 1 +
+/// adj - pads` for valid and explicit paddings, and `(i - 1) * s + 1 + adj`
+/// for same paddings. `adjustments` picks among the output sizes that a
+/// forward convolution with the same geometry would map to the input size.
+#[derive(Debug, Clone, new, Hash)]
+pub struct DeconvUnary {
+    pub data_format: DataFormat,
+    pub kernel_format: KernelFormat,
+    pub padding: PaddingSpec,
+    pub strides: TVec<usize>,
+    pub dilations: TVec<usize>,
+    pub kernel: Arc<Tensor>,
+    pub bias: Option<Arc<Tensor>>,
+    pub adjustments: TVec<usize>,
+    pub group: usize,
+}
+
+impl_dyn_hash!(DeconvUnary);
+
+impl DeconvUnary {
+    fn hw_rank(&self) -> usize {
+        self.kernel.rank() - 2
+    }
+
+    fn kernel_spatial_shape(&self) -> &[usize] {
+        &self.kernel.shape()[self.kernel_format.h_axis()..][..self.hw_rank()]
+    }
+
+    fn input_channels(&self) -> usize {
+        let kshape = self.kernel.shape();
+        match self.kernel_format {
+            KernelFormat::OIHW => kshape[0],
+            KernelFormat::HWIO => kshape[kshape.len() - 1] * self.group,
+        }
+    }
+
+    fn output_channels(&self) -> usize {
+        let kshape = self.kernel.shape();
+        match self.kernel_format {
+            KernelFormat::OIHW => kshape[1] * self.group,
+            KernelFormat::HWIO => kshape[kshape.len() - 2],
+        }
+    }
+
+    fn output_spatial_dim<D: DimLike>(&self, axis: usize, input: &D) -> D {
+        let kernel_field = (self.kernel_spatial_shape()[axis] - 1) * self.dilations[axis] + 1;
+        let stride = self.strides[axis];
+        let adj = self.adjustments[axis];
+        match &self.padding {
+            PaddingSpec::Valid => (input.clone() - 1) * stride + kernel_field + adj,
+            PaddingSpec::Explicit(bef, aft, _) => {
+                (input.clone() - 1) * stride + kernel_field + adj - bef[axis] - aft[axis]
+            }
+            PaddingSpec::SameUpper | PaddingSpec::SameLower => {
+                (input.clone() - 1) * stride + 1 + adj
+            }
+        }
+    }
+
+    fn pad_before(&self, axis: usize, output: usize) -> usize {
+        // padding of the forward convolution mapping the output to the input
+        self.padding
+            .compute_one(
+                axis,
+                &output,
+                self.kernel_spatial_shape()[axis],
+                self.dilations[axis],
+                self.strides[axis],
+            )
+            .pad_before
+    }
+
+    fn eval_t<T>(&self, input: &Tensor) -> TractResult<Tensor>
+    where
+        T: Datum + Copy + num_traits::Zero + std::ops::Mul<Output = T> + std::ops::AddAssign,
+    {
+        let hw_rank = self.hw_rank();
+        let mut input = input.to_array_view::<T>()?;
+        if !self.data_format.has_n() {
+            input = input.insert_axis(Axis(0));
+        }
+        if self.data_format == DataFormat::NHWC || self.data_format == DataFormat::HWC {
+            let mut permutation = vec![0, hw_rank + 1];
+            permutation.extend(1..hw_rank + 1);
+            input = input.permuted_axes(permutation);
+        }
+        let kernel = self.kernel.to_array_view::<T>()?;
+        let input_spatial = &input.shape()[2..];
+        let output_spatial: TVec<usize> = input_spatial
+            .iter()
+            .enumerate()
+            .map(|(ax, i)| self.output_spatial_dim(ax, i))
+            .collect();
+        let pad_before: TVec<usize> =
+            output_spatial.iter().enumerate().map(|(ax, &o)| self.pad_before(ax, o)).collect();
+        let mut output_shape = tvec!(input.shape()[0], self.output_channels());
+        output_shape.extend(output_spatial.iter().cloned());
+        let mut output = ArrayD::<T>::zeros(&*output_shape);
+        let ci_per_group = self.input_channels() / self.group;
+        let co_per_group = self.output_channels() / self.group;
+        let mut kernel_coords = vec![0; hw_rank + 2];
+        let mut output_coords = vec![0; hw_rank + 2];
+        for icoords in indices(input.shape()) {
+            let (n, ci) = (icoords[0], icoords[1]);
+            let x = input[&icoords];
+            let g = ci / ci_per_group;
+            'kernel: for kcoords in indices(self.kernel_spatial_shape()) {
+                output_coords[0] = n;
+                for ax in 0..hw_rank {
+                    let y = (icoords[2 + ax] * self.strides[ax] + kcoords[ax] * self.dilations[ax])
+                        as isize
+                        - pad_before[ax] as isize;
+                    if y < 0 || y as usize >= output_spatial[ax] {
+                        continue 'kernel;
+                    }
+                    output_coords[2 + ax] = y as usize;
+                }
+                for co in 0..co_per_group {
+                    match self.kernel_format {
+                        KernelFormat::OIHW => {
+                            kernel_coords[0] = ci;
+                            kernel_coords[1] = co;
+                            kernel_coords[2..].copy_from_slice(kcoords.slice());
+                        }
+                        KernelFormat::HWIO => {
+                            kernel_coords[..hw_rank].copy_from_slice(kcoords.slice());
+                            kernel_coords[hw_rank] = g * co_per_group + co;
+                            kernel_coords[hw_rank + 1] = ci % ci_per_group;
+                        }
+                    }
+                    output_coords[1] = g * co_per_group + co;
+                    output[&*output_coords] += x * kernel[&*kernel_coords];
+                }
+            }
+        }
+        if let Some(bias) = &self.bias {
+            let bias = bias.to_array_view::<T>()?;
+            for (c, b) in bias.iter().enumerate() {
+                output.index_axis_mut(Axis(1), c).map_inplace(|x| *x += *b);
+            }
+        }
+        if self.data_format == DataFormat::NHWC || self.data_format == DataFormat::HWC {
+            let mut permutation = vec![0];
+            permutation.extend(2..hw_rank + 2);
+            permutation.push(1);
+            output = output.permuted_axes(permutation);
+        }
+        if !self.data_format.has_n() {
+            output = output.index_axis_move(Axis(0), 0);
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for DeconvUnary {
+    fn name(&self) -> Cow<str> {
+        "DeconvUnary".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("Data format: {:?}, kernel format: {:?}", self.data_format, self.kernel_format),
+            format!(
+                "Kernel shape:{:?} (strides:{:?}, padding:{:?}, dilations:{:?}, adjustments:{:?}, groups:{})",
+                self.kernel_spatial_shape(),
+                self.strides,
+                self.padding,
+                self.dilations,
+                self.adjustments,
+                self.group
+            ),
+        ])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for DeconvUnary {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = match input.datum_type() {
+            DatumType::F32 => self.eval_t::<f32>(&input)?,
+            DatumType::F64 => self.eval_t::<f64>(&input)?,
+            dt => bail!("Deconvolution is not implemented for {:?}", dt),
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for DeconvUnary {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let hw_rank = self.hw_rank();
+        if self.strides.len() != hw_rank
+            || self.dilations.len() != hw_rank
+            || self.adjustments.len() != hw_rank
+        {
+            bail!("Inconsistent deconvolution geometry: {:?}", self);
+        }
+        let shape = self.data_format.shape(inputs[0].shape.to_tvec())?;
+        if shape.hw_rank() != hw_rank {
+            bail!(
+                "Inconsistent deconvolution: input is {:?}, kernel is {:?}",
+                inputs[0],
+                self.kernel
+            );
+        }
+        if shape.c() != &self.input_channels().to_dim() {
+            bail!(
+                "Inconsistent deconvolution: input is {:?}, kernel expects {} input channels",
+                inputs[0],
+                self.input_channels()
+            );
+        }
+        if let Some(bias) = &self.bias {
+            if bias.len() != self.output_channels() {
+                bail!("Bias should have one value per output channel, got:{:?}", bias);
+            }
+        }
+        let spatial: TVec<TDim> = shape
+            .hw_dims()
+            .iter()
+            .enumerate()
+            .map(|(ax, d)| self.output_spatial_dim(ax, d))
+            .collect();
+        let output_shape = self.data_format.from_n_c_hw(
+            shape.n().cloned().unwrap_or(1.into()),
+            self.output_channels().into(),
+            &*spatial,
+        )?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*output_shape.shape)))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        let fact = model.outlet_fact(node.inputs[0])?;
+        let shape = self.data_format.shape(fact.shape.to_tvec())?;
+        Ok(shape.n_axis().map(|n| AxisInfo::simple(n).disposable(true)).into_iter().collect())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deconv_1d(kernel: Tensor, strides: usize, padding: PaddingSpec, adj: usize) -> DeconvUnary {
+        DeconvUnary::new(
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+            padding,
+            tvec!(strides),
+            tvec!(1),
+            kernel.into_arc_tensor(),
+            None,
+            tvec!(adj),
+            1,
+        )
+    }
+
+    fn run(op: &DeconvUnary, input: Tensor) -> Tensor {
+        let fact = TypedFact::from(input.clone());
+        let output_fact = op.output_facts(&[&fact]).unwrap().remove(0);
+        let output = op.eval(tvec!(input.into_arc_tensor())).unwrap().remove(0).into_tensor();
+        assert_eq!(output_fact.shape.as_concrete().unwrap(), output.shape());
+        output
+    }
+
+    #[test]
+    fn valid_stride_1() {
+        let op = deconv_1d(tensor3(&[[[1f32, 2.]]]), 1, PaddingSpec::Valid, 0);
+        let output = run(&op, tensor3(&[[[1f32, 10., 100.]]]));
+        assert_eq!(output, tensor3(&[[[1f32, 12., 120., 200.]]]));
+    }
+
+    #[test]
+    fn valid_stride_2() {
+        let op = deconv_1d(tensor3(&[[[1f32, 2., 3.]]]), 2, PaddingSpec::Valid, 0);
+        let output = run(&op, tensor3(&[[[1f32, 10.]]]));
+        assert_eq!(output, tensor3(&[[[1f32, 2., 13., 20., 30.]]]));
+    }
+
+    #[test]
+    fn explicit_padding() {
+        let op = deconv_1d(
+            tensor3(&[[[1f32, 2., 3.]]]),
+            1,
+            PaddingSpec::Explicit(tvec!(1), tvec!(1), false),
+            0,
+        );
+        let output = run(&op, tensor3(&[[[1f32, 10., 100.]]]));
+        assert_eq!(output, tensor3(&[[[12f32, 123., 230.]]]));
+    }
+
+    #[test]
+    fn same_stride_2() {
+        let op = deconv_1d(tensor3(&[[[1f32, 2., 3.]]]), 2, PaddingSpec::SameUpper, 1);
+        let output = run(&op, tensor3(&[[[1f32, 10.]]]));
+        assert_eq!(output, tensor3(&[[[1f32, 2., 13., 20.]]]));
+    }
+
+    #[test]
+    fn hwio_nhwc_two_channels() {
+        let op = DeconvUnary::new(
+            DataFormat::NHWC,
+            KernelFormat::HWIO,
+            PaddingSpec::Valid,
+            tvec!(1),
+            tvec!(1),
+            // H=1, I(deconv output)=2, O(deconv input)=1
+            tensor3(&[[[1f32], [2.]]]).into_arc_tensor(),
+            Some(rctensor1(&[0f32, 1.])),
+            tvec!(0),
+            1,
+        );
+        let output = run(&op, tensor3(&[[[1f32], [3.]]]));
+        assert_eq!(output, tensor3(&[[[1f32, 3.], [3., 7.]]]));
+    }
+}
@@ -190,3 +190,42 @@ impl TypedOp for MaxPoolFixed {
         Ok(facts)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::PaddingSpec;
+    use crate::ops::nn::DataFormat;
+
+    #[test]
+    fn max_pool_3d() -> TractResult<()> {
+        let input =
+            tensor1(&(0..27).map(|x| x as f32).collect::<Vec<_>>()).into_shape(&[1, 1, 3, 3, 3])?;
+        let spec =
+            PoolSpec::new(DataFormat::NCHW, tvec!(2, 2, 2), PaddingSpec::Valid, None, None, None);
+        let output = MaxPool::new(spec, None).eval(tvec!(input.into_arc_tensor()))?;
+        let expected =
+            tensor1(&[13f32, 14., 16., 17., 22., 23., 25., 26.]).into_shape(&[1, 1, 2, 2, 2])?;
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn max_pool_3d_dilated() -> TractResult<()> {
+        let input =
+            tensor1(&(0..64).map(|x| x as f32).collect::<Vec<_>>()).into_shape(&[1, 1, 4, 4, 4])?;
+        let spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(2, 2, 2),
+            PaddingSpec::Valid,
+            Some(tvec!(2, 2, 2)),
+            None,
+            None,
+        );
+        let output = MaxPool::new(spec, None).eval(tvec!(input.into_arc_tensor()))?;
+        let expected =
+            tensor1(&[42f32, 43., 46., 47., 58., 59., 62., 63.]).into_shape(&[1, 1, 2, 2, 2])?;
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+}
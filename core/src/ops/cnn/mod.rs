@@ -1,15 +1,19 @@
 pub mod conv;
+mod deconv;
 mod maxpool;
 mod padding;
 mod patch_axis;
 mod patches;
 pub mod pools;
+mod roi;
 mod sumpool;
 
 pub use self::conv::{ConvUnary, KernelFormat};
+pub use self::deconv::DeconvUnary;
 pub use self::maxpool::MaxPool;
 pub use self::padding::PaddingSpec;
 pub use self::patch_axis::PatchAxis;
 pub use self::patches::{Patch, PatchSpec};
 pub use self::pools::PoolSpec;
+pub use self::roi::{RoiAlign, RoiPool, RoiPoolMode, RoiResample, SamplingMethod};
 pub use self::sumpool::SumPool;
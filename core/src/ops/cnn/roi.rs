@@ -0,0 +1,623 @@
+use crate::internal::*;
+use ndarray::*;
+use num_traits::{AsPrimitive, Float};
+
+/// How the bins of a region of interest are reduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoiPoolMode {
+    Avg,
+    Max,
+}
+
+/// Where the output cells of a resampled region are sampled.
+///
+/// With `h` the size of the region along an axis starting at `a`, and `n`
+/// the output size, the i-th sample is taken at:
+/// * `Symmetric`: `a + (i + 0.5) * h / n - 0.5` (cell centers),
+/// * `Asymmetric`: `a + i * h / n` (cell corners),
+/// * `Aligned`: `a + i * h / (n - 1)` (the region corners are sampled).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SamplingMethod {
+    Symmetric,
+    Asymmetric,
+    Aligned,
+}
+
+impl SamplingMethod {
+    fn coord(&self, start: f32, end: f32, ix: usize, size: usize) -> f32 {
+        let h = end - start;
+        match self {
+            SamplingMethod::Symmetric => start + (ix as f32 + 0.5) * h / size as f32 - 0.5,
+            SamplingMethod::Asymmetric => start + ix as f32 * h / size as f32,
+            SamplingMethod::Aligned if size > 1 => start + ix as f32 * h / (size - 1) as f32,
+            SamplingMethod::Aligned => start + 0.5 * h,
+        }
+    }
+}
+
+fn check_roi_inputs(output_size: &[usize], inputs: &[&TypedFact]) -> TractResult<()> {
+    if output_size.len() != 2 {
+        bail!("Only 2D regions of interest are supported, got output size {:?}", output_size);
+    }
+    if inputs[0].rank() != 4 {
+        bail!("Expected NCHW input, got {:?}", inputs[0]);
+    }
+    if inputs[1].rank() != 2 || inputs[1].shape[1] != 4.to_dim() {
+        bail!("Expected regions of interest as a [r, 4] tensor, got {:?}", inputs[1]);
+    }
+    if inputs[2].rank() != 1 || inputs[2].shape[0] != inputs[1].shape[0] {
+        bail!("Expected one batch index per region of interest, got {:?}", inputs[2]);
+    }
+    Ok(())
+}
+
+fn roi_output_fact(output_size: &[usize], inputs: &[&TypedFact]) -> TractResult<TypedFact> {
+    check_roi_inputs(output_size, inputs)?;
+    let shape = tvec!(
+        inputs[1].shape[0].clone(),
+        inputs[0].shape[1].clone(),
+        output_size[0].to_dim(),
+        output_size[1].to_dim()
+    );
+    Ok(TypedFact::dt_shape(inputs[0].datum_type, &*shape))
+}
+
+/// Regions of interest and their batch index, checked against the input
+/// batch size.
+fn regions(rois: &Tensor, batch_index: &Tensor, batch: usize) -> TractResult<Vec<[f32; 5]>> {
+    let rois = rois.cast_to::<f32>()?;
+    let rois = rois.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+    let batch_index = batch_index.cast_to::<i64>()?;
+    let batch_index = batch_index.as_slice::<i64>()?;
+    rois.outer_iter()
+        .zip(batch_index.iter())
+        .map(|(roi, &b)| {
+            if b < 0 || b as usize >= batch {
+                bail!("Batch index {} out of range (batch size is {})", b, batch)
+            }
+            Ok([b as f32, roi[0], roi[1], roi[2], roi[3]])
+        })
+        .collect()
+}
+
+/// Pooling of regions of interest to a fixed spatial size.
+///
+/// Inputs are the NCHW data, the regions as a `[r, 4]` tensor of
+/// `(y1, x1, y2, x2)` coordinates in pixels, and the `[r]` batch index of each
+/// region. Each region is divided in a grid of `output_size` bins, and each
+/// bin covers the pixels its (rounded outwards) boundaries intersect. Empty
+/// bins produce zeros.
+#[derive(Debug, Clone, new, Hash)]
+pub struct RoiPool {
+    pub output_size: TVec<usize>,
+    pub mode: RoiPoolMode,
+}
+
+impl_dyn_hash!(RoiPool);
+
+impl RoiPool {
+    fn bin(start: f32, end: f32, ix: usize, size: usize, len: usize) -> (usize, usize) {
+        let step = (end - start) / size as f32;
+        let from = (start + ix as f32 * step).floor().max(0.0).min(len as f32);
+        let to = (start + (ix + 1) as f32 * step).ceil().max(0.0).min(len as f32);
+        (from as usize, to as usize)
+    }
+
+    fn eval_t<T: Datum + Float>(
+        &self,
+        input: &Tensor,
+        rois: &Tensor,
+        batch_index: &Tensor,
+    ) -> TractResult<Tensor>
+    where
+        usize: AsPrimitive<T>,
+    {
+        let input = input.to_array_view::<T>()?.into_dimensionality::<Ix4>()?;
+        let (n, c, h, w) = input.dim();
+        let regions = regions(rois, batch_index, n)?;
+        let (oh, ow) = (self.output_size[0], self.output_size[1]);
+        let mut output = Array4::<T>::zeros((regions.len(), c, oh, ow));
+        for (r, region) in regions.iter().enumerate() {
+            let b = region[0] as usize;
+            for i in 0..oh {
+                let (y0, y1) = Self::bin(region[1], region[3], i, oh, h);
+                for j in 0..ow {
+                    let (x0, x1) = Self::bin(region[2], region[4], j, ow, w);
+                    if y0 >= y1 || x0 >= x1 {
+                        continue;
+                    }
+                    for ch in 0..c {
+                        let window = input.slice(s![b, ch, y0..y1, x0..x1]);
+                        output[(r, ch, i, j)] = match self.mode {
+                            RoiPoolMode::Avg => {
+                                window.fold(T::zero(), |acc, &x| acc + x) / window.len().as_()
+                            }
+                            RoiPoolMode::Max => {
+                                window.fold(T::neg_infinity(), |acc, &x| acc.max(x))
+                            }
+                        };
+                    }
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for RoiPool {
+    fn name(&self) -> Cow<str> {
+        "RoiPool".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?} to {:?}", self.mode, self.output_size)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for RoiPool {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, rois, batch_index) = args_3!(inputs);
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(
+            self,
+            &input,
+            &rois,
+            &batch_index
+        ))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for RoiPool {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(roi_output_fact(&self.output_size, inputs)?))
+    }
+
+    as_op!();
+}
+
+/// Resampling of regions of interest to a fixed spatial size, by bilinear
+/// interpolation.
+///
+/// Inputs are the same as `RoiPool`. Samples falling outside of the input
+/// take `extrapolation_value` if it is set, the value of the nearest border
+/// pixel otherwise.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct RoiResample {
+    pub output_size: TVec<usize>,
+    pub method: SamplingMethod,
+    #[educe(Hash(method = "hash_opt_f32"))]
+    pub extrapolation_value: Option<f32>,
+}
+
+impl_dyn_hash!(RoiResample);
+
+impl RoiResample {
+    /// Neighbours and weight of the second one.
+    fn interpolation(coord: f32, len: usize) -> (usize, usize, f32) {
+        let coord = coord.max(0.0).min((len - 1) as f32);
+        let low = coord.floor() as usize;
+        let high = (low + 1).min(len - 1);
+        (low, high, coord - low as f32)
+    }
+
+    fn eval_t<T: Datum + Float>(
+        &self,
+        input: &Tensor,
+        rois: &Tensor,
+        batch_index: &Tensor,
+    ) -> TractResult<Tensor>
+    where
+        f32: AsPrimitive<T>,
+    {
+        let input = input.to_array_view::<T>()?.into_dimensionality::<Ix4>()?;
+        let (n, c, h, w) = input.dim();
+        let regions = regions(rois, batch_index, n)?;
+        let (oh, ow) = (self.output_size[0], self.output_size[1]);
+        let mut output = Array4::<T>::zeros((regions.len(), c, oh, ow));
+        if h == 0 || w == 0 {
+            return Ok(output.into_tensor());
+        }
+        let outside = |coord: f32, len: usize| coord < 0.0 || coord > (len - 1) as f32;
+        for (r, region) in regions.iter().enumerate() {
+            let b = region[0] as usize;
+            for i in 0..oh {
+                let y = self.method.coord(region[1], region[3], i, oh);
+                let (y0, y1, fy) = Self::interpolation(y, h);
+                for j in 0..ow {
+                    let x = self.method.coord(region[2], region[4], j, ow);
+                    if let Some(value) = self.extrapolation_value {
+                        if outside(y, h) || outside(x, w) {
+                            output.slice_mut(s![r, .., i, j]).fill(value.as_());
+                            continue;
+                        }
+                    }
+                    let (x0, x1, fx) = Self::interpolation(x, w);
+                    let (fy, fx): (T, T) = (fy.as_(), fx.as_());
+                    let one = T::one();
+                    for ch in 0..c {
+                        let plane = input.slice(s![b, ch, .., ..]);
+                        output[(r, ch, i, j)] = (one - fy) * (one - fx) * plane[(y0, x0)]
+                            + (one - fy) * fx * plane[(y0, x1)]
+                            + fy * (one - fx) * plane[(y1, x0)]
+                            + fy * fx * plane[(y1, x1)];
+                    }
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for RoiResample {
+    fn name(&self) -> Cow<str> {
+        "RoiResample".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut info = vec![format!("{:?} to {:?}", self.method, self.output_size)];
+        if let Some(value) = self.extrapolation_value {
+            info.push(format!("extrapolation value: {}", value));
+        }
+        Ok(info)
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for RoiResample {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, rois, batch_index) = args_3!(inputs);
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(
+            self,
+            &input,
+            &rois,
+            &batch_index
+        ))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for RoiResample {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(roi_output_fact(&self.output_size, inputs)?))
+    }
+
+    as_op!();
+}
+
+/// Pooling of regions of interest by bilinear sampling, as introduced by
+/// Mask R-CNN.
+///
+/// Inputs are the same as `RoiPool`. Each bin is sampled on a regular grid of
+/// `sampling_ratio` points along each axis, or of `ceil(region size / output
+/// size)` points if it is not set, and the samples are averaged or maxed.
+/// With `aligned`, region coordinates are shifted by half a pixel so that
+/// pixel centers sit on integer coordinates, otherwise regions are at least
+/// one pixel large. Samples further than one pixel outside of the input are
+/// zeros.
+#[derive(Debug, Clone, new, Hash)]
+pub struct RoiAlign {
+    pub output_size: TVec<usize>,
+    pub sampling_ratio: Option<usize>,
+    pub mode: RoiPoolMode,
+    pub aligned: bool,
+}
+
+impl_dyn_hash!(RoiAlign);
+
+impl RoiAlign {
+    fn sample<T: Datum + Float>(plane: ArrayView2<T>, y: f32, x: f32) -> T
+    where
+        f32: AsPrimitive<T>,
+    {
+        let (h, w) = plane.dim();
+        if y < -1.0 || y > h as f32 || x < -1.0 || x > w as f32 {
+            return T::zero();
+        }
+        let neighbours = |coord: f32, len: usize| {
+            let coord = coord.max(0.0);
+            if coord >= (len - 1) as f32 {
+                (len - 1, len - 1, 0.0)
+            } else {
+                let low = coord.floor();
+                (low as usize, low as usize + 1, coord - low)
+            }
+        };
+        let (y0, y1, fy) = neighbours(y, h);
+        let (x0, x1, fx) = neighbours(x, w);
+        let (fy, fx): (T, T) = (fy.as_(), fx.as_());
+        let one = T::one();
+        (one - fy) * (one - fx) * plane[(y0, x0)]
+            + (one - fy) * fx * plane[(y0, x1)]
+            + fy * (one - fx) * plane[(y1, x0)]
+            + fy * fx * plane[(y1, x1)]
+    }
+
+    fn eval_t<T: Datum + Float>(
+        &self,
+        input: &Tensor,
+        rois: &Tensor,
+        batch_index: &Tensor,
+    ) -> TractResult<Tensor>
+    where
+        f32: AsPrimitive<T>,
+        usize: AsPrimitive<T>,
+    {
+        let input = input.to_array_view::<T>()?.into_dimensionality::<Ix4>()?;
+        let (n, c, h, w) = input.dim();
+        let regions = regions(rois, batch_index, n)?;
+        let (oh, ow) = (self.output_size[0], self.output_size[1]);
+        let mut output = Array4::<T>::zeros((regions.len(), c, oh, ow));
+        if h == 0 || w == 0 {
+            return Ok(output.into_tensor());
+        }
+        let offset = if self.aligned { 0.5 } else { 0.0 };
+        for (r, region) in regions.iter().enumerate() {
+            let b = region[0] as usize;
+            let (y, x) = (region[1] - offset, region[2] - offset);
+            let (mut rh, mut rw) = (region[3] - region[1], region[4] - region[2]);
+            if !self.aligned {
+                rh = rh.max(1.0);
+                rw = rw.max(1.0);
+            }
+            let (bin_h, bin_w) = (rh / oh as f32, rw / ow as f32);
+            let grid_h = self.sampling_ratio.unwrap_or_else(|| bin_h.ceil().max(0.0) as usize);
+            let grid_w = self.sampling_ratio.unwrap_or_else(|| bin_w.ceil().max(0.0) as usize);
+            if grid_h == 0 || grid_w == 0 {
+                continue;
+            }
+            for ch in 0..c {
+                let plane = input.slice(s![b, ch, .., ..]);
+                for i in 0..oh {
+                    for j in 0..ow {
+                        let samples = (0..grid_h).flat_map(|gy| {
+                            let sy =
+                                y + i as f32 * bin_h + (gy as f32 + 0.5) * bin_h / grid_h as f32;
+                            (0..grid_w).map(move |gx| {
+                                let sx = x
+                                    + j as f32 * bin_w
+                                    + (gx as f32 + 0.5) * bin_w / grid_w as f32;
+                                Self::sample(plane, sy, sx)
+                            })
+                        });
+                        output[(r, ch, i, j)] = match self.mode {
+                            RoiPoolMode::Avg => {
+                                samples.fold(T::zero(), |acc, x| acc + x) / (grid_h * grid_w).as_()
+                            }
+                            RoiPoolMode::Max => {
+                                samples.fold(T::neg_infinity(), |acc, x| acc.max(x))
+                            }
+                        };
+                    }
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for RoiAlign {
+    fn name(&self) -> Cow<str> {
+        "RoiAlign".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("{:?} to {:?}", self.mode, self.output_size),
+            format!("sampling ratio: {:?}, aligned: {}", self.sampling_ratio, self.aligned),
+        ])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for RoiAlign {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, rois, batch_index) = args_3!(inputs);
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(
+            self,
+            &input,
+            &rois,
+            &batch_index
+        ))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for RoiAlign {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(roi_output_fact(&self.output_size, inputs)?))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> Arc<Tensor> {
+        let data: Vec<f32> = (0..16).map(|x| x as f32).collect();
+        rctensor4(&[[[
+            [data[0], data[1], data[2], data[3]],
+            [data[4], data[5], data[6], data[7]],
+            [data[8], data[9], data[10], data[11]],
+            [data[12], data[13], data[14], data[15]],
+        ]]])
+    }
+
+    fn run(op: &dyn TypedOp, rois: Tensor) -> Tensor {
+        let batch_index = Tensor::zero::<i64>(&[rois.shape()[0]]).unwrap();
+        let inputs = tvec!(image(), rois.into_arc_tensor(), batch_index.into_arc_tensor());
+        let facts: TVec<TypedFact> = inputs.iter().map(|t| TypedFact::from(t.clone())).collect();
+        let output_fact = op.output_facts(&*facts.iter().collect::<TVec<_>>()).unwrap().remove(0);
+        let output = op.eval(inputs).unwrap().remove(0).into_tensor();
+        assert_eq!(output_fact.shape.as_concrete().unwrap(), output.shape());
+        output
+    }
+
+    #[test]
+    fn max_roi_pool() {
+        let op = RoiPool::new(tvec!(2, 2), RoiPoolMode::Max);
+        let output = run(&op, tensor2(&[[0f32, 0., 4., 4.], [1., 1., 3., 3.]]));
+        assert_eq!(output, tensor4(&[[[[5f32, 7.], [13., 15.]]], [[[5f32, 6.], [9., 10.]]]]));
+    }
+
+    #[test]
+    fn avg_roi_pool() {
+        let op = RoiPool::new(tvec!(1, 2), RoiPoolMode::Avg);
+        let output = run(&op, tensor2(&[[0f32, 0., 2., 4.]]));
+        assert_eq!(output, tensor4(&[[[[2.5f32, 4.5]]]]));
+    }
+
+    #[test]
+    fn resample_identity() {
+        let op = RoiResample::new(tvec!(4, 4), SamplingMethod::Symmetric, None);
+        let output = run(&op, tensor2(&[[0f32, 0., 4., 4.]]));
+        assert_eq!(output, image().into_tensor());
+    }
+
+    #[test]
+    fn resample_aligned() {
+        let op = RoiResample::new(tvec!(1, 3), SamplingMethod::Aligned, None);
+        let output = run(&op, tensor2(&[[1f32, 0., 1., 3.]]));
+        assert_eq!(output, tensor4(&[[[[4f32, 5.5, 7.]]]]));
+    }
+
+    #[test]
+    fn resample_extrapolation() {
+        let op = RoiResample::new(tvec!(1, 3), SamplingMethod::Aligned, Some(-1.0));
+        let output = run(&op, tensor2(&[[1f32, 2., 1., 4.]]));
+        assert_eq!(output, tensor4(&[[[[6f32, 7., -1.]]]]));
+    }
+
+    #[test]
+    fn avg_roi_align() {
+        let op = RoiAlign::new(tvec!(2, 2), Some(2), RoiPoolMode::Avg, true);
+        let output = run(&op, tensor2(&[[0f32, 0., 4., 4.]]));
+        assert_eq!(output, tensor4(&[[[[2.5f32, 4.5], [10.5, 12.5]]]]));
+    }
+
+    #[test]
+    fn max_roi_align() {
+        let op = RoiAlign::new(tvec!(1, 1), None, RoiPoolMode::Max, false);
+        let output = run(&op, tensor2(&[[1f32, 1., 3., 3.]]));
+        assert_eq!(output, tensor4(&[[[[12.5f32]]]]));
+    }
+
+    #[test]
+    fn roi_pool_bins_round_outwards() {
+        // rows 0.5..2.5 cover rows 0 to 2, and are split at 1.5
+        let op = RoiPool::new(tvec!(2, 1), RoiPoolMode::Max);
+        let output = run(&op, tensor2(&[[0.5f32, 0., 2.5, 1.]]));
+        assert_eq!(output, tensor4(&[[[[4f32], [8.]]]]));
+    }
+
+    #[test]
+    fn roi_pool_clamped_and_empty_bins() {
+        // the second bin (columns 3.5..5) is clamped to column 3
+        let op = RoiPool::new(tvec!(1, 2), RoiPoolMode::Avg);
+        let output = run(&op, tensor2(&[[0f32, 2., 1., 5.]]));
+        assert_eq!(output, tensor4(&[[[[2.5f32, 3.]]]]));
+        // entirely past the right border
+        let output = run(&op, tensor2(&[[0f32, 4., 1., 8.]]));
+        assert_eq!(output, tensor4(&[[[[0f32, 0.]]]]));
+    }
+
+    #[test]
+    fn roi_pool_batch_index() {
+        let op = RoiPool::new(tvec!(1, 1), RoiPoolMode::Max);
+        let image = image();
+        let second = image.to_array_view::<f32>().unwrap().mapv(|x| -x);
+        let input = Tensor::stack_tensors(0, &[image.into_tensor(), second.into_tensor()]).unwrap();
+        let inputs = tvec!(
+            input.into_arc_tensor(),
+            rctensor2(&[[0f32, 0., 2., 2.], [0., 0., 2., 2.]]),
+            rctensor1(&[1i64, 0])
+        );
+        let output = op.eval(inputs).unwrap().remove(0);
+        assert_eq!(*output, tensor4(&[[[[0f32]]], [[[5f32]]]]));
+    }
+
+    #[test]
+    fn batch_index_out_of_range() {
+        let op = RoiPool::new(tvec!(1, 1), RoiPoolMode::Max);
+        let inputs = tvec!(image(), rctensor2(&[[0f32, 0., 2., 2.]]), rctensor1(&[1i64]));
+        assert!(op.eval(inputs).is_err());
+    }
+
+    #[test]
+    fn resample_asymmetric() {
+        let op = RoiResample::new(tvec!(1, 2), SamplingMethod::Asymmetric, None);
+        let output = run(&op, tensor2(&[[1f32, 0., 2., 3.]]));
+        assert_eq!(output, tensor4(&[[[[4f32, 5.5]]]]));
+    }
+
+    #[test]
+    fn resample_clamps_to_border() {
+        // symmetric samples at x = -0.5 and x = 3.5 without extrapolation value
+        let op = RoiResample::new(tvec!(1, 2), SamplingMethod::Symmetric, None);
+        let output = run(&op, tensor2(&[[0f32, -1., 1., 5.]]));
+        assert_eq!(output, tensor4(&[[[[0f32, 3.]]]]));
+    }
+
+    #[test]
+    fn roi_align_unaligned_regions_are_at_least_one_pixel() {
+        // a degenerate region is sampled on a one pixel square from (1, 1)
+        let op = RoiAlign::new(tvec!(1, 1), Some(1), RoiPoolMode::Avg, false);
+        let output = run(&op, tensor2(&[[1f32, 1., 1., 1.]]));
+        assert_eq!(output, tensor4(&[[[[7.5f32]]]]));
+    }
+
+    #[test]
+    fn roi_align_far_outside_is_zero() {
+        let op = RoiAlign::new(tvec!(1, 1), Some(1), RoiPoolMode::Max, true);
+        let output = run(&op, tensor2(&[[10f32, 10., 12., 12.]]));
+        assert_eq!(output, tensor4(&[[[[0f32]]]]));
+    }
+
+    #[test]
+    fn f64_input() {
+        let op = RoiPool::new(tvec!(1, 1), RoiPoolMode::Avg);
+        let input = image().cast_to::<f64>().unwrap().into_owned().into_arc_tensor();
+        let inputs = tvec!(input, rctensor2(&[[0f32, 0., 2., 2.]]), rctensor1(&[0i64]));
+        let output = op.eval(inputs).unwrap().remove(0);
+        assert_eq!(*output, tensor4(&[[[[2.5f64]]]]));
+    }
+
+    #[test]
+    fn output_facts_checks() {
+        let op = RoiPool::new(tvec!(2, 2), RoiPoolMode::Max);
+        let image = TypedFact::dt_shape(f32::datum_type(), &[1, 1, 4, 4]);
+        let rois = TypedFact::dt_shape(f32::datum_type(), &[3, 4]);
+        let batch_index = TypedFact::dt_shape(i64::datum_type(), &[3]);
+        let fact = op.output_facts(&[&image, &rois, &batch_index]).unwrap().remove(0);
+        assert_eq!(fact, TypedFact::dt_shape(f32::datum_type(), &[3, 1, 2, 2]));
+        let bad_rois = TypedFact::dt_shape(f32::datum_type(), &[3, 5]);
+        assert!(op.output_facts(&[&image, &bad_rois, &batch_index]).is_err());
+        let bad_index = TypedFact::dt_shape(i64::datum_type(), &[2]);
+        assert!(op.output_facts(&[&image, &rois, &bad_index]).is_err());
+        let flat = TypedFact::dt_shape(f32::datum_type(), &[1, 16]);
+        assert!(op.output_facts(&[&flat, &rois, &batch_index]).is_err());
+        let op_3d = RoiPool::new(tvec!(2, 2, 2), RoiPoolMode::Max);
+        assert!(op_3d.output_facts(&[&image, &rois, &batch_index]).is_err());
+    }
+}
@@ -133,10 +133,8 @@ impl SumPoolFixed {
                             .map(|v| *input_ptr.offset(v + input_offset as isize))
                             .sum::<T>();
 
-                        if let Some(div) = div {
-                            *values_ptr.offset(output_offset as isize + visitor.output_offset) =
-                                sum * div;
-                        }
+                        *values_ptr.offset(output_offset as isize + visitor.output_offset) =
+                            if let Some(div) = div { sum * div } else { sum };
                     }
                 }
             });
@@ -175,3 +173,23 @@ impl TypedOp for SumPoolFixed {
 
     as_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::PaddingSpec;
+    use crate::ops::nn::DataFormat;
+
+    #[test]
+    fn avg_pool_3d() -> TractResult<()> {
+        let input =
+            tensor1(&(0..27).map(|x| x as f32).collect::<Vec<_>>()).into_shape(&[1, 1, 3, 3, 3])?;
+        let spec =
+            PoolSpec::new(DataFormat::NCHW, tvec!(2, 2, 2), PaddingSpec::Valid, None, None, None);
+        let output = SumPool::new(spec, false, true).eval(tvec!(input.into_arc_tensor()))?;
+        let expected = tensor1(&[6.5f32, 7.5, 9.5, 10.5, 15.5, 16.5, 18.5, 19.5])
+            .into_shape(&[1, 1, 2, 2, 2])?;
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+}
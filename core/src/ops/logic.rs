@@ -5,6 +5,9 @@ use crate::internal::*;
 
 use super::binary::commute;
 
+mod if_then_else;
+pub use if_then_else::If;
+
 bin_to_super_type!(and, And, flip: commute,
                    [bool, u8, u16, u32, u64, i8, i16, i32, i64] => |c, &a, &b| *c = (a as i64 != 0 && b as i64 != 0) as _);
 bin_to_super_type!(or, Or, flip: commute,
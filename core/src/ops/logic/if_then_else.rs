@@ -0,0 +1,217 @@
+use crate::internal::*;
+
+/// Conditional execution of one of two sub-models.
+///
+/// Input 0 is the condition (a scalar, non-zero meaning true), the other
+/// inputs are made available to the branches through their input mappings:
+/// `then_input_mapping[ix]` is the outer input slot feeding the `ix`-th input
+/// of `then_body`. Both branches must produce the same number of outputs,
+/// with the same types and shapes. Their plans are built once, with the op.
+#[derive(Debug, Clone, Hash)]
+pub struct If {
+    then_plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub then_input_mapping: Vec<usize>,
+    else_plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub else_input_mapping: Vec<usize>,
+}
+
+impl_dyn_hash!(If);
+
+impl If {
+    pub fn new(
+        then_body: TypedModel,
+        then_input_mapping: Vec<usize>,
+        else_body: TypedModel,
+        else_input_mapping: Vec<usize>,
+    ) -> TractResult<If> {
+        Ok(If {
+            then_plan: Arc::new(SimplePlan::new(then_body)?),
+            then_input_mapping,
+            else_plan: Arc::new(SimplePlan::new(else_body)?),
+            else_input_mapping,
+        })
+    }
+
+    pub fn then_body(&self) -> &TypedModel {
+        self.then_plan.model()
+    }
+
+    pub fn else_body(&self) -> &TypedModel {
+        self.else_plan.model()
+    }
+
+    fn branch(&self, cond: bool) -> (&TypedSimplePlan<TypedModel>, &[usize]) {
+        if cond {
+            (&self.then_plan, &self.then_input_mapping)
+        } else {
+            (&self.else_plan, &self.else_input_mapping)
+        }
+    }
+}
+
+impl Op for If {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("then inputs: {:?}", self.then_input_mapping),
+            format!("else inputs: {:?}", self.else_input_mapping),
+        ])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for If {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let cond = inputs[0].cast_to_scalar::<bool>()?;
+        let (plan, mapping) = self.branch(cond);
+        let inputs: TVec<Tensor> =
+            mapping.iter().map(|slot| inputs[*slot].clone().into_tensor()).collect();
+        plan.run(inputs)
+    }
+}
+
+impl TypedOp for If {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].rank() != 0 {
+            bail!("If condition must be a scalar, got {:?}", inputs[0]);
+        }
+        let then_outputs = self.then_body().output_outlets()?.len();
+        let else_outputs = self.else_body().output_outlets()?.len();
+        if then_outputs != else_outputs {
+            bail!("If branches have {} and {} outputs", then_outputs, else_outputs);
+        }
+        (0..then_outputs)
+            .map(|ix| {
+                let then_fact = self.then_body().output_fact(ix)?;
+                let else_fact = self.else_body().output_fact(ix)?;
+                if then_fact.datum_type != else_fact.datum_type {
+                    bail!(
+                        "If branches output #{} datum types differ: {:?} and {:?}",
+                        ix,
+                        then_fact.datum_type,
+                        else_fact.datum_type
+                    );
+                }
+                if then_fact.shape != else_fact.shape {
+                    bail!(
+                        "If branches output #{} shapes differ: {:?} and {:?}",
+                        ix,
+                        then_fact.shape,
+                        else_fact.shape
+                    );
+                }
+                Ok(TypedFact::dt_shape(then_fact.datum_type, then_fact.shape.clone()))
+            })
+            .collect()
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let cond = if let Some(cond) = &model.outlet_fact(node.inputs[0])?.konst {
+            cond.cast_to_scalar::<bool>()?
+        } else {
+            return Ok(None);
+        };
+        let (plan, mapping) = self.branch(cond);
+        let body = plan.model();
+        let mut patch = TypedModelPatch::new(format!("Fold {} on constant condition", node.name));
+        let mut wires: HashMap<OutletId, OutletId> = HashMap::new();
+        for (ix, input) in body.input_outlets()?.iter().enumerate() {
+            wires.insert(*input, patch.tap_model(model, node.inputs[mapping[ix]])?);
+        }
+        for id in body.eval_order()? {
+            let inner = body.node(id);
+            if body.input_outlets()?.iter().any(|i| i.node == id) {
+                continue;
+            }
+            let inputs = inner.inputs.iter().map(|i| wires[i]).collect::<TVec<_>>();
+            let outputs = patch.wire_node(
+                format!("{}.{}", node.name, inner.name),
+                inner.op.clone(),
+                &inputs,
+            )?;
+            for (slot, o) in outputs.into_iter().enumerate() {
+                wires.insert(OutletId::new(id, slot), o);
+            }
+        }
+        for (ix, output) in body.output_outlets()?.iter().enumerate() {
+            // the branch may know more about its output (shape, value) than
+            // the If node, which has to accommodate both branches
+            unsafe { patch.shunt_outside_unchecked(OutletId::new(node.id, ix), wires[output])? };
+        }
+        Ok(Some(patch))
+    }
+
+    fn nested_model_multipliers(&self, _inputs: &[&TypedFact]) -> Vec<(Cow<str>, f64)> {
+        vec![("then".into(), 1.0), ("else".into(), 1.0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::math;
+
+    fn branch(op: Box<dyn TypedOp>) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let a = model.add_source("a", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let b = model.add_source("b", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let sum = model.wire_node("op", op, &[a, b])?;
+        model.set_output_outlets(&sum)?;
+        Ok(model)
+    }
+
+    fn if_model(cond: Option<bool>) -> TractResult<TypedModel> {
+        let op = If::new(
+            branch(Box::new(math::add::bin_typed()))?,
+            vec![1, 2],
+            branch(Box::new(math::mul::bin_typed()))?,
+            vec![1, 2],
+        )?;
+        let mut model = TypedModel::default();
+        let c = if let Some(c) = cond {
+            model.add_const("cond", tensor0(c))?
+        } else {
+            model.add_source("cond", TypedFact::dt_shape(bool::datum_type(), &[0usize; 0]))?
+        };
+        let a = model.add_source("a", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let b = model.add_source("b", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+        let wire = model.wire_node("if", op, &[c, a, b])?;
+        model.set_output_outlets(&wire)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn eval_both_branches() -> TractResult<()> {
+        let model = if_model(None)?;
+        let plan = SimplePlan::new(&model)?;
+        let args = |c: bool| tvec!(tensor0(c), tensor1(&[2f32, 3.]), tensor1(&[4f32, 5.]));
+        assert_eq!(*plan.run(args(true))?[0], tensor1(&[6f32, 8.]));
+        assert_eq!(*plan.run(args(false))?[0], tensor1(&[8f32, 15.]));
+        Ok(())
+    }
+
+    #[test]
+    fn fold_constant_condition() -> TractResult<()> {
+        let model = if_model(Some(false))?.declutter()?;
+        assert!(model.nodes().iter().all(|n| n.op_as::<If>().is_none()));
+        let result =
+            SimplePlan::new(&model)?.run(tvec!(tensor1(&[2f32, 3.]), tensor1(&[4f32, 5.])))?;
+        assert_eq!(*result[0], tensor1(&[8f32, 15.]));
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 mod data_formats;
+mod non_max_suppression;
 mod reduce;
 
 pub use self::data_formats::{BaseDataShape, DataFormat, DataShape};
+pub use self::non_max_suppression::{non_max_suppression, BoxRepr, NonMaxSuppression};
 pub use self::reduce::{Reduce, Reducer};
 
 pub use crate::internal::*;
@@ -0,0 +1,205 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// How boxes are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoxRepr {
+    /// `(y1, x1, y2, x2)`, any pair of diagonal corners.
+    TwoPoints,
+    /// `(x_center, y_center, width, height)`.
+    CenterWidthHeight,
+}
+
+impl BoxRepr {
+    /// `(y_min, x_min, y_max, x_max)` of a box.
+    fn corners(&self, b: ArrayView1<f32>) -> [f32; 4] {
+        match self {
+            BoxRepr::TwoPoints => [b[0].min(b[2]), b[1].min(b[3]), b[0].max(b[2]), b[1].max(b[3])],
+            BoxRepr::CenterWidthHeight => {
+                let (half_w, half_h) = (b[2] / 2.0, b[3] / 2.0);
+                [b[1] - half_h, b[0] - half_w, b[1] + half_h, b[0] + half_w]
+            }
+        }
+    }
+}
+
+fn iou(a: &[f32; 4], b: &[f32; 4]) -> f32 {
+    let area = |c: &[f32; 4]| (c[2] - c[0]) * (c[3] - c[1]);
+    let inter_h = (a[2].min(b[2]) - a[0].max(b[0])).max(0.0);
+    let inter_w = (a[3].min(b[3]) - a[1].max(b[1])).max(0.0);
+    let inter = inter_h * inter_w;
+    let union = area(a) + area(b) - inter;
+    if union <= 0.0 {
+        0.0
+    } else {
+        inter / union
+    }
+}
+
+/// Greedy non maximum suppression over one set of boxes.
+///
+/// Boxes are visited by decreasing score (lowest index first on ties), and
+/// kept if their score is above `score_threshold` and their intersection over
+/// union with every box kept so far is not above `iou_threshold`. Returns the
+/// indices of at most `max_output` kept boxes, in selection order.
+pub fn non_max_suppression(
+    boxes: ArrayView2<f32>,
+    scores: ArrayView1<f32>,
+    repr: BoxRepr,
+    max_output: usize,
+    iou_threshold: f32,
+    score_threshold: f32,
+) -> Vec<usize> {
+    let mut candidates: Vec<usize> =
+        (0..scores.len()).filter(|&ix| scores[ix] > score_threshold).collect();
+    candidates
+        .sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap_or(std::cmp::Ordering::Equal));
+    let mut selected: Vec<(usize, [f32; 4])> = vec![];
+    for ix in candidates {
+        if selected.len() >= max_output {
+            break;
+        }
+        let corners = repr.corners(boxes.row(ix));
+        if selected.iter().all(|(_, other)| iou(&corners, other) <= iou_threshold) {
+            selected.push((ix, corners));
+        }
+    }
+    selected.into_iter().map(|(ix, _)| ix).collect()
+}
+
+/// Non maximum suppression, performed independently for each batch and class.
+///
+/// Inputs are the `[batch, boxes, 4]` boxes, the `[batch, classes, boxes]`
+/// scores, and three scalars: the maximum number of boxes to select per batch
+/// and class, the intersection over union threshold and the score threshold.
+/// The output is a `[num_selected, 3]` tensor of `(batch, class, box)`
+/// indices. As its size depends on the input values, it is expressed by the
+/// `num_selected` symbol.
+#[derive(Debug, Clone, new, Hash)]
+pub struct NonMaxSuppression {
+    pub box_repr: BoxRepr,
+    pub num_selected: Symbol,
+}
+
+impl_dyn_hash!(NonMaxSuppression);
+
+impl Op for NonMaxSuppression {
+    fn name(&self) -> Cow<str> {
+        "NonMaxSuppression".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?}, output size: {}", self.box_repr, self.num_selected)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for NonMaxSuppression {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (boxes, scores, max_output, iou_threshold, score_threshold) = args_5!(inputs);
+        let boxes = boxes.cast_to::<f32>()?;
+        let boxes = boxes.to_array_view::<f32>()?.into_dimensionality::<Ix3>()?;
+        let scores = scores.cast_to::<f32>()?;
+        let scores = scores.to_array_view::<f32>()?.into_dimensionality::<Ix3>()?;
+        let max_output = max_output.cast_to_scalar::<i64>()?.max(0) as usize;
+        let iou_threshold = iou_threshold.cast_to_scalar::<f32>()?;
+        let score_threshold = score_threshold.cast_to_scalar::<f32>()?;
+        if boxes.shape()[0] != scores.shape()[0] || boxes.shape()[1] != scores.shape()[2] {
+            bail!("Inconsistent boxes {:?} and scores {:?} shapes", boxes.shape(), scores.shape());
+        }
+        let mut selected: Vec<i64> = vec![];
+        for b in 0..scores.shape()[0] {
+            for c in 0..scores.shape()[1] {
+                let kept = non_max_suppression(
+                    boxes.index_axis(Axis(0), b),
+                    scores.slice(s![b, c, ..]),
+                    self.box_repr,
+                    max_output,
+                    iou_threshold,
+                    score_threshold,
+                );
+                for ix in kept {
+                    selected.extend_from_slice(&[b as i64, c as i64, ix as i64]);
+                }
+            }
+        }
+        let output = tensor1(&selected).into_shape(&[selected.len() / 3, 3])?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for NonMaxSuppression {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].rank() != 3 || inputs[0].shape[2] != 4.to_dim() {
+            bail!("Expected boxes as a [batch, boxes, 4] tensor, got {:?}", inputs[0]);
+        }
+        if inputs[1].rank() != 3 {
+            bail!("Expected scores as a [batch, classes, boxes] tensor, got {:?}", inputs[1]);
+        }
+        for input in &inputs[2..] {
+            if input.rank() != 0 {
+                bail!("Expected a scalar, got {:?}", input);
+            }
+        }
+        Ok(tvec!(TypedFact::dt_shape(i64::datum_type(), &[self.num_selected.to_dim(), 3.to_dim()])))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boxes() -> Tensor {
+        tensor3(&[[
+            [0f32, 0., 1., 1.],
+            [0., 0.1, 1., 1.1],
+            [0., -0.1, 1., 0.9],
+            [0., 10., 1., 11.],
+            [0., 10.1, 1., 11.1],
+            [0., 100., 1., 101.],
+        ]])
+    }
+
+    fn run(op: &NonMaxSuppression, boxes: Tensor, inputs: TVec<Tensor>) -> Tensor {
+        let mut inputs: TVec<Arc<Tensor>> =
+            inputs.into_iter().map(|t| t.into_arc_tensor()).collect();
+        inputs.insert(0, boxes.into_arc_tensor());
+        op.eval(inputs).unwrap().remove(0).into_tensor()
+    }
+
+    #[test]
+    fn suppress_overlapping() {
+        let op = NonMaxSuppression::new(BoxRepr::TwoPoints, Symbol::new("n"));
+        let scores = tensor3(&[[[0.9f32, 0.75, 0.6, 0.95, 0.5, 0.3]]]);
+        let inputs = tvec!(scores, tensor0(3i64), tensor0(0.5f32), tensor0(0f32));
+        let output = run(&op, boxes(), inputs);
+        assert_eq!(output, tensor2(&[[0i64, 0, 3], [0, 0, 0], [0, 0, 5]]));
+    }
+
+    #[test]
+    fn score_threshold_and_max_output() {
+        let op = NonMaxSuppression::new(BoxRepr::TwoPoints, Symbol::new("n"));
+        let scores = tensor3(&[[[0.9f32, 0.75, 0.6, 0.95, 0.5, 0.3]]]);
+        let inputs = tvec!(scores.clone(), tensor0(2i64), tensor0(0.5f32), tensor0(0f32));
+        assert_eq!(run(&op, boxes(), inputs), tensor2(&[[0i64, 0, 3], [0, 0, 0]]));
+        let inputs = tvec!(scores, tensor0(3i64), tensor0(0.5f32), tensor0(0.92f32));
+        assert_eq!(run(&op, boxes(), inputs), tensor2(&[[0i64, 0, 3]]));
+    }
+
+    #[test]
+    fn center_width_height() {
+        let op = NonMaxSuppression::new(BoxRepr::CenterWidthHeight, Symbol::new("n"));
+        let boxes = tensor3(&[[[0.5f32, 0.5, 1., 1.], [0.5, 0.6, 1., 1.], [0.5, 10.5, 1., 1.]]]);
+        let scores = tensor3(&[[[0.9f32, 0.95, 0.5]]]);
+        let inputs = tvec!(scores, tensor0(3i64), tensor0(0.5f32), tensor0(0f32));
+        assert_eq!(run(&op, boxes, inputs), tensor2(&[[0i64, 0, 1], [0, 0, 2]]));
+    }
+}
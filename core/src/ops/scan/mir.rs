@@ -216,6 +216,12 @@ impl Scan {
         for (inner_input_id, input) in self.body.input_outlets()?.iter().enumerate() {
             let source_node = self.body.node(input.node);
             if source_node.outputs[0].successors.len() == 0 {
+                // the iteration count comes from the scanned inputs
+                if self.input_mapping[inner_input_id].as_scan().is_some()
+                    && self.input_mapping.iter().filter(|m| m.as_scan().is_some()).count() == 1
+                {
+                    continue;
+                }
                 let mut new_inputs = node.inputs.clone();
                 let slot = match &self.input_mapping[inner_input_id] {
                     InputMapping::Full { slot } => Some(slot),
@@ -3,6 +3,7 @@ use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
 
 use crate::internal::*;
+use crate::memory::{ArenaState, MemoryPlan};
 use crate::model::order::eval_order_for_nodes;
 use crate::model::{Fact, Graph, OutletId};
 
@@ -13,6 +14,14 @@ pub struct SessionState {
     pub tensors: HashMap<String, Tensor>,
 }
 
+/// Options of a plan.
+#[derive(Clone, Debug, Default)]
+pub struct PlanOptions {
+    /// Serve the values with a concrete shape from a single arena, laid out
+    /// by the memory planner (see `crate::memory`).
+    pub memory_arena: bool,
+}
+
 #[derive(Debug, Clone, Educe)]
 #[educe(Hash)]
 pub struct SimplePlan<F, O, M>
@@ -25,6 +34,14 @@ where
     pub outputs: Vec<OutletId>,
     pub order: Vec<usize>,
     pub flush_lists: Vec<TVec<usize>>,
+    /// For each node, the `(slot, axis, symbol)` output dimensions that are
+    /// symbols the node introduces (they appear in none of its inputs).
+    /// Their values depend on the input values, and are resolved in the
+    /// session state as the node is evaluated.
+    pub output_symbols: Vec<TVec<(usize, usize, Symbol)>>,
+    /// Arena layout, when the plan was built with `PlanOptions::memory_arena`.
+    #[educe(Hash(ignore))]
+    pub memory: Option<MemoryPlan>,
     _casper: PhantomData<(F, O)>,
 }
 
@@ -40,6 +57,16 @@ where
         Self::new_for_outputs(model, &outputs)
     }
 
+    /// This contructor returns a plan that will compute all the model default
+    /// outputs in one pass, with options.
+    pub fn new_with_options(model: M, options: &PlanOptions) -> TractResult<SimplePlan<F, O, M>> {
+        let mut plan = Self::new(model)?;
+        if options.memory_arena {
+            plan.memory = Some(plan.memory_plan()?);
+        }
+        Ok(plan)
+    }
+
     /// This contructor returns a plan that will compute the specified output.
     pub fn new_for_output(model: M, output: OutletId) -> TractResult<SimplePlan<F, O, M>> {
         Self::new_for_outputs_and_deps(model, &[output], &[])
@@ -73,15 +100,75 @@ where
                 flush_lists[flush_at].push(node)
             }
         }
+        let output_symbols = model
+            .borrow()
+            .nodes()
+            .iter()
+            .map(|node| Self::node_output_symbols(model.borrow(), node))
+            .collect();
         Ok(SimplePlan {
             model,
             order,
             flush_lists,
             outputs: outputs.to_vec(),
+            output_symbols,
+            memory: None,
             _casper: PhantomData,
         })
     }
 
+    fn node_output_symbols(model: &Graph<F, O>, node: &Node<F, O>) -> TVec<(usize, usize, Symbol)> {
+        let mut known = std::collections::HashSet::new();
+        for input in &node.inputs {
+            if let Ok(fact) = model.outlet_fact(*input).and_then(|f| f.to_typed_fact()) {
+                fact.shape.iter().for_each(|d| known.extend(d.symbols()));
+            }
+        }
+        let mut symbols = tvec!();
+        for (slot, output) in node.outputs.iter().enumerate() {
+            if let Ok(fact) = output.fact.to_typed_fact() {
+                for (axis, dim) in fact.shape.iter().enumerate() {
+                    if let TDim::Sym(s) = dim {
+                        if !known.contains(&s) {
+                            symbols.push((slot, axis, s));
+                        }
+                    }
+                }
+            }
+        }
+        symbols
+    }
+
+    /// Record the values of the symbols introduced by a node from its
+    /// outputs. Fails if a symbol already has a different value in this
+    /// evaluation.
+    pub fn resolve_output_symbols(
+        &self,
+        session_state: &mut SessionState,
+        node: usize,
+        outputs: &[Arc<Tensor>],
+    ) -> TractResult<()> {
+        for &(slot, axis, symbol) in &self.output_symbols[node] {
+            let value = outputs.get(slot).and_then(|o| o.shape().get(axis)).ok_or_else(|| {
+                format_err!("Missing output {} axis {} of node {}", slot, axis, node)
+            })?;
+            let value = *value as i64;
+            match session_state.resolved_symbols[symbol] {
+                Some(resolved) if resolved != value => bail!(
+                    "Output {} axis {} of node {} resolves symbol {} to {}, but it is already {}",
+                    slot,
+                    axis,
+                    node,
+                    symbol,
+                    value,
+                    resolved
+                ),
+                _ => session_state.resolved_symbols[symbol] = Some(value),
+            }
+        }
+        Ok(())
+    }
+
     pub fn run(&self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
         let mut state = SimpleState::new(self)?;
         state.run(inputs)
@@ -90,6 +177,15 @@ where
     pub fn model(&self) -> &Graph<F, O> {
         self.model.borrow()
     }
+
+    /// Memory plan for this plan evaluation order.
+    pub fn memory_plan(&self) -> TractResult<MemoryPlan> {
+        if let Some(memory) = &self.memory {
+            Ok(memory.clone())
+        } else {
+            MemoryPlan::new(self.model(), &self.order, &self.outputs)
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -104,6 +200,7 @@ where
     pub states: Vec<Option<Box<dyn OpState>>>,
     pub session_state: SessionState,
     pub values: Vec<Option<TVec<Arc<Tensor>>>>,
+    pub arena: ArenaState,
     _phantom: PhantomData<(M, F, O)>,
 }
 
@@ -123,7 +220,14 @@ where
             .iter()
             .map(|n: &Node<F, O>| n.op().state(&mut session, n.id))
             .collect::<TractResult<_>>()?;
-        Ok(SimpleState { plan, states, session_state: session, values, _phantom: PhantomData })
+        Ok(SimpleState {
+            plan,
+            states,
+            session_state: session,
+            values,
+            arena: ArenaState::default(),
+            _phantom: PhantomData,
+        })
     }
 
     /// Reset wires state.
@@ -150,6 +254,24 @@ where
     }
 
     pub fn run_plan_with_eval<Eval, E>(
+        &mut self,
+        inputs: TVec<Tensor>,
+        eval: Eval,
+    ) -> TractResult<TVec<Arc<Tensor>>>
+    where
+        Eval: for<'a, 'b, 'c> FnMut(
+            &'a mut SessionState,
+            Option<&'b mut (dyn OpState + 'static)>,
+            &'c Node<F, O>,
+            TVec<Arc<Tensor>>,
+        ) -> Result<TVec<Arc<Tensor>>, E>,
+        E: Into<anyhow::Error> + Send + Sync + 'static,
+    {
+        // the offers of an enclosing plan are for the node running this one
+        without_storage_offers(|| self.run_steps(inputs, eval))
+    }
+
+    fn run_steps<Eval, E>(
         &mut self,
         inputs: TVec<Tensor>,
         mut eval: Eval,
@@ -166,11 +288,16 @@ where
         let mut result = tvec!();
         {
             self.set_inputs(inputs)?;
+            self.model()
+                .symbol_table
+                .check(&self.session_state.resolved_symbols)
+                .context("Checking symbol assertions")?;
             let &mut SimpleState {
                 ref plan,
                 ref mut session_state,
                 ref mut states,
                 ref mut values,
+                ref mut arena,
                 ..
             } = self;
             let plan = plan.borrow();
@@ -204,7 +331,7 @@ where
                         );
                     }
                     for (ix, (v, f)) in inputs.iter().zip(facts.iter()).enumerate() {
-                        if !f.matches(v)? {
+                        if !f.matches_with_symbols(v, &session_state.resolved_symbols)? {
                             bail!(
                                 "Evaluating {}: input {:?}, expected {:?}, got {:?}",
                                 node,
@@ -216,9 +343,17 @@ where
                     }
                 }
 
-                let vs =
-                    eval(session_state, states[node.id].as_mut().map(|s| &mut **s), node, inputs)
-                        .map_err(|e| e.into())?;
+                let state = states[node.id].as_mut().map(|s| &mut **s);
+                let vs = if let Some(memory) = &plan.memory {
+                    let offers = arena.offers(memory, model, node.id, &plan.outputs)?;
+                    unsafe {
+                        with_storage_offers(offers, || eval(session_state, state, node, inputs))
+                    }
+                } else {
+                    eval(session_state, state, node, inputs)
+                }
+                .map_err(|e| e.into())?;
+                plan.resolve_output_symbols(session_state, node.id, &vs)?;
 
                 if cfg!(debug_assertions) {
                     let facts = model.node_output_facts(node.id)?;
@@ -234,7 +369,7 @@ where
                         if node.outputs[ix].successors.len() == 0 {
                             continue;
                         }
-                        if !f.matches(v)? {
+                        if !f.matches_with_symbols(v, &session_state.resolved_symbols)? {
                             bail!(
                                 "Evaluating {}: output {:?}, expected {:?}, got {:?}",
                                 node,
@@ -257,7 +392,14 @@ where
         Ok(result)
     }
 
+    /// Set all the inputs, starting a new evaluation: the symbols resolved
+    /// from the outputs of a previous evaluation are forgotten.
     pub fn set_inputs(&mut self, inputs: TVec<Tensor>) -> TractResult<()> {
+        for symbols in &self.plan.borrow().output_symbols {
+            for &(_, _, symbol) in symbols {
+                self.session_state.resolved_symbols[symbol] = None;
+            }
+        }
         for (ix, t) in inputs.into_iter().enumerate() {
             self.set_input(ix, t)?
         }
@@ -270,12 +412,13 @@ where
             .input_outlets()?
             .get(input)
             .ok_or_else(|| format_err!("Invalid input id for model ({}).", input))?;
-        self.plan
-            .borrow()
-            .model()
-            .outlet_fact(outlet)?
-            .matches(&t)
-            .with_context(|| format!("Setting input {}", input))?;
+        let fact = self.plan.borrow().model().outlet_fact(outlet)?.to_typed_fact()?;
+        fact.matches(&t).with_context(|| format!("Setting input {}", input))?;
+        for (dim, value) in fact.shape.iter().zip(t.shape().iter()) {
+            if let TDim::Sym(s) = dim {
+                self.session_state.resolved_symbols[s] = Some(*value as i64);
+            }
+        }
         self.session_state.inputs.insert(outlet.node, t.into());
         Ok(())
     }
@@ -339,6 +482,7 @@ where
             None => node.op().eval(inputs),
         }
         .with_context(|| format!("Evaluating {}", node))?;
+        plan.resolve_output_symbols(session_state, node.id, &vs)?;
         values[node.id] = Some(vs);
         Ok(())
     }
@@ -369,6 +513,8 @@ where
             }
             .with_context(|| format!("Evaluating {:?}", node))?
         };
+        let Self { ref mut session_state, ref plan, .. } = self;
+        plan.borrow().resolve_output_symbols(session_state, node, &values)?;
         self.values[node] = Some(values);
         Ok(&*self.values[node].as_ref().unwrap())
     }
@@ -0,0 +1,396 @@
+use std::fmt;
+
+use super::sym::{Symbol, SymbolTable, SymbolValues};
+use super::tree::TDim;
+use super::tree::TDim::*;
+use anyhow::{bail, Context};
+use itertools::Itertools;
+
+/// A fact about symbols, holding for all the values they may take.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Assertion {
+    Eq(TDim, TDim),
+    GTE(TDim, TDim),
+    LTE(TDim, TDim),
+    MultipleOf(TDim, u64),
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Assertion::Eq(a, b) => write!(fmt, "{}=={}", a, b),
+            Assertion::GTE(a, b) => write!(fmt, "{}>={}", a, b),
+            Assertion::LTE(a, b) => write!(fmt, "{}<={}", a, b),
+            Assertion::MultipleOf(a, q) => write!(fmt, "({})%{}==0", a, q),
+        }
+    }
+}
+
+impl Assertion {
+    /// Check the assertion against symbol values. Returns None if some
+    /// symbols are not resolved.
+    pub fn check(&self, values: &SymbolValues) -> Option<bool> {
+        let eval = |d: &TDim| d.eval(values).to_i64().ok();
+        match self {
+            Assertion::Eq(a, b) => Some(eval(a)? == eval(b)?),
+            Assertion::GTE(a, b) => Some(eval(a)? >= eval(b)?),
+            Assertion::LTE(a, b) => Some(eval(a)? <= eval(b)?),
+            Assertion::MultipleOf(a, q) => Some(eval(a)?.rem_euclid(*q as i64) == 0),
+        }
+    }
+
+    /// Expressions asserted to be positive or zero.
+    fn positives(&self) -> Vec<TDim> {
+        match self {
+            Assertion::Eq(a, b) => vec![(a.clone() - b).reduce(), (b.clone() - a).reduce()],
+            Assertion::GTE(a, b) => vec![(a.clone() - b).reduce()],
+            Assertion::LTE(a, b) => vec![(b.clone() - a).reduce()],
+            Assertion::MultipleOf(..) => vec![],
+        }
+    }
+
+    pub fn symbols(&self) -> std::collections::HashSet<Symbol> {
+        match self {
+            Assertion::Eq(a, b) | Assertion::GTE(a, b) | Assertion::LTE(a, b) => {
+                a.symbols().union(&b.symbols()).cloned().collect()
+            }
+            Assertion::MultipleOf(a, _) => a.symbols(),
+        }
+    }
+}
+
+/// Match `k*s+c`.
+fn as_linear(dim: &TDim) -> Option<(Symbol, i64, i64)> {
+    match dim {
+        Sym(s) => Some((*s, 1, 0)),
+        MulInt(k, a) => match &**a {
+            Sym(s) => Some((*s, *k, 0)),
+            _ => None,
+        },
+        Add(terms) if terms.len() == 2 => {
+            let (var, cst) = if let Val(c) = terms[0] { (&terms[1], c) } else { (&terms[0], 0) };
+            let cst = if let Val(c) = terms[1] { cst + c } else { cst };
+            let (s, k, c) = as_linear(var)?;
+            if terms.iter().filter(|t| if let Val(_) = t { true } else { false }).count() == 1 {
+                Some((s, k, c + cst))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+type Bounds = (Option<i64>, Option<i64>);
+
+fn add_bounds(a: Bounds, b: Bounds) -> Bounds {
+    (a.0.and_then(|x| Some(x + b.0?)), a.1.and_then(|x| Some(x + b.1?)))
+}
+
+impl SymbolTable {
+    /// Parse an assertion like `S>=1`, `B<=32`, `T%4==0` or `S==2*T`,
+    /// registering its symbols in the table.
+    pub fn parse_assertion(&mut self, input: &str) -> anyhow::Result<Assertion> {
+        let pos = input
+            .find(|c| c == '<' || c == '>' || c == '=')
+            .with_context(|| format!("Expected a comparison in assertion {:?}", input))?;
+        let (left, right) = input.split_at(pos);
+        let (op, right) =
+            if right.starts_with("==") || right.starts_with(">=") || right.starts_with("<=") {
+                right.split_at(2)
+            } else {
+                right.split_at(1)
+            };
+        let left = self.parse_tdim(left)?;
+        let right = self.parse_tdim(right)?;
+        let assertion = match op {
+            "==" | "=" => match (&left, &right) {
+                (Mod(a, q), Val(0)) => Assertion::MultipleOf((**a).clone(), *q),
+                _ => Assertion::Eq(left, right),
+            },
+            ">=" => Assertion::GTE(left, right),
+            "<=" => Assertion::LTE(left, right),
+            ">" => Assertion::GTE(left, right + 1),
+            "<" => Assertion::LTE(left + 1, right),
+            _ => bail!("Unexpected comparison {} in assertion {:?}", op, input),
+        };
+        Ok(assertion)
+    }
+
+    /// Lower and upper bounds of a symbol, as implied by assertions.
+    pub fn symbol_bounds(&self, symbol: Symbol) -> Bounds {
+        let mut bounds = (None, None);
+        for positive in self.assertions().iter().flat_map(|a| a.positives()) {
+            if let Some((s, k, c)) = as_linear(&positive) {
+                if s != symbol || k == 0 {
+                    continue;
+                }
+                // k*s + c >= 0
+                if k > 0 {
+                    let low = num_integer::Integer::div_ceil(&-c, &k);
+                    bounds.0 = Some(bounds.0.map(|b: i64| b.max(low)).unwrap_or(low));
+                } else {
+                    let high = num_integer::Integer::div_floor(&c, &-k);
+                    bounds.1 = Some(bounds.1.map(|b: i64| b.min(high)).unwrap_or(high));
+                }
+            }
+        }
+        bounds
+    }
+
+    /// Lower and upper bounds of a dimension expression, as implied by
+    /// assertions.
+    pub fn bounds(&self, dim: &TDim) -> Bounds {
+        match dim {
+            Val(v) => (Some(*v), Some(*v)),
+            Sym(s) => self.symbol_bounds(*s),
+            Add(terms) => {
+                terms.iter().fold((Some(0), Some(0)), |acc, t| add_bounds(acc, self.bounds(t)))
+            }
+            MulInt(k, a) => {
+                let (low, high) = self.bounds(a);
+                let (low, high) = (low.map(|x| x * k), high.map(|x| x * k));
+                if *k >= 0 {
+                    (low, high)
+                } else {
+                    (high, low)
+                }
+            }
+            Mul(terms) => {
+                let bounds: Vec<Bounds> = terms.iter().map(|t| self.bounds(t)).collect();
+                if bounds.iter().all(|b| b.0.map(|x| x >= 0).unwrap_or(false)) {
+                    (bounds.iter().map(|b| b.0).product(), bounds.iter().map(|b| b.1).product())
+                } else {
+                    (None, None)
+                }
+            }
+            Div(a, q) => match self.bounds(a) {
+                (Some(low), high) if low >= 0 => {
+                    (Some(low / *q as i64), high.map(|h| h / *q as i64))
+                }
+                _ => (None, None),
+            },
+            Mod(a, q) => match self.bounds(a) {
+                (Some(low), high) if low >= 0 => {
+                    (Some(0), Some(high.unwrap_or(i64::MAX).min(*q as i64 - 1)))
+                }
+                _ => (Some(0), Some(*q as i64 - 1)),
+            },
+            Min(terms) => {
+                let bounds: Vec<Bounds> = terms.iter().map(|t| self.bounds(t)).collect();
+                (
+                    bounds.iter().map(|b| b.0).fold(Some(i64::MAX), |acc, b| Some(acc?.min(b?))),
+                    bounds.iter().filter_map(|b| b.1).min(),
+                )
+            }
+            Max(terms) => {
+                let bounds: Vec<Bounds> = terms.iter().map(|t| self.bounds(t)).collect();
+                (
+                    bounds.iter().filter_map(|b| b.0).max(),
+                    bounds.iter().map(|b| b.1).fold(Some(i64::MIN), |acc, b| Some(acc?.max(b?))),
+                )
+            }
+        }
+    }
+
+    /// Is the expression known to be positive or zero for all symbol values?
+    pub fn prove_positive_or_zero(&self, dim: &TDim) -> bool {
+        self.bounds(&dim.clone().reduce()).0.map(|low| low >= 0).unwrap_or(false)
+    }
+
+    /// Is the expression known to be a multiple of q for all symbol values?
+    pub fn prove_multiple_of(&self, dim: &TDim, q: u64) -> bool {
+        match dim {
+            Val(v) => v.rem_euclid(q as i64) == 0,
+            Sym(_) => self.assertions().iter().any(|a| match a {
+                Assertion::MultipleOf(a, m) => a == dim && m % q == 0,
+                _ => false,
+            }),
+            MulInt(k, a) => k.rem_euclid(q as i64) == 0 || self.prove_multiple_of(a, q),
+            Add(terms) => terms.iter().all(|t| self.prove_multiple_of(t, q)),
+            Mul(terms) => terms.iter().any(|t| self.prove_multiple_of(t, q)),
+            _ => false,
+        }
+    }
+
+    /// Simplify an expression using the assertions.
+    pub fn simplify(&self, dim: &TDim) -> TDim {
+        let mut dim = dim.clone();
+        for a in self.assertions() {
+            if let Assertion::Eq(left, right) = a {
+                let (s, e) = match (left, right) {
+                    (Sym(s), e) | (e, Sym(s)) if !e.symbols().contains(s) => (s, e),
+                    _ => continue,
+                };
+                dim = substitute(&dim, *s, e);
+            }
+        }
+        self.simplify_rec(dim.reduce()).reduce()
+    }
+
+    fn simplify_rec(&self, dim: TDim) -> TDim {
+        let map = |terms: Vec<TDim>| -> Vec<TDim> {
+            terms.into_iter().map(|t| self.simplify_rec(t)).collect()
+        };
+        match dim {
+            Add(terms) => Add(map(terms)),
+            Mul(terms) => Mul(map(terms)),
+            MulInt(k, a) => MulInt(k, Box::new(self.simplify_rec(*a))),
+            Div(a, q) => {
+                let a = self.simplify_rec(*a);
+                match self.bounds(&a) {
+                    (Some(low), Some(high)) if low >= 0 && high < q as i64 => Val(0),
+                    _ => Div(Box::new(a), q),
+                }
+            }
+            Mod(a, q) => {
+                let a = self.simplify_rec(*a);
+                if self.prove_multiple_of(&a, q) {
+                    return Val(0);
+                }
+                match self.bounds(&a) {
+                    (Some(low), Some(high)) if low >= 0 && high < q as i64 => a,
+                    _ => Mod(Box::new(a), q),
+                }
+            }
+            Min(terms) => Min(self.drop_dominated(map(terms), true)),
+            Max(terms) => Max(self.drop_dominated(map(terms), false)),
+            Sym(_) | Val(_) => dim,
+        }
+    }
+
+    /// Remove from a min (or max) the terms known to be greater (or smaller)
+    /// than another term.
+    fn drop_dominated(&self, mut terms: Vec<TDim>, min: bool) -> Vec<TDim> {
+        let mut ix = 0;
+        while ix < terms.len() {
+            let dominated = (0..terms.len()).filter(|&other| other != ix).any(|other| {
+                let diff = if min {
+                    terms[ix].clone() - &terms[other]
+                } else {
+                    terms[other].clone() - &terms[ix]
+                };
+                self.prove_positive_or_zero(&diff)
+            });
+            if dominated {
+                terms.remove(ix);
+            } else {
+                ix += 1;
+            }
+        }
+        terms
+    }
+
+    /// Check the assertions hold for the given symbol values. Assertions
+    /// involving unresolved symbols are ignored.
+    pub fn check(&self, values: &SymbolValues) -> anyhow::Result<()> {
+        for a in self.assertions() {
+            if a.check(values) == Some(false) {
+                let values = a
+                    .symbols()
+                    .into_iter()
+                    .sorted()
+                    .map(|s| format!("{}={}", s, values[s].unwrap()))
+                    .collect::<Vec<_>>();
+                bail!("Assertion {} does not hold for {}", a, values.join(", "))
+            }
+        }
+        Ok(())
+    }
+}
+
+fn substitute(dim: &TDim, s: Symbol, e: &TDim) -> TDim {
+    let map = |terms: &[TDim]| terms.iter().map(|t| substitute(t, s, e)).collect();
+    match dim {
+        Sym(sym) if *sym == s => e.clone(),
+        Sym(_) | Val(_) => dim.clone(),
+        Add(terms) => Add(map(terms)),
+        Mul(terms) => Mul(map(terms)),
+        Min(terms) => Min(map(terms)),
+        Max(terms) => Max(map(terms)),
+        MulInt(k, a) => MulInt(*k, Box::new(substitute(a, s, e))),
+        Div(a, q) => Div(Box::new(substitute(a, s, e)), *q),
+        Mod(a, q) => Mod(Box::new(substitute(a, s, e)), *q),
+    }
+}
+
+impl TDim {
+    /// Simplify using the assertions of a symbol table.
+    pub fn simplify_with(self, table: &SymbolTable) -> TDim {
+        table.simplify(&self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(assertions: &[&str]) -> SymbolTable {
+        let mut table = SymbolTable::default();
+        for a in assertions {
+            let a = table.parse_assertion(a).unwrap();
+            table.add_assertion(a);
+        }
+        table
+    }
+
+    #[test]
+    fn parse() {
+        let mut table = SymbolTable::default();
+        let s: TDim = table.sym("S").into();
+        assert_eq!(table.parse_assertion("S>=1").unwrap(), Assertion::GTE(s.clone(), 1.into()));
+        assert_eq!(
+            table.parse_assertion("S < 33").unwrap(),
+            Assertion::LTE(s.clone() + 1, 33.into())
+        );
+        assert_eq!(table.parse_assertion("S%4==0").unwrap(), Assertion::MultipleOf(s.clone(), 4));
+        assert!(table.parse_assertion("S").is_err());
+        for a in &["S>=1", "S<=32", "S%4==0", "S==2*T"] {
+            let a = table.parse_assertion(a).unwrap();
+            assert_eq!(table.parse_assertion(&a.to_string()).unwrap(), a);
+        }
+    }
+
+    #[test]
+    fn bounds() {
+        let mut table = table(&["B>=1", "B<=32", "2*S>=4"]);
+        let b: TDim = table.sym("B").into();
+        let s: TDim = table.sym("S").into();
+        assert_eq!(table.bounds(&b), (Some(1), Some(32)));
+        assert_eq!(table.bounds(&s), (Some(2), None));
+        assert_eq!(table.bounds(&(b.clone() * 2 + 1)), (Some(3), Some(65)));
+        assert_eq!(table.bounds(&(b.clone() * &s)), (Some(2), None));
+        assert!(table.prove_positive_or_zero(&(s.clone() - 2)));
+        assert!(!table.prove_positive_or_zero(&(s - 3)));
+    }
+
+    #[test]
+    fn simplify_min_max() {
+        let mut table = table(&["S<=10", "S>=0"]);
+        let s: TDim = table.sym("S").into();
+        assert_eq!(s.clone().mini(10.into()).simplify_with(&table), s);
+        assert_eq!(s.clone().maxi(10.into()).simplify_with(&table), 10.into());
+        assert_eq!((s.clone() / 11).simplify_with(&table), 0.into());
+        assert_eq!((s.clone() % 11).simplify_with(&table), s);
+    }
+
+    #[test]
+    fn simplify_multiple_and_eq() {
+        let mut table = table(&["T%4==0", "U==2*T"]);
+        let t: TDim = table.sym("T").into();
+        let u: TDim = table.sym("U").into();
+        assert_eq!((t.clone() % 4).simplify_with(&table), 0.into());
+        assert_eq!((t.clone() * 3 % 2).simplify_with(&table), 0.into());
+        assert_eq!((u - &t).simplify_with(&table), t);
+    }
+
+    #[test]
+    fn check() {
+        let mut table = table(&["S>=1", "S%2==0"]);
+        let s = table.sym("S");
+        assert!(table.check(&SymbolValues::default()).is_ok());
+        assert!(table.check(&SymbolValues::default().with(s, 4)).is_ok());
+        assert!(table.check(&SymbolValues::default().with(s, 0)).is_err());
+        assert!(table.check(&SymbolValues::default().with(s, 3)).is_err());
+    }
+}
@@ -3,9 +3,14 @@ use num_traits::Zero;
 use std::fmt;
 use std::ops;
 
+mod assertion;
+mod parse;
+mod sym;
 mod tree;
 
-pub use self::tree::{Symbol, SymbolValues, TDim};
+pub use self::assertion::Assertion;
+pub use self::sym::{Symbol, SymbolTable, SymbolValues};
+pub use self::tree::TDim;
 type TractError = anyhow::Error;
 type TractResult<T> = anyhow::Result<T>;
 
@@ -85,13 +90,16 @@ impl DimLike for TDim {
             (_, _) => {
                 if self.symbols().len() == 1 && other.symbols().len() == 1 {
                     let sym = self.symbols().into_iter().nth(0).unwrap();
-                    let slope_p = self.slope(sym);
-                    let slope_q = other.slope(sym);
-                    let (p, q) = tree::reduce_ratio(
-                        slope_p.0 * slope_q.1 as i64,
-                        slope_q.0 * slope_p.1 as i64,
-                    );
-                    Some((p.into(), q))
+                    match (self.slope(sym), other.slope(sym)) {
+                        (Some(slope_p), Some(slope_q)) if slope_q.0 != 0 => {
+                            let (p, q) = tree::reduce_ratio(
+                                slope_p.0 * slope_q.1 as i64,
+                                slope_q.0 * slope_p.1 as i64,
+                            );
+                            Some((p.into(), q))
+                        }
+                        _ => None,
+                    }
                 } else {
                     None
                 }
@@ -111,7 +119,7 @@ impl DimLike for TDim {
         } else if let Ok(a) = self.to_i64() {
             Ok(other.clone() * a)
         } else {
-            anyhow::bail!("product with too many symbols")
+            Ok(self.clone() * other)
         }
     }
 
@@ -230,4 +238,28 @@ mod tests {
     fn div_sym_sym_rem() {
         assert!((s() + 1).maybe_div(&(s() * 4)).is_err());
     }
+
+    #[test]
+    fn slope() {
+        assert_eq!((s() * 3 + 1).slope(*S), Some((3, 1)));
+        assert_eq!(((s() + 1) / 2).slope(*S), Some((1, 2)));
+        assert_eq!((TDim::Mod(Box::new(s()), 2) + s()).slope(*S), None);
+    }
+
+    #[test]
+    fn div_sym_non_linear() {
+        let min = TDim::Min(vec![s(), 4.into()]);
+        assert!(min.maybe_div(&s()).is_err());
+        assert!(s().maybe_div(&min).is_err());
+        let modulo = TDim::Mod(Box::new(s()), 2);
+        assert!(modulo.maybe_div(&s()).is_err());
+        assert!((s() * 2).maybe_div(&(modulo + s())).is_err());
+    }
+
+    #[test]
+    fn mul_sym_sym() {
+        let t: TDim = Symbol::new('T').into();
+        assert_eq!(s().maybe_mul(&t).unwrap(), t.clone() * s());
+        assert_eq!(s().maybe_mul(&(t.clone() * 2)).unwrap(), s() * &t * 2);
+    }
 }
@@ -0,0 +1,232 @@
+//! Parsing of symbolic dimension expressions.
+//!
+//! Supported syntax: integers, symbols (identifiers like `S` or `batch_size`),
+//! `+`, `-`, `*` (or `.`), `/` and `%` by an integer, parenthesis,
+//! `min(a, b, ...)`, `max(a, b, ...)` and `ceil(a / q)`. An integer directly followed by a symbol is a product, so
+//! `2S` is the same as `2*S`.
+use super::sym::Symbol;
+use super::tree::TDim;
+use anyhow::{bail, Context};
+
+pub fn parse_tdim(input: &str) -> anyhow::Result<TDim> {
+    parse_tdim_with(input, &mut |name| Symbol::from(name))
+}
+
+/// Parse a dimension expression, using `resolve` to map identifiers to symbols.
+pub fn parse_tdim_with(
+    input: &str,
+    resolve: &mut dyn FnMut(&str) -> Symbol,
+) -> anyhow::Result<TDim> {
+    let mut parser = Parser { input: input.as_bytes(), pos: 0, resolve };
+    let dim = parser.expr().with_context(|| format!("Parsing dimension expression {:?}", input))?;
+    parser.skip_spaces();
+    if parser.pos != parser.input.len() {
+        bail!("Parsing dimension expression {:?}: unexpected trailing input", input);
+    }
+    Ok(dim.reduce())
+}
+
+struct Parser<'i, 'r> {
+    input: &'i [u8],
+    pos: usize,
+    resolve: &'r mut dyn FnMut(&str) -> Symbol,
+}
+
+impl<'i, 'r> Parser<'i, 'r> {
+    fn skip_spaces(&mut self) {
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_spaces();
+        self.input.get(self.pos).cloned()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> anyhow::Result<()> {
+        if !self.eat(c) {
+            bail!("expected {:?} at position {}", c as char, self.pos)
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self) -> anyhow::Result<TDim> {
+        let mut terms = vec![self.term()?];
+        loop {
+            if self.eat(b'+') {
+                terms.push(self.term()?);
+            } else if self.eat(b'-') {
+                terms.push(TDim::MulInt(-1, Box::new(self.term()?)));
+            } else {
+                break;
+            }
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { TDim::Add(terms) })
+    }
+
+    fn term(&mut self) -> anyhow::Result<TDim> {
+        let mut dim = self.factor()?;
+        loop {
+            if self.eat(b'*') || self.eat(b'.') {
+                dim = TDim::Mul(vec![dim, self.factor()?]);
+            } else if self.eat(b'/') {
+                dim = TDim::Div(Box::new(dim), self.divisor()?);
+            } else if self.eat(b'%') {
+                dim = TDim::Mod(Box::new(dim), self.divisor()?);
+            } else {
+                break;
+            }
+        }
+        Ok(dim)
+    }
+
+    fn divisor(&mut self) -> anyhow::Result<u64> {
+        let pos = self.pos;
+        match self.factor()?.reduce() {
+            TDim::Val(v) if v > 0 => Ok(v as u64),
+            _ => bail!("expected a positive integer divisor at position {}", pos),
+        }
+    }
+
+    fn factor(&mut self) -> anyhow::Result<TDim> {
+        if self.eat(b'-') {
+            return Ok(TDim::MulInt(-1, Box::new(self.factor()?)));
+        }
+        match self.peek() {
+            Some(b'(') => {
+                self.pos += 1;
+                let e = self.expr()?;
+                self.expect(b')')?;
+                Ok(e)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.pos;
+                while self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+                    self.pos += 1;
+                }
+                let value: i64 = std::str::from_utf8(&self.input[start..self.pos])?.parse()?;
+                if self.input.get(self.pos).map(|c| is_ident_start(*c)).unwrap_or(false) {
+                    Ok(TDim::MulInt(value, Box::new(self.ident()?)))
+                } else {
+                    Ok(TDim::Val(value))
+                }
+            }
+            Some(c) if is_ident_start(c) => self.ident(),
+            Some(c) => bail!("unexpected {:?} at position {}", c as char, self.pos),
+            None => bail!("unexpected end of expression"),
+        }
+    }
+
+    fn ident(&mut self) -> anyhow::Result<TDim> {
+        let start = self.pos;
+        while self.pos < self.input.len()
+            && (is_ident_start(self.input[self.pos]) || self.input[self.pos].is_ascii_digit())
+        {
+            self.pos += 1;
+        }
+        let name = std::str::from_utf8(&self.input[start..self.pos])?;
+        if self.peek() == Some(b'(') {
+            self.pos += 1;
+            let mut args = vec![self.expr()?];
+            while self.eat(b',') {
+                args.push(self.expr()?);
+            }
+            self.expect(b')')?;
+            match name {
+                "min" => Ok(TDim::Min(args)),
+                "max" => Ok(TDim::Max(args)),
+                "ceil" if args.len() == 1 => match args.remove(0) {
+                    TDim::Div(a, q) => Ok(a.div_ceil(q)),
+                    _ => bail!("ceil() expects a division by an integer"),
+                },
+                _ => bail!("unknown function {}", name),
+            }
+        } else {
+            Ok(TDim::Sym((self.resolve)(name)))
+        }
+    }
+}
+
+fn is_ident_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dim::SymbolValues;
+
+    fn s() -> TDim {
+        TDim::Sym(Symbol::from('S'))
+    }
+
+    #[test]
+    fn parse_int() {
+        assert_eq!(parse_tdim("12").unwrap(), 12.into());
+        assert_eq!(parse_tdim("-12").unwrap(), (-12).into());
+    }
+
+    #[test]
+    fn parse_sym() {
+        assert_eq!(parse_tdim("S").unwrap(), s());
+        assert_eq!(parse_tdim("2S").unwrap(), s() * 2);
+        assert_eq!(parse_tdim("2*S+1").unwrap(), s() * 2 + 1);
+        assert_eq!(parse_tdim("(S+3)/2").unwrap(), (s() + 3) / 2);
+        assert_eq!(parse_tdim("S % 2").unwrap(), s() % 2);
+    }
+
+    #[test]
+    fn parse_named_sym() {
+        let batch = TDim::Sym(Symbol::from("batch_size"));
+        assert_eq!(parse_tdim("batch_size").unwrap(), batch);
+        assert_eq!(parse_tdim("4batch_size").unwrap(), batch.clone() * 4);
+        assert_eq!(parse_tdim("batch_size*S").unwrap(), batch * s());
+    }
+
+    #[test]
+    fn parse_min_max_ceil() {
+        assert_eq!(parse_tdim("min(S, 4)").unwrap(), s().mini(4.into()));
+        assert_eq!(parse_tdim("max(S, 4, 2)").unwrap(), s().maxi(4.into()));
+        assert_eq!(parse_tdim("ceil(S/3)").unwrap(), s().div_ceil(3));
+    }
+
+    #[test]
+    fn parse_display_roundtrip() {
+        let t = TDim::Sym(Symbol::from('T'));
+        for e in &[
+            s() * 2 + 1,
+            (s() + 3) / 2,
+            (s() * 3 + 1) % 4,
+            s() * &t + 2,
+            s().mini(t.clone()) - 1,
+            (s() + 1).maxi(12.into()),
+        ] {
+            assert_eq!(&parse_tdim(&e.to_string()).unwrap(), e);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse_tdim("S/T").is_err());
+        assert!(parse_tdim("foo(S)").is_err());
+        assert!(parse_tdim("S+").is_err());
+        assert!(parse_tdim("(S").is_err());
+    }
+
+    #[test]
+    fn parse_eval() {
+        let e = parse_tdim("min(S, 10) + ceil(S/4)").unwrap();
+        let values = SymbolValues::default().with(Symbol::from('S'), 13);
+        assert_eq!(e.eval(&values), 14.into());
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use super::assertion::Assertion;
+use super::TDim;
+
+lazy_static::lazy_static! {
+    static ref SYMBOLS: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+/// Process-wide symbol bookkeeping: interned names (each distinct name is
+/// allocated once), the symbols `Symbol::from` hands out, and the next id.
+#[derive(Default)]
+struct Registry {
+    names: HashMap<String, &'static str>,
+    named: HashMap<&'static str, Symbol>,
+    next_id: usize,
+}
+
+impl Registry {
+    fn intern(&mut self, name: &str) -> &'static str {
+        if let Some(interned) = self.names.get(name) {
+            return interned;
+        }
+        let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+        self.names.insert(name.to_string(), interned);
+        interned
+    }
+
+    fn fresh(&mut self, name: &str) -> Symbol {
+        let name = self.intern(name);
+        self.next_id += 1;
+        Symbol(name, self.next_id - 1)
+    }
+}
+
+/// A named symbol, for use in symbolic dimensions.
+///
+/// `Symbol::new` always creates a fresh symbol, while `Symbol::from("name")`
+/// returns the same process-wide symbol for a given name. Models get their
+/// own symbols from their `SymbolTable`.
+#[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub struct Symbol(&'static str, usize);
+
+impl Symbol {
+    pub fn new(name: impl ToString) -> Symbol {
+        SYMBOLS.lock().unwrap().fresh(&name.to_string())
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl<'a> From<&'a str> for Symbol {
+    fn from(name: &'a str) -> Symbol {
+        let mut registry = SYMBOLS.lock().unwrap();
+        if let Some(s) = registry.named.get(name) {
+            return *s;
+        }
+        let s = registry.fresh(name);
+        registry.named.insert(s.0, s);
+        s
+    }
+}
+
+impl From<char> for Symbol {
+    fn from(c: char) -> Symbol {
+        Symbol::from(&*c.to_string())
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.0)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}#{}", self.0, self.1)
+    }
+}
+
+/// The symbols of a model, by name, and the assertions about them.
+///
+/// Loaders register the dimension names they find in the model (ONNX
+/// `dim_param`, NNEF dimension expressions, ...) so they can later be looked
+/// up to concretize or assert about them. The symbols belong to the table:
+/// two models loaded with the same dimension names do not share symbols.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    assertions: Vec<Assertion>,
+}
+
+impl SymbolTable {
+    /// Get the symbol called `name`, creating it in the table if needed.
+    pub fn sym(&mut self, name: &str) -> Symbol {
+        if let Some(s) = self.get(name) {
+            s
+        } else {
+            let s = Symbol::new(name);
+            self.symbols.push(s);
+            s
+        }
+    }
+
+    /// Add an existing symbol to the table (no-op if a symbol with the same
+    /// name is already there).
+    pub fn add(&mut self, symbol: Symbol) {
+        if self.get(symbol.name()).is_none() {
+            self.symbols.push(symbol)
+        }
+    }
+
+    /// Create a fresh symbol and add it to the table. It is called `prefix`,
+    /// or `prefix_1`, `prefix_2`... if the name is already taken.
+    ///
+    /// Ops whose output size depends on input values use it for their output
+    /// dimensions.
+    pub fn new_symbol(&mut self, prefix: &str) -> Symbol {
+        let name = std::iter::once(prefix.to_string())
+            .chain((1..).map(|i| format!("{}_{}", prefix, i)))
+            .find(|name| self.get(name).is_none())
+            .unwrap();
+        let s = Symbol::new(name);
+        self.symbols.push(s);
+        s
+    }
+
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.iter().find(|s| s.name() == name).cloned()
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Add an assertion, registering its symbols.
+    pub fn add_assertion(&mut self, assertion: Assertion) {
+        for s in assertion.symbols() {
+            self.add(s);
+        }
+        if !self.assertions.contains(&assertion) {
+            self.assertions.push(assertion)
+        }
+    }
+
+    pub fn assertions(&self) -> &[Assertion] {
+        &self.assertions
+    }
+
+    /// Parse a dimension expression, resolving and registering its symbols
+    /// in this table.
+    pub fn parse_tdim(&mut self, input: &str) -> anyhow::Result<TDim> {
+        let dim = super::parse::parse_tdim_with(input, &mut |name| self.sym(name))?;
+        Ok(dim)
+    }
+
+    /// Build a SymbolValues from name and value pairs. Fails on names
+    /// unknown to the table.
+    pub fn values<'n>(
+        &self,
+        values: impl IntoIterator<Item = (&'n str, i64)>,
+    ) -> anyhow::Result<SymbolValues> {
+        let mut result = SymbolValues::default();
+        for (name, value) in values {
+            if let Some(s) = self.get(name) {
+                result[s] = Some(value);
+            } else {
+                anyhow::bail!("Unknown symbol {}", name)
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Values for some symbols, indexed by symbol id.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolValues(Vec<Option<i64>>);
+
+impl SymbolValues {
+    pub fn with(mut self, s: Symbol, v: i64) -> Self {
+        self[s] = Some(v);
+        self
+    }
+}
+
+impl std::ops::Index<Symbol> for SymbolValues {
+    type Output = Option<i64>;
+    fn index(&self, index: Symbol) -> &Self::Output {
+        if index.1 < self.0.len() {
+            &self.0[index.1]
+        } else {
+            &None
+        }
+    }
+}
+
+impl std::ops::IndexMut<Symbol> for SymbolValues {
+    fn index_mut(&mut self, index: Symbol) -> &mut Self::Output {
+        if index.1 >= self.0.len() {
+            self.0.resize_with(index.1 + 1, Default::default)
+        }
+        &mut self.0[index.1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_symbols() {
+        let batch = Symbol::from("batch_size");
+        assert_eq!(batch.name(), "batch_size");
+        assert_eq!(batch, Symbol::from("batch_size"));
+        assert_eq!(batch.to_string(), "batch_size");
+        assert_ne!(batch, Symbol::new("batch_size"));
+    }
+
+    #[test]
+    fn table() {
+        let mut table = SymbolTable::default();
+        assert!(table.get("sequence_length").is_none());
+        let dim = table.parse_tdim("2*sequence_length+1").unwrap();
+        let seq = table.get("sequence_length").unwrap();
+        assert_eq!(dim, TDim::from(seq) * 2 + 1);
+        assert_eq!(table.symbols(), &[seq]);
+        let values = table.values(vec![("sequence_length", 4)]).unwrap();
+        assert_eq!(dim.eval(&values), 9.into());
+        assert!(table.values(vec![("batch_size", 4)]).is_err());
+    }
+
+    #[test]
+    fn tables_own_their_symbols() {
+        let mut a = SymbolTable::default();
+        let mut b = SymbolTable::default();
+        let sa = a.sym("N");
+        let sb = b.sym("N");
+        assert_ne!(sa, sb);
+        assert_eq!(sa.name(), sb.name());
+        assert_ne!(sa, Symbol::from("N"));
+        let values = a.values(vec![("N", 3)]).unwrap();
+        assert_eq!(values[sa], Some(3));
+        assert_eq!(values[sb], None);
+    }
+
+    #[test]
+    fn names_are_interned() {
+        let a = Symbol::new("interned_name");
+        let b = Symbol::new("interned_name");
+        assert_ne!(a, b);
+        assert!(std::ptr::eq(a.name(), b.name()));
+    }
+
+    #[test]
+    fn new_symbols() {
+        let mut table = SymbolTable::default();
+        let a = table.new_symbol("count");
+        let b = table.new_symbol("count");
+        assert_ne!(a, b);
+        assert_eq!(b.name(), "count_1");
+        assert_eq!(table.symbols(), &[a, b]);
+    }
+}
@@ -3,72 +3,21 @@ use num_traits::{AsPrimitive, Zero};
 use std::collections::HashMap;
 use std::{fmt, ops};
 
-macro_rules! b( ($e:expr) => { Box::new($e) } );
-
-lazy_static::lazy_static! {
-    static ref SYMBOL_TABLE: std::sync::Mutex<Vec<char>> = std::sync::Mutex::new(Vec::new());
-}
-
-#[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
-pub struct Symbol(char, usize);
-
-impl Symbol {
-    pub fn new(c: char) -> Symbol {
-        let mut table = SYMBOL_TABLE.lock().unwrap();
-        table.push(c);
-        Symbol(c, table.len() - 1)
-    }
-}
-
-impl From<char> for Symbol {
-    fn from(c: char) -> Symbol {
-        let mut table = SYMBOL_TABLE.lock().unwrap();
-        if let Some(pos) = table.iter().position(|s| *s == c) {
-            Symbol(c, pos)
-        } else {
-            table.push(c);
-            Symbol(c, table.len() - 1)
-        }
-    }
-}
-
-#[derive(Clone, Debug, Default)]
-pub struct SymbolValues(Vec<Option<i64>>);
+use super::sym::{Symbol, SymbolValues};
 
-impl SymbolValues {
-    pub fn with(mut self, s: Symbol, v: i64) -> Self {
-        self[s] = Some(v);
-        self
-    }
-}
-
-impl std::ops::Index<Symbol> for SymbolValues {
-    type Output = Option<i64>;
-    fn index(&self, index: Symbol) -> &Self::Output {
-        if index.1 < self.0.len() {
-            &self.0[index.1]
-        } else {
-            &None
-        }
-    }
-}
-
-impl std::ops::IndexMut<Symbol> for SymbolValues {
-    fn index_mut(&mut self, index: Symbol) -> &mut Self::Output {
-        if index.1 >= self.0.len() {
-            self.0.resize_with(index.1 + 1, Default::default)
-        }
-        &mut self.0[index.1]
-    }
-}
+macro_rules! b( ($e:expr) => { Box::new($e) } );
 
 #[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
 pub enum TDim {
     Sym(Symbol),
     Val(i64),
     Add(Vec<TDim>),
-    Mul(i64, Box<TDim>),
+    Mul(Vec<TDim>),
+    MulInt(i64, Box<TDim>),
     Div(Box<TDim>, u64),
+    Mod(Box<TDim>, u64),
+    Min(Vec<TDim>),
+    Max(Vec<TDim>),
 }
 
 use TDim::*;
@@ -76,11 +25,27 @@ use TDim::*;
 impl fmt::Display for TDim {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match &self {
-            Sym(sym) => write!(fmt, "{}", sym.0),
+            Sym(sym) => write!(fmt, "{}", sym),
             Val(it) => write!(fmt, "{}", it),
             Add(it) => write!(fmt, "{}", it.iter().map(|x| format!("{}", x)).join("+")),
-            Mul(a, b) => write!(fmt, "{}.{}", a, b),
+            Mul(it) => write!(
+                fmt,
+                "{}",
+                it.iter()
+                    .map(|x| if let Add(_) = x { format!("({})", x) } else { format!("{}", x) })
+                    .join("*")
+            ),
+            MulInt(a, b) => {
+                if let Add(_) = &**b {
+                    write!(fmt, "{}.({})", a, b)
+                } else {
+                    write!(fmt, "{}.{}", a, b)
+                }
+            }
             Div(a, b) => write!(fmt, "({})/{}", a, b),
+            Mod(a, b) => write!(fmt, "({})%{}", a, b),
+            Min(it) => write!(fmt, "min({})", it.iter().join(",")),
+            Max(it) => write!(fmt, "max({})", it.iter().join(",")),
         }
     }
 }
@@ -103,11 +68,25 @@ impl TDim {
             Sym(sym) => values[*sym].map(|s| Val(s)).unwrap_or(Sym(*sym)),
             Val(v) => Val(*v),
             Add(terms) => terms.iter().fold(Val(0), |acc, it| -> TDim { acc + it.eval(values) }),
+            Mul(terms) => terms.iter().fold(Val(1), |acc, it| -> TDim { acc * it.eval(values) }),
+            MulInt(p, a) => a.eval(values) * *p,
             Div(a, q) => a.eval(values) / *q as i64,
-            Mul(p, a) => a.eval(values) * *p,
+            Mod(a, q) => a.eval(values) % *q as i64,
+            Min(terms) => Min(terms.iter().map(|t| t.eval(values)).collect()).reduce(),
+            Max(terms) => Max(terms.iter().map(|t| t.eval(values)).collect()).reduce(),
         }
     }
 
+    /// Smallest of two dimensions.
+    pub fn mini(self, other: TDim) -> TDim {
+        Min(vec![self, other]).reduce()
+    }
+
+    /// Largest of two dimensions.
+    pub fn maxi(self, other: TDim) -> TDim {
+        Max(vec![self, other]).reduce()
+    }
+
     pub fn reduce(self) -> TDim {
         self.simplify()
             .wiggle()
@@ -123,16 +102,18 @@ impl TDim {
         use self::TDim::*;
         match self {
             Sym(_) | Val(_) => 1,
-            Add(terms) => 2 * terms.iter().map(TDim::cost).sum::<usize>(),
-            Div(a, _) => 3 * a.cost(),
-            Mul(_, a) => 2 * a.cost(),
+            Add(terms) | Mul(terms) | Min(terms) | Max(terms) => {
+                2 * terms.iter().map(TDim::cost).sum::<usize>()
+            }
+            Div(a, _) | Mod(a, _) => 3 * a.cost(),
+            MulInt(_, a) => 2 * a.cost(),
         }
     }
 
     fn wiggle(&self) -> Vec<TDim> {
         use self::TDim::*;
         match self {
-            Sym(_) | Val(_) => vec![self.clone()],
+            Sym(_) | Val(_) | Mul(_) | Mod(..) | Min(_) | Max(_) => vec![self.clone()],
             Add(terms) => {
                 let mut forms = vec![];
                 let sub_wiggle = terms.iter().map(|e| e.wiggle()).multi_cartesian_product();
@@ -150,7 +131,7 @@ impl TDim {
                             .enumerate()
                             .map(|(ix2, t)| {
                                 if ix2 != ix {
-                                    Mul(*q as i64, b!(t.clone()))
+                                    MulInt(*q as i64, b!(t.clone()))
                                 } else {
                                     (**num).clone()
                                 }
@@ -162,7 +143,7 @@ impl TDim {
                 }
                 forms
             }
-            Mul(p, a) => a.wiggle().into_iter().map(|a| Mul(*p, b!(a))).collect(),
+            MulInt(p, a) => a.wiggle().into_iter().map(|a| MulInt(*p, b!(a))).collect(),
             Div(a, q) => {
                 let mut forms = vec![];
                 for num in a.wiggle() {
@@ -198,7 +179,7 @@ impl TDim {
                         }
                         Val(0) => (),
                         Val(v) => *reduced.entry(Val(1)).or_insert(0) += v,
-                        Mul(v, f) => {
+                        MulInt(v, f) => {
                             *reduced.entry((*f).clone()).or_insert(0) += v;
                         }
                         n => *reduced.entry(n).or_insert(0) += 1,
@@ -214,7 +195,7 @@ impl TDim {
                         } else if v == 1 {
                             Some(k)
                         } else {
-                            Some(Mul(v, b![k]))
+                            Some(MulInt(v, b![k]))
                         }
                     })
                     .collect();
@@ -227,9 +208,40 @@ impl TDim {
                     members.remove(0)
                 }
             }
-            Mul(p, a) => {
-                if let Mul(p2, a) = *a {
-                    return Mul(p * p2, a).simplify();
+            Mul(mut terms) => {
+                let mut coef = 1i64;
+                let mut factors = vec![];
+                while let Some(item) = terms.pop() {
+                    match item.simplify() {
+                        Val(v) => coef *= v,
+                        MulInt(p, a) => {
+                            coef *= p;
+                            terms.push(*a);
+                        }
+                        Mul(items) => terms.extend(items.into_iter()),
+                        n => factors.push(n),
+                    }
+                }
+                if coef == 0 {
+                    return Val(0);
+                }
+                factors.sort();
+                let product = match factors.len() {
+                    0 => return Val(coef),
+                    1 => factors.remove(0),
+                    _ => Mul(factors),
+                };
+                if coef == 1 {
+                    product
+                } else if let MulInt(p, a) = product {
+                    MulInt(coef * p, a)
+                } else {
+                    MulInt(coef, b!(product))
+                }
+            }
+            MulInt(p, a) => {
+                if let MulInt(p2, a) = *a {
+                    return MulInt(p * p2, a).simplify();
                 } else if let Val(p2) = *a {
                     return Val(p * p2);
                 }
@@ -239,13 +251,13 @@ impl TDim {
                 } else if p == 1 {
                     a
                 } else if let Add(terms) = &a {
-                    Add(terms.clone().into_iter().map(|a| Mul(p, b!(a)).simplify()).collect())
+                    Add(terms.clone().into_iter().map(|a| MulInt(p, b!(a)).simplify()).collect())
                 } else if let Val(p2) = a {
                     Val(p * p2)
-                } else if let Mul(p2, a) = a {
-                    Mul(p * p2, a)
+                } else if let MulInt(p2, a) = a {
+                    MulInt(p * p2, a)
                 } else {
-                    Mul(p, b!(a))
+                    MulInt(p, b!(a))
                 }
             }
             Div(a, q) => {
@@ -257,11 +269,17 @@ impl TDim {
                 let a = a.simplify();
                 if let Val(a) = a {
                     Val(a / q as i64)
-                } else if let Mul(-1, a) = a {
-                    Mul(-1, b!(Div(a, q)))
+                } else if let Mod(_, m) = a {
+                    if m <= q {
+                        Val(0)
+                    } else {
+                        Div(b!(a), q)
+                    }
+                } else if let MulInt(-1, a) = a {
+                    MulInt(-1, b!(Div(a, q)))
                 } else if let Add(mut terms) = a {
                     if terms.iter().any(|t| {
-                        if let Mul(-1, s) = t {
+                        if let MulInt(-1, s) = t {
                             if let Sym(_) = &**s {
                                 true
                             } else {
@@ -271,10 +289,10 @@ impl TDim {
                             false
                         }
                     }) {
-                        Mul(
+                        MulInt(
                             -1,
                             b!(Div(
-                                b!(Add(terms.into_iter().map(|t| Mul(-1, b!(t))).collect())
+                                b!(Add(terms.into_iter().map(|t| MulInt(-1, b!(t))).collect())
                                     .simplify()),
                                 q
                             )),
@@ -300,7 +318,7 @@ impl TDim {
                     } else {
                         Div(b!(Add(terms)), q)
                     }
-                } else if let Mul(p, a) = a {
+                } else if let MulInt(p, a) = a {
                     if p == q as i64 {
                         a.simplify()
                     } else {
@@ -308,17 +326,45 @@ impl TDim {
                         if gcd == p {
                             Div(a, q / gcd as u64)
                         } else if gcd == q as i64 {
-                            Mul(p / gcd, a)
+                            MulInt(p / gcd, a)
                         } else if gcd > 1 {
-                            Div(b!(Mul(p / gcd, a)), q / gcd as u64).simplify()
+                            Div(b!(MulInt(p / gcd, a)), q / gcd as u64).simplify()
                         } else {
-                            Div(b!(Mul(p, a)), q)
+                            Div(b!(MulInt(p, a)), q)
                         }
                     }
                 } else {
                     Div(b!(a), q)
                 }
             }
+            Mod(a, q) => {
+                if q == 1 {
+                    return Val(0);
+                }
+                match a.simplify() {
+                    Val(v) => Val(v.rem_euclid(q as i64)),
+                    Mod(a, q2) if q2 % q == 0 => Mod(a, q).simplify(),
+                    Mod(a, q2) if q % q2 == 0 => Mod(a, q2),
+                    Add(terms) => {
+                        let terms: Vec<TDim> = terms
+                            .into_iter()
+                            .filter_map(|t| match t {
+                                Val(v) => Some(Val(v.rem_euclid(q as i64))),
+                                t if t.gcd() % q == 0 => None,
+                                t => Some(t),
+                            })
+                            .collect();
+                        match Add(terms).simplify() {
+                            Val(v) => Val(v.rem_euclid(q as i64)),
+                            a => Mod(b!(a), q),
+                        }
+                    }
+                    a if a.gcd() % q == 0 => Val(0),
+                    a => Mod(b!(a), q),
+                }
+            }
+            Min(terms) => simplify_min_max(terms, true),
+            Max(terms) => simplify_min_max(terms, false),
             _ => self,
         }
     }
@@ -328,12 +374,12 @@ impl TDim {
         use num_integer::Integer;
         match self {
             Val(v) => v.abs() as u64,
-            Sym(_) => 1,
+            Sym(_) | Mul(_) | Mod(..) | Min(_) | Max(_) => 1,
             Add(terms) => {
                 let (head, tail) = terms.split_first().unwrap();
                 tail.iter().fold(head.gcd(), |a, b| a.gcd(&b.gcd()))
             }
-            Mul(p, a) => a.gcd() * p.abs() as u64,
+            MulInt(p, a) => a.gcd() * p.abs() as u64,
             Div(a, q) => {
                 if a.gcd() % *q == 0 {
                     a.gcd() / *q
@@ -352,14 +398,14 @@ impl TDim {
         }
         match self {
             Val(v) => Val(v / d as i64),
-            Sym(_) => panic!(),
+            Sym(_) | Mul(_) | Mod(..) | Min(_) | Max(_) => panic!(),
             Add(terms) => Add(terms.iter().map(|t| t.div(d)).collect()),
-            Mul(p, a) => {
+            MulInt(p, a) => {
                 if *p == d as i64 {
                     (**a).clone()
                 } else {
                     let gcd = (p.abs() as u64).gcd(&d);
-                    Mul(p / gcd as i64, b!(a.div(d / gcd)))
+                    MulInt(p / gcd as i64, b!(a.div(d / gcd)))
                 }
             }
             Div(a, q) => Div(a.clone(), q * d),
@@ -370,43 +416,103 @@ impl TDim {
         TDim::Div(Box::new(Add(vec![self, Val(rhs as i64 - 1)])), rhs).reduce()
     }
 
-    pub fn slope(&self, sym: Symbol) -> (i64, u64) {
-        fn slope_rec(d: &TDim, sym: Symbol) -> (i64, i64) {
+    /// Slope of the expression along `sym`, if it is linear in `sym`.
+    pub fn slope(&self, sym: Symbol) -> Option<(i64, u64)> {
+        fn slope_rec(d: &TDim, sym: Symbol) -> Option<(i64, i64)> {
             match d {
-                Val(_) => (0, 1),
-                Sym(s) => ((sym == *s) as i64, 1),
-                Add(terms) => terms
-                    .iter()
-                    .map(|d| slope_rec(d, sym))
-                    .fold((1, 1), |a, b| ((a.0 * b.1 + a.1 * b.0), (b.1 * a.1))),
-                Mul(p, a) => {
-                    let (n, d) = slope_rec(a, sym);
-                    (p * n, d)
+                Val(_) => Some((0, 1)),
+                Sym(s) => Some(((sym == *s) as i64, 1)),
+                Mul(_) | Mod(..) | Min(_) | Max(_) => {
+                    if d.symbols().contains(&sym) {
+                        None
+                    } else {
+                        Some((0, 1))
+                    }
+                }
+                Add(terms) => terms.iter().try_fold((0, 1), |a, d| {
+                    let b = slope_rec(d, sym)?;
+                    Some(((a.0 * b.1 + a.1 * b.0), (b.1 * a.1)))
+                }),
+                MulInt(p, a) => {
+                    let (n, d) = slope_rec(a, sym)?;
+                    Some((p * n, d))
                 }
                 Div(a, q) => {
-                    let (n, d) = slope_rec(a, sym);
-                    (n, d * *q as i64)
+                    let (n, d) = slope_rec(a, sym)?;
+                    Some((n, d * *q as i64))
                 }
             }
         }
-        let (p, q) = slope_rec(self, sym);
-        reduce_ratio(p, q)
+        let (p, q) = slope_rec(self, sym)?;
+        Some(reduce_ratio(p, q))
     }
 
     pub fn symbols(&self) -> std::collections::HashSet<Symbol> {
         match self {
             Val(_) => maplit::hashset!(),
             Sym(s) => maplit::hashset!(*s),
-            Add(terms) => terms.iter().fold(maplit::hashset!(), |mut set, v| {
-                set.extend(v.symbols().into_iter());
-                set
-            }),
-            Mul(_, a) => a.symbols(),
-            Div(a, _) => a.symbols(),
+            Add(terms) | Mul(terms) | Min(terms) | Max(terms) => {
+                terms.iter().fold(maplit::hashset!(), |mut set, v| {
+                    set.extend(v.symbols().into_iter());
+                    set
+                })
+            }
+            MulInt(_, a) => a.symbols(),
+            Div(a, _) | Mod(a, _) => a.symbols(),
         }
     }
 }
 
+fn simplify_min_max(mut terms: Vec<TDim>, min: bool) -> TDim {
+    let mut members: Vec<TDim> = vec![];
+    let mut konst: Option<i64> = None;
+    while let Some(item) = terms.pop() {
+        match item.simplify() {
+            Min(items) if min => terms.extend(items.into_iter()),
+            Max(items) if !min => terms.extend(items.into_iter()),
+            Val(v) => {
+                konst = Some(konst.map(|k| if min { k.min(v) } else { k.max(v) }).unwrap_or(v))
+            }
+            n => {
+                if !members.contains(&n) {
+                    members.push(n)
+                }
+            }
+        }
+    }
+    if let Some(k) = konst {
+        members.push(Val(k));
+    }
+    // drop members that are dominated by another one by a constant margin
+    let mut ix = 0;
+    while ix < members.len() {
+        let dominated = (0..members.len()).filter(|&other| other != ix).any(|other| {
+            if let Val(diff) = (members[ix].clone() - &members[other]).simplify() {
+                if min {
+                    diff >= 0
+                } else {
+                    diff <= 0
+                }
+            } else {
+                false
+            }
+        });
+        if dominated {
+            members.remove(ix);
+        } else {
+            ix += 1;
+        }
+    }
+    members.sort();
+    if members.len() == 1 {
+        members.remove(0)
+    } else if min {
+        Min(members)
+    } else {
+        Max(members)
+    }
+}
+
 pub(super) fn reduce_ratio(mut p: i64, mut q: i64) -> (i64, u64) {
     use num_integer::Integer;
     let gcd = p.abs().gcd(&q.abs());
@@ -483,7 +589,7 @@ impl<'a> From<&'a Symbol> for TDim {
 impl ops::Neg for TDim {
     type Output = Self;
     fn neg(self) -> Self {
-        TDim::Mul(-1, Box::new(self)).reduce()
+        TDim::MulInt(-1, Box::new(self)).reduce()
     }
 }
 
@@ -560,7 +666,35 @@ impl<'a> ops::Sub<&'a TDim> for TDim {
 
 impl ops::MulAssign<i64> for TDim {
     fn mul_assign(&mut self, rhs: i64) {
-        *self = TDim::Mul(rhs, Box::new(std::mem::take(self))).reduce()
+        *self = TDim::MulInt(rhs, Box::new(std::mem::take(self))).reduce()
+    }
+}
+
+impl<'a> ops::MulAssign<&'a TDim> for TDim {
+    fn mul_assign(&mut self, rhs: &'a TDim) {
+        *self = TDim::Mul(vec![std::mem::take(self), rhs.clone()]).reduce()
+    }
+}
+
+impl ops::MulAssign<TDim> for TDim {
+    fn mul_assign(&mut self, rhs: TDim) {
+        *self *= &rhs
+    }
+}
+
+impl ops::Mul<TDim> for TDim {
+    type Output = Self;
+    fn mul(mut self, rhs: TDim) -> Self {
+        self *= rhs;
+        self
+    }
+}
+
+impl<'a> ops::Mul<&'a TDim> for TDim {
+    type Output = Self;
+    fn mul(mut self, rhs: &'a TDim) -> Self {
+        self *= rhs;
+        self
     }
 }
 
@@ -588,7 +722,7 @@ impl<I: AsPrimitive<u64>> ops::Div<I> for TDim {
 
 impl<I: AsPrimitive<u64>> ops::RemAssign<I> for TDim {
     fn rem_assign(&mut self, rhs: I) {
-        *self += -(self.clone() / rhs.as_() * rhs.as_());
+        *self = TDim::Mod(Box::new(std::mem::take(self)), rhs.as_()).reduce()
     }
 }
 
@@ -601,9 +735,9 @@ impl<I: AsPrimitive<u64>> ops::Rem<I> for TDim {
 }
 
 impl std::str::FromStr for TDim {
-    type Err = std::num::ParseIntError;
+    type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<TDim, Self::Err> {
-        s.parse::<i64>().map(|i| i.into())
+        super::parse::parse_tdim(s)
     }
 }
 
@@ -630,7 +764,7 @@ mod tests {
     }
 
     fn mul(a: i64, b: &TDim) -> TDim {
-        TDim::Mul(a, b![b.clone()])
+        TDim::MulInt(a, b![b.clone()])
     }
 
     fn div(a: &TDim, b: u64) -> TDim {
@@ -658,7 +792,7 @@ mod tests {
 
     #[test]
     fn reduce_cplx_ex_3() {
-        assert_eq!(div(&Mul(1, b!(Mul(4, b!(s())))), 4).reduce(), s())
+        assert_eq!(div(&MulInt(1, b!(MulInt(4, b!(s())))), 4).reduce(), s())
     }
 
     #[test]
@@ -811,4 +945,38 @@ mod tests {
         let e = (s() - 3 + 1).div_ceil(1);
         assert_eq!(e, s() + -2);
     }
+
+    #[test]
+    fn reduce_min_max_consts() {
+        assert_eq!(TDim::from(3).mini(5.into()), 3.into());
+        assert_eq!(TDim::from(3).maxi(5.into()), 5.into());
+        assert_eq!(s().mini(s() + 2), s());
+        assert_eq!(s().maxi(s() + 2), s() + 2);
+        assert_eq!(s().mini(4.into()).mini(2.into()), s().mini(2.into()));
+        assert_eq!(s().mini(4.into()), TDim::from(4).mini(s()));
+    }
+
+    #[test]
+    fn eval_min_max_mod() {
+        let values = SymbolValues::default().with(*S, 7);
+        assert_eq!(s().mini(4.into()).eval(&values), 4.into());
+        assert_eq!(s().maxi(4.into()).eval(&values), 7.into());
+        assert_eq!((s() % 4).eval(&values), 3.into());
+        assert_eq!((s() * s()).eval(&values), 49.into());
+    }
+
+    #[test]
+    fn reduce_mod() {
+        assert_eq!((s() * 4 + 1) % 2, 1.into());
+        assert_eq!((s() % 8) % 4, s() % 4);
+        assert_eq!((s() % 2) % 4, s() % 2);
+    }
+
+    #[test]
+    fn reduce_mul_sym_sym() {
+        let t: TDim = Symbol::new('T').into();
+        assert_eq!(s() * &t, t.clone() * &s());
+        assert_eq!(s() * &t + s() * &t, (s() * &t) * 2);
+        assert_eq!((s() * 2) * &(t.clone() * 3), s() * &t * 6);
+    }
 }
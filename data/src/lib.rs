@@ -14,7 +14,7 @@ pub type TVec<T> = smallvec::SmallVec<[T; 4]>;
 
 pub mod prelude {
     pub use crate::datum::{Blob, Datum, DatumType};
-    pub use crate::dim::{Symbol, SymbolValues, TDim};
+    pub use crate::dim::{Symbol, SymbolTable, SymbolValues, TDim};
     pub use crate::f16::*;
     pub use crate::tensor::litteral::*;
     pub use crate::tensor::{IntoArcTensor, IntoTensor, Tensor};
@@ -27,8 +27,9 @@ pub mod prelude {
 }
 
 pub mod internal {
-    pub use crate::dim::{DimLike, MaybeProduct, TDim, ToDim};
+    pub use crate::dim::{Assertion, DimLike, MaybeProduct, TDim, ToDim};
     pub use crate::prelude::*;
+    pub use crate::tensor::storage::{with_storage_offers, without_storage_offers, StorageOffer};
     pub use crate::tensor::view::TensorView;
     pub use ndarray as tract_ndarray;
 }
@@ -15,6 +15,7 @@ use std::ops::Range;
 use std::sync::Arc;
 
 pub mod litteral;
+pub mod storage;
 pub mod view;
 
 /// Tensor is a concrete tensor in tract.
@@ -24,6 +25,9 @@ pub struct Tensor {
     strides: TVec<isize>,
     layout: alloc::Layout,
     data: *mut u8,
+    /// Keeps alive the memory `data` points to, when the tensor does not own
+    /// it (see `from_raw_dt_owned_by`).
+    owner: Option<Arc<dyn std::any::Any + Send + Sync>>,
 }
 
 unsafe impl Send for Tensor {}
@@ -95,7 +99,7 @@ impl Drop for Tensor {
                     .for_each(|s| std::ptr::drop_in_place(s as *mut TDim));
             }
         }
-        if !self.data.is_null() && self.layout.size() > 0 {
+        if self.owner.is_none() && !self.data.is_null() && self.layout.size() > 0 {
             unsafe { alloc::dealloc(self.data, self.layout) }
         }
     }
@@ -112,6 +116,22 @@ impl Tensor {
         Self::uninitialized_aligned_dt(dt, shape, dt.alignment())
     }
 
+    /// Create an uninitialized tensor meant to be an operator output: it uses
+    /// the storage offered for an output of this type and shape if there is
+    /// one (see `storage::with_storage_offers`), and allocates otherwise.
+    ///
+    /// Operators must not use it for scratch space.
+    pub unsafe fn uninitialized_output_dt(
+        dt: DatumType,
+        shape: &[usize],
+    ) -> anyhow::Result<Tensor> {
+        if let Some(offer) = storage::take_offer(dt, shape) {
+            Self::from_raw_dt_owned_by(dt, shape, offer.data, offer.owner)
+        } else {
+            Self::uninitialized_dt(dt, shape)
+        }
+    }
+
     /// Create an uninitialized tensor with a given alignment (in bytes).
     pub unsafe fn uninitialized_aligned<T: Datum>(
         shape: &[usize],
@@ -143,7 +163,8 @@ impl Tensor {
             assert!(!ptr.is_null());
             ptr
         } as *mut u8;
-        let mut tensor = Tensor { strides: tvec!(), layout, dt, shape: shape.into(), data };
+        let mut tensor =
+            Tensor { strides: tvec!(), layout, dt, shape: shape.into(), data, owner: None };
         #[cfg(debug_assertions)]
         {
             if dt == DatumType::F32 {
@@ -256,6 +277,32 @@ impl Tensor {
         Ok(tensor)
     }
 
+    /// Create a tensor over memory owned by something else, without copying
+    /// it (for instance, a memory mapped file).
+    ///
+    /// `data` must be aligned for `dt`, hold the tensor items and stay valid
+    /// as long as `owner` is alive. It must be writable too, as operators may
+    /// work in place on tensors they own.
+    pub unsafe fn from_raw_dt_owned_by(
+        dt: DatumType,
+        shape: &[usize],
+        data: *mut u8,
+        owner: Arc<dyn std::any::Any + Send + Sync>,
+    ) -> anyhow::Result<Tensor> {
+        if !dt.is_copy() {
+            anyhow::bail!("Can not borrow storage for {:?} tensors", dt);
+        }
+        if data as usize % dt.alignment() != 0 {
+            anyhow::bail!("Misaligned data for {:?} tensor", dt);
+        }
+        let bytes = shape.iter().product::<usize>() * dt.size_of();
+        let layout = alloc::Layout::from_size_align(bytes, dt.alignment())?;
+        let mut tensor =
+            Tensor { strides: tvec!(), layout, dt, shape: shape.into(), data, owner: Some(owner) };
+        tensor.update_strides();
+        Ok(tensor)
+    }
+
     pub unsafe fn from_slice_align<T: Datum>(
         content: &[T],
         align: usize,
@@ -878,7 +925,8 @@ impl Tensor {
         let layout =
             alloc::Layout::from_size_align(vec.len() * size_of::<T>(), align_of::<T>()).unwrap();
         let data = Box::into_raw(vec) as *mut u8;
-        let mut t = Tensor { dt: T::datum_type(), shape, layout, data, strides: tvec!() };
+        let mut t =
+            Tensor { dt: T::datum_type(), shape, layout, data, strides: tvec!(), owner: None };
         t.update_strides();
         t
     }
@@ -890,6 +938,7 @@ impl Tensor {
                 data: data.as_ptr() as *mut u8,
                 shape: self.shape.clone(),
                 strides: self.strides.clone(),
+                owner: None,
                 ..*self
             };
             std::mem::forget(data);
@@ -900,6 +949,7 @@ impl Tensor {
                 data: data.as_ptr() as *mut u8,
                 shape: self.shape.clone(),
                 strides: self.strides.clone(),
+                owner: None,
                 ..*self
             };
             std::mem::forget(data);
@@ -0,0 +1,131 @@
+//! Storage offered to operator outputs.
+//!
+//! A plan evaluating an operator can offer it preallocated memory for its
+//! outputs: while the offers are installed on the current thread, output
+//! tensors allocated with `Tensor::uninitialized_output_dt` with the type and
+//! shape of an offer are built over its memory instead of allocating. Other
+//! allocations (scratch space, intermediate values) never use offers.
+use crate::datum::DatumType;
+use crate::TVec;
+use std::any::Any;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+/// Storage for an output of type `dt` and shape `shape`, kept alive by
+/// `owner`.
+pub struct StorageOffer {
+    pub dt: DatumType,
+    pub shape: TVec<usize>,
+    pub data: *mut u8,
+    pub owner: Arc<dyn Any + Send + Sync>,
+}
+
+thread_local! {
+    static OFFERS: RefCell<Vec<StorageOffer>> = RefCell::new(vec![]);
+}
+
+struct Restore(Option<Vec<StorageOffer>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            OFFERS.with(|offers| *offers.borrow_mut() = previous);
+        }
+    }
+}
+
+/// Run `f` with `offers` installed on the current thread. Each offer is used
+/// at most once. Offers left when `f` returns are dropped, and the previous
+/// offers (of an enclosing call) are restored.
+///
+/// Each offer `data` must be aligned for `dt`, writable for the tensor items,
+/// and stay valid as long as its `owner` is alive.
+pub unsafe fn with_storage_offers<R>(offers: Vec<StorageOffer>, f: impl FnOnce() -> R) -> R {
+    let previous = OFFERS.with(|o| std::mem::replace(&mut *o.borrow_mut(), offers));
+    let _restore = Restore(Some(previous));
+    f()
+}
+
+/// Run `f` hiding the offers of an enclosing call, for instance while
+/// running a nested plan.
+pub fn without_storage_offers<R>(f: impl FnOnce() -> R) -> R {
+    if OFFERS.with(|o| o.borrow().is_empty()) {
+        f()
+    } else {
+        unsafe { with_storage_offers(vec![], f) }
+    }
+}
+
+/// Take the offer for an output of type `dt` and shape `shape`.
+pub(crate) fn take_offer(dt: DatumType, shape: &[usize]) -> Option<StorageOffer> {
+    OFFERS
+        .try_with(|offers| {
+            let mut offers = offers.borrow_mut();
+            let ix = offers.iter().position(|o| o.dt == dt && &*o.shape == shape)?;
+            Some(offers.swap_remove(ix))
+        })
+        .ok()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn offer(buffer: &mut [f32], owner: &Arc<dyn Any + Send + Sync>) -> StorageOffer {
+        StorageOffer {
+            dt: f32::datum_type(),
+            shape: tvec!(buffer.len()),
+            data: buffer.as_mut_ptr() as *mut u8,
+            owner: owner.clone(),
+        }
+    }
+
+    #[test]
+    fn offer_is_used_once_by_outputs() -> anyhow::Result<()> {
+        let mut buffer = vec![0f32; 4];
+        let data = buffer.as_ptr();
+        let owner: Arc<dyn Any + Send + Sync> = Arc::new(());
+        let offers = vec![offer(&mut buffer, &owner)];
+        let (scratch, other, output, again) = unsafe {
+            with_storage_offers(offers, || -> anyhow::Result<_> {
+                let scratch = Tensor::uninitialized::<f32>(&[4])?;
+                let other = Tensor::uninitialized_output_dt(i32::datum_type(), &[4])?;
+                let output = Tensor::uninitialized_output_dt(f32::datum_type(), &[4])?;
+                let again = Tensor::uninitialized_output_dt(f32::datum_type(), &[4])?;
+                Ok((scratch, other, output, again))
+            })?
+        };
+        assert_ne!(scratch.as_ptr::<f32>()?, data);
+        assert_ne!(other.as_ptr::<i32>()? as *const f32, data);
+        assert_eq!(output.as_ptr::<f32>()?, data);
+        assert_ne!(again.as_ptr::<f32>()?, data);
+        assert_eq!(Arc::strong_count(&owner), 2);
+        drop(output);
+        assert_eq!(Arc::strong_count(&owner), 1);
+        let after = unsafe { Tensor::uninitialized_output_dt(f32::datum_type(), &[4])? };
+        assert_ne!(after.as_ptr::<f32>()?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn nested_runs_hide_offers() -> anyhow::Result<()> {
+        let mut buffer = vec![0f32; 4];
+        let data = buffer.as_ptr();
+        let owner: Arc<dyn Any + Send + Sync> = Arc::new(());
+        let offers = vec![offer(&mut buffer, &owner)];
+        let (inner, outer) = unsafe {
+            with_storage_offers(offers, || -> anyhow::Result<_> {
+                let inner = without_storage_offers(|| {
+                    Tensor::uninitialized_output_dt(f32::datum_type(), &[4])
+                })?;
+                let outer = Tensor::uninitialized_output_dt(f32::datum_type(), &[4])?;
+                Ok((inner, outer))
+            })?
+        };
+        assert_ne!(inner.as_ptr::<f32>()?, data);
+        assert_eq!(outer.as_ptr::<f32>()?, data);
+        Ok(())
+    }
+}
@@ -144,7 +144,7 @@ impl<'a> TryFrom<&'a InferenceFact> for TypedFact {
             (fact.datum_type.concretize(), fact.shape.concretize())
         {
             let shape = ShapeFact::from_dims(shape);
-            Ok(TypedFact { datum_type, shape, konst: fact.value.concretize() })
+            Ok(TypedFact { datum_type, shape, konst: fact.value.concretize(), quant: None })
         } else {
             bail!("Can not make a TypedFact out of {:?}", fact)
         }
@@ -44,6 +44,7 @@ impl InferenceModelExt for InferenceModel {
     /// Perform early transformation before going typed.
     fn incorporate(self) -> TractResult<InferenceModel> {
         let mut model = self;
+        let mut analysed = false;
         loop {
             let mut done_something = false;
             for p in crate::infer::optim::incorporate() {
@@ -52,12 +53,17 @@ impl InferenceModelExt for InferenceModel {
                     model.check_edges()?;
                 }
             }
+            if !done_something && analysed {
+                break;
+            }
+            // patches may need the facts of the nodes wired by previous ones
+            model = model.compact()?;
+            model.analyse(false)?;
+            analysed = true;
             if !done_something {
                 break;
             }
         }
-        model = model.compact()?;
-        model.analyse(false)?;
         Ok(model)
     }
 
@@ -34,6 +34,7 @@ pub mod ops {
     pub mod nn;
     pub use tract_core::ops::quant;
     pub mod scan;
+    pub mod sequence;
     pub mod source;
     pub mod unimpl;
 }
@@ -0,0 +1,322 @@
+use crate::infer::*;
+use crate::internal::*;
+use tract_core::ops::change_axes::perm_to_ops;
+use tract_core::ops::nn::DataFormat;
+
+/// How DepthToSpace splits the channel axis: depth, column, row (blocks
+/// outermost) or column, row, depth (blocks innermost).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DepthToSpaceMode {
+    DepthColumnRow,
+    ColumnRowDepth,
+}
+
+impl Default for DepthToSpaceMode {
+    fn default() -> DepthToSpaceMode {
+        DepthToSpaceMode::DepthColumnRow
+    }
+}
+
+/// Wire the moves from the `from` axes layout to the `to` one. Axes are
+/// labelled: n, c, h and w are the data axes, i and j the height and width
+/// block axes.
+fn wire_permutation(
+    prefix: &str,
+    model: &mut TypedModel,
+    wire: OutletId,
+    from: &[char],
+    to: &[char],
+) -> TractResult<OutletId> {
+    let perm: TVec<usize> =
+        to.iter().map(|label| from.iter().position(|l| l == label).unwrap()).collect();
+    let mut wire = wire;
+    for (ix, op) in perm_to_ops(&perm).into_iter().enumerate() {
+        wire = model.wire_node(format!("{}.perm-{}", prefix, ix), op, &[wire])?[0];
+    }
+    Ok(wire)
+}
+
+fn labels(fmt: DataFormat) -> TVec<char> {
+    match fmt {
+        DataFormat::NCHW => tvec!('n', 'c', 'h', 'w'),
+        DataFormat::NHWC => tvec!('n', 'h', 'w', 'c'),
+        DataFormat::CHW => tvec!('c', 'h', 'w'),
+        DataFormat::HWC => tvec!('h', 'w', 'c'),
+    }
+}
+
+/// Move blocks of channels to the spatial axes: channels are split in
+/// `block_size * block_size` blocks which are laid out in the height and
+/// width axes.
+///
+/// Lowered to a reshape, a sequence of moves and reshapes, for the axis
+/// change optimizer to cancel them with their neighbours.
+#[derive(Debug, Clone, new, Hash)]
+pub struct DepthToSpace {
+    pub block_size: usize,
+    pub mode: DepthToSpaceMode,
+    pub data_format: DataFormat,
+}
+
+impl_dyn_hash!(DepthToSpace);
+
+impl DepthToSpace {
+    fn output_shape(&self, shape: &[TDim]) -> TractResult<TVec<TDim>> {
+        let shape = self.data_format.shape(shape.iter().cloned().collect::<TVec<_>>())?;
+        let b = self.block_size;
+        let mut output = shape.shape.clone();
+        output[shape.c_axis()] = shape.c().clone() / (b * b) as u64;
+        for axis in shape.hw_axes() {
+            output[axis] = output[axis].clone() * b;
+        }
+        Ok(output)
+    }
+}
+
+impl Expansion for DepthToSpace {
+    fn name(&self) -> Cow<str> {
+        "DepthToSpace".into()
+    }
+
+    op_hir!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, labels(self.data_format).len() as i64)?;
+        s.equals(&outputs[0].rank, labels(self.data_format).len() as i64)?;
+        s.given(&inputs[0].shape, move |s, shape| {
+            s.equals(&outputs[0].shape, self.output_shape(&shape)?)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let input_shape = model.outlet_fact(inputs[0])?.shape.to_tvec();
+        let shape = self.data_format.shape(input_shape.clone())?;
+        let b = self.block_size.to_dim();
+        let c = shape.c().clone();
+        if c.to_usize().map(|c| c % (self.block_size * self.block_size) != 0).unwrap_or(false) {
+            bail!("DepthToSpace: {} channels are not divisible in {}x{} blocks", c, b, b)
+        }
+        let depth = c.clone() / (self.block_size * self.block_size) as u64;
+        let layout = labels(self.data_format);
+        let (split, blocks): (TVec<TDim>, &[char]) = match self.mode {
+            DepthToSpaceMode::DepthColumnRow => {
+                (tvec!(b.clone(), b.clone(), depth), &['i', 'j', 'c'])
+            }
+            DepthToSpaceMode::ColumnRowDepth => {
+                (tvec!(depth, b.clone(), b.clone()), &['c', 'i', 'j'])
+            }
+        };
+        let wire = model.wire_node(
+            format!("{}.split-channels", prefix),
+            AxisOp::Reshape(shape.c_axis(), tvec!(c), split),
+            inputs,
+        )?[0];
+        let from: TVec<char> =
+            layout.iter().flat_map(|&l| if l == 'c' { blocks.to_vec() } else { vec![l] }).collect();
+        let to: TVec<char> = layout
+            .iter()
+            .flat_map(|&l| match l {
+                'h' => vec!['h', 'i'],
+                'w' => vec!['w', 'j'],
+                l => vec![l],
+            })
+            .collect();
+        let mut wire = wire_permutation(prefix, model, wire, &from, &to)?;
+        for (ix, axis) in shape.hw_axes().enumerate() {
+            let at = to.iter().position(|&l| l == ['h', 'w'][ix]).unwrap() - ix;
+            let dim = input_shape[axis].clone();
+            wire = model.wire_node(
+                format!("{}.merge-{}", prefix, ['h', 'w'][ix]),
+                AxisOp::Reshape(at, tvec!(dim.clone(), b.clone()), tvec!(dim * &b)),
+                &[wire],
+            )?[0];
+        }
+        Ok(tvec!(wire))
+    }
+}
+
+/// Move blocks of the spatial axes to channels: the inverse of
+/// `DepthToSpace` in depth, column, row mode.
+///
+/// Lowered to reshapes, a sequence of moves and a reshape, for the axis
+/// change optimizer to cancel them with their neighbours.
+#[derive(Debug, Clone, new, Hash)]
+pub struct SpaceToDepth {
+    pub block_size: usize,
+    pub data_format: DataFormat,
+}
+
+impl_dyn_hash!(SpaceToDepth);
+
+impl SpaceToDepth {
+    fn output_shape(&self, shape: &[TDim]) -> TractResult<TVec<TDim>> {
+        let shape = self.data_format.shape(shape.iter().cloned().collect::<TVec<_>>())?;
+        let b = self.block_size;
+        let mut output = shape.shape.clone();
+        output[shape.c_axis()] = shape.c().clone() * (b * b);
+        for axis in shape.hw_axes() {
+            output[axis] = output[axis].clone() / b as u64;
+        }
+        Ok(output)
+    }
+}
+
+impl Expansion for SpaceToDepth {
+    fn name(&self) -> Cow<str> {
+        "SpaceToDepth".into()
+    }
+
+    op_hir!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, labels(self.data_format).len() as i64)?;
+        s.equals(&outputs[0].rank, labels(self.data_format).len() as i64)?;
+        s.given(&inputs[0].shape, move |s, shape| {
+            s.equals(&outputs[0].shape, self.output_shape(&shape)?)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let input_shape = model.outlet_fact(inputs[0])?.shape.to_tvec();
+        let shape = self.data_format.shape(input_shape.clone())?;
+        let b = self.block_size.to_dim();
+        let layout = labels(self.data_format);
+        let mut wire = inputs[0];
+        for (ix, axis) in shape.hw_axes().enumerate() {
+            let dim = input_shape[axis].clone();
+            if dim.to_usize().map(|d| d % self.block_size != 0).unwrap_or(false) {
+                bail!("SpaceToDepth: {} is not divisible in blocks of {}", dim, b)
+            }
+            wire = model.wire_node(
+                format!("{}.split-{}", prefix, ['h', 'w'][ix]),
+                AxisOp::Reshape(
+                    axis + ix,
+                    tvec!(dim.clone()),
+                    tvec!(dim / self.block_size as u64, b.clone()),
+                ),
+                &[wire],
+            )?[0];
+        }
+        let from: TVec<char> = layout
+            .iter()
+            .flat_map(|&l| match l {
+                'h' => vec!['h', 'i'],
+                'w' => vec!['w', 'j'],
+                l => vec![l],
+            })
+            .collect();
+        let to: TVec<char> = layout
+            .iter()
+            .flat_map(|&l| if l == 'c' { vec!['i', 'j', 'c'] } else { vec![l] })
+            .collect();
+        let wire = wire_permutation(prefix, model, wire, &from, &to)?;
+        let at = to.iter().position(|&l| l == 'i').unwrap();
+        let c = shape.c().clone();
+        model.wire_node(
+            format!("{}.merge-channels", prefix),
+            AxisOp::Reshape(at, tvec!(b.clone(), b.clone(), c.clone()), tvec!(c * &b * &b)),
+            &[wire],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(op: impl Expansion, input: Tensor) -> TractResult<Tensor> {
+        let mut model = InferenceModel::default();
+        let source =
+            model.add_source("input", InferenceFact::dt_shape(f32::datum_type(), input.shape()))?;
+        let output = model.wire_node("op", expand(op), &[source])?;
+        model.set_output_outlets(&output)?;
+        let mut outputs = model.into_typed()?.into_runnable()?.run(tvec!(input))?;
+        Ok(outputs.remove(0).into_tensor())
+    }
+
+    fn nchw_input() -> Tensor {
+        tensor1(&(0..16).map(|i| i as f32).collect::<Vec<_>>()).into_shape(&[1, 8, 1, 2]).unwrap()
+    }
+
+    #[test]
+    fn depth_to_space_dcr() -> TractResult<()> {
+        let op = DepthToSpace::new(2, DepthToSpaceMode::DepthColumnRow, DataFormat::NCHW);
+        let expected =
+            tensor1(&[0f32, 4., 1., 5., 8., 12., 9., 13., 2., 6., 3., 7., 10., 14., 11., 15.])
+                .into_shape(&[1, 2, 2, 4])?;
+        assert_eq!(run(op, nchw_input())?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn depth_to_space_crd() -> TractResult<()> {
+        let op = DepthToSpace::new(2, DepthToSpaceMode::ColumnRowDepth, DataFormat::NCHW);
+        let expected =
+            tensor1(&[0f32, 2., 1., 3., 4., 6., 5., 7., 8., 10., 9., 11., 12., 14., 13., 15.])
+                .into_shape(&[1, 2, 2, 4])?;
+        assert_eq!(run(op, nchw_input())?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn space_to_depth_inverts_depth_to_space() -> TractResult<()> {
+        for &fmt in &[DataFormat::NCHW, DataFormat::NHWC] {
+            let input = nchw_input().into_shape(&[1, 2, 2, 4])?;
+            let op = SpaceToDepth::new(2, fmt);
+            let space = run(op, input.clone())?;
+            let op = DepthToSpace::new(2, DepthToSpaceMode::DepthColumnRow, fmt);
+            assert_eq!(run(op, space)?, input);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn depth_to_space_pulsify_on_batch() -> TractResult<()> {
+        use tract_pulse::internal::{stream_dim, PulsedModel, PulsedModelExt};
+        let mut model = InferenceModel::default();
+        let fact = InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(S, 8, 1, 2));
+        let source = model.add_source("input", fact)?;
+        let op = DepthToSpace::new(2, DepthToSpaceMode::DepthColumnRow, DataFormat::NCHW);
+        let output = model.wire_node("op", expand(op), &[source])?;
+        model.set_output_outlets(&output)?;
+        let typed = model.into_typed()?.declutter()?;
+        assert_eq!(typed.output_fact(0)?.shape[0], stream_dim());
+        let pulsed = PulsedModel::new(&typed, 1)?;
+        let fact = pulsed.output_fact(0)?;
+        assert_eq!(fact.axis, 0);
+        assert_eq!(fact.delay, 0);
+        let mut outputs = pulsed.into_typed()?.into_runnable()?.run(tvec!(nchw_input()))?;
+        let expected = run(
+            DepthToSpace::new(2, DepthToSpaceMode::DepthColumnRow, DataFormat::NCHW),
+            nchw_input(),
+        )?;
+        assert_eq!(outputs.remove(0).into_tensor(), expected);
+        Ok(())
+    }
+}
@@ -4,10 +4,12 @@ mod concat;
 mod constant_like;
 mod constant_of_shape;
 mod crop;
+mod depth_to_space;
 mod flatten;
 mod gather;
 mod pad;
 pub mod permute_axes;
+mod range;
 mod reshape;
 mod rm_dims;
 mod shape;
@@ -24,10 +26,12 @@ pub use concat::{Concat, ConcatSlice, TypedConcat};
 pub use constant_like::{ConstantLike, EyeLike};
 pub use constant_of_shape::ConstantOfShape;
 pub use crop::Crop;
+pub use depth_to_space::{DepthToSpace, DepthToSpaceMode, SpaceToDepth};
 pub use flatten::Flatten;
 pub use gather::Gather;
 pub use pad::{Pad, PadMode};
 pub use permute_axes::PermuteAxes;
+pub use range::Range;
 pub use reshape::Reshape;
 pub use rm_dims::RmDims;
 pub use shape::Shape;
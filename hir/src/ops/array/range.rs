@@ -0,0 +1,44 @@
+use crate::infer::*;
+use crate::internal::*;
+
+/// Range of numbers from scalar `start`, `limit` and `delta` inputs.
+///
+/// The typed op length is an expression of the inputs when they are known
+/// at typing time, a fresh symbol otherwise.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct Range;
+impl_dyn_hash!(Range);
+
+impl Expansion for Range {
+    fn name(&self) -> Cow<str> {
+        "Range".into()
+    }
+
+    op_hir!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        for input in inputs {
+            s.equals(&input.rank, 0)?;
+        }
+        s.equals(&outputs[0].rank, 1)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let op = tract_core::ops::array::Range::for_inputs(model, inputs)?;
+        model.wire_node(prefix, op, inputs)
+    }
+}
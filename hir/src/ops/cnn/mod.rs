@@ -3,4 +3,7 @@ mod pools;
 
 pub use conv::Conv;
 pub use pools::{MaxPool, SumPool};
-pub use tract_core::ops::cnn::{ConvUnary, PaddingSpec, PoolSpec};
+pub use tract_core::ops::cnn::{
+    ConvUnary, DeconvUnary, KernelFormat, PaddingSpec, PoolSpec, RoiAlign, RoiPool, RoiPoolMode,
+    RoiResample, SamplingMethod,
+};
@@ -4,6 +4,9 @@ use crate::internal::*;
 use tract_core::broadcast::multi_broadcast;
 pub use tract_core::ops::logic::*;
 
+mod if_then_else;
+pub use if_then_else::InferenceIf;
+
 impl InferenceRulesOp for Iff {
     fn rules<'r, 'p: 'r, 's: 'r>(
         &'s self,
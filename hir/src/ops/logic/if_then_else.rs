@@ -0,0 +1,244 @@
+use crate::infer::*;
+use crate::internal::*;
+
+pub use tract_core::ops::logic::If;
+
+/// Inference counterpart of core `If`: the branches are inference models,
+/// typed once the outer facts are known.
+#[derive(Debug, Clone, new, Hash)]
+pub struct InferenceIf {
+    pub then_body: InferenceModel,
+    pub then_input_mapping: Vec<usize>,
+    pub else_body: InferenceModel,
+    pub else_input_mapping: Vec<usize>,
+}
+
+impl_dyn_hash!(InferenceIf);
+
+impl InferenceIf {
+    fn branch(&self, cond: bool) -> (&InferenceModel, &[usize]) {
+        if cond {
+            (&self.then_body, &self.then_input_mapping)
+        } else {
+            (&self.else_body, &self.else_input_mapping)
+        }
+    }
+
+    fn analyse_branch(
+        body: &mut InferenceModel,
+        mapping: &[usize],
+        inputs: &[InferenceFact],
+    ) -> TractResult<()> {
+        for (ix, slot) in mapping.iter().enumerate() {
+            let mut outer = inputs
+                .get(*slot)
+                .with_context(|| format!("If branch wants input #{}", slot))?
+                .clone();
+            outer.unify_with_mut(body.input_fact_mut(ix)?)?;
+        }
+        body.analyse(false).context("analysing If branch")?;
+        Ok(())
+    }
+}
+
+impl Op for InferenceIf {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    op_hir!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for InferenceIf {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (body, mapping) = self.branch(inputs[0].cast_to_scalar::<bool>()?);
+        let inputs: TVec<Tensor> =
+            mapping.iter().map(|slot| inputs[*slot].clone().into_tensor()).collect();
+        InferenceSimplePlan::new(body)?.run(inputs)
+    }
+}
+
+impl InferenceOp for InferenceIf {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        let cond = inputs[0].value.concretize().map(|c| c.cast_to_scalar::<bool>()).transpose()?;
+        if let Some(cond) = cond {
+            let (mut body, mapping) = {
+                let (body, mapping) = self.branch(cond);
+                (body.clone(), mapping.to_vec())
+            };
+            Self::analyse_branch(&mut body, &mapping, &inputs)?;
+            for (ix, output) in outputs.iter_mut().enumerate() {
+                output.unify_with(body.output_fact(ix)?)?;
+            }
+            if cond {
+                self.then_body = body;
+            } else {
+                self.else_body = body;
+            }
+        } else {
+            Self::analyse_branch(&mut self.then_body, &self.then_input_mapping, &inputs)?;
+            Self::analyse_branch(&mut self.else_body, &self.else_input_mapping, &inputs)?;
+            for (ix, output) in outputs.iter_mut().enumerate() {
+                let then_fact = self.then_body.output_fact(ix)?;
+                let else_fact = self.else_body.output_fact(ix)?;
+                output.datum_type.unify_with(&then_fact.datum_type)?;
+                output.datum_type.unify_with(&else_fact.datum_type)?;
+                if then_fact.shape == else_fact.shape {
+                    output.shape.unify_with(&then_fact.shape)?;
+                }
+            }
+        }
+        Ok((inputs, outputs, observed.into_iter().cloned().collect()))
+    }
+
+    fn incorporate(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+    ) -> TractResult<Option<InferenceModelPatch>> {
+        let cond = if let Some(cond) = model.outlet_fact(node.inputs[0])?.value.concretize() {
+            cond.cast_to_scalar::<bool>()?
+        } else {
+            return Ok(None);
+        };
+        let (body, mapping) = self.branch(cond);
+        let mut patch = InferenceModelPatch::default();
+        let mut wires: HashMap<OutletId, OutletId> = HashMap::new();
+        for (ix, input) in body.input_outlets()?.iter().enumerate() {
+            wires.insert(*input, patch.tap_model(model, node.inputs[mapping[ix]])?);
+        }
+        for id in body.eval_order()? {
+            if body.input_outlets()?.iter().any(|i| i.node == id) {
+                continue;
+            }
+            let inner = body.node(id);
+            let inputs = inner.inputs.iter().map(|i| wires[i]).collect::<TVec<_>>();
+            let outputs = patch.wire_node(
+                format!("{}.{}", node.name, inner.name),
+                inner.op.clone(),
+                &inputs,
+            )?;
+            for (slot, o) in outputs.into_iter().enumerate() {
+                wires.insert(OutletId::new(id, slot), o);
+            }
+        }
+        for (ix, output) in body.output_outlets()?.iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(node.id, ix), wires[output])?;
+        }
+        Ok(Some(patch))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let typed_branch = |body: &InferenceModel, body_mapping: &[usize]| {
+            let mut body = body.clone();
+            for (ix, slot) in body_mapping.iter().enumerate() {
+                let fact = InferenceFact::from(target.outlet_fact(inputs[*slot])?).without_value();
+                body.set_input_fact(ix, fact)?;
+            }
+            body.into_typed().with_context(|| format!("typing branch of {}", node.name))
+        };
+        let op = If::new(
+            typed_branch(&self.then_body, &self.then_input_mapping)?,
+            self.then_input_mapping.clone(),
+            typed_branch(&self.else_body, &self.else_input_mapping)?,
+            self.else_input_mapping.clone(),
+        )?;
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.then_body.output_outlets()?.len())
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::binary::IntoHir;
+
+    fn branch(op: Box<dyn InferenceOp>) -> TractResult<InferenceModel> {
+        let mut model = InferenceModel::default();
+        let a = model.add_source("a", InferenceFact::default())?;
+        let b = model.add_source("b", InferenceFact::default())?;
+        let output = model.wire_node("op", op, &[a, b])?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    fn if_model() -> TractResult<InferenceModel> {
+        let op = InferenceIf::new(
+            branch(tract_core::ops::math::Add.into_hir())?,
+            vec![1, 2],
+            branch(tract_core::ops::math::Sub.into_hir())?,
+            vec![2, 1],
+        );
+        let mut model = InferenceModel::default();
+        let cond = model
+            .add_source("cond", InferenceFact::dt_shape(bool::datum_type(), shapefactoid!()))?;
+        let a = model.add_source("a", InferenceFact::dt_shape(f32::datum_type(), tvec!(2)))?;
+        let b = model.add_source("b", InferenceFact::dt_shape(f32::datum_type(), tvec!(2)))?;
+        let output = model.wire_node("if", op, &[cond, a, b])?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn branches_with_different_values() -> TractResult<()> {
+        let args = |c: bool| tvec!(tensor0(c), tensor1(&[2f32, 3.]), tensor1(&[4f32, 6.]));
+        let mut model = if_model()?;
+        model.analyse(false)?;
+        let plan = SimplePlan::new(&model)?;
+        assert_eq!(*plan.run(args(true))?[0], tensor1(&[6f32, 9.]));
+        assert_eq!(*plan.run(args(false))?[0], tensor1(&[2f32, 3.]));
+        let typed = model.into_typed()?;
+        assert!(typed.nodes().iter().any(|n| n.op_is::<If>()));
+        let plan = SimplePlan::new(typed)?;
+        assert_eq!(*plan.run(args(true))?[0], tensor1(&[6f32, 9.]));
+        assert_eq!(*plan.run(args(false))?[0], tensor1(&[2f32, 3.]));
+        Ok(())
+    }
+
+    #[test]
+    fn branches_with_different_shapes() -> TractResult<()> {
+        let mut then_body = InferenceModel::default();
+        let a = then_body.add_source("a", InferenceFact::default())?;
+        then_body.set_output_outlets(&[a])?;
+        let mut else_body = InferenceModel::default();
+        let a = else_body.add_source("a", InferenceFact::default())?;
+        let b = else_body.add_source("b", InferenceFact::default())?;
+        let concat =
+            else_body.wire_node("concat", expand(crate::ops::array::Concat::new(0)), &[a, b])?;
+        else_body.set_output_outlets(&concat)?;
+        let op = InferenceIf::new(then_body, vec![1], else_body, vec![1, 2]);
+        let mut model = InferenceModel::default();
+        let cond = model
+            .add_source("cond", InferenceFact::dt_shape(bool::datum_type(), shapefactoid!()))?;
+        let a = model.add_source("a", InferenceFact::dt_shape(f32::datum_type(), tvec!(2)))?;
+        let b = model.add_source("b", InferenceFact::dt_shape(f32::datum_type(), tvec!(2)))?;
+        let output = model.wire_node("if", op, &[cond, a, b])?;
+        model.set_output_outlets(&output)?;
+        assert!(model.into_typed().is_err());
+        Ok(())
+    }
+}
@@ -6,4 +6,6 @@ pub use global_pools::*;
 pub use layer_max::*;
 pub use reduce::{Reduce, Reducer};
 
-pub use tract_core::ops::nn::{sigmoid, DataFormat};
+pub use tract_core::ops::nn::{
+    non_max_suppression, sigmoid, BoxRepr, DataFormat, NonMaxSuppression,
+};
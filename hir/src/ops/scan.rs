@@ -76,13 +76,18 @@ impl InferenceScan {
             .iter()
             .enumerate()
             .map(|(ix, im)| {
+                let chunk = if im.full_slot.is_some() {
+                    typed_model.output_fact(ix)?.shape[im.axis].to_isize()?
+                } else {
+                    im.chunk
+                };
                 Ok(OutputMapping {
                     state: im.state,
                     axis: im.axis,
                     full_slot: im.full_slot,
                     full_dim_hint: im.full_dim_hint.clone(),
                     last_value_slot: im.last_value_slot,
-                    chunk: typed_model.input_fact(ix)?.shape[im.axis].to_isize()?,
+                    chunk,
                 })
             })
             .collect::<TractResult<_>>()?;
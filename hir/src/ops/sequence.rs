@@ -0,0 +1,438 @@
+//! Sequences of tensors, and optional tensors (sequences of at most one
+//! tensor).
+//!
+//! There is no sequence value at runtime: the ops are lowered during
+//! incorporation to ops working on the sequence elements, as soon as the
+//! sequence lengths and positions are known. A sequence is always built by a
+//! `Construct`, the other ops producing sequences are first rewritten as a
+//! `Construct` of the new elements.
+use crate::infer::*;
+use crate::internal::*;
+
+use crate::ops::array::{AddDims, Concat, RmDims, Slice};
+use crate::ops::identity::Identity;
+use crate::ops::konst::Const;
+
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum SequenceOp {
+    /// Builds a sequence of its inputs.
+    Construct,
+    /// Picks an element of a sequence: inputs are the sequence and a
+    /// position.
+    At,
+    /// Inserts a tensor in a sequence: inputs are the sequence, the tensor
+    /// and an optional position (default to the end).
+    Insert,
+    /// Removes an element from a sequence: inputs are the sequence and an
+    /// optional position (default to the last element).
+    Erase,
+    /// Number of elements, as a I64 scalar.
+    Length,
+    /// Splits a tensor in a sequence of chunks along `axis`. The optional
+    /// second input is either a scalar chunk size or the list of chunk
+    /// sizes. Without it, the tensor is split in chunks of size one and
+    /// `keep_dims` says if the axis is kept in them.
+    Split { axis: isize, keep_dims: bool },
+    /// Concatenates the elements along `axis`, or stacks them along a new
+    /// `axis` if `new_axis` is set.
+    Concat { axis: isize, new_axis: bool },
+    /// Whether an optional has an element, as a boolean scalar.
+    HasElement,
+    /// Element of an optional.
+    GetElement,
+}
+
+impl_dyn_hash!(SequenceOp);
+
+/// Elements of the sequence at `outlet`, if it is built by a `Construct`.
+fn elements(model: &InferenceModel, outlet: OutletId) -> Option<&[OutletId]> {
+    let node = model.node(outlet.node);
+    if node.op_as::<SequenceOp>() == Some(&SequenceOp::Construct) {
+        Some(&node.inputs)
+    } else {
+        None
+    }
+}
+
+fn is_sequence(model: &InferenceModel, outlet: OutletId) -> bool {
+    model.node(outlet.node).op_as::<SequenceOp>().is_some()
+}
+
+/// Value of a constant scalar input.
+fn scalar(model: &InferenceModel, outlet: OutletId) -> TractResult<Option<i64>> {
+    model.outlet_fact(outlet)?.value.concretize().map(|v| v.cast_to_scalar::<i64>()).transpose()
+}
+
+/// Resolves a possibly negative position in a sequence of length `len`.
+/// Positions one past the end are valid for insertion.
+fn position(pos: i64, len: usize, insertion: bool) -> TractResult<usize> {
+    let max = len as i64 + insertion as i64;
+    if -(len as i64) <= pos && pos < max {
+        Ok(if pos < 0 { pos + len as i64 } else { pos } as usize)
+    } else {
+        bail!("Position {} out of a sequence of {} elements", pos, len)
+    }
+}
+
+impl SequenceOp {
+    /// Rewrites the op as a `Construct` of `elements`.
+    fn construct(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+        elements: &[OutletId],
+    ) -> TractResult<Option<InferenceModelPatch>> {
+        let mut patch = InferenceModelPatch::default();
+        let taps = elements
+            .iter()
+            .map(|e| patch.tap_model(model, *e))
+            .collect::<TractResult<TVec<_>>>()?;
+        let wire = patch.wire_node(&*node.name, SequenceOp::Construct, &taps)?;
+        patch.shunt_outside(model, OutletId::new(node.id, 0), wire[0])?;
+        Ok(Some(patch))
+    }
+
+    /// Rewrites the op as `op` applied to `inputs`.
+    fn replace_by(
+        model: &InferenceModel,
+        node: &InferenceNode,
+        op: impl Into<Box<dyn InferenceOp>>,
+        inputs: &[OutletId],
+    ) -> TractResult<Option<InferenceModelPatch>> {
+        let mut patch = InferenceModelPatch::default();
+        let taps =
+            inputs.iter().map(|i| patch.tap_model(model, *i)).collect::<TractResult<TVec<_>>>()?;
+        let wire = patch.wire_node(&*node.name, op, &taps)?;
+        patch.shunt_outside(model, OutletId::new(node.id, 0), wire[0])?;
+        Ok(Some(patch))
+    }
+
+    fn split(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+        axis: isize,
+        keep_dims: bool,
+    ) -> TractResult<Option<InferenceModelPatch>> {
+        let fact = model.outlet_fact(node.inputs[0])?;
+        let rank = if let Some(rank) = fact.shape.rank().concretize() {
+            rank as usize
+        } else {
+            return Ok(None);
+        };
+        let axis = if axis < 0 { axis + rank as isize } else { axis } as usize;
+        let dim = if let Some(dim) = fact.shape.dims().nth(axis).and_then(|d| d.concretize()) {
+            dim.to_usize()?
+        } else {
+            return Ok(None);
+        };
+        let split = if let Some(split) = node.inputs.get(1) {
+            if let Some(split) = model.outlet_fact(*split)?.value.concretize() {
+                Some(split.cast_to::<i64>()?.into_owned())
+            } else {
+                return Ok(None);
+            }
+        } else {
+            None
+        };
+        let sizes: Vec<usize> = match split {
+            Some(ref split) if split.rank() == 0 => {
+                let size = *split.to_scalar::<i64>()? as usize;
+                (0..dim).step_by(size.max(1)).map(|start| size.min(dim - start)).collect()
+            }
+            Some(ref split) => split.as_slice::<i64>()?.iter().map(|s| *s as usize).collect(),
+            None => vec![1; dim],
+        };
+        if sizes.iter().sum::<usize>() != dim {
+            bail!("Can not split axis {} of {} in chunks of {:?}", axis, dim, sizes)
+        }
+        let mut patch = InferenceModelPatch::default();
+        let input = patch.tap_model(model, node.inputs[0])?;
+        let mut start = 0;
+        let mut chunks = tvec!();
+        for (ix, size) in sizes.iter().enumerate() {
+            let name = format!("{}.chunk-{}", node.name, ix);
+            let mut wire =
+                patch.wire_node(&*name, Slice::new(axis, start, start + size), &[input])?;
+            if split.is_none() && !keep_dims {
+                wire = patch.wire_node(
+                    format!("{}.rm_axis", name),
+                    expand(RmDims::new(vec![axis as isize])),
+                    &wire,
+                )?;
+            }
+            chunks.push(wire[0]);
+            start += size;
+        }
+        let wire = patch.wire_node(&*node.name, SequenceOp::Construct, &chunks)?;
+        patch.shunt_outside(model, OutletId::new(node.id, 0), wire[0])?;
+        Ok(Some(patch))
+    }
+
+    fn concat(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+        elements: &[OutletId],
+        axis: isize,
+        new_axis: bool,
+    ) -> TractResult<Option<InferenceModelPatch>> {
+        if elements.len() == 0 {
+            bail!("Can not concatenate an empty sequence")
+        }
+        let rank = if let Some(rank) = model.outlet_fact(elements[0])?.shape.rank().concretize() {
+            rank as isize
+        } else {
+            return Ok(None);
+        };
+        let axis = if new_axis && axis < 0 { axis + rank + 1 } else { axis };
+        let mut patch = InferenceModelPatch::default();
+        let mut inputs = tvec!();
+        for (ix, e) in elements.iter().enumerate() {
+            let mut wire = patch.tap_model(model, *e)?;
+            if new_axis {
+                wire = patch.wire_node(
+                    format!("{}.add_axis-{}", node.name, ix),
+                    expand(AddDims::new(vec![axis])),
+                    &[wire],
+                )?[0];
+            }
+            inputs.push(wire);
+        }
+        let wire = patch.wire_node(&*node.name, expand(Concat::new(axis as i64)), &inputs)?;
+        patch.shunt_outside(model, OutletId::new(node.id, 0), wire[0])?;
+        Ok(Some(patch))
+    }
+}
+
+impl Op for SequenceOp {
+    fn name(&self) -> Cow<str> {
+        match self {
+            SequenceOp::Split { .. } => "SequenceSplit".into(),
+            SequenceOp::Concat { .. } => "SequenceConcat".into(),
+            _ => format!("Sequence{:?}", self).into(),
+        }
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(match self {
+            SequenceOp::Split { axis, keep_dims } => {
+                vec![format!("axis: {} keep_dims: {}", axis, keep_dims)]
+            }
+            SequenceOp::Concat { axis, new_axis } => {
+                vec![format!("axis: {} new_axis: {}", axis, new_axis)]
+            }
+            _ => vec![],
+        })
+    }
+
+    op_hir!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for SequenceOp {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+}
+
+impl InferenceRulesOp for SequenceOp {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_output_arity(&outputs, 1)?;
+        match self {
+            SequenceOp::Length => {
+                check_input_arity(&inputs, 1)?;
+                s.equals(&outputs[0].datum_type, i64::datum_type())?;
+                s.equals(&outputs[0].rank, 0)?;
+            }
+            SequenceOp::HasElement => {
+                check_input_arity(&inputs, 1)?;
+                s.equals(&outputs[0].datum_type, bool::datum_type())?;
+                s.equals(&outputs[0].rank, 0)?;
+            }
+            SequenceOp::Construct => {
+                for input in inputs.iter().skip(1) {
+                    s.equals(&input.datum_type, &inputs[0].datum_type)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn incorporate(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+    ) -> TractResult<Option<InferenceModelPatch>> {
+        if let SequenceOp::Split { axis, keep_dims } = *self {
+            return self.split(model, node, axis, keep_dims);
+        }
+        if *self == SequenceOp::Construct {
+            return Ok(None);
+        }
+        let input = node.inputs[0];
+        let seq = if let Some(seq) = elements(model, input) {
+            seq
+        } else if !is_sequence(model, input) {
+            // optionals can be given a plain tensor
+            return match self {
+                SequenceOp::HasElement => {
+                    Self::replace_by(model, node, Const(rctensor0(true)), &[])
+                }
+                SequenceOp::GetElement => {
+                    Self::replace_by(model, node, Identity::default(), &[input])
+                }
+                _ => bail!("{} input is not a sequence", node),
+            };
+        } else {
+            return Ok(None);
+        };
+        let pos =
+            if let Some(pos) = node.inputs.get(if *self == SequenceOp::Insert { 2 } else { 1 }) {
+                if let Some(pos) = scalar(model, *pos)? {
+                    Some(pos)
+                } else {
+                    return Ok(None);
+                }
+            } else {
+                None
+            };
+        match self {
+            SequenceOp::At => {
+                let pos = pos.context("SequenceAt expects a position")?;
+                let element = seq[position(pos, seq.len(), false)?];
+                Self::replace_by(model, node, Identity::default(), &[element])
+            }
+            SequenceOp::Insert => {
+                let pos = position(pos.unwrap_or(seq.len() as i64), seq.len(), true)?;
+                let mut seq = seq.to_vec();
+                seq.insert(pos, node.inputs[1]);
+                self.construct(model, node, &seq)
+            }
+            SequenceOp::Erase => {
+                let pos = position(pos.unwrap_or(-1), seq.len(), false)?;
+                let mut seq = seq.to_vec();
+                seq.remove(pos);
+                self.construct(model, node, &seq)
+            }
+            SequenceOp::Length => {
+                Self::replace_by(model, node, Const(rctensor0(seq.len() as i64)), &[])
+            }
+            SequenceOp::Concat { axis, new_axis } => {
+                self.concat(model, node, seq, *axis, *new_axis)
+            }
+            SequenceOp::HasElement => {
+                Self::replace_by(model, node, Const(rctensor0(seq.len() > 0)), &[])
+            }
+            SequenceOp::GetElement => {
+                let element = seq.get(0).context("Getting the element of an empty optional")?;
+                Self::replace_by(model, node, Identity::default(), &[*element])
+            }
+            SequenceOp::Construct | SequenceOp::Split { .. } => unreachable!(),
+        }
+    }
+
+    as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        _target: &mut TypedModel,
+        _mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        bail!(
+            "{} could not be lowered: sequence lengths and positions must be known \
+             when the model is loaded",
+            node
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(model: InferenceModel, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        model.into_optimized()?.into_runnable()?.run(inputs)
+    }
+
+    #[test]
+    fn construct_insert_at() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let a = model.add_source("a", InferenceFact::dt_shape(f32::datum_type(), &[2]))?;
+        let b = model.add_const("b", tensor1(&[3f32, 4.]))?;
+        let seq = model.wire_node("seq", SequenceOp::Construct, &[a])?;
+        let pos = model.add_const("pos", tensor0(0i64))?;
+        let seq = model.wire_node("insert", SequenceOp::Insert, &[seq[0], b, pos])?;
+        let one = model.add_const("one", tensor0(-1i64))?;
+        let at = model.wire_node("at", SequenceOp::At, &[seq[0], one])?;
+        let len = model.wire_node("len", SequenceOp::Length, &seq)?;
+        model.set_output_outlets(&[at[0], len[0]])?;
+        let outputs = run(model, tvec!(tensor1(&[1f32, 2.])))?;
+        assert_eq!(*outputs[0], tensor1(&[1f32, 2.]));
+        assert_eq!(*outputs[1], tensor0(2i64));
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_concat() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let a = model.add_source("a", InferenceFact::dt_shape(f32::datum_type(), &[2, 3]))?;
+        let seq =
+            model.wire_node("split", SequenceOp::Split { axis: -1, keep_dims: false }, &[a])?;
+        let seq = model.wire_node("erase", SequenceOp::Erase, &seq)?;
+        let stacked =
+            model.wire_node("stack", SequenceOp::Concat { axis: 0, new_axis: true }, &seq)?;
+        let size = model.add_const("size", tensor0(2i64))?;
+        let seq = model.wire_node(
+            "chunks",
+            SequenceOp::Split { axis: 1, keep_dims: true },
+            &[a, size],
+        )?;
+        let concat =
+            model.wire_node("concat", SequenceOp::Concat { axis: -1, new_axis: false }, &seq)?;
+        model.set_output_outlets(&[stacked[0], concat[0]])?;
+        let outputs = run(model, tvec!(tensor2(&[[1f32, 2., 3.], [4., 5., 6.]])))?;
+        assert_eq!(*outputs[0], tensor2(&[[1f32, 4.], [2., 5.]]));
+        assert_eq!(*outputs[1], tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn optional() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let a = model.add_source("a", InferenceFact::dt_shape(f32::datum_type(), &[2]))?;
+        let none = model.wire_node("none", SequenceOp::Construct, &[])?;
+        let some = model.wire_node("some", SequenceOp::Construct, &[a])?;
+        let has_none = model.wire_node("has_none", SequenceOp::HasElement, &none)?;
+        let has_some = model.wire_node("has_some", SequenceOp::HasElement, &some)?;
+        let has_tensor = model.wire_node("has_tensor", SequenceOp::HasElement, &[a])?;
+        let get = model.wire_node("get", SequenceOp::GetElement, &some)?;
+        model.set_output_outlets(&[has_none[0], has_some[0], has_tensor[0], get[0]])?;
+        let outputs = run(model, tvec!(tensor1(&[1f32, 2.])))?;
+        assert_eq!(*outputs[0], tensor0(false));
+        assert_eq!(*outputs[1], tensor0(true));
+        assert_eq!(*outputs[2], tensor0(true));
+        assert_eq!(*outputs[3], tensor1(&[1f32, 2.]));
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_position() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let a = model.add_source("a", InferenceFact::dt_shape(f32::datum_type(), &[2]))?;
+        let pos = model.add_source("pos", InferenceFact::dt_shape(i64::datum_type(), &[0; 0]))?;
+        let seq = model.wire_node("seq", SequenceOp::Construct, &[a])?;
+        let at = model.wire_node("at", SequenceOp::At, &[seq[0], pos])?;
+        model.set_output_outlets(&at)?;
+        assert!(model.into_typed().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! Evaluation of whole utterances, chunk by chunk, the way `nnet3-compute`
+//! does it.
+use tract_hir::internal::*;
+use tract_ndarray::{Axis, Ix2};
+
+use crate::model::{Kaldi, KaldiProtoModel, TimeSpan};
+
+/// Splits utterances in chunks of `frames_per_chunk` output frames, pads
+/// each chunk with the left and right context the model needs (repeating the
+/// first and last frames of the utterance at its edges), and computes every
+/// chunk from scratch: recurrent models do not carry their state from one
+/// chunk to the next.
+///
+/// With a `frame_subsampling_factor`, outputs are computed for the frames at
+/// a multiple of the factor.
+#[derive(Debug)]
+pub struct ChunkedComputation {
+    model: InferenceModel,
+    /// Plans for the model, by number of input frames.
+    plans: HashMap<usize, TypedSimplePlan<TypedModel>>,
+    output_spans: Vec<TimeSpan>,
+    frame_subsampling_factor: usize,
+    frames_per_chunk: usize,
+    left_context: usize,
+    right_context: usize,
+}
+
+impl ChunkedComputation {
+    pub fn new(
+        kaldi: &Kaldi,
+        proto_model: &KaldiProtoModel,
+        frames_per_chunk: usize,
+    ) -> TractResult<ChunkedComputation> {
+        let subsampling = proto_model.frame_subsampling_factor.max(1);
+        if frames_per_chunk == 0 {
+            bail!("frames_per_chunk must be positive")
+        }
+        let output_spans = proto_model.output_spans()?;
+        let (left_context, right_context) = proto_model.context()?;
+        // the first input frame of a chunk must be at a multiple of the
+        // subsampling factor for the downsampling in the model to pick the
+        // right frames
+        let left_context = (left_context + subsampling - 1) / subsampling * subsampling;
+        let model = kaldi.model_for_proto_model(proto_model)?;
+        Ok(ChunkedComputation {
+            model,
+            plans: HashMap::default(),
+            output_spans,
+            frame_subsampling_factor: subsampling,
+            // chunks are made of whole subsampled frames
+            frames_per_chunk: (frames_per_chunk + subsampling - 1) / subsampling * subsampling,
+            left_context,
+            right_context,
+        })
+    }
+
+    fn plan(&mut self, inputs: &[Tensor]) -> TractResult<&TypedSimplePlan<TypedModel>> {
+        let frames = inputs[0].shape()[0];
+        if !self.plans.contains_key(&frames) {
+            let mut model = self.model.clone();
+            for (ix, input) in inputs.iter().enumerate() {
+                let fact = InferenceFact::dt_shape(f32::datum_type(), input.shape());
+                model = model.with_input_fact(ix, fact)?;
+            }
+            self.plans.insert(frames, SimplePlan::new(model.into_optimized()?)?);
+        }
+        Ok(&self.plans[&frames])
+    }
+
+    /// Computes the outputs for an utterance, given a tensor for each input
+    /// node of the model (one frame per row). Inputs like ivectors must be
+    /// given for every frame too.
+    pub fn run(&mut self, inputs: &[Tensor]) -> TractResult<TVec<Tensor>> {
+        let inputs = inputs
+            .iter()
+            .map(|input| Ok(input.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?))
+            .collect::<TractResult<TVec<_>>>()?;
+        if inputs.len() != self.model.input_outlets()?.len() {
+            bail!("Model has {} inputs, got {}", self.model.input_outlets()?.len(), inputs.len())
+        }
+        let frames = inputs[0].nrows();
+        if frames == 0 {
+            bail!("Can not compute an empty utterance")
+        }
+        if inputs.iter().any(|input| input.nrows() != frames) {
+            bail!("All inputs must have the same number of frames")
+        }
+        let subsampling = self.frame_subsampling_factor;
+        let output_frames = (frames + subsampling - 1) / subsampling;
+        let chunk_frames = self.frames_per_chunk / subsampling;
+        let mut chunks: Vec<Vec<Tensor>> = vec![vec![]; self.output_spans.len()];
+        for first in (0..output_frames).step_by(chunk_frames) {
+            let len = chunk_frames.min(output_frames - first);
+            let first_input = (first * subsampling) as isize - self.left_context as isize;
+            let last_input = (first + len - 1) * subsampling + self.right_context;
+            let rows: Vec<usize> = (first_input..=last_input as isize)
+                .map(|t| t.max(0).min(frames as isize - 1) as usize)
+                .collect();
+            let chunk: TVec<Tensor> =
+                inputs.iter().map(|input| input.select(Axis(0), &rows).into_tensor()).collect();
+            let outputs = self.plan(&chunk)?.run(chunk)?;
+            for (ix, output) in outputs.iter().enumerate() {
+                // the first frame of the output is the first one at a
+                // multiple of the subsampling factor in its span
+                let left = self.output_spans[ix].left;
+                let first_frame = -(-left).div_euclid(subsampling as isize) * subsampling as isize;
+                let skip = (self.left_context as isize - first_frame) as usize / subsampling;
+                if output.shape()[0] < skip + len {
+                    bail!(
+                        "Output {} computed {} frames, expected at least {}",
+                        ix,
+                        output.shape()[0],
+                        skip + len
+                    )
+                }
+                chunks[ix].push(output.slice(0, skip, skip + len)?);
+            }
+        }
+        chunks.iter().map(|chunks| Tensor::stack_tensors(0, &chunks)).collect()
+    }
+}
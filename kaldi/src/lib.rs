@@ -5,10 +5,12 @@ extern crate educe;
 #[macro_use]
 extern crate log;
 
+pub mod compute;
 pub mod model;
 mod ops;
 pub mod parser;
 
+pub use compute::ChunkedComputation;
 pub use model::Kaldi;
 pub use model::KaldiProtoModel;
 
@@ -7,12 +7,123 @@ pub struct KaldiProtoModel {
     pub config_lines: ConfigLines,
     pub components: HashMap<String, Component>,
     pub adjust_final_offset: isize,
+    /// Outputs are only computed for the frames at a multiple of this factor.
+    pub frame_subsampling_factor: usize,
+}
+
+impl KaldiProtoModel {
+    /// Span of each node, in the config lines order.
+    fn spans(&self) -> TractResult<HashMap<String, TimeSpan>> {
+        let mut spans: HashMap<String, TimeSpan> = HashMap::default();
+        for input in &self.config_lines.inputs {
+            spans.insert(input.name.clone(), TimeSpan::default());
+        }
+        for (name, node) in &self.config_lines.nodes {
+            let span = match node {
+                NodeLine::Component(line) => {
+                    let component = self
+                        .components
+                        .get(&line.component)
+                        .with_context(|| format!("Could not find component {}", line.component))?;
+                    let conv_taps = if crate::ops::AFFINE.contains(&&*component.klass) {
+                        line.input.as_conv_taps()
+                    } else {
+                        None
+                    };
+                    if let Some((input, offsets)) = conv_taps {
+                        spans.get(input).cloned().unwrap_or_default().conv(&offsets)
+                    } else if component.klass == "TdnnComponent" {
+                        let offsets = component
+                            .attributes
+                            .get("TimeOffsets")
+                            .context("missing attribute TimeOffsets")?
+                            .cast_to::<i32>()?
+                            .as_slice::<i32>()?
+                            .iter()
+                            .map(|&o| o as isize)
+                            .collect::<TVec<_>>();
+                        line.input.span(&spans).unwrap_or_default().conv(&offsets)
+                    } else {
+                        line.input.span(&spans).unwrap_or_default()
+                    }
+                }
+                NodeLine::DimRange(line) => line.input.span(&spans).unwrap_or_default(),
+            };
+            spans.insert(name.to_string(), span);
+        }
+        Ok(spans)
+    }
+
+    /// Span of each output, before frame subsampling.
+    pub fn output_spans(&self) -> TractResult<Vec<TimeSpan>> {
+        let spans = self.flatten_composite_components()?.spans()?;
+        Ok(self
+            .config_lines
+            .outputs
+            .iter()
+            .map(|o| o.descriptor.span(&spans).unwrap_or_default())
+            .collect())
+    }
+
+    /// Frames of left and right context the outputs need: the number of
+    /// frames the input must be padded with to compute an output for every
+    /// input frame.
+    pub fn context(&self) -> TractResult<(usize, usize)> {
+        let spans = self.output_spans()?;
+        let left = spans.iter().map(|s| s.left.max(0) as usize).max().unwrap_or(0);
+        let right = spans.iter().map(|s| s.right.max(0) as usize).max().unwrap_or(0);
+        Ok((left, right))
+    }
+
+    /// Replaces each node running a CompositeComponent by a chain of nodes, one
+    /// per nested component. The last node of the chain keeps the node name.
+    fn flatten_composite_components(&self) -> TractResult<KaldiProtoModel> {
+        let mut flat = self.clone();
+        flat.config_lines.nodes.clear();
+        for (name, node) in &self.config_lines.nodes {
+            if let NodeLine::Component(line) = node {
+                let component = self
+                    .components
+                    .get(&line.component)
+                    .with_context(|| format!("Could not find component {}", line.component))?;
+                flat.push_component_node(name, line.input.clone(), &line.component, component);
+            } else {
+                flat.config_lines.nodes.push((name.clone(), node.clone()));
+            }
+        }
+        Ok(flat)
+    }
+
+    fn push_component_node(
+        &mut self,
+        name: &str,
+        input: GeneralDescriptor,
+        component_name: &str,
+        component: &Component,
+    ) {
+        if component.klass == "CompositeComponent" {
+            let mut input = input;
+            for (ix, nested) in component.components.iter().enumerate() {
+                let nested_name = if ix + 1 == component.components.len() {
+                    name.to_string()
+                } else {
+                    format!("{}.{}", name, ix)
+                };
+                let nested_component = format!("{}.{}", component_name, ix);
+                self.push_component_node(&nested_name, input, &nested_component, nested);
+                input = GeneralDescriptor::Name(nested_name);
+            }
+        } else {
+            self.components.insert(component_name.to_string(), component.clone());
+            let line = ComponentNode { input, component: component_name.to_string() };
+            self.config_lines.nodes.push((name.to_string(), NodeLine::Component(line)));
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ConfigLines {
-    pub input_name: String,
-    pub input_dim: usize,
+    pub inputs: Vec<InputLine>,
     pub nodes: Vec<(String, NodeLine)>,
     pub outputs: Vec<OutputLine>,
 }
@@ -23,6 +134,14 @@ pub enum NodeLine {
     DimRange(DimRangeNode),
 }
 
+/// An input node. All inputs stream at the frame rate: an ivector is given
+/// for every frame, and read through `ReplaceIndex` or `Round` descriptors.
+#[derive(Clone, Debug)]
+pub struct InputLine {
+    pub name: String,
+    pub dim: usize,
+}
+
 #[derive(Clone, Debug)]
 pub struct OutputLine {
     pub output_alias: String,
@@ -32,47 +151,186 @@ pub struct OutputLine {
 #[derive(Clone, Debug, PartialEq)]
 pub enum GeneralDescriptor {
     Append(Vec<GeneralDescriptor>),
+    Const(f32, usize),
+    Failover(Box<GeneralDescriptor>, Box<GeneralDescriptor>),
     IfDefined(Box<GeneralDescriptor>),
     Name(String),
     Offset(Box<GeneralDescriptor>, isize),
+    ReplaceIndex(Box<GeneralDescriptor>, String, isize),
+    Round(Box<GeneralDescriptor>, usize),
+    Scale(f32, Box<GeneralDescriptor>),
+    Sum(Box<GeneralDescriptor>, Box<GeneralDescriptor>),
+    Switch(Vec<GeneralDescriptor>),
+}
+
+/// Frames of the input a value is defined for: row `i` of the value is the
+/// frame at time `i + left`, and it is `left + right` rows shorter than the
+/// input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeSpan {
+    pub left: isize,
+    pub right: isize,
+}
+
+impl TimeSpan {
+    /// The span covered by all the given spans. Spans of values that do not
+    /// follow the input (constants, memories) are ignored.
+    fn intersection(spans: impl IntoIterator<Item = Option<TimeSpan>>) -> Option<TimeSpan> {
+        spans.into_iter().fold(None, |acc, span| match (acc, span) {
+            (Some(a), Some(b)) => {
+                Some(TimeSpan { left: a.left.max(b.left), right: a.right.max(b.right) })
+            }
+            (a, b) => a.or(b),
+        })
+    }
+
+    /// Span of a convolution output, with `offsets` its taps.
+    pub fn conv(&self, offsets: &[isize]) -> TimeSpan {
+        let min = offsets.iter().min().cloned().unwrap_or(0);
+        let max = offsets.iter().max().cloned().unwrap_or(0);
+        TimeSpan { left: self.left - min, right: self.right + max }
+    }
+}
+
+fn round_up(x: isize, modulus: usize) -> isize {
+    -(-x).div_euclid(modulus as isize) * modulus as isize
+}
+
+/// Adds a node feeding `inlet`, and returns its first inlet. Edges are
+/// deferred as the inlets of a node must be connected in order.
+fn insert(
+    model: &mut InferenceModel,
+    deferred: &mut BTreeMap<InletId, String>,
+    inlet: InletId,
+    name: &str,
+    op: impl Into<Box<dyn InferenceOp>>,
+) -> TractResult<InletId> {
+    let id = model.add_node(name, op, tvec!(InferenceFact::default()))?;
+    deferred.insert(inlet, name.to_string());
+    Ok(InletId::new(id, 0))
 }
 
 impl GeneralDescriptor {
     pub fn inputs(&self) -> TVec<&str> {
-        match self {
-            GeneralDescriptor::Append(ref gds) => gds.iter().fold(tvec!(), |mut acc, gd| {
+        use GeneralDescriptor::*;
+        fn union<'a>(gds: impl Iterator<Item = &'a GeneralDescriptor>) -> TVec<&'a str> {
+            gds.fold(tvec!(), |mut acc, gd| {
                 gd.inputs().iter().for_each(|i| {
                     if !acc.contains(i) {
                         acc.push(i)
                     }
                 });
                 acc
-            }),
-            GeneralDescriptor::IfDefined(ref gd) => gd.inputs(),
-            GeneralDescriptor::Name(ref s) => tvec!(&**s),
-            GeneralDescriptor::Offset(ref gd, _) => gd.inputs(),
+            })
+        }
+        match self {
+            Append(ref gds) | Switch(ref gds) => union(gds.iter()),
+            Const(_, _) => tvec!(),
+            Failover(ref a, ref b) | Sum(ref a, ref b) => union(vec![&**a, &**b].into_iter()),
+            Name(ref s) => tvec!(&**s),
+            IfDefined(ref gd)
+            | Offset(ref gd, _)
+            | ReplaceIndex(ref gd, _, _)
+            | Round(ref gd, _)
+            | Scale(_, ref gd) => gd.inputs(),
+        }
+    }
+
+    /// Recognizes a descriptor appending several time offsets of the same
+    /// node, which is a convolution over time. Returns the node and the
+    /// offsets, in the appending order.
+    pub fn as_conv_taps(&self) -> Option<(&str, TVec<isize>)> {
+        fn tap(gd: &GeneralDescriptor) -> Option<(&str, isize)> {
+            match gd {
+                GeneralDescriptor::Name(name) => Some((name, 0)),
+                GeneralDescriptor::Offset(inner, offset) => {
+                    tap(inner).map(|(name, o)| (name, o + offset))
+                }
+                _ => None,
+            }
+        }
+        match self {
+            GeneralDescriptor::Name(name) => Some((name, tvec!(0))),
+            GeneralDescriptor::Append(appendees) if appendees.len() > 0 => {
+                let taps = appendees.iter().map(tap).collect::<Option<TVec<_>>>()?;
+                let name = taps[0].0;
+                let offsets: TVec<isize> = taps.iter().map(|t| t.1).collect();
+                if taps.iter().any(|t| t.0 != name)
+                    || offsets.iter().enumerate().any(|(ix, o)| offsets[..ix].contains(o))
+                {
+                    return None;
+                }
+                Some((name, offsets))
+            }
+            _ => None,
         }
     }
 
     pub fn as_conv_shape_dilation(&self) -> Option<(usize, usize)> {
-        if let GeneralDescriptor::Name(_) = self {
-            return Some((1, 1));
+        self.as_conv_taps().map(|(_, offsets)| crate::ops::affine::kernel_len_dilation(&offsets))
+    }
+
+    /// Span of the value, given the spans of the nodes.
+    pub fn span(&self, spans: &HashMap<String, TimeSpan>) -> Option<TimeSpan> {
+        use GeneralDescriptor::*;
+        match self {
+            Append(gds) | Switch(gds) => {
+                TimeSpan::intersection(gds.iter().map(|gd| gd.span(spans)))
+            }
+            Sum(a, b) => TimeSpan::intersection(vec![a.span(spans), b.span(spans)]),
+            Const(_, _) => None,
+            Failover(a, b) => a.span(spans).or_else(|| b.span(spans)),
+            IfDefined(_) if self.as_memory().is_some() => None,
+            IfDefined(gd) | ReplaceIndex(gd, _, _) | Scale(_, gd) => gd.span(spans),
+            Name(n) => spans.get(n).cloned(),
+            Offset(gd, o) => gd.span(spans).map(|s| {
+                if *o > 0 {
+                    TimeSpan { left: s.left, right: s.right + o }
+                } else {
+                    TimeSpan { left: s.left - o, right: s.right + o }
+                }
+            }),
+            Round(gd, modulus) => gd
+                .span(spans)
+                .map(|s| TimeSpan { left: round_up(s.left, *modulus), right: s.right }),
         }
-        if let GeneralDescriptor::Append(ref appendees) = self {
-            let mut offsets = vec![];
-            for app in appendees {
-                match app {
-                    GeneralDescriptor::Name(_) => offsets.push(0),
-                    GeneralDescriptor::Offset(_, offset) => offsets.push(*offset),
-                    _ => return None,
+    }
+
+    /// `IfDefined(Offset(node, offset))` reads a past value of a node.
+    fn as_memory(&self) -> Option<(&str, isize)> {
+        if let GeneralDescriptor::IfDefined(ref o) = self {
+            if let GeneralDescriptor::Offset(ref n, o) = &**o {
+                if let GeneralDescriptor::Name(n) = &**n {
+                    return Some((n, *o));
                 }
             }
-            let dilation = offsets[1] - offsets[0];
-            if offsets.windows(2).all(|pair| pair[1] - pair[0] == dilation) {
-                return Some((offsets.len(), dilation as usize));
+        }
+        None
+    }
+
+    /// Wires the value, cropped to start and end with `target`.
+    fn wire_aligned(
+        &self,
+        target: Option<TimeSpan>,
+        inlet: InletId,
+        name: &str,
+        model: &mut InferenceModel,
+        deferred: &mut BTreeMap<InletId, String>,
+        spans: &HashMap<String, TimeSpan>,
+        adjust_final_offset: Option<isize>,
+    ) -> TractResult<()> {
+        let mut inlet = inlet;
+        if let (Some(target), Some(span)) = (target, self.span(spans)) {
+            if target != span {
+                let crop = tract_hir::ops::array::Crop::new(
+                    0,
+                    (target.left - span.left) as usize,
+                    (target.right - span.right) as usize,
+                );
+                inlet = insert(model, deferred, inlet, &format!("{}.Align", name), expand(crop))?;
             }
         }
-        return None;
+        self.wire(inlet, name, model, deferred, spans, adjust_final_offset)
     }
 
     fn wire<'a>(
@@ -81,9 +339,11 @@ impl GeneralDescriptor {
         name: &str,
         model: &mut InferenceModel,
         deferred: &mut BTreeMap<InletId, String>,
+        spans: &HashMap<String, TimeSpan>,
         adjust_final_offset: Option<isize>,
     ) -> TractResult<()> {
         use GeneralDescriptor::*;
+        let target = self.span(spans);
         match &self {
             &Name(n) => {
                 deferred.insert(inlet, n.to_string());
@@ -91,37 +351,74 @@ impl GeneralDescriptor {
             }
             &Append(appendees) => {
                 let name = format!("{}.Append", name);
+                // constants are padded on their neighbour stream operand, pads
+                // are stored from the innermost one
+                let mut operands: Vec<(&GeneralDescriptor, Vec<(usize, usize, f32)>)> = vec![];
+                let mut leading = vec![];
+                for appendee in appendees {
+                    if let Const(value, dim) = appendee {
+                        if let Some(last) = operands.last_mut() {
+                            last.1.push((0, *dim, *value));
+                        } else {
+                            leading.push((*dim, 0, *value));
+                        }
+                    } else {
+                        operands.push((appendee, leading.drain(..).rev().collect()));
+                    }
+                }
+                if operands.len() == 0 {
+                    bail!("Can not wire {:?}, it has no streaming input", self)
+                }
                 let id = model.add_node(
                     &*name,
                     expand(tract_hir::ops::array::Concat::new(1)),
                     tvec!(InferenceFact::default()),
                 )?;
-                model.add_edge(OutletId::new(id, 0), inlet)?;
-                for (ix, appendee) in appendees.iter().enumerate() {
+                deferred.insert(inlet, name.clone());
+                for (ix, (appendee, pads)) in operands.iter().enumerate() {
                     let name = format!("{}-{}", name, ix);
-                    appendee.wire(
-                        InletId::new(id, ix),
+                    let mut inlet = InletId::new(id, ix);
+                    for (pad_ix, (before, after, value)) in pads.iter().rev().enumerate() {
+                        let pad = tract_hir::ops::array::Pad::new(
+                            vec![(0, 0), (*before, *after)],
+                            tract_hir::ops::array::PadMode::Constant(rctensor0(*value)),
+                        );
+                        inlet = insert(
+                            model,
+                            deferred,
+                            inlet,
+                            &format!("{}.Const-{}", name, pad_ix),
+                            pad,
+                        )?;
+                    }
+                    appendee.wire_aligned(
+                        target,
+                        inlet,
                         &*name,
                         model,
                         deferred,
+                        spans,
                         adjust_final_offset,
                     )?;
                 }
                 return Ok(());
             }
+            &Const(_, _) => bail!("Can not wire {:?}, it has no streaming input", self),
+            &Failover(a, _) => {
+                return a.wire(inlet, name, model, deferred, spans, adjust_final_offset)
+            }
             &IfDefined(ref o) => {
-                if let &Offset(ref n, ref o) = &**o {
-                    if let Name(n) = &**n {
-                        let name = format!("{}.memory", name);
-                        model.add_node(
-                            &*name,
-                            crate::ops::memory::Memory::new(n.to_string(), *o),
-                            tvec!(InferenceFact::default()),
-                        )?;
-                        deferred.insert(inlet, name);
-                        return Ok(());
-                    }
+                if let Some((n, o)) = self.as_memory() {
+                    let name = format!("{}.memory", name);
+                    model.add_node(
+                        &*name,
+                        crate::ops::memory::Memory::new(n.to_string(), o),
+                        tvec!(InferenceFact::default()),
+                    )?;
+                    deferred.insert(inlet, name);
+                    return Ok(());
                 }
+                return o.wire(inlet, name, model, deferred, spans, adjust_final_offset);
             }
             &Offset(ref n, o) if *o > 0 => {
                 let name = format!("{}-Delay", name);
@@ -134,13 +431,90 @@ impl GeneralDescriptor {
                     expand(tract_hir::ops::array::Crop::new(0, crop as usize, 0)),
                     tvec!(InferenceFact::default()),
                 )?;
-                model.add_edge(OutletId::new(id, 0), inlet)?;
-                n.wire(InletId::new(id, 0), &*name, model, deferred, adjust_final_offset)?;
+                deferred.insert(inlet, name.clone());
+                n.wire(InletId::new(id, 0), &*name, model, deferred, spans, adjust_final_offset)?;
+                return Ok(());
+            }
+            &Offset(ref n, _) => {
+                return n.wire(inlet, name, model, deferred, spans, adjust_final_offset)
+            }
+            &ReplaceIndex(ref n, variable, value) => {
+                let span = n.span(spans).unwrap_or_default();
+                if variable != "t" || *value < span.left {
+                    bail!("Can not wire {:?}, the index must be a time in the input", self)
+                }
+                let name = format!("{}.ReplaceIndex", name);
+                let op = crate::ops::time::TimeReplaceIndex::new((value - span.left) as usize, 0);
+                let inlet = insert(model, deferred, inlet, &name, op)?;
+                return n.wire(inlet, &name, model, deferred, spans, adjust_final_offset);
+            }
+            &Round(ref n, modulus) => {
+                let span = n.span(spans).unwrap_or_default();
+                let name = format!("{}.Round", name);
+                let op = crate::ops::time::TimeRound::new(*modulus, 0);
+                let mut inlet = insert(model, deferred, inlet, &name, op)?;
+                let skip = (round_up(span.left, *modulus) - span.left) as usize;
+                if skip > 0 {
+                    let crop = tract_hir::ops::array::Crop::new(0, skip, 0);
+                    inlet =
+                        insert(model, deferred, inlet, &format!("{}.Crop", name), expand(crop))?;
+                }
+                return n.wire(inlet, &name, model, deferred, spans, adjust_final_offset);
+            }
+            &Scale(scale, ref n) => {
+                let name = format!("{}.Scale", name);
+                let mul =
+                    insert(model, deferred, inlet, &name, tract_hir::ops::math::Mul.into_hir())?;
+                let factor = format!("{}.factor", name);
+                model.add_const(&*factor, tensor2(&[[*scale]]))?;
+                deferred.insert(InletId::new(mul.node, 1), factor);
+                return n.wire(mul, &name, model, deferred, spans, adjust_final_offset);
+            }
+            &Sum(ref a, ref b) => {
+                let name = format!("{}.Sum", name);
+                let add =
+                    insert(model, deferred, inlet, &name, tract_hir::ops::math::Add.into_hir())?;
+                for (ix, operand) in [a, b].iter().enumerate() {
+                    let name = format!("{}-{}", name, ix);
+                    let inlet = InletId::new(add.node, ix);
+                    if let Const(value, dim) = &***operand {
+                        let konst = tensor1(&vec![*value; *dim]).into_shape(&[1, *dim])?;
+                        model.add_const(&*name, konst)?;
+                        deferred.insert(inlet, name);
+                    } else {
+                        operand.wire_aligned(
+                            target,
+                            inlet,
+                            &name,
+                            model,
+                            deferred,
+                            spans,
+                            adjust_final_offset,
+                        )?;
+                    }
+                }
+                return Ok(());
+            }
+            &Switch(ref gds) => {
+                let name = format!("{}.Switch", name);
+                let phase = target.unwrap_or_default().left.rem_euclid(gds.len() as isize);
+                let op = crate::ops::time::TimeSwitch::new(phase as usize, 0);
+                let id = model.add_node(&*name, op, tvec!(InferenceFact::default()))?;
+                deferred.insert(inlet, name.clone());
+                for (ix, gd) in gds.iter().enumerate() {
+                    gd.wire_aligned(
+                        target,
+                        InletId::new(id, ix),
+                        &format!("{}-{}", name, ix),
+                        model,
+                        deferred,
+                        spans,
+                        adjust_final_offset,
+                    )?;
+                }
                 return Ok(());
             }
-            _ => (),
         }
-        bail!("Unhandled input descriptor: {:?}", self)
     }
 }
 
@@ -161,12 +535,28 @@ pub struct ComponentNode {
 pub struct Component {
     pub klass: String,
     pub attributes: HashMap<String, Arc<Tensor>>,
+    /// Nested components, for CompositeComponent.
+    pub components: Vec<Component>,
 }
 
 pub struct ParsingContext<'a> {
     pub proto_model: &'a KaldiProtoModel,
 }
 
+impl<'a> ParsingContext<'a> {
+    pub fn component(&self, node: &str) -> TractResult<&'a Component> {
+        let line = self.proto_model.config_lines.nodes.iter().find(|l| l.0 == node);
+        if let Some((_, NodeLine::Component(line))) = line {
+            self.proto_model
+                .components
+                .get(&line.component)
+                .with_context(|| format!("Could not find component {}", line.component))
+        } else {
+            bail!("Could not find component node {}", node)
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct KaldiOpRegister(
     pub HashMap<String, fn(&ParsingContext, node: &str) -> TractResult<Box<dyn InferenceOp>>>,
@@ -196,32 +586,35 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
     }
 
     fn model_for_proto_model(&self, proto_model: &KaldiProtoModel) -> TractResult<InferenceModel> {
+        let proto_model = &proto_model.flatten_composite_components()?;
         let ctx = ParsingContext { proto_model };
         let mut model = InferenceModel::default();
         let s = tract_pulse::internal::stream_dim();
-        model.add_source(
-            proto_model.config_lines.input_name.clone(),
-            InferenceFact::dt_shape(
-                f32::datum_type(),
-                shapefactoid!(s, (proto_model.config_lines.input_dim)),
-            ),
-        )?;
+        for input in &proto_model.config_lines.inputs {
+            model.add_source(
+                input.name.clone(),
+                InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(s, (input.dim))),
+            )?;
+        }
         let mut inputs_to_wire: BTreeMap<InletId, String> = Default::default();
+        let spans = proto_model.spans()?;
         for (name, node) in &proto_model.config_lines.nodes {
             match node {
                 NodeLine::Component(line) => {
                     let component = &proto_model.components[&line.component];
-                    if crate::ops::AFFINE.contains(&&*component.klass)
-                        && line.input.as_conv_shape_dilation().is_some()
-                    {
+                    let conv_taps = if crate::ops::AFFINE.contains(&&*component.klass) {
+                        line.input.as_conv_taps()
+                    } else {
+                        None
+                    };
+                    if let Some((input, _)) = conv_taps {
                         let op = crate::ops::affine::affine_component(&ctx, name)?;
                         let id = model.add_node(
                             name.to_string(),
                             op,
                             tvec!(InferenceFact::default()),
                         )?;
-                        inputs_to_wire
-                            .insert(InletId::new(id, 0), line.input.inputs()[0].to_owned());
+                        inputs_to_wire.insert(InletId::new(id, 0), input.to_string());
                     } else {
                         let op = match self.op_register.0.get(&*component.klass) {
                             Some(builder) => (builder)(&ctx, name)?,
@@ -241,6 +634,7 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
                             name,
                             &mut model,
                             &mut inputs_to_wire,
+                            &spans,
                             None,
                         )?
                     }
@@ -258,23 +652,34 @@ impl Framework<KaldiProtoModel, InferenceModel> for Kaldi {
                         name,
                         &mut model,
                         &mut inputs_to_wire,
+                        &spans,
                         None,
                     )?
                 }
             }
         }
         let mut outputs = vec![];
+        let subsampling = proto_model.frame_subsampling_factor;
         for o in &proto_model.config_lines.outputs {
-            let output = model.add_node(
-                &*o.output_alias,
-                tract_hir::ops::identity::Identity::default(),
-                tvec!(InferenceFact::default()),
-            )?;
+            let op: Box<dyn InferenceOp> = if subsampling > 1 {
+                // keep the frames at a multiple of the subsampling factor
+                let first = o.descriptor.span(&spans).unwrap_or_default().left;
+                let modulo = (-first).rem_euclid(subsampling as isize) as usize;
+                Box::new(tract_hir::ops::downsample::Downsample::new(
+                    0,
+                    subsampling as isize,
+                    modulo,
+                ))
+            } else {
+                Box::new(tract_hir::ops::identity::Identity::default())
+            };
+            let output = model.add_node(&*o.output_alias, op, tvec!(InferenceFact::default()))?;
             o.descriptor.wire(
                 InletId::new(output, 0),
-                "output",
+                &*o.output_alias,
                 &mut model,
                 &mut inputs_to_wire,
+                &spans,
                 Some(proto_model.adjust_final_offset),
             )?;
             outputs.push(OutletId::new(output, 0));
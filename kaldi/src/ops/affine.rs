@@ -11,20 +11,75 @@ pub fn affine_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn
         bail!("Could not find component {}", name);
     };
     let component = &ctx.proto_model.components[&line.component];
-    let (kernel_len, dilation) = line.input.as_conv_shape_dilation().unwrap_or((1, 1));
+    let offsets = line.input.as_conv_taps().map(|(_, offsets)| offsets).unwrap_or(tvec!(0));
+    // LinearComponent calls its matrix Params and has no bias
+    let kernel: &Tensor = component
+        .attributes
+        .get("LinearParams")
+        .or_else(|| component.attributes.get("Params"))
+        .context("missing attribute LinearParams")?;
+    let bias = component.attributes.get("BiasParams");
+    affine(kernel, bias, &offsets)
+}
+
+pub fn tdnn_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let offsets =
+        component.attributes.get("TimeOffsets").context("missing attribute TimeOffsets")?;
+    let offsets = offsets.cast_to::<i32>()?;
+    let offsets = offsets.as_slice::<i32>()?;
+    if offsets.len() == 0 || offsets.windows(2).any(|pair| pair[0] >= pair[1]) {
+        bail!("TimeOffsets must be sorted and unique, got {:?}", offsets)
+    }
+    let offsets: TVec<isize> = offsets.iter().map(|&o| o as isize).collect();
     let kernel: &Tensor =
         component.attributes.get("LinearParams").context("missing attribute LinearParams")?;
-    let bias = component.attributes.get("BiasParams").context("missing attribute BiasParams")?;
+    let bias = component.attributes.get("BiasParams");
+    affine(kernel, bias, &offsets)
+}
+
+/// Dilation and length of the smallest regular kernel covering the offsets.
+pub fn kernel_len_dilation(offsets: &[isize]) -> (usize, usize) {
+    let min = offsets.iter().min().cloned().unwrap_or(0);
+    let max = offsets.iter().max().cloned().unwrap_or(0);
+    let dilation = offsets.iter().map(|o| (o - min) as usize).fold(0, gcd).max(1);
+    ((max - min) as usize / dilation + 1, dilation)
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn affine(
+    kernel: &Tensor,
+    bias: Option<&Arc<Tensor>>,
+    offsets: &[isize],
+) -> TractResult<Box<dyn InferenceOp>> {
+    // spread the taps over a regular kernel, the missing ones are zeroes
+    let (kernel_len, dilation) = kernel_len_dilation(offsets);
+    let min = offsets.iter().min().cloned().unwrap_or(0);
+    let o_ti = kernel.to_array_view::<f32>()?.into_dimensionality::<tract_ndarray::Ix2>()?;
+    let output_dim = o_ti.shape()[0];
+    let input_dim = o_ti.shape()[1] / offsets.len();
+    let mut o_ki = tract_ndarray::Array2::<f32>::zeros((output_dim, kernel_len * input_dim));
+    for (ix, offset) in offsets.iter().enumerate() {
+        let tap = (offset - min) as usize / dilation;
+        o_ki.slice_mut(tract_ndarray::s![.., tap * input_dim..(tap + 1) * input_dim])
+            .assign(&o_ti.slice(tract_ndarray::s![.., ix * input_dim..(ix + 1) * input_dim]));
+    }
     // O•TI -> t -> TI•O -> T•I•O = HWIO
-    let o_ti = kernel.to_array_view::<f32>()?;
-    let t_i_o_shape = (kernel_len, kernel.len() / kernel_len / bias.len(), bias.len());
+    let t_i_o_shape = (kernel_len, input_dim, output_dim);
     let t_i_o =
-        tract_ndarray::Array::from_shape_vec(t_i_o_shape, o_ti.t().iter().cloned().collect())?;
+        tract_ndarray::Array::from_shape_vec(t_i_o_shape, o_ki.t().iter().cloned().collect())?;
     Ok(expand(Affine {
         kernel_len,
         dilation,
         linear_params: t_i_o.into_arc_tensor(),
-        bias_params: Arc::clone(bias),
+        bias_params: bias.filter(|b| b.len() > 0).cloned(),
     }))
 }
 
@@ -33,7 +88,7 @@ struct Affine {
     kernel_len: usize,
     dilation: usize,
     linear_params: Arc<Tensor>, // TIO
-    bias_params: Arc<Tensor>,
+    bias_params: Option<Arc<Tensor>>,
 }
 
 impl_dyn_hash!(Affine);
@@ -96,12 +151,12 @@ impl Expansion for Affine {
                     PaddingSpec::Valid,
                     Some(tvec!(self.dilation)),
                     None,
-                    Some(self.bias_params.len()),
+                    Some(self.linear_params.shape()[2]),
                 ),
                 kernel_fmt: KernelFormat::HWIO,
                 kernel: self.linear_params.clone(),
                 group: 1,
-                bias: Some(self.bias_params.clone().into_arc_tensor()),
+                bias: self.bias_params.clone(),
                 q_params: None,
             },
             inputs,
@@ -3,7 +3,7 @@ use tract_hir::internal::*;
 use crate::model::ParsingContext;
 
 pub fn renorm(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
-    let component = &ctx.proto_model.components[name];
+    let component = ctx.component(name)?;
     let rms = *component
         .attributes
         .get("TargetRms")
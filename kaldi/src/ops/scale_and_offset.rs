@@ -0,0 +1,84 @@
+use tract_hir::internal::*;
+
+use crate::model::{Component, ParsingContext};
+
+pub fn scale_and_offset(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = attribute(component, "Dim")?.cast_to_scalar::<i64>()? as usize;
+    let scales = attribute(component, "Scales")?.as_slice::<f32>()?;
+    let offsets = attribute(component, "Offsets")?.as_slice::<f32>()?;
+    Ok(expand(ScaleAndOffset::new(tile(scales, dim)?, tile(offsets, dim)?)))
+}
+
+pub fn batch_norm(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = attribute(component, "Dim")?.cast_to_scalar::<i64>()? as usize;
+    let epsilon = attribute(component, "Epsilon")?.cast_to_scalar::<f32>()?;
+    let target_rms = attribute(component, "TargetRms")?.cast_to_scalar::<f32>()?;
+    let mean = attribute(component, "StatsMean")?.as_slice::<f32>()?;
+    let var = attribute(component, "StatsVar")?.as_slice::<f32>()?;
+    // same as BatchNormComponent::ComputeDerived
+    let scales: Vec<f32> =
+        var.iter().map(|v| (v.max(0.0) + epsilon).powf(-0.5) * target_rms).collect();
+    let offsets: Vec<f32> = mean.iter().zip(scales.iter()).map(|(m, s)| -m * s).collect();
+    Ok(expand(ScaleAndOffset::new(tile(&scales, dim)?, tile(&offsets, dim)?)))
+}
+
+fn attribute<'a>(component: &'a Component, name: &str) -> TractResult<&'a Tensor> {
+    Ok(component.attributes.get(name).with_context(|| format!("missing attribute {}", name))?)
+}
+
+/// Repeats per-block parameters over the whole dimension.
+fn tile(block: &[f32], dim: usize) -> TractResult<Arc<Tensor>> {
+    if block.len() == 0 || dim % block.len() != 0 {
+        bail!("Block of {} parameters can not be tiled over dimension {}", block.len(), dim)
+    }
+    let data: Vec<f32> = block.iter().cloned().cycle().take(dim).collect();
+    Ok(tensor1(&data).into_arc_tensor())
+}
+
+#[derive(Clone, Debug, new, Hash)]
+struct ScaleAndOffset {
+    scales: Arc<Tensor>,
+    offsets: Arc<Tensor>,
+}
+
+impl_dyn_hash!(ScaleAndOffset);
+
+impl Expansion for ScaleAndOffset {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "ScaleAndOffset".into()
+    }
+
+    op_kaldi!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.scales.len().to_dim())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_hir::ops::math;
+        let scales = (*self.scales).clone().broadcast_into_rank(2)?.into_arc_tensor();
+        let offsets = (*self.offsets).clone().broadcast_into_rank(2)?.into_arc_tensor();
+        let scaled =
+            model.wire_node(format!("{}.scale", prefix), math::mul::unary(scales), inputs)?;
+        model.wire_node(prefix, math::add::unary(offsets), &scaled)
+    }
+}
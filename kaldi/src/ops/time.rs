@@ -0,0 +1,369 @@
+//! Ops for the descriptors that pick frames by their time index (`Round`,
+//! `ReplaceIndex` and `Switch`).
+//!
+//! The output rows depend on the absolute position of the input rows in the
+//! stream, so the ops count the rows they have seen. `delay` is the number of
+//! meaningless rows a pulsed input starts with, it is zero in a plain model.
+use tract_hir::internal::*;
+use tract_hir::tract_core::dyn_clone::clone_box;
+use tract_ndarray::{Array2, ArrayView2, Axis, Ix2};
+use tract_pulse::internal::{OpPulsifier, PulsedFact, PulsedModel, PulsedOp, Pulses};
+
+#[derive(Clone, Debug, Default)]
+struct TimeState {
+    position: usize,
+    held: Option<Array2<f32>>,
+}
+
+impl TimeState {
+    /// Index in the (unpulsed) stream of the n-th row of the current input.
+    fn time(&self, delay: usize, row: usize) -> isize {
+        (self.position + row) as isize - delay as isize
+    }
+
+    fn hold(&mut self, input: &ArrayView2<f32>, row: usize) {
+        self.held = Some(input.slice_axis(Axis(0), (row..row + 1).into()).to_owned());
+    }
+
+    fn held_row(&self, dim: usize) -> Array2<f32> {
+        self.held.clone().unwrap_or_else(|| Array2::zeros((1, dim)))
+    }
+}
+
+fn input_view(input: &Tensor) -> TractResult<ArrayView2<f32>> {
+    Ok(input.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?)
+}
+
+fn rules<'r, 'p: 'r, 's: 'r>(
+    s: &mut Solver<'r>,
+    inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+) -> InferenceResult {
+    check_output_arity(&outputs, 1)?;
+    for input in inputs {
+        s.equals(&input.datum_type, f32::datum_type())?;
+        s.equals(&input.shape, &outputs[0].shape)?;
+    }
+    s.equals(&outputs[0].datum_type, f32::datum_type())?;
+    s.equals(&outputs[0].rank, 2)?;
+    Ok(())
+}
+
+/// Kaldi `Round(x, modulus)`: each row is replaced by the last row whose time
+/// is a multiple of `modulus`. The input first row must be at such a time.
+#[derive(Clone, Debug, new, Hash)]
+pub struct TimeRound {
+    pub modulus: usize,
+    pub delay: usize,
+}
+
+impl_dyn_hash!(TimeRound);
+
+impl Op for TimeRound {
+    fn name(&self) -> Cow<str> {
+        "TimeRound".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("modulus: {} delay: {}", self.modulus, self.delay)])
+    }
+
+    op_kaldi!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for TimeRound {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(TimeState::default())))
+    }
+}
+
+impl OpState for TimeState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = if let Some(op) = op.downcast_ref::<TimeRound>() {
+            let input = input_view(&inputs[0])?;
+            let mut output = Array2::<f32>::zeros(input.dim());
+            for row in 0..input.nrows() {
+                let time = self.time(op.delay, row);
+                if time >= 0 && time as usize % op.modulus == 0 {
+                    self.hold(&input, row);
+                }
+                output.row_mut(row).assign(&self.held_row(input.ncols()).row(0));
+            }
+            output
+        } else if let Some(op) = op.downcast_ref::<TimeReplaceIndex>() {
+            let input = input_view(&inputs[0])?;
+            for row in 0..input.nrows() {
+                if self.time(op.delay, row) == op.index as isize {
+                    self.hold(&input, row);
+                }
+            }
+            let held = self.held_row(input.ncols());
+            held.broadcast(input.dim()).unwrap().to_owned()
+        } else if let Some(op) = op.downcast_ref::<TimeSwitch>() {
+            let inputs = inputs.iter().map(|i| input_view(i)).collect::<TractResult<TVec<_>>>()?;
+            let mut output = Array2::<f32>::zeros(inputs[0].dim());
+            for row in 0..output.nrows() {
+                let time = self.time(op.delay, row) + op.phase as isize;
+                let choice = time.rem_euclid(inputs.len() as isize) as usize;
+                output.row_mut(row).assign(&inputs[choice].row(row));
+            }
+            output
+        } else {
+            bail!("Unexpected op {:?}", op)
+        };
+        self.position += output.nrows();
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for TimeRound {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for TimeRound {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        rules(s, inputs, outputs)
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl PulsedOp for TimeRound {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    as_op!();
+
+    fn to_typed(&self) -> Box<dyn TypedOp> {
+        clone_box(self)
+    }
+}
+
+fn pulsify_round(
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulses: &Pulses,
+) -> TractResult<TVec<OutletId>> {
+    let op = node.op_as::<TimeRound>().unwrap();
+    let input = mapping[&node.inputs[0]];
+    let delay = target.outlet_fact(input)?.delay;
+    target.wire_node(&*node.name, TimeRound { delay, ..op.clone() }, &[input])
+}
+
+inventory::submit!(OpPulsifier {
+    type_id: std::any::TypeId::of::<TimeRound>(),
+    name: "TimeRound",
+    func: pulsify_round,
+});
+
+/// Kaldi `ReplaceIndex(x, t, value)`: every row is replaced by the row at
+/// `index` in the input. When pulsing, the output is delayed until that row
+/// is available.
+#[derive(Clone, Debug, new, Hash)]
+pub struct TimeReplaceIndex {
+    pub index: usize,
+    pub delay: usize,
+}
+
+impl_dyn_hash!(TimeReplaceIndex);
+
+impl Op for TimeReplaceIndex {
+    fn name(&self) -> Cow<str> {
+        "TimeReplaceIndex".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("index: {} delay: {}", self.index, self.delay)])
+    }
+
+    op_kaldi!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for TimeReplaceIndex {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(TimeState::default())))
+    }
+}
+
+impl TypedOp for TimeReplaceIndex {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for TimeReplaceIndex {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        rules(s, inputs, outputs)
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl PulsedOp for TimeReplaceIndex {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.delay += self.index;
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+
+    fn to_typed(&self) -> Box<dyn TypedOp> {
+        clone_box(self)
+    }
+}
+
+fn pulsify_replace_index(
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulses: &Pulses,
+) -> TractResult<TVec<OutletId>> {
+    let op = node.op_as::<TimeReplaceIndex>().unwrap();
+    let input = mapping[&node.inputs[0]];
+    let delay = target.outlet_fact(input)?.delay;
+    target.wire_node(&*node.name, TimeReplaceIndex { delay, ..op.clone() }, &[input])
+}
+
+inventory::submit!(OpPulsifier {
+    type_id: std::any::TypeId::of::<TimeReplaceIndex>(),
+    name: "TimeReplaceIndex",
+    func: pulsify_replace_index,
+});
+
+/// Kaldi `Switch(x0, x1, ...)`: the row at time t comes from input
+/// `t % inputs.len()`. The first row is at time `phase`.
+#[derive(Clone, Debug, new, Hash)]
+pub struct TimeSwitch {
+    pub phase: usize,
+    pub delay: usize,
+}
+
+impl_dyn_hash!(TimeSwitch);
+
+impl Op for TimeSwitch {
+    fn name(&self) -> Cow<str> {
+        "TimeSwitch".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("phase: {} delay: {}", self.phase, self.delay)])
+    }
+
+    op_kaldi!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for TimeSwitch {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(TimeState::default())))
+    }
+}
+
+impl TypedOp for TimeSwitch {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for TimeSwitch {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        rules(s, inputs, outputs)
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl PulsedOp for TimeSwitch {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    as_op!();
+
+    fn to_typed(&self) -> Box<dyn TypedOp> {
+        clone_box(self)
+    }
+}
+
+fn pulsify_switch(
+    _source: &TypedModel,
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    _pulses: &Pulses,
+) -> TractResult<TVec<OutletId>> {
+    let op = node.op_as::<TimeSwitch>().unwrap();
+    let inputs = tract_pulse::ops::sync_inputs(node, target, mapping)?;
+    let delay = target.outlet_fact(inputs[0])?.delay;
+    target.wire_node(&*node.name, TimeSwitch { delay, ..op.clone() }, &inputs)
+}
+
+inventory::submit!(OpPulsifier {
+    type_id: std::any::TypeId::of::<TimeSwitch>(),
+    name: "TimeSwitch",
+    func: pulsify_switch,
+});
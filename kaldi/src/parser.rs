@@ -31,7 +31,12 @@ pub fn nnet3(slice: &[u8]) -> TractResult<KaldiProtoModel> {
         e => format_err!("{:?}", e),
     })?;
     let config_lines = config_lines::parse_config(config)?;
-    Ok(KaldiProtoModel { config_lines, components, adjust_final_offset: 0 })
+    Ok(KaldiProtoModel {
+        config_lines,
+        components,
+        adjust_final_offset: 0,
+        frame_subsampling_factor: 1,
+    })
 }
 
 pub fn if_then_else<'a, T>(
@@ -52,7 +57,7 @@ fn parse_top_level(i: &[u8]) -> IResult<&[u8], (&str, HashMap<String, Component>
     for _ in 0..num_components {
         let (new_i, name) = component_name(i)?;
         debug!("Parsing component {}", name);
-        let (new_i, comp) = component(bin)(new_i)?;
+        let (new_i, comp) = component(new_i, bin)?;
         i = new_i;
         components.insert(name.to_owned(), comp);
     }
@@ -66,13 +71,16 @@ fn num_components(bin: bool, i: &[u8]) -> IResult<&[u8], usize> {
     Ok((i, n as usize))
 }
 
-fn component(bin: bool) -> impl Fn(&[u8]) -> IResult<&[u8], Component> {
-    move |i: &[u8]| {
-        let (i, klass) = open_any(i)?;
-        let (i, attributes) = if bin { bin::attributes(i, klass)? } else { text::attributes(i)? };
-        let (i, _) = close(i, klass)?;
-        Ok((i, Component { klass: klass.to_string(), attributes }))
-    }
+fn component(i: &[u8], bin: bool) -> IResult<&[u8], Component> {
+    let (i, klass) = open_any(i)?;
+    let (i, attributes) = if bin { bin::attributes(i, klass)? } else { text::attributes(i)? };
+    let (i, components) = if klass == "CompositeComponent" {
+        nom::multi::many0(|i| component(i, bin))(i)?
+    } else {
+        (i, vec![])
+    };
+    let (i, _) = close(i, klass)?;
+    Ok((i, Component { klass: klass.to_string(), attributes, components }))
 }
 
 fn component_name(i: &[u8]) -> IResult<&[u8], &str> {
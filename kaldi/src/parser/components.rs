@@ -10,7 +10,7 @@ use nom::{
     bytes::complete::*,
     combinator::*,
     multi::many_m_n,
-    number::complete::{le_f32, le_f64},
+    number::complete::{le_f32, le_f64, le_i32},
     sequence::*,
     IResult,
 };
@@ -21,6 +21,11 @@ pub enum KaldiAttributeKind {
     Float,
     FloatVector,
     FloatMatrix,
+    IntVector,
+    /// A token with no value, meaning true when present.
+    Flag,
+    /// Several scalars following a single token, collected as a f32 vector.
+    Tuple(&'static [KaldiAttributeKind]),
 }
 
 impl KaldiAttributeKind {
@@ -34,6 +39,21 @@ impl KaldiAttributeKind {
             Float => map(Self::parse_float_value, Tensor::from)(i),
             FloatVector => preceded(multispaced(tag("FV")), Self::parse_float_vector)(i),
             FloatMatrix => preceded(multispaced(tag("FM")), Self::parse_float_matrix)(i),
+            IntVector => Self::parse_int_vector(i),
+            Flag => Ok((i, Tensor::from(true))),
+            Tuple(kinds) => {
+                let mut i = i;
+                let mut values = vec![];
+                for kind in kinds.iter() {
+                    let (next, value) = kind.parse_bin(i)?;
+                    let value = value
+                        .cast_to_scalar::<f32>()
+                        .map_err(|_| nom::Err::Error((i, nom::error::ErrorKind::MapRes)))?;
+                    values.push(value);
+                    i = next;
+                }
+                Ok((i, tensor1(&values)))
+            }
         }
     }
 
@@ -45,12 +65,21 @@ impl KaldiAttributeKind {
         let (i, len) = super::integer(true)(i)?;
         // FIXME pending merge of https://github.com/Geal/nom/pull/995
         if len == 0 {
-            Ok((i, tensor1(&[0.0f32])))
+            Ok((i, tensor1(&[0.0f32; 0])))
         } else {
             map(many_m_n(len as usize, len as usize, le_f32), |data| tensor1(&*data))(i)
         }
     }
 
+    fn parse_int_vector<'a>(i: &'a [u8]) -> IResult<&'a [u8], Tensor> {
+        let (i, len) = preceded(tag([4]), le_i32)(i)?;
+        if len == 0 {
+            Ok((i, tensor1(&[0i32; 0])))
+        } else {
+            map(many_m_n(len as usize, len as usize, le_i32), |data| tensor1(&*data))(i)
+        }
+    }
+
     fn parse_float_matrix<'a>(i: &'a [u8]) -> IResult<&'a [u8], Tensor> {
         let (i, rows) = super::integer(true)(i)?;
         let (i, cols) = super::integer(true)(i)?;
@@ -71,27 +100,111 @@ impl KaldiAttributeKind {
 
 use KaldiAttributeKind::*;
 
+/// Attributes written by UpdatableComponent::WriteUpdatableCommon.
+fn updatable(
+    mut attributes: HashMap<&'static str, KaldiAttributeKind>,
+) -> HashMap<&'static str, KaldiAttributeKind> {
+    attributes.insert("LearningRateFactor", Float);
+    attributes.insert("IsGradient", Bool);
+    attributes.insert("MaxChange", Float);
+    attributes.insert("L2Regularize", Float);
+    attributes.insert("LearningRate", Float);
+    attributes
+}
+
+/// Attributes written by NonlinearComponent::Write.
+fn nonlinear() -> HashMap<&'static str, KaldiAttributeKind> {
+    hashmap! {
+        "Dim" => Int,
+        "BlockDim" => Int,
+        "ValueAvg" => FloatVector,
+        "DerivAvg" => FloatVector,
+        "Count" => Float,
+        "OderivRms" => FloatVector,
+        "OderivCount" => Float,
+        "NumDimsSelfRepaired" => Float,
+        "NumDimsProcessed" => Float,
+        "SelfRepairLowerThreshold" => Float,
+        "SelfRepairUpperThreshold" => Float,
+        "SelfRepairScale" => Float,
+    }
+}
+
 lazy_static::lazy_static! {
     pub static ref COMPONENTS: HashMap<&'static str, HashMap<&'static str, KaldiAttributeKind>> = hashmap! {
         "FixedAffineComponent" => hashmap! {
             "LinearParams" => FloatMatrix,
             "BiasParams" => FloatVector,
         },
-        "NaturalGradientAffineComponent" => hashmap! {
-            "LearningRateFactor" => Float,
-            "MaxChange" => Float,
-            "LearningRate" => Float,
+        "NaturalGradientAffineComponent" => updatable(hashmap! {
             "LinearParams" => FloatMatrix,
             "BiasParams" => FloatVector,
+            "OrthonormalConstraint" => Float,
             "RankIn" => Int,
             "RankOut" => Int,
             "UpdatePeriod" => Int,
             "NumSamplesHistory" => Float,
             "Alpha" => Float,
+        }),
+        "LinearComponent" => updatable(hashmap! {
+            "Params" => FloatMatrix,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "RankInOut" => Tuple(&[Int, Int]),
+            "Alpha" => Float,
+            "NumSamplesHistory" => Float,
+            "UpdatePeriod" => Int,
+        }),
+        "TdnnComponent" => updatable(hashmap! {
+            "TimeOffsets" => IntVector,
+            "LinearParams" => FloatMatrix,
+            "BiasParams" => FloatVector,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "NumSamplesHistory" => Float,
+            "AlphaInOut" => Tuple(&[Float, Float]),
+            "RankInOut" => Tuple(&[Int, Int]),
+        }),
+        "BatchNormComponent" => hashmap! {
+            "Dim" => Int,
+            "BlockDim" => Int,
+            "Epsilon" => Float,
+            "TargetRms" => Float,
+            "TestMode" => Bool,
+            "Count" => Float,
+            "StatsMean" => FloatVector,
+            "StatsVar" => FloatVector,
+        },
+        "ScaleAndOffsetComponent" => updatable(hashmap! {
+            "Dim" => Int,
+            "Scales" => FloatVector,
+            "Offsets" => FloatVector,
+            "UseNaturalGradient" => Bool,
+            "Rank" => Int,
+        }),
+        "GeneralDropoutComponent" => hashmap! {
+            "Dim" => Int,
+            "BlockDim" => Int,
+            "TimePeriod" => Int,
+            "DropoutProportion" => Float,
+            "SpecAugmentConfig" => Tuple(&[Float, Int]),
+            "Continuous" => Flag,
+            "TestMode" => Bool,
+        },
+        "NoOpComponent" => hashmap! {
+            "Dim" => Int,
+            "BackpropScale" => Float,
+        },
+        "CompositeComponent" => hashmap! {
+            "LearningRateFactor" => Float,
             "IsGradient" => Bool,
+            "LearningRate" => Float,
+            "MaxRowsProcess" => Int,
+            "NumComponents" => Int,
         },
         "NormalizeComponent" => hashmap!{
             "InputDim" => Int,
+            "BlockDim" => Int,
             "TargetRms" => Float,
             "AddLogStddev" => Bool,
         },
@@ -123,22 +236,9 @@ lazy_static::lazy_static! {
             "NumElementsProcessed" => Float,
             "NumZeroingBoundaries" => Float,
         },
-        "LogSoftmaxComponent" => hashmap!{
-            "Dim" => Int,
-            "ValueAvg" => FloatVector,
-            "DerivAvg" => FloatVector,
-            "Count" => Int,
-            "NumDimsSelfRepaired" => Int,
-            "NumDimsProcessed" => Int,
-        },
-        "RectifiedLinearComponent" => hashmap!{
-            "Dim" => Int,
-            "ValueAvg" => FloatVector,
-            "DerivAvg" => FloatVector,
-            "Count" => Float,
-            "NumDimsSelfRepaired" => Float,
-            "NumDimsProcessed" => Float,
-            "SelfRepairScale" => Float,
-        }
+        "LogSoftmaxComponent" => nonlinear(),
+        "RectifiedLinearComponent" => nonlinear(),
+        "SigmoidComponent" => nonlinear(),
+        "TanhComponent" => nonlinear(),
     };
 }
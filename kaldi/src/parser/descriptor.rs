@@ -1,46 +1,76 @@
 use nom::IResult;
 use nom::{
-    bytes::complete::*, character::complete::*, combinator::*, multi::separated_list, sequence::*,
+    branch::alt, bytes::complete::*, character::complete::*, combinator::*, multi::separated_list,
+    number::complete::float, sequence::*,
 };
 
 use crate::model::GeneralDescriptor;
+use crate::parser::config_lines::uinteger;
 use crate::parser::spaced;
 
 pub fn parse_general(i: &str) -> IResult<&str, GeneralDescriptor> {
+    use GeneralDescriptor::*;
     spaced(nom::branch::alt((
+        map(call("Append", separated_list(comma, parse_general)), Append),
+        map(call("Const", separated_pair(float, comma, uinteger)), |(value, dim)| {
+            Const(value, dim)
+        }),
+        map(call("Failover", separated_pair(parse_general, comma, parse_general)), |(a, b)| {
+            Failover(Box::new(a), Box::new(b))
+        }),
+        map(call("IfDefined", parse_general), |inner| IfDefined(Box::new(inner))),
         map(
-            preceded(
-                tag("Append"),
-                cut(delimited(
-                    spaced(tag("(")),
-                    separated_list(spaced(tag(",")), parse_general),
-                    spaced(tag(")")),
+            call(
+                "Offset",
+                tuple((
+                    parse_general,
+                    preceded(comma, integer),
+                    // only the t offset is meaningful for speech models
+                    opt(preceded(comma, verify(integer, |x| *x == 0))),
                 )),
             ),
-            GeneralDescriptor::Append,
+            |(inner, offset, _)| Offset(Box::new(inner), offset as isize),
         ),
         map(
-            preceded(
-                tag("Offset"),
-                cut(delimited(
-                    spaced(tag("(")),
-                    separated_pair(parse_general, spaced(tag(",")), integer),
-                    spaced(tag(")")),
+            call(
+                "ReplaceIndex",
+                tuple((
+                    parse_general,
+                    preceded(comma, alt((tag("t"), tag("x")))),
+                    preceded(comma, integer),
                 )),
             ),
-            |(inner, offset)| GeneralDescriptor::Offset(Box::new(inner), offset as isize),
-        ),
-        map(
-            preceded(
-                tag("IfDefined"),
-                cut(delimited(spaced(tag("(")), parse_general, spaced(tag(")")))),
-            ),
-            |inner| GeneralDescriptor::IfDefined(Box::new(inner)),
+            |(inner, variable, value)| {
+                ReplaceIndex(Box::new(inner), variable.to_string(), value as isize)
+            },
         ),
-        map(super::config_lines::identifier, |i| GeneralDescriptor::Name(i.to_string())),
+        map(call("Round", separated_pair(parse_general, comma, uinteger)), |(inner, modulus)| {
+            Round(Box::new(inner), modulus)
+        }),
+        map(call("Scale", separated_pair(float, comma, parse_general)), |(scale, inner)| {
+            Scale(scale, Box::new(inner))
+        }),
+        map(call("Sum", separated_pair(parse_general, comma, parse_general)), |(a, b)| {
+            Sum(Box::new(a), Box::new(b))
+        }),
+        map(call("Switch", separated_list(comma, parse_general)), Switch),
+        map(super::config_lines::identifier, |i| Name(i.to_string())),
     )))(i)
 }
 
+/// A descriptor expression `name(args)`. Once the opening parenthesis is
+/// found, the arguments must parse.
+fn call<'a, O>(
+    name: &'static str,
+    args: impl Fn(&'a str) -> IResult<&'a str, O>,
+) -> impl Fn(&'a str) -> IResult<&'a str, O> {
+    preceded(pair(tag(name), spaced(tag("("))), cut(terminated(args, spaced(tag(")")))))
+}
+
+fn comma(i: &str) -> IResult<&str, &str> {
+    spaced(tag(","))(i)
+}
+
 pub fn integer(i: &str) -> IResult<&str, i32> {
     map_res(recognize(pair(opt(tag("-")), digit1)), |s: &str| s.parse::<i32>())(i)
 }
@@ -71,6 +101,57 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_conv_taps() {
+        let conv = parse_general("Append(Offset(x, -2), x, Offset(Offset(x, 1), 2))").unwrap().1;
+        let (name, offsets) = conv.as_conv_taps().unwrap();
+        assert_eq!((name, &*offsets), ("x", &[-2, 0, 3][..]));
+        assert_eq!(conv.as_conv_shape_dilation(), Some((6, 1)));
+        let dilated = parse_general("Append(Offset(x, 3), Offset(x, -3))").unwrap().1;
+        assert_eq!(dilated.as_conv_shape_dilation(), Some((2, 6)));
+        assert!(parse_general("Append(x, Offset(y, 1))").unwrap().1.as_conv_taps().is_none());
+        assert!(parse_general("Append(x, Offset(x, 0))").unwrap().1.as_conv_taps().is_none());
+    }
+
+    #[test]
+    fn test_tdnnf_bypass() {
+        assert_eq!(
+            parse_general("Sum(Scale(0.66, tdnnf2.dropout), tdnnf3.dropout)").unwrap().1,
+            Sum(Scale(0.66, name("tdnnf2.dropout").into()).into(), name("tdnnf3.dropout").into())
+        )
+    }
+
+    #[test]
+    fn test_ivector() {
+        assert_eq!(
+            parse_general("Append(input, ReplaceIndex(ivector, t, 0), Round(ivector, 10))")
+                .unwrap()
+                .1,
+            Append(vec!(
+                name("input"),
+                ReplaceIndex(name("ivector").into(), "t".to_string(), 0),
+                Round(name("ivector").into(), 10)
+            ))
+        )
+    }
+
+    #[test]
+    fn test_const_failover_switch() {
+        assert_eq!(
+            parse_general("Failover(Offset(a, -1, 0), Switch(b, Const(1.5, 3)))").unwrap().1,
+            Failover(
+                Offset(name("a").into(), -1).into(),
+                Switch(vec!(name("b"), Const(1.5, 3))).into()
+            )
+        )
+    }
+
+    #[test]
+    fn test_names_like_keywords() {
+        assert_eq!(parse_general("Summary").unwrap().1, name("Summary"));
+        assert!(parse_general("Sum(a)").is_err());
+    }
+
     #[test]
     fn test_lstm() {
         assert_eq!(
@@ -9,14 +9,24 @@ use nom::{
 use super::{integer, multispaced, open_any, spaced};
 
 pub fn attributes(i: &[u8]) -> IResult<&[u8], HashMap<String, Arc<Tensor>>> {
-    let (i, attributes) = nom::multi::many0(map(pair(open_any, tensor), |(k, v)| {
-        (k.to_string(), v.into_arc_tensor())
+    // nested components (in a CompositeComponent) end the attribute list
+    let attribute_name = verify(open_any, |name: &str| !name.ends_with("Component"));
+    let (i, attributes) = nom::multi::many0(map(pair(attribute_name, opt(tensor)), |(k, v)| {
+        // a token without a value is a flag
+        (k.to_string(), v.unwrap_or_else(|| Tensor::from(true)).into_arc_tensor())
     }))(i)?;
     Ok((i, attributes.into_iter().collect()))
 }
 
 pub fn tensor(i: &[u8]) -> IResult<&[u8], Tensor> {
-    nom::branch::alt((scalar, vector, matrix))(i)
+    nom::branch::alt((scalars, scalar, vector, matrix))(i)
+}
+
+pub fn scalars(i: &[u8]) -> IResult<&[u8], Tensor> {
+    map(pair(float, nom::multi::many1(preceded(space1, float))), |(first, mut others)| {
+        others.insert(0, first);
+        tensor1(&*others)
+    })(i)
 }
 
 pub fn scalar(i: &[u8]) -> IResult<&[u8], Tensor> {
@@ -89,6 +99,15 @@ output-node name=output input=fixed1
         );
     }
 
+    #[test]
+    fn test_flag_and_scalars() {
+        let slice = r#"<TimePeriod> 0 <SpecAugmentConfig> 0.5 1 <Continuous> <TestMode> F </"#;
+        let attributes = attributes(slice.as_bytes()).unwrap().1;
+        assert_eq!(*attributes["SpecAugmentConfig"], tensor1(&[0.5f32, 1.0]));
+        assert_eq!(*attributes["Continuous"], Tensor::from(true));
+        assert_eq!(*attributes["TestMode"], Tensor::from(false));
+    }
+
     #[test]
     fn fixed_affine_40x10_T40_S3() {
         let slice = std::fs::read("test_cases/fixed_affine_40x10_T40_S3/model.raw.txt").unwrap();
@@ -0,0 +1,102 @@
+use std::path::Path;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ndarray::{s, stack, Array2, Axis};
+use tract_pulse::internal::*;
+
+const FRAMES: usize = 24;
+
+fn model() -> TractResult<InferenceModel> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/models/descriptors.raw.txt");
+    tract_kaldi::kaldi().model_for_path(path)
+}
+
+fn input() -> Array2<f32> {
+    Array2::from_shape_fn((FRAMES, 2), |(t, c)| ((t * 7 + c * 5) % 11) as f32 / 2.0 - 2.5)
+}
+
+/// Expected output, as a list of (first frame, rows).
+fn expected(x: &Array2<f32>) -> Vec<(usize, Array2<f32>)> {
+    let conv = |t: usize| [x[(t - 2, 0)] + 0.5, x[(t, 1)] + 2.0 * x[(t + 3, 1)] - 0.5];
+    let bypass = |t: usize| {
+        let c = conv(t);
+        [0.5 * c[0] + x[(t + 1, 0)], 0.5 * c[1] + x[(t + 1, 1)]]
+    };
+    let rows = |first: usize, last: usize, f: &dyn Fn(usize) -> Vec<f32>| {
+        let rows: Vec<Vec<f32>> = (first..last).map(f).collect();
+        let dim = rows[0].len();
+        let rows = Array2::from_shape_vec(
+            (last - first, dim),
+            rows.into_iter().flat_map(|r| r.into_iter()).collect(),
+        )
+        .unwrap();
+        (first, rows)
+    };
+    vec![
+        rows(2, FRAMES - 3, &|t| bypass(t).to_vec()),
+        rows(2, FRAMES - 3, &|t| vec![1.5, bypass(t)[0], bypass(t)[1], -1.0, -1.0]),
+        rows(3, FRAMES - 3, &|t| bypass(t / 3 * 3).to_vec()),
+        rows(2, FRAMES - 3, &|t| vec![bypass(t)[0] + x[(4, 0)], bypass(t)[1] + x[(4, 1)]]),
+        rows(2, FRAMES - 3, &|t| {
+            if t % 2 == 0 {
+                conv(t).to_vec()
+            } else {
+                x.row(t - 1).to_vec()
+            }
+        }),
+        rows(3, FRAMES - 2, &|t| conv(t - 1).to_vec()),
+    ]
+}
+
+#[test]
+fn descriptors() -> TractResult<()> {
+    let x = input();
+    let model = model()?
+        .with_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), &[FRAMES, 2]))?
+        .into_optimized()?;
+    let outputs = model.into_runnable()?.run(tvec!(x.clone().into_tensor()))?;
+    for (found, (_, expected)) in outputs.iter().zip(expected(&x).into_iter()) {
+        found.close_enough(&expected.into_tensor(), true)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn pulsed_descriptors() -> TractResult<()> {
+    let x = input();
+    let expected = expected(&x);
+    for &pulse in &[1, 4, 5] {
+        let typed = model()?.into_typed()?.declutter()?;
+        let pulsed = PulsedModel::new(&typed, pulse)?;
+        let delays: Vec<usize> = pulsed
+            .output_outlets()?
+            .iter()
+            .map(|o| Ok(pulsed.outlet_fact(*o)?.delay))
+            .collect::<TractResult<_>>()?;
+        let max_delay = delays.iter().max().cloned().unwrap();
+        let mut state = SimpleState::new(SimplePlan::new(pulsed.into_typed()?)?)?;
+        let mut got: Vec<Vec<Array2<f32>>> = vec![vec![]; expected.len()];
+        for chunk in 0..(FRAMES + max_delay + pulse - 1) / pulse {
+            let mut input = Array2::<f32>::zeros((pulse, 2));
+            for row in 0..pulse {
+                if chunk * pulse + row < FRAMES {
+                    input.row_mut(row).assign(&x.row(chunk * pulse + row));
+                }
+            }
+            let outputs = state.run(tvec!(input.into_tensor()))?;
+            for (ix, output) in outputs.iter().enumerate() {
+                got[ix].push(output.to_array_view::<f32>()?.into_dimensionality()?.to_owned());
+            }
+        }
+        for ix in 0..expected.len() {
+            let (_, ref expected) = expected[ix];
+            let views: Vec<_> = got[ix].iter().map(|a| a.view()).collect();
+            let got = stack(Axis(0), &views)?;
+            let found = got.slice(s![delays[ix]..delays[ix] + expected.nrows(), ..]).to_owned();
+            found
+                .into_tensor()
+                .close_enough(&expected.clone().into_tensor(), true)
+                .with_context(|| format!("output {} with pulse {}", ix, pulse))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use std::path::Path;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ndarray::{s, stack, Array2, Axis};
+use tract_pulse::internal::*;
+
+const FRAMES: usize = 13;
+
+fn path() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/models/ivector.raw.txt")
+}
+
+fn model() -> TractResult<InferenceModel> {
+    tract_kaldi::kaldi().model_for_path(path())
+}
+
+fn input() -> Array2<f32> {
+    Array2::from_shape_fn((FRAMES, 2), |(t, c)| ((t * 7 + c * 5) % 11) as f32 / 2.0 - 2.5)
+}
+
+/// A slowly varying ivector: it changes every 4 frames.
+fn ivector() -> Array2<f32> {
+    Array2::from_shape_fn((FRAMES, 2), |(t, c)| (t / 4) as f32 + c as f32 * 0.5)
+}
+
+/// Expected outputs, as a list of (first frame, rows).
+fn expected(x: &Array2<f32>, iv: &Array2<f32>) -> Vec<(usize, Array2<f32>)> {
+    let affine = Array2::from_shape_fn((FRAMES - 1, 2), |(t, c)| {
+        if c == 0 {
+            x[(t, 0)] + iv[(0, 0)] + 0.25
+        } else {
+            x[(t + 1, 1)] + 2.0 * iv[(0, 1)] - 0.25
+        }
+    });
+    let rounded =
+        Array2::from_shape_fn(
+            (FRAMES, 4),
+            |(t, c)| if c < 2 { x[(t, c)] } else { iv[(t / 4 * 4, c - 2)] },
+        );
+    vec![(1, affine), (0, rounded)]
+}
+
+#[test]
+fn inputs() -> TractResult<()> {
+    let proto_model = tract_kaldi::kaldi().proto_model_for_path(path())?;
+    let inputs = &proto_model.config_lines.inputs;
+    assert_eq!(inputs.len(), 2);
+    assert_eq!((&*inputs[0].name, inputs[0].dim), ("input", 2));
+    assert_eq!((&*inputs[1].name, inputs[1].dim), ("ivector", 2));
+    let model = model()?;
+    assert_eq!(model.input_outlets()?.len(), 2);
+    assert_eq!(model.node(model.input_outlets()?[1].node).name, "ivector");
+    Ok(())
+}
+
+#[test]
+fn ivector_input() -> TractResult<()> {
+    let (x, iv) = (input(), ivector());
+    let fact = InferenceFact::dt_shape(f32::datum_type(), &[FRAMES, 2]);
+    let model =
+        model()?.with_input_fact(0, fact.clone())?.with_input_fact(1, fact)?.into_optimized()?;
+    let outputs =
+        model.into_runnable()?.run(tvec!(x.clone().into_tensor(), iv.clone().into_tensor()))?;
+    for (found, (_, expected)) in outputs.iter().zip(expected(&x, &iv).into_iter()) {
+        found.close_enough(&expected.into_tensor(), true)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn pulsed_ivector_input() -> TractResult<()> {
+    let (x, iv) = (input(), ivector());
+    let expected = expected(&x, &iv);
+    for &pulse in &[1, 3, 4] {
+        let typed = model()?.into_typed()?.declutter()?;
+        let pulsed = PulsedModel::new(&typed, pulse)?;
+        let delays: Vec<usize> = pulsed
+            .output_outlets()?
+            .iter()
+            .map(|o| Ok(pulsed.outlet_fact(*o)?.delay))
+            .collect::<TractResult<_>>()?;
+        let max_delay = delays.iter().max().cloned().unwrap();
+        let mut state = SimpleState::new(SimplePlan::new(pulsed.into_typed()?)?)?;
+        let mut got: Vec<Vec<Array2<f32>>> = vec![vec![]; expected.len()];
+        for chunk in 0..(FRAMES + max_delay + pulse - 1) / pulse {
+            let mut inputs = tvec!();
+            for stream in &[&x, &iv] {
+                let mut input = Array2::<f32>::zeros((pulse, 2));
+                for row in 0..pulse {
+                    if chunk * pulse + row < FRAMES {
+                        input.row_mut(row).assign(&stream.row(chunk * pulse + row));
+                    }
+                }
+                inputs.push(input.into_tensor());
+            }
+            let outputs = state.run(inputs)?;
+            for (ix, output) in outputs.iter().enumerate() {
+                got[ix].push(output.to_array_view::<f32>()?.into_dimensionality()?.to_owned());
+            }
+        }
+        for ix in 0..expected.len() {
+            let (_, ref expected) = expected[ix];
+            let views: Vec<_> = got[ix].iter().map(|a| a.view()).collect();
+            let got = stack(Axis(0), &views)?;
+            let found = got.slice(s![delays[ix]..delays[ix] + expected.nrows(), ..]).to_owned();
+            found
+                .into_tensor()
+                .close_enough(&expected.clone().into_tensor(), true)
+                .with_context(|| format!("output {} with pulse {}", ix, pulse))?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn chunked_ivector_input() -> TractResult<()> {
+    // every chunk reads the ivector at its first frame, so it must be
+    // constant for the chunks to match the whole utterance
+    let x = input();
+    let iv = Array2::from_shape_fn((FRAMES, 2), |(_, c)| c as f32 - 0.5);
+    let kaldi = tract_kaldi::kaldi();
+    let proto_model = kaldi.proto_model_for_path(path())?;
+    let inputs = [x.clone().into_tensor(), iv.clone().into_tensor()];
+    let whole = tract_kaldi::ChunkedComputation::new(&kaldi, &proto_model, FRAMES)?.run(&inputs)?;
+    let chunked = tract_kaldi::ChunkedComputation::new(&kaldi, &proto_model, 4)?.run(&inputs)?;
+    for (whole, chunked) in whole.iter().zip(chunked.iter()) {
+        chunked.close_enough(whole, true)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+#!/usr/bin/env python3
+"""Reference outputs of tdnnf.raw.txt, computed without tract nor kaldi.
+
+Follows nnet3-compute in test mode (batchnorm uses the stored stats, dropout
+is the identity), and only computes the frames for which every offset is
+available: for a 20 frames input, output frames 2 to 17.
+
+Writes tdnnf.output.ark and tdnnf.output-xent.ark next to this script.
+"""
+import math
+import os
+import re
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+TEXT = open(os.path.join(HERE, "tdnnf.raw.txt")).read()
+
+
+def component(name):
+    start = TEXT.index("<ComponentName> %s " % name)
+    end = TEXT.find("<ComponentName>", start + 1)
+    return TEXT[start:end if end >= 0 else len(TEXT)]
+
+
+def matrix(text, tag):
+    body = re.search(r"<%s>\s*\[(.*?)\]" % tag, text, re.S).group(1)
+    return [[float(x) for x in line.split()] for line in body.splitlines() if line.split()]
+
+
+def vector(text, tag):
+    rows = matrix(text, tag)
+    return rows[0] if rows else []
+
+
+def number(text, tag):
+    return float(re.search(r"<%s>\s*(\S+)" % tag, text).group(1))
+
+
+def affine(w, b, x):
+    return [sum(wi * xi for wi, xi in zip(row, x)) + (b[i] if b else 0.0) for i, row in enumerate(w)]
+
+
+def batchnorm(text, x):
+    mean, var = vector(text, "StatsMean"), vector(text, "StatsVar")
+    eps, rms = number(text, "Epsilon"), number(text, "TargetRms")
+    block = len(mean)
+    y = []
+    for i, v in enumerate(x):
+        scale = rms * (var[i % block] + eps) ** -0.5
+        y.append((v - mean[i % block]) * scale)
+    return y
+
+
+def scale_and_offset(text, x):
+    scales, offsets = vector(text, "Scales"), vector(text, "Offsets")
+    return [v * scales[i % len(scales)] + offsets[i % len(scales)] for i, v in enumerate(x)]
+
+
+def fixed_or_ng_affine(name):
+    text = component(name)
+    return lambda x: affine(matrix(text, "LinearParams"), vector(text, "BiasParams"), x)
+
+
+def linear(name):
+    text = component(name)
+    return lambda x: affine(matrix(text, "Params"), [], x)
+
+
+relu = lambda x: [max(0.0, v) for v in x]
+sigmoid = lambda x: [1.0 / (1.0 + math.exp(-v)) for v in x]
+tanh = lambda x: [math.tanh(v) for v in x]
+
+
+def log_softmax(x):
+    m = max(x)
+    s = math.log(sum(math.exp(v - m) for v in x)) + m
+    return [v - s for v in x]
+
+
+def tdnn(name, frames, t):
+    text = component(name)
+    offsets = [int(o) for o in vector(text, "TimeOffsets")]
+    x = sum((frames[t + o] for o in offsets), [])
+    return affine(matrix(text, "LinearParams"), vector(text, "BiasParams"), x)
+
+
+def prefinal_chain_nonlin(x):
+    text = component("prefinal-chain.nonlin")
+    parts = re.split(r"(?=<BatchNormComponent>|<ScaleAndOffsetComponent>)", text)
+    return scale_and_offset(parts[2], batchnorm(parts[1], sigmoid(x)))
+
+
+def main():
+    frames, dim = 20, 4
+    # same input as kaldi/tests/tdnnf.rs
+    flat = [((i * 7) % 13) / 4.0 - 1.5 for i in range(frames * dim)]
+    x = {t: flat[t * dim:(t + 1) * dim] for t in range(frames)}
+
+    lda = fixed_or_ng_affine("lda")
+    h = {t: lda(x[t - 1] + x[t] + x[t + 1]) for t in range(1, frames - 1)}
+    tdnn1 = fixed_or_ng_affine("tdnn1.affine")
+    h = {t: batchnorm(component("tdnn1.batchnorm"), relu(tdnn1(v))) for t, v in h.items()}
+    h = {t: tdnn("tdnnf2.linear", h, t) for t in h if t - 1 in h}
+    h = {t: tdnn("tdnnf2.affine", h, t) for t in h if t + 1 in h}
+    h = {t: batchnorm(component("tdnnf2.batchnorm"), relu(v)) for t, v in h.items()}
+    prefinal = {t: linear("prefinal-l")(v) for t, v in h.items()}
+
+    chain = fixed_or_ng_affine("prefinal-chain.affine")
+    output = {t: linear("output.affine")(prefinal_chain_nonlin(chain(v))) for t, v in prefinal.items()}
+    xent = fixed_or_ng_affine("prefinal-xent.affine")
+    xent_out = fixed_or_ng_affine("output-xent.affine")
+    output_xent = {t: log_softmax(xent_out(tanh(xent(v)))) for t, v in prefinal.items()}
+
+    for name, values in [("output", output), ("output-xent", output_xent)]:
+        rows = [" ".join("%.7g" % v for v in values[t]) for t in sorted(values)]
+        with open(os.path.join(HERE, "tdnnf.%s.ark" % name), "w") as f:
+            f.write("tdnnf  [\n  " + " \n  ".join(rows) + " ]\n")
+
+
+if __name__ == "__main__":
+    main()
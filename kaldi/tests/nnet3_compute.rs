@@ -0,0 +1,97 @@
+//! Runs the test cases against the outputs of `nnet3-compute`.
+use std::path::{Path, PathBuf};
+use tract_hir::internal::*;
+use tract_kaldi::ChunkedComputation;
+
+mod utils;
+use utils::read_ark;
+
+/// Reads `name=value` from the test case scripts.
+fn variable(case: &Path, file: &str, name: &str) -> TractResult<usize> {
+    let script = std::fs::read_to_string(case.join(file))?;
+    let pattern = format!("{}=", name);
+    let start = script.find(&pattern).with_context(|| format!("{} not found", name))?;
+    let value: String =
+        script[start + pattern.len()..].chars().take_while(|c| c.is_ascii_digit()).collect();
+    Ok(value.parse()?)
+}
+
+fn cases() -> TractResult<Vec<PathBuf>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_cases");
+    let mut cases = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.join("vars.sh").exists() {
+            cases.push(path);
+        }
+    }
+    cases.sort();
+    Ok(cases)
+}
+
+fn computation(case: &Path, frames_per_chunk: usize) -> TractResult<ChunkedComputation> {
+    let kaldi = tract_kaldi::kaldi();
+    let mut proto_model = kaldi.proto_model_for_path(case.join("model.raw"))?;
+    proto_model.frame_subsampling_factor = variable(case, "vars.sh", "subsampling")?;
+    ChunkedComputation::new(&kaldi, &proto_model, frames_per_chunk)
+}
+
+#[test]
+fn context() -> TractResult<()> {
+    let kaldi = tract_kaldi::kaldi();
+    for case in cases()? {
+        let proto_model = kaldi.proto_model_for_path(case.join("model.raw"))?;
+        let left = variable(&case, "vars.sh", "left_context")?;
+        let right = variable(&case, "vars.sh", "right_context")?;
+        assert_eq!(proto_model.context()?, (left, right), "{:?}", case);
+    }
+    Ok(())
+}
+
+/// nnet3-compute writes its outputs with 7 significant digits, and sums in a
+/// different order than tract, so an affine output smaller than its terms
+/// can be a few units off in the 6th digit: feed forward models agree up to
+/// a relative 1e-5. Recurrent models feed these last digit differences back through
+/// their state, where they grow over time, so they get the looser
+/// `close_enough` tolerance.
+fn same_outputs(case: &Path, found: &Tensor, expected: &Tensor) -> TractResult<()> {
+    if case.to_string_lossy().contains("lstm") {
+        return found.close_enough(expected, true);
+    }
+    if found.shape() != expected.shape() {
+        bail!("Shape mismatch {:?} != {:?}", found.shape(), expected.shape())
+    }
+    let found = found.as_slice::<f32>()?;
+    for (ix, (a, b)) in found.iter().zip(expected.as_slice::<f32>()?.iter()).enumerate() {
+        if (a - b).abs() > 1e-5 * b.abs().max(1.0) {
+            bail!("Mismatch at {}: {} != {}", ix, a, b)
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn same_as_nnet3_compute() -> TractResult<()> {
+    for case in cases()? {
+        let frames_per_chunk = variable(&case, "test.sh", "--frames-per-chunk")?;
+        let input = read_ark(&case.join("input.ark"))?;
+        let expected = read_ark(&case.join("output.ark"))?;
+        let found = computation(&case, frames_per_chunk)?.run(&[input.clone()])?;
+        same_outputs(&case, &found[0], &expected).with_context(|| format!("{:?}", case))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn chunks_of_feed_forward_models() -> TractResult<()> {
+    for case in cases()? {
+        if case.to_string_lossy().contains("lstm") {
+            continue;
+        }
+        let input = read_ark(&case.join("input.ark"))?;
+        let whole = computation(&case, input.shape()[0])?.run(&[input.clone()])?;
+        let chunked = computation(&case, 4)?.run(&[input.clone()])?;
+        chunked[0].close_enough(&whole[0], false).with_context(|| format!("{:?}", case))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::path::Path;
+use tract_hir::internal::*;
+
+mod utils;
+use utils::read_ark;
+
+fn model(name: &str) -> TractResult<InferenceModel> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/models").join(name);
+    tract_kaldi::kaldi().model_for_path(path)
+}
+
+fn run(model: InferenceModel, input: &Tensor) -> TractResult<TVec<Arc<Tensor>>> {
+    model
+        .with_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), input.shape()))?
+        .into_optimized()?
+        .into_runnable()?
+        .run(tvec!(input.clone()))
+}
+
+fn input(frames: usize, dim: usize) -> Tensor {
+    let values: Vec<f32> = (0..frames * dim).map(|i| ((i * 7) % 13) as f32 / 4.0 - 1.5).collect();
+    tensor1(&values).into_shape(&[frames, dim]).unwrap()
+}
+
+#[test]
+fn tdnnf_text_and_binary() -> TractResult<()> {
+    let input = input(20, 4);
+    let text = model("tdnnf.raw.txt")?;
+    assert!(text.node_by_name("prefinal-chain.nonlin.0").is_ok());
+    assert!(text.node_by_name("prefinal-chain.nonlin.1").is_ok());
+    let text = run(text, &input)?;
+    let bin = run(model("tdnnf.raw")?, &input)?;
+    assert_eq!(text.len(), 2);
+    for (t, b) in text.iter().zip(bin.iter()) {
+        // lda, tdnnf2.linear and tdnnf2.affine eat two, one and one frames
+        assert_eq!(t.shape(), &[16, 5]);
+        t.close_enough(b, true)?;
+        assert!(t.as_slice::<f32>()?.iter().all(|x| x.is_finite()));
+    }
+    for row in text[1].to_array_view::<f32>()?.outer_iter() {
+        assert!((row.iter().map(|x| x.exp()).sum::<f32>() - 1.0).abs() < 1e-5);
+    }
+    // computed by tests/models/tdnnf_reference.py
+    let models = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/models");
+    text[0].close_enough(&read_ark(&models.join("tdnnf.output.ark"))?, true)?;
+    text[1].close_enough(&read_ark(&models.join("tdnnf.output-xent.ark"))?, true)?;
+    Ok(())
+}
+
+#[test]
+fn dilated_tdnn_with_missing_offsets() -> TractResult<()> {
+    let x = input(16, 2);
+    for name in &["dilated_tdnn.raw", "dilated_tdnn.raw.txt"] {
+        let output = run(model(name)?, &x)?;
+        let x = x.to_array_view::<f32>()?;
+        let output = output[0].to_array_view::<f32>()?;
+        assert_eq!(output.shape(), &[4, 2]);
+        for t in 0..4 {
+            let y0 = x[[t, 0]] + 2.0 * x[[t + 3, 1]] + 0.5;
+            let y1 = x[[t + 6, 0]] + x[[t + 6, 1]] - x[[t + 12, 1]] - 0.5;
+            assert!((output[[t, 0]] - y0).abs() < 1e-5);
+            assert!((output[[t, 1]] - y1).abs() < 1e-5);
+        }
+    }
+    Ok(())
+}
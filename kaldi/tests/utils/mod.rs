@@ -0,0 +1,16 @@
+use std::path::Path;
+use tract_hir::internal::*;
+
+/// Reads a single matrix from a text archive.
+pub fn read_ark(path: &Path) -> TractResult<Tensor> {
+    let text = std::fs::read_to_string(path)?;
+    let start = text.find('[').context("Missing [ in archive")?;
+    let end = text.find(']').context("Missing ] in archive")?;
+    let rows = text[start + 1..end]
+        .lines()
+        .filter(|line| line.trim().len() > 0)
+        .map(|line| line.split_whitespace().map(|x| Ok(x.parse::<f32>()?)).collect())
+        .collect::<TractResult<Vec<Vec<f32>>>>()?;
+    let dim = rows[0].len();
+    Ok(tensor1(&rows.concat()).into_shape(&[rows.len(), dim])?)
+}
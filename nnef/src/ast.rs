@@ -7,6 +7,8 @@ pub mod parse;
 pub struct ProtoModel {
     pub doc: Document,
     pub tensors: Vec<(String, Arc<Tensor>)>,
+    /// Content of graph.quant, by tensor identifier.
+    pub quantization: Option<HashMap<String, QuantFormat>>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -143,6 +145,40 @@ pub enum RValue {
     Invocation(Invocation),
 }
 
+impl RValue {
+    /// Calls `f` on this value and, recursively, on every value it contains.
+    pub fn visit<'a>(&'a self, f: &mut dyn FnMut(&'a RValue)) {
+        f(self);
+        match self {
+            RValue::Identifier(_) | RValue::Literal(_) => (),
+            RValue::Binary(left, _, right) => {
+                left.visit(f);
+                right.visit(f);
+            }
+            RValue::Unary(_, rv) => rv.visit(f),
+            RValue::Tuple(items) | RValue::Array(items) => items.iter().for_each(|rv| rv.visit(f)),
+            RValue::Subscript(rv, sub) => {
+                rv.visit(f);
+                match &**sub {
+                    Subscript::Single(rv) => rv.visit(f),
+                    Subscript::Range(a, b) => a.iter().chain(b.iter()).for_each(|rv| rv.visit(f)),
+                }
+            }
+            RValue::Comprehension(c) => {
+                c.loop_iters.iter().for_each(|(_, rv)| rv.visit(f));
+                c.filter.iter().for_each(|rv| rv.visit(f));
+                c.yields.visit(f);
+            }
+            RValue::IfThenElse(ite) => {
+                ite.cond.visit(f);
+                ite.then.visit(f);
+                ite.otherwise.visit(f);
+            }
+            RValue::Invocation(inv) => inv.arguments.iter().for_each(|arg| arg.rvalue.visit(f)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Comprehension {
     pub loop_iters: Vec<(String, RValue)>,
@@ -35,6 +35,28 @@ impl<'a> Dumper<'a> {
         Ok(())
     }
 
+    pub fn quantization(&mut self, quantization: &HashMap<String, QuantFormat>) -> TractResult<()> {
+        for (id, format) in quantization.iter().sorted_by_key(|(id, _)| id.to_string()) {
+            write!(self.w, "{:?}: ", id)?;
+            match format {
+                QuantFormat::Linear { min, max, bits } => write!(
+                    self.w,
+                    "linear_quantize(min = {:?}, max = {:?}, bits = {})",
+                    min, max, bits
+                )?,
+                QuantFormat::ZeroPointLinear { zero_point, scale, bits, signed, symmetric } => {
+                    write!(
+                        self.w,
+                        "zero_point_linear_quantize(zero_point = {}, scale = {:?}, bits = {}, signed = {}, symmetric = {})",
+                        zero_point, scale, bits, signed, symmetric
+                    )?
+                }
+            }
+            writeln!(self.w, ";")?;
+        }
+        Ok(())
+    }
+
     pub fn fragments(&mut self, defs: &[FragmentDef]) -> TractResult<()> {
         for fragment_def in defs.iter().sorted_by_key(|frag| &frag.decl.id) {
             self.fragment_def(&fragment_def)?
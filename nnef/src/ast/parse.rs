@@ -31,6 +31,74 @@ pub fn parse_parameters(doc: &str) -> TractResult<Vec<Parameter>> {
     all_consuming(parameter_list)(doc).map(|pair| pair.1).map_err(translate_error)
 }
 
+#[inline(never)]
+pub fn parse_quantization(doc: &str) -> TractResult<Vec<(String, QuantFormat)>> {
+    let entries = all_consuming(quantization)(doc).map(|pair| pair.1).map_err(translate_error)?;
+    let mut formats = vec![];
+    for (id, invocation) in entries {
+        if let Some(format) = quant_format(&invocation)? {
+            formats.push((id, format));
+        } else {
+            warn!("Ignore unsupported quantization {} for tensor {:?}", invocation.id, id);
+        }
+    }
+    Ok(formats)
+}
+
+fn quant_format(invocation: &Invocation) -> TractResult<Option<QuantFormat>> {
+    let arg = |name: &str| -> TractResult<&Literal> {
+        invocation
+            .arguments
+            .iter()
+            .find(|arg| arg.id.as_deref() == Some(name))
+            .and_then(|arg| if let RValue::Literal(lit) = &arg.rvalue { Some(lit) } else { None })
+            .with_context(|| format!("Expected a literal {} argument in {:?}", name, invocation))
+    };
+    let num = |name: &str| -> TractResult<f32> {
+        if let Literal::Numeric(n) = arg(name)? {
+            Ok(n.parse::<f32>()?)
+        } else {
+            bail!("Expected a numeric {} argument in {:?}", name, invocation)
+        }
+    };
+    let logical = |name: &str| -> TractResult<bool> {
+        if let Literal::Logical(b) = arg(name)? {
+            Ok(*b)
+        } else {
+            bail!("Expected a logical {} argument in {:?}", name, invocation)
+        }
+    };
+    let bits = || -> TractResult<usize> {
+        let bits = num("bits")?;
+        if bits.fract() != 0.0 || !(1.0..=32.0).contains(&bits) {
+            bail!("Expected bits to be an integer from 1 to 32 in {:?}", invocation)
+        }
+        Ok(bits as usize)
+    };
+    let format = match &*invocation.id {
+        "linear_quantize" => {
+            QuantFormat::Linear { min: num("min")?, max: num("max")?, bits: bits()? }
+        }
+        "zero_point_linear_quantize" => QuantFormat::ZeroPointLinear {
+            zero_point: num("zero_point")? as i32,
+            scale: num("scale")?,
+            bits: bits()?,
+            signed: logical("signed")?,
+            symmetric: logical("symmetric")?,
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(format))
+}
+
+// <quantization> ::= (<string-literal> ":" <invocation> ";")*
+fn quantization(i: &str) -> IResult<&str, Vec<(String, Invocation)>> {
+    spaced(many0(pair(
+        terminated(spaced(string_literal), stag(":")),
+        terminated(invocation, stag(";")),
+    )))(i)
+}
+
 // <document> ::= <version> <extension>* <fragmentdefinition>* <graph-definition>
 fn document(i: &str) -> IResult<&str, Document> {
     map(
@@ -55,8 +123,15 @@ fn version(i: &str) -> IResult<&str, NumericLiteral> {
 }
 
 // <extension> ::= "extension" <identifier>+ ";"
+// (tract relaxes this to an identifier followed by free tokens, for instance
+// `extension tract_assert S >= 1;`)
 fn extension(i: &str) -> IResult<&str, Vec<String>> {
-    delimited(stag("extension"), many1(spaced(identifier)), stag(";"))(i)
+    map(
+        delimited(stag("extension"), pair(spaced(identifier), take_until(";")), stag(";")),
+        |(id, rest)| {
+            std::iter::once(id).chain(rest.split_whitespace().map(|s| s.to_string())).collect()
+        },
+    )(i)
 }
 
 // FRAGMENT
@@ -254,7 +329,7 @@ fn rvalue(i: &str) -> IResult<&str, RValue> {
             map(invocation, RValue::Invocation),
             map(literal, RValue::Literal),
             map(identifier, RValue::Identifier),
-            map(pair(spaced(recognize(one_of("+-!"))), rvalue), |(op, rv)| {
+            map(pair(spaced(recognize(one_of("+-!"))), sub), |(op, rv)| {
                 RValue::Unary(op.into(), Box::new(rv))
             }),
             map(delimited(tag("("), separated_list(stag(","), rvalue), tag(")")), |mut rvs| {
@@ -307,7 +382,7 @@ fn rvalue(i: &str) -> IResult<&str, RValue> {
     bin!(exp, sub, tag("^"));
     bin!(mul, exp, one_of("*/"));
     bin!(add, mul, one_of("+-"));
-    bin!(comp, add, alt((tag("=="), tag("!="), tag("<"), tag(">"), tag("<="), tag(">="))));
+    bin!(comp, add, alt((tag("=="), tag("!="), tag("<="), tag(">="), tag("<"), tag(">"))));
     bin!(boolean, comp, alt((tag("||"), tag("&&"))));
     bin!(in_for, boolean, tag("in"));
 
@@ -315,7 +390,7 @@ fn rvalue(i: &str) -> IResult<&str, RValue> {
     fn ite(i: &str) -> IResult<&str, RValue> {
         spaced(alt((
             map(
-                tuple((in_for, stag("if"), in_for, stag("else"), in_for)),
+                tuple((in_for, stag("if"), in_for, stag("else"), ite)),
                 |(then, _, cond, _, otherwise)| {
                     RValue::IfThenElse(Box::new(IfThenElse { cond, then, otherwise }))
                 },
@@ -459,6 +534,18 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_extension() {
+        assert_eq!(
+            p(extension, "extension tract_registry tract_core;"),
+            vec!("tract_registry", "tract_core")
+        );
+        assert_eq!(
+            p(extension, "extension tract_assert S >= 1;"),
+            vec!("tract_assert", "S", ">=", "1")
+        );
+    }
+
     #[test]
     fn test_fragment_decl_fizz() {
         let parsed = p(
@@ -675,6 +762,45 @@ mod test {
         p(lvalue, "(foo,bar)");
     }
 
+    #[test]
+    fn test_quantization() {
+        let parsed = parse_quantization(
+            r#"
+            "input": zero_point_linear_quantize(zero_point = 128, scale = 0.5, bits = 8, signed = false, symmetric = false);
+            "conv": linear_quantize(min = -1.0, max = 1.0, bits = 8);
+            "weights": logarithmic_quantize(max = 1.0, bits = 8);
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            vec!(
+                (
+                    "input".to_string(),
+                    QuantFormat::ZeroPointLinear {
+                        zero_point: 128,
+                        scale: 0.5,
+                        bits: 8,
+                        signed: false,
+                        symmetric: false
+                    }
+                ),
+                ("conv".to_string(), QuantFormat::Linear { min: -1.0, max: 1.0, bits: 8 })
+            )
+        );
+    }
+
+    #[test]
+    fn test_quantization_bits() {
+        for bits in &["0", "33", "64", "-8", "7.5"] {
+            let doc =
+                format!(r#""conv": linear_quantize(min = -1.0, max = 1.0, bits = {});"#, bits);
+            assert!(parse_quantization(&doc).is_err(), "bits = {}", bits);
+        }
+        let doc = r#""conv": linear_quantize(min = -1.0, max = 1.0, bits = 32);"#;
+        assert!(parse_quantization(doc).is_ok());
+    }
+
     #[test]
     fn test_graph_def() {
         p(graph_def, "graph foo() -> () {}");
@@ -720,6 +846,29 @@ mod test {
         p(rvalue, "scalar(2 ^ (bits - 1) - integer(symmetric) if signed else 0)");
     }
 
+    #[test]
+    fn test_unary_precedence() {
+        let minus = |rv| RValue::Unary("-".into(), Box::new(rv));
+        let r = || RValue::Identifier("r".into());
+        assert_eq!(
+            p(rvalue, "-r - 1.0"),
+            RValue::Binary(
+                Box::new(minus(r())),
+                "-".into(),
+                Box::new(RValue::Literal(Literal::Numeric("1.0".into())))
+            )
+        );
+        p(rvalue, "0.0 if !signed else -r if symmetric else -r - 1.0");
+        assert_eq!(
+            p(rvalue, "a <= b"),
+            RValue::Binary(
+                Box::new(RValue::Identifier("a".into())),
+                "<=".into(),
+                Box::new(RValue::Identifier("b".into()))
+            )
+        );
+    }
+
     #[test]
     fn test_comprehenion() {
         p(comprehension_expr, "[for i in range_of(output_size) yield output_size * sampling_rate]");
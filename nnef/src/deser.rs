@@ -1,5 +1,6 @@
 use crate::ast::*;
 use crate::internal::*;
+use tract_core::itertools::Itertools;
 
 pub struct ModelBuilder<'a> {
     pub framework: &'a Nnef,
@@ -23,18 +24,7 @@ impl<'mb> ModelBuilder<'mb> {
     }
 
     fn translate(&mut self) -> TractResult<()> {
-        for ext in &self.proto_model.doc.extension {
-            match &*ext[0] {
-                "tract_registry" => {
-                    if self.framework.registries.iter().any(|reg| reg.id == ext[1]) {
-                        self.registries.push(ext[1].to_string())
-                    } else {
-                        bail!("Registry not found {}", &ext[1])
-                    }
-                }
-                _ => warn!("Ignore unknown extension {}", ext.join(" ")),
-            };
-        }
+        self.check_compatibility()?;
         self.scopes.push(HashMap::new());
         self.wire_body(&self.proto_model.doc.graph_def.body)?;
         let vars = self.scopes.pop().unwrap();
@@ -63,6 +53,118 @@ impl<'mb> ModelBuilder<'mb> {
         Ok(())
     }
 
+    /// Checks the document against what this tract supports before wiring
+    /// anything, reporting all the problems at once.
+    fn check_compatibility(&mut self) -> TractResult<()> {
+        use crate::framework::{KHR_EXTENSIONS, SUPPORTED_TRACT_NNEF_FORMAT_VERSIONS};
+        let doc = &self.proto_model.doc;
+        let mut problems: Vec<String> = vec![];
+        let mut version = doc.version.split('.').map(|v| v.parse::<usize>());
+        if let (Some(Ok(1)), Some(Ok(0)), None) = (version.next(), version.next(), version.next()) {
+        } else {
+            problems.push(format!("NNEF version {} (tract supports 1.0)", doc.version));
+        }
+        if let Some(format) = tract_format_version(doc) {
+            if !SUPPORTED_TRACT_NNEF_FORMAT_VERSIONS.contains(&&*format) {
+                problems.push(format!(
+                    "tract NNEF format version {} (this tract supports {})",
+                    format,
+                    SUPPORTED_TRACT_NNEF_FORMAT_VERSIONS.join(", ")
+                ));
+            }
+        }
+        let mut khr_flags: Vec<&str> = vec![];
+        for ext in &doc.extension {
+            match &*ext[0] {
+                "tract_registry" => {
+                    if self.framework.registries.iter().any(|reg| reg.id == ext[1]) {
+                        self.registries.push(ext[1].to_string())
+                    } else {
+                        problems.push(format!("registry {} (not found)", ext[1]))
+                    }
+                }
+                "tract_assert" => {
+                    let assertion = self.model.symbol_table.parse_assertion(&ext[1..].join(" "))?;
+                    self.model.symbol_table.add_assertion(assertion);
+                }
+                khr if khr.starts_with("KHR_") => {
+                    // extension KHR_enable_fragment_definitions, KHR_enable_operator_expressions;
+                    for flag in ext.iter().flat_map(|s| s.split(',')).filter(|s| s.len() > 0) {
+                        if KHR_EXTENSIONS.contains(&flag) {
+                            khr_flags.push(flag)
+                        } else {
+                            problems.push(format!("extension {}", flag))
+                        }
+                    }
+                }
+                _ => warn!("Ignore unknown extension {}", ext.join(" ")),
+            };
+        }
+        // Documents without any KHR flag predate them: they are not checked
+        // for the features the flags enable.
+        if khr_flags.len() > 0 {
+            if !khr_flags.contains(&"KHR_enable_fragment_definitions") && doc.fragments.len() > 0 {
+                problems.push(
+                    "fragment definitions (missing extension KHR_enable_fragment_definitions)"
+                        .to_string(),
+                );
+            }
+            if !khr_flags.contains(&"KHR_enable_operator_expressions") {
+                let mut expressions = false;
+                for assignment in &doc.graph_def.body {
+                    assignment.right.visit(&mut |rv| match rv {
+                        RValue::Identifier(_)
+                        | RValue::Literal(_)
+                        | RValue::Tuple(_)
+                        | RValue::Array(_)
+                        | RValue::Invocation(_) => (),
+                        _ => expressions = true,
+                    });
+                }
+                if expressions {
+                    problems.push(
+                        "operator expressions in graph body (missing extension KHR_enable_operator_expressions)"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+        let mut invoked: Vec<&str> = vec![];
+        let bodies = std::iter::once(&doc.graph_def.body)
+            .chain(doc.fragments.iter().filter_map(|f| f.body.as_ref()));
+        for body in bodies {
+            for assignment in body {
+                assignment.right.visit(&mut |rv| {
+                    if let RValue::Invocation(inv) = rv {
+                        invoked.push(&inv.id)
+                    }
+                });
+            }
+        }
+        for id in invoked.into_iter().sorted().dedup() {
+            if BUILTINS.contains(&id)
+                || doc.fragments.iter().any(|f| f.decl.id == id && f.body.is_some())
+                || self
+                    .framework
+                    .registries
+                    .iter()
+                    .any(|reg| self.registries.contains(&reg.id) && reg.handles(id))
+            {
+                continue;
+            }
+            if let Some(reg) = self.framework.registries.iter().find(|reg| reg.handles(id)) {
+                problems
+                    .push(format!("operator {} (needs extension tract_registry {})", id, reg.id));
+            } else {
+                problems.push(format!("operator {}", id));
+            }
+        }
+        if problems.len() > 0 {
+            bail!("Unsupported NNEF features: {}", problems.join(", "));
+        }
+        Ok(())
+    }
+
     pub fn into_typed_model(mut self) -> Result<TypedModel, (TypedModel, TractError)> {
         match self.translate() {
             Ok(()) => Ok(self.model),
@@ -71,30 +173,50 @@ impl<'mb> ModelBuilder<'mb> {
     }
 
     pub fn wire_body(&mut self, body: &[Assignment]) -> TractResult<()> {
-        // todo: can i relax the outlet id constraint ?
         for assignment in body {
             let identifiers = assignment.left.to_identifiers()?;
             self.naming_scopes.push(identifiers[0].to_string());
-            let values: TVec<OutletId> =
-                assignment.right.resolve(self).and_then(|v| v.to(self)).with_context(|| {
-                    format!("Plugging in assignement for {:?}", identifiers.join(", "))
-                })?;
-            if values.len() != identifiers.len() {
-                bail!(
-                    "Assignement for {} received {} value(s).",
-                    identifiers.join(","),
-                    values.len()
-                )
-            }
-            self.model.node_mut(values[0].node).name = format!("{}", self.naming_scopes.join("."));
-            for (id, outlet) in identifiers.iter().zip(values.iter()) {
-                self.scopes.last_mut().unwrap().insert(id.to_string(), Value::Wire(*outlet));
+            let value = assignment.right.resolve(self).with_context(|| {
+                format!("Plugging in assignement for {:?}", identifiers.join(", "))
+            })?;
+            let values = match value {
+                Value::Tuple(values) if values.len() == identifiers.len() => values,
+                value if identifiers.len() == 1 => vec![value],
+                value => bail!("Assignement for {} received {:?}.", identifiers.join(","), value),
+            };
+            let mut renamed = false;
+            for (id, value) in identifiers.iter().zip(values.into_iter()) {
+                let value = match value {
+                    Value::Tuple(mut values) if values.len() == 1 => values.remove(0),
+                    value => value,
+                };
+                if let Value::Wire(outlet) = value {
+                    if !renamed {
+                        self.model.node_mut(outlet.node).name =
+                            format!("{}", self.naming_scopes.join("."));
+                        renamed = true;
+                    }
+                    if let Some(quant) = self.quantization(id) {
+                        let fact = self.model.outlet_fact(outlet)?.clone().with_quant(Some(quant));
+                        self.model.set_outlet_fact(outlet, fact)?;
+                    }
+                }
+                // values known at build time (integers, arrays, ...) are kept as is
+                self.scopes.last_mut().unwrap().insert(id.to_string(), value);
             }
             self.naming_scopes.pop();
         }
         Ok(())
     }
 
+    /// Quantization format given by graph.quant for a graph level identifier.
+    pub fn quantization(&self, id: &str) -> Option<QuantFormat> {
+        if self.scopes.len() != 1 {
+            return None;
+        }
+        self.proto_model.quantization.as_ref().and_then(|q| q.get(id)).cloned()
+    }
+
     pub fn wire_invocation(&mut self, invocation: &Invocation) -> TractResult<Value> {
         for frag in &self.proto_model.doc.fragments {
             if frag.decl.id == invocation.id && frag.body.is_some() {
@@ -138,6 +260,52 @@ impl<'mb> ModelBuilder<'mb> {
         ))
     }
 
+    /// Wire an element-wise unary operator from the active registries.
+    pub fn wire_unary(&mut self, id: &str, input: OutletId) -> TractResult<Value> {
+        let framework = self.framework;
+        let op = framework
+            .registries
+            .iter()
+            .filter(|reg| self.registries.contains(&reg.id))
+            .find_map(|reg| reg.unit_element_wise_ops.iter().find(|ew| ew.0 == id))
+            .map(|ew| ew.1.clone())
+            .ok_or_else(|| format_err!("No definition for operator `{}'", id))?;
+        let op = tract_core::ops::element_wise::ElementWiseOp(op);
+        Ok(Value::Wire(self.wire(op, &[input])?[0]))
+    }
+
+    /// Wire a binary operator from the active registries. Operands known at
+    /// build time are cast to the type of the other one.
+    pub fn wire_binary(&mut self, id: &str, left: &Value, right: &Value) -> TractResult<Value> {
+        let framework = self.framework;
+        let op = framework
+            .registries
+            .iter()
+            .filter(|reg| self.registries.contains(&reg.id))
+            .find_map(|reg| reg.binary_ops.iter().find(|bin| bin.0 == id))
+            .map(|bin| bin.1.clone())
+            .ok_or_else(|| format_err!("No definition for operator `{}'", id))?;
+        let (a, b) = match (left.as_number(), right.as_number()) {
+            (Some(a), None) => {
+                let b = right.to::<OutletId>(self)?;
+                let dt = self.model.outlet_fact(b)?.datum_type;
+                (self.add_const(a.cast_to_dt(dt)?.into_owned())?, b)
+            }
+            (None, Some(b)) => {
+                let a = left.to::<OutletId>(self)?;
+                let dt = self.model.outlet_fact(a)?.datum_type;
+                (a, self.add_const(b.cast_to_dt(dt)?.into_owned())?)
+            }
+            _ => (left.to::<OutletId>(self)?, right.to::<OutletId>(self)?),
+        };
+        let inputs = crate::registry::multicast(self, &[a, b])?;
+        Ok(Value::Wire(self.wire(tract_core::ops::binary::TypedBinOp(op), &inputs)?[0]))
+    }
+
+    fn add_const(&mut self, tensor: Tensor) -> TractResult<OutletId> {
+        Ok(self.wire(tract_core::ops::konst::Const::new(tensor.into_arc_tensor()), &[])?[0])
+    }
+
     pub fn wire(
         &mut self,
         op: impl Into<Box<dyn TypedOp>>,
@@ -260,8 +428,18 @@ impl RValue {
                     .ok_or_else(|| format_err!("No value for name {}", id))?;
                 Ok(outlet)
             }
-            RValue::Invocation(inv) => builder.wire_invocation(inv),
+            RValue::Invocation(inv) => {
+                if let Some(value) = builtin(builder, inv)? {
+                    return Ok(value);
+                }
+                builder.wire_invocation(inv)
+            }
             RValue::Binary(left, op, right) => {
+                let left = left.resolve(builder)?;
+                let right = right.resolve(builder)?;
+                if let Some(value) = static_binary(op, &left, &right)? {
+                    return Ok(value);
+                }
                 let op = match &**op {
                     "+" => "add",
                     "-" => "sub",
@@ -274,17 +452,95 @@ impl RValue {
                     "!=" => "ne",
                     ">=" => "ge",
                     "<=" => "le",
+                    "&&" => "and",
+                    "||" => "or",
                     op => bail!("Unknown binary operator: {}", op),
                 };
-                let inv = Invocation {
-                    id: op.to_string(),
-                    generic_type_name: None,
-                    arguments: vec![
-                        Argument { id: None, rvalue: left.as_ref().clone() },
-                        Argument { id: None, rvalue: right.as_ref().clone() },
-                    ],
+                builder.wire_binary(op, &left, &right)
+            }
+            RValue::Unary(op, rv) => {
+                let value = rv.resolve(builder)?;
+                match (&**op, value) {
+                    ("+", value) => Ok(value),
+                    ("-", Value::Dim(d)) => Ok(Value::Dim(-d)),
+                    ("-", Value::Scalar(f)) => Ok(Value::Scalar(-f)),
+                    ("!", Value::Bool(b)) => Ok(Value::Bool(!b)),
+                    (op, value) => {
+                        let id = match op {
+                            "-" => "neg",
+                            "!" => "not",
+                            _ => bail!("Unknown unary operator: {}", op),
+                        };
+                        let input = value.to::<OutletId>(builder)?;
+                        builder.wire_unary(id, input)
+                    }
+                }
+            }
+            RValue::Subscript(rv, subscript) => {
+                let value = rv.resolve(builder)?;
+                let items = match &value {
+                    Value::Array(items) | Value::Tuple(items) => items,
+                    _ => bail!("Subscripts are only supported on arrays, got {:?}", value),
                 };
-                builder.wire_invocation(&inv)
+                match &**subscript {
+                    Subscript::Single(ix) => {
+                        let ix: usize = ix.resolve(builder)?.to(builder)?;
+                        items
+                            .get(ix)
+                            .cloned()
+                            .ok_or_else(|| format_err!("Index {} out of range for {:?}", ix, value))
+                    }
+                    Subscript::Range(begin, end) => {
+                        let begin: usize = if let Some(begin) = begin {
+                            begin.resolve(builder)?.to(builder)?
+                        } else {
+                            0
+                        };
+                        let end: usize = if let Some(end) = end {
+                            end.resolve(builder)?.to(builder)?
+                        } else {
+                            items.len()
+                        };
+                        if begin > end || end > items.len() {
+                            bail!("Range {}:{} out of range for {:?}", begin, end, value)
+                        }
+                        Ok(Value::Array(items[begin..end].to_vec()))
+                    }
+                }
+            }
+            RValue::Comprehension(comp) => {
+                let iters = comp
+                    .loop_iters
+                    .iter()
+                    .map(|(id, rv)| Ok((id, rv.resolve(builder)?.to::<TVec<Value>>(builder)?)))
+                    .collect::<TractResult<Vec<_>>>()?;
+                let len = iters.iter().map(|(_, items)| items.len()).min().unwrap_or(0);
+                let mut items = vec![];
+                for ix in 0..len {
+                    let mut scope = builder.scopes.last().unwrap().clone();
+                    for (id, values) in &iters {
+                        scope.insert(id.to_string(), values[ix].clone());
+                    }
+                    builder.scopes.push(scope);
+                    let item = (|| {
+                        if let Some(filter) = &comp.filter {
+                            if !filter.resolve(builder)?.to::<bool>(builder)? {
+                                return Ok(None);
+                            }
+                        }
+                        comp.yields.resolve(builder).map(Some)
+                    })();
+                    builder.scopes.pop();
+                    items.extend(item?);
+                }
+                Ok(Value::Array(items))
+            }
+            RValue::IfThenElse(ite) => {
+                if ite.cond.resolve(builder)?.to::<bool>(builder)? {
+                    ite.then.resolve(builder)
+                } else {
+                    ite.otherwise.resolve(builder)
+                }
             }
             RValue::Array(array) => Ok(Value::Array(
                 array.iter().map(|i| i.resolve(builder)).collect::<TractResult<_>>()?,
@@ -311,11 +567,147 @@ impl RValue {
                     .map(|i| RValue::Literal(i.clone()).resolve(builder))
                     .collect::<TractResult<_>>()?,
             )),
-            _ => panic!("{:?}", self),
+            RValue::Literal(Literal::Tuple(tuple)) => Ok(Value::Tuple(
+                tuple
+                    .iter()
+                    .map(|i| RValue::Literal(i.clone()).resolve(builder))
+                    .collect::<TractResult<_>>()?,
+            )),
         }
     }
 }
 
+/// Functions evaluated at model build time.
+const BUILTINS: &[&str] = &["length_of", "range_of", "shape_of", "scalar", "integer", "logical"];
+
+/// tract format version recorded in the properties written by the serializer.
+fn tract_format_version(doc: &Document) -> Option<String> {
+    let properties = doc
+        .fragments
+        .iter()
+        .find(|f| f.decl.id == "tract_core_properties")
+        .and_then(|f| f.body.as_ref())
+        .and_then(|body| body.get(0))?;
+    let mut version = None;
+    properties.right.visit(&mut |rv| {
+        if let RValue::Tuple(pair) = rv {
+            if let [RValue::Literal(Literal::String(key)), RValue::Literal(Literal::String(value))] =
+                &**pair
+            {
+                if key == "tract_nnef_format_version" {
+                    version = Some(value.clone())
+                }
+            }
+        }
+    });
+    version
+}
+
+/// Functions of the NNEF language, evaluated at build time.
+fn builtin(builder: &mut ModelBuilder, inv: &Invocation) -> TractResult<Option<Value>> {
+    let id = &*inv.id;
+    if !BUILTINS.contains(&id) {
+        return Ok(None);
+    }
+    if inv.arguments.len() != 1 {
+        bail!("{} expects one argument, got {:?}", id, inv.arguments)
+    }
+    let arg = inv.arguments[0].rvalue.resolve(builder)?;
+    let value = match (id, &arg) {
+        ("length_of", Value::Array(items)) | ("length_of", Value::Tuple(items)) => {
+            Value::Dim(items.len().to_dim())
+        }
+        ("length_of", Value::String(s)) => Value::Dim(s.len().to_dim()),
+        ("range_of", Value::Array(items)) | ("range_of", Value::Tuple(items)) => {
+            Value::Array((0..items.len()).map(|i| Value::Dim(i.to_dim())).collect())
+        }
+        ("range_of", Value::String(s)) => {
+            Value::Array((0..s.len()).map(|i| Value::Dim(i.to_dim())).collect())
+        }
+        ("shape_of", _) => {
+            let wire = arg.to::<OutletId>(builder)?;
+            let shape = builder.model.outlet_fact(wire)?.shape.to_tvec();
+            Value::Array(shape.into_iter().map(Value::Dim).collect())
+        }
+        ("scalar", Value::Scalar(_)) => arg.clone(),
+        ("scalar", Value::Dim(d)) => Value::Scalar(d.to_i64()? as f32),
+        ("scalar", Value::Bool(b)) => Value::Scalar(*b as usize as f32),
+        ("integer", Value::Scalar(f)) => Value::Dim((*f as i64).to_dim()),
+        ("integer", Value::Dim(_)) => arg.clone(),
+        ("integer", Value::Bool(b)) => Value::Dim((*b as i64).to_dim()),
+        ("logical", Value::Scalar(f)) => Value::Bool(*f != 0.0),
+        ("logical", Value::Dim(d)) => Value::Bool(d.to_i64()? != 0),
+        ("logical", Value::Bool(_)) => arg.clone(),
+        _ => bail!("Unsupported argument for {}: {:?}", id, arg),
+    };
+    Ok(Some(value))
+}
+
+/// Binary operators on values known at build time: arithmetic and
+/// comparisons on numbers and logicals, concatenation (`+`) and repetition
+/// (`*`) of arrays.
+fn static_binary(op: &str, left: &Value, right: &Value) -> TractResult<Option<Value>> {
+    use Value::*;
+    let value = match (left, right) {
+        (Array(a), Array(b)) if op == "+" => Array(a.iter().chain(b.iter()).cloned().collect()),
+        (Array(a), Dim(n)) | (Dim(n), Array(a)) if op == "*" => {
+            Array((0..n.to_usize()?).flat_map(|_| a.iter().cloned()).collect())
+        }
+        (Bool(a), Bool(b)) => match op {
+            "&&" => Bool(*a && *b),
+            "||" => Bool(*a || *b),
+            "==" => Bool(a == b),
+            "!=" => Bool(a != b),
+            _ => bail!("Unsupported operator {} on logicals", op),
+        },
+        (String(a), String(b)) => match op {
+            "+" => String(format!("{}{}", a, b)),
+            "==" => Bool(a == b),
+            "!=" => Bool(a != b),
+            _ => bail!("Unsupported operator {} on strings", op),
+        },
+        (Dim(a), Dim(b)) if ["+", "-", "*"].contains(&op) => match op {
+            "+" => Dim(a.clone() + b),
+            "-" => Dim(a.clone() - b),
+            _ => Dim(a.clone() * b),
+        },
+        (Dim(a), Dim(b)) => {
+            let (a, b) = (a.to_i64()?, b.to_i64()?);
+            match op {
+                "/" => Dim((a / b).to_dim()),
+                "^" => Dim(a.pow(b as u32).to_dim()),
+                _ => Bool(compare(op, a, b)?),
+            }
+        }
+        (Dim(_), Scalar(_)) | (Scalar(_), Dim(_)) | (Scalar(_), Scalar(_)) => {
+            let a = left.as_number().unwrap().cast_to_scalar::<f32>()?;
+            let b = right.as_number().unwrap().cast_to_scalar::<f32>()?;
+            match op {
+                "+" => Scalar(a + b),
+                "-" => Scalar(a - b),
+                "*" => Scalar(a * b),
+                "/" => Scalar(a / b),
+                "^" => Scalar(a.powf(b)),
+                _ => Bool(compare(op, a, b)?),
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+fn compare<T: PartialOrd>(op: &str, a: T, b: T) -> TractResult<bool> {
+    Ok(match op {
+        "<" => a < b,
+        ">" => a > b,
+        "<=" => a <= b,
+        ">=" => a >= b,
+        "==" => a == b,
+        "!=" => a != b,
+        _ => bail!("Unsupported operator {} on numbers", op),
+    })
+}
+
 #[derive(Clone, Debug)]
 pub enum Value {
     Tensor(Arc<Tensor>),
@@ -329,6 +721,16 @@ pub enum Value {
 }
 
 impl Value {
+    /// Numbers and logicals known at build time, as scalar tensors.
+    fn as_number(&self) -> Option<Tensor> {
+        match self {
+            Value::Scalar(f) => Some(tensor0(*f)),
+            Value::Dim(d) => d.to_i64().ok().map(tensor0),
+            Value::Bool(b) => Some(tensor0(*b)),
+            _ => None,
+        }
+    }
+
     pub fn to<T>(&self, builder: &mut ModelBuilder) -> TractResult<T>
     where
         T: CoerceFrom<Value>,
@@ -372,6 +774,10 @@ impl CoerceFrom<Value> for OutletId {
             Value::Scalar(f) => {
                 Ok(builder.wire(tract_core::ops::konst::Const::new(rctensor0(*f)), &[])?[0])
             }
+            Value::Dim(_) | Value::Bool(_) => {
+                let tensor = from.as_number().context("Not a number")?;
+                builder.add_const(tensor)
+            }
             Value::Wire(outlet) => Ok(*outlet),
             Value::Tuple(tuple) if tuple.len() == 1 => OutletId::coerce(builder, &tuple[0]),
             _ => bail!("Can not build an outletid from {:?}", from),
@@ -389,9 +795,10 @@ impl CoerceFrom<Value> for i64 {
 }
 
 impl CoerceFrom<Value> for TDim {
-    fn coerce(_builder: &mut ModelBuilder, from: &Value) -> TractResult<Self> {
+    fn coerce(builder: &mut ModelBuilder, from: &Value) -> TractResult<Self> {
         match from {
             Value::Dim(d) => Ok(d.clone()),
+            Value::String(s) => builder.model.symbol_table.parse_tdim(s),
             _ => bail!("Can not build a TDim from {:?}", from),
         }
     }
@@ -1,7 +1,21 @@
-use std::path::Path;
-use std::io::{Read};
 use crate::ast::ProtoModel;
 use crate::internal::*;
+use crate::mmap::Mmap;
+use crate::tensors::read_tensor_in_place;
+use std::any::Any;
+use std::io::Read;
+use std::path::Path;
+
+/// Version of the tract conventions on top of NNEF (properties, registries,
+/// extensions) written by this version of tract.
+pub const TRACT_NNEF_FORMAT_VERSION: &str = "alpha1";
+
+/// Versions of the tract conventions this version of tract can load.
+pub const SUPPORTED_TRACT_NNEF_FORMAT_VERSIONS: &[&str] = &["alpha1"];
+
+/// Khronos extension flags understood by the loader.
+pub const KHR_EXTENSIONS: &[&str] =
+    &["KHR_enable_fragment_definitions", "KHR_enable_operator_expressions"];
 
 pub fn stdlib() -> Vec<FragmentDef> {
     crate::ast::parse::parse_fragments(include_str!("../stdlib.nnef")).unwrap()
@@ -10,11 +24,20 @@ pub fn stdlib() -> Vec<FragmentDef> {
 pub struct Nnef {
     pub stdlib: Vec<FragmentDef>,
     pub registries: Vec<Registry>,
+    /// Serialize repeated subgraphs as fragments.
+    pub compound_fragments: bool,
+    /// Memory map directories and uncompressed tars on load.
+    pub mmap: bool,
 }
 
 impl Nnef {
     pub fn new() -> Nnef {
-        Nnef { stdlib: stdlib(), registries: vec![crate::ops::tract_nnef()] }
+        Nnef {
+            stdlib: stdlib(),
+            registries: vec![crate::ops::tract_nnef()],
+            compound_fragments: false,
+            mmap: false,
+        }
     }
 
     pub fn with_registry(mut self, registry: Registry) -> Nnef {
@@ -22,6 +45,21 @@ impl Nnef {
         self
     }
 
+    /// Serialize subgraphs repeated under different name scopes (e.g.
+    /// `layer_0/...`, `layer_1/...`) as a single fragment, invoked once per
+    /// occurrence with its own weights. Off by default.
+    pub fn with_compound_fragments(self, compound_fragments: bool) -> Self {
+        Nnef { compound_fragments, ..self }
+    }
+
+    /// Memory map the `.dat` files of directories and uncompressed tars
+    /// given to `proto_model_for_path`: constant tensors borrow their data
+    /// from the mapping instead of copying it. Mapped files must not be
+    /// modified as long as the model is alive. Only effective on unix.
+    pub fn with_mmap(self, mmap: bool) -> Self {
+        Nnef { mmap, ..self }
+    }
+
     pub fn with_tract_core(mut self) -> Self {
         self.registries.push(crate::ops::tract_core());
         self
@@ -54,6 +92,18 @@ impl Nnef {
         header.set_cksum();
         ar.append(&header, &mut &*graph_data)?;
 
+        if let Some(quantization) = &proto_model.quantization {
+            let mut quant_data = vec![];
+            crate::ast::dump::Dumper::new(&mut quant_data).quantization(quantization)?;
+            let mut header = tar::Header::new_gnu();
+            header.set_path("graph.quant")?;
+            header.set_size(quant_data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(now.as_secs());
+            header.set_cksum();
+            ar.append(&header, &mut &*quant_data)?;
+        }
+
         for (label, t) in &proto_model.tensors {
             let label = label.to_string() + ".dat";
             let filename = std::path::Path::new(&label);
@@ -84,6 +134,10 @@ impl Nnef {
         std::fs::create_dir_all(path)?;
         let mut graph_nnef = std::fs::File::create(path.join("graph.nnef"))?;
         crate::ast::dump::Dumper::new(&mut graph_nnef).document(&proto_model.doc)?;
+        if let Some(quantization) = &proto_model.quantization {
+            let mut graph_quant = std::fs::File::create(path.join("graph.quant"))?;
+            crate::ast::dump::Dumper::new(&mut graph_quant).quantization(quantization)?;
+        }
         for (label, t) in &proto_model.tensors {
             let label = label.to_string() + ".dat";
             std::fs::create_dir_all(path.join(&label).parent().unwrap())?;
@@ -103,11 +157,19 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
 
     fn proto_model_for_path(&self, path: impl AsRef<Path>) -> TractResult<ProtoModel> {
         let path = path.as_ref();
+        let mmap = self.mmap && cfg!(unix);
         if path.is_file() {
             let mut f = std::fs::File::open(path)?;
+            if mmap {
+                let mmap = Arc::new(Mmap::map(&f)?);
+                if mmap.as_slice().get(0..2) != Some(&[0x1f, 0x8b]) {
+                    return proto_model_for_mapped_tar(mmap);
+                }
+            }
             return self.proto_model_for_read(&mut f);
         }
         let mut text: Option<String> = None;
+        let mut quant: Option<String> = None;
         let mut tensors: Vec<(String, Arc<Tensor>)> = Default::default();
         for entry in walkdir::WalkDir::new(path) {
             let entry =
@@ -118,15 +180,21 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
                 .skip(path.components().count())
                 .collect::<std::path::PathBuf>();
             let mut stream = std::fs::File::open(entry.path())?;
-            read_stream(&subpath, &mut stream, &mut text, &mut tensors)?;
+            if mmap && is_tensor(&subpath) {
+                let mmap = Arc::new(Mmap::map(&stream)?);
+                let owner: Arc<dyn Any + Send + Sync> = mmap.clone();
+                let tensor = unsafe { read_tensor_in_place(mmap.as_slice(), &owner)? };
+                tensors.push((tensor_id(&subpath)?, tensor.into_arc_tensor()));
+            } else {
+                read_stream(&subpath, &mut stream, &mut text, &mut quant, &mut tensors)?;
+            }
         }
-        let text = text.ok_or_else(|| format_err!("Model must contain graph.nnef at top level"))?;
-        let doc = crate::ast::parse::parse_document(&text)?;
-        Ok(ProtoModel { doc, tensors })
+        proto_model(text, quant, tensors)
     }
 
     fn proto_model_for_read(&self, reader: &mut dyn std::io::Read) -> TractResult<ProtoModel> {
         let mut text: Option<String> = None;
+        let mut quant: Option<String> = None;
         let mut tensors: Vec<(String, Arc<Tensor>)> = Default::default();
         let mut buffer = vec![0u8; 2];
         reader.read_exact(&mut buffer)?;
@@ -146,11 +214,9 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
         for entry in tar.entries()? {
             let mut entry = entry?;
             let path = entry.path()?.to_path_buf();
-            read_stream(&path, &mut entry, &mut text, &mut tensors)?;
+            read_stream(&path, &mut entry, &mut text, &mut quant, &mut tensors)?;
         }
-        let text = text.ok_or_else(|| format_err!("Model must contain graph.nnef at top level"))?;
-        let doc = crate::ast::parse::parse_document(&text)?;
-        Ok(ProtoModel { doc, tensors })
+        proto_model(text, quant, tensors)
     }
 
     fn model_for_proto_model(&self, proto: &ProtoModel) -> TractResult<TypedModel> {
@@ -158,24 +224,76 @@ impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
     }
 }
 
+fn proto_model(
+    text: Option<String>,
+    quant: Option<String>,
+    tensors: Vec<(String, Arc<Tensor>)>,
+) -> TractResult<ProtoModel> {
+    let text = text.ok_or_else(|| format_err!("Model must contain graph.nnef at top level"))?;
+    let doc = crate::ast::parse::parse_document(&text)?;
+    let quantization = if let Some(quant) = quant {
+        Some(crate::ast::parse::parse_quantization(&quant)?.into_iter().collect())
+    } else {
+        None
+    };
+    Ok(ProtoModel { doc, tensors, quantization })
+}
+
+fn proto_model_for_mapped_tar(mmap: Arc<Mmap>) -> TractResult<ProtoModel> {
+    let mut text: Option<String> = None;
+    let mut quant: Option<String> = None;
+    let mut tensors: Vec<(String, Arc<Tensor>)> = Default::default();
+    let owner: Arc<dyn Any + Send + Sync> = mmap.clone();
+    let bytes = mmap.as_slice();
+    let mut tar = tar::Archive::new(bytes);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        if is_tensor(&path) {
+            let start = entry.raw_file_position() as usize;
+            let data = bytes
+                .get(start..start + entry.size() as usize)
+                .ok_or_else(|| format_err!("Truncated tar entry {:?}", path))?;
+            let tensor = unsafe { read_tensor_in_place(data, &owner)? };
+            tensors.push((tensor_id(&path)?, tensor.into_arc_tensor()));
+        } else {
+            read_stream(&path, &mut entry, &mut text, &mut quant, &mut tensors)?;
+        }
+    }
+    proto_model(text, quant, tensors)
+}
+
+fn is_tensor(path: &Path) -> bool {
+    path.extension().map(|e| e == "dat").unwrap_or(false)
+}
+
+fn tensor_id(path: &Path) -> TractResult<String> {
+    let mut path = path.to_path_buf();
+    path.set_extension("");
+    let id = path
+        .to_str()
+        .ok_or_else(|| format_err!("Badly encoded filename for tensor: {:?}", path))?;
+    Ok(id.to_string())
+}
+
 fn read_stream<R: std::io::Read>(
     path: &std::path::Path,
     reader: &mut R,
     text: &mut Option<String>,
+    quant: &mut Option<String>,
     tensors: &mut Vec<(String, Arc<Tensor>)>,
 ) -> TractResult<()> {
     if path.file_name().map(|n| n == "graph.nnef").unwrap_or(false) {
         let mut t = String::new();
         reader.read_to_string(&mut t)?;
         *text = Some(t);
-    } else if path.extension().map(|e| e == "dat").unwrap_or(false) {
-        let mut path = path.to_path_buf();
-        path.set_extension("");
-        let id = path
-            .to_str()
-            .ok_or_else(|| format_err!("Badly encoded filename for tensor: {:?}", path))?;
+    } else if path.file_name().map(|n| n == "graph.quant").unwrap_or(false) {
+        let mut t = String::new();
+        reader.read_to_string(&mut t)?;
+        *quant = Some(t);
+    } else if is_tensor(path) {
         let tensor = crate::tensors::read_tensor(reader)?;
-        tensors.push((id.to_string(), tensor.into_arc_tensor()));
+        tensors.push((tensor_id(path)?, tensor.into_arc_tensor()));
     }
     Ok(())
 }
@@ -4,6 +4,7 @@ extern crate log;
 pub mod ast;
 pub mod deser;
 pub mod framework;
+mod mmap;
 pub mod ops;
 pub mod registry;
 pub mod ser;
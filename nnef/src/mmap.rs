@@ -0,0 +1,89 @@
+//! Memory mapping of model files, so that constant tensors can borrow their
+//! data from the page cache instead of copying it.
+use std::fs::File;
+use tract_core::internal::*;
+
+/// A private, copy-on-write mapping of a whole file. Pages are shared with
+/// other processes mapping the same file until they are written to.
+pub struct Mmap {
+    ptr: *mut u8,
+    len: usize,
+}
+
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    #[cfg(unix)]
+    pub fn map(file: &File) -> TractResult<Mmap> {
+        use std::os::unix::io::AsRawFd;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Ok(Mmap { ptr: std::ptr::null_mut(), len });
+        }
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            bail!("Failed to map file: {}", std::io::Error::last_os_error());
+        }
+        Ok(Mmap { ptr: ptr as *mut u8, len })
+    }
+
+    #[cfg(not(unix))]
+    pub fn map(_file: &File) -> TractResult<Mmap> {
+        bail!("Memory mapping is only supported on unix platforms")
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+        }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            if self.len > 0 {
+                libc::munmap(self.ptr as _, self.len);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use crate::tensors::{read_tensor_in_place, write_tensor};
+
+    #[test]
+    fn tensor_borrows_mapped_data() -> TractResult<()> {
+        let path = std::env::temp_dir().join(format!("tract-mmap-{}.dat", std::process::id()));
+        let tensor = tensor2(&[[1.0f32, 2.0], [3.0, 4.0]]);
+        write_tensor(&mut std::fs::File::create(&path)?, &tensor)?;
+        let mmap = Arc::new(Mmap::map(&File::open(&path)?)?);
+        std::fs::remove_file(&path)?;
+        let owner: Arc<dyn std::any::Any + Send + Sync> = mmap.clone();
+        let bytes = mmap.as_slice();
+        let found = unsafe { read_tensor_in_place(bytes, &owner)? };
+        assert_eq!(found, tensor);
+        let data = found.as_slice::<f32>()?.as_ptr() as *const u8;
+        assert_eq!(data, bytes[128..].as_ptr());
+        // the tensor keeps the mapping alive
+        drop(owner);
+        drop(mmap);
+        assert_eq!(found, tensor);
+        Ok(())
+    }
+}
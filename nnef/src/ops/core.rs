@@ -3,12 +3,17 @@ use tract_core::ops;
 
 mod broadcast;
 mod cast;
+mod cumsum;
 mod downsample;
 mod gather;
 mod one_hot;
+mod range;
 mod reduce;
+mod reverse_sequence;
 mod scan;
 mod source;
+mod trilu;
+mod unique;
 
 
 pub fn register(registry: &mut Registry) {
@@ -21,6 +26,7 @@ pub fn register(registry: &mut Registry) {
     registry.register_unit_element_wise("tract_core_acosh", &ops::math::Acosh {});
     registry.register_unit_element_wise("tract_core_asinh", &ops::math::Asinh {});
     registry.register_unit_element_wise("tract_core_atanh", &ops::math::Atanh {});
+    registry.register_unit_element_wise("tract_core_erf", &ops::math::Erf {});
 
     registry.register_unit_element_wise("tract_core_round_even", &ops::math::RoundHalfToEven {});
 
@@ -28,10 +34,15 @@ pub fn register(registry: &mut Registry) {
 
     broadcast::register(registry);
     cast::register(registry);
+    cumsum::register(registry);
     downsample::register(registry);
     gather::register(registry);
     one_hot::register(registry);
+    range::register(registry);
     reduce::register(registry);
+    reverse_sequence::register(registry);
     scan::register(registry);
     source::register(registry);
+    trilu::register(registry);
+    unique::register(registry);
 }
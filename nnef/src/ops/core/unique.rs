@@ -0,0 +1,39 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::array::Unique;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(TypeId::of::<Unique>(), unique_dump);
+    registry.register_primitive(
+        "tract_core_unique",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.array().named("axis"),
+            TypeName::Logical.named("sorted").default(true),
+        ],
+        unique_load,
+    );
+}
+
+// The axis is an empty array when the input is flattened.
+fn unique_dump(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<Unique>().unwrap();
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let axis = op.axis.iter().cloned().collect::<Vec<usize>>();
+    Ok(Some(invocation(
+        "tract_core_unique",
+        &[input],
+        &[("axis", ints(&axis)), ("sorted", logical(op.sorted))],
+    )))
+}
+
+fn unique_load(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let axis: TVec<usize> = invocation.named_arg_as(builder, "axis")?;
+    let sorted = invocation.named_arg_as(builder, "sorted")?;
+    let count = builder.model.symbol_table.new_symbol("unique");
+    builder.wire(Unique { axis: axis.get(0).cloned(), sorted, count }, &[input])
+}
@@ -13,12 +13,11 @@ pub fn external(
     invocation: &ResolvedInvocation,
 ) -> TractResult<TVec<OutletId>> {
     let type_name = invocation.invocation.generic_type_name.unwrap_or(TypeName::Scalar);
-    let dt = if type_name == TypeName::Scalar {
-        f32::datum_type()
-    } else if type_name == TypeName::Logical {
-        bool::datum_type()
-    } else {
-        todo!()
+    let dt = match type_name {
+        TypeName::Scalar => f32::datum_type(),
+        TypeName::Integer => i64::datum_type(),
+        TypeName::Logical => bool::datum_type(),
+        _ => bail!("Unsupported external type {:?}", type_name),
     };
     let shape: TVec<usize> = invocation.named_arg_as(builder, "shape")?;
     Ok(tvec!(builder.model.add_source("", TypedFact::dt_shape(dt, &shape))?))
@@ -47,10 +46,18 @@ pub fn variable(
             shape
         );
     }
-    let tensor = if tensor.datum_type() == f32::datum_type() {
+    // quantized tensors keep their integer type
+    let quantized = builder.naming_scopes.len() == 1
+        && builder.quantization(&builder.naming_scopes[0]).is_some();
+    let dt = match invocation.invocation.generic_type_name.unwrap_or(TypeName::Scalar) {
+        TypeName::Integer => i64::datum_type(),
+        TypeName::Logical => bool::datum_type(),
+        _ => f32::datum_type(),
+    };
+    let tensor = if tensor.datum_type() == dt || quantized {
         tensor.clone()
     } else {
-        tensor.cast_to::<f32>()?.into_owned().into_arc_tensor()
+        tensor.cast_to_dt(dt)?.into_owned().into_arc_tensor()
     };
     builder.wire(tract_core::ops::konst::Const::new(tensor), &[])
 }
@@ -135,6 +142,62 @@ pub fn slice(
     })
 }
 
+// fragment split<?>( value: tensor<?>, axis: integer, ratios: integer[] ) -> ( values: tensor<?>[] );
+pub fn split(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "value")?;
+    let axis: usize = invocation.named_arg_as(builder, "axis")?;
+    let ratios: TVec<usize> = invocation.named_arg_as(builder, "ratios")?;
+    let dim = builder.model.outlet_fact(input)?.shape[axis].clone();
+    let total: usize = ratios.iter().sum();
+    let unit = dim.clone().div_ceil(total as u64);
+    if unit.clone() * total != dim {
+        bail!("Can not split axis {} of size {} with ratios {:?}", axis, dim, ratios);
+    }
+    let mut start = 0.to_dim();
+    let mut outputs = tvec!();
+    for ratio in ratios {
+        let end = start.clone() + unit.clone() * ratio;
+        outputs
+            .push(builder.wire(ops::array::Slice { axis, start, end: end.clone() }, &[input])?[0]);
+        start = end;
+    }
+    Ok(outputs)
+}
+
+// fragment stack<?>( values: tensor<?>[], axis: integer ) -> ( value: tensor<?> );
+pub fn stack(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let axis: usize = invocation.named_arg_as(builder, "axis")?;
+    let values: TVec<OutletId> = invocation.named_arg_as(builder, "values")?;
+    let values = values
+        .iter()
+        .map(|v| Ok(builder.wire(ops::change_axes::AxisOp::Add(axis), &[*v])?[0]))
+        .collect::<TractResult<TVec<OutletId>>>()?;
+    builder.wire(ops::array::TypedConcat::concat_vars(axis, values.len()), &values)
+}
+
+// fragment unstack<?>( value: tensor<?>, axis: integer ) -> ( values: tensor<?>[] );
+pub fn unstack(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "value")?;
+    let axis: usize = invocation.named_arg_as(builder, "axis")?;
+    let dim = builder.model.outlet_fact(input)?.shape[axis].to_usize()?;
+    (0..dim)
+        .map(|i| {
+            let slice = ops::array::Slice::new(axis, i, i + 1);
+            let wire = builder.wire(slice, &[input])?;
+            Ok(builder.wire(ops::change_axes::AxisOp::Rm(axis), &wire)?[0])
+        })
+        .collect()
+}
+
 // fragment squeeze<?>( input: tensor<?>, axes: integer[] ) -> ( output: tensor<?> );
 pub fn squeeze(
     builder: &mut ModelBuilder,
@@ -164,7 +227,9 @@ pub fn tile(
     builder: &mut ModelBuilder,
     invocation: &ResolvedInvocation,
 ) -> TractResult<TVec<OutletId>> {
-    let multipliers: TVec<usize> = invocation.named_arg_as(builder, "repeat")?;
+    // "repeat" was used by earlier versions of tract, the specification says "repeats"
+    let name = if invocation.get_named_arg("repeat").is_some() { "repeat" } else { "repeats" };
+    let multipliers: TVec<usize> = invocation.named_arg_as(builder, name)?;
     let wire = tvec!(invocation.named_arg_as(builder, "input")?);
     Ok(builder.wire(ops::array::Tile { multipliers }, &wire)?)
 }
@@ -184,6 +249,7 @@ pub fn pad(
         "constant" => PadMode::Constant(value.into_arc_tensor()),
         "replicated" => PadMode::Edge,
         "reflect" => PadMode::Reflect,
+        "reflect-even" => PadMode::Symmetric,
         _ => bail!("unsupported padding mode {}", border),
     };
     builder.wire(Pad { pads: padding, mode }, &wire)
@@ -205,7 +271,9 @@ pub fn conv(
     use ops::cnn::{PaddingSpec, PoolSpec};
     use ops::nn::DataFormat;
     let input: OutletId = invocation.named_arg_as(builder, "input")?;
-    let kernel: Arc<Tensor> = invocation.named_arg_as(builder, "filter")?;
+    let filter: OutletId = invocation.named_arg_as(builder, "filter")?;
+    let filter_fact = builder.model.outlet_fact(filter)?.clone();
+    let kernel = filter_fact.konst.clone().context("Convolution filter must be constant")?;
     let input_fact = builder.model.outlet_fact(input)?.clone();
     if input_fact.rank() != kernel.rank() {
         bail!(
@@ -255,46 +323,160 @@ pub fn conv(
 
     let border: String = invocation.named_arg_as(builder, "border")?;
     assert_eq!(border, "constant");
-    let op = ConvUnary::new(pool_spec, KernelFormat::OIHW, kernel.clone(), group, bias, None);
+    let output_quant = if builder.naming_scopes.len() == 1 {
+        builder.quantization(&builder.naming_scopes[0])
+    } else {
+        None
+    };
+    let q_params = if kernel.datum_type().is_float() {
+        None
+    } else {
+        conv_q_params(&input_fact, &filter_fact, output_quant.as_ref())?
+    };
+    let op = ConvUnary::new(pool_spec, KernelFormat::OIHW, kernel.clone(), group, bias, q_params);
     builder.wire(op, &[input])
 }
 
-fn pool_spec_for_pools(
-    builder: &mut ModelBuilder,
-    invocation: &ResolvedInvocation,
-    shape: &[usize],
-) -> TractResult<ops::cnn::PoolSpec> {
-    use ops::cnn::{PaddingSpec, PoolSpec};
-    use ops::nn::DataFormat;
-    let dilation: TVec<usize> = invocation.named_arg_as(builder, "dilation")?;
-    if dilation.len() > 0 && (dilation.len() != shape.len() || dilation[0] != 1 || dilation[1] != 1)
-    {
-        bail!("dilation should be like [1, 1, ... ]. Got dilation {:?}.", dilation);
+/// Quantization parameters of an integer convolution from the graph.quant
+/// formats of its input, filter and output.
+fn conv_q_params(
+    input: &TypedFact,
+    filter: &TypedFact,
+    output: Option<&QuantFormat>,
+) -> TractResult<Option<ops::quant::QParams>> {
+    if input.quant.is_none() && filter.quant.is_none() && output.is_none() {
+        return Ok(None);
     }
-    let stride: TVec<usize> = invocation.named_arg_as(builder, "stride")?;
-    if stride.len() > 0 && (stride.len() != shape.len() || stride[0] != 1 || stride[1] != 1) {
-        bail!("stride should be like [1, 1, ... ]. Got stride {:?}.", stride);
+    let zero_point = |quant: Option<&QuantFormat>, dt: DatumType| -> TractResult<Arc<Tensor>> {
+        let zp = quant.map(|q| q.zp_scale().0).unwrap_or(0);
+        Ok(tensor0(zp).cast_to_dt(dt)?.into_owned().into_arc_tensor())
+    };
+    let scale = |quant: Option<&QuantFormat>| quant.map(|q| q.zp_scale().1).unwrap_or(1.0);
+    let output_dt = output.map(|q| q.datum_type()).transpose()?.unwrap_or(input.datum_type);
+    let mut qp = ops::quant::QParams::new(output_dt)
+        .with_zero_point_a(&zero_point(filter.quant.as_ref(), filter.datum_type)?)
+        .with_zero_point_b(&zero_point(input.quant.as_ref(), input.datum_type)?)
+        .with_zero_point_c(&zero_point(output, output_dt)?);
+    let scale = scale(input.quant.as_ref()) * scale(filter.quant.as_ref()) / scale(output);
+    if scale != 1.0 {
+        qp.set_scale_factor(scale);
     }
-    let padding: TVec<TVec<usize>> = invocation.named_arg_as(builder, "padding")?;
-    let padding = if padding.len() == 0 {
-        PaddingSpec::SameUpper
-    } else {
-        let mut before = tvec!();
-        let mut after = tvec!();
-        for p in padding {
-            before.push(p[0]);
-            after.push(p[1]);
+    Ok(Some(qp))
+}
+
+/// Sliding window geometry, as given to box and pools: dilation, stride and
+/// padding cover all the input axes.
+struct PoolGeometry {
+    size: TVec<usize>,
+    dilation: TVec<usize>,
+    stride: TVec<usize>,
+    padding: TVec<(usize, usize)>,
+}
+
+impl PoolGeometry {
+    fn from_invocation(
+        builder: &mut ModelBuilder,
+        invocation: &ResolvedInvocation,
+    ) -> TractResult<PoolGeometry> {
+        let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+        let dilation: TVec<usize> = invocation.named_arg_as(builder, "dilation")?;
+        let stride: TVec<usize> = invocation.named_arg_as(builder, "stride")?;
+        let padding: TVec<TVec<usize>> = invocation.named_arg_as(builder, "padding")?;
+        let mut padding: TVec<(usize, usize)> = padding.iter().map(|p| (p[0], p[1])).collect();
+        // tract used to write the spatial padding only
+        if padding.len() > 0 && padding.len() + 2 == size.len() {
+            padding.insert(0, (0, 0));
+            padding.insert(0, (0, 0));
         }
-        PaddingSpec::Explicit(before, after, false)
-    };
-    Ok(PoolSpec::new(
-        DataFormat::NCHW,
-        shape[2..].into(),
-        padding,
-        if dilation.len() > 2 { Some(dilation[2..].into()) } else { None },
-        if stride.len() > 2 { Some(stride[2..].into()) } else { None },
-        None,
-    ))
+        for (name, value) in &[("dilation", &dilation), ("stride", &stride)] {
+            if value.len() > 0 && value.len() != size.len() {
+                bail!("{} should be of rank {}. Got {:?}", name, size.len(), value);
+            }
+        }
+        if padding.len() > 0 && padding.len() != size.len() {
+            bail!("padding should be of rank {}. Got {:?}", size.len(), padding);
+        }
+        Ok(PoolGeometry { size, dilation, stride, padding })
+    }
+
+    /// Same geometry over an extra leading unit axis, so that the channel
+    /// axis can be pooled as a spatial one.
+    fn with_channels_as_spatial(&self) -> PoolGeometry {
+        fn prepend<T: Clone>(items: &[T], item: T) -> TVec<T> {
+            if items.len() == 0 {
+                tvec!()
+            } else {
+                std::iter::once(item).chain(items.iter().cloned()).collect()
+            }
+        }
+        PoolGeometry {
+            size: prepend(&self.size, 1),
+            dilation: prepend(&self.dilation, 1),
+            stride: prepend(&self.stride, 1),
+            padding: prepend(&self.padding, (0, 0)),
+        }
+    }
+
+    fn pool_spec(&self) -> TractResult<ops::cnn::PoolSpec> {
+        use ops::cnn::{PaddingSpec, PoolSpec};
+        use ops::nn::DataFormat;
+        for (name, value) in
+            &[("size", &self.size), ("dilation", &self.dilation), ("stride", &self.stride)]
+        {
+            if value.len() > 0 && (value[0] != 1 || value[1] != 1) {
+                bail!("{} should be like [1, 1, ... ]. Got {} {:?}.", name, name, value);
+            }
+        }
+        if self.padding.len() > 0 && (self.padding[0] != (0, 0) || self.padding[1] != (0, 0)) {
+            bail!("padding should be like [(0, 0), (0, 0), ... ]. Got {:?}.", self.padding);
+        }
+        let padding = if self.padding.len() == 0 {
+            PaddingSpec::SameUpper
+        } else {
+            PaddingSpec::Explicit(
+                self.padding[2..].iter().map(|p| p.0).collect(),
+                self.padding[2..].iter().map(|p| p.1).collect(),
+                false,
+            )
+        };
+        Ok(PoolSpec::new(
+            DataFormat::NCHW,
+            self.size[2..].into(),
+            padding,
+            if self.dilation.len() > 2 { Some(self.dilation[2..].into()) } else { None },
+            if self.stride.len() > 2 { Some(self.stride[2..].into()) } else { None },
+            None,
+        ))
+    }
+}
+
+/// Pad explicitly with zeros what a pool spec would implicitly pad, for
+/// pools with a constant border.
+fn pad_with_zeros(
+    builder: &mut ModelBuilder,
+    input: OutletId,
+    pool_spec: &mut ops::cnn::PoolSpec,
+) -> TractResult<OutletId> {
+    use ops::array::{Pad, PadMode};
+    use ops::cnn::PaddingSpec;
+    let fact = builder.model.outlet_fact(input)?.clone();
+    let spatial = fact.shape.iter().skip(2).collect::<TVec<TDim>>();
+    let computed = pool_spec.padding.compute(
+        &*spatial,
+        &*pool_spec.kernel_shape,
+        &*pool_spec.dilations(),
+        &*pool_spec.strides(),
+    );
+    if computed.iter().all(|d| d.pad_before == 0.to_dim() && d.pad_after == 0.to_dim()) {
+        return Ok(input);
+    }
+    let mut pads = vec![(0, 0), (0, 0)];
+    for d in computed {
+        pads.push((d.pad_before.to_usize()?, d.pad_after.to_usize()?));
+    }
+    pool_spec.padding = PaddingSpec::Valid;
+    let zero = Tensor::zero_dt(fact.datum_type, &[])?.into_arc_tensor();
+    Ok(builder.wire(Pad { pads, mode: PadMode::Constant(zero) }, &[input])?[0])
 }
 
 /*
@@ -307,23 +489,186 @@ pub fn max_pool_with_index(
     builder: &mut ModelBuilder,
     invocation: &ResolvedInvocation,
 ) -> TractResult<TVec<OutletId>> {
-    let input = invocation.named_arg_as(builder, "input")?;
-    let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+    let mut input = invocation.named_arg_as(builder, "input")?;
+    let geometry = PoolGeometry::from_invocation(builder, invocation)?;
     let input_fact = builder.model.outlet_fact(input)?;
-    if input_fact.rank() != size.len() {
+    if input_fact.rank() != geometry.size.len() {
         bail!(
             "Max pool input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
             input_fact,
-            size
+            geometry.size
             );
     }
+    let mut pool_spec = geometry.pool_spec()?;
     let border: String = invocation.named_arg_as(builder, "border")?;
-    assert_eq!(border, "ignore");
-    let pool_spec = pool_spec_for_pools(builder, invocation, &size)?;
+    match &*border {
+        "ignore" => (),
+        "constant" => input = pad_with_zeros(builder, input, &mut pool_spec)?,
+        _ => bail!("Unsupported border mode {} for max pooling", border),
+    }
     let op = ops::cnn::MaxPool { pool_spec, with_index_outputs: Some(i64::datum_type()) };
     builder.wire(op, &[input])
 }
 
+/*
+ * fragment argmax_pool( input: tensor<scalar>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [] )
+ * -> ( index: tensor<integer> );
+ *
+ * Indices are offsets in the flattened spatial plane of the input.
+ */
+pub fn argmax_pool(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let outputs = max_pool_with_index(builder, invocation)?;
+    Ok(tvec!(outputs[1]))
+}
+
+/// Border handling of sample and desample: the plane indexed by argmax_pool
+/// is padded with zeros for the "constant" border, left as is for "ignore".
+fn sampled_plane_padded(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<bool> {
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    match &*border {
+        "ignore" => Ok(false),
+        "constant" => Ok(true),
+        _ => bail!("Unsupported border mode {} for sampling", border),
+    }
+}
+
+/*
+ * fragment sample( input: tensor<scalar>, index: tensor<integer>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [] )
+ * -> ( output: tensor<scalar> );
+ *
+ * Values of the input at the indices given by argmax_pool, gathered from the
+ * flattened input.
+ */
+pub fn sample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let mut input = invocation.named_arg_as(builder, "input")?;
+    let index = invocation.named_arg_as(builder, "index")?;
+    let geometry = PoolGeometry::from_invocation(builder, invocation)?;
+    if builder.model.outlet_fact(input)?.rank() != geometry.size.len() {
+        bail!("Sample input rank does not match size {:?}", geometry.size);
+    }
+    let mut pool_spec = geometry.pool_spec()?;
+    if sampled_plane_padded(builder, invocation)? {
+        input = pad_with_zeros(builder, input, &mut pool_spec)?;
+    }
+    let shape = builder
+        .model
+        .outlet_fact(input)?
+        .shape
+        .iter()
+        .map(|d| d.to_usize())
+        .collect::<TractResult<TVec<usize>>>()?;
+    let plane: usize = shape[2..].iter().product();
+    // offset of each (batch, channel) plane in the flattened input
+    let mut offsets_shape = tvec!(shape[0], shape[1]);
+    offsets_shape.extend(shape[2..].iter().map(|_| 1));
+    let offsets = tract_ndarray::Array::from_shape_fn(&*offsets_shape, |ix| {
+        ((ix[0] * shape[1] + ix[1]) * plane) as i64
+    });
+    let offsets = builder.wire(ops::konst::Const::new(offsets.into_arc_tensor()), &[])?;
+    let index = builder.wire(ops::cast::cast(i64::datum_type()), &[index])?;
+    let index = builder.wire(ops::math::add::bin_typed(), &[index[0], offsets[0]])?;
+    let total = shape.iter().product::<usize>().to_dim();
+    let flat = AxisOp::Reshape(0, shape.iter().map(|d| d.to_dim()).collect(), tvec!(total));
+    let flat = builder.wire(flat, &[input])?;
+    builder.wire(ops::array::Gather::new(0), &[flat[0], index[0]])
+}
+
+/*
+ * fragment desample( input: tensor<scalar>, index: tensor<integer>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [],
+ *   output_shape: integer[] = [] )
+ * -> ( output: tensor<scalar> );
+ *
+ * Inverse of sample: each input value is added to the output at its index,
+ * through a one-hot encoding of the indices.
+ */
+pub fn desample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let index = invocation.named_arg_as(builder, "index")?;
+    let geometry = PoolGeometry::from_invocation(builder, invocation)?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    let rank = input_fact.rank();
+    if rank != geometry.size.len() {
+        bail!("Desample input rank does not match size {:?}", geometry.size);
+    }
+    let pool_spec = geometry.pool_spec()?;
+    let padded = sampled_plane_padded(builder, invocation)?;
+    let shape =
+        input_fact.shape.iter().map(|d| d.to_usize()).collect::<TractResult<TVec<usize>>>()?;
+    let output_shape: TVec<usize> = invocation.named_arg_as(builder, "output_shape")?;
+    let spatial: TVec<usize> = if !output_shape.is_empty() {
+        if output_shape.len() != rank {
+            bail!("output_shape should be of rank {}. Got {:?}", rank, output_shape);
+        }
+        output_shape[2..].into()
+    } else {
+        (0..rank - 2)
+            .map(|ix| {
+                let stride = pool_spec.strides()[ix];
+                if geometry.padding.is_empty() {
+                    shape[ix + 2] * stride
+                } else {
+                    let kernel = (pool_spec.kernel_shape[ix] - 1) * pool_spec.dilations()[ix] + 1;
+                    let (before, after) = geometry.padding[ix + 2];
+                    (shape[ix + 2] - 1) * stride + kernel - before - after
+                }
+            })
+            .collect()
+    };
+    let pads: TVec<(usize, usize)> = if padded {
+        pool_spec
+            .padding
+            .compute(
+                &spatial,
+                &pool_spec.kernel_shape,
+                &pool_spec.dilations(),
+                &pool_spec.strides(),
+            )
+            .iter()
+            .map(|d| (d.pad_before, d.pad_after))
+            .collect()
+    } else {
+        spatial.iter().map(|_| (0, 0)).collect()
+    };
+    let plane: TVec<usize> = spatial.iter().zip(pads.iter()).map(|(d, p)| d + p.0 + p.1).collect();
+    let plane_len = plane.iter().product::<usize>();
+    let off = Tensor::zero_dt(input_fact.datum_type, &[])?.into_arc_tensor();
+    let on = tensor0(1f32).cast_to_dt(input_fact.datum_type)?.into_owned().into_arc_tensor();
+    let one_hot = ops::array::OneHot { axis: rank, dim: plane_len, off, on };
+    let one_hot = builder.wire(one_hot, &[index])?;
+    let values = builder.wire(AxisOp::Add(rank), &[input])?;
+    let wire = builder.wire(ops::math::mul::bin_typed(), &[one_hot[0], values[0]])?;
+    let mut wire =
+        builder.wire(ops::nn::Reduce::new((2..rank).collect(), ops::nn::Reducer::Sum), &wire)?;
+    for _ in 2..rank {
+        wire = builder.wire(AxisOp::Rm(2), &wire)?;
+    }
+    let reshape =
+        AxisOp::Reshape(2, tvec!(plane_len.to_dim()), plane.iter().map(|d| d.to_dim()).collect());
+    wire = builder.wire(reshape, &wire)?;
+    for (ix, (d, (before, _))) in spatial.iter().zip(pads.iter()).enumerate() {
+        if plane[ix] != *d {
+            let slice = ops::array::Slice::new(ix + 2, *before, before + d);
+            wire = builder.wire(slice, &wire)?;
+        }
+    }
+    Ok(wire)
+}
+
 /*
  * fragment box( input: tensor<scalar>, size: integer[], border: string = 'constant', padding: (integer,integer)[] = [],
  *   stride: integer[] = [], dilation: integer[] = [], normalize: logical = false )
@@ -335,24 +680,40 @@ pub fn sum_pool(
     invocation: &ResolvedInvocation,
 ) -> TractResult<TVec<OutletId>> {
     let input = invocation.named_arg_as(builder, "input")?;
-    let size: TVec<usize> = invocation.named_arg_as(builder, "size")?;
+    let geometry = PoolGeometry::from_invocation(builder, invocation)?;
     let input_fact = builder.model.outlet_fact(input)?;
-    if input_fact.rank() != size.len() {
+    if input_fact.rank() != geometry.size.len() {
         bail!(
-            "Max pool input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
+            "Box input expected as NCHW, and \"size\" paramater must be [ 1, c, x, y ]. Got {:?}, and {:?}",
             input_fact,
-            size
+            geometry.size
             );
     }
     let border: String = invocation.named_arg_as(builder, "border")?;
-    assert_eq!(border, "ignore");
-    let pool_spec = pool_spec_for_pools(builder, invocation, &size)?;
-    let op = ops::cnn::SumPool {
-        pool_spec,
-        count_include_pad: false,
-        normalize: invocation.named_arg_as(builder, "normalize")?,
+    let count_include_pad = match &*border {
+        "ignore" => false,
+        "constant" => true,
+        _ => bail!("Unsupported border mode {} for box", border),
     };
-    builder.wire(op, &[input])
+    let normalize = invocation.named_arg_as(builder, "normalize")?;
+    // a box across channels (as in local response normalization) is done
+    // with channels moved to an extra spatial axis
+    let across_channels = geometry.size[1] != 1
+        || geometry.stride.get(1).cloned().unwrap_or(1) != 1
+        || geometry.padding.get(1).cloned().unwrap_or((0, 0)) != (0, 0);
+    let pool_spec = if across_channels {
+        geometry.with_channels_as_spatial().pool_spec()?
+    } else {
+        geometry.pool_spec()?
+    };
+    let op = ops::cnn::SumPool { pool_spec, count_include_pad, normalize };
+    if across_channels {
+        let wire = builder.wire(AxisOp::Add(1), &[input])?;
+        let wire = builder.wire(op, &wire)?;
+        builder.wire(AxisOp::Rm(1), &wire)
+    } else {
+        builder.wire(op, &[input])
+    }
 }
 
 /*
@@ -373,6 +734,14 @@ pub fn reduce(
         "max" => ops::nn::Reducer::Max,
         "argmin" => ops::nn::Reducer::ArgMin(false),
         "argmax" => ops::nn::Reducer::ArgMax(false),
+        "any" | "all" => {
+            // logical reductions run as max or min over 0/1 values
+            let reducer =
+                if reducer_name == "any" { ops::nn::Reducer::Max } else { ops::nn::Reducer::Min };
+            let wire = builder.wire(ops::cast::cast(u8::datum_type()), &[input])?;
+            let wire = builder.wire(ops::nn::Reduce::new(axes, reducer), &wire)?;
+            return builder.wire(ops::cast::cast(bool::datum_type()), &wire);
+        }
         _ => bail!("unsupported reducer: {}", invocation.invocation.id),
     };
     let wire = builder.wire(ops::nn::Reduce::new(axes.clone(), reducer), &[input])?;
@@ -427,3 +796,378 @@ pub fn select(
     let inputs = crate::registry::multicast(builder, &[cond, true_value, false_value])?;
     builder.wire(ops::logic::Iff {}, &inputs)
 }
+
+/*
+ * fragment constant<? = scalar>( shape: integer[], value: ?[] ) -> ( output: tensor<?> );
+ */
+pub fn constant(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let type_name = invocation.invocation.generic_type_name.unwrap_or(TypeName::Scalar);
+    let shape: TVec<usize> = invocation.named_arg_as(builder, "shape")?;
+    let value = match type_name {
+        TypeName::Scalar => tensor1(&*invocation.named_arg_as::<TVec<f32>>(builder, "value")?),
+        TypeName::Integer => tensor1(&*invocation.named_arg_as::<TVec<i64>>(builder, "value")?),
+        TypeName::Logical => tensor1(&*invocation.named_arg_as::<TVec<bool>>(builder, "value")?),
+        _ => bail!("Unsupported constant type {:?}", type_name),
+    };
+    let value = if value.len() == 1 {
+        value.broadcast_scalar_to_shape(&shape)?
+    } else if value.len() == shape.iter().product::<usize>() {
+        value.into_shape(&shape)?
+    } else {
+        bail!("Constant of shape {:?} can not be built from {:?}", shape, value)
+    };
+    builder.wire(ops::konst::Const::new(value.into_arc_tensor()), &[])
+}
+
+// fragment update<?>( variable: tensor<?>, value: tensor<?> ) -> ( result: tensor<?> );
+//
+// Variables are constants in tract: the result of an update is the new value.
+pub fn update(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    Ok(tvec!(invocation.named_arg_as(builder, "value")?))
+}
+
+// fragment copy<?>( x: tensor<?> ) -> ( y: tensor<?> );
+pub fn copy(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    Ok(tvec!(invocation.named_arg_as(builder, "x")?))
+}
+
+/// Transposed convolution shared by deconv and debox. The geometry is given
+/// for the spatial axes only.
+fn wire_deconv(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+    input: OutletId,
+    kernel: Arc<Tensor>,
+    group: usize,
+    bias: Option<Arc<Tensor>>,
+    geometry: &PoolGeometry,
+) -> TractResult<TVec<OutletId>> {
+    use ops::cnn::{DeconvUnary, KernelFormat, PaddingSpec};
+    use ops::nn::DataFormat;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    let hw_rank = input_fact.rank() - 2;
+    let or_ones = |values: &TVec<usize>| -> TractResult<TVec<usize>> {
+        if values.len() == 0 {
+            Ok(tvec![1; hw_rank])
+        } else if values.len() == hw_rank {
+            Ok(values.clone())
+        } else {
+            bail!("Expected {} values for the spatial axes, got {:?}", hw_rank, values)
+        }
+    };
+    let strides = or_ones(&geometry.stride)?;
+    let dilations = or_ones(&geometry.dilation)?;
+    let padding = if geometry.padding.len() == 0 {
+        PaddingSpec::SameUpper
+    } else {
+        PaddingSpec::Explicit(
+            geometry.padding.iter().map(|p| p.0).collect(),
+            geometry.padding.iter().map(|p| p.1).collect(),
+            false,
+        )
+    };
+    // with automatic padding, the output is the input scaled by the strides
+    let adjustments: TVec<usize> = if padding == PaddingSpec::SameUpper {
+        strides.iter().map(|s| s - 1).collect()
+    } else {
+        tvec![0; hw_rank]
+    };
+    let mut op = DeconvUnary::new(
+        DataFormat::NCHW,
+        KernelFormat::OIHW,
+        padding,
+        strides,
+        dilations,
+        kernel,
+        bias,
+        adjustments,
+        group,
+    );
+    let output_shape: TVec<usize> = invocation.named_arg_as(builder, "output_shape")?;
+    if output_shape.len() > 0 {
+        op.adjustments = tvec![0; hw_rank];
+        let computed = op.output_facts(&[&input_fact])?.remove(0);
+        for ax in 0..hw_rank {
+            let computed = computed.shape[ax + 2].to_usize()?;
+            let wanted = output_shape[ax + 2];
+            if wanted < computed || wanted - computed >= op.strides[ax] {
+                bail!(
+                    "Can not produce output shape {:?} from input {:?} with {:?}",
+                    output_shape,
+                    input_fact,
+                    op
+                );
+            }
+            op.adjustments[ax] = wanted - computed;
+        }
+    }
+    builder.wire(op, &[input])
+}
+
+/*
+ * fragment deconv( input: tensor<scalar>, filter: tensor<scalar>, bias: tensor<scalar> = 0.0,
+ *   border: string = 'constant', padding: (integer,integer)[] = [], stride: integer[] = [],
+ *   dilation: integer[] = [], output_shape: integer[] = [], groups: integer = 1 )
+ * -> ( output: tensor<scalar> );
+ */
+pub fn deconv(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input: OutletId = invocation.named_arg_as(builder, "input")?;
+    let kernel: Arc<Tensor> = invocation.named_arg_as(builder, "filter")?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    if input_fact.rank() != kernel.rank() {
+        bail!(
+            "Deconvolution input expected as NCHW, filter as OIHW. Got {:?} and {:?}.",
+            input_fact,
+            kernel
+        );
+    }
+    if input_fact.shape[1] != kernel.shape()[0].to_dim() {
+        bail!(
+            "Deconvolution input channels must match the filter first axis. Got {:?} and {:?}.",
+            input_fact,
+            kernel
+        );
+    }
+    let mut group = invocation.named_arg_as(builder, "groups")?;
+    if group == 0 {
+        group = kernel.shape()[0]
+    }
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    if border != "constant" {
+        bail!("Unsupported border mode {} for deconvolution", border);
+    }
+    let bias: Arc<Tensor> = invocation.named_arg_as(builder, "bias")?;
+    let output_channels = kernel.shape()[1] * group;
+    let bias = if bias.is_uniform()? && bias.cast_to_scalar::<f32>()? == 0.0 {
+        None
+    } else if bias.len() == 1 {
+        Some(bias.broadcast_scalar_to_shape(&[output_channels])?.into_arc_tensor())
+    } else {
+        Some(bias.into_tensor().into_shape(&[output_channels])?.into_arc_tensor())
+    };
+    let padding: TVec<TVec<usize>> = invocation.named_arg_as(builder, "padding")?;
+    let geometry = PoolGeometry {
+        size: kernel.shape()[2..].into(),
+        dilation: invocation.named_arg_as(builder, "dilation")?,
+        stride: invocation.named_arg_as(builder, "stride")?,
+        padding: padding.iter().map(|p| (p[0], p[1])).collect(),
+    };
+    wire_deconv(builder, invocation, input, kernel, group, bias, &geometry)
+}
+
+/*
+ * fragment debox( input: tensor<scalar>, size: integer[], border: string = 'constant',
+ *   padding: (integer,integer)[] = [], stride: integer[] = [], dilation: integer[] = [],
+ *   output_shape: integer[] = [], normalize: logical = false )
+ * -> ( output: tensor<scalar> );
+ */
+pub fn debox(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let geometry = PoolGeometry::from_invocation(builder, invocation)?;
+    let input_fact = builder.model.outlet_fact(input)?.clone();
+    if input_fact.rank() != geometry.size.len() {
+        bail!(
+            "Debox input expected as NCHW, and \"size\" paramater must be [ 1, 1, x, y ]. Got {:?}, and {:?}",
+            input_fact,
+            geometry.size
+        );
+    }
+    // validates batch and channel axes are left alone
+    geometry.pool_spec()?;
+    let normalize: bool = invocation.named_arg_as(builder, "normalize")?;
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    if border != "constant" && (border != "ignore" || normalize) {
+        bail!("Unsupported border mode {} for debox", border);
+    }
+    // a depthwise deconvolution with a kernel of ones
+    let channels = input_fact.shape[1].to_usize()?;
+    let mut kernel_shape = tvec!(channels, 1);
+    kernel_shape.extend(geometry.size[2..].iter().cloned());
+    let value = if normalize { 1.0 / geometry.size.iter().product::<usize>() as f32 } else { 1.0 };
+    let kernel = tensor0(value)
+        .cast_to_dt(input_fact.datum_type)?
+        .broadcast_scalar_to_shape(&kernel_shape)?;
+    let spatial =
+        |values: &TVec<usize>| -> TVec<usize> { values.iter().skip(2).cloned().collect() };
+    let geometry = PoolGeometry {
+        size: spatial(&geometry.size),
+        dilation: spatial(&geometry.dilation),
+        stride: spatial(&geometry.stride),
+        padding: geometry.padding.iter().skip(2).cloned().collect(),
+    };
+    wire_deconv(builder, invocation, input, kernel.into_arc_tensor(), channels, None, &geometry)
+}
+
+/*
+ * fragment multilinear_upsample( input: tensor<scalar>, factor: integer[], method: string = 'symmetric',
+ *   border: string = 'replicate' ) -> ( output: tensor<scalar> );
+ */
+pub fn multilinear_upsample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let mut wire: OutletId = invocation.named_arg_as(builder, "input")?;
+    let factor: TVec<usize> = invocation.named_arg_as(builder, "factor")?;
+    let method: String = invocation.named_arg_as(builder, "method")?;
+    let border: String = invocation.named_arg_as(builder, "border")?;
+    let input_fact = builder.model.outlet_fact(wire)?.clone();
+    if input_fact.rank() != factor.len() + 2 {
+        bail!("Upsampling factor {:?} does not match input {:?}", factor, input_fact);
+    }
+    let replicate = match &*border {
+        "replicate" => true,
+        "constant" => false,
+        _ => bail!("Unsupported border mode {} for multilinear upsampling", border),
+    };
+    // one linear interpolation per spatial axis: a weighted sum of two
+    // gathers of the input along the axis
+    for (ix, &factor) in factor.iter().enumerate() {
+        let axis = ix + 2;
+        let input = input_fact.shape[axis].to_usize()?;
+        let output = input * factor;
+        let mut indices = [vec![], vec![]];
+        let mut weights = [vec![], vec![]];
+        for o in 0..output {
+            let x = match &*method {
+                "symmetric" => (o as f32 + 0.5) / factor as f32 - 0.5,
+                "asymmetric" => o as f32 / factor as f32,
+                "aligned" if output > 1 => o as f32 * (input - 1) as f32 / (output - 1) as f32,
+                "aligned" => 0.0,
+                _ => bail!("Unsupported method {} for multilinear upsampling", method),
+            };
+            let low = x.floor();
+            for (side, (pos, weight)) in
+                [(low, 1.0 - (x - low)), (low + 1.0, x - low)].iter().enumerate()
+            {
+                let outside = *pos < 0.0 || *pos > (input - 1) as f32;
+                let clamped = pos.max(0.0).min((input - 1) as f32) as i64;
+                indices[side].push(clamped);
+                weights[side].push(if outside && !replicate { 0.0 } else { *weight });
+            }
+        }
+        let mut weights_shape = tvec![1; input_fact.rank()];
+        weights_shape[axis] = output;
+        let mut terms = tvec!();
+        for side in 0..2 {
+            let indices = tensor1(&indices[side]).into_arc_tensor();
+            let indices = builder.wire(ops::konst::Const::new(indices), &[])?[0];
+            let gathered = builder.wire(ops::array::Gather::new(axis), &[wire, indices])?[0];
+            let weights = tensor1(&weights[side])
+                .into_shape(&weights_shape)?
+                .cast_to_dt(input_fact.datum_type)?
+                .into_owned()
+                .into_arc_tensor();
+            terms.push(builder.wire(ops::math::mul::unary(weights), &[gathered])?[0]);
+        }
+        wire = builder.wire(ops::math::add::bin_typed(), &terms)?[0];
+    }
+    Ok(tvec!(wire))
+}
+
+fn roi_inputs(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    Ok(tvec!(
+        invocation.named_arg_as(builder, "input")?,
+        invocation.named_arg_as(builder, "rois")?,
+        invocation.named_arg_as(builder, "batch_index")?,
+    ))
+}
+
+fn sampling_method(method: &str) -> TractResult<ops::cnn::SamplingMethod> {
+    use ops::cnn::SamplingMethod;
+    match method {
+        "symmetric" => Ok(SamplingMethod::Symmetric),
+        "asymmetric" => Ok(SamplingMethod::Asymmetric),
+        "aligned" => Ok(SamplingMethod::Aligned),
+        _ => bail!("Unsupported sampling method {}", method),
+    }
+}
+
+/*
+ * fragment avg_roi_pool( input: tensor<scalar>, rois: tensor<scalar>, batch_index: tensor<integer>,
+ *   output_size: integer[] ) -> ( output: tensor<scalar> );
+ * fragment max_roi_pool( ... same ... )
+ */
+pub fn roi_pool(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    use ops::cnn::{RoiPool, RoiPoolMode};
+    let inputs = roi_inputs(builder, invocation)?;
+    let output_size: TVec<usize> = invocation.named_arg_as(builder, "output_size")?;
+    let mode = if invocation.invocation.id.starts_with("max") {
+        RoiPoolMode::Max
+    } else {
+        RoiPoolMode::Avg
+    };
+    builder.wire(RoiPool::new(output_size, mode), &inputs)
+}
+
+/*
+ * fragment roi_resample( input: tensor<scalar>, rois: tensor<scalar>, batch_index: tensor<integer>,
+ *   output_size: integer[], method: string = 'symmetric' ) -> ( output: tensor<scalar> );
+ */
+pub fn roi_resample(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    let inputs = roi_inputs(builder, invocation)?;
+    let output_size: TVec<usize> = invocation.named_arg_as(builder, "output_size")?;
+    let method: String = invocation.named_arg_as(builder, "method")?;
+    builder.wire(ops::cnn::RoiResample::new(output_size, sampling_method(&method)?, None), &inputs)
+}
+
+/*
+ * fragment avg_roi_align( input: tensor<scalar>, rois: tensor<scalar>, batch_index: tensor<integer>,
+ *   output_size: integer[], sampling_rate: integer[], resize_method: string = 'symmetric' )
+ * -> ( output: tensor<scalar> );
+ * fragment max_roi_align( ... same ... )
+ */
+pub fn roi_align(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<TVec<OutletId>> {
+    use ops::cnn::{PaddingSpec, PoolSpec};
+    use ops::nn::DataFormat;
+    let inputs = roi_inputs(builder, invocation)?;
+    let output_size: TVec<usize> = invocation.named_arg_as(builder, "output_size")?;
+    let sampling_rate: TVec<usize> = invocation.named_arg_as(builder, "sampling_rate")?;
+    let method: String = invocation.named_arg_as(builder, "resize_method")?;
+    if sampling_rate.len() != output_size.len() {
+        bail!("Expected sampling rate for {:?}, got {:?}", output_size, sampling_rate);
+    }
+    let resampled_size = output_size.iter().zip(sampling_rate.iter()).map(|(o, s)| o * s).collect();
+    let resample = ops::cnn::RoiResample::new(resampled_size, sampling_method(&method)?, None);
+    let wire = builder.wire(resample, &inputs)?;
+    let pool_spec = PoolSpec::new(
+        DataFormat::NCHW,
+        sampling_rate.clone(),
+        PaddingSpec::Valid,
+        None,
+        Some(sampling_rate),
+        None,
+    );
+    if invocation.invocation.id.starts_with("max") {
+        builder.wire(ops::cnn::MaxPool { pool_spec, with_index_outputs: None }, &wire)
+    } else {
+        builder
+            .wire(ops::cnn::SumPool { pool_spec, count_include_pad: false, normalize: true }, &wire)
+    }
+}
@@ -25,6 +25,9 @@ pub fn tract_nnef() -> Registry {
     dumper!(ops::source::TypedSource, ser::source);
     primitive(&mut registry, "variable", deser::variable);
     dumper!(ops::konst::Const, ser::konst);
+    primitive(&mut registry, "constant", deser::constant);
+    primitive(&mut registry, "copy", deser::copy);
+    primitive(&mut registry, "update", deser::update);
 
     primitive(&mut registry, "reshape", deser::reshape);
     primitive(&mut registry, "transpose", deser::transpose);
@@ -33,6 +36,9 @@ pub fn tract_nnef() -> Registry {
     dumper!(ops::array::TypedConcat, ser::concat);
     primitive(&mut registry, "slice", deser::slice);
     dumper!(ops::array::Slice, ser::slice);
+    primitive(&mut registry, "split", deser::split);
+    primitive(&mut registry, "stack", deser::stack);
+    primitive(&mut registry, "unstack", deser::unstack);
 
     primitive(&mut registry, "squeeze", deser::squeeze);
     primitive(&mut registry, "unsqueeze", deser::unsqueeze);
@@ -57,6 +63,7 @@ pub fn tract_nnef() -> Registry {
     registry.register_unit_element_wise("abs", &ops::math::Abs {});
     registry.register_unit_element_wise("neg", &ops::math::Neg {});
     registry.register_unit_element_wise("sign", &ops::math::Sign {});
+    registry.register_unit_element_wise("rcp", &ops::math::Recip {});
     registry.register_unit_element_wise("recip", &ops::math::Recip {});
 
     registry.register_unit_element_wise("floor", &ops::math::Floor {});
@@ -97,18 +104,37 @@ pub fn tract_nnef() -> Registry {
 
     primitive(&mut registry, "conv", deser::conv);
     dumper!(ops::cnn::ConvUnary, ser::conv);
+    primitive(&mut registry, "deconv", deser::deconv);
+    dumper!(ops::cnn::DeconvUnary, ser::deconv);
 
     primitive(&mut registry, "sum_reduce", deser::reduce);
     primitive(&mut registry, "max_reduce", deser::reduce);
     primitive(&mut registry, "min_reduce", deser::reduce);
     primitive(&mut registry, "argmax_reduce", deser::reduce);
     primitive(&mut registry, "argmin_reduce", deser::reduce);
+    primitive(&mut registry, "any_reduce", deser::reduce);
+    primitive(&mut registry, "all_reduce", deser::reduce);
     dumper!(ops::nn::Reduce, ser::reduce);
 
     primitive(&mut registry, "max_pool_with_index", deser::max_pool_with_index);
     dumper!(ops::cnn::MaxPool, ser::max_pool);
+    primitive(&mut registry, "argmax_pool", deser::argmax_pool);
+    primitive(&mut registry, "sample", deser::sample);
+    primitive(&mut registry, "desample", deser::desample);
     primitive(&mut registry, "box", deser::sum_pool);
     dumper!(ops::cnn::SumPool, ser::sum_pool);
+    primitive(&mut registry, "debox", deser::debox);
+
+    primitive(&mut registry, "multilinear_upsample", deser::multilinear_upsample);
+
+    primitive(&mut registry, "avg_roi_pool", deser::roi_pool);
+    primitive(&mut registry, "max_roi_pool", deser::roi_pool);
+    dumper!(ops::cnn::RoiPool, ser::roi_pool);
+    primitive(&mut registry, "roi_resample", deser::roi_resample);
+    dumper!(ops::cnn::RoiResample, ser::roi_resample);
+    primitive(&mut registry, "avg_roi_align", deser::roi_align);
+    primitive(&mut registry, "max_roi_align", deser::roi_align);
+    dumper!(ops::cnn::RoiAlign, ser::roi_align);
 
     for frag in stdlib {
         if frag.body.is_some() {
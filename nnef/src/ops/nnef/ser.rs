@@ -24,7 +24,12 @@ pub fn konst(
     node: &TypedNode,
     op: &ops::konst::Const,
 ) -> TractResult<Option<Arc<RValue>>> {
-    Ok(Some(ast.konst(&node.name, &op.0)))
+    if node.outputs[0].fact.quant.is_some() {
+        // quantized tensors must keep their type, and be referred to in graph.quant
+        Ok(Some(ast.konst_variable(&node.name, &op.0)))
+    } else {
+        Ok(Some(ast.konst(&node.name, &op.0)))
+    }
 }
 
 pub fn concat(
@@ -68,7 +73,7 @@ pub fn tile(
     op: &ops::array::Tile,
 ) -> TractResult<Option<Arc<RValue>>> {
     let wire = ast.mapping[&node.inputs[0]].clone();
-    Ok(Some(invocation("tile", &[wire], &[("repeat", ints(&op.multipliers))])))
+    Ok(Some(invocation("tile", &[wire], &[("repeats", ints(&op.multipliers))])))
 }
 
 pub fn pad(
@@ -94,6 +99,7 @@ pub fn pad(
             "constant"
         }
         PadMode::Reflect => "reflect",
+        PadMode::Symmetric => "reflect-even",
         PadMode::Edge => "replicated",
     };
     params.push(("border", string(border)));
@@ -185,6 +191,16 @@ pub fn conv(
     weights.set_shape(&*kernel_shape)?;
     let weigths = ast.konst_variable(format!("{}_weigths", node.name), &weights.into_arc_tensor());
     wire = ast.force_assign(format!("{}_input", node.name), &wire);
+    if let Some(qp) = &op.q_params {
+        if op.pool_spec.data_format != DataFormat::NCHW {
+            bail!("Quantized convolutions are only serialized in NCHW")
+        }
+        // graph.quant only gets the combined scale, on the filter
+        let scale = qp.scale_factor.unwrap_or(1.0);
+        let input_dt = ast.model.outlet_fact(node.inputs[0])?.datum_type;
+        quantize(ast, &wire, input_dt, &qp.zero_point_b, 1.0)?;
+        quantize(ast, &weigths, op.kernel.datum_type(), &qp.zero_point_a, scale)?;
+    }
     let conv_fragment = conv_fragment(ast, op.pool_spec.data_format, op.pool_spec.rank());
     let padding = match &op.pool_spec.padding {
         PaddingSpec::Explicit(bef, after, _) => array(
@@ -201,7 +217,15 @@ pub fn conv(
     };
     let mut inputs = tvec![wire, weigths];
     if let Some(bias) = op.bias.as_ref() {
-        let bias = ast.konst(format!("{}_bias", node.name), bias);
+        let bias = match &op.q_params {
+            Some(qp) if !bias.datum_type().is_float() => {
+                // integer bias is added to the accumulator
+                let rv = ast.konst_variable(format!("{}_bias", node.name), bias);
+                quantize(ast, &rv, bias.datum_type(), &None, qp.scale_factor.unwrap_or(1.0))?;
+                rv
+            }
+            _ => ast.konst(format!("{}_bias", node.name), bias),
+        };
         inputs.push(bias)
     }
     wire = invocation(
@@ -216,6 +240,97 @@ pub fn conv(
         ],
     );
     wire = ast.force_assign(&node.name, &wire);
+    if let Some(qp) = &op.q_params {
+        quantize(ast, &wire, qp.c_datum_type, &qp.zero_point_c, 1.0)?;
+    }
+    Ok(Some(wire))
+}
+
+/// Record the quantization of an identifier in graph.quant.
+fn quantize(
+    ast: &mut IntoAst,
+    rv: &Arc<RValue>,
+    dt: DatumType,
+    zero_point: &Option<Arc<Tensor>>,
+    scale: f32,
+) -> TractResult<()> {
+    let id = if let RValue::Identifier(id) = &**rv {
+        id.to_string()
+    } else {
+        bail!("Expected an identifier, got {:?}", rv)
+    };
+    let zero_point = if let Some(zp) = zero_point {
+        if zp.rank() != 0 {
+            bail!("graph.quant only supports scalar zero points, got {:?}", zp)
+        }
+        zp.cast_to_scalar::<i32>()?
+    } else {
+        0
+    };
+    let format = QuantFormat::ZeroPointLinear {
+        zero_point,
+        scale,
+        bits: dt.size_of() * 8,
+        signed: dt.is_signed(),
+        symmetric: false,
+    };
+    ast.quantization.insert(id, format);
+    Ok(())
+}
+
+pub fn deconv(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::DeconvUnary,
+) -> TractResult<Option<Arc<RValue>>> {
+    use tract_core::ops::cnn::{KernelFormat, PaddingSpec};
+    let geo_rank = op.kernel.rank() - 2;
+    let kernel = match op.kernel_format {
+        KernelFormat::OIHW => op.kernel.clone(),
+        KernelFormat::HWIO if op.group == 1 => {
+            let mut axes = tvec!(geo_rank + 1, geo_rank);
+            axes.extend(0..geo_rank);
+            op.kernel.clone().into_tensor().permute_axes(&axes)?.into_arc_tensor()
+        }
+        _ => return Ok(None),
+    };
+    let padding = match &op.padding {
+        PaddingSpec::Explicit(bef, after, _) => array(
+            &bef.iter()
+                .zip(after.iter())
+                .map(|(a, b)| tuple_2(numeric(a), numeric(b)))
+                .collect::<Vec<_>>(),
+        ),
+        PaddingSpec::SameUpper => array(&[]),
+        PaddingSpec::SameLower => bail!("Unsupported padding scheme"),
+        PaddingSpec::Valid => {
+            array((0..geo_rank).map(|_| tuple_2(numeric(0), numeric(0))).collect::<Vec<_>>())
+        }
+    };
+    let output_shape = op.data_format.shape(node.outputs[0].fact.shape.to_tvec())?;
+    let mut nchw = tvec!(output_shape.n().cloned().unwrap_or(1.to_dim()), output_shape.c().clone());
+    nchw.extend(output_shape.hw_dims().iter().cloned());
+    let mut wire = ast.mapping[&node.inputs[0]].clone();
+    wire = ast.force_assign(format!("{}_input", node.name), &wire);
+    wire = data_into_ncwh(op.data_format, geo_rank, wire);
+    let weights = ast.konst_variable(format!("{}_weights", node.name), &kernel);
+    let mut inputs = tvec![wire, weights];
+    if let Some(bias) = op.bias.as_ref() {
+        inputs.push(ast.konst(format!("{}_bias", node.name), bias));
+    }
+    let mut params = vec![
+        ("dilation", ints(&op.dilations)),
+        ("stride", ints(&op.strides)),
+        ("border", string("constant")),
+        ("groups", numeric(op.group)),
+        ("padding", padding),
+    ];
+    if let Ok(nchw) = nchw.iter().map(|d| d.to_usize()).collect::<TractResult<TVec<usize>>>() {
+        params.push(("output_shape", ints(&nchw)));
+    }
+    wire = invocation("deconv", &inputs, &params);
+    wire = data_from_ncwh(op.data_format, geo_rank, wire);
+    wire = ast.force_assign(&node.name, &wire);
     Ok(Some(wire))
 }
 
@@ -267,6 +382,7 @@ fn cnn_pool(
     node: &TypedNode,
     op_name: &str,
     pool_spec: &tract_core::ops::cnn::PoolSpec,
+    border: &str,
     normalize_arg: Option<(&'static str, RValue)>,
 ) -> TractResult<Option<Arc<RValue>>> {
     use tract_core::ops::cnn::PaddingSpec;
@@ -275,15 +391,17 @@ fn cnn_pool(
     let conv_fragment = cnn_pool_fragment(ast, pool_spec.data_format, pool_spec.rank(), op_name);
     let padding = match &pool_spec.padding {
         PaddingSpec::Explicit(bef, after, _) => array(
-            &bef.iter()
-                .zip(after.iter())
+            &[(&0, &0), (&0, &0)]
+                .iter()
+                .cloned()
+                .chain(bef.iter().zip(after.iter()))
                 .map(|(a, b)| tuple_2(numeric(a), numeric(b)))
                 .collect::<Vec<_>>(),
         ),
         PaddingSpec::SameUpper => array(&[]),
         PaddingSpec::SameLower => bail!("Unsupported padding scheme"),
         PaddingSpec::Valid => array(
-            (0..pool_spec.rank()).map(|_| tuple_2(numeric(0), numeric(0))).collect::<Vec<_>>(),
+            (0..pool_spec.rank() + 2).map(|_| tuple_2(numeric(0), numeric(0))).collect::<Vec<_>>(),
         ),
     };
     let mut size = tvec!(1, 1);
@@ -296,7 +414,7 @@ fn cnn_pool(
         ("size", ints(&size)),
         ("dilation", ints(&dilations)),
         ("stride", ints(&strides)),
-        ("border", string("ignore")),
+        ("border", string(border)),
         ("padding", padding),
     );
     if let Some(normalize_arg) = normalize_arg {
@@ -312,7 +430,7 @@ pub fn max_pool(
     node: &TypedNode,
     op: &ops::cnn::MaxPool,
 ) -> TractResult<Option<Arc<RValue>>> {
-    cnn_pool(ast, node, "max_pool", &op.pool_spec, None)
+    cnn_pool(ast, node, "max_pool", &op.pool_spec, "ignore", None)
 }
 
 pub fn sum_pool(
@@ -320,7 +438,8 @@ pub fn sum_pool(
     node: &TypedNode,
     op: &ops::cnn::SumPool,
 ) -> TractResult<Option<Arc<RValue>>> {
-    cnn_pool(ast, node, "box", &op.pool_spec, Some(("normalize", logical(op.normalize))))
+    let border = if op.count_include_pad { "constant" } else { "ignore" };
+    cnn_pool(ast, node, "box", &op.pool_spec, border, Some(("normalize", logical(op.normalize))))
 }
 
 pub fn axis_op(
@@ -429,3 +548,67 @@ pub fn select(
         &[],
     )))
 }
+
+fn roi_inputs(ast: &IntoAst, node: &TypedNode) -> TVec<Arc<RValue>> {
+    node.inputs.iter().map(|o| ast.mapping[o].clone()).collect()
+}
+
+pub fn roi_pool(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::RoiPool,
+) -> TractResult<Option<Arc<RValue>>> {
+    let oper = match op.mode {
+        ops::cnn::RoiPoolMode::Avg => "avg_roi_pool",
+        ops::cnn::RoiPoolMode::Max => "max_roi_pool",
+    };
+    Ok(Some(invocation(oper, &roi_inputs(ast, node), &[("output_size", ints(&op.output_size))])))
+}
+
+pub fn roi_resample(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::RoiResample,
+) -> TractResult<Option<Arc<RValue>>> {
+    if op.extrapolation_value.is_some() {
+        return Ok(None);
+    }
+    let method = match op.method {
+        ops::cnn::SamplingMethod::Symmetric => "symmetric",
+        ops::cnn::SamplingMethod::Asymmetric => "asymmetric",
+        ops::cnn::SamplingMethod::Aligned => "aligned",
+    };
+    Ok(Some(invocation(
+        "roi_resample",
+        &roi_inputs(ast, node),
+        &[("output_size", ints(&op.output_size)), ("method", string(method))],
+    )))
+}
+
+/// The stdlib roi align fragments resample and pool with a fixed sampling
+/// rate and pixel centers at half integers, so only aligned `RoiAlign` with a
+/// sampling ratio can be dumped.
+pub fn roi_align(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ops::cnn::RoiAlign,
+) -> TractResult<Option<Arc<RValue>>> {
+    let ratio = if let (Some(ratio), true) = (op.sampling_ratio, op.aligned) {
+        ratio
+    } else {
+        return Ok(None);
+    };
+    let oper = match op.mode {
+        ops::cnn::RoiPoolMode::Avg => "avg_roi_align",
+        ops::cnn::RoiPoolMode::Max => "max_roi_align",
+    };
+    Ok(Some(invocation(
+        oper,
+        &roi_inputs(ast, node),
+        &[
+            ("output_size", ints(&op.output_size)),
+            ("sampling_rate", ints(&[ratio, ratio])),
+            ("resize_method", string("symmetric")),
+        ],
+    )))
+}
@@ -67,6 +67,15 @@ impl Registry {
         self.binary_ops.push((id.into(), tract_core::dyn_clone::clone_box(op)));
     }
 
+    /// Does this registry provide an operator for this invocation identifier.
+    pub fn handles(&self, id: &str) -> bool {
+        self.primitives.contains_key(id)
+            || self.fragments.contains_key(id)
+            || self.unit_element_wise_ops.iter().any(|ew| ew.0 == id)
+            || self.element_wise_ops.iter().any(|ew| ew.0 == id)
+            || self.binary_ops.iter().any(|bin| bin.0 == id)
+    }
+
     pub fn serialize(
         &self,
         ast: &mut IntoAst,
@@ -2,8 +2,15 @@ use crate::ast::*;
 use crate::internal::*;
 use tract_itertools::Itertools;
 
+mod compound;
+
+use compound::Unit;
+
 pub fn to_proto_model(framework: &Nnef, model: &TypedModel) -> TractResult<ProtoModel> {
     let mut into_ast = IntoAst::new(framework, model);
+    if framework.compound_fragments {
+        into_ast.compound = compound::repeated_blocks(framework, model)?;
+    }
     into_ast.translate()?;
     into_ast.into_proto_model()
 }
@@ -28,8 +35,10 @@ pub struct IntoAst<'a> {
     pub results: Vec<String>,
     pub mapping: HashMap<OutletId, Arc<RValue>>,
     pub tensors: Vec<(String, Arc<Tensor>)>,
+    pub quantization: HashMap<String, QuantFormat>,
     pub fragments: HashMap<String, FragmentDef>,
     pub body: Vec<Assignment>,
+    pub compound: compound::Compound,
 }
 
 pub struct RequiredTensorParameter {
@@ -50,9 +59,11 @@ impl<'a> IntoAst<'a> {
             results: vec![],
             mapping: Default::default(),
             tensors: Default::default(),
+            quantization: Default::default(),
             fragments: Default::default(),
             body: vec![],
             parent: None,
+            compound: Default::default(),
         }
     }
 
@@ -84,11 +95,16 @@ impl<'a> IntoAst<'a> {
             self.node(self.model.node(input.node))?;
             self.mapping.insert(*input, RValue::Identifier(left).into());
         }
-        for node in self.model.eval_order()? {
-            if self.model.input_outlets()?.iter().any(|io| io.node == node) {
-                continue;
+        for unit in self.units()? {
+            match unit {
+                Unit::Node(node) => {
+                    if self.model.input_outlets()?.iter().any(|io| io.node == node) {
+                        continue;
+                    }
+                    self.node(self.model.node(node))?;
+                }
+                Unit::Block(ix) => self.block_instance(ix)?,
             }
-            self.node(self.model.node(node))?;
         }
         let outlets: Vec<OutletId> = self.model.output_outlets()?.to_vec();
         for (ix, o) in outlets.into_iter().enumerate() {
@@ -155,24 +171,50 @@ impl<'a> IntoAst<'a> {
             .collect::<Vec<_>>();
         properties.push(tuple_2(
             string("tract_nnef_format_version".to_string()),
-            self.konst("tract_nnef_format_version", &rctensor0("alpha1".to_string()))
-                .as_ref()
-                .clone(),
+            self.konst(
+                "tract_nnef_format_version",
+                &rctensor0(crate::framework::TRACT_NNEF_FORMAT_VERSION.to_string()),
+            )
+            .as_ref()
+            .clone(),
         ));
         let properties: Assignment = assignment("properties", Arc::new(array(properties)));
-        let IntoAst { prefix, mut fragments, body, tensors, parameters, results, .. } = self;
+        let IntoAst {
+            prefix,
+            mut fragments,
+            body,
+            tensors,
+            quantization,
+            parameters,
+            results,
+            compound,
+            mut registries,
+            ..
+        } = self;
+        for frag in compound.fragments {
+            fragments.insert(frag.decl.id.clone(), frag);
+        }
+        for reg in compound.registries {
+            if !registries.contains(&reg) {
+                registries.push(reg)
+            }
+        }
         let mut id = prefix
             .map(|p| p.trim_end_matches(&['-', '/', '.'][..]).replace(&['-', '/', '.'][..], "_"))
             .unwrap_or("network".into());
         if id.len() > 0 && char::is_digit(id.chars().next().unwrap(), 10) {
             id = "_".to_string() + &id;
         }
-        let mut extension = vec![];
-        for reg in self.registries {
+        // properties are stored in a fragment, so there is always at least one
+        let mut extension = vec![vec!["KHR_enable_fragment_definitions".to_string()]];
+        for reg in registries {
             if reg != "tract_nnef" {
                 extension.push(vec!["tract_registry".to_string(), reg]);
             }
         }
+        for assertion in self.model.symbol_table.assertions() {
+            extension.push(vec!["tract_assert".to_string(), assertion.to_string()]);
+        }
         let properties = FragmentDef {
             decl: FragmentDecl {
                 id: "tract_core_properties".to_string(),
@@ -193,7 +235,8 @@ impl<'a> IntoAst<'a> {
             fragments: fragments.into_iter().map(|(_, v)| v).collect(),
             graph_def: GraphDef { id, parameters, results, body },
         };
-        Ok(ProtoModel { doc, tensors })
+        let quantization = if quantization.len() > 0 { Some(quantization) } else { None };
+        Ok(ProtoModel { doc, tensors, quantization })
     }
 
     fn node(&mut self, node: &TypedNode) -> TractResult<TVec<Arc<RValue>>> {
@@ -218,6 +261,9 @@ impl<'a> IntoAst<'a> {
                 };
                 let mut outputs = tvec!();
                 for (ix, o) in names.into_iter().enumerate() {
+                    if let Some(quant) = node.outputs[ix].fact.quant {
+                        self.quantization.insert(o.clone(), quant);
+                    }
                     let rv = Arc::new(ident(o));
                     self.mapping.insert((node.id, ix).into(), rv.clone());
                     outputs.push(rv);
@@ -313,6 +359,23 @@ pub fn ints(shape: &[usize]) -> RValue {
     RValue::Array(shape.iter().map(|s| RValue::Literal(Literal::Numeric(s.to_string()))).collect())
 }
 
+/// Dimensions as an array of integers, with symbolic dimensions serialized as
+/// strings (parsed back as TDim expressions).
+pub fn tdims(shape: &[TDim]) -> RValue {
+    RValue::Array(
+        shape
+            .iter()
+            .map(|d| {
+                if let Ok(d) = d.to_i64() {
+                    RValue::Literal(Literal::Numeric(d.to_string()))
+                } else {
+                    RValue::Literal(Literal::String(d.to_string()))
+                }
+            })
+            .collect(),
+    )
+}
+
 pub fn string(s: impl Into<String>) -> RValue {
     RValue::Literal(Literal::String(s.into()))
 }
@@ -0,0 +1,392 @@
+//! Detection of repeated subgraphs, serialized as compound fragments.
+//!
+//! Candidate blocks are the sets of nodes sharing a name scope (a name prefix
+//! ending with `/` or `.`). Blocks are first bucketed by a structural hash
+//! (operator names, facts and wiring, but not the weights), then each
+//! candidate is serialized as a standalone fragment: blocks producing the
+//! same fragment body are emitted once as a `fragment`, and invoked with
+//! their own weights as tensor parameters.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use crate::ast::*;
+use crate::internal::*;
+use tract_core::ops::konst::Const;
+use tract_core::ops::source::TypedSource;
+
+use super::{IntoAst, RequiredTensorParameter};
+
+/// Smallest block worth a fragment, in number of non-constant nodes.
+const MIN_BLOCK_OPS: usize = 3;
+
+const SEPARATORS: &[char] = &['/', '.'];
+
+/// Repeated blocks found in a model.
+#[derive(Debug, Default)]
+pub struct Compound {
+    pub fragments: Vec<FragmentDef>,
+    pub registries: Vec<String>,
+    pub instances: Vec<Instance>,
+}
+
+/// One occurrence of a repeated block, to be serialized as a fragment
+/// invocation.
+#[derive(Debug)]
+pub struct Instance {
+    pub fragment: String,
+    pub nodes: Vec<usize>,
+    pub inputs: Vec<OutletId>,
+    pub outputs: Vec<OutletId>,
+    /// fragment parameter id, label in the enclosing graph, value
+    pub tensors: Vec<(String, String, Arc<Tensor>)>,
+}
+
+#[derive(Debug, Clone)]
+struct Group {
+    scope: String,
+    nodes: Vec<usize>,
+    inputs: Vec<OutletId>,
+    outputs: Vec<OutletId>,
+}
+
+pub fn repeated_blocks(framework: &Nnef, model: &TypedModel) -> TractResult<Compound> {
+    let order = model.eval_order()?;
+    let mut scopes: HashMap<String, Vec<usize>> = HashMap::new();
+    for &n in &order {
+        let node = model.node(n);
+        if node.op_is::<TypedSource>() {
+            continue;
+        }
+        for (ix, c) in node.name.char_indices() {
+            if ix > 0 && SEPARATORS.contains(&c) {
+                scopes.entry(node.name[..ix].to_string()).or_default().push(n);
+            }
+        }
+    }
+    // a node set reachable under several scopes is considered once, with the
+    // most specific scope
+    let mut groups: HashMap<Vec<usize>, String> = HashMap::new();
+    for (scope, nodes) in scopes {
+        let ops = nodes.iter().filter(|n| !model.node(**n).op_is::<Const>()).count();
+        if ops < MIN_BLOCK_OPS {
+            continue;
+        }
+        let entry = groups.entry(nodes).or_insert_with(|| scope.clone());
+        if entry.len() < scope.len() {
+            *entry = scope;
+        }
+    }
+    let mut buckets: HashMap<u64, Vec<Group>> = HashMap::new();
+    for (nodes, scope) in groups {
+        let group = group(model, scope, nodes)?;
+        buckets.entry(signature(model, &group)?).or_default().push(group);
+    }
+    let mut buckets: Vec<Vec<Group>> = buckets.into_iter().map(|(_, b)| b).collect();
+    for bucket in &mut buckets {
+        bucket.sort_by_key(|g| g.nodes[0]);
+    }
+    buckets.sort_by_key(|b| (std::cmp::Reverse(b[0].nodes.len()), b[0].nodes[0]));
+
+    let mut compound = Compound::default();
+    let mut taken: HashSet<usize> = HashSet::new();
+    for bucket in buckets {
+        if bucket.len() < 2 {
+            continue;
+        }
+        let mut candidates: Vec<(Group, FragmentDef, Vec<RequiredTensorParameter>)> = vec![];
+        for group in bucket {
+            if group.nodes.iter().any(|n| taken.contains(n)) || !is_convex(model, &group) {
+                continue;
+            }
+            if let Some((def, tensors, registries)) = as_fragment(framework, model, &group)? {
+                taken.extend(group.nodes.iter().cloned());
+                for reg in registries {
+                    if !compound.registries.contains(&reg) {
+                        compound.registries.push(reg)
+                    }
+                }
+                candidates.push((group, def, tensors));
+            }
+        }
+        let mut classes: Vec<(
+            FragmentDef,
+            Vec<(Group, FragmentDef, Vec<RequiredTensorParameter>)>,
+        )> = vec![];
+        for candidate in candidates {
+            if let Some(class) = classes.iter_mut().find(|c| c.0 == candidate.1) {
+                class.1.push(candidate);
+            } else {
+                classes.push((candidate.1.clone(), vec![candidate]));
+            }
+        }
+        for (mut def, instances) in classes {
+            if instances.len() < 2 {
+                for (group, _, _) in instances {
+                    group.nodes.iter().for_each(|n| {
+                        taken.remove(n);
+                    });
+                }
+                continue;
+            }
+            def.decl.id = fragment_id(&instances[0].0.scope, &compound.fragments);
+            for (group, _, tensors) in instances {
+                let tensors = tensors
+                    .into_iter()
+                    .map(|t| {
+                        let label = format!("{}/{}", group.scope, t.label);
+                        (t.parameter_id, label, t.value)
+                    })
+                    .collect();
+                compound.instances.push(Instance {
+                    fragment: def.decl.id.clone(),
+                    nodes: group.nodes,
+                    inputs: group.inputs,
+                    outputs: group.outputs,
+                    tensors,
+                });
+            }
+            compound.fragments.push(def);
+        }
+    }
+    Ok(compound)
+}
+
+fn group(model: &TypedModel, scope: String, nodes: Vec<usize>) -> TractResult<Group> {
+    let set: HashSet<usize> = nodes.iter().cloned().collect();
+    let mut inputs: Vec<OutletId> = vec![];
+    let mut outputs: Vec<OutletId> = vec![];
+    for &n in &nodes {
+        let node = model.node(n);
+        for input in &node.inputs {
+            if !set.contains(&input.node) && !inputs.contains(input) {
+                inputs.push(*input);
+            }
+        }
+        for (slot, output) in node.outputs.iter().enumerate() {
+            let outlet = OutletId::new(n, slot);
+            if output.successors.iter().any(|s| !set.contains(&s.node))
+                || model.output_outlets()?.contains(&outlet)
+            {
+                outputs.push(outlet);
+            }
+        }
+    }
+    Ok(Group { scope, nodes, inputs, outputs })
+}
+
+fn hash_fact(fact: &TypedFact, state: &mut DefaultHasher) {
+    fact.datum_type.hash(state);
+    fact.shape.iter().for_each(|d| d.hash(state));
+}
+
+fn signature(model: &TypedModel, group: &Group) -> TractResult<u64> {
+    let mut state = DefaultHasher::new();
+    group.nodes.len().hash(&mut state);
+    for input in &group.inputs {
+        hash_fact(model.outlet_fact(*input)?, &mut state);
+    }
+    for &n in &group.nodes {
+        let node = model.node(n);
+        // only the operator name: the attributes hash would include the
+        // weights embedded in decluttered operators, which become tensor
+        // parameters of the fragment. Attributes are compared on the
+        // serialized fragment bodies.
+        node.op.name().hash(&mut state);
+        for input in &node.inputs {
+            if let Some(ix) = group.inputs.iter().position(|i| i == input) {
+                (0, ix).hash(&mut state);
+            } else {
+                let ix = group.nodes.iter().position(|n| *n == input.node).unwrap();
+                (1, ix, input.slot).hash(&mut state);
+            }
+        }
+        for output in &node.outputs {
+            hash_fact(&output.fact, &mut state);
+        }
+    }
+    for output in &group.outputs {
+        (group.nodes.iter().position(|n| *n == output.node), output.slot).hash(&mut state);
+    }
+    Ok(state.finish())
+}
+
+/// A block can be replaced by a single invocation only if none of its inputs
+/// depends on one of its outputs.
+fn is_convex(model: &TypedModel, group: &Group) -> bool {
+    let set: HashSet<usize> = group.nodes.iter().cloned().collect();
+    let mut downstream: HashSet<usize> = HashSet::new();
+    let mut todo: Vec<usize> = group
+        .outputs
+        .iter()
+        .flat_map(|o| model.node(o.node).outputs[o.slot].successors.iter().map(|s| s.node))
+        .filter(|n| !set.contains(n))
+        .collect();
+    while let Some(n) = todo.pop() {
+        if set.contains(&n) {
+            return false;
+        }
+        if downstream.insert(n) {
+            for output in &model.node(n).outputs {
+                todo.extend(output.successors.iter().map(|s| s.node));
+            }
+        }
+    }
+    true
+}
+
+/// Serialize a block as a fragment, with names relative to its scope.
+fn as_fragment(
+    framework: &Nnef,
+    model: &TypedModel,
+    group: &Group,
+) -> TractResult<Option<(FragmentDef, Vec<RequiredTensorParameter>, Vec<String>)>> {
+    let mut block = TypedModel::default();
+    let mut mapping: HashMap<OutletId, OutletId> = HashMap::new();
+    for (ix, input) in group.inputs.iter().enumerate() {
+        let fact = model.outlet_fact(*input)?.clone();
+        mapping.insert(*input, block.add_source(format!("input_{}", ix), fact)?);
+    }
+    for &n in &group.nodes {
+        let node = model.node(n);
+        let name = &node.name[group.scope.len() + 1..];
+        let inputs: TVec<OutletId> = node.inputs.iter().map(|i| mapping[i]).collect();
+        let outputs = block.wire_node(name, node.op.clone(), &inputs)?;
+        for (slot, o) in outputs.into_iter().enumerate() {
+            mapping.insert(OutletId::new(n, slot), o);
+        }
+    }
+    block.set_output_outlets(&group.outputs.iter().map(|o| mapping[o]).collect::<Vec<_>>())?;
+    let mut into_ast = IntoAst::new(framework, &block);
+    if into_ast.translate().is_err() || into_ast.fragments.len() > 0 {
+        return Ok(None);
+    }
+    let registries = into_ast.registries.clone();
+    let (def, tensors) = into_ast.into_fragment()?;
+    Ok(Some((def, tensors, registries)))
+}
+
+fn fragment_id(scope: &str, existing: &[FragmentDef]) -> String {
+    let base =
+        scope.trim_end_matches(|c: char| c.is_ascii_digit() || SEPARATORS.contains(&c) || c == '_');
+    let base = IntoAst::sanitize(format!("{}_block", base.trim_start_matches(SEPARATORS)));
+    let mut id = base.clone();
+    let mut ix = 1;
+    while existing.iter().any(|f| f.decl.id == id) {
+        id = format!("{}_{}", base, ix);
+        ix += 1;
+    }
+    id
+}
+
+/// What the serializer emits in turn: a node, or a block instance.
+pub enum Unit {
+    Node(usize),
+    Block(usize),
+}
+
+impl<'a> IntoAst<'a> {
+    /// Evaluation order where each block instance appears as a single unit.
+    pub(super) fn units(&self) -> TractResult<Vec<Unit>> {
+        let order = self.model.eval_order()?;
+        if self.compound.instances.len() == 0 {
+            return Ok(order.into_iter().map(Unit::Node).collect());
+        }
+        let mut block_of: HashMap<usize, usize> = HashMap::new();
+        for (ix, instance) in self.compound.instances.iter().enumerate() {
+            for n in &instance.nodes {
+                block_of.insert(*n, ix);
+            }
+        }
+        let blocks_offset = self.model.nodes().len();
+        let unit = |n: usize| block_of.get(&n).map(|b| blocks_offset + b).unwrap_or(n);
+        let mut position: HashMap<usize, usize> = HashMap::new();
+        let mut deps: HashMap<usize, HashSet<usize>> = HashMap::new();
+        let mut succs: HashMap<usize, HashSet<usize>> = HashMap::new();
+        for (pos, &n) in order.iter().enumerate() {
+            let u = unit(n);
+            position.entry(u).or_insert(pos);
+            deps.entry(u).or_default();
+            for input in &self.model.node(n).inputs {
+                let prec = unit(input.node);
+                if prec != u {
+                    deps.get_mut(&u).unwrap().insert(prec);
+                    succs.entry(prec).or_default().insert(u);
+                }
+            }
+        }
+        let mut ready: std::collections::BinaryHeap<std::cmp::Reverse<(usize, usize)>> = deps
+            .iter()
+            .filter(|(_, d)| d.len() == 0)
+            .map(|(u, _)| std::cmp::Reverse((position[u], *u)))
+            .collect();
+        let mut units = vec![];
+        while let Some(std::cmp::Reverse((_, u))) = ready.pop() {
+            units.push(if u >= blocks_offset {
+                Unit::Block(u - blocks_offset)
+            } else {
+                Unit::Node(u)
+            });
+            for succ in succs.get(&u).into_iter().flatten() {
+                let d = deps.get_mut(succ).unwrap();
+                d.remove(&u);
+                if d.len() == 0 {
+                    ready.push(std::cmp::Reverse((position[succ], *succ)));
+                }
+            }
+        }
+        if units.len() != deps.len() {
+            bail!("Cycle between repeated blocks while serializing")
+        }
+        Ok(units)
+    }
+
+    /// Serialize a block instance as an invocation of its fragment.
+    pub(super) fn block_instance(&mut self, ix: usize) -> TractResult<()> {
+        let instance = &self.compound.instances[ix];
+        let (fragment, inputs, outputs) =
+            (instance.fragment.clone(), instance.inputs.clone(), instance.outputs.clone());
+        let tensors = instance.tensors.clone();
+        let decl = &self.compound.fragments.iter().find(|f| f.decl.id == fragment).unwrap().decl;
+        let mut arguments: Vec<Argument> = inputs
+            .iter()
+            .zip(decl.parameters.iter())
+            .map(|(input, param)| super::named_arg(&param.id, self.mapping[input].as_ref().clone()))
+            .collect();
+        let results: Vec<String> = outputs
+            .iter()
+            .map(|o| {
+                let scoped = self.scoped_id(&self.model.node(o.node).name);
+                if o.slot > 0 {
+                    format!("{}_{}", scoped, o.slot)
+                } else {
+                    scoped
+                }
+            })
+            .collect();
+        for (param, label, value) in &tensors {
+            let value = self.konst_variable(label, value);
+            arguments.push(super::named_arg(param, value.as_ref().clone()));
+        }
+        let left = if results.len() == 1 {
+            LValue::Identifier(results[0].clone())
+        } else {
+            LValue::Tuple(results.iter().map(|r| LValue::Identifier(r.clone())).collect())
+        };
+        self.body.push(Assignment {
+            left,
+            right: RValue::Invocation(Invocation {
+                id: fragment,
+                generic_type_name: None,
+                arguments,
+            }),
+        });
+        for (outlet, name) in outputs.iter().zip(results.into_iter()) {
+            if let Some(quant) = self.model.outlet_fact(*outlet)?.quant {
+                self.quantization.insert(name.clone(), quant);
+            }
+            self.mapping.insert(*outlet, RValue::Identifier(name).into());
+        }
+        Ok(())
+    }
+}
@@ -10,62 +10,127 @@ struct Header {
     rank: u32,
     dims: [u32; 8],
     bits_per_item: u32,
-    item_type_vendor: u16,
-    item_type: u16,
+    item_type: u32,
     item_type_params_deprecated: [u8; 32],
     padding: [u32; 11],
 }
 
+// Khronos item types. Vendor specific types have non-zero upper 16 bits.
+const FLOAT: u32 = 0x00;
+const UINT: u32 = 0x01;
+const QUINT: u32 = 0x02;
+const QINT: u32 = 0x03;
+const INT: u32 = 0x04;
+const BOOL: u32 = 0x05;
+// NNEF 1.0.0 linear quantization, with min and max as parameters
+const LEGACY_LINEAR: u32 = 0x10;
+// signed integers, as written by older tract versions
+const TRACT_INT: u32 = 0x0100_0000;
+
 pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
     unsafe {
         let mut header: Header = std::mem::zeroed();
         let buffer: &mut [u8; 128] = std::mem::transmute(&mut header);
         reader.read_exact(buffer)?;
-        if header.magic != [0x4e, 0xef] {
-            bail!("Wrong magic number");
-        }
-        if header.version_maj != 1 && header.version_min != 0 {
-            bail!("Wrong version number");
-        }
-        if header.rank > 8 {
-            bail!("Wrong tensor rank {}", header.rank);
-        }
-        let shape: TVec<usize> =
-            header.dims[0..header.rank as usize].iter().map(|d| *d as _).collect();
-        let len = shape.iter().product::<usize>();
-        if len * (header.bits_per_item as usize / 8) != header.data_size_bytes as usize {
-            bail!(
-                "Shape and len mismatch: shape:{:?}, bits_per_item:{}, bytes:{} ",
-                shape,
-                header.bits_per_item,
-                header.data_size_bytes
-            );
-        }
-        if header.item_type_vendor != 0 {
-            bail!("Unknownn item type vendor {}", header.item_type_vendor);
-        }
-        let dt = match (header.item_type, header.bits_per_item) {
-            (0, 16) => DatumType::F16,
-            (0, 32) => DatumType::F32,
-            (0, 64) => DatumType::F64,
-            (1, 8) => DatumType::U8,
-            (1, 16) => DatumType::U16,
-            (1, 32) => DatumType::U32,
-            (1, 64) => DatumType::U64,
-            (0x0100, 8) => DatumType::I8,
-            (0x0100, 16) => DatumType::I16,
-            (0x0100, 32) => DatumType::I32,
-            (0x0100, 64) => DatumType::I64,
-            _ => bail!(
-                "Unsupported type in tensor type:{} bits_per_item:{}",
-                header.item_type,
-                header.bits_per_item
-            ),
-        };
+        let (dt, shape) = check_header(&header)?;
         let mut tensor = Tensor::uninitialized_dt(dt, &shape)?;
         reader.read_exact(tensor.as_bytes_mut())?;
-        Ok(tensor)
+        decode(&header, tensor)
+    }
+}
+
+/// Build a tensor from the content of a .dat file found in memory owned by
+/// `owner`. The tensor borrows the data when it is usable as is (suitably
+/// aligned, not in a legacy quantized format), it is copied otherwise.
+///
+/// Safety: `bytes` must point inside `owner`, and stay unchanged as long as
+/// `owner` is alive.
+pub unsafe fn read_tensor_in_place(
+    bytes: &[u8],
+    owner: &Arc<dyn std::any::Any + Send + Sync>,
+) -> TractResult<Tensor> {
+    if bytes.len() < 128 {
+        bail!("Truncated tensor file");
+    }
+    let mut header: Header = std::mem::zeroed();
+    let buffer: &mut [u8; 128] = std::mem::transmute(&mut header);
+    buffer.copy_from_slice(&bytes[..128]);
+    let (dt, shape) = check_header(&header)?;
+    let data = &bytes[128..];
+    if data.len() < header.data_size_bytes as usize {
+        bail!("Truncated tensor file: expected {} bytes of data", header.data_size_bytes);
+    }
+    if header.item_type == LEGACY_LINEAR || data.as_ptr() as usize % dt.alignment() != 0 {
+        return read_tensor(bytes);
+    }
+    Ok(Tensor::from_raw_dt_owned_by(dt, &shape, data.as_ptr() as *mut u8, owner.clone())?)
+}
+
+fn check_header(header: &Header) -> TractResult<(DatumType, TVec<usize>)> {
+    if header.magic != [0x4e, 0xef] {
+        bail!("Wrong magic number");
+    }
+    if header.version_maj != 1 && header.version_min != 0 {
+        bail!("Wrong version number");
+    }
+    if header.rank > 8 {
+        bail!("Wrong tensor rank {}", header.rank);
+    }
+    let shape: TVec<usize> = header.dims[0..header.rank as usize].iter().map(|d| *d as _).collect();
+    let len = shape.iter().product::<usize>();
+    if len * (header.bits_per_item as usize / 8) != header.data_size_bytes as usize {
+        bail!(
+            "Shape and len mismatch: shape:{:?}, bits_per_item:{}, bytes:{} ",
+            shape,
+            header.bits_per_item,
+            header.data_size_bytes
+        );
+    }
+    let dt = match (header.item_type, header.bits_per_item) {
+        (FLOAT, 16) => DatumType::F16,
+        (FLOAT, 32) => DatumType::F32,
+        (FLOAT, 64) => DatumType::F64,
+        (UINT, 8) | (QUINT, 8) | (LEGACY_LINEAR, 8) => DatumType::U8,
+        (UINT, 16) | (QUINT, 16) | (LEGACY_LINEAR, 16) => DatumType::U16,
+        (UINT, 32) | (QUINT, 32) => DatumType::U32,
+        (UINT, 64) => DatumType::U64,
+        (INT, 8) | (QINT, 8) | (TRACT_INT, 8) => DatumType::I8,
+        (INT, 16) | (QINT, 16) | (TRACT_INT, 16) => DatumType::I16,
+        (INT, 32) | (QINT, 32) | (TRACT_INT, 32) => DatumType::I32,
+        (INT, 64) | (TRACT_INT, 64) => DatumType::I64,
+        (BOOL, 8) => DatumType::Bool,
+        _ if header.item_type >> 16 != 0 => bail!(
+            "Unsupported vendor item type {:#x} (vendor {:#x}), bits_per_item:{}",
+            header.item_type,
+            header.item_type >> 16,
+            header.bits_per_item
+        ),
+        _ => bail!(
+            "Unsupported type in tensor type:{} bits_per_item:{}",
+            header.item_type,
+            header.bits_per_item
+        ),
+    };
+    Ok((dt, shape))
+}
+
+fn decode(header: &Header, tensor: Tensor) -> TractResult<Tensor> {
+    if header.item_type == LEGACY_LINEAR {
+        // NNEF 1.0.0 stored min and max in the header, values are decoded
+        // to floats.
+        let param = |i: usize| {
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(&header.item_type_params_deprecated[4 * i..][..4]);
+            f32::from_le_bytes(bytes)
+        };
+        let (min, max) = (param(0), param(1));
+        let scale = (max - min) / ((1u64 << header.bits_per_item) - 1) as f32;
+        let tensor = tensor.cast_to::<f32>()?.into_owned();
+        let mut tensor = tensor.into_array::<f32>()?;
+        tensor.mapv_inplace(|q| min + q * scale);
+        return Ok(tensor.into_tensor());
     }
+    Ok(tensor)
 }
 
 pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResult<()> {
@@ -84,11 +149,14 @@ pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResul
         header.data_size_bytes = (tensor.len() * tensor.datum_type().size_of()) as u32;
         header.bits_per_item = (tensor.datum_type().size_of() * 8) as u32;
         header.item_type = if tensor.datum_type().is_float() {
-            0
+            FLOAT
         } else if tensor.datum_type().is_signed() {
-            0x100
+            // keep the tract type, older versions can not read INT
+            TRACT_INT
         } else if tensor.datum_type().is_unsigned() {
-            1
+            UINT
+        } else if tensor.datum_type() == DatumType::Bool {
+            BOOL
         } else {
             bail!("Don't know how to serialize {:?}", tensor.datum_type())
         };
@@ -107,4 +175,66 @@ mod test {
     fn header_is_128_bytes() {
         assert_eq!(std::mem::size_of::<Header>(), 128);
     }
+
+    fn header(item_type: u32, bits: u32, len: usize) -> Vec<u8> {
+        let mut header = vec![0u8; 128];
+        header[0..4].copy_from_slice(&[0x4e, 0xef, 1, 0]);
+        header[4..8].copy_from_slice(&(len as u32 * bits / 8).to_le_bytes());
+        header[8..12].copy_from_slice(&1u32.to_le_bytes());
+        header[12..16].copy_from_slice(&(len as u32).to_le_bytes());
+        header[44..48].copy_from_slice(&bits.to_le_bytes());
+        header[48..52].copy_from_slice(&item_type.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn read_quantized_unsigned() {
+        let mut data = header(QUINT, 8, 3);
+        data.extend(&[0u8, 128, 255]);
+        let tensor = read_tensor(&*data).unwrap();
+        assert_eq!(tensor, tensor1(&[0u8, 128, 255]));
+    }
+
+    #[test]
+    fn read_legacy_linear_quantized() {
+        let mut data = header(LEGACY_LINEAR, 8, 3);
+        data[52..56].copy_from_slice(&(-1.0f32).to_le_bytes());
+        data[56..60].copy_from_slice(&(1.0f32).to_le_bytes());
+        data.extend(&[0u8, 51, 255]);
+        let tensor = read_tensor(&*data).unwrap();
+        tensor.close_enough(&tensor1(&[-1.0f32, -0.6, 1.0]), true).unwrap();
+    }
+
+    #[test]
+    fn read_vendor_type() {
+        let mut data = header(0x0042_0001, 8, 1);
+        data.push(0);
+        assert!(read_tensor(&*data).is_err());
+    }
+
+    #[test]
+    fn write_read_signed_and_logical() {
+        for t in &[tensor1(&[-1i8, 2]), tensor1(&[-1i64, 2]), tensor1(&[true, false])] {
+            let mut data = vec![];
+            write_tensor(&mut data, t).unwrap();
+            if t.datum_type().is_signed() {
+                assert_eq!(&data[48..52], &TRACT_INT.to_le_bytes());
+            }
+            assert_eq!(&read_tensor(&*data).unwrap(), t);
+        }
+    }
+
+    #[test]
+    fn read_khronos_signed() {
+        let mut data = header(INT, 32, 1);
+        data.extend(&(-3i32).to_le_bytes());
+        assert_eq!(read_tensor(&*data).unwrap(), tensor1(&[-3i32]));
+    }
+
+    #[test]
+    fn read_tract_legacy_signed() {
+        let mut data = header(TRACT_INT, 32, 1);
+        data.extend(&(-3i32).to_le_bytes());
+        assert_eq!(read_tensor(&*data).unwrap(), tensor1(&[-3i32]));
+    }
 }
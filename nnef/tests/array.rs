@@ -0,0 +1,88 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::array::*;
+
+fn model(
+    inputs: &[(&str, DatumType, &[usize])],
+    wire: impl Fn(&mut TypedModel, &[OutletId]) -> TractResult<TVec<OutletId>>,
+) -> TractResult<TypedModel> {
+    let mut model = TypedModel::default();
+    let sources = inputs
+        .iter()
+        .map(|(name, dt, shape)| model.add_source(*name, TypedFact::dt_shape(*dt, *shape)))
+        .collect::<TractResult<TVec<_>>>()?;
+    let outputs = wire(&mut model, &sources)?;
+    model.set_output_outlets(&outputs)?;
+    Ok(model)
+}
+
+fn round_trip(model: TypedModel, inputs: TVec<Tensor>) -> TractResult<()> {
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let mut buffer = vec![];
+    nnef.write_to_tar(&model, &mut buffer)?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+    let expected = model.into_runnable()?.run(inputs.clone())?;
+    let found = reloaded.into_runnable()?.run(inputs)?;
+    assert_eq!(expected.len(), found.len());
+    for (expected, found) in expected.iter().zip(found.iter()) {
+        expected.close_enough(found, false)?;
+    }
+    Ok(())
+}
+
+fn input() -> Tensor {
+    tensor2(&[[3f32, 1., 4.], [1., 5., 9.], [3., 1., 4.]])
+}
+
+#[test]
+fn cumsum() -> TractResult<()> {
+    let model = model(&[("input", f32::datum_type(), &[3, 3])], |model, inputs| {
+        model.wire_node("cumsum", CumSum::new(1, true, true), inputs)
+    })?;
+    round_trip(model, tvec!(input()))
+}
+
+#[test]
+fn range() -> TractResult<()> {
+    let scalar: &[usize] = &[];
+    let inputs = [
+        ("start", i64::datum_type(), scalar),
+        ("end", i64::datum_type(), scalar),
+        ("step", i64::datum_type(), scalar),
+    ];
+    let model = model(&inputs, |model, inputs| {
+        let op = Range::for_inputs(model, inputs)?;
+        model.wire_node("range", op, inputs)
+    })?;
+    round_trip(model, tvec!(tensor0(2i64), tensor0(11i64), tensor0(3i64)))
+}
+
+#[test]
+fn reverse_sequence() -> TractResult<()> {
+    let inputs =
+        [("input", f32::datum_type(), &[3usize, 3] as &[usize]), ("lens", i64::datum_type(), &[3])];
+    let model = model(&inputs, |model, inputs| {
+        model.wire_node("reverse", ReverseSequence::new(0, 1), inputs)
+    })?;
+    round_trip(model, tvec!(input(), tensor1(&[3i64, 0, 2])))
+}
+
+#[test]
+fn trilu() -> TractResult<()> {
+    let model = model(&[("input", f32::datum_type(), &[3, 3])], |model, inputs| {
+        let k = model.add_const("k", tensor0(-1i64))?;
+        model.wire_node("trilu", Trilu::new(false), &[inputs[0], k])
+    })?;
+    round_trip(model, tvec!(input()))
+}
+
+#[test]
+fn unique() -> TractResult<()> {
+    for axis in &[None, Some(0)] {
+        let model = model(&[("input", f32::datum_type(), &[3, 3])], |model, inputs| {
+            let count = model.symbol_table.new_symbol("unique");
+            model.wire_node("unique", Unique::new(*axis, true, count), inputs)
+        })?;
+        round_trip(model, tvec!(input()))?;
+    }
+    Ok(())
+}
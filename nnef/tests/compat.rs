@@ -0,0 +1,102 @@
+use tract_nnef::ast::parse::parse_document;
+use tract_nnef::internal::*;
+use tract_nnef::ProtoModel;
+
+fn load(nnef: &Nnef, text: &str) -> TractResult<TypedModel> {
+    let doc = parse_document(text)?;
+    nnef.model_for_proto_model(&ProtoModel { doc, tensors: vec![], quantization: None })
+}
+
+fn load_error(nnef: &Nnef, text: &str) -> String {
+    format!("{:?}", load(nnef, text).unwrap_err())
+}
+
+#[test]
+fn reports_all_unsupported_operators_and_registries() {
+    let error = load_error(
+        &tract_nnef::nnef().with_tract_core(),
+        "version 1.0;
+        extension tract_registry tract_unknown;
+        graph G(input) -> (output) {
+            input = external(shape = [2]);
+            a = frobnicate(input);
+            output = tract_core_gather(a, indices = [0], axis = 0);
+        }",
+    );
+    assert!(error.contains("registry tract_unknown"), "{}", error);
+    assert!(error.contains("operator frobnicate"), "{}", error);
+    assert!(
+        error.contains("operator tract_core_gather (needs extension tract_registry tract_core)"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn rejects_unsupported_versions() {
+    let graph = "graph G(input) -> (input) { input = external(shape = [2]); }";
+    let error = load_error(&tract_nnef::nnef(), &format!("version 2.0; {}", graph));
+    assert!(error.contains("NNEF version 2.0"), "{}", error);
+
+    let properties =
+        "fragment tract_core_properties() -> (properties: (string, tensor<scalar>)[]) {
+        properties = [(\"tract_nnef_format_version\", \"beta9\")];
+    }";
+    let error = load_error(
+        &tract_nnef::nnef(),
+        &format!(
+            "version 1.0; extension KHR_enable_fragment_definitions; {} {}",
+            properties, graph
+        ),
+    );
+    assert!(error.contains("tract NNEF format version beta9"), "{}", error);
+}
+
+#[test]
+fn khr_extension_flags() -> TractResult<()> {
+    let nnef = tract_nnef::nnef();
+    let body = "graph G(input) -> (output) {
+        input = external(shape = [2]);
+        output = twice(input) + 1.0;
+    }";
+    let fragment = "fragment twice(x: tensor<scalar>) -> (y: tensor<scalar>) { y = x * 2.0; }";
+    let model = load(
+        &nnef,
+        &format!(
+            "version 1.0;
+            extension KHR_enable_fragment_definitions, KHR_enable_operator_expressions;
+            {} {}",
+            fragment, body
+        ),
+    )?;
+    let output = model.into_runnable()?.run(tvec!(tensor1(&[1.0f32, 2.0])))?;
+    assert_eq!(*output[0], tensor1(&[3.0f32, 5.0]));
+
+    let error = load_error(
+        &nnef,
+        &format!("version 1.0; extension KHR_enable_fragment_definitions; {} {}", fragment, body),
+    );
+    assert!(error.contains("KHR_enable_operator_expressions"), "{}", error);
+
+    let error = load_error(
+        &nnef,
+        &format!("version 1.0; extension KHR_enable_operator_expressions; {} {}", fragment, body),
+    );
+    assert!(error.contains("KHR_enable_fragment_definitions"), "{}", error);
+
+    let error =
+        load_error(&nnef, &format!("version 1.0; extension KHR_enable_teleportation; {}", body));
+    assert!(error.contains("extension KHR_enable_teleportation"), "{}", error);
+    Ok(())
+}
+
+#[test]
+fn written_documents_declare_their_features() -> TractResult<()> {
+    let mut model = TypedModel::default();
+    model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[2]))?;
+    model.auto_outputs()?;
+    let proto = tract_nnef::ser::to_proto_model(&tract_nnef::nnef(), &model)?;
+    assert!(proto.doc.extension.contains(&vec!["KHR_enable_fragment_definitions".to_string()]));
+    tract_nnef::nnef().model_for_proto_model(&proto)?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use tract_nnef::ast::*;
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::math;
+
+fn layered_model(layers: usize) -> TractResult<TypedModel> {
+    let mut model = TypedModel::default();
+    let mut wire = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[1, 4]))?;
+    for layer in 0..layers {
+        let scale = layer as f32 + 1.0;
+        let w = model.add_const(
+            format!("layer_{}/w", layer),
+            tensor2(&[[scale, -scale, 0.5 * scale, 2.0]]),
+        )?;
+        let b =
+            model.add_const(format!("layer_{}/b", layer), tensor2(&[[0.1, 0.2, 0.3, scale]]))?;
+        wire =
+            model.wire_node(format!("layer_{}/mul", layer), math::mul::bin_typed(), &[wire, w])?[0];
+        wire =
+            model.wire_node(format!("layer_{}/add", layer), math::add::bin_typed(), &[wire, b])?[0];
+        wire = model.wire_node(format!("layer_{}/tanh", layer), math::tanh(), &[wire])?[0];
+    }
+    model.set_output_outlets(&[wire])?;
+    Ok(model)
+}
+
+fn invocations(proto: &ProtoModel, id: &str) -> usize {
+    proto
+        .doc
+        .graph_def
+        .body
+        .iter()
+        .filter(|a| match &a.right {
+            RValue::Invocation(inv) => inv.id == id,
+            _ => false,
+        })
+        .count()
+}
+
+#[test]
+fn repeated_blocks_as_fragment() -> TractResult<()> {
+    let nnef = tract_nnef::nnef().with_compound_fragments(true);
+    let proto = tract_nnef::ser::to_proto_model(&nnef, &layered_model(3)?)?;
+    let blocks: Vec<&FragmentDef> =
+        proto.doc.fragments.iter().filter(|f| f.decl.id == "layer_block").collect();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].body.as_ref().unwrap().len(), 3);
+    assert_eq!(invocations(&proto, "layer_block"), 3);
+    assert_eq!(invocations(&proto, "tanh"), 0);
+    Ok(())
+}
+
+#[test]
+fn no_compound_fragments_by_default() -> TractResult<()> {
+    let nnef = tract_nnef::nnef();
+    let proto = tract_nnef::ser::to_proto_model(&nnef, &layered_model(3)?)?;
+    assert!(proto.doc.fragments.iter().all(|f| f.decl.id != "layer_block"));
+    assert_eq!(invocations(&proto, "tanh"), 3);
+    Ok(())
+}
+
+#[test]
+fn repeated_blocks_round_trip() -> TractResult<()> {
+    let nnef = tract_nnef::nnef().with_compound_fragments(true);
+    let model = layered_model(3)?;
+    let mut buffer = vec![];
+    nnef.write_to_tar(&model, &mut buffer)?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+
+    let input = tensor2(&[[1.0f32, 2.0, -0.5, 0.25]]);
+    let expected = model.into_runnable()?.run(tvec!(input.clone()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input))?;
+    expected[0].close_enough(&found[0], true)
+}
+
+#[test]
+fn decluttered_repeated_blocks() -> TractResult<()> {
+    let nnef = tract_nnef::nnef().with_compound_fragments(true);
+    // decluttering embeds the weights in the operators
+    let model = layered_model(3)?.declutter()?;
+    let proto = tract_nnef::ser::to_proto_model(&nnef, &model)?;
+    assert_eq!(invocations(&proto, "layer_block"), 3);
+    assert_eq!(invocations(&proto, "tanh"), 0);
+
+    let mut buffer = vec![];
+    nnef.write_to_tar(&model, &mut buffer)?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+    let input = tensor2(&[[1.0f32, 2.0, -0.5, 0.25]]);
+    let expected = model.into_runnable()?.run(tvec!(input.clone()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input))?;
+    expected[0].close_enough(&found[0], true)
+}
@@ -0,0 +1,470 @@
+//! Graphs in the style of the Khronos NNEF-Tools test cases, exercising the
+//! stdlib fragments that are not trivially mapped to a core op. Outputs are
+//! checked against straightforward implementations of the NNEF spec
+//! definitions, not against the outputs published with NNEF-Tools.
+use std::collections::HashMap;
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ndarray::{s, Array4, ArrayView2, ArrayView4, Ix4};
+
+type Variables = HashMap<String, Arc<Tensor>>;
+
+fn input() -> Tensor {
+    let values: Vec<f32> = (0..256).map(|i| ((i * 7) % 19) as f32 / 4.0 - 2.0).collect();
+    tensor1(&values).into_shape(&[1, 4, 8, 8]).unwrap()
+}
+
+/// Regions of interest, as (y1, x1, y2, x2).
+fn rois() -> Tensor {
+    tensor2(&[[0.0f32, 0.0, 4.0, 4.0], [2.0, 1.0, 7.0, 6.0], [1.0, 1.0, 3.0, 8.0]])
+}
+
+fn path(case: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/khronos").join(case)
+}
+
+fn run(case: &str) -> TractResult<(Tensor, TVec<Arc<Tensor>>)> {
+    let model = tract_nnef::nnef().model_for_path(path(case))?;
+    let input = input();
+    let mut inputs = tvec!(input.clone());
+    if model.input_outlets()?.len() == 3 {
+        inputs.push(rois());
+        inputs.push(tensor1(&[0i64, 0, 0]));
+    }
+    let outputs = model.into_runnable()?.run(inputs)?;
+    Ok((input, outputs))
+}
+
+fn variables(case: &str) -> TractResult<Variables> {
+    Ok(tract_nnef::nnef().proto_model_for_path(path(case))?.tensors.into_iter().collect())
+}
+
+macro_rules! reference_test {
+    ($case: ident, $reference: expr) => {
+        #[test]
+        fn $case() -> TractResult<()> {
+            let reference: fn(&Tensor, &Variables) -> TractResult<Vec<Tensor>> = $reference;
+            let (input, outputs) = run(stringify!($case))?;
+            let expected = reference(&input, &variables(stringify!($case))?)?;
+            assert_eq!(outputs.len(), expected.len());
+            for (found, expected) in outputs.iter().zip(expected.iter()) {
+                if expected.datum_type().is_float() {
+                    found.close_enough(expected, true)?;
+                } else {
+                    assert_eq!(&**found, expected);
+                }
+            }
+            Ok(())
+        }
+    };
+}
+
+fn view4(t: &Tensor) -> TractResult<ArrayView4<f32>> {
+    Ok(t.to_array_view::<f32>()?.into_dimensionality::<Ix4>()?)
+}
+
+/// Output length and padding before for a transposed convolution. Without
+/// explicit padding, the output is `stride` times longer than the input.
+fn deconv_geometry(
+    len: usize,
+    kernel: usize,
+    stride: usize,
+    padding: Option<usize>,
+) -> (usize, isize) {
+    let full = (len - 1) * stride + kernel;
+    if let Some(padding) = padding {
+        (full - 2 * padding, padding as isize)
+    } else {
+        let output = len * stride;
+        (output, (full.saturating_sub(output) / 2) as isize)
+    }
+}
+
+/// Transposed convolution of an NCHW input by a [ci, co / groups, kh, kw]
+/// filter. `groups` 0 means one group per input channel.
+fn deconv(
+    input: &Tensor,
+    filter: &Tensor,
+    bias: &[f32],
+    stride: usize,
+    padding: Option<usize>,
+    groups: usize,
+) -> TractResult<Tensor> {
+    let input = view4(input)?;
+    let filter = view4(filter)?;
+    let (_, ci, h, w) = input.dim();
+    let (_, co_per_group, kh, kw) = filter.dim();
+    let groups = if groups == 0 { ci } else { groups };
+    let co = co_per_group * groups;
+    let (oh, pad_y) = deconv_geometry(h, kh, stride, padding);
+    let (ow, pad_x) = deconv_geometry(w, kw, stride, padding);
+    let mut output = Array4::<f32>::zeros((1, co, oh, ow));
+    for o in 0..co {
+        output.slice_mut(s![0, o, .., ..]).fill(bias[if bias.len() == 1 { 0 } else { o }]);
+    }
+    for i in 0..ci {
+        let group = i / (ci / groups);
+        for j in 0..co_per_group {
+            let o = group * co_per_group + j;
+            for ((y, x), v) in input.slice(s![0, i, .., ..]).indexed_iter() {
+                for ky in 0..kh {
+                    for kx in 0..kw {
+                        let oy = (y * stride + ky) as isize - pad_y;
+                        let ox = (x * stride + kx) as isize - pad_x;
+                        if oy >= 0 && ox >= 0 && (oy as usize) < oh && (ox as usize) < ow {
+                            output[[0, o, oy as usize, ox as usize]] += v * filter[[i, j, ky, kx]];
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(output.into_tensor())
+}
+
+fn bias(variables: &Variables) -> TractResult<&[f32]> {
+    variables["bias"].as_slice::<f32>()
+}
+
+/// Linear interpolation along both spatial axes. Samples outside of the
+/// input are either replicated from the border, or zeros.
+fn upsample(input: &Tensor, symmetric: bool, replicate: bool) -> TractResult<Tensor> {
+    let input = view4(input)?;
+    let (_, c, h, w) = input.dim();
+    let coord = |o: usize| {
+        let coord = if symmetric { (o as f32 + 0.5) / 2.0 - 0.5 } else { o as f32 / 2.0 };
+        (coord.floor() as isize, coord - coord.floor())
+    };
+    let get = |plane: &ArrayView2<f32>, y: isize, x: isize| {
+        if replicate {
+            plane[[y.max(0).min(h as isize - 1) as usize, x.max(0).min(w as isize - 1) as usize]]
+        } else if y < 0 || x < 0 || y >= h as isize || x >= w as isize {
+            0.0
+        } else {
+            plane[[y as usize, x as usize]]
+        }
+    };
+    let mut output = Array4::<f32>::zeros((1, c, 2 * h, 2 * w));
+    for ch in 0..c {
+        let plane = input.slice(s![0, ch, .., ..]);
+        for oy in 0..2 * h {
+            let (y, fy) = coord(oy);
+            for ox in 0..2 * w {
+                let (x, fx) = coord(ox);
+                output[[0, ch, oy, ox]] = (1.0 - fy) * (1.0 - fx) * get(&plane, y, x)
+                    + (1.0 - fy) * fx * get(&plane, y, x + 1)
+                    + fy * (1.0 - fx) * get(&plane, y + 1, x)
+                    + fy * fx * get(&plane, y + 1, x + 1);
+            }
+        }
+    }
+    Ok(output.into_tensor())
+}
+
+/// Pooling of each region of interest on a 2x2 grid of bins, rounded
+/// outwards to whole pixels.
+fn roi_pool(input: &Tensor, max: bool) -> TractResult<Tensor> {
+    let input = view4(input)?;
+    let rois = rois();
+    let rois = rois.to_array_view::<f32>()?;
+    let mut output = Array4::<f32>::zeros((3, 4, 2, 2));
+    let bin = |start: f32, end: f32, ix: usize| {
+        let step = (end - start) / 2.0;
+        let from = (start + ix as f32 * step).floor().max(0.0) as usize;
+        let to = (start + (ix + 1) as f32 * step).ceil().min(8.0) as usize;
+        from..to
+    };
+    for r in 0..3 {
+        for ch in 0..4 {
+            for i in 0..2 {
+                for j in 0..2 {
+                    let ys = bin(rois[[r, 0]], rois[[r, 2]], i);
+                    let xs = bin(rois[[r, 1]], rois[[r, 3]], j);
+                    let window = input.slice(s![0, ch, ys, xs]);
+                    output[[r, ch, i, j]] = if max {
+                        window.iter().cloned().fold(std::f32::MIN, f32::max)
+                    } else {
+                        window.sum() / window.len() as f32
+                    };
+                }
+            }
+        }
+    }
+    Ok(output.into_tensor())
+}
+
+/// Bilinear resampling of each region of interest to `size` x `size`,
+/// sampling cell centers, clamped to the input.
+fn resample_rois(input: &Tensor, size: usize) -> TractResult<Array4<f32>> {
+    let input = view4(input)?;
+    let rois = rois();
+    let rois = rois.to_array_view::<f32>()?;
+    let mut output = Array4::<f32>::zeros((3, 4, size, size));
+    let coord = |start: f32, end: f32, ix: usize| {
+        let coord =
+            (start + (ix as f32 + 0.5) * (end - start) / size as f32 - 0.5).max(0.0).min(7.0);
+        let low = coord.floor() as usize;
+        (low, (low + 1).min(7), coord - low as f32)
+    };
+    for r in 0..3 {
+        for ch in 0..4 {
+            let plane = input.slice(s![0, ch, .., ..]);
+            for i in 0..size {
+                let (y0, y1, fy) = coord(rois[[r, 0]], rois[[r, 2]], i);
+                for j in 0..size {
+                    let (x0, x1, fx) = coord(rois[[r, 1]], rois[[r, 3]], j);
+                    output[[r, ch, i, j]] = (1.0 - fy) * (1.0 - fx) * plane[[y0, x0]]
+                        + (1.0 - fy) * fx * plane[[y0, x1]]
+                        + fy * (1.0 - fx) * plane[[y1, x0]]
+                        + fy * fx * plane[[y1, x1]];
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Regions resampled to 4x4, then pooled by 2x2 windows.
+fn roi_align(input: &Tensor, max: bool) -> TractResult<Tensor> {
+    let resampled = resample_rois(input, 4)?;
+    let mut output = Array4::<f32>::zeros((3, 4, 2, 2));
+    for ((r, ch, i, j), v) in output.indexed_iter_mut() {
+        let window = resampled.slice(s![r, ch, 2 * i..2 * i + 2, 2 * j..2 * j + 2]);
+        *v = if max {
+            window.iter().cloned().fold(std::f32::MIN, f32::max)
+        } else {
+            window.sum() / 4.0
+        };
+    }
+    Ok(output.into_tensor())
+}
+
+/// Max pooling by `size` windows with a stride of 2 over the zero padded
+/// input, then the maxima added back at their (first) position in the input.
+fn max_pool_and_restore(input: &Tensor, size: usize, pad: usize) -> TractResult<(Tensor, Tensor)> {
+    let input = view4(input)?;
+    let get = |c: usize, y: isize, x: isize| {
+        if y < 0 || x < 0 || y >= 8 || x >= 8 {
+            0.0
+        } else {
+            input[[0, c, y as usize, x as usize]]
+        }
+    };
+    let mut output = Array4::<f32>::zeros((1, 4, 4, 4));
+    let mut restored = Array4::<f32>::zeros((1, 4, 8, 8));
+    for ((_, c, oy, ox), v) in output.indexed_iter_mut() {
+        let mut max = None;
+        for ky in 0..size {
+            for kx in 0..size {
+                let y = (2 * oy + ky) as isize - pad as isize;
+                let x = (2 * ox + kx) as isize - pad as isize;
+                if max.map(|(m, _, _)| get(c, y, x) > m).unwrap_or(true) {
+                    max = Some((get(c, y, x), y, x));
+                }
+            }
+        }
+        let (m, y, x) = max.unwrap();
+        *v = m;
+        if y >= 0 && x >= 0 && y < 8 && x < 8 {
+            restored[[0, c, y as usize, x as usize]] += m;
+        }
+    }
+    Ok((output.into_tensor(), restored.into_tensor()))
+}
+
+fn map(input: &Tensor, f: impl Fn(f32) -> f32) -> TractResult<Tensor> {
+    Ok(input.to_array_view::<f32>()?.mapv(f).into_tensor())
+}
+
+reference_test!(deconv3x3, |input, v| Ok(vec![deconv(input, &v["filter"], bias(v)?, 1, None, 1)?]));
+reference_test!(deconv3x3_stride2x2, |input, v| {
+    Ok(vec![deconv(input, &v["filter"], bias(v)?, 2, None, 1)?])
+});
+reference_test!(deconv3x3_pad0_0, |input, v| {
+    Ok(vec![deconv(input, &v["filter"], bias(v)?, 1, Some(0), 1)?])
+});
+reference_test!(deconv4x4_stride2x2_output_shape, |input, v| {
+    Ok(vec![deconv(input, &v["filter"], bias(v)?, 2, Some(1), 1)?])
+});
+reference_test!(deconv3x3_groups0, |input, v| {
+    Ok(vec![deconv(input, &v["filter"], bias(v)?, 1, None, 0)?])
+});
+reference_test!(separable_deconv3x3, |input, v| {
+    let filtered = deconv(input, &v["point_filter"], &[0.0], 1, None, 1)?;
+    Ok(vec![deconv(&filtered, &v["plane_filter"], bias(v)?, 1, None, 0)?])
+});
+reference_test!(debox3x3_normalize, |input, _| {
+    let filter = tensor0(1.0f32 / 9.0).broadcast_scalar_to_shape(&[4, 1, 3, 3])?;
+    Ok(vec![deconv(input, &filter, &[0.0], 1, None, 0)?])
+});
+reference_test!(multilinear_upsample_symmetric_replicate, |input, _| {
+    Ok(vec![upsample(input, true, true)?])
+});
+reference_test!(multilinear_upsample_symmetric_constant, |input, _| {
+    Ok(vec![upsample(input, true, false)?])
+});
+reference_test!(multilinear_upsample_asymmetric_replicate, |input, _| {
+    Ok(vec![upsample(input, false, true)?])
+});
+reference_test!(avg_roi_pool, |input, _| Ok(vec![roi_pool(input, false)?]));
+reference_test!(max_roi_pool, |input, _| Ok(vec![roi_pool(input, true)?]));
+reference_test!(roi_resample, |input, _| Ok(vec![resample_rois(input, 2)?.into_tensor()]));
+reference_test!(avg_roi_align, |input, _| Ok(vec![roi_align(input, false)?]));
+reference_test!(max_roi_align, |input, _| Ok(vec![roi_align(input, true)?]));
+reference_test!(min_max_linear_quantize, |input, _| {
+    // signed and symmetric: 254 steps over [-1, 1]
+    Ok(vec![map(input, |x| ((x.max(-1.0).min(1.0) + 1.0) * 127.0).round() / 127.0 - 1.0)?])
+});
+reference_test!(logarithmic_quantize, |input, _| {
+    // m = ceil(log2(4)) = 2, exponents clamped to [m - 15, m]
+    Ok(vec![map(input, |x| {
+        if x == 0.0 {
+            0.0
+        } else {
+            x.signum() * 2f32.powf(x.abs().log2().max(-13.0).min(2.0).round())
+        }
+    })?])
+});
+reference_test!(any_all_reduce, |input, _| {
+    let input = view4(input)?;
+    let any = Array4::from_shape_fn((1, 4, 1, 1), |(_, c, _, _)| {
+        input.slice(s![0, c, .., ..]).iter().any(|&x| x > 0.0)
+    });
+    let all = Array4::from_shape_fn((1, 1, 8, 8), |(_, _, y, x)| {
+        input.slice(s![0, .., y, x]).iter().all(|&x| x > 0.0)
+    });
+    Ok(vec![any.into_tensor(), all.into_tensor()])
+});
+reference_test!(argmax_pool, |input, _| {
+    // offsets of the first maximum of each window in the input plane
+    let input = view4(input)?;
+    let index = Array4::from_shape_fn((1, 4, 4, 4), |(_, c, y, x)| {
+        let window =
+            [(2 * y, 2 * x), (2 * y, 2 * x + 1), (2 * y + 1, 2 * x), (2 * y + 1, 2 * x + 1)];
+        let (y, x) = window.iter().cloned().fold(window[0], |m, p| {
+            if input[[0, c, p.0, p.1]] > input[[0, c, m.0, m.1]] {
+                p
+            } else {
+                m
+            }
+        });
+        (y * 8 + x) as i64
+    });
+    Ok(vec![index.into_tensor()])
+});
+reference_test!(sample_desample, |input, _| {
+    let (output, restored) = max_pool_and_restore(input, 2, 0)?;
+    let (padded_output, padded_restored) = max_pool_and_restore(input, 3, 1)?;
+    Ok(vec![output, restored, padded_output, padded_restored])
+});
+reference_test!(tile, |input, _| {
+    let input = view4(input)?;
+    Ok(vec![
+        Array4::from_shape_fn((1, 4, 16, 8), |(_, c, y, x)| input[[0, c, y % 8, x]]).into_tensor()
+    ])
+});
+
+#[test]
+fn nearest_upsample() -> TractResult<()> {
+    let (input, outputs) = run("nearest_upsample")?;
+    let input = input.to_array_view::<f32>()?;
+    let output = outputs[0].to_array_view::<f32>()?;
+    assert_eq!(output.shape(), &[1, 4, 16, 16]);
+    for ((_, c, y, x), v) in output.clone().into_dimensionality::<Ix4>()?.indexed_iter() {
+        assert_eq!(*v, input[[0, c, y / 2, x / 2]]);
+    }
+    Ok(())
+}
+
+#[test]
+fn debox2x2_stride2x2() -> TractResult<()> {
+    let (_, debox) = run("debox2x2_stride2x2")?;
+    let (_, upsample) = run("nearest_upsample")?;
+    debox[0].close_enough(&upsample[0], true)
+}
+
+#[test]
+fn nearest_downsample() -> TractResult<()> {
+    let (input, outputs) = run("nearest_downsample")?;
+    let expected = input.to_array_view::<f32>()?.slice(s![.., .., ..;2, ..;2]).to_owned();
+    outputs[0].close_enough(&expected.into_tensor(), true)
+}
+
+#[test]
+fn area_downsample() -> TractResult<()> {
+    let (input, outputs) = run("area_downsample")?;
+    let input = input.to_array_view::<f32>()?;
+    let output = outputs[0].to_array_view::<f32>()?;
+    assert_eq!(output.shape(), &[1, 4, 4, 4]);
+    for ((_, c, y, x), v) in output.clone().into_dimensionality::<Ix4>()?.indexed_iter() {
+        let window = input.slice(s![0, c, 2 * y..2 * y + 2, 2 * x..2 * x + 2]);
+        assert!((window.sum() / 4.0 - v).abs() < 1e-5);
+    }
+    Ok(())
+}
+
+#[test]
+fn multilinear_upsample_aligned_replicate() -> TractResult<()> {
+    let (input, outputs) = run("multilinear_upsample_aligned_replicate")?;
+    let input = input.to_array_view::<f32>()?;
+    let output = outputs[0].to_array_view::<f32>()?;
+    assert_eq!(output.shape(), &[1, 4, 16, 16]);
+    for c in 0..4 {
+        for &(y, x) in &[(0, 0), (0, 7), (7, 0), (7, 7)] {
+            assert!((output[[0, c, y * 15 / 7, x * 15 / 7]] - input[[0, c, y, x]]).abs() < 1e-5);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn local_response_normalization() -> TractResult<()> {
+    let (input, outputs) = run("local_response_normalization")?;
+    let input = input.to_array_view::<f32>()?;
+    let output = outputs[0].to_array_view::<f32>()?;
+    for ((_, c, y, x), v) in output.clone().into_dimensionality::<Ix4>()?.indexed_iter() {
+        let channels = c.saturating_sub(1)..(c + 2).min(4);
+        let sum: f32 = channels.map(|c| input[[0, c, y, x]].powi(2)).sum();
+        let expected = input[[0, c, y, x]] / (1.0 + 1e-4 * sum / 3.0).powf(0.75);
+        assert!((expected - v).abs() < 1e-5);
+    }
+    Ok(())
+}
+
+#[test]
+fn linear_quantize() -> TractResult<()> {
+    let (input, outputs) = run("linear_quantize")?;
+    let input = input.as_slice::<f32>()?;
+    for (x, q) in input.iter().zip(outputs[0].as_slice::<f32>()?.iter()) {
+        let steps = (q + 1.0) / 2.0 * 255.0;
+        assert!((steps - steps.round()).abs() < 1e-3);
+        assert!((x.max(-1.0).min(1.0) - q).abs() <= 1.0 / 255.0 + 1e-6);
+    }
+    Ok(())
+}
+
+#[test]
+fn zero_point_linear_quantize() -> TractResult<()> {
+    let (input, outputs) = run("zero_point_linear_quantize")?;
+    let input = input.as_slice::<f32>()?;
+    for (x, q) in input.iter().zip(outputs[0].as_slice::<f32>()?.iter()) {
+        let expected = ((x / 0.0625).round() + 128.0).max(0.0).min(255.0) - 128.0;
+        assert_eq!(*q, expected * 0.0625);
+    }
+    Ok(())
+}
+
+#[test]
+fn split() -> TractResult<()> {
+    let (input, outputs) = run("split")?;
+    let input = input.to_array_view::<f32>()?;
+    outputs[0].close_enough(&input.slice(s![.., ..1, .., ..]).to_owned().into_tensor(), false)?;
+    outputs[1].close_enough(&input.slice(s![.., 1.., .., ..]).to_owned().into_tensor(), false)
+}
+
+#[test]
+fn unstack_stack() -> TractResult<()> {
+    let (input, outputs) = run("unstack_stack")?;
+    let expected = input.to_array_view::<f32>()?.permuted_axes(&[0, 2, 3, 1][..]).to_owned();
+    assert_eq!(outputs[0].shape(), &[1, 8, 8, 4]);
+    outputs[0].close_enough(&expected.into_tensor(), false)
+}
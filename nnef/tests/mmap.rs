@@ -0,0 +1,51 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::math;
+
+fn model() -> TractResult<TypedModel> {
+    let mut model = TypedModel::default();
+    let input = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[4, 3]))?;
+    let weights: Vec<f32> = (0..12).map(|i| i as f32 / 4.0 - 1.0).collect();
+    let weights = model.add_const("weights", tensor1(&weights).into_shape(&[4, 3])?)?;
+    let indices = model.add_const("indices", tensor1(&[3i64, 1, 2, 0]))?;
+    let product = model.wire_node("mul", math::mul::bin_typed(), &[input, weights])?[0];
+    let indices =
+        model.wire_node("cast", tract_core::ops::cast::cast(f32::datum_type()), &[indices])?[0];
+    let indices = model.wire_node("indices_2d", AxisOp::Add(1), &[indices])?[0];
+    model.wire_node("add", math::add::bin_typed(), &[product, indices])?;
+    model.auto_outputs()?;
+    Ok(model)
+}
+
+fn check(model: &TypedModel, reloaded: TypedModel) -> TractResult<()> {
+    let input: Vec<f32> = (0..12).map(|i| i as f32).collect();
+    let input = tensor1(&input).into_shape(&[4, 3])?;
+    let expected = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input))?;
+    expected[0].close_enough(&found[0], false)
+}
+
+fn scratch(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("tract-nnef-mmap-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn mmap_directory() -> TractResult<()> {
+    let model = model()?;
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let dir = scratch("dir");
+    nnef.write_to_dir(&model, &dir)?;
+    let reloaded = nnef.with_mmap(true).model_for_path(&dir);
+    std::fs::remove_dir_all(&dir)?;
+    check(&model, reloaded?)
+}
+
+#[test]
+fn mmap_tar() -> TractResult<()> {
+    let model = model()?;
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let tar = scratch("model.nnef.tar");
+    nnef.write_to_tar(&model, std::fs::File::create(&tar)?)?;
+    let reloaded = nnef.with_mmap(true).model_for_path(&tar);
+    std::fs::remove_file(&tar)?;
+    check(&model, reloaded?)
+}
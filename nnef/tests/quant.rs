@@ -0,0 +1,115 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::math::add;
+
+fn quantized_model() -> TractResult<TypedModel> {
+    let mut model = TypedModel::default();
+    let input_quant = QuantFormat::ZeroPointLinear {
+        zero_point: 128,
+        scale: 0.5,
+        bits: 8,
+        signed: false,
+        symmetric: false,
+    };
+    let input = model.add_source(
+        "input",
+        TypedFact::dt_shape(u8::datum_type(), &[3]).with_quant(Some(input_quant)),
+    )?;
+    let weights = model.add_const("weights", tensor1(&[1u8, 2, 3]))?;
+    let fact = model.outlet_fact(weights)?.clone();
+    model.set_outlet_fact(
+        weights,
+        fact.with_quant(Some(QuantFormat::Linear { min: -1.0, max: 1.0, bits: 8 })),
+    )?;
+    model.wire_node("output", add::bin_typed(), &[input, weights])?;
+    model.auto_outputs()?;
+    Ok(model)
+}
+
+#[test]
+fn quantized_model_round_trip() -> TractResult<()> {
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let model = quantized_model()?;
+    let mut buffer = vec![];
+    nnef.write_to_tar(&model, &mut buffer)?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+
+    let input = reloaded.input_outlets()?[0];
+    assert_eq!(reloaded.outlet_fact(input)?.datum_type, u8::datum_type());
+    assert_eq!(
+        reloaded.outlet_fact(input)?.quant,
+        model.outlet_fact(model.input_outlets()?[0])?.quant
+    );
+    let weights = reloaded.node_by_name("weights")?;
+    assert_eq!(weights.outputs[0].fact.datum_type, u8::datum_type());
+    assert_eq!(
+        weights.outputs[0].fact.quant,
+        Some(QuantFormat::Linear { min: -1.0, max: 1.0, bits: 8 })
+    );
+
+    let result = reloaded.into_runnable()?.run(tvec!(tensor1(&[10u8, 20, 30])))?;
+    assert_eq!(*result[0], tensor1(&[11u8, 22, 33]));
+    Ok(())
+}
+
+#[test]
+fn graph_quant_is_written() -> TractResult<()> {
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let proto = tract_nnef::ser::to_proto_model(&nnef, &quantized_model()?)?;
+    let quantization = proto.quantization.unwrap();
+    assert_eq!(quantization.len(), 2);
+    Ok(())
+}
+
+fn quantized_conv() -> TractResult<TypedModel> {
+    use tract_nnef::tract_core::ops::cnn::*;
+    use tract_nnef::tract_core::ops::nn::DataFormat;
+    use tract_nnef::tract_core::ops::quant::QParams;
+    let mut model = TypedModel::default();
+    let input = model.add_source("input", TypedFact::dt_shape(u8::datum_type(), &[1, 2, 2, 2]))?;
+    let kernel = tensor1(&[1u8, 2, 3, 4, 5, 6]).into_shape(&[3, 2, 1, 1])?.into_arc_tensor();
+    let q_params = QParams::new(u8::datum_type())
+        .with_zero_point_a(&rctensor0(1u8))
+        .with_zero_point_b(&rctensor0(2u8))
+        .with_zero_point_c(&rctensor0(3u8))
+        .with_scale_factor(0.25);
+    let conv = ConvUnary::new(
+        PoolSpec::new(DataFormat::NCHW, tvec!(1, 1), PaddingSpec::Valid, None, None, Some(3)),
+        KernelFormat::OIHW,
+        kernel,
+        1,
+        Some(rctensor1(&[4i32, 0, -4])),
+        Some(q_params),
+    );
+    model.wire_node("conv", conv, &[input])?;
+    model.auto_outputs()?;
+    Ok(model)
+}
+
+#[test]
+fn quantized_conv_round_trip() -> TractResult<()> {
+    use tract_nnef::tract_core::ops::cnn::ConvUnary;
+    let nnef = tract_nnef::nnef().with_tract_core();
+    let model = quantized_conv()?;
+    let mut buffer = vec![];
+    nnef.write_to_tar(&model, &mut buffer)?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+
+    let conv = |m: &TypedModel| -> TractResult<ConvUnary> {
+        Ok(m.node_by_name("conv")?.op_as::<ConvUnary>().unwrap().clone())
+    };
+    let (expected, found) = (conv(&model)?, conv(&reloaded)?);
+    assert_eq!(found.kernel, expected.kernel);
+    assert_eq!(found.bias, expected.bias);
+    let (expected, found) = (expected.q_params.unwrap(), found.q_params.unwrap());
+    assert_eq!(found.c_datum_type, expected.c_datum_type);
+    assert_eq!(found.zero_point_a, expected.zero_point_a);
+    assert_eq!(found.zero_point_b, expected.zero_point_b);
+    assert_eq!(found.zero_point_c, expected.zero_point_c);
+    assert_eq!(found.scale_factor, expected.scale_factor);
+
+    let input = tensor1(&[2u8, 6, 10, 14, 3, 5, 7, 9]).into_shape(&[1, 2, 2, 2])?;
+    let expected = model.into_runnable()?.run(tvec!(input.clone()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input))?;
+    assert_eq!(found, expected);
+    Ok(())
+}
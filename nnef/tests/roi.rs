@@ -0,0 +1,41 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_core::ops::cnn::{RoiAlign, RoiPool, RoiPoolMode};
+
+fn roi_model(op: impl Into<Box<dyn TypedOp>>) -> TractResult<TypedModel> {
+    let mut model = TypedModel::default();
+    let input = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[2, 2, 8, 8]))?;
+    let rois = model.add_source("rois", TypedFact::dt_shape(f32::datum_type(), &[2, 4]))?;
+    let batch_index = model.add_const("batch_index", tensor1(&[1i64, 0]))?;
+    let output = model.wire_node("roi", op, &[input, rois, batch_index])?;
+    model.set_output_outlets(&output)?;
+    Ok(model)
+}
+
+fn round_trip(model: TypedModel) -> TractResult<()> {
+    let nnef = tract_nnef::nnef();
+    let mut buffer = vec![];
+    nnef.write_to_tar(&model, &mut buffer)?;
+    let reloaded = nnef.model_for_read(&mut &*buffer)?;
+
+    let values: Vec<f32> = (0..256).map(|i| ((i * 7) % 19) as f32 / 4.0).collect();
+    let input = tensor1(&values).into_shape(&[2, 2, 8, 8])?;
+    let rois = tensor2(&[[0f32, 0., 4., 4.], [2., 1., 7., 6.]]);
+    let expected = model.into_runnable()?.run(tvec!(input.clone(), rois.clone()))?;
+    let found = reloaded.into_runnable()?.run(tvec!(input, rois))?;
+    expected[0].close_enough(&found[0], true)
+}
+
+#[test]
+fn max_roi_pool_round_trip() -> TractResult<()> {
+    round_trip(roi_model(RoiPool::new(tvec!(2, 2), RoiPoolMode::Max))?)
+}
+
+#[test]
+fn avg_roi_align_round_trip() -> TractResult<()> {
+    round_trip(roi_model(RoiAlign::new(tvec!(2, 2), Some(2), RoiPoolMode::Avg, true))?)
+}
+
+#[test]
+fn max_roi_align_round_trip() -> TractResult<()> {
+    round_trip(roi_model(RoiAlign::new(tvec!(2, 2), Some(2), RoiPoolMode::Max, true))?)
+}
@@ -1,31 +1,32 @@
-use tract_nnef::internal::*;
+pub use tract_nnef::tract_core::ops::math::{erf, Erf};
 
-tract_core::element_wise!(erf, Erf,
-    [f32] => |_, xs| {
-        xs.iter_mut().for_each(|x| *x = erf_f32(*x));
-        Ok(())
-    };
-    prefix: "onnx."
-);
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::WithOnnx;
+    use tract_nnef::internal::*;
 
-#[allow(non_upper_case_globals)]
-fn erf_f32(x: f32) -> f32 {
-    const a1: f32 = 0.0705230784;
-    const a2: f32 = 0.0422820123;
-    const a3: f32 = 0.0092705272;
-    const a4: f32 = 0.0001520143;
-    const a5: f32 = 0.0002765672;
-    const a6: f32 = 0.0000430638;
+    #[test]
+    fn dump_as_core_and_load_legacy_name() -> TractResult<()> {
+        let nnef = tract_nnef::nnef().with_onnx();
+        let mut model = TypedModel::default();
+        let source = model.add_source("input", TypedFact::dt_shape(f32::datum_type(), &[3]))?;
+        let erf = model.wire_node("erf", erf(), &[source])?;
+        model.set_output_outlets(&erf)?;
 
-    let signum = x.signum();
-    let x = x.abs();
-    let y = a6 * x;
-    let y = (a5 + y) * x;
-    let y = (a4 + y) * x;
-    let y = (a3 + y) * x;
-    let y = (a2 + y) * x;
-    let y = (a1 + y) * x;
-    let y = 1.0 - (y + 1.0).powi(16).recip();
+        let mut proto = tract_nnef::ser::to_proto_model(&nnef, &model)?;
+        let mut text = vec![];
+        tract_nnef::ast::dump::Dumper::new(&mut text).document(&proto.doc)?;
+        let text = String::from_utf8(text)?;
+        assert!(text.contains("tract_core_erf"));
 
-    y.copysign(signum)
+        let legacy = text.replace("tract_core_erf", "tract_onnx_erf");
+        proto.doc = tract_nnef::ast::parse::parse_document(&legacy)?;
+        proto.doc.extension.push(vec!["tract_registry".to_string(), "tract_onnx".to_string()]);
+        let reloaded = nnef.model_for_proto_model(&proto)?;
+        let input = tvec!(tensor1(&[-1f32, 0., 0.5]));
+        let expected = model.into_runnable()?.run(input.clone())?;
+        let found = reloaded.into_runnable()?.run(input)?;
+        expected[0].close_enough(&found[0], false)
+    }
 }
@@ -25,6 +25,7 @@ impl WithOnnx for tract_nnef::framework::Nnef {
 
 fn onnx_opl_registry() -> Registry {
     let mut registry: Registry = Registry::new("tract_onnx");
+    // Erf is dumped by tract_core now, this keeps older models loading
     registry.register_unit_element_wise("tract_onnx_erf", &erf::Erf {});
     registry.register_element_wise(
         "tract_onnx_isinf",
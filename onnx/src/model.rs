@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use tract_hir::internal::*;
 
 use crate::pb;
+use crate::tensor::translate_inference_fact;
 use prost::Message;
 
 pub fn optional_inputs(pb: &pb::NodeProto) -> impl Iterator<Item = Option<usize>> + '_ {
@@ -70,11 +71,10 @@ impl<'a> ParsingContext<'a> {
                 outlets_by_name.insert(input.name.to_owned(), id);
             } else {
                 let fact = input.r#type.as_ref().unwrap().value.as_ref().unwrap();
-                #[allow(irrefutable_let_patterns)]
                 let fact: InferenceFact = if let pb::type_proto::Value::TensorType(fact) = fact {
-                    fact.try_into()?
+                    translate_inference_fact(fact, Some(&mut model.symbol_table))?
                 } else {
-                    bail!("Can not parse tensor type");
+                    bail!("Input {}: sequence and optional inputs are not supported", input.name);
                 };
                 trace!("Input: {} is a source ({:?})", input.name, fact);
                 let id = model.add_source(&*input.name, fact)?;
@@ -151,11 +151,12 @@ impl<'a> ParsingContext<'a> {
         let mut outputs = vec![];
         for output in graph.output.iter() {
             let fact = output.r#type.as_ref().unwrap().value.as_ref().unwrap();
-            let pb::type_proto::Value::TensorType(fact) = fact;
             let outlet = outlets_by_name[&*output.name];
             outputs.push(outlet);
             model.set_outlet_label(outlet, output.name.clone())?;
-            model.set_outlet_fact(outlet, fact.try_into()?)?;
+            if let pb::type_proto::Value::TensorType(fact) = fact {
+                model.set_outlet_fact(outlet, fact.try_into()?)?;
+            }
         }
         model.set_output_outlets(&outputs)?;
         let result = ParseResult { model, unresolved_inputs, outlets_by_name };
@@ -110,4 +110,64 @@ impl InferenceRulesOp for Compress {
     }
 
     as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_hir::tract_core::ops::array::{Gather, NonZero};
+        let count = target.symbol_table.new_symbol("compressed");
+        let indices = target.wire_node(
+            format!("{}.indices", node.name),
+            NonZero::new(count),
+            &[mapping[&node.inputs[1]]],
+        )?;
+        let indices =
+            target.wire_node(format!("{}.indices.rm", node.name), AxisOp::Rm(1), &indices)?[0];
+        let mut input = mapping[&node.inputs[0]];
+        let axis = if let Some(axis) = self.axis {
+            axis
+        } else {
+            let shape = target.outlet_fact(input)?.shape.to_tvec();
+            let len = shape.iter().fold(1.to_dim(), |acc, d| acc * d);
+            input = target.wire_node(
+                format!("{}.flatten", node.name),
+                AxisOp::Reshape(0, shape, tvec!(len)),
+                &[input],
+            )?[0];
+            0
+        };
+        target.wire_node(&*node.name, Gather::new(axis), &[input, indices])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(op: Compress) -> TractResult<TVec<Arc<Tensor>>> {
+        let mut model = InferenceModel::default();
+        let input =
+            model.add_source("input", InferenceFact::dt_shape(f32::datum_type(), &[2, 3]))?;
+        let conds = model.add_source("conds", InferenceFact::dt_shape(bool::datum_type(), &[3]))?;
+        let output = model.wire_node("compress", op, &[input, conds])?;
+        model.set_output_outlets(&output)?;
+        let input = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        model.into_optimized()?.into_runnable()?.run(tvec!(input, tensor1(&[true, false, true])))
+    }
+
+    #[test]
+    fn compress_axis() -> TractResult<()> {
+        assert_eq!(*run(Compress::new(Some(1)))?[0], tensor2(&[[1f32, 3.], [4., 6.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn compress_flat() -> TractResult<()> {
+        assert_eq!(*run(Compress::new(None))?[0], tensor1(&[1f32, 3.]));
+        Ok(())
+    }
 }
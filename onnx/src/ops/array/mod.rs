@@ -2,10 +2,14 @@ mod compress;
 mod nonzero;
 mod one_hot;
 mod pad;
+mod reverse_sequence;
 mod slice;
+mod trilu;
+mod unique;
 
 use tract_hir::internal::*;
 use tract_hir::ops::array;
+use tract_hir::ops::nn::DataFormat;
 
 use crate::model::{OnnxOpRegister, ParsingContext};
 use crate::pb::*;
@@ -15,6 +19,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Concat", concat);
     reg.insert("ConstantLike", constant_like);
     reg.insert("ConstantOfShape", constant_of_shape);
+    reg.insert("DepthToSpace", depth_to_space);
     reg.insert("Expand", |_, _| Ok((expand(array::MultiBroadcastTo::default()), vec![])));
     reg.insert("EyeLike", eye_like);
     reg.insert("Flatten", flatten);
@@ -22,14 +27,19 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("NonZero", |_, _| Ok((Box::new(nonzero::NonZero), vec![])));
     reg.insert("OneHot", one_hot::one_hot);
     reg.insert("Pad", pad::pad);
+    reg.insert("Range", |_, _| Ok((expand(array::Range::default()), vec![])));
     reg.insert("Reshape", |_, _| Ok((expand(array::Reshape::default()), vec![])));
+    reg.insert("ReverseSequence", reverse_sequence::reverse_sequence);
     reg.insert("Shape", |_, _| Ok((expand(array::Shape::new(DatumType::I64)), vec![])));
     reg.insert("Size", |_, _| Ok((expand(array::Size::new(DatumType::I64)), vec![])));
     reg.insert("Transpose", transpose);
     reg.insert("Tile", |_, _| Ok((expand(array::Tile::default()), vec![])));
     reg.insert("Slice", slice::slice);
+    reg.insert("SpaceToDepth", space_to_depth);
     reg.insert("Split", split);
     reg.insert("Squeeze", squeeze);
+    reg.insert("Trilu", trilu::trilu);
+    reg.insert("Unique", unique::unique);
     reg.insert("Unsqueeze", unsqueeze);
 }
 
@@ -74,6 +84,19 @@ pub fn constant_of_shape(
     Ok((expand(array::ConstantOfShape::new(value)), vec![]))
 }
 
+pub fn depth_to_space(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let block_size = node.get_attr("blocksize")?;
+    let mode = match node.get_attr_opt("mode")?.unwrap_or("DCR") {
+        "DCR" => array::DepthToSpaceMode::DepthColumnRow,
+        "CRD" => array::DepthToSpaceMode::ColumnRowDepth,
+        mode => bail!("Unsupported DepthToSpace mode {}", mode),
+    };
+    Ok((expand(array::DepthToSpace::new(block_size, mode, DataFormat::NCHW)), vec![]))
+}
+
 pub fn eye_like(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -99,6 +122,14 @@ pub fn gather(
     Ok((Box::new(array::Gather::new(axis)), vec![]))
 }
 
+pub fn space_to_depth(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let block_size = node.get_attr("blocksize")?;
+    Ok((expand(array::SpaceToDepth::new(block_size, DataFormat::NCHW)), vec![]))
+}
+
 pub fn split(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -67,4 +67,39 @@ impl InferenceRulesOp for NonZero {
     }
 
     as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let count = target.symbol_table.new_symbol("nonzero");
+        let coords = target.wire_node(
+            format!("{}.coords", node.name),
+            tract_hir::tract_core::ops::array::NonZero::new(count),
+            &[mapping[&node.inputs[0]]],
+        )?;
+        target.wire_node(&*node.name, AxisOp::Move(1, 0), &coords)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_non_zero() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let input =
+            model.add_source("input", InferenceFact::dt_shape(f32::datum_type(), &[2, 2]))?;
+        let output = model.wire_node("nonzero", NonZero, &[input])?;
+        model.set_output_outlets(&output)?;
+        let model = model.into_optimized()?;
+        assert_eq!(model.output_fact(0)?.shape[0], 2.to_dim());
+        let output = model.into_runnable()?.run(tvec!(tensor2(&[[0f32, 1.], [2., 3.]])))?;
+        assert_eq!(*output[0], tensor2(&[[0i64, 1, 1], [1, 0, 1]]));
+        Ok(())
+    }
 }
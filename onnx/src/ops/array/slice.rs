@@ -75,9 +75,17 @@ impl Expansion for Slice1 {
                             e = d.into();
                         }
                     }
-                    let b = if b < 0 { d.bex() + TDim::from(b) } else { TDim::from(b).bex() };
-                    let e = if e < 0 { d.bex() + TDim::from(e) } else { TDim::from(e).bex() };
-                    s.equals(&outputs[0].shape[axis], e - b)
+                    let b = if b < 0 {
+                        d.clone() + TDim::from(b)
+                    } else {
+                        TDim::from(b).mini(d.clone())
+                    };
+                    let e = if e < 0 {
+                        d.clone() + TDim::from(e)
+                    } else {
+                        TDim::from(e).mini(d.clone())
+                    };
+                    s.equals(&outputs[0].shape[axis], (e - b).bex())
                 } else {
                     s.equals(&outputs[0].shape[axis], &shape[axis])
                 }
@@ -0,0 +1,78 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn trilu(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let upper = node.get_attr_opt("upper")?.unwrap_or(1i64) != 0;
+    let has_k = node.input.get(1).filter(|s| !s.is_empty()).is_some();
+    Ok((expand(Trilu::new(upper, has_k)), vec![]))
+}
+
+#[derive(Debug, Clone, new, Hash)]
+pub struct Trilu {
+    upper: bool,
+    has_k: bool,
+}
+
+impl_dyn_hash!(Trilu);
+
+impl Expansion for Trilu {
+    fn name(&self) -> Cow<str> {
+        "Trilu".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1 + self.has_k as usize)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&outputs[0].shape, &inputs[0].shape)?;
+        if self.has_k {
+            s.equals(&inputs[1].rank, 0)?;
+        }
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let k = if self.has_k {
+            inputs[1]
+        } else {
+            model.add_const(format!("{}.k", prefix), tensor0(0i64))?
+        };
+        let op = tract_hir::tract_core::ops::array::Trilu::new(self.upper);
+        model.wire_node(prefix, op, &[inputs[0], k])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lower_without_k() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let input =
+            model.add_source("input", InferenceFact::dt_shape(f32::datum_type(), &[2, 2, 3]))?;
+        let output = model.wire_node("trilu", expand(Trilu::new(false, false)), &[input])?;
+        model.set_output_outlets(&output)?;
+        let input = tensor3(&[[[1f32, 2., 3.], [4., 5., 6.]], [[7., 8., 9.], [10., 11., 12.]]]);
+        let outputs = model.into_optimized()?.into_runnable()?.run(tvec!(input))?;
+        let expected = tensor3(&[[[1f32, 0., 0.], [4., 5., 0.]], [[7., 0., 0.], [10., 11., 0.]]]);
+        assert_eq!(*outputs[0], expected);
+        Ok(())
+    }
+}
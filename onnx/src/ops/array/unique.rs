@@ -0,0 +1,105 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn unique(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?;
+    let sorted = node.get_attr_opt("sorted")?.unwrap_or(1i64) != 0;
+    let optional_outputs = crate::model::optional_outputs(node).take(4).collect();
+    Ok((expand(Unique { axis, sorted, optional_outputs }), vec![]))
+}
+
+/// ONNX Unique, whose outputs (values, indices, inverse indices and counts)
+/// are all optional.
+#[derive(Debug, Clone, Hash)]
+pub struct Unique {
+    axis: Option<i64>,
+    sorted: bool,
+    optional_outputs: TVec<Option<usize>>,
+}
+
+impl_dyn_hash!(Unique);
+
+impl Expansion for Unique {
+    fn name(&self) -> Cow<str> {
+        "Unique".into()
+    }
+
+    op_onnx!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.optional_outputs.iter().filter(|o| o.is_some()).count())
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, self.nboutputs()?)?;
+        for (ix, slot) in self.optional_outputs.iter().enumerate() {
+            if let Some(slot) = *slot {
+                if ix == 0 {
+                    s.equals(&outputs[slot].datum_type, &inputs[0].datum_type)?;
+                    if self.axis.is_some() {
+                        s.equals(&outputs[slot].rank, &inputs[0].rank)?;
+                    } else {
+                        s.equals(&outputs[slot].rank, 1)?;
+                    }
+                } else {
+                    s.equals(&outputs[slot].datum_type, i64::datum_type())?;
+                    s.equals(&outputs[slot].rank, 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = model.outlet_fact(inputs[0])?.rank() as i64;
+        let axis = self.axis.map(|axis| if axis < 0 { axis + rank } else { axis } as usize);
+        let count = model.symbol_table.new_symbol("unique");
+        let op = tract_hir::tract_core::ops::array::Unique::new(axis, self.sorted, count);
+        let wires = model.wire_node(prefix, op, inputs)?;
+        Ok(self
+            .optional_outputs
+            .iter()
+            .zip(wires.iter())
+            .filter(|(slot, _)| slot.is_some())
+            .map(|(_, wire)| *wire)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_counts() -> TractResult<()> {
+        let op = Unique {
+            axis: None,
+            sorted: false,
+            optional_outputs: tvec!(Some(0), None, None, Some(1)),
+        };
+        let mut model = InferenceModel::default();
+        let input = model.add_source("input", InferenceFact::dt_shape(i64::datum_type(), &[5]))?;
+        let outputs = model.wire_node("unique", expand(op), &[input])?;
+        model.set_output_outlets(&outputs)?;
+        let model = model.into_optimized()?;
+        let outputs = model.into_runnable()?.run(tvec!(tensor1(&[3i64, 1, 3, 3, 2])))?;
+        assert_eq!(*outputs[0], tensor1(&[3i64, 1, 2]));
+        assert_eq!(*outputs[1], tensor1(&[3i64, 1, 1]));
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use tract_hir::ops;
 use tract_hir::ops::binary::Nary;
 
 mod clip;
+mod cumsum;
 mod gemm;
 mod mat_mul_integer;
 mod pow;
@@ -28,6 +29,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Floor", |_, _| Ok((Box::new(ops::math::floor()), vec![])));
     reg.insert("Round", |_, _| Ok((Box::new(ops::math::round_half_to_even()), vec![])));
     reg.insert("Clip", clip::clip);
+    reg.insert("CumSum", cumsum::cumsum);
 
     reg.insert("Cos", |_, _| Ok((Box::new(ops::math::cos()), vec![])));
     reg.insert("Sin", |_, _| Ok((Box::new(ops::math::sin()), vec![])));
@@ -0,0 +1,60 @@
+use crate::model::ParsingContext;
+use crate::pb::*;
+use tract_hir::internal::*;
+
+pub fn cumsum(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let exclusive = node.get_attr_opt("exclusive")?.unwrap_or(0i64) != 0;
+    let reverse = node.get_attr_opt("reverse")?.unwrap_or(0i64) != 0;
+    Ok((expand(CumSum::new(exclusive, reverse)), vec![]))
+}
+
+#[derive(Debug, Clone, new, Hash)]
+pub struct CumSum {
+    exclusive: bool,
+    reverse: bool,
+}
+
+impl_dyn_hash!(CumSum);
+
+impl Expansion for CumSum {
+    fn name(&self) -> Cow<str> {
+        "CumSum".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&outputs[0].shape, &inputs[0].shape)?;
+        s.equals(&inputs[1].rank, 0)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        if let Some(ref axis) = model.outlet_fact(inputs[1])?.konst {
+            let rank = model.outlet_fact(inputs[0])?.rank() as i64;
+            let axis = axis.cast_to_scalar::<i64>()?;
+            let axis = if axis < 0 { axis + rank } else { axis } as usize;
+            let op =
+                tract_hir::tract_core::ops::array::CumSum::new(axis, self.exclusive, self.reverse);
+            model.wire_node(prefix, op, &inputs[0..1])
+        } else {
+            bail!("CumSum axis must be a constant")
+        }
+    }
+}
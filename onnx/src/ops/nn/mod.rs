@@ -10,6 +10,8 @@ mod batch_norm;
 mod dropout;
 mod instance_norm;
 mod lrn;
+mod non_max_suppression;
+mod roi;
 
 pub fn arg_max_min(
     _ctx: &ParsingContext,
@@ -54,6 +56,8 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("LogSoftmax", layer_log_soft_max);
     reg.insert("LRN", lrn::lrn);
     reg.insert("MaxPool", max_pool);
+    reg.insert("MaxRoiPool", roi::max_roi_pool);
+    reg.insert("NonMaxSuppression", non_max_suppression::non_max_suppression);
     reg.insert("ParametricSoftplus", parametric_softplus);
     reg.insert("QLinearConv", conv_qlinear);
     reg.insert("PRelu", |_, _| Ok((expand(Prelu), vec![])));
@@ -67,6 +71,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("ReduceProd", |_, node| reduce(node, nn::Reducer::Prod));
     reg.insert("ReduceSum", |_, node| reduce(node, nn::Reducer::Sum));
     reg.insert("ReduceSumSquare", |_, node| reduce(node, nn::Reducer::SumSquare));
+    reg.insert("RoiAlign", roi::roi_align);
     reg.insert("Relu", |_, _| Ok((expand(ops::activations::Clip::new(Some(0.0), None)), vec![])));
     reg.insert("ScaledTanh", scaled_tanh);
     reg.insert("Shrink", shrink);
@@ -199,11 +204,12 @@ pub fn average_pool(
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
     let kernel_shape = node.get_attr_tvec("kernel_shape")?;
     let pad = pad(node)?;
+    let dilations = dilations(node)?;
     let strides = strides(node)?;
     let count_include_pad = node.get_attr_opt("count_include_pad")?.unwrap_or(false);
     Ok((
         Box::new(cnn::SumPool::new(
-            cnn::PoolSpec::new(nn::DataFormat::NCHW, kernel_shape, pad, None, strides, None),
+            cnn::PoolSpec::new(nn::DataFormat::NCHW, kernel_shape, pad, dilations, strides, None),
             count_include_pad,
             true,
         )),
@@ -274,10 +280,11 @@ pub fn max_pool(
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
     let kernel_shape = node.get_attr_tvec("kernel_shape")?;
     let pad = pad(node)?;
+    let dilations = dilations(node)?;
     let strides = strides(node)?;
     Ok((
         Box::new(cnn::MaxPool::new(
-            cnn::PoolSpec::new(nn::DataFormat::NCHW, kernel_shape, pad, None, strides, None),
+            cnn::PoolSpec::new(nn::DataFormat::NCHW, kernel_shape, pad, dilations, strides, None),
             if node.output.len() == 2 { Some(DatumType::I64) } else { None },
         )),
         vec![],
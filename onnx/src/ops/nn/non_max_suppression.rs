@@ -0,0 +1,149 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::nn::BoxRepr;
+
+pub fn non_max_suppression(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let box_repr = match node.get_attr_opt("center_point_box")?.unwrap_or(0i64) {
+        0 => BoxRepr::TwoPoints,
+        1 => BoxRepr::CenterWidthHeight,
+        other => bail!("Unsupported center_point_box value {}", other),
+    };
+    let mut options = crate::model::optional_inputs(node).skip(2);
+    let op = NonMaxSuppression {
+        box_repr,
+        optional_max_output_input: options.next().unwrap(),
+        optional_iou_threshold_input: options.next().unwrap(),
+        optional_score_threshold_input: options.next().unwrap(),
+    };
+    Ok((expand(op), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct NonMaxSuppression {
+    box_repr: BoxRepr,
+    optional_max_output_input: Option<usize>,
+    optional_iou_threshold_input: Option<usize>,
+    optional_score_threshold_input: Option<usize>,
+}
+
+impl_dyn_hash!(NonMaxSuppression);
+
+impl NonMaxSuppression {
+    /// The scalar value of an optional input, or a constant default.
+    fn wire_scalar(
+        name: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+        input: Option<usize>,
+        default: Tensor,
+    ) -> TractResult<OutletId> {
+        if let Some(input) = input {
+            let mut wire = inputs[input];
+            while model.outlet_fact(wire)?.rank() > 0 {
+                wire = model.wire_node(format!("{}.rm", name), AxisOp::Rm(0), &[wire])?[0];
+            }
+            Ok(wire)
+        } else {
+            model.add_const(name, default)
+        }
+    }
+}
+
+impl Expansion for NonMaxSuppression {
+    fn name(&self) -> Cow<str> {
+        "NonMaxSuppression".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(
+            inputs,
+            2 + self.optional_max_output_input.is_some() as usize
+                + self.optional_iou_threshold_input.is_some() as usize
+                + self.optional_score_threshold_input.is_some() as usize,
+        )?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].rank, 3)?;
+        s.equals(&inputs[0].shape[2], 4.to_dim())?;
+        s.equals(&inputs[1].rank, 3)?;
+        s.equals(&inputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&inputs[0].shape[1], &inputs[1].shape[2])?;
+        s.equals(&outputs[0].datum_type, i64::datum_type())?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[1], 3.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        name: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let max_output = Self::wire_scalar(
+            &format!("{}.max_output_boxes_per_class", name),
+            model,
+            inputs,
+            self.optional_max_output_input,
+            tensor0(0i64),
+        )?;
+        let iou_threshold = Self::wire_scalar(
+            &format!("{}.iou_threshold", name),
+            model,
+            inputs,
+            self.optional_iou_threshold_input,
+            tensor0(0f32),
+        )?;
+        let score_threshold = Self::wire_scalar(
+            &format!("{}.score_threshold", name),
+            model,
+            inputs,
+            self.optional_score_threshold_input,
+            tensor0(std::f32::NEG_INFINITY),
+        )?;
+        let num_selected = model.symbol_table.new_symbol("selected");
+        model.wire_node(
+            name,
+            tract_hir::ops::nn::NonMaxSuppression::new(self.box_repr, num_selected),
+            &[inputs[0], inputs[1], max_output, iou_threshold, score_threshold],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_thresholds() -> TractResult<()> {
+        let op = NonMaxSuppression {
+            box_repr: BoxRepr::TwoPoints,
+            optional_max_output_input: Some(2),
+            optional_iou_threshold_input: None,
+            optional_score_threshold_input: None,
+        };
+        let boxes = tensor3(&[[[0f32, 0., 1., 1.], [0., 1., 1., 2.], [0., 0., 1., 1.]]]);
+        let scores = tensor3(&[[[0.5f32, 0.7, 0.9], [0.1, 0.2, 0.3]]]);
+        let mut model = InferenceModel::default();
+        let boxes_source =
+            model.add_source("boxes", InferenceFact::dt_shape(f32::datum_type(), boxes.shape()))?;
+        let scores_source = model
+            .add_source("scores", InferenceFact::dt_shape(f32::datum_type(), scores.shape()))?;
+        let max = model.add_const("max", rctensor1(&[5i64]))?;
+        let output = model.wire_node("nms", expand(op), &[boxes_source, scores_source, max])?;
+        model.set_output_outlets(&output)?;
+        let outputs = model.into_optimized()?.into_runnable()?.run(tvec!(boxes, scores))?;
+        assert_eq!(*outputs[0], tensor2(&[[0i64, 0, 2], [0, 0, 1], [0, 1, 2], [0, 1, 1]]));
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::cnn::{RoiPool, RoiPoolMode};
+
+pub fn roi_align(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let mode = match node.get_attr_opt("mode")?.unwrap_or("avg") {
+        "avg" => RoiPoolMode::Avg,
+        "max" => RoiPoolMode::Max,
+        mode => bail!("Unsupported RoiAlign mode {}", mode),
+    };
+    let output_height = node.get_attr_opt("output_height")?.unwrap_or(1);
+    let output_width = node.get_attr_opt("output_width")?.unwrap_or(1);
+    let sampling_ratio = node.get_attr_opt("sampling_ratio")?.unwrap_or(0usize);
+    let spatial_scale = node.get_attr_opt("spatial_scale")?.unwrap_or(1.0);
+    let default_transformation =
+        if ctx.onnx_operator_set_version >= 16 { "half_pixel" } else { "output_half_pixel" };
+    let aligned = match node
+        .get_attr_opt("coordinate_transformation_mode")?
+        .unwrap_or(default_transformation)
+    {
+        "half_pixel" => true,
+        "output_half_pixel" => false,
+        mode => bail!("Unsupported RoiAlign coordinate transformation mode {}", mode),
+    };
+    let op = tract_hir::ops::cnn::RoiAlign::new(
+        tvec!(output_height, output_width),
+        Some(sampling_ratio).filter(|&r| r > 0),
+        mode,
+        aligned,
+    );
+    Ok((expand(RoiAlign::new(op, spatial_scale)), vec![]))
+}
+
+pub fn max_roi_pool(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let pooled_shape = node.get_attr_tvec("pooled_shape")?;
+    let spatial_scale = node.get_attr_opt("spatial_scale")?.unwrap_or(1.0);
+    Ok((expand(MaxRoiPool::new(pooled_shape, spatial_scale)), vec![]))
+}
+
+/// Scale `(x1, y1, x2, y2)` regions from the input image to the feature map,
+/// and swap them to the `(y1, x1, y2, x2)` order of tract.
+fn wire_regions(
+    name: &str,
+    model: &mut TypedModel,
+    rois: OutletId,
+    spatial_scale: f32,
+) -> TractResult<OutletId> {
+    let dt = model.outlet_fact(rois)?.datum_type;
+    let scale = tensor0(spatial_scale).cast_to_dt(dt)?.into_owned().broadcast_into_rank(2)?;
+    let scaled = model.wire_node(
+        format!("{}.scale", name),
+        tract_hir::ops::math::mul::unary(scale.into_arc_tensor()),
+        &[rois],
+    )?[0];
+    let order = model.add_const(format!("{}.yx-order", name), tensor1(&[1i64, 0, 3, 2]))?;
+    Ok(model.wire_node(
+        format!("{}.yx", name),
+        tract_hir::ops::array::Gather::new(1),
+        &[scaled, order],
+    )?[0])
+}
+
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+struct RoiAlign {
+    op: tract_hir::ops::cnn::RoiAlign,
+    #[educe(Hash(method = "hash_f32"))]
+    spatial_scale: f32,
+}
+
+impl_dyn_hash!(RoiAlign);
+
+impl Expansion for RoiAlign {
+    fn name(&self) -> Cow<str> {
+        "RoiAlign".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 3)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 2)?;
+        s.equals(&inputs[1].shape[1], 4.to_dim())?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+        s.equals(&outputs[0].shape[2], self.op.output_size[0].to_dim())?;
+        s.equals(&outputs[0].shape[3], self.op.output_size[1].to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        name: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let regions = wire_regions(name, model, inputs[1], self.spatial_scale)?;
+        model.wire_node(name, self.op.clone(), &[inputs[0], regions, inputs[2]])
+    }
+}
+
+/// ONNX MaxRoiPool, with regions given as `[r, 5]` `(batch, x1, y1, x2, y2)`
+/// rows. Region corners are rounded to the nearest feature map pixel and
+/// both included.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+struct MaxRoiPool {
+    pooled_shape: TVec<usize>,
+    #[educe(Hash(method = "hash_f32"))]
+    spatial_scale: f32,
+}
+
+impl_dyn_hash!(MaxRoiPool);
+
+impl Expansion for MaxRoiPool {
+    fn name(&self) -> Cow<str> {
+        "MaxRoiPool".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        if self.pooled_shape.len() != 2 {
+            bail!("Expected a 2D pooled shape, got {:?}", self.pooled_shape);
+        }
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 2)?;
+        s.equals(&inputs[1].shape[1], 5.to_dim())?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+        s.equals(&outputs[0].shape[2], self.pooled_shape[0].to_dim())?;
+        s.equals(&outputs[0].shape[3], self.pooled_shape[1].to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        name: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_hir::ops::array::{Slice, TypedConcat};
+        use tract_hir::ops::math;
+        let rois = inputs[1];
+        let batch = model.wire_node(format!("{}.batch", name), Slice::new(1, 0, 1), &[rois])?[0];
+        let batch = model.wire_node(format!("{}.batch.rm", name), AxisOp::Rm(1), &[batch])?[0];
+        let batch = model.wire_node(
+            format!("{}.batch.cast", name),
+            tract_hir::ops::cast(i64::datum_type()),
+            &[batch],
+        )?[0];
+        let coords = model.wire_node(format!("{}.coords", name), Slice::new(1, 1, 5), &[rois])?[0];
+        let regions = wire_regions(name, model, coords, self.spatial_scale)?;
+        let regions = model.wire_node(format!("{}.round", name), math::round(), &[regions])?[0];
+        let starts =
+            model.wire_node(format!("{}.starts", name), Slice::new(1, 0, 2), &[regions])?[0];
+        let ends = model.wire_node(format!("{}.ends", name), Slice::new(1, 2, 4), &[regions])?[0];
+        let ends = model.wire_node(
+            format!("{}.ends.max", name),
+            math::max::bin_typed(),
+            &[ends, starts],
+        )?[0];
+        let dt = model.outlet_fact(ends)?.datum_type;
+        let one = tensor0(1f32).cast_to_dt(dt)?.into_owned().broadcast_into_rank(2)?;
+        let ends = model.wire_node(
+            format!("{}.ends.inclusive", name),
+            math::add::unary(one.into_arc_tensor()),
+            &[ends],
+        )?[0];
+        let regions = model.wire_node(
+            format!("{}.regions", name),
+            TypedConcat::concat_vars(1, 2),
+            &[starts, ends],
+        )?[0];
+        model.wire_node(
+            name,
+            RoiPool::new(self.pooled_shape.clone(), RoiPoolMode::Max),
+            &[inputs[0], regions, batch],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> Tensor {
+        let data: Vec<f32> = (0..16).map(|x| x as f32).collect();
+        tensor1(&data).into_shape(&[1, 1, 4, 4]).unwrap()
+    }
+
+    fn run(op: Box<dyn InferenceOp>, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        let mut model = InferenceModel::default();
+        let sources = inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, t)| {
+                model.add_source(
+                    format!("input-{}", ix),
+                    InferenceFact::dt_shape(t.datum_type(), t.shape()),
+                )
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        let output = model.wire_node("op", op, &sources)?;
+        model.set_output_outlets(&output)?;
+        model.into_optimized()?.into_runnable()?.run(inputs)
+    }
+
+    #[test]
+    fn max_roi_pool() -> TractResult<()> {
+        let op = expand(MaxRoiPool::new(tvec!(2, 2), 0.5));
+        let rois = tensor2(&[[0f32, 0., 0., 6., 6.], [0., 2., 2., 4., 4.]]);
+        let output = run(op, tvec!(image(), rois))?;
+        assert_eq!(*output[0], tensor4(&[[[[5f32, 7.], [13., 15.]]], [[[5f32, 6.], [9., 10.]]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn roi_align() -> TractResult<()> {
+        let op = tract_hir::ops::cnn::RoiAlign::new(tvec!(1, 2), Some(1), RoiPoolMode::Avg, true);
+        let op = expand(RoiAlign::new(op, 1.0));
+        let rois = tensor2(&[[0f32, 1., 4., 3.]]);
+        let output = run(op, tvec!(image(), rois, tensor1(&[0i64])))?;
+        assert_eq!(*output[0], tensor4(&[[[[6.5f32, 8.5]]]]));
+        Ok(())
+    }
+}
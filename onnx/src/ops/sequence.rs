@@ -0,0 +1,129 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_hir::ops::sequence::SequenceOp;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("ConcatFromSequence", concat_from_sequence);
+    reg.insert("Optional", |_, _| Ok((Box::new(SequenceOp::Construct), vec![])));
+    reg.insert("OptionalGetElement", |_, _| Ok((Box::new(SequenceOp::GetElement), vec![])));
+    reg.insert("OptionalHasElement", |_, _| Ok((Box::new(SequenceOp::HasElement), vec![])));
+    reg.insert("SequenceAt", |_, _| Ok((Box::new(SequenceOp::At), vec![])));
+    reg.insert("SequenceConstruct", |_, _| Ok((Box::new(SequenceOp::Construct), vec![])));
+    reg.insert("SequenceEmpty", |_, _| Ok((Box::new(SequenceOp::Construct), vec![])));
+    reg.insert("SequenceErase", |_, _| Ok((Box::new(SequenceOp::Erase), vec![])));
+    reg.insert("SequenceInsert", |_, _| Ok((Box::new(SequenceOp::Insert), vec![])));
+    reg.insert("SequenceLength", |_, _| Ok((Box::new(SequenceOp::Length), vec![])));
+    reg.insert("SplitToSequence", split_to_sequence);
+}
+
+fn concat_from_sequence(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr("axis")?;
+    let new_axis = node.get_attr_opt("new_axis")?.unwrap_or(0i64) == 1;
+    Ok((Box::new(SequenceOp::Concat { axis, new_axis }), vec![]))
+}
+
+fn split_to_sequence(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    let keep_dims = node.get_attr_opt("keepdims")?.unwrap_or(1i64) == 1;
+    Ok((Box::new(SequenceOp::Split { axis, keep_dims }), vec![]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use attribute_proto::AttributeType;
+    use tensor_shape_proto::dimension;
+    use type_proto::Value;
+
+    fn node(op_type: &str, inputs: &[&str], output: &str, ints: &[(&str, i64)]) -> NodeProto {
+        NodeProto {
+            op_type: op_type.to_string(),
+            input: inputs.iter().map(|s| s.to_string()).collect(),
+            output: vec![output.to_string()],
+            attribute: ints
+                .iter()
+                .map(|(name, i)| AttributeProto {
+                    name: name.to_string(),
+                    r#type: AttributeType::Int as i32,
+                    i: *i,
+                    ..AttributeProto::default()
+                })
+                .collect(),
+            ..NodeProto::default()
+        }
+    }
+
+    fn tensor_type(name: &str, dt: tensor_proto::DataType, shape: &[i64]) -> ValueInfoProto {
+        let dim = shape
+            .iter()
+            .map(|d| tensor_shape_proto::Dimension {
+                value: Some(dimension::Value::DimValue(*d)),
+                ..tensor_shape_proto::Dimension::default()
+            })
+            .collect();
+        let tensor =
+            type_proto::Tensor { elem_type: dt as i32, shape: Some(TensorShapeProto { dim }) };
+        ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(TypeProto {
+                value: Some(Value::TensorType(tensor)),
+                ..TypeProto::default()
+            }),
+            ..ValueInfoProto::default()
+        }
+    }
+
+    #[test]
+    fn sequences_and_optionals() -> TractResult<()> {
+        use tensor_proto::DataType;
+        let graph = GraphProto {
+            node: vec![
+                node("SplitToSequence", &["x"], "columns", &[("axis", 1), ("keepdims", 0)]),
+                node("SequenceLength", &["columns"], "length", &[]),
+                node("SequenceAt", &["columns", "one"], "second", &[]),
+                node("SequenceErase", &["columns"], "erased", &[]),
+                node("SequenceInsert", &["erased", "second", "one"], "inserted", &[]),
+                node(
+                    "ConcatFromSequence",
+                    &["inserted"],
+                    "stacked",
+                    &[("axis", 0), ("new_axis", 1)],
+                ),
+                node("Optional", &["x"], "optional", &[]),
+                node("OptionalHasElement", &["optional"], "has_element", &[]),
+            ],
+            initializer: vec![TensorProto {
+                name: "one".to_string(),
+                data_type: DataType::Int64 as i32,
+                int64_data: vec![1],
+                ..TensorProto::default()
+            }],
+            input: vec![tensor_type("x", DataType::Float, &[2, 3])],
+            output: vec![
+                tensor_type("length", DataType::Int64, &[]),
+                tensor_type("stacked", DataType::Float, &[3, 2]),
+                tensor_type("has_element", DataType::Bool, &[]),
+            ],
+            ..GraphProto::default()
+        };
+        let proto = ModelProto {
+            opset_import: vec![OperatorSetIdProto { domain: "".to_string(), version: 12 }],
+            graph: Some(graph),
+            ..ModelProto::default()
+        };
+        let model = crate::onnx().model_for_proto_model(&proto)?.into_optimized()?;
+        let outputs =
+            model.into_runnable()?.run(tvec!(tensor2(&[[1f32, 2., 3.], [4., 5., 6.]])))?;
+        assert_eq!(*outputs[0], tensor0(3i64));
+        assert_eq!(*outputs[1], tensor2(&[[1f32, 4.], [2., 5.], [2., 5.]]));
+        assert_eq!(*outputs[2], tensor0(true));
+        Ok(())
+    }
+}
@@ -26,28 +26,42 @@ impl TryFrom<DataType> for DatumType {
     }
 }
 
+/// Translate an ONNX tensor type to an InferenceFact. If a symbol table is
+/// given, `dim_param` dimensions are mapped to the symbols of the same name,
+/// otherwise they are left unknown.
+pub fn translate_inference_fact(
+    t: &type_proto::Tensor,
+    mut symbol_table: Option<&mut SymbolTable>,
+) -> TractResult<InferenceFact> {
+    let mut fact = InferenceFact::default();
+    fact = fact.with_datum_type(DataType::from_i32(t.elem_type).unwrap().try_into()?);
+    if let Some(shape) = &t.shape {
+        let shape: TVec<DimFact> = shape
+            .dim
+            .iter()
+            .map(|d| match &d.value {
+                Some(tensor_shape_proto::dimension::Value::DimValue(v)) if *v > 0 => {
+                    DimFact::from(v.to_dim())
+                }
+                Some(tensor_shape_proto::dimension::Value::DimParam(name)) if name != "" => {
+                    if let Some(table) = symbol_table.as_mut() {
+                        DimFact::from(TDim::from(table.sym(name)))
+                    } else {
+                        DimFact::default()
+                    }
+                }
+                _ => DimFact::default(),
+            })
+            .collect();
+        fact = fact.with_shape(ShapeFactoid::closed(shape));
+    }
+    Ok(fact)
+}
+
 impl<'a> TryFrom<&'a type_proto::Tensor> for InferenceFact {
     type Error = TractError;
     fn try_from(t: &'a type_proto::Tensor) -> TractResult<InferenceFact> {
-        let mut fact = InferenceFact::default();
-        fact = fact.with_datum_type(DataType::from_i32(t.elem_type).unwrap().try_into()?);
-        if let Some(shape) = &t.shape {
-            let shape: TVec<DimFact> = shape
-                .dim
-                .iter()
-                .map(|d| {
-                    let mut fact = DimFact::default();
-                    if let Some(tensor_shape_proto::dimension::Value::DimValue(v)) = d.value {
-                        if v > 0 {
-                            fact = DimFact::from(v.to_dim())
-                        }
-                    }
-                    fact
-                })
-                .collect();
-            fact = fact.with_shape(ShapeFactoid::closed(shape));
-        }
-        Ok(fact)
+        translate_inference_fact(t, None)
     }
 }
 
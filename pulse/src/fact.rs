@@ -1,7 +1,9 @@
 use crate::internal::*;
+use std::collections::HashSet;
+use tract_itertools::Itertools;
 
 lazy_static::lazy_static! {
-    static ref S: Symbol = Symbol::new('S');
+    static ref S: Symbol = Symbol::from('S');
 }
 
 pub fn stream_symbol() -> Symbol {
@@ -13,16 +15,20 @@ pub fn stream_dim() -> TDim {
 }
 
 pub trait StreamFact {
-    fn stream_info(&self) -> Option<(usize, &TDim)>;
+    /// Streaming axis and dimension for the default stream symbol `S`.
+    fn stream_info(&self) -> Option<(usize, &TDim)> {
+        self.stream_info_for(&stream_symbol())
+    }
+
+    /// Streaming axis and dimension for a given stream symbol. Returns `None`
+    /// if the symbol does not appear in exactly one dimension.
+    fn stream_info_for(&self, symbol: &Symbol) -> Option<(usize, &TDim)>;
 }
 
 impl StreamFact for ShapeFact {
-    fn stream_info(&self) -> Option<(usize, &TDim)> {
-        let streaming_dims: TVec<(usize, &TDim)> = (&**self)
-            .iter()
-            .enumerate()
-            .filter(|(_ix, d)| d.symbols().contains(&stream_symbol()))
-            .collect();
+    fn stream_info_for(&self, symbol: &Symbol) -> Option<(usize, &TDim)> {
+        let streaming_dims: TVec<(usize, &TDim)> =
+            (&**self).iter().enumerate().filter(|(_ix, d)| d.symbols().contains(symbol)).collect();
         if streaming_dims.len() != 1 {
             None
         } else {
@@ -44,16 +50,63 @@ impl_dyn_hash!(PulsedFact);
 
 impl PulsedFact {
     pub fn from_tensor_fact_pulse(tf: &TypedFact, pulse: usize) -> TractResult<PulsedFact> {
+        Self::from_tensor_fact_pulses(tf, &tvec!((stream_symbol(), pulse)).into_iter().collect())
+    }
+
+    /// Pulse a fact, picking the stream among the ones in `pulses`. The fact
+    /// must mention exactly one of the stream symbols, in exactly one axis.
+    pub fn from_tensor_fact_pulses(tf: &TypedFact, pulses: &Pulses) -> TractResult<PulsedFact> {
         let datum_type = tf.datum_type;
-        let (axis, len) = tf
-            .shape
-            .stream_info()
-            .ok_or_else(|| format_err!("Can not pulse a tensor with no streaming dim"))?;
+        let symbols = tf.shape.iter().flat_map(|d| d.symbols()).collect::<HashSet<Symbol>>();
+        let streams = pulses.iter().filter(|(sym, _)| symbols.contains(sym)).collect::<TVec<_>>();
+        if streams.len() == 0 {
+            bail!("Can not pulse a tensor with no streaming dim ({:?})", tf);
+        } else if streams.len() > 1 {
+            bail!(
+                "Can not pulse a tensor with more than one streaming symbol ({:?}): {}",
+                tf,
+                streams.iter().map(|(s, _)| s.to_string()).join(", ")
+            );
+        }
+        let (symbol, pulse) = streams[0];
+        let (axis, len) = tf.shape.stream_info_for(symbol).ok_or_else(|| {
+            format_err!("Can not pulse a tensor with {} in more than one axis ({:?})", symbol, tf)
+        })?;
         let mut shape: TVec<TDim> = tf.shape.iter().collect();
-        shape[axis] = pulse.into();
+        shape[axis] = (*pulse).into();
         Ok(PulsedFact { datum_type, shape, axis, dim: len.clone(), delay: 0 })
     }
 
+    /// The symbol of the stream this fact belongs to.
+    pub fn stream_symbol(&self) -> TractResult<Symbol> {
+        let symbols = self.dim.symbols();
+        if symbols.len() != 1 {
+            bail!("Expected exactly one symbol in streaming dim, got {}", self.dim)
+        }
+        Ok(symbols.into_iter().next().unwrap())
+    }
+
+    /// Check that a set of facts are pulsed along the same stream, and
+    /// return the streaming symbol.
+    pub fn same_stream(facts: &[&PulsedFact]) -> TractResult<Symbol> {
+        let symbol = facts[0].stream_symbol()?;
+        for f in &facts[1..] {
+            let other = f.stream_symbol()?;
+            if other != symbol {
+                bail!("Can not mix independent streams {} and {}", symbol, other);
+            }
+            if f.axis != facts[0].axis {
+                bail!(
+                    "Stream {} is pulsed on axis {} and {}, expected a single axis",
+                    symbol,
+                    facts[0].axis,
+                    f.axis
+                );
+            }
+        }
+        Ok(symbol)
+    }
+
     pub fn pulse(&self) -> usize {
         self.shape[self.axis].to_usize().expect("Pulse should be an integer. This is a tract bug.")
     }
@@ -79,7 +132,6 @@ impl PulsedFact {
 
 impl fmt::Debug for PulsedFact {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        use tract_itertools::Itertools;
         write!(
             fmt,
             "{},{:?} [pulse axis:{} ∂:{} full dim:{}]",
@@ -13,7 +13,7 @@ pub mod internal {
     pub use downcast_rs::Downcast;
 
     pub use crate::fact::{stream_dim, stream_symbol, PulsedFact};
-    pub use crate::model::{PulsedModel, PulsedModelExt};
+    pub use crate::model::{PulsedModel, PulsedModelExt, Pulses};
     pub use crate::ops::{OpPulsifier, PulsedOp};
     pub use tract_pulse_opl::op_pulse;
 }
@@ -94,4 +94,112 @@ mod tests {
             TypedFact::dt_shape(DatumType::F32, &[4, 2, 3])
         );
     }
+
+    #[test]
+    fn test_model_stream_symbol() {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        assert_ne!(s, stream_symbol());
+        model
+            .add_source(
+                "a",
+                TypedFact::dt_shape(f32::datum_type(), [s.into(), 2.to_dim()].as_ref()),
+            )
+            .unwrap();
+        model.auto_outputs().unwrap();
+
+        let pulse = PulsedModel::new(&model, 4).unwrap();
+
+        assert_eq!(pulse.input_fact(0).unwrap().stream_symbol().unwrap(), s);
+        assert_eq!(
+            pulse.input_fact(0).unwrap().to_typed_fact().unwrap(),
+            TypedFact::dt_shape(DatumType::F32, &[4, 2])
+        );
+    }
+
+    #[test]
+    fn test_independent_streams() {
+        let s = Symbol::new('S');
+        let t = Symbol::new('T');
+        let mut model = TypedModel::default();
+        let _a = model
+            .add_source(
+                "a",
+                TypedFact::dt_shape(f32::datum_type(), [s.to_dim(), 2.to_dim()].as_ref()),
+            )
+            .unwrap();
+        let _b = model
+            .add_source(
+                "b",
+                TypedFact::dt_shape(f32::datum_type(), [3.to_dim(), t.to_dim()].as_ref()),
+            )
+            .unwrap();
+        model.auto_outputs().unwrap();
+
+        let pulses = vec![(s, 4), (t, 2)].into_iter().collect();
+        let pulse = PulsedModel::new_with_pulses(&model, &pulses).unwrap();
+        assert_eq!(pulse.input_fact(0).unwrap().axis, 0);
+        assert_eq!(pulse.input_fact(0).unwrap().stream_symbol().unwrap(), s);
+        assert_eq!(
+            pulse.input_fact(0).unwrap().to_typed_fact().unwrap(),
+            TypedFact::dt_shape(DatumType::F32, &[4, 2])
+        );
+        assert_eq!(pulse.input_fact(1).unwrap().axis, 1);
+        assert_eq!(pulse.input_fact(1).unwrap().stream_symbol().unwrap(), t);
+        assert_eq!(
+            pulse.input_fact(1).unwrap().to_typed_fact().unwrap(),
+            TypedFact::dt_shape(DatumType::F32, &[3, 2])
+        );
+    }
+
+    #[test]
+    fn test_mixed_streams_fail() {
+        let s = Symbol::new('S');
+        let t = Symbol::new('T');
+        let mut model = TypedModel::default();
+        let a = model
+            .add_source(
+                "a",
+                TypedFact::dt_shape(f32::datum_type(), [s.to_dim(), 1.to_dim()].as_ref()),
+            )
+            .unwrap();
+        let b = model
+            .add_source(
+                "b",
+                TypedFact::dt_shape(f32::datum_type(), [1.to_dim(), t.to_dim()].as_ref()),
+            )
+            .unwrap();
+        model.wire_node("mix", tract_core::ops::math::add::bin_typed(), &[a, b]).unwrap();
+        model.auto_outputs().unwrap();
+
+        let pulses = vec![(s, 4), (t, 4)].into_iter().collect();
+        let err = PulsedModel::new_with_pulses(&model, &pulses).unwrap_err();
+        assert!(format!("{:?}", err).contains("mix"));
+    }
+
+    #[test]
+    fn test_wire_mixed_streams_fail() {
+        let s = Symbol::new('S');
+        let t = Symbol::new('T');
+        let mut model = TypedModel::default();
+        model
+            .add_source(
+                "a",
+                TypedFact::dt_shape(f32::datum_type(), [s.to_dim(), 1.to_dim()].as_ref()),
+            )
+            .unwrap();
+        model
+            .add_source(
+                "b",
+                TypedFact::dt_shape(f32::datum_type(), [t.to_dim(), 1.to_dim()].as_ref()),
+            )
+            .unwrap();
+        model.auto_outputs().unwrap();
+
+        let pulses = vec![(s, 4), (t, 4)].into_iter().collect();
+        let mut pulse = PulsedModel::new_with_pulses(&model, &pulses).unwrap();
+        let inputs = pulse.input_outlets().unwrap().to_vec();
+        let op = tract_core::ops::math::add::bin_typed();
+        assert!(pulse.wire_node("mix", op, &inputs).is_err());
+    }
 }
@@ -15,10 +15,10 @@ macro_rules! submit_op_pulsifier {
                    node: &TypedNode,
                    target: &mut PulsedModel,
                    mapping: &HashMap<OutletId, OutletId>,
-                   pulse: usize|
+                   pulses: &Pulses|
              -> TractResult<TVec<OutletId>> {
                 let op = node.op_as::<$op>().unwrap();
-                ($func)(op, source, node, target, mapping, pulse)
+                ($func)(op, source, node, target, mapping, pulses)
             },
             name: stringify!($op)
         });
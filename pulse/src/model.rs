@@ -4,7 +4,11 @@ use tract_core::model::translator::Translate;
 pub type PulsedModel = Graph<PulsedFact, Box<dyn PulsedOp>>;
 pub type PulsedNode = Node<PulsedFact, Box<dyn PulsedOp>>;
 
+/// Pulse size for each independent stream, indexed by stream symbol.
+pub type Pulses = HashMap<Symbol, usize>;
+
 pub trait PulsedModelExt {
+    /// Pulsify a model with a single stream on the default symbol `S`.
     fn new(source: &TypedModel, pulse: usize) -> TractResult<PulsedModel>;
 
     fn new_with_mapping(
@@ -12,6 +16,14 @@ pub trait PulsedModelExt {
         pulse: usize,
     ) -> TractResult<(PulsedModel, HashMap<OutletId, OutletId>)>;
 
+    /// Pulsify a model with one or more independent streams.
+    fn new_with_pulses(source: &TypedModel, pulses: &Pulses) -> TractResult<PulsedModel>;
+
+    fn new_with_pulses_and_mapping(
+        source: &TypedModel,
+        pulses: &Pulses,
+    ) -> TractResult<(PulsedModel, HashMap<OutletId, OutletId>)>;
+
     fn into_typed(self) -> TractResult<TypedModel>;
 }
 
@@ -24,7 +36,23 @@ impl PulsedModelExt for PulsedModel {
         source: &TypedModel,
         pulse: usize,
     ) -> TractResult<(PulsedModel, HashMap<OutletId, OutletId>)> {
-        Pulsifier(pulse).translate_model_with_mappings(source)
+        let symbol = source.symbol_table.get("S").unwrap_or_else(stream_symbol);
+        let pulses = std::iter::once((symbol, pulse)).collect();
+        PulsedModel::new_with_pulses_and_mapping(source, &pulses)
+    }
+
+    fn new_with_pulses(source: &TypedModel, pulses: &Pulses) -> TractResult<PulsedModel> {
+        Ok(PulsedModel::new_with_pulses_and_mapping(source, pulses)?.0)
+    }
+
+    fn new_with_pulses_and_mapping(
+        source: &TypedModel,
+        pulses: &Pulses,
+    ) -> TractResult<(PulsedModel, HashMap<OutletId, OutletId>)> {
+        if pulses.len() == 0 {
+            bail!("Pulsification requires at least one stream symbol")
+        }
+        Pulsifier(pulses.clone()).translate_model_with_mappings(source)
     }
 
     fn into_typed(self) -> TractResult<TypedModel> {
@@ -64,6 +92,9 @@ impl SpecialOps<PulsedFact, Box<dyn PulsedOp>> for PulsedModel {
         let output_facts = {
             let input_facts =
                 inputs.iter().map(|o| self.outlet_fact(*o)).collect::<TractResult<TVec<_>>>()?;
+            if input_facts.len() > 1 {
+                PulsedFact::same_stream(&input_facts)?;
+            }
             op.pulsed_output_facts(&*input_facts)?
         };
         let id = self.add_node(name, op, output_facts)?;
@@ -76,7 +107,7 @@ impl SpecialOps<PulsedFact, Box<dyn PulsedOp>> for PulsedModel {
 }
 
 #[derive(Debug)]
-struct Pulsifier(usize);
+struct Pulsifier(Pulses);
 
 impl
     tract_core::model::translator::Translate<
@@ -96,7 +127,8 @@ impl
         if let Some(pulsifier) =
             inventory::iter::<crate::ops::OpPulsifier>().find(|p| p.type_id == node.op.type_id())
         {
-            (pulsifier.func)(source, node, target, mapping, self.0)
+            (pulsifier.func)(source, node, target, mapping, &self.0)
+                .with_context(|| format!("Pulsifying node {}", node))
         } else {
             bail!("No pulsifier for {}", node);
         }
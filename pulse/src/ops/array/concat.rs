@@ -12,20 +12,30 @@ fn pulsify(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
-    if node.inputs.len() > 1 {
-        bail!("Pulsification not implemented for more than one input to Concat")
-    }
-
     let input = mapping[&node.inputs[0]];
     let fact = target.outlet_fact(input)?;
 
     if fact.axis == op.axis {
         pulsify_along_concat_axis(op, source, node, target, mapping)
+    } else if op.slices.iter().all(|s| s.is_var()) {
+        let inputs = crate::ops::sync_inputs(node, target, mapping)?;
+        target.wire_node(&*node.name, op.clone(), &inputs)
     } else {
-        bail!("Pulsify for Concat on a separate axis is not implemented (but possible)");
+        bail!("Pulsify for Concat on a separate axis with constant slices is not implemented")
+    }
+}
+
+impl PulsedOp for TypedConcat {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.shape[self.axis] = inputs.iter().map(|f| f.shape[self.axis].clone()).sum();
+        Ok(tvec!(fact))
     }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
 }
 
 fn pulsify_along_concat_axis(
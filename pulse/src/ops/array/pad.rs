@@ -11,10 +11,13 @@ fn pulsify(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
     let mut input = mapping[&node.inputs[0]];
     let fact = target.outlet_fact(input)?.clone();
+    if op.pads[fact.axis] == (0, 0) {
+        return target.wire_node(&*node.name, op.clone(), &[input]);
+    }
     if !op.pads.iter().enumerate().all(|(ax, &(a, b))| ax == fact.axis || (a == 0 && b == 0)) {
         bail!("Pad pulse only implemented for streaming dim");
     }
@@ -34,7 +37,9 @@ fn pulsify(
             pulse,
             before
         ),
-        PadMode::Reflect => bail!("Reflect padding mode pulsing is not supported"),
+        PadMode::Reflect | PadMode::Symmetric => {
+            bail!("Reflect padding mode pulsing is not supported")
+        }
     };
     if extra_delay > 0 {
         input = target.wire_node(
@@ -197,6 +202,19 @@ impl PulsePadOpState {
     }
 }
 
+impl PulsedOp for Pad {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        for (ix, (before, after)) in self.pads.iter().enumerate() {
+            fact.shape[ix] += (before + after).to_dim();
+        }
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
 impl PulsedOp for PulsePad {
     fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
         let mut fact = inputs[0].clone();
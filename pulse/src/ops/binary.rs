@@ -1,49 +1,20 @@
 use crate::internal::*;
 use tract_core::ops::binary::*;
 use tract_core::ops::logic::Iff;
-use tract_pulse_opl::ops::Delay;
 
 submit_op_pulsifier!(UnaryOp, pulsify_un);
 submit_op_pulsifier!(TypedBinOp, pulsify_bin);
 submit_op_pulsifier!(Iff, pulsify_iff);
 
-fn sync_inputs(
-    node: &TypedNode,
-    target: &mut PulsedModel,
-    mapping: &HashMap<OutletId, OutletId>,
-) -> TractResult<TVec<OutletId>> {
-    let delay = node
-        .inputs
-        .iter()
-        .map(|input| target.outlet_fact(mapping[input]).unwrap().delay)
-        .max()
-        .unwrap();
-    let mut inputs = tvec!();
-    for input in &node.inputs {
-        let mut input = mapping[input];
-        let fact = target.outlet_fact(input)?.clone();
-        if fact.delay < delay {
-            let add_delay = delay - fact.delay;
-            input = target.wire_node(
-                format!("{}.Delay", &*node.name),
-                Delay::new(fact.axis, &fact.into(), add_delay, 0),
-                &[input],
-            )?[0];
-        }
-        inputs.push(input);
-    }
-    Ok(inputs)
-}
-
 fn pulsify_bin(
     op: &TypedBinOp,
     _source: &TypedModel,
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
-    let inputs = &*sync_inputs(node, target, mapping)?;
+    let inputs = &*super::sync_inputs(node, target, mapping)?;
     target.wire_node(&*node.name, op.clone(), &inputs)
 }
 
@@ -68,7 +39,7 @@ fn pulsify_un(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
     let input = mapping[&node.inputs[0]];
     target.wire_node(&*node.name, op.clone(), &[input])
@@ -97,9 +68,9 @@ fn pulsify_iff(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
-    let inputs = &*sync_inputs(node, target, mapping)?;
+    let inputs = &*super::sync_inputs(node, target, mapping)?;
     target.wire_node(&*node.name, op.clone(), &inputs)
 }
 
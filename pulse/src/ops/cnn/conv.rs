@@ -9,7 +9,7 @@ fn pulsify(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
     fn zero<D: Datum>() -> Tensor {
         tensor0(D::default())
@@ -11,7 +11,7 @@ fn pulsify_max_pool(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
     fn min_value<D: Datum + tract_core::num_traits::Bounded>() -> Tensor {
         tensor0(D::min_value())
@@ -28,7 +28,7 @@ fn pulsify_sum_pool(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
     let (wire, pool_spec) = pulsify(&op.pool_spec, source, node, target, mapping, None)?;
     target.wire_node(&node.name, SumPool { pool_spec, ..op.clone() }, &[wire])
@@ -1,4 +1,5 @@
 use crate::internal::*;
+use tract_pulse_opl::ops::Delay;
 
 pub mod array;
 pub mod binary;
@@ -14,6 +15,38 @@ pub mod quant;
 pub mod scan;
 pub mod source;
 
+/// Delays the inputs of a node so that they all share the same delay.
+pub fn sync_inputs(
+    node: &TypedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+) -> TractResult<TVec<OutletId>> {
+    let delay = {
+        let facts = node
+            .inputs
+            .iter()
+            .map(|i| target.outlet_fact(mapping[i]))
+            .collect::<TractResult<TVec<_>>>()?;
+        PulsedFact::same_stream(&facts)?;
+        facts.iter().map(|f| f.delay).max().unwrap()
+    };
+    let mut inputs = tvec!();
+    for (ix, input) in node.inputs.iter().enumerate() {
+        let mut input = mapping[input];
+        let fact = target.outlet_fact(input)?.clone();
+        if fact.delay < delay {
+            let add_delay = delay - fact.delay;
+            input = target.wire_node(
+                format!("{}.Delay-{}", &*node.name, ix),
+                Delay::new(fact.axis, &fact.into(), add_delay, 0),
+                &[input],
+            )?[0];
+        }
+        inputs.push(input);
+    }
+    Ok(inputs)
+}
+
 inventory::collect!(OpPulsifier);
 pub struct OpPulsifier {
     pub type_id: std::any::TypeId,
@@ -23,7 +56,7 @@ pub struct OpPulsifier {
         &TypedNode,
         &mut PulsedModel,
         &HashMap<OutletId, OutletId>,
-        usize,
+        &Pulses,
     ) -> TractResult<TVec<OutletId>>,
 }
 
@@ -9,7 +9,7 @@ fn pulsify(
     node: &TypedNode,
     target: &mut PulsedModel,
     mapping: &HashMap<OutletId, OutletId>,
-    _pulse: usize,
+    _pulses: &Pulses,
 ) -> TractResult<TVec<OutletId>> {
     for input_id in 0..node.inputs.len() {
         let input = mapping[&node.inputs[input_id]];
@@ -28,7 +28,7 @@ fn pulsify(
         }
     }
 
-    let pulse_inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+    let pulse_inputs = super::sync_inputs(node, target, mapping)?;
 
     let mut op = op.clone();
     op.skip = target.outlet_fact(pulse_inputs[0])?.delay;
@@ -0,0 +1,408 @@
+//! TensorBundle checkpoint reader.
+//!
+//! This is the format of `variables/variables.{index,data-*}` in SavedModel
+//! directories. The index is a LevelDB-style sorted table mapping tensor
+//! names to `BundleEntryProto` (the empty key holding a `BundleHeaderProto`),
+//! the data files are raw tensor bytes.
+//!
+//! Only uncompressed tables and little endian, non partitioned tensors are
+//! supported.
+use crate::tfpb::tensorflow::bundle_header_proto::Endianness;
+use crate::tfpb::tensorflow::{
+    BundleEntryProto, BundleHeaderProto, DataType, TrackableObjectGraph,
+};
+use prost::Message;
+use std::convert::{TryFrom, TryInto};
+use std::{fs, path};
+use tract_hir::internal::*;
+
+/// Key of the object graph in TF2 (object-based) checkpoints.
+pub const OBJECT_GRAPH_KEY: &'static str = "_CHECKPOINTABLE_OBJECT_GRAPH";
+
+const TABLE_MAGIC: u64 = 0xdb4775248b80fb57;
+const FOOTER_LEN: usize = 48;
+const BLOCK_TRAILER_LEN: usize = 5;
+
+#[derive(Clone, Debug)]
+pub struct TensorBundle {
+    prefix: path::PathBuf,
+    header: BundleHeaderProto,
+    entries: HashMap<String, BundleEntryProto>,
+}
+
+impl TensorBundle {
+    /// Open a bundle from its prefix (like `saved_model/variables/variables`).
+    pub fn open(prefix: impl AsRef<path::Path>) -> TractResult<TensorBundle> {
+        let prefix = prefix.as_ref().to_path_buf();
+        let index_path = Self::path_with_suffix(&prefix, ".index");
+        let index = fs::read(&index_path)
+            .with_context(|| format!("Reading checkpoint index {:?}", index_path))?;
+        let mut header = None;
+        let mut entries = HashMap::default();
+        for (key, value) in read_table(&index)
+            .with_context(|| format!("Parsing checkpoint index {:?}", index_path))?
+        {
+            if key.len() == 0 {
+                header = Some(BundleHeaderProto::decode(&*value)?);
+            } else {
+                let key = String::from_utf8(key)?;
+                entries.insert(key, BundleEntryProto::decode(&*value)?);
+            }
+        }
+        let header = header.context("Checkpoint index has no bundle header")?;
+        if header.endianness() == Endianness::Big {
+            bail!("Big endian checkpoints are not supported")
+        }
+        Ok(TensorBundle { prefix, header, entries })
+    }
+
+    fn path_with_suffix(prefix: &path::Path, suffix: &str) -> path::PathBuf {
+        let mut path = prefix.as_os_str().to_owned();
+        path.push(suffix);
+        path.into()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|k| &**k)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    fn raw(&self, name: &str) -> TractResult<(&BundleEntryProto, Vec<u8>)> {
+        let entry =
+            self.entries.get(name).with_context(|| format!("No tensor {} in checkpoint", name))?;
+        if entry.slices.len() > 0 {
+            bail!("Tensor {} is partitioned in checkpoint, this is not supported", name)
+        }
+        let data_path = Self::path_with_suffix(
+            &self.prefix,
+            &format!(".data-{:05}-of-{:05}", entry.shard_id, self.header.num_shards),
+        );
+        let mut file = fs::File::open(&data_path)
+            .with_context(|| format!("Opening checkpoint data {:?}", data_path))?;
+        use std::io::{Read, Seek, SeekFrom};
+        file.seek(SeekFrom::Start(entry.offset as u64))?;
+        let mut data = vec![0u8; entry.size as usize];
+        file.read_exact(&mut data)
+            .with_context(|| format!("Reading tensor {} from {:?}", name, data_path))?;
+        if unmask_crc(entry.crc32c) != data_crc32c(entry, &data)? {
+            bail!("Checksum mismatch for tensor {} in checkpoint", name)
+        }
+        Ok((entry, data))
+    }
+
+    /// Read a tensor. TensorFlow strings are read as Blob tensors.
+    pub fn tensor(&self, name: &str) -> TractResult<Tensor> {
+        let (entry, data) = self.raw(name)?;
+        let dt = DataType::from_i32(entry.dtype)
+            .with_context(|| format!("Invalid dtype for tensor {} in checkpoint", name))?;
+        let dt = DatumType::try_from(dt)?;
+        let shape: TVec<usize> =
+            entry.shape.as_ref().map(|s| s.try_into()).transpose()?.unwrap_or(tvec!());
+        if dt == DatumType::Blob {
+            let blobs = read_strings(&data, shape.iter().product())
+                .with_context(|| format!("Decoding string tensor {}", name))?;
+            let blobs = blobs.into_iter().map(|b| Blob(b.to_vec())).collect();
+            Ok(tract_ndarray::ArrayD::from_shape_vec(&*shape, blobs)?.into())
+        } else {
+            if data.len() != shape.iter().product::<usize>() * dt.size_of() {
+                bail!("Wrong data size for tensor {} in checkpoint", name)
+            }
+            unsafe { Tensor::from_raw_dt(dt, &shape, &data) }
+        }
+    }
+
+    /// For object-based checkpoints (TF2), maps variable names (as in
+    /// `tf.train.Saver`) to their checkpoint keys. Empty for name-based
+    /// checkpoints, where variables are stored under their own name.
+    pub fn variable_keys(&self) -> TractResult<HashMap<String, String>> {
+        let mut keys = HashMap::default();
+        if self.contains(OBJECT_GRAPH_KEY) {
+            let (_, data) = self.raw(OBJECT_GRAPH_KEY)?;
+            let graph = read_strings(&data, 1)?;
+            let graph = TrackableObjectGraph::decode(graph[0])?;
+            for node in graph.nodes {
+                for attr in node.attributes {
+                    if attr.full_name.len() > 0 {
+                        keys.insert(attr.full_name, attr.checkpoint_key);
+                    }
+                }
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// String tensors are stored as all the lengths (varint64), a checksum of the
+/// lengths, then all the bytes.
+fn read_strings(data: &[u8], len: usize) -> TractResult<Vec<&[u8]>> {
+    let mut reader = Reader(data);
+    let lengths = (0..len).map(|_| reader.varint()).collect::<TractResult<Vec<_>>>()?;
+    if len > 0 {
+        reader.take(4)?;
+    }
+    lengths.into_iter().map(|l| reader.take(l as usize)).collect()
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> TractResult<&'a [u8]> {
+        if len > self.0.len() {
+            bail!("Unexpected end of data")
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn varint(&mut self) -> TractResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("Invalid varint")
+    }
+
+    fn block_handle(&mut self) -> TractResult<(usize, usize)> {
+        Ok((self.varint()? as usize, self.varint()? as usize))
+    }
+}
+
+/// Read all key/value pairs from a sorted string table.
+fn read_table(table: &[u8]) -> TractResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    if table.len() < FOOTER_LEN {
+        bail!("Table too short")
+    }
+    let footer = &table[table.len() - FOOTER_LEN..];
+    if u64::from_le_bytes(footer[FOOTER_LEN - 8..].try_into()?) != TABLE_MAGIC {
+        bail!("Invalid table magic number")
+    }
+    let mut footer = Reader(footer);
+    let _metaindex = footer.block_handle()?;
+    let index = footer.block_handle()?;
+    let mut entries = vec![];
+    for (_, handle) in read_block(table, index)? {
+        let handle = Reader(&handle).block_handle()?;
+        entries.extend(read_block(table, handle)?);
+    }
+    Ok(entries)
+}
+
+fn read_block(
+    table: &[u8],
+    (offset, size): (usize, usize),
+) -> TractResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    let end = offset
+        .checked_add(size)
+        .and_then(|end| end.checked_add(BLOCK_TRAILER_LEN))
+        .context("Table block handle overflows")?;
+    if end > table.len() {
+        bail!("Table block out of bounds (truncated table?)")
+    }
+    if size < 4 {
+        bail!("Table block too short")
+    }
+    if table[offset + size] != 0 {
+        bail!("Compressed tables are not supported")
+    }
+    let block = &table[offset..][..size];
+    let restarts = u32::from_le_bytes(block[size - 4..].try_into()?) as usize;
+    let entries_len = size
+        .checked_sub(restarts.saturating_add(1).saturating_mul(4))
+        .context("Invalid number of restarts in table block")?;
+    let mut reader = Reader(&block[..entries_len]);
+    let mut entries = vec![];
+    let mut key: Vec<u8> = vec![];
+    while reader.0.len() > 0 {
+        let shared = reader.varint()? as usize;
+        let non_shared = reader.varint()? as usize;
+        let value_len = reader.varint()? as usize;
+        if shared > key.len() {
+            bail!("Invalid key prefix in table block")
+        }
+        key.truncate(shared);
+        key.extend_from_slice(reader.take(non_shared)?);
+        entries.push((key.clone(), reader.take(value_len)?.to_vec()));
+    }
+    Ok(entries)
+}
+
+/// Checksum of a tensor data, as computed by TensorFlow. For strings, the
+/// lengths are checksummed as fixed 32-bit integers (64-bit if they don't
+/// fit), not as their varints.
+fn data_crc32c(entry: &BundleEntryProto, data: &[u8]) -> TractResult<u32> {
+    if entry.dtype != DataType::DtString as i32 {
+        return Ok(crc32c(data));
+    }
+    let len = entry.shape.as_ref().map(|s| s.dim.iter().map(|d| d.size).product()).unwrap_or(1);
+    let mut reader = Reader(data);
+    let mut crc = 0;
+    for _ in 0..len {
+        let length = reader.varint()?;
+        crc = match u32::try_from(length) {
+            Ok(length) => crc32c_extend(crc, &length.to_le_bytes()),
+            Err(_) => crc32c_extend(crc, &length.to_le_bytes()),
+        };
+    }
+    Ok(crc32c_extend(crc, reader.0))
+}
+
+fn crc32c(data: &[u8]) -> u32 {
+    crc32c_extend(0, data)
+}
+
+fn crc32c_extend(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0x82f63b78 & (!(crc & 1)).wrapping_add(1));
+        }
+    }
+    !crc
+}
+
+fn unmask_crc(masked: u32) -> u32 {
+    let rot = masked.wrapping_sub(0xa282ead8);
+    (rot >> 17) | (rot << 15)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::tfpb::tensorflow::TensorShapeProto;
+
+    fn varint(mut v: u64, buf: &mut Vec<u8>) {
+        while v >= 0x80 {
+            buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        buf.push(v as u8);
+    }
+
+    fn mask_crc(crc: u32) -> u32 {
+        ((crc >> 15) | (crc << 17)).wrapping_add(0xa282ead8)
+    }
+
+    fn block(entries: &[(Vec<u8>, Vec<u8>)], table: &mut Vec<u8>) -> (usize, usize) {
+        let offset = table.len();
+        for (k, v) in entries {
+            varint(0, table);
+            varint(k.len() as u64, table);
+            varint(v.len() as u64, table);
+            table.extend_from_slice(k);
+            table.extend_from_slice(v);
+        }
+        table.extend_from_slice(&0u32.to_le_bytes());
+        table.extend_from_slice(&1u32.to_le_bytes());
+        let size = table.len() - offset;
+        table.extend_from_slice(&[0, 0, 0, 0, 0]);
+        (offset, size)
+    }
+
+    /// Write a single-shard, name-based checkpoint.
+    pub fn write_bundle(prefix: &path::Path, tensors: &[(&str, Tensor)]) -> TractResult<()> {
+        let mut data = vec![];
+        let header = BundleHeaderProto { num_shards: 1, ..Default::default() };
+        let mut entries = vec![(vec![], encode(&header))];
+        let mut tensors: Vec<_> = tensors.iter().collect();
+        tensors.sort_by_key(|t| t.0);
+        for (name, t) in tensors {
+            let bytes = unsafe { t.as_bytes() };
+            let entry = BundleEntryProto {
+                dtype: DataType::try_from(t.datum_type())? as i32,
+                shape: Some(TensorShapeProto {
+                    dim: t
+                        .shape()
+                        .iter()
+                        .map(|d| crate::tfpb::tensorflow::tensor_shape_proto::Dim {
+                            size: *d as i64,
+                            name: String::new(),
+                        })
+                        .collect(),
+                    unknown_rank: false,
+                }),
+                offset: data.len() as i64,
+                size: bytes.len() as i64,
+                crc32c: mask_crc(crc32c(bytes)),
+                ..Default::default()
+            };
+            data.extend_from_slice(bytes);
+            entries.push((name.as_bytes().to_vec(), encode(&entry)));
+        }
+        let mut table = vec![];
+        let (offset, size) = block(&entries, &mut table);
+        let mut handle = vec![];
+        varint(offset as u64, &mut handle);
+        varint(size as u64, &mut handle);
+        let last_key = entries.last().unwrap().0.clone();
+        let index = block(&[(last_key, handle)], &mut table);
+        let metaindex = block(&[], &mut table);
+        let mut footer = vec![];
+        for v in &[metaindex.0, metaindex.1, index.0, index.1] {
+            varint(*v as u64, &mut footer);
+        }
+        footer.resize(FOOTER_LEN - 8, 0);
+        footer.extend_from_slice(&TABLE_MAGIC.to_le_bytes());
+        table.extend_from_slice(&footer);
+        fs::write(TensorBundle::path_with_suffix(prefix, ".index"), table)?;
+        fs::write(TensorBundle::path_with_suffix(prefix, ".data-00000-of-00001"), data)?;
+        Ok(())
+    }
+
+    fn encode<M: Message>(m: &M) -> Vec<u8> {
+        let mut v = vec![];
+        m.encode(&mut v).unwrap();
+        v
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32c(b"123456789"), 0xe3069283);
+        assert_eq!(unmask_crc(mask_crc(0x12345678)), 0x12345678);
+    }
+
+    #[test]
+    fn bundle_roundtrip() -> TractResult<()> {
+        let dir = std::env::temp_dir().join(format!("tract-tf-bundle-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let prefix = dir.join("variables");
+        let kernel = tensor2(&[[1.0f32, 2.0], [3.0, 4.0]]);
+        let bias = tensor1(&[5i64, 6]);
+        write_bundle(&prefix, &[("dense/kernel", kernel.clone()), ("dense/bias", bias.clone())])?;
+        let bundle = TensorBundle::open(&prefix)?;
+        let mut names: Vec<&str> = bundle.names().collect();
+        names.sort();
+        assert_eq!(names, vec!["dense/bias", "dense/kernel"]);
+        assert_eq!(bundle.tensor("dense/kernel")?, kernel);
+        assert_eq!(bundle.tensor("dense/bias")?, bias);
+        assert!(bundle.tensor("dense/foo").is_err());
+        assert!(bundle.variable_keys()?.is_empty());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_table() -> TractResult<()> {
+        let dir = std::env::temp_dir().join(format!("tract-tf-truncated-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let prefix = dir.join("variables");
+        write_bundle(&prefix, &[("w", tensor1(&[1f32, 2.0]))])?;
+        let table = fs::read(TensorBundle::path_with_suffix(&prefix, ".index"))?;
+        let footer = table[table.len() - FOOTER_LEN..].to_vec();
+        let mut truncated = table[..10].to_vec();
+        truncated.extend_from_slice(&footer);
+        assert!(read_table(&truncated).is_err());
+        assert!(read_block(&table, (!0 - 2, 8)).is_err());
+        assert!(read_block(&table, (0, !0)).is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,323 @@
+//! TensorFlow 2 function library.
+//!
+//! `tf.function` calls (`PartitionedCall`, `StatefulPartitionedCall` or a
+//! node whose op is the name of a library function) are inlined in the
+//! GraphDef before parsing. Functional control flow ops (`If`, `While`) keep
+//! their functions as nested models, see `ParsingContext::parse_function`.
+
+use crate::tfpb::tensorflow::attr_value::Value;
+use crate::tfpb::tensorflow::{AttrValue, FunctionDef, GraphDef, NodeDef};
+use tract_hir::internal::*;
+
+pub type FunctionLibrary = HashMap<String, FunctionDef>;
+
+const MAX_INLINING_DEPTH: usize = 32;
+
+pub fn library(graph: &GraphDef) -> FunctionLibrary {
+    graph
+        .library
+        .iter()
+        .flat_map(|lib| lib.function.iter())
+        .filter_map(|f| f.signature.as_ref().map(|s| (s.name.clone(), f.clone())))
+        .collect()
+}
+
+/// Inline all function calls in `nodes`, recursively.
+///
+/// Function body nodes are renamed "{call}/{node}" and the call node is
+/// replaced by an IdentityN over the function results, so that its outputs
+/// keep the same name.
+pub fn inline_calls(nodes: &mut Vec<NodeDef>, library: &FunctionLibrary) -> TractResult<()> {
+    for _ in 0..MAX_INLINING_DEPTH {
+        let mut inlined = false;
+        let mut new_nodes = Vec::with_capacity(nodes.len());
+        for node in nodes.drain(..) {
+            if let Some(func) = called_function(&node, library)? {
+                let func = library
+                    .get(func)
+                    .with_context(|| format!("Function {} not found in library", func))?;
+                new_nodes.extend(inline_call(&node, func)?);
+                inlined = true;
+            } else {
+                new_nodes.push(node);
+            }
+        }
+        *nodes = new_nodes;
+        if !inlined {
+            return Ok(());
+        }
+    }
+    bail!("Function calls nested more than {} times (recursive function ?)", MAX_INLINING_DEPTH)
+}
+
+/// Turn a function into a standalone graph: one Placeholder per argument (in
+/// signature order), followed by the body nodes, with calls inlined.
+///
+/// Returns the graph and the node references of the function results.
+pub fn function_graph(
+    func: &FunctionDef,
+    library: &FunctionLibrary,
+) -> TractResult<(GraphDef, Vec<String>)> {
+    let signature = func.signature.as_ref().context("Function without signature")?;
+    let mut nodes = vec![];
+    for arg in &signature.input_arg {
+        if arg.r#type == 0 {
+            bail!(
+                "Argument {} of function {} has a polymorphic type, only instantiated functions are supported",
+                arg.name,
+                signature.name
+            );
+        }
+        let placeholder = crate::tfpb::node()
+            .name(&arg.name)
+            .op("Placeholder")
+            .attr("dtype", AttrValue { value: Some(Value::Type(arg.r#type)) });
+        nodes.push(placeholder);
+    }
+    let args: HashMap<&str, String> =
+        signature.input_arg.iter().map(|arg| (&*arg.name, arg.name.clone())).collect();
+    nodes.extend(body_nodes(func, "", &args, &func.attr)?);
+    inline_calls(&mut nodes, library)?;
+    let outputs = results(func, "", &args)?;
+    let mut graph = crate::tfpb::graph();
+    graph.node = nodes;
+    Ok((graph, outputs))
+}
+
+fn called_function<'n>(
+    node: &'n NodeDef,
+    library: &FunctionLibrary,
+) -> TractResult<Option<&'n str>> {
+    if node.op == "PartitionedCall" || node.op == "StatefulPartitionedCall" {
+        Ok(Some(node.get_attr_func("f")?))
+    } else if library.contains_key(&node.op) {
+        Ok(Some(&node.op))
+    } else {
+        Ok(None)
+    }
+}
+
+fn inline_call(call: &NodeDef, func: &FunctionDef) -> TractResult<Vec<NodeDef>> {
+    let signature = func.signature.as_ref().context("Function without signature")?;
+    let data_inputs: Vec<&String> = call.input.iter().filter(|i| !i.starts_with("^")).collect();
+    if data_inputs.len() != signature.input_arg.len() {
+        bail!(
+            "Call {} passes {} inputs to {}, which expects {}",
+            call.name,
+            data_inputs.len(),
+            signature.name,
+            signature.input_arg.len()
+        );
+    }
+    let args: HashMap<&str, String> = signature
+        .input_arg
+        .iter()
+        .zip(data_inputs.iter())
+        .map(|(arg, input)| (&*arg.name, input.to_string()))
+        .collect();
+    // attributes come from the call node, or from the function attribute of
+    // a PartitionedCall
+    let mut attrs = call.attr.clone();
+    if let Some(Value::Func(f)) = call.attr.get("f").and_then(|a| a.value.as_ref()) {
+        attrs.extend(f.attr.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    let prefix = format!("{}/", call.name);
+    let mut nodes = body_nodes(func, &prefix, &args, &attrs)?;
+    let mut outputs = results(func, &prefix, &args)?;
+    outputs.extend(call.input.iter().filter(|i| i.starts_with("^")).cloned());
+    let mut identity = crate::tfpb::node().name(&call.name).op("IdentityN");
+    identity.input = outputs;
+    identity.device = call.device.clone();
+    nodes.push(identity);
+    Ok(nodes)
+}
+
+fn body_nodes(
+    func: &FunctionDef,
+    prefix: &str,
+    args: &HashMap<&str, String>,
+    attrs: &HashMap<String, AttrValue>,
+) -> TractResult<Vec<NodeDef>> {
+    let ops: HashMap<&str, &str> = func.node_def.iter().map(|n| (&*n.name, &*n.op)).collect();
+    func.node_def
+        .iter()
+        .map(|node| {
+            let mut node = node.clone();
+            node.name = format!("{}{}", prefix, node.name);
+            node.input = node
+                .input
+                .iter()
+                .map(|i| rewrite_input(i, prefix, args, &ops))
+                .collect::<TractResult<_>>()?;
+            let name = &node.name;
+            for value in node.attr.values_mut() {
+                if let Some(Value::Placeholder(p)) = &value.value {
+                    *value = attrs.get(p).cloned().with_context(|| {
+                        format!("Attribute placeholder {} not provided for {}", p, name)
+                    })?;
+                }
+            }
+            Ok(node)
+        })
+        .collect()
+}
+
+fn results(
+    func: &FunctionDef,
+    prefix: &str,
+    args: &HashMap<&str, String>,
+) -> TractResult<Vec<String>> {
+    let signature = func.signature.as_ref().context("Function without signature")?;
+    let ops: HashMap<&str, &str> = func.node_def.iter().map(|n| (&*n.name, &*n.op)).collect();
+    signature
+        .output_arg
+        .iter()
+        .map(|arg| {
+            let ret = func.ret.get(&arg.name).with_context(|| {
+                format!("No value for result {} of {}", arg.name, signature.name)
+            })?;
+            rewrite_input(ret, prefix, args, &ops)
+        })
+        .collect()
+}
+
+// In function bodies, inputs are either a function argument name, or
+// "node:output_arg:index" where output_arg is the name of one of the
+// (possibly list-valued) outputs of the node op, or "^node" for control
+// dependencies.
+fn rewrite_input(
+    input: &str,
+    prefix: &str,
+    args: &HashMap<&str, String>,
+    ops: &HashMap<&str, &str>,
+) -> TractResult<String> {
+    if input.starts_with("^") {
+        let name = &input[1..];
+        return Ok(if let Some(arg) = args.get(name) {
+            format!("^{}", arg.trim_start_matches("^").split(':').next().unwrap())
+        } else {
+            format!("^{}{}", prefix, name)
+        });
+    }
+    let splits: Vec<&str> = input.split(':').collect();
+    match &*splits {
+        [arg] => args
+            .get(arg)
+            .cloned()
+            .with_context(|| format!("Reference to unknown function argument {}", arg)),
+        [node, output_arg, ix] => {
+            let op =
+                ops.get(node).with_context(|| format!("Reference to unknown node {}", node))?;
+            let slot = output_arg_base(op, output_arg) + ix.parse::<usize>()?;
+            Ok(format!("{}{}:{}", prefix, node, slot))
+        }
+        _ => bail!("Can not interpret function input {}", input),
+    }
+}
+
+/// Index of the first tensor of an output argument among the outputs of an
+/// op. Most ops have a single (possibly list-valued) output argument.
+fn output_arg_base(op: &str, output_arg: &str) -> usize {
+    match (op, output_arg) {
+        ("Switch", "output_true") => 1,
+        ("Merge", "value_index") => 1,
+        ("TensorArrayV3", "flow") => 1,
+        ("TopKV2", "indices") => 1,
+        ("Unique", "idx") => 1,
+        ("NonMaxSuppressionV4", "valid_outputs") => 1,
+        ("NonMaxSuppressionV5", "selected_scores") => 1,
+        ("NonMaxSuppressionV5", "valid_outputs") => 2,
+        ("FusedBatchNorm", arg) | ("FusedBatchNormV2", arg) | ("FusedBatchNormV3", arg) => {
+            match arg {
+                "batch_mean" => 1,
+                "batch_variance" => 2,
+                "reserve_space_1" => 3,
+                "reserve_space_2" => 4,
+                "reserve_space_3" => 5,
+                _ => 0,
+            }
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tfpb::tensorflow::op_def::ArgDef;
+    use crate::tfpb::tensorflow::{FunctionDefLibrary, NameAttrList, OpDef};
+
+    fn node(name: &str, op: &str, inputs: &[&str]) -> NodeDef {
+        inputs.iter().fold(crate::tfpb::node().name(name).op(op), |n, i| n.input(*i))
+    }
+
+    fn function(name: &str, inputs: &[&str], ret: &str, nodes: Vec<NodeDef>) -> FunctionDef {
+        let arg = |name: &str| ArgDef { name: name.to_string(), ..ArgDef::default() };
+        FunctionDef {
+            signature: Some(OpDef {
+                name: name.to_string(),
+                input_arg: inputs.iter().map(|n| arg(n)).collect(),
+                output_arg: vec![arg("y")],
+                ..OpDef::default()
+            }),
+            node_def: nodes,
+            ret: vec![("y".to_string(), ret.to_string())].into_iter().collect(),
+            ..FunctionDef::default()
+        }
+    }
+
+    fn graph(nodes: Vec<NodeDef>) -> GraphDef {
+        let square = function("square", &["x"], "mul:z:0", vec![node("mul", "Mul", &["x", "x"])]);
+        let square_sum = function(
+            "square_sum",
+            &["a", "b"],
+            "square:y:0",
+            vec![node("sum", "Add", &["a", "b"]), node("square", "square", &["sum:z:0"])],
+        );
+        let mut graph = nodes.into_iter().fold(crate::tfpb::graph(), |g, n| g.node(n));
+        graph.library = Some(FunctionDefLibrary {
+            function: vec![square, square_sum],
+            ..FunctionDefLibrary::default()
+        });
+        graph
+    }
+
+    #[test]
+    fn inline_partitioned_call() -> TractResult<()> {
+        let f = NameAttrList { name: "square_sum".to_string(), attr: HashMap::new() };
+        let graph = graph(vec![
+            node("x", "Placeholder", &[]),
+            node("y", "Placeholder", &[]),
+            node("call", "PartitionedCall", &["x", "y:0", "^x"]).attr("f", f),
+            node("output", "Neg", &["call"]),
+        ]);
+        let mut nodes = graph.node.clone();
+        inline_calls(&mut nodes, &library(&graph))?;
+        let summary: Vec<(&str, &str, Vec<&str>)> = nodes
+            .iter()
+            .map(|n| (&*n.name, &*n.op, n.input.iter().map(|i| &**i).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("x", "Placeholder", vec![]),
+                ("y", "Placeholder", vec![]),
+                ("call/sum", "Add", vec!["x", "y:0"]),
+                ("call/square/mul", "Mul", vec!["call/sum:0", "call/sum:0"]),
+                ("call/square", "IdentityN", vec!["call/square/mul:0"]),
+                ("call", "IdentityN", vec!["call/square:0", "^x"]),
+                ("output", "Neg", vec!["call"]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn recursive_call() {
+        let mut graph = graph(vec![node("x", "Placeholder", &[]), node("call", "loop", &["x"])]);
+        let looping = function("loop", &["x"], "again:y:0", vec![node("again", "loop", &["x"])]);
+        graph.library.as_mut().unwrap().function.push(looping);
+        let mut nodes = graph.node.clone();
+        assert!(inline_calls(&mut nodes, &library(&graph)).is_err());
+    }
+}
@@ -1,11 +1,38 @@
-use crate::tfpb::tensorflow::{GraphDef, NodeDef, SavedModel};
+use crate::function::FunctionLibrary;
+use crate::tfpb::tensorflow::{GraphDef, NodeDef, SavedModel, SignatureDef, TensorInfo};
 use prost::Message;
+use std::convert::TryInto;
 use std::{fs, path};
 use tract_hir::internal::*;
 
 #[derive(Default)]
 pub struct ParsingContext {
     pub node_output_arities: HashMap<String, usize>,
+    pub op_register: TfOpRegister,
+    pub library: Arc<FunctionLibrary>,
+}
+
+impl ParsingContext {
+    /// Parse a function from the graph library as a model whose inputs are
+    /// the function arguments and outputs the function results.
+    pub fn parse_function(&self, name: &str) -> TractResult<InferenceModel> {
+        let func =
+            self.library.get(name).with_context(|| format!("Function {} not found", name))?;
+        let (graph, results) = crate::function::function_graph(func, &self.library)?;
+        let tf = Tensorflow { op_register: self.op_register.clone() };
+        let TfModelAndExtensions(mut model, _) = tf
+            .parse_graph_with_library(&graph, self.library.clone())
+            .with_context(|| format!("Parsing function {}", name))?;
+        let outputs = results
+            .iter()
+            .map(|r| {
+                let (node, slot) = Tensorflow::parse_input(r)?;
+                Ok(OutletId::new(model.node_id_by_name(node)?, slot))
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        model.set_output_outlets(&outputs)?;
+        Ok(model)
+    }
 }
 
 #[derive(Clone, Default)]
@@ -50,6 +77,7 @@ impl TfModelExtensions {
                 }
             }
         }
+        crate::ops::control_flow::lower_while_loops(&mut original)?;
         Ok(original)
     }
 }
@@ -115,12 +143,232 @@ impl Tensorflow {
         Ok(saved.meta_graphs.remove(0).graph_def.unwrap())
     }
 
+    /// Read a SavedModel directory.
+    ///
+    /// Picks the meta graph matching all `tags` (typically `["serve"]`) and
+    /// replaces variables by constants holding their values from the
+    /// `variables/variables` checkpoint. Returns this frozen-like graph and
+    /// the requested `signature` (typically "serving_default"). The signature
+    /// is None if the meta graph has no signatures at all, otherwise the graph
+    /// is pruned to the nodes the signature needs (dropping the saver and
+    /// restore functions of TF2 SavedModels).
+    pub fn read_saved_model_dir(
+        &self,
+        dir: impl AsRef<path::Path>,
+        tags: &[&str],
+        signature: &str,
+    ) -> TractResult<(GraphDef, Option<SignatureDef>)> {
+        let dir = dir.as_ref();
+        let saved_model = dir.join("saved_model.pb");
+        let saved = self
+            .open_saved_model(&mut fs::File::open(&saved_model)?)
+            .with_context(|| format!("Reading {:?}", saved_model))?;
+        let mut meta_graph = saved
+            .meta_graphs
+            .into_iter()
+            .find(|mg| {
+                let found = mg.meta_info_def.as_ref().map(|i| &*i.tags).unwrap_or(&[]);
+                tags.iter().all(|t| found.iter().any(|f| f == t))
+            })
+            .with_context(|| format!("No meta graph tagged with {:?} in {:?}", tags, dir))?;
+        let mut graph = meta_graph.graph_def.take().context("Meta graph without graph")?;
+        let variables = dir.join("variables").join("variables");
+        if variables.with_extension("index").exists() {
+            let bundle = crate::checkpoint::TensorBundle::open(&variables)?;
+            Self::restore_variables(&mut graph, &bundle)?;
+        }
+        let signature_def = if meta_graph.signature_def.len() > 0 {
+            let found = meta_graph.signature_def.keys().cloned().collect::<Vec<_>>();
+            let signature_def = meta_graph.signature_def.remove(signature).with_context(|| {
+                format!("No signature {} in meta graph, found: {:?}", signature, found)
+            })?;
+            Self::prune(&mut graph, &signature_def)?;
+            Some(signature_def)
+        } else {
+            None
+        };
+        Ok((graph, signature_def))
+    }
+
+    /// Keep only the signature inputs and the nodes the signature outputs
+    /// depend on (through data or control dependencies).
+    fn prune(graph: &mut GraphDef, signature: &SignatureDef) -> TractResult<()> {
+        use crate::tfpb::tensorflow::tensor_info::Encoding;
+        let mut todo = vec![];
+        for info in signature.inputs.values().chain(signature.outputs.values()) {
+            if let Some(Encoding::Name(name)) = &info.encoding {
+                todo.push(Self::parse_input(name)?.0.to_string());
+            }
+        }
+        let nodes: HashMap<&str, &NodeDef> = graph.node.iter().map(|n| (&*n.name, n)).collect();
+        let mut kept = std::collections::HashSet::new();
+        while let Some(name) = todo.pop() {
+            if kept.contains(&name) {
+                continue;
+            }
+            if let Some(node) = nodes.get(&*name) {
+                for input in &node.input {
+                    todo.push(Self::parse_input(input)?.0.to_string());
+                }
+            }
+            kept.insert(name);
+        }
+        graph.node.retain(|n| kept.contains(&n.name));
+        Ok(())
+    }
+
+    /// Load a SavedModel directory (see `read_saved_model_dir`).
+    ///
+    /// The signature inputs and outputs, ordered by signature key, are used
+    /// as model inputs and outputs. Without signature, they are guessed as for
+    /// frozen models.
+    pub fn parse_saved_model_dir(
+        &self,
+        dir: impl AsRef<path::Path>,
+        tags: &[&str],
+        signature: &str,
+    ) -> TractResult<(GraphDef, TfModelAndExtensions)> {
+        let (graph, signature_def) = self.read_saved_model_dir(dir, tags, signature)?;
+        let mut model = self.parse_graph(&graph)?;
+        if let Some(signature_def) = signature_def {
+            Self::apply_signature(&mut model.0, &signature_def)?;
+        }
+        Ok((graph, model))
+    }
+
+    /// Replace variables nodes for which the checkpoint has a value by Const
+    /// nodes. Assign nodes to these variables become no-op Identity nodes,
+    /// AssignVariableOp nodes (resource variables) become NoOp nodes. Reading
+    /// a resource variable (ReadVariableOp) is an Identity.
+    pub fn restore_variables(
+        graph: &mut GraphDef,
+        bundle: &crate::checkpoint::TensorBundle,
+    ) -> TractResult<()> {
+        let keys = bundle.variable_keys()?;
+        let mut restored = std::collections::HashSet::new();
+        for node in &mut graph.node {
+            if node.op != "VariableV2" && node.op != "VarHandleOp" {
+                continue;
+            }
+            let name = match node.get_attr_opt_str("shared_name")? {
+                Some(shared) if shared != "" => shared,
+                _ => node.name.clone(),
+            };
+            let key = keys.get(&name).unwrap_or(&name);
+            if !bundle.contains(key) {
+                continue;
+            }
+            let value = bundle
+                .tensor(key)
+                .with_context(|| format!("Restoring variable {} from checkpoint", node.name))?;
+            let dt: crate::tfpb::tensorflow::DataType = value.datum_type().try_into()?;
+            let value: crate::tfpb::tensorflow::TensorProto = (&value).try_into()?;
+            node.op = "Const".into();
+            node.attr.clear();
+            node.attr.insert("dtype".into(), dt.into());
+            node.attr.insert("value".into(), value.into());
+            restored.insert(node.name.clone());
+        }
+        for node in &mut graph.node {
+            if node.op == "Assign"
+                && node.input.len() > 1
+                && restored.contains(Self::parse_input(&node.input[0])?.0)
+            {
+                node.op = "Identity".into();
+                node.attr.clear();
+                node.input.remove(1);
+            } else if node.op == "AssignVariableOp"
+                && node.input.len() > 1
+                && restored.contains(Self::parse_input(&node.input[0])?.0)
+            {
+                node.op = "NoOp".into();
+                node.attr.clear();
+                node.input.retain(|i| i.starts_with("^"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Use a signature inputs and outputs as model inputs and outputs.
+    pub fn apply_signature(
+        model: &mut InferenceModel,
+        signature: &SignatureDef,
+    ) -> TractResult<()> {
+        fn outlet(model: &InferenceModel, info: &TensorInfo) -> TractResult<OutletId> {
+            use crate::tfpb::tensorflow::tensor_info::Encoding;
+            let name = match &info.encoding {
+                Some(Encoding::Name(name)) => name,
+                _ => bail!("Only dense tensors are supported in signatures, got {:?}", info),
+            };
+            let (node, slot) = Tensorflow::parse_input(name)?;
+            Ok(OutletId::new(model.node_by_name(node)?.id, slot))
+        }
+        let mut inputs: Vec<_> = signature.inputs.iter().collect();
+        inputs.sort_by_key(|pair| pair.0);
+        let mut input_outlets = tvec!();
+        for (_, info) in inputs {
+            let outlet = outlet(model, info)?;
+            let mut fact = InferenceFact::default();
+            if let Some(dt) = crate::tfpb::tensorflow::DataType::from_i32(info.dtype) {
+                if let Ok(dt) = dt.try_into() {
+                    fact = fact.with_datum_type(dt);
+                }
+            }
+            if let Some(shape) = info.tensor_shape.as_ref().filter(|s| !s.unknown_rank) {
+                fact = fact.with_shape(ShapeFactoid::closed(
+                    shape
+                        .dim
+                        .iter()
+                        .map(|d| {
+                            if d.size < 0 {
+                                GenericFactoid::Any
+                            } else {
+                                GenericFactoid::Only(d.size.to_dim())
+                            }
+                        })
+                        .collect(),
+                ));
+            }
+            let fact = model.outlet_fact(outlet)?.unify(&fact)?;
+            model.set_outlet_fact(outlet, fact)?;
+            input_outlets.push(outlet);
+        }
+        model.set_input_outlets(&input_outlets)?;
+        let mut outputs: Vec<_> = signature.outputs.iter().collect();
+        outputs.sort_by_key(|pair| pair.0);
+        let output_outlets = outputs
+            .iter()
+            .map(|(_, info)| outlet(model, info))
+            .collect::<TractResult<TVec<_>>>()?;
+        model.set_output_outlets(&output_outlets)?;
+        Ok(())
+    }
+
     pub fn parse_graph(&self, graph: &GraphDef) -> TractResult<TfModelAndExtensions> {
+        let library = crate::function::library(graph);
+        if library.len() > 0 {
+            let mut graph = graph.clone();
+            crate::function::inline_calls(&mut graph.node, &library)?;
+            self.parse_graph_with_library(&graph, Arc::new(library))
+        } else {
+            self.parse_graph_with_library(graph, Arc::new(library))
+        }
+    }
+
+    fn parse_graph_with_library(
+        &self,
+        graph: &GraphDef,
+        library: Arc<FunctionLibrary>,
+    ) -> TractResult<TfModelAndExtensions> {
         use crate::ops::control_flow as cf;
 
         let mut model = InferenceModel::default();
         let mut inputs = tvec!();
-        let mut context = ParsingContext::default();
+        let mut context = ParsingContext {
+            op_register: self.op_register.clone(),
+            library,
+            ..ParsingContext::default()
+        };
         let mut control_inputs = vec![];
 
         // compute min output arity for all nodes
@@ -235,7 +483,12 @@ impl Tensorflow {
 
 impl Framework<GraphDef, InferenceModel> for Tensorflow {
     /// This method will try to read as frozen model, then as a saved model.
+    /// Directories are read as SavedModel directories (see
+    /// `parse_saved_model_dir`).
     fn proto_model_for_path(&self, r: impl AsRef<path::Path>) -> TractResult<GraphDef> {
+        if r.as_ref().is_dir() {
+            return Ok(self.read_saved_model_dir(r, &["serve"], "serving_default")?.0);
+        }
         self.read_frozen_model(&mut fs::File::open(r.as_ref())?)
             .or_else(|_| self.read_saved_model(&mut fs::File::open(r.as_ref())?))
     }
@@ -249,4 +502,128 @@ impl Framework<GraphDef, InferenceModel> for Tensorflow {
     fn model_for_proto_model(&self, graph: &GraphDef) -> TractResult<InferenceModel> {
         Ok(self.parse_graph(graph)?.0)
     }
+
+    fn model_for_path(&self, p: impl AsRef<path::Path>) -> TractResult<InferenceModel> {
+        if p.as_ref().is_dir() {
+            Ok(self.parse_saved_model_dir(p, &["serve"], "serving_default")?.1 .0)
+        } else {
+            let proto = self.proto_model_for_path(p)?;
+            self.model_for_proto_model(&proto)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tfpb;
+    use crate::tfpb::tensorflow::meta_graph_def::MetaInfoDef;
+    use crate::tfpb::tensorflow::tensor_info::Encoding;
+    use crate::tfpb::tensorflow::DataType::DtFloat;
+    use crate::tfpb::tensorflow::{MetaGraphDef, TensorProto, TensorShapeProto};
+
+    fn tensor_info(name: &str) -> TensorInfo {
+        TensorInfo {
+            encoding: Some(Encoding::Name(name.into())),
+            dtype: DtFloat as i32,
+            tensor_shape: None,
+        }
+    }
+
+    #[test]
+    fn saved_model_dir() -> TractResult<()> {
+        let dir = std::env::temp_dir().join(format!("tract-tf-saved-{}", std::process::id()));
+        fs::create_dir_all(dir.join("variables"))?;
+        let zeros: TensorProto = (&tensor1(&[0f32, 0.0])).try_into()?;
+        let graph = tfpb::graph()
+            .node(tfpb::node().name("x").op("Placeholder").attr("dtype", DtFloat))
+            .node(
+                tfpb::node()
+                    .name("w")
+                    .op("VariableV2")
+                    .attr("dtype", DtFloat)
+                    .attr("shape", TensorShapeProto::default())
+                    .attr("shared_name", "")
+                    .attr("container", ""),
+            )
+            .node(
+                tfpb::node().name("w/init").op("Const").attr("dtype", DtFloat).attr("value", zeros),
+            )
+            .node(tfpb::node().name("w/Assign").op("Assign").input("w").input("w/init"))
+            .node(tfpb::node().name("w/read").op("Identity").input("w"))
+            .node(tfpb::node().name("mul").op("Mul").attr("T", DtFloat).input("x").input("w/read"));
+        let mut signature = SignatureDef::default();
+        signature.inputs.insert("x".into(), tensor_info("x:0"));
+        signature.outputs.insert("y".into(), tensor_info("mul"));
+        let mut meta_graph = MetaGraphDef::default();
+        meta_graph.meta_info_def =
+            Some(MetaInfoDef { tags: vec!["serve".into()], ..Default::default() });
+        meta_graph.graph_def = Some(graph);
+        meta_graph.signature_def.insert("serving_default".into(), signature);
+        let saved = SavedModel { meta_graphs: vec![meta_graph], ..Default::default() };
+        let mut buf = vec![];
+        saved.encode(&mut buf)?;
+        fs::write(dir.join("saved_model.pb"), buf)?;
+        crate::checkpoint::test::write_bundle(
+            &dir.join("variables").join("variables"),
+            &[("w", tensor1(&[3f32, 4.0]))],
+        )?;
+
+        let tf = crate::tensorflow();
+        let model = tf.model_for_path(&dir)?;
+        assert_eq!(model.input_outlets()?, &[OutletId::new(model.node_id_by_name("x")?, 0)]);
+        assert_eq!(model.output_outlets()?, &[OutletId::new(model.node_id_by_name("mul")?, 0)]);
+        assert!(model.node_by_name("w")?.op_is::<tract_hir::ops::konst::Const>());
+        let mut model = model;
+        model.set_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), tvec!(2)))?;
+        let outputs = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 2.0])))?;
+        assert_eq!(outputs[0], rctensor1(&[3f32, 8.0]));
+
+        assert!(tf.parse_saved_model_dir(&dir, &["train"], "serving_default").is_err());
+        assert!(tf.parse_saved_model_dir(&dir, &["serve"], "classify").is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn restore_resource_variables() -> TractResult<()> {
+        let dir = std::env::temp_dir().join(format!("tract-tf-resource-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let zeros: TensorProto = (&tensor1(&[0f32, 0.0])).try_into()?;
+        let mut graph = tfpb::graph()
+            .node(
+                tfpb::node()
+                    .name("w")
+                    .op("VarHandleOp")
+                    .attr("dtype", DtFloat)
+                    .attr("shape", TensorShapeProto::default())
+                    .attr("shared_name", "w"),
+            )
+            .node(
+                tfpb::node().name("w/init").op("Const").attr("dtype", DtFloat).attr("value", zeros),
+            )
+            .node(
+                tfpb::node()
+                    .name("w/Assign")
+                    .op("AssignVariableOp")
+                    .attr("dtype", DtFloat)
+                    .input("w")
+                    .input("w/init"),
+            )
+            .node(tfpb::node().name("w/Read").op("ReadVariableOp").input("w").input("^w/Assign"));
+        let prefix = dir.join("variables");
+        crate::checkpoint::test::write_bundle(&prefix, &[("w", tensor1(&[3f32, 4.0]))])?;
+        let bundle = crate::checkpoint::TensorBundle::open(&prefix)?;
+        Tensorflow::restore_variables(&mut graph, &bundle)?;
+        assert_eq!(graph.node[0].op, "Const");
+        assert_eq!(graph.node[2].op, "NoOp");
+        assert!(graph.node[2].input.is_empty());
+
+        let mut model = crate::tensorflow().model_for_proto_model(&graph)?;
+        model.set_output_names(&["w/Read"])?;
+        let outputs = model.into_runnable()?.run(tvec!())?;
+        assert_eq!(outputs[0], rctensor1(&[3f32, 4.0]));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,49 @@
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+use tract_hir::internal::*;
+
+pub fn identity_n(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    Ok(expand(IdentityN(pb.input.iter().filter(|i| !i.starts_with("^")).count())))
+}
+
+#[derive(Debug, Clone, new, Hash)]
+pub struct IdentityN(pub usize);
+
+impl_dyn_hash!(IdentityN);
+
+impl Expansion for IdentityN {
+    fn name(&self) -> Cow<str> {
+        "IdentityN".into()
+    }
+
+    op_tf!();
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.0)
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, self.0)?;
+        check_output_arity(&outputs, self.0)?;
+        for (i, o) in inputs.iter().zip(outputs.iter()) {
+            s.equals(&i.datum_type, &o.datum_type)?;
+            s.equals(&i.shape, &o.shape)?;
+            s.equals(&i.value, &o.value)?;
+        }
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        _prefix: &str,
+        _model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        Ok(inputs.into())
+    }
+}
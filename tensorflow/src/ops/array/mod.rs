@@ -9,28 +9,55 @@ mod expand_dims;
 mod fill;
 mod gather;
 mod gather_v2;
+mod identity_n;
+mod mirror_pad;
+mod one_hot;
 mod pack;
 mod pad;
-mod range;
+mod split;
 mod squeeze;
 mod transpose;
+mod unpack;
+mod where_op;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("ConcatV2", concatv2::build);
+    reg.insert("DepthToSpace", depth_to_space);
     reg.insert("ExpandDims", expand_dims::build);
     reg.insert("Fill", fill::fill);
     reg.insert("GatherNd", gather::gather_nd);
     reg.insert("GatherV2", gather_v2::gather_v2);
+    reg.insert("IdentityN", identity_n::identity_n);
+    reg.insert("MirrorPad", mirror_pad::mirror_pad);
+    reg.insert("OneHot", one_hot::one_hot);
     reg.insert("Pack", pack::pack);
     reg.insert("Pad", pad::pad);
-    reg.insert("Range", range::range);
+    reg.insert("Range", |_, _| Ok(expand(tract_hir::ops::array::Range::default())));
     reg.insert("Reshape", |_, _| Ok(expand(tract_hir::ops::array::Reshape::new())));
     reg.insert("Shape", |_, _| Ok(expand(tract_hir::ops::array::Shape::new(DatumType::I32))));
     reg.insert("Slice", slice);
+    reg.insert("SpaceToDepth", space_to_depth);
+    reg.insert("Split", split::split);
+    reg.insert("SplitV", split::split_v);
     reg.insert("Squeeze", squeeze::squeeze);
     reg.insert("StridedSlice", strided_slice);
     reg.insert("Tile", |_, _| Ok(expand(::tract_hir::ops::array::Tile)));
     reg.insert("Transpose", transpose::transpose);
+    reg.insert("Unpack", unpack::unpack);
+    reg.insert("Where", where_op::where_op);
+}
+
+fn depth_to_space(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    use tract_hir::ops::array::{DepthToSpace, DepthToSpaceMode};
+    let block_size = pb.get_attr_int("block_size")?;
+    let data_format = crate::ops::nn::data_format(pb)?;
+    Ok(expand(DepthToSpace::new(block_size, DepthToSpaceMode::DepthColumnRow, data_format)))
+}
+
+fn space_to_depth(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let block_size = pb.get_attr_int("block_size")?;
+    let data_format = crate::ops::nn::data_format(pb)?;
+    Ok(expand(tract_hir::ops::array::SpaceToDepth::new(block_size, data_format)))
 }
 
 fn strided_slice(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
@@ -0,0 +1,90 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::logic::{equals, Iff};
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn one_hot(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let axis = pb.get_attr_opt_int("axis")?.unwrap_or(-1);
+    Ok(expand(OneHot::new(axis)))
+}
+
+/// Inputs are indices, depth, on value and off value.
+///
+/// Unlike ONNX, negative indices are not wrapped: as any out of range index,
+/// they give a vector of off values. So this is wired as a comparison of the
+/// indices with a range, and an Iff.
+#[derive(Debug, Clone, new, Hash)]
+pub struct OneHot {
+    axis: i64,
+}
+
+impl_dyn_hash!(OneHot);
+
+impl OneHot {
+    fn axis(&self, input_rank: usize) -> usize {
+        (if self.axis < 0 { self.axis + input_rank as i64 + 1 } else { self.axis }) as usize
+    }
+}
+
+impl Expansion for OneHot {
+    fn name(&self) -> Cow<str> {
+        "OneHot".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 4)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[1].rank, 0)?;
+        s.equals(&inputs[2].rank, 0)?;
+        s.equals(&inputs[3].rank, 0)?;
+        s.equals(&inputs[2].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[3].datum_type, &outputs[0].datum_type)?;
+        s.equals(inputs[0].rank.bex() + 1, &outputs[0].rank)?;
+        s.given_2(&inputs[0].shape, &inputs[1].value, move |s, shape, depth| {
+            let mut shape = shape.clone();
+            shape.insert(self.axis(shape.len()), depth.cast_to_scalar::<i64>()?.to_dim());
+            s.equals(&outputs[0].shape, shape)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let konst = |ix: usize| -> TractResult<Arc<Tensor>> {
+            target
+                .outlet_fact(inputs[ix])?
+                .konst
+                .clone()
+                .with_context(|| format!("Expect input #{} of OneHot to be a constant", ix))
+        };
+        let dim = konst(1)?.cast_to_scalar::<i64>()? as usize;
+        let on = konst(2)?.into_tensor();
+        let off = konst(3)?.into_tensor();
+        let indices = target.outlet_fact(inputs[0])?.clone();
+        let axis = self.axis(indices.rank());
+        let unit_shape = tvec!(1; indices.rank() + 1);
+        let mut range_shape = unit_shape.clone();
+        range_shape[axis] = dim;
+        let range = tract_ndarray::Array1::from_shape_fn(dim, |i| i as i64).into_tensor();
+        let range = range.cast_to_dt(indices.datum_type)?.into_owned().into_shape(&range_shape)?;
+        let range = target.add_const(format!("{}.range", prefix), range)?;
+        let on = target.add_const(format!("{}.on", prefix), on.into_shape(&unit_shape)?)?;
+        let off = target.add_const(format!("{}.off", prefix), off.into_shape(&unit_shape)?)?;
+        let wire =
+            target.wire_node(format!("{}.add-axis", prefix), AxisOp::Add(axis), &inputs[0..1])?;
+        let test =
+            target.wire_node(format!("{}.eq", prefix), equals::bin_typed(), &[wire[0], range])?;
+        target.wire_node(prefix, Iff, &[test[0], on, off])
+    }
+}
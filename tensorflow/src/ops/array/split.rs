@@ -0,0 +1,166 @@
+use tract_hir::internal::*;
+use tract_hir::ops::array::Split as HirSplit;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn split(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let num_split = pb.get_attr_int("num_split")?;
+    Ok(expand(Split::new(num_split)))
+}
+
+pub fn split_v(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let num_split = pb.get_attr_int("num_split")?;
+    Ok(expand(SplitV::new(num_split)))
+}
+
+fn normalize_axis(axis: &Tensor, rank: usize) -> TractResult<usize> {
+    let axis = axis.cast_to_scalar::<i64>()?;
+    Ok(if axis < 0 { axis + rank as i64 } else { axis } as usize)
+}
+
+/// Split in equal parts. Inputs are the axis and the tensor.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Split {
+    num_split: usize,
+}
+
+impl_dyn_hash!(Split);
+
+impl Expansion for Split {
+    fn name(&self) -> Cow<str> {
+        "Split".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, self.num_split)?;
+        s.equals(&inputs[0].rank, 0)?;
+        for output in outputs {
+            s.equals(&output.datum_type, &inputs[1].datum_type)?;
+            s.equals(&output.rank, &inputs[1].rank)?;
+        }
+        s.given_2(&inputs[1].shape, &inputs[0].value, move |s, shape, axis| {
+            let axis = normalize_axis(&axis, shape.len())?;
+            let mut shape = shape.clone();
+            shape[axis] = shape[axis].clone() / self.num_split;
+            for output in outputs {
+                s.equals(&output.shape, shape.clone())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.num_split)
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let axis = target.outlet_fact(inputs[0])?.konst.clone().context("Expect constant axis")?;
+        let axis = normalize_axis(&axis, target.outlet_fact(inputs[1])?.rank())?;
+        HirSplit::new(axis as isize, self.num_split, None).wire(prefix, target, &inputs[1..2])
+    }
+}
+
+/// Split in parts of given sizes, one of them may be -1. Inputs are the
+/// tensor, the sizes and the axis.
+#[derive(Debug, Clone, new, Hash)]
+pub struct SplitV {
+    num_split: usize,
+}
+
+impl_dyn_hash!(SplitV);
+
+impl SplitV {
+    fn sizes(&self, dim: &TDim, sizes: &Tensor) -> TractResult<Vec<usize>> {
+        let sizes = sizes.cast_to::<i64>()?;
+        let sizes = sizes.as_slice::<i64>()?;
+        if sizes.len() != self.num_split {
+            bail!("Expected {} sizes, got {:?}", self.num_split, sizes);
+        }
+        if sizes.iter().any(|&s| s < 0) {
+            let dim = dim.to_usize()?;
+            let known: i64 = sizes.iter().filter(|&&s| s >= 0).sum();
+            Ok(sizes
+                .iter()
+                .map(|&s| if s < 0 { dim - known as usize } else { s as usize })
+                .collect())
+        } else {
+            Ok(sizes.iter().map(|&s| s as usize).collect())
+        }
+    }
+}
+
+impl Expansion for SplitV {
+    fn name(&self) -> Cow<str> {
+        "SplitV".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, self.num_split)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[2].rank, 0)?;
+        for output in outputs {
+            s.equals(&output.datum_type, &inputs[0].datum_type)?;
+            s.equals(&output.rank, &inputs[0].rank)?;
+        }
+        s.given_3(
+            &inputs[0].shape,
+            &inputs[1].value,
+            &inputs[2].value,
+            move |s, shape, sizes, axis| {
+                let axis = normalize_axis(&axis, shape.len())?;
+                let sizes = self.sizes(&shape[axis], &sizes)?;
+                for (output, size) in outputs.iter().zip(sizes.iter()) {
+                    let mut shape = shape.clone();
+                    shape[axis] = size.to_dim();
+                    s.equals(&output.shape, shape)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.num_split)
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let sizes =
+            target.outlet_fact(inputs[1])?.konst.clone().context("Expect constant sizes")?;
+        let axis = target.outlet_fact(inputs[2])?.konst.clone().context("Expect constant axis")?;
+        let input = target.outlet_fact(inputs[0])?.clone();
+        let axis = normalize_axis(&axis, input.rank())?;
+        let sizes = self.sizes(&input.shape[axis], &sizes)?;
+        HirSplit::new(axis as isize, self.num_split, Some(sizes)).wire(
+            prefix,
+            target,
+            &inputs[0..1],
+        )
+    }
+}
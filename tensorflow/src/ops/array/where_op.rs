@@ -0,0 +1,126 @@
+use tract_hir::internal::*;
+use tract_ndarray::Dimension;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn where_op(_ctx: &ParsingContext, _pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    Ok(Box::new(Where))
+}
+
+/// Coordinates of the true (or non-zero) elements of the input, as a
+/// [count, rank] i64 tensor.
+///
+/// The output shape depends on the input values: it is typed as a core
+/// `NonZero` with a fresh count symbol, or folded when its input is constant.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct Where;
+
+impl_dyn_hash!(Where);
+
+impl Where {
+    unsafe fn eval_t<T: Datum + tract_num_traits::Zero>(input: &Tensor) -> TractResult<Tensor> {
+        let count = input.as_slice_unchecked::<T>().iter().filter(|d| !d.is_zero()).count();
+        let view = input.to_array_view_unchecked::<T>();
+        let mut output = Tensor::uninitialized::<i64>(&[count, input.rank()])?;
+        let mut view_mut: tract_ndarray::ArrayViewMut2<i64> =
+            output.to_array_view_mut_unchecked::<i64>().into_dimensionality().unwrap();
+        for (i, (coords, _)) in
+            view.indexed_iter().filter(|(_, value)| !value.is_zero()).enumerate()
+        {
+            view_mut
+                .index_axis_mut(tract_ndarray::Axis(0), i)
+                .assign(&coords.as_array_view().map(|d| *d as i64));
+        }
+        Ok(output)
+    }
+}
+
+impl Op for Where {
+    fn name(&self) -> Cow<str> {
+        "Where".into()
+    }
+
+    op_tf!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for Where {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        unsafe {
+            let input = args_1!(inputs);
+            let output = if input.datum_type() == bool::datum_type() {
+                Self::eval_t::<u8>(input.as_ref())?
+            } else {
+                dispatch_numbers!(Self::eval_t(input.datum_type())(input.as_ref()))?
+            };
+            Ok(tvec!(output.into_arc_tensor()))
+        }
+    }
+}
+
+impl InferenceRulesOp for Where {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, i64::datum_type())?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[1], inputs[0].rank.bex().to_dim())?;
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        if let Some(value) = node.outputs[0].fact.value.concretize() {
+            return Ok(tvec!(target.add_const(&*node.name, value)?));
+        }
+        let count = target.symbol_table.new_symbol("nonzero");
+        target.wire_node(
+            &*node.name,
+            tract_hir::tract_core::ops::array::NonZero::new(count),
+            &[mapping[&node.inputs[0]]],
+        )
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn where_bool() {
+        let input = tensor2(&[[true, false], [false, true]]);
+        let output = Where.eval(tvec!(input.into_arc_tensor())).unwrap();
+        assert_eq!(*output[0], tensor2(&[[0i64, 0], [1, 1]]));
+    }
+
+    #[test]
+    fn where_typed() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let input =
+            model.add_source("input", InferenceFact::dt_shape(bool::datum_type(), &[2, 2]))?;
+        let output = model.wire_node("where", Where, &[input])?;
+        model.set_output_outlets(&output)?;
+        let model = model.into_typed()?;
+        assert_eq!(model.output_fact(0)?.shape[1], 2.to_dim());
+        let output = model.into_runnable()?.run(tvec!(tensor2(&[[false, true], [true, true]])))?;
+        assert_eq!(*output[0], tensor2(&[[0i64, 1], [1, 0], [1, 1]]));
+        Ok(())
+    }
+}
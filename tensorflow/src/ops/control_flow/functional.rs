@@ -0,0 +1,497 @@
+use tract_hir::internal::*;
+use tract_hir::ops::binary::InferenceBinOp;
+use tract_hir::ops::logic::InferenceIf;
+use tract_hir::ops::scan::{InferenceScan, InputMapping, OutputMapping, StateInitializer};
+
+use crate::model::{ParsingContext, TfOpRegister};
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn register_all_ops(reg: &mut TfOpRegister) {
+    reg.insert("If", if_then_else);
+    reg.insert("StatelessIf", if_then_else);
+    reg.insert("While", while_loop);
+    reg.insert("StatelessWhile", while_loop);
+}
+
+fn data_inputs(pb: &NodeDef) -> usize {
+    pb.input.iter().filter(|i| !i.starts_with("^")).count()
+}
+
+fn check_function_arity(
+    pb: &NodeDef,
+    attr: &str,
+    model: &InferenceModel,
+    inputs: usize,
+) -> TractResult<()> {
+    if model.input_outlets()?.len() != inputs {
+        bail!(
+            "{} ({}) gets {} inputs but {} function expects {}",
+            pb.name,
+            pb.op,
+            inputs,
+            attr,
+            model.input_outlets()?.len()
+        )
+    }
+    Ok(())
+}
+
+fn if_then_else(ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let then_body = ctx.parse_function(pb.get_attr_func("then_branch")?)?;
+    let else_body = ctx.parse_function(pb.get_attr_func("else_branch")?)?;
+    let inputs = data_inputs(pb) - 1;
+    check_function_arity(pb, "then_branch", &then_body, inputs)?;
+    check_function_arity(pb, "else_branch", &else_body, inputs)?;
+    let mapping: Vec<usize> = (1..=inputs).collect();
+    Ok(Box::new(InferenceIf::new(then_body, mapping.clone(), else_body, mapping)))
+}
+
+fn while_loop(ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let cond = ctx.parse_function(pb.get_attr_func("cond")?)?;
+    let body = ctx.parse_function(pb.get_attr_func("body")?)?;
+    check_function_arity(pb, "cond", &cond, data_inputs(pb))?;
+    check_function_arity(pb, "body", &body, data_inputs(pb))?;
+    if body.output_outlets()?.len() != data_inputs(pb) {
+        bail!("{} body must return as many values as loop variables", pb.name)
+    }
+    Ok(Box::new(While { cond, body }))
+}
+
+/// TensorFlow 2 functional while loop.
+///
+/// It can be evaluated as is, but translation to a typed model requires the
+/// loop to be turned into a Scan, which is done at incorporation time when
+/// the iteration count can be derived from the condition: a conjunction of
+/// `counter < limit` or `counter <= limit` where the counter is a loop
+/// variable incremented by a constant step in the body, and the limit and the
+/// counter initial value are constant.
+#[derive(Debug, Clone, Hash)]
+pub struct While {
+    pub cond: InferenceModel,
+    pub body: InferenceModel,
+}
+
+impl_dyn_hash!(While);
+
+impl Op for While {
+    fn name(&self) -> Cow<str> {
+        "While".into()
+    }
+
+    op_tf!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for While {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let cond = SimplePlan::new(&self.cond)?;
+        let body = SimplePlan::new(&self.body)?;
+        let mut vars = inputs;
+        loop {
+            let tensors = || vars.iter().map(|t| t.clone().into_tensor()).collect();
+            if !cond.run(tensors())?[0].cast_to_scalar::<bool>()? {
+                return Ok(vars);
+            }
+            vars = body.run(tensors())?;
+        }
+    }
+}
+
+impl InferenceRulesOp for While {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_output_arity(&outputs, inputs.len())?;
+        for (i, o) in inputs.iter().zip(outputs.iter()) {
+            s.equals(&i.datum_type, &o.datum_type)?;
+        }
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.body.output_outlets()?.len())
+    }
+
+    fn incorporate(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+    ) -> TractResult<Option<InferenceModelPatch>> {
+        let outer: TVec<InferenceFact> = node
+            .inputs
+            .iter()
+            .map(|i| model.outlet_fact(*i).map(|f| f.clone()))
+            .collect::<TractResult<_>>()?;
+        let iters = if let Some(iters) = self.iteration_count(&outer)? {
+            iters
+        } else {
+            return Ok(None);
+        };
+        let mut patch = InferenceModelPatch::default();
+        let mut wires = node
+            .inputs
+            .iter()
+            .map(|i| patch.tap_model(model, *i))
+            .collect::<TractResult<TVec<_>>>()?;
+        if iters == 0 {
+            for (ix, wire) in wires.iter().enumerate() {
+                patch.shunt_outside(model, OutletId::new(node.id, ix), *wire)?;
+            }
+            return Ok(Some(patch));
+        }
+        // Scan iterates over its scanned inputs: feed it a dummy one with
+        // the right length.
+        let mut body = self.body.clone();
+        body.add_source(
+            format!("{}.iteration", node.name),
+            InferenceFact::dt_shape(i64::datum_type(), tvec!(1)),
+        )?;
+        let vars = node.inputs.len();
+        let mut input_mapping: Vec<InputMapping> = (0..vars)
+            .map(|ix| InputMapping::State { initializer: StateInitializer::FromInput(ix) })
+            .collect();
+        input_mapping.push(InputMapping::Scan { slot: vars, axis: 0, chunk: 1 });
+        let output_mapping = (0..vars)
+            .map(|ix| OutputMapping {
+                state: true,
+                last_value_slot: Some(ix),
+                full_slot: None,
+                axis: 0,
+                chunk: 1,
+                full_dim_hint: None,
+            })
+            .collect();
+        let scan = InferenceScan::new(
+            body,
+            input_mapping,
+            output_mapping,
+            None,
+            true,
+            GenericFactoid::default(),
+        );
+        wires.push(
+            patch.add_const(format!("{}.iterations", node.name), Tensor::zero::<i64>(&[iters])?)?,
+        );
+        let outputs = patch.wire_node(&*node.name, scan, &wires)?;
+        for (ix, output) in outputs.into_iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(node.id, ix), output)?;
+        }
+        Ok(Some(patch))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        _mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let values =
+            node.outputs.iter().map(|o| o.fact.value.concretize()).collect::<Option<TVec<_>>>();
+        if let Some(values) = values {
+            return values
+                .into_iter()
+                .enumerate()
+                .map(|(ix, v)| target.add_const(format!("{}.{}", node.name, ix), v))
+                .collect();
+        }
+        bail!(
+            "While loop {} could not be turned into a Scan: its iteration count can not be derived from its condition",
+            node.name
+        )
+    }
+
+    as_op!();
+}
+
+impl While {
+    /// Derive the number of iterations from the loop condition, given the
+    /// facts about the loop inputs.
+    fn iteration_count(&self, outer: &[InferenceFact]) -> TractResult<Option<usize>> {
+        let vars = outer.len();
+        let body_inputs = self.body.input_outlets()?;
+        let body_outputs = self.body.output_outlets()?;
+        // only variables passed through the body unchanged keep their
+        // initial value along the loop
+        let invariant: Vec<bool> =
+            (0..vars).map(|ix| body_inputs[ix] == body_outputs[ix]).collect();
+        let analysed = |model: &InferenceModel| -> TractResult<InferenceModel> {
+            let mut model = model.clone();
+            for ix in 0..vars {
+                let fact = if invariant[ix] {
+                    outer[ix].clone()
+                } else {
+                    outer[ix].clone().without_value()
+                };
+                model.set_input_fact(ix, fact)?;
+            }
+            model.analyse(false)?;
+            Ok(model)
+        };
+        let cond = analysed(&self.cond)?;
+        let body = analysed(&self.body)?;
+        let value = |model: &InferenceModel, outlet: OutletId| -> Option<i64> {
+            let fact = model.outlet_fact(outlet).ok()?;
+            fact.value.concretize().and_then(|v| v.cast_to_scalar::<i64>().ok())
+        };
+        let mut comparisons = vec![];
+        if !collect_comparisons(&cond, cond.output_outlets()?[0], &mut comparisons) {
+            return Ok(None);
+        }
+        let mut iters: Option<i64> = None;
+        for (counter, limit, inclusive) in comparisons {
+            let counter = skip_identities(&cond, counter);
+            let var = if let Some(var) = cond.input_outlets()?.iter().position(|i| *i == counter) {
+                var
+            } else {
+                return Ok(None);
+            };
+            let (limit, start) = match (value(&cond, limit), outer[var].value.concretize()) {
+                (Some(limit), Some(start)) => (limit, start.cast_to_scalar::<i64>()?),
+                _ => return Ok(None),
+            };
+            let increment = skip_identities(&body, body_outputs[var]);
+            let step = match bin_op_name(body.node(increment.node)).as_deref() {
+                Some("Add") => {
+                    let inputs = &body.node(increment.node).inputs;
+                    let other = if skip_identities(&body, inputs[0]) == body_inputs[var] {
+                        inputs[1]
+                    } else if skip_identities(&body, inputs[1]) == body_inputs[var] {
+                        inputs[0]
+                    } else {
+                        return Ok(None);
+                    };
+                    value(&body, other)
+                }
+                _ => None,
+            };
+            let step = match step {
+                Some(step) if step > 0 => step,
+                _ => return Ok(None),
+            };
+            let count = if inclusive {
+                if limit < start {
+                    0
+                } else {
+                    (limit - start) / step + 1
+                }
+            } else if limit <= start {
+                0
+            } else {
+                (limit - start + step - 1) / step
+            };
+            iters = Some(iters.map(|it| it.min(count)).unwrap_or(count));
+        }
+        Ok(iters.map(|it| it as usize))
+    }
+}
+
+pub(super) fn bin_op_name(node: &InferenceNode) -> Option<String> {
+    node.op_as::<Box<dyn Expansion>>()
+        .and_then(|e| e.as_any().downcast_ref::<InferenceBinOp>())
+        .map(|bin| bin.0.name().to_string())
+}
+
+pub(super) fn skip_identities(model: &InferenceModel, mut outlet: OutletId) -> OutletId {
+    while model.node(outlet.node).op_is::<tract_hir::ops::identity::Identity>() {
+        outlet = model.node(outlet.node).inputs[0];
+    }
+    outlet
+}
+
+// Collect the (counter, limit, inclusive) comparisons whose conjunction
+// makes `outlet`. Returns false if the condition has any other form.
+fn collect_comparisons(
+    model: &InferenceModel,
+    outlet: OutletId,
+    comparisons: &mut Vec<(OutletId, OutletId, bool)>,
+) -> bool {
+    let outlet = skip_identities(model, outlet);
+    let node = model.node(outlet.node);
+    match bin_op_name(node).as_deref() {
+        Some("And") => {
+            collect_comparisons(model, node.inputs[0], comparisons)
+                && collect_comparisons(model, node.inputs[1], comparisons)
+        }
+        Some("Lesser") => {
+            comparisons.push((node.inputs[0], node.inputs[1], false));
+            true
+        }
+        Some("LesserEqual") => {
+            comparisons.push((node.inputs[0], node.inputs[1], true));
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tfpb;
+    use crate::tfpb::tensorflow::op_def::ArgDef;
+    use crate::tfpb::tensorflow::DataType::{self, DtBool, DtFloat, DtInt32};
+    use crate::tfpb::tensorflow::{
+        FunctionDef, FunctionDefLibrary, GraphDef, NameAttrList, NodeDef, OpDef, TensorProto,
+    };
+    use std::convert::TryInto;
+    use tract_hir::internal::*;
+
+    fn konst(name: &str, t: Tensor) -> TractResult<NodeDef> {
+        let dt: DataType = t.datum_type().try_into()?;
+        let t: TensorProto = (&t).try_into()?;
+        Ok(tfpb::node().name(name).op("Const").attr("dtype", dt).attr("value", t))
+    }
+
+    fn node(name: &str, op: &str, inputs: &[&str]) -> NodeDef {
+        inputs.iter().fold(tfpb::node().name(name).op(op), |n, i| n.input(*i))
+    }
+
+    fn func(name: &str) -> NameAttrList {
+        NameAttrList { name: name.to_string(), attr: HashMap::new() }
+    }
+
+    fn function(
+        name: &str,
+        inputs: &[(&str, DataType)],
+        outputs: &[(&str, DataType, &str)],
+        nodes: Vec<NodeDef>,
+    ) -> FunctionDef {
+        let arg = |name: &str, dt: DataType| ArgDef {
+            name: name.to_string(),
+            r#type: dt as i32,
+            ..ArgDef::default()
+        };
+        FunctionDef {
+            signature: Some(OpDef {
+                name: name.to_string(),
+                input_arg: inputs.iter().map(|(n, dt)| arg(n, *dt)).collect(),
+                output_arg: outputs.iter().map(|(n, dt, _)| arg(n, *dt)).collect(),
+                ..OpDef::default()
+            }),
+            node_def: nodes,
+            ret: outputs.iter().map(|(n, _, r)| (n.to_string(), r.to_string())).collect(),
+            ..FunctionDef::default()
+        }
+    }
+
+    fn library() -> TractResult<FunctionDefLibrary> {
+        let add_one = function(
+            "add_one",
+            &[("x", DtFloat)],
+            &[("y", DtFloat, "add:z:0")],
+            vec![konst("one", tensor0(1f32))?, node("add", "Add", &["x", "one:output:0"])],
+        );
+        let double = function(
+            "double",
+            &[("x", DtFloat)],
+            &[("y", DtFloat, "mul:z:0")],
+            vec![konst("two", tensor0(2f32))?, node("mul", "Mul", &["x", "two:output:0"])],
+        );
+        let less_than_four = function(
+            "less_than_four",
+            &[("i", DtInt32), ("acc", DtFloat)],
+            &[("c", DtBool, "less:z:0")],
+            vec![konst("four", tensor0(4i32))?, node("less", "Less", &["i", "four:output:0"])],
+        );
+        let step = function(
+            "step",
+            &[("i", DtInt32), ("acc", DtFloat)],
+            &[("i_next", DtInt32, "next:z:0"), ("acc_next", DtFloat, "call:output:0")],
+            vec![
+                konst("one", tensor0(1i32))?,
+                node("next", "Add", &["i", "one:output:0"]),
+                node("call", "double", &["acc"]),
+            ],
+        );
+        Ok(FunctionDefLibrary {
+            function: vec![add_one, double, less_than_four, step],
+            ..FunctionDefLibrary::default()
+        })
+    }
+
+    fn graph(nodes: Vec<NodeDef>) -> TractResult<GraphDef> {
+        let mut graph = nodes.into_iter().fold(tfpb::graph(), |g, n| g.node(n));
+        graph.library = Some(library()?);
+        Ok(graph)
+    }
+
+    fn run(graph: &GraphDef, output: &str, inputs: TVec<Tensor>) -> TractResult<Arc<Tensor>> {
+        let mut model = crate::tensorflow().model_for_proto_model(graph)?;
+        model.set_output_names(&[output])?;
+        for (ix, input) in inputs.iter().enumerate() {
+            model.set_input_fact(ix, InferenceFact::dt_shape_from_tensor(input))?;
+        }
+        let model = model.into_optimized()?;
+        Ok(model.into_runnable()?.run(inputs)?.remove(0))
+    }
+
+    #[test]
+    fn partitioned_call() -> TractResult<()> {
+        let graph = graph(vec![
+            tfpb::node().name("x").op("Placeholder").attr("dtype", DtFloat),
+            node("call", "PartitionedCall", &["x"]).attr("f", func("add_one")),
+        ])?;
+        assert_eq!(*run(&graph, "call", tvec!(tensor1(&[1f32, 2.])))?, tensor1(&[2f32, 3.]));
+        Ok(())
+    }
+
+    #[test]
+    fn if_then_else() -> TractResult<()> {
+        let graph = graph(vec![
+            tfpb::node().name("cond").op("Placeholder").attr("dtype", DtBool),
+            tfpb::node().name("x").op("Placeholder").attr("dtype", DtFloat),
+            node("if", "StatelessIf", &["cond", "x"])
+                .attr("then_branch", func("double"))
+                .attr("else_branch", func("add_one")),
+        ])?;
+        let x = || tensor1(&[1f32, 2.]);
+        assert_eq!(*run(&graph, "if", tvec!(tensor0(true), x()))?, tensor1(&[2f32, 4.]));
+        assert_eq!(*run(&graph, "if", tvec!(tensor0(false), x()))?, tensor1(&[2f32, 3.]));
+        Ok(())
+    }
+
+    #[test]
+    fn if_then_else_with_constant_condition() -> TractResult<()> {
+        let graph = graph(vec![
+            konst("cond", tensor0(false))?,
+            tfpb::node().name("x").op("Placeholder").attr("dtype", DtFloat),
+            node("if", "If", &["cond", "x"])
+                .attr("then_branch", func("double"))
+                .attr("else_branch", func("add_one")),
+        ])?;
+        let mut model = crate::tensorflow().model_for_proto_model(&graph)?;
+        model.set_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), tvec!(2)))?;
+        let model = model.into_typed()?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<tract_hir::ops::logic::If>()));
+        let result = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 2.])))?;
+        assert_eq!(*result[0], tensor1(&[2f32, 3.]));
+        Ok(())
+    }
+
+    #[test]
+    fn while_to_scan() -> TractResult<()> {
+        let graph = graph(vec![
+            konst("zero", tensor0(0i32))?,
+            tfpb::node().name("x").op("Placeholder").attr("dtype", DtFloat),
+            node("loop", "StatelessWhile", &["zero", "x"])
+                .attr("cond", func("less_than_four"))
+                .attr("body", func("step")),
+        ])?;
+        let mut model = crate::tensorflow().model_for_proto_model(&graph)?;
+        model.set_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), tvec!(2)))?;
+        let loop_id = model.node_id_by_name("loop")?;
+        model.set_output_outlets(&[OutletId::new(loop_id, 1)])?;
+        let model = model.into_typed()?;
+        assert!(model.nodes().iter().any(|n| n.op_is::<tract_hir::ops::scan::Scan>()));
+        let result = model.into_optimized()?.into_runnable()?.run(tvec!(tensor1(&[1f32, 3.])))?;
+        assert_eq!(*result[0], tensor1(&[16f32, 48.]));
+        Ok(())
+    }
+}
@@ -2,7 +2,16 @@ use tract_hir::internal::*;
 
 use crate::model::TfOpRegister;
 
+mod functional;
+mod tensor_array;
+mod while_loop;
+
+pub use functional::While;
+pub use while_loop::lower_while_loops;
+
 pub fn register_all_ops(reg: &mut TfOpRegister) {
+    functional::register_all_ops(reg);
+    tensor_array::register_all_ops(reg);
     reg.insert("Enter", |_, node| {
         Ok(Box::new(LoopGate(LoopGateRole::Enter(node.get_attr_str("frame_name")?))))
     });
@@ -115,5 +124,9 @@ impl InferenceRulesOp for NextIteration {
         Ok(())
     }
 
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok((self.role == NextIterationRole::Source) as usize)
+    }
+
     as_op!();
 }
@@ -0,0 +1,133 @@
+use tract_hir::internal::*;
+
+use crate::model::TfOpRegister;
+
+pub fn register_all_ops(reg: &mut TfOpRegister) {
+    use TensorArrayRole::*;
+    reg.insert("TensorArrayV3", |_, _| Ok(Box::new(TensorArray(Create))));
+    reg.insert("TensorArrayScatterV3", |_, _| Ok(Box::new(TensorArray(Scatter))));
+    reg.insert("TensorArrayReadV3", |_, _| Ok(Box::new(TensorArray(Read))));
+    reg.insert("TensorArrayWriteV3", |_, _| Ok(Box::new(TensorArray(Write))));
+    reg.insert("TensorArrayGatherV3", |_, _| Ok(Box::new(TensorArray(Gather))));
+    reg.insert("TensorArraySizeV3", |_, _| Ok(Box::new(TensorArray(Size))));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Hash)]
+pub enum TensorArrayRole {
+    Create,
+    Scatter,
+    Read,
+    Write,
+    Gather,
+    Size,
+}
+
+/// TensorArray operations are placeholders: they only make sense inside
+/// while loops, which are lowered to Scan (see `super::while_loop`) where
+/// reads and writes become scanned inputs and outputs.
+#[derive(Debug, Clone, Hash)]
+pub struct TensorArray(pub TensorArrayRole);
+
+impl_dyn_hash!(TensorArray);
+
+impl Op for TensorArray {
+    fn name(&self) -> Cow<str> {
+        use TensorArrayRole::*;
+        match self.0 {
+            Create => "TensorArrayV3",
+            Scatter => "TensorArrayScatterV3",
+            Read => "TensorArrayReadV3",
+            Write => "TensorArrayWriteV3",
+            Gather => "TensorArrayGatherV3",
+            Size => "TensorArraySizeV3",
+        }
+        .into()
+    }
+
+    op_tf!();
+    not_a_typed_op!();
+}
+
+impl EvalOp for TensorArray {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _state: &mut SessionState,
+        _id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        bail!("{} is only supported in while loops that can be lowered to Scan", self.name())
+    }
+}
+
+impl InferenceRulesOp for TensorArray {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        _s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        use TensorArrayRole::*;
+        let arity = match self.0 {
+            Create => 1,
+            Size => 2,
+            Read | Gather => 3,
+            Scatter | Write => 4,
+        };
+        check_input_arity(&inputs, arity)?;
+        check_output_arity(&outputs, 1 + (self.0 == Create) as usize)?;
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(if self.0 == TensorArrayRole::Create { 2 } else { 1 })
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::while_loop::test::*;
+    use crate::tfpb;
+    use crate::tfpb::tensorflow::DataType::DtFloat;
+    use tract_hir::internal::*;
+
+    #[test]
+    fn read_write_loop() -> TractResult<()> {
+        let model = preprocessed(&dynamic_rnn_like(false, false)?)?;
+        assert!(model.nodes().iter().any(|n| n.op_is::<tract_hir::ops::scan::InferenceScan>()));
+        let input = tensor2(&[[1f32, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        let outputs = run(model, &["gather"], input)?;
+        assert_eq!(outputs[0], rctensor2(&[[1f32, 4.0], [9.0, 16.0], [25.0, 36.0]]));
+        Ok(())
+    }
+
+    #[test]
+    fn read_write_loop_over_input_length() -> TractResult<()> {
+        let model = preprocessed(&dynamic_rnn_like(false, true)?)?;
+        let outputs = run(model.clone(), &["gather"], tensor2(&[[2f32, 3.0]]))?;
+        assert_eq!(outputs[0], rctensor2(&[[4f32, 9.0]]));
+        let outputs = run(model, &["gather"], tensor2(&[[1f32, 2.0], [3.0, 4.0]]))?;
+        assert_eq!(outputs[0], rctensor2(&[[1f32, 4.0], [9.0, 16.0]]));
+        Ok(())
+    }
+
+    #[test]
+    fn outside_of_a_loop() -> TractResult<()> {
+        let graph = tfpb::graph()
+            .node(tfpb::node().name("x").op("Placeholder").attr("dtype", DtFloat))
+            .node(konst("size", tensor0(1i32))?)
+            .node(konst("index", tensor0(0i32))?)
+            .node(node("ta", "TensorArrayV3", &["size"]))
+            .node(node("write", "TensorArrayWriteV3", &["ta", "index", "x", "ta:1"]))
+            .node(node("read", "TensorArrayReadV3", &["ta", "index", "write"]));
+        let mut model = preprocessed(&graph)?;
+        model.set_output_names(&["read"])?;
+        let error = SimplePlan::new(&model)?.run(tvec!(tensor1(&[1f32, 2.0]))).unwrap_err();
+        assert!(format!("{:?}", error).contains("only supported in while loops"), "{:?}", error);
+        Ok(())
+    }
+}
@@ -0,0 +1,647 @@
+//! Lowering of TensorFlow 1 while loops to Scan.
+//!
+//! In a `tf.while_loop` frame, each loop variable goes through `Enter ->
+//! Merge -> Switch`, the Switch sending the value to an `Exit` when the
+//! LoopCond is false, or to the loop body when it is true. The body result
+//! is fed back to the Merge through a `NextIteration`. Loop invariants enter
+//! the frame through Enter nodes not followed by a Merge.
+//!
+//! Scan iterates over its scanned inputs instead of evaluating a condition,
+//! so we only lower frames built like the ones of `tf.nn.dynamic_rnn`: a
+//! loop counter starts at 0 and is incremented by 1, the loop condition is
+//! `counter < size` where `size` is the size of the TensorArrays scattered
+//! before the loop, and the body reads (and writes) these TensorArrays at
+//! the counter. Other frames (early exit, bound different from the
+//! TensorArray size, reads at another index) are left as they are.
+//!
+//! The TensorArray reads become scanned inputs, TensorArray writes become
+//! full outputs (replacing the TensorArray gather after the loop), and the
+//! other loop variables become states.
+
+use super::functional::{bin_op_name, skip_identities};
+use super::tensor_array::{TensorArray, TensorArrayRole};
+use super::{LoopGate, LoopGateRole, NextIteration, NextIterationRole};
+use crate::ops::logic::{Merge, Switch};
+use tract_hir::internal::*;
+use tract_hir::ops;
+use tract_hir::ops::scan::{InferenceScan, InputMapping, OutputMapping, StateInitializer};
+
+/// Rewrite all the while loop frames that can be expressed as a Scan.
+pub fn lower_while_loops(model: &mut InferenceModel) -> TractResult<()> {
+    let mut frames: Vec<String> = vec![];
+    let mut errors: HashMap<String, TractError> = HashMap::new();
+    for node in model.nodes() {
+        if let Some(LoopGate(LoopGateRole::Enter(frame))) = node.op_as::<LoopGate>() {
+            if !frames.contains(frame) {
+                frames.push(frame.clone());
+            }
+        }
+    }
+    // a frame containing a nested loop can only be lowered once the inner
+    // loop has been.
+    loop {
+        let mut lowered = None;
+        for (ix, frame) in frames.iter().enumerate() {
+            match lower_frame(model, frame) {
+                Ok((patch, replaced)) => {
+                    for id in replaced {
+                        let name = format!("{}.lowered", model.node(id).name);
+                        model.rename_node(id, &name)?;
+                    }
+                    patch.apply(model)?;
+                    lowered = Some(ix);
+                    break;
+                }
+                Err(e) => {
+                    debug!("While loop {} not lowered yet: {:?}", frame, e);
+                    errors.insert(frame.clone(), e);
+                }
+            }
+        }
+        if let Some(ix) = lowered {
+            frames.remove(ix);
+        } else {
+            break;
+        }
+    }
+    for frame in frames {
+        if let Some(e) = errors.get(&frame) {
+            warn!("While loop {} could not be lowered to Scan: {:?}", frame, e);
+        } else {
+            warn!("While loop {} could not be lowered to Scan", frame);
+        }
+    }
+    Ok(())
+}
+
+fn tensor_array_role(node: &InferenceNode) -> Option<TensorArrayRole> {
+    node.op_as::<TensorArray>().map(|ta| ta.0)
+}
+
+#[derive(Debug)]
+struct LoopVar {
+    outer: OutletId,
+    merge: OutletId,
+    current: OutletId,
+    next: OutletId,
+    exit: Option<OutletId>,
+}
+
+fn loop_var(model: &InferenceModel, enter: &InferenceNode) -> TractResult<Option<LoopVar>> {
+    let merge = if let Some(merge) = model
+        .outlet_successors(OutletId::new(enter.id, 0))
+        .iter()
+        .find(|succ| model.node(succ.node).op_is::<Merge>())
+    {
+        model.node(merge.node)
+    } else {
+        return Ok(None);
+    };
+    let source = merge
+        .inputs
+        .iter()
+        .find(|i| {
+            model.node(i.node).op_as::<NextIteration>().map(|n| n.role)
+                == Some(NextIterationRole::Source)
+        })
+        .with_context(|| format!("No NextIteration for {}", merge))?;
+    let sink = model.node_by_name(&format!("{}-Sink", model.node(source.node).name))?;
+    let switch = model
+        .outlet_successors(OutletId::new(merge.id, 0))
+        .iter()
+        .find(|succ| model.node(succ.node).op_is::<Switch>())
+        .with_context(|| format!("No Switch for {}", merge))?
+        .node;
+    let exit = model
+        .outlet_successors(OutletId::new(switch, 0))
+        .iter()
+        .find(|succ| {
+            if let Some(LoopGate(LoopGateRole::Exit)) = model.node(succ.node).op_as::<LoopGate>() {
+                true
+            } else {
+                false
+            }
+        })
+        .map(|succ| OutletId::new(succ.node, 0));
+    Ok(Some(LoopVar {
+        outer: enter.inputs[0],
+        merge: OutletId::new(merge.id, 0),
+        current: OutletId::new(switch, 1),
+        next: sink.inputs[0],
+        exit,
+    }))
+}
+
+fn konst_i64(model: &InferenceModel, outlet: OutletId) -> Option<i64> {
+    let konst = model.node(skip_identities(model, outlet).node).op_as::<ops::konst::Const>()?;
+    konst.0.cast_to_scalar::<i64>().ok()
+}
+
+/// Find the loop counter: a loop variable starting at 0, incremented by 1,
+/// the loop condition being `counter < bound`. Returns the index of the
+/// counter in `vars` and the outer value of the bound.
+fn loop_counter(
+    model: &InferenceModel,
+    vars: &[LoopVar],
+    invariants: &HashMap<OutletId, OutletId>,
+) -> TractResult<(usize, OutletId)> {
+    let switch = model.node(vars.first().context("No loop variable")?.current.node);
+    let cond = model.node(switch.inputs[1].node);
+    match cond.op_as::<LoopGate>() {
+        Some(LoopGate(LoopGateRole::LoopCond)) => (),
+        _ => bail!("{} is not controlled by a LoopCond", switch),
+    }
+    let less = model.node(skip_identities(model, cond.inputs[0]).node);
+    if bin_op_name(less).as_deref() != Some("Lesser") {
+        bail!("Loop condition {} is not a comparison of a loop counter to a bound", less)
+    }
+    let counter_merge = skip_identities(model, less.inputs[0]);
+    let counter = vars
+        .iter()
+        .position(|var| var.merge == counter_merge)
+        .with_context(|| format!("Loop condition {} does not compare a loop variable", less))?;
+    let var = &vars[counter];
+    if konst_i64(model, var.outer) != Some(0) {
+        bail!("Loop counter {} does not start at 0", model.node(var.merge.node))
+    }
+    let next = model.node(skip_identities(model, var.next).node);
+    let increment = next.inputs.iter().position(|i| skip_identities(model, *i) == var.current);
+    let incremented = bin_op_name(next).as_deref() == Some("Add")
+        && match increment {
+            Some(ix) => next.inputs.len() == 2 && konst_i64(model, next.inputs[1 - ix]) == Some(1),
+            None => false,
+        };
+    if !incremented {
+        bail!("Loop counter {} is not incremented by 1 ({})", model.node(var.merge.node), next)
+    }
+    let bound = invariants
+        .get(&skip_identities(model, less.inputs[1]))
+        .with_context(|| format!("Loop condition {} bound is not a loop invariant", less))?;
+    Ok((counter, *bound))
+}
+
+/// Check a TensorArray accessed in the loop body has `bound` as size.
+fn check_tensor_array_size(
+    model: &InferenceModel,
+    handle: OutletId,
+    bound: OutletId,
+) -> TractResult<()> {
+    let create = model.node(handle.node);
+    let sized_by_bound = create.inputs.first() == Some(&bound)
+        || (tensor_array_role(model.node(bound.node)) == Some(TensorArrayRole::Size)
+            && model.node(bound.node).inputs[0] == handle);
+    if !sized_by_bound {
+        bail!("Loop bound {} is not the size of TensorArray {}", model.node(bound.node), create)
+    }
+    Ok(())
+}
+
+/// Copy the loop body to a standalone model, from its outputs up to the
+/// loop variables, invariants and TensorArray reads.
+struct BodyBuilder<'a> {
+    model: &'a InferenceModel,
+    invariants: HashMap<OutletId, OutletId>,
+    body: InferenceModel,
+    /// loop counter value in the body, and outer loop bound
+    counter: OutletId,
+    bound: OutletId,
+    mapping: HashMap<OutletId, OutletId>,
+    scanned: Vec<(OutletId, OutletId)>,
+    full: Vec<(OutletId, OutletId)>,
+}
+
+impl<'a> BodyBuilder<'a> {
+    fn wire(&mut self, outlet: OutletId) -> TractResult<OutletId> {
+        if let Some(wired) = self.mapping.get(&outlet) {
+            return Ok(*wired);
+        }
+        let node = self.model.node(outlet.node);
+        let wired = if let Some(outer) = self.invariants.get(&outlet) {
+            let source = self.body.add_source(&*node.name, InferenceFact::default())?;
+            self.full.push((source, *outer));
+            source
+        } else if tensor_array_role(node) == Some(TensorArrayRole::Read) {
+            let handle = self
+                .invariants
+                .get(&node.inputs[0])
+                .with_context(|| format!("{} handle is not a loop invariant", node))?;
+            if skip_identities(self.model, node.inputs[1]) != self.counter {
+                bail!("{} does not read at the loop counter", node)
+            }
+            check_tensor_array_size(self.model, *handle, self.bound)?;
+            let scatter = self
+                .model
+                .outlet_successors(*handle)
+                .iter()
+                .find(|succ| {
+                    tensor_array_role(self.model.node(succ.node)) == Some(TensorArrayRole::Scatter)
+                })
+                .with_context(|| format!("No scatter found for {}", node))?
+                .node;
+            let value = self.model.node(scatter).inputs[2];
+            let source =
+                self.body.add_source(format!("{}.input", node.name), InferenceFact::default())?;
+            self.scanned.push((source, value));
+            self.body.wire_node(&*node.name, expand(ops::array::RmDims::new(vec![0])), &[source])?
+                [0]
+        } else if node.op_is::<LoopGate>()
+            || node.op_is::<Merge>()
+            || node.op_is::<Switch>()
+            || node.op_is::<NextIteration>()
+            || node.op_is::<TensorArray>()
+            || node.op_is::<tract_hir::ops::source::Source>()
+        {
+            bail!("Unexpected {} in while loop body", node)
+        } else {
+            let inputs =
+                node.inputs.iter().map(|i| self.wire(*i)).collect::<TractResult<TVec<_>>>()?;
+            let facts = tvec!(InferenceFact::default(); node.outputs.len());
+            let id = self.body.add_node(&*node.name, node.op.clone(), facts)?;
+            for (ix, input) in inputs.into_iter().enumerate() {
+                self.body.add_edge(input, InletId::new(id, ix))?;
+            }
+            for slot in 0..node.outputs.len() {
+                self.mapping.insert(OutletId::new(node.id, slot), OutletId::new(id, slot));
+            }
+            return Ok(OutletId::new(id, outlet.slot));
+        };
+        self.mapping.insert(outlet, wired);
+        Ok(wired)
+    }
+}
+
+fn lower_frame(
+    model: &InferenceModel,
+    frame: &str,
+) -> TractResult<(InferenceModelPatch, Vec<usize>)> {
+    let mut vars = vec![];
+    let mut invariants = HashMap::new();
+    for node in model.nodes() {
+        match node.op_as::<LoopGate>() {
+            Some(LoopGate(LoopGateRole::Enter(f))) if f == frame => (),
+            _ => continue,
+        }
+        if let Some(var) = loop_var(model, node)? {
+            vars.push(var);
+        } else {
+            invariants.insert(OutletId::new(node.id, 0), node.inputs[0]);
+        }
+    }
+
+    let (counter, bound) = loop_counter(model, &vars, &invariants)?;
+    let counter = vars[counter].current;
+
+    // loop variables carrying the flow of TensorArray writes are the loop
+    // outputs, the others are states.
+    let mut states = vec![];
+    let mut writes = vec![];
+    for var in vars {
+        let next = model.node(var.next.node);
+        if tensor_array_role(next) == Some(TensorArrayRole::Write) {
+            let handle = invariants
+                .get(&next.inputs[0])
+                .with_context(|| format!("{} handle is not a loop invariant", next))?;
+            if skip_identities(model, next.inputs[1]) != counter {
+                bail!("{} does not write at the loop counter", next)
+            }
+            check_tensor_array_size(model, *handle, bound)?;
+            writes.push((*handle, next));
+        } else {
+            states.push(var);
+        }
+    }
+
+    let mut builder = BodyBuilder {
+        model,
+        invariants,
+        body: InferenceModel::default(),
+        counter,
+        bound,
+        mapping: HashMap::new(),
+        scanned: vec![],
+        full: vec![],
+    };
+    let mut state_sources = tvec!();
+    for var in &states {
+        let name = &model.node(var.current.node).name;
+        let source = builder.body.add_source(&**name, InferenceFact::default())?;
+        builder.mapping.insert(var.current, source);
+        state_sources.push(source);
+    }
+    let mut body_outputs = tvec!();
+    for var in &states {
+        body_outputs.push(builder.wire(var.next)?);
+    }
+    for (_, write) in &writes {
+        let value = builder.wire(write.inputs[2])?;
+        let op = expand(ops::array::AddDims::new(vec![0]));
+        body_outputs
+            .push(builder.body.wire_node(format!("{}.output", write.name), op, &[value])?[0]);
+    }
+    if builder.scanned.len() == 0 {
+        bail!("No TensorArray is read in the body of {}", frame)
+    }
+    let BodyBuilder { mut body, scanned, full, .. } = builder;
+
+    let mut input_mapping = vec![];
+    let mut body_inputs = state_sources;
+    let mut outer_inputs: TVec<OutletId> = states.iter().map(|var| var.outer).collect();
+    for ix in 0..states.len() {
+        input_mapping.push(InputMapping::State { initializer: StateInitializer::FromInput(ix) });
+    }
+    for (source, outer) in scanned {
+        input_mapping.push(InputMapping::Scan { slot: outer_inputs.len(), axis: 0, chunk: 1 });
+        body_inputs.push(source);
+        outer_inputs.push(outer);
+    }
+    for (source, outer) in full {
+        input_mapping.push(InputMapping::Full { slot: outer_inputs.len() });
+        body_inputs.push(source);
+        outer_inputs.push(outer);
+    }
+    body.set_input_outlets(&body_inputs)?;
+    body.set_output_outlets(&body_outputs)?;
+
+    let mut output_mapping = vec![];
+    for ix in 0..states.len() {
+        output_mapping.push(OutputMapping {
+            state: true,
+            last_value_slot: Some(ix),
+            full_slot: None,
+            axis: 0,
+            chunk: 1,
+            full_dim_hint: None,
+        });
+    }
+    for ix in 0..writes.len() {
+        output_mapping.push(OutputMapping {
+            state: false,
+            last_value_slot: None,
+            full_slot: Some(states.len() + ix),
+            axis: 0,
+            chunk: 1,
+            full_dim_hint: None,
+        });
+    }
+    let scan = InferenceScan::new(
+        body,
+        input_mapping,
+        output_mapping,
+        None,
+        true,
+        GenericFactoid::default(),
+    );
+
+    let mut patch = InferenceModelPatch::default();
+    let taps = outer_inputs
+        .iter()
+        .map(|i| patch.tap_model(model, *i))
+        .collect::<TractResult<TVec<_>>>()?;
+    let outputs = patch.wire_node(frame, scan, &taps)?;
+    // Exit and TensorArray gather nodes are replaced by identities with the
+    // same names, so the loop outputs can still be designated by name.
+    let mut replaced = vec![];
+    for (ix, var) in states.iter().enumerate() {
+        if let Some(exit) = var.exit {
+            replaced.push((exit, outputs[ix]));
+        }
+    }
+    for (ix, (handle, _)) in writes.iter().enumerate() {
+        for succ in model.outlet_successors(*handle) {
+            if tensor_array_role(model.node(succ.node)) == Some(TensorArrayRole::Gather) {
+                replaced.push((OutletId::new(succ.node, 0), outputs[states.len() + ix]));
+            }
+        }
+    }
+    for (outlet, by) in &replaced {
+        let name = &*model.node(outlet.node).name;
+        let id = patch.wire_node(name, tract_hir::ops::identity::Identity, &[*by])?[0];
+        patch.shunt_outside(model, *outlet, id)?;
+    }
+    Ok((patch, replaced.into_iter().map(|(outlet, _)| outlet.node).collect()))
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use crate::model::TfModelAndExtensions;
+    use crate::tfpb;
+    use crate::tfpb::tensorflow::DataType::DtFloat;
+    use crate::tfpb::tensorflow::{GraphDef, NodeDef, TensorProto};
+    use std::convert::TryInto;
+    use tract_hir::internal::*;
+
+    pub(in crate::ops::control_flow) fn konst(name: &str, t: Tensor) -> TractResult<NodeDef> {
+        let dt: tfpb::tensorflow::DataType = t.datum_type().try_into()?;
+        let t: TensorProto = (&t).try_into()?;
+        Ok(tfpb::node().name(name).op("Const").attr("dtype", dt).attr("value", t))
+    }
+
+    pub(in crate::ops::control_flow) fn node(name: &str, op: &str, inputs: &[&str]) -> NodeDef {
+        inputs.iter().fold(tfpb::node().name(name).op(op), |n, i| n.input(*i))
+    }
+
+    fn enter(name: &str, input: &str) -> NodeDef {
+        node(name, "Enter", &[input]).attr("frame_name", "while/while_context")
+    }
+
+    /// A loop as built by dynamic_rnn, over the rows of `x`. With an
+    /// accumulator, the output rows are twice the running sum of the input
+    /// rows, and the final accumulator is the sum. Without, the output rows
+    /// are the squares of the input rows. If `size_from_input`, the trip
+    /// count and the scattered indices are computed from the shape of `x`.
+    pub(in crate::ops::control_flow) fn dynamic_rnn_like(
+        accumulate: bool,
+        size_from_input: bool,
+    ) -> TractResult<GraphDef> {
+        let mut graph = tfpb::graph()
+            .node(tfpb::node().name("x").op("Placeholder").attr("dtype", DtFloat))
+            .node(konst("time", tensor0(0i32))?);
+        if size_from_input {
+            graph = graph
+                .node(node("shape", "Shape", &["x"]))
+                .node(konst("begin", tensor1(&[0i32]))?)
+                .node(konst("end", tensor1(&[1i32]))?)
+                .node(konst("stride", tensor1(&[1i32]))?)
+                .node(konst("delta", tensor0(1i32))?)
+                .node(
+                    node("size", "StridedSlice", &["shape", "begin", "end", "stride"])
+                        .attr("shrink_axis_mask", 1i64),
+                )
+                .node(node("indices", "Range", &["time", "size", "delta"]));
+        } else {
+            graph = graph
+                .node(konst("size", tensor0(3i32))?)
+                .node(konst("indices", tensor1(&[0i32, 1, 2]))?);
+        }
+        graph = graph
+            .node(node("ta_in", "TensorArrayV3", &["size"]))
+            .node(node("scatter", "TensorArrayScatterV3", &["ta_in", "indices", "x", "ta_in:1"]))
+            .node(node("ta_out", "TensorArrayV3", &["size"]))
+            .node(enter("while/Enter", "time"))
+            .node(enter("while/Enter_1", "ta_out:1"))
+            .node(enter("while/Less/Enter", "size"))
+            .node(enter("while/Read/Enter", "ta_in"))
+            .node(enter("while/Read/Enter_1", "scatter"))
+            .node(enter("while/Write/Enter", "ta_out"));
+        let vars: &[&str] = if accumulate {
+            graph = graph
+                .node(konst("zeros", tensor1(&[0f32, 0.0]))?)
+                .node(enter("while/Enter_2", "zeros"));
+            &["", "_1", "_2"]
+        } else {
+            &["", "_1"]
+        };
+        for ix in vars {
+            graph = graph
+                .node(
+                    node(&format!("while/Merge{}", ix), "Merge", &[])
+                        .input(format!("while/Enter{}", ix))
+                        .input(format!("while/NextIteration{}", ix))
+                        .attr("N", 2i64),
+                )
+                .node(
+                    node(&format!("while/Switch{}", ix), "Switch", &[])
+                        .input(format!("while/Merge{}", ix))
+                        .input("while/LoopCond"),
+                )
+                .node(
+                    node(&format!("while/Identity{}", ix), "Identity", &[])
+                        .input(format!("while/Switch{}:1", ix)),
+                )
+                .node(
+                    node(&format!("while/Exit{}", ix), "Exit", &[])
+                        .input(format!("while/Switch{}", ix)),
+                );
+        }
+        graph = graph
+            .node(node("while/Less", "Less", &["while/Merge", "while/Less/Enter"]))
+            .node(node("while/LoopCond", "LoopCond", &["while/Less"]))
+            .node(node(
+                "while/Read",
+                "TensorArrayReadV3",
+                &["while/Read/Enter", "while/Identity", "while/Read/Enter_1"],
+            ));
+        let written = if accumulate {
+            graph = graph
+                .node(node("while/add", "Add", &["while/Identity_2", "while/Read"]))
+                .node(konst("while/two", tensor0(2f32))?)
+                .node(node("while/mul", "Mul", &["while/add", "while/two"]))
+                .node(node("while/NextIteration_2", "NextIteration", &["while/add"]));
+            "while/mul"
+        } else {
+            graph = graph.node(node("while/mul", "Mul", &["while/Read", "while/Read"]));
+            "while/mul"
+        };
+        graph = graph
+            .node(node(
+                "while/Write",
+                "TensorArrayWriteV3",
+                &["while/Write/Enter", "while/Identity", written, "while/Identity_1"],
+            ))
+            .node(konst("while/one", tensor0(1i32))?)
+            .node(node("while/add_1", "Add", &["while/Identity", "while/one"]))
+            .node(node("while/NextIteration", "NextIteration", &["while/add_1"]))
+            .node(node("while/NextIteration_1", "NextIteration", &["while/Write"]))
+            .node(node("gather", "TensorArrayGatherV3", &["ta_out", "indices", "while/Exit_1"]));
+        Ok(graph)
+    }
+
+    pub(in crate::ops::control_flow) fn preprocessed(
+        graph: &GraphDef,
+    ) -> TractResult<InferenceModel> {
+        let TfModelAndExtensions(model, extensions) = crate::tensorflow().parse_graph(graph)?;
+        extensions.preproc(model)
+    }
+
+    pub(in crate::ops::control_flow) fn run(
+        mut model: InferenceModel,
+        outputs: &[&str],
+        input: Tensor,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        model.set_output_names(outputs)?;
+        model.set_input_fact(0, InferenceFact::dt_shape_from_tensor(&input))?;
+        model.into_optimized()?.into_runnable()?.run(tvec!(input))
+    }
+
+    #[test]
+    fn dynamic_rnn_like_loop() -> TractResult<()> {
+        let model = preprocessed(&dynamic_rnn_like(true, false)?)?;
+        assert!(model.nodes().iter().any(|n| n.op_is::<tract_hir::ops::scan::InferenceScan>()));
+        let input = tensor2(&[[1f32, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        let outputs = run(model, &["gather", "while/Exit_2"], input)?;
+        assert_eq!(outputs[0], rctensor2(&[[2f32, 4.0], [8.0, 12.0], [18.0, 24.0]]));
+        assert_eq!(outputs[1], rctensor1(&[9f32, 12.0]));
+        Ok(())
+    }
+
+    #[test]
+    fn not_lowered_without_preproc() -> TractResult<()> {
+        let model = crate::tensorflow().model_for_proto_model(&dynamic_rnn_like(true, false)?)?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<tract_hir::ops::scan::InferenceScan>()));
+        Ok(())
+    }
+
+    #[test]
+    fn trip_count_from_input() -> TractResult<()> {
+        let model = preprocessed(&dynamic_rnn_like(true, true)?)?;
+        let input = tensor2(&[[1f32, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        let outputs = run(model.clone(), &["gather", "while/Exit_2"], input)?;
+        assert_eq!(outputs[0], rctensor2(&[[2f32, 4.0], [8.0, 12.0], [18.0, 24.0]]));
+        assert_eq!(outputs[1], rctensor1(&[9f32, 12.0]));
+        let input = tensor2(&[[1f32, 0.0], [1.0, 0.0], [1.0, 0.0], [1.0, 0.0], [1.0, 0.0]]);
+        let outputs = run(model, &["gather", "while/Exit_2"], input)?;
+        assert_eq!(
+            outputs[0],
+            rctensor2(&[[2f32, 0.0], [4.0, 0.0], [6.0, 0.0], [8.0, 0.0], [10.0, 0.0]])
+        );
+        assert_eq!(outputs[1], rctensor1(&[5f32, 0.0]));
+        Ok(())
+    }
+
+    /// Replace input `ix` of the node named `name`.
+    fn rewire(graph: &mut GraphDef, name: &str, ix: usize, input: &str) {
+        let node = graph.node.iter_mut().find(|n| n.name == name).unwrap();
+        node.input[ix] = input.to_string();
+    }
+
+    fn assert_not_lowered(graph: &GraphDef, reason: &str) -> TractResult<()> {
+        let model = crate::tensorflow().model_for_proto_model(graph)?;
+        let err = super::lower_frame(&model, "while/while_context").unwrap_err();
+        assert!(format!("{:?}", err).contains(reason), "{:?}", err);
+        let model = preprocessed(graph)?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<tract_hir::ops::scan::InferenceScan>()));
+        Ok(())
+    }
+
+    #[test]
+    fn bound_is_not_the_tensor_array_size() -> TractResult<()> {
+        let mut graph = dynamic_rnn_like(true, false)?.node(konst("max_len", tensor0(2i32))?);
+        rewire(&mut graph, "while/Less/Enter", 0, "max_len");
+        assert_not_lowered(&graph, "is not the size of TensorArray")
+    }
+
+    #[test]
+    fn condition_is_not_a_counter_comparison() -> TractResult<()> {
+        let mut graph = dynamic_rnn_like(true, false)?.node(node(
+            "while/Greater",
+            "Greater",
+            &["while/Merge", "while/Less/Enter"],
+        ));
+        rewire(&mut graph, "while/LoopCond", 0, "while/Greater");
+        assert_not_lowered(&graph, "is not a comparison of a loop counter")
+    }
+
+    #[test]
+    fn read_is_not_at_the_counter() -> TractResult<()> {
+        let mut graph = dynamic_rnn_like(true, false)?.node(konst("while/zero", tensor0(0i32))?);
+        rewire(&mut graph, "while/Read", 1, "while/zero");
+        assert_not_lowered(&graph, "does not read at the loop counter")
+    }
+
+    #[test]
+    fn counter_does_not_step_by_one() -> TractResult<()> {
+        let mut graph = dynamic_rnn_like(true, false)?.node(konst("while/two_i", tensor0(2i32))?);
+        rewire(&mut graph, "while/add_1", 1, "while/two_i");
+        assert_not_lowered(&graph, "is not incremented by 1")
+    }
+}
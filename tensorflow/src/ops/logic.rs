@@ -15,9 +15,96 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("LogicalAnd", |_, _| Ok(ops::logic::And.into_hir()));
     reg.insert("LogicalOr", |_, _| Ok(ops::logic::Or.into_hir()));
     reg.insert("Merge", merge);
+    reg.insert("Select", |_, _| Ok(expand(Select::new(false))));
+    reg.insert("SelectV2", |_, _| Ok(expand(Select::new(true))));
     reg.insert("Switch", |_, _| Ok(Box::new(Switch)));
 }
 
+/// Select (and SelectV2, with full broadcasting) as an Iff.
+///
+/// Select accepts a 1D condition for a higher rank input, picking along the
+/// first axis.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Select {
+    v2: bool,
+}
+
+impl_dyn_hash!(Select);
+
+impl Select {
+    fn cond_shape<D: DimLike>(&self, cond: &[D], then: &[D]) -> TVec<D> {
+        let mut cond: TVec<D> = cond.into();
+        if !self.v2 && cond.len() == 1 && then.len() > 1 {
+            cond.extend(std::iter::repeat(D::one()).take(then.len() - 1));
+        }
+        cond
+    }
+}
+
+impl Expansion for Select {
+    fn name(&self) -> Cow<str> {
+        if self.v2 { "SelectV2" } else { "Select" }.into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, DatumType::Bool)?;
+        s.equals(&inputs[1].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[2].datum_type, &outputs[0].datum_type)?;
+        s.given_3(&inputs[0].shape, &inputs[1].shape, &inputs[2].shape, move |s, c, t, f| {
+            let c = self.cond_shape(&c, &t);
+            let shape = tract_hir::tract_core::broadcast::multi_broadcast(&[&c, &t, &f])
+                .with_context(|| format!("Incompatible shapes {:?}, {:?} and {:?}", c, t, f))?;
+            s.equals(&outputs[0].shape, shape)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let facts = inputs
+            .iter()
+            .map(|i| target.outlet_fact(*i).map(|f| f.clone()))
+            .collect::<TractResult<TVec<_>>>()?;
+        let mut wires: TVec<OutletId> = inputs.into();
+        if !self.v2 && facts[0].rank() == 1 && facts[1].rank() > 1 {
+            for axis in 1..facts[1].rank() {
+                wires[0] = target.wire_node(
+                    format!("{}.cond-add-{}", prefix, axis),
+                    AxisOp::Add(axis),
+                    &[wires[0]],
+                )?[0];
+            }
+        }
+        let ranks = wires
+            .iter()
+            .map(|w| Ok(target.outlet_fact(*w)?.rank()))
+            .collect::<TractResult<TVec<usize>>>()?;
+        let rank = *ranks.iter().max().unwrap();
+        for (ix, wire) in wires.iter_mut().enumerate() {
+            for i in ranks[ix]..rank {
+                *wire = target.wire_node(
+                    format!("{}.fix-rank-{}-{}", prefix, ix, i),
+                    AxisOp::Add(0),
+                    &[*wire],
+                )?[0];
+            }
+        }
+        target.wire_node(prefix, ops::logic::Iff, &wires)
+    }
+}
+
 #[derive(Debug, Clone, new, Hash)]
 pub struct Switch;
 
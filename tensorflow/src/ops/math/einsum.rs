@@ -0,0 +1,216 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::change_axes::perm_to_ops;
+use tract_hir::tract_core::ops::math::mul;
+use tract_hir::tract_core::ops::nn::{Reduce, Reducer};
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+/// Einstein summation, without ellipsis nor diagonals.
+///
+/// Every operand is transposed and padded with unit axes to a common layout
+/// (output axes first, then summed axes), then operands are multiplied with
+/// broadcasting and the summed axes are reduced.
+#[derive(Debug, Clone, Hash)]
+pub struct Einsum {
+    inputs: Vec<Vec<char>>,
+    output: Vec<char>,
+}
+
+impl_dyn_hash!(Einsum);
+
+pub fn einsum(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let equation = pb.get_attr_str("equation")?;
+    Ok(expand(Einsum::parse(&equation)?))
+}
+
+impl Einsum {
+    pub fn parse(equation: &str) -> TractResult<Einsum> {
+        let equation: String = equation.chars().filter(|c| !c.is_whitespace()).collect();
+        if equation.contains("...") {
+            bail!("Einsum with ellipsis are not supported ({})", equation);
+        }
+        let (inputs, output) = if let Some(arrow) = equation.find("->") {
+            (&equation[..arrow], Some(&equation[arrow + 2..]))
+        } else {
+            (&*equation, None)
+        };
+        let inputs: Vec<Vec<char>> = inputs.split(',').map(|s| s.chars().collect()).collect();
+        let output: Vec<char> = if let Some(output) = output {
+            output.chars().collect()
+        } else {
+            // implicit mode: letters appearing exactly once, in alphabetical order
+            let mut output: Vec<char> = inputs
+                .iter()
+                .flat_map(|i| i.iter())
+                .filter(|c| inputs.iter().flat_map(|i| i.iter()).filter(|d| d == c).count() == 1)
+                .cloned()
+                .collect();
+            output.sort();
+            output
+        };
+        for term in inputs.iter().chain(std::iter::once(&output)) {
+            for (ix, c) in term.iter().enumerate() {
+                if !c.is_ascii_alphabetic() {
+                    bail!("Invalid character {:?} in einsum equation {}", c, equation);
+                }
+                if term[ix + 1..].contains(c) {
+                    bail!("Einsum with repeated indices are not supported ({})", equation);
+                }
+            }
+        }
+        for c in &output {
+            if !inputs.iter().any(|i| i.contains(c)) {
+                bail!("Output index {} does not appear in inputs ({})", c, equation);
+            }
+        }
+        Ok(Einsum { inputs, output })
+    }
+
+    /// All indices: output ones first, then summed ones.
+    fn indices(&self) -> Vec<char> {
+        let mut indices = self.output.clone();
+        for c in self.inputs.iter().flat_map(|i| i.iter()) {
+            if !indices.contains(c) {
+                indices.push(*c);
+            }
+        }
+        indices
+    }
+}
+
+impl Expansion for Einsum {
+    fn name(&self) -> Cow<str> {
+        "Einsum".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let inputs: Vec<String> = self.inputs.iter().map(|i| i.iter().collect()).collect();
+        Ok(vec![format!("{}->{}", inputs.join(","), self.output.iter().collect::<String>())])
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, self.inputs.len())?;
+        check_output_arity(&outputs, 1)?;
+        for (ix, term) in self.inputs.iter().enumerate() {
+            s.equals(&inputs[ix].datum_type, &outputs[0].datum_type)?;
+            s.equals(&inputs[ix].rank, term.len() as i64)?;
+        }
+        s.equals(&outputs[0].rank, self.output.len() as i64)?;
+        for (axis, c) in self.output.iter().enumerate() {
+            let inputs_dims = self
+                .inputs
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, term)| term.iter().position(|d| d == c).map(|pos| (ix, pos)))
+                .collect::<Vec<_>>();
+            s.given_all(
+                inputs_dims.iter().map(|&(ix, pos)| &inputs[ix].shape[pos]),
+                move |s, dims| {
+                    let dims: Vec<TDim> = dims.into_iter().filter(|d| !d.is_one()).collect();
+                    let dim = dims.get(0).cloned().unwrap_or(1.into());
+                    s.equals(&outputs[0].shape[axis], dim)
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let indices = self.indices();
+        let mut operands = tvec!();
+        for (ix, term) in self.inputs.iter().enumerate() {
+            let mut wire = inputs[ix];
+            let mut perm: Vec<usize> = (0..term.len()).collect();
+            perm.sort_by_key(|&axis| indices.iter().position(|c| *c == term[axis]));
+            for op in perm_to_ops(&perm) {
+                wire = target.wire_node(format!("{}.{}.{:?}", prefix, ix, op), op, &[wire])?[0];
+            }
+            for (axis, c) in indices.iter().enumerate() {
+                if !term.contains(c) {
+                    wire = target.wire_node(
+                        format!("{}.{}.add-{}", prefix, ix, c),
+                        AxisOp::Add(axis),
+                        &[wire],
+                    )?[0];
+                }
+            }
+            operands.push(wire);
+        }
+        let mut wire = operands[0];
+        for (ix, operand) in operands.iter().enumerate().skip(1) {
+            wire = target.wire_node(
+                format!("{}.mul-{}", prefix, ix),
+                mul::bin_typed(),
+                &[wire, *operand],
+            )?[0];
+        }
+        let summed: TVec<usize> = (self.output.len()..indices.len()).collect();
+        if summed.len() > 0 {
+            wire = target.wire_node(
+                format!("{}.sum", prefix),
+                Reduce::new(summed.clone(), Reducer::Sum),
+                &[wire],
+            )?[0];
+            for axis in summed.iter().rev() {
+                wire = target.wire_node(
+                    format!("{}.rm-{}", prefix, axis),
+                    AxisOp::Rm(*axis),
+                    &[wire],
+                )?[0];
+            }
+        }
+        Ok(tvec!(wire))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(equation: &str, inputs: TVec<Tensor>) -> Tensor {
+        let op = expand(Einsum::parse(equation).unwrap());
+        op.eval(inputs.into_iter().map(|t| t.into_arc_tensor()).collect())
+            .unwrap()
+            .remove(0)
+            .into_tensor()
+    }
+
+    #[test]
+    fn matmul() {
+        let a = tensor2(&[[1f32, 2.], [3., 4.]]);
+        let b = tensor2(&[[5f32, 6.], [7., 8.]]);
+        assert_eq!(run("ij,jk->ik", tvec!(a, b)), tensor2(&[[19f32, 22.], [43., 50.]]));
+    }
+
+    #[test]
+    fn transposed_output() {
+        let a = tensor2(&[[1f32, 2.], [3., 4.]]);
+        let b = tensor2(&[[5f32, 6.], [7., 8.]]);
+        assert_eq!(run("ij,jk->ki", tvec!(a, b)), tensor2(&[[19f32, 43.], [22., 50.]]));
+    }
+
+    #[test]
+    fn implicit_output_and_trace_free_sum() {
+        let a = tensor2(&[[1f32, 2.], [3., 4.]]);
+        assert_eq!(run("ij", tvec!(a.clone())), a);
+        assert_eq!(run("ij->j", tvec!(a)), tensor1(&[4f32, 6.]));
+    }
+
+    #[test]
+    fn reject_ellipsis() {
+        assert!(Einsum::parse("...ij,jk->...ik").is_err());
+    }
+}
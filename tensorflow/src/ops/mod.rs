@@ -35,6 +35,7 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Cast", cast);
     reg.insert("Const", konst);
     reg.insert("Identity", |_, _| Ok(Box::new(tract_hir::ops::identity::Identity)));
+    reg.insert("ReadVariableOp", |_, _| Ok(Box::new(tract_hir::ops::identity::Identity)));
     reg.insert("NoOp", |_, _| Ok(Box::new(Noop)));
     reg.insert("Placeholder", |_, _| Ok(Box::new(tract_hir::ops::source::Source::new())));
 }
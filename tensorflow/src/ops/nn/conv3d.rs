@@ -0,0 +1,29 @@
+use tract_hir::internal::*;
+use tract_hir::ops::cnn;
+use tract_hir::ops::nn::DataFormat;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn conv3d(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let data_format = super::data_format(pb)?;
+    let spatial = if data_format == DataFormat::NHWC { 1..4 } else { 2..5 };
+    let strides: TVec<usize> = pb.get_attr_list_int("strides")?.into();
+    if strides.len() != 5 {
+        bail!("strides must have 5 values, found {:?}", strides)
+    }
+    let mut op = cnn::Conv::default()
+        .hwio()
+        .padding(super::padding(pb)?)
+        .strides(strides[spatial.clone()].into());
+    if let Some(dilations) = pb.get_attr_opt_list_int::<usize>("dilations")? {
+        if dilations.len() != 5 {
+            bail!("dilations must have 5 values, found {:?}", dilations)
+        }
+        op = op.dilations(dilations[spatial].into());
+    }
+    if data_format == DataFormat::NHWC {
+        op = op.nhwc()
+    }
+    Ok(expand(op))
+}
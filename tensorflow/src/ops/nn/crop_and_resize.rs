@@ -0,0 +1,138 @@
+use tract_hir::internal::*;
+use tract_hir::ops::cnn::{RoiResample, SamplingMethod};
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn crop_and_resize(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let method = pb.get_attr_opt_str("method")?.unwrap_or_else(|| "bilinear".to_string());
+    if method != "bilinear" {
+        bail!("Unsupported CropAndResize method {}", method);
+    }
+    let extrapolation_value = pb.get_attr_opt_float("extrapolation_value")?.unwrap_or(0.0);
+    Ok(expand(CropAndResize::new(extrapolation_value)))
+}
+
+/// Bilinear resampling of boxes of a NHWC image to the (constant) crop size.
+///
+/// Boxes are `(y1, x1, y2, x2)` rows normalized to the image size, whose
+/// corners are sampled. Samples outside of the image take
+/// `extrapolation_value`. The output is f32.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct CropAndResize {
+    #[educe(Hash(method = "hash_f32"))]
+    extrapolation_value: f32,
+}
+
+impl_dyn_hash!(CropAndResize);
+
+impl Expansion for CropAndResize {
+    fn name(&self) -> Cow<str> {
+        "CropAndResize".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 4)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 2)?;
+        s.equals(&inputs[1].shape[1], 4.to_dim())?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.equals(&inputs[1].shape[0], &inputs[2].shape[0])?;
+        s.equals(&inputs[3].rank, 1)?;
+        s.equals(&inputs[3].shape[0], 2.to_dim())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[3], &inputs[0].shape[3])?;
+        s.given(&inputs[3].value, move |s, size| {
+            let size = size.cast_to::<i64>()?;
+            let size = size.as_slice::<i64>()?;
+            s.equals(&outputs[0].shape[1], size[0].to_dim())?;
+            s.equals(&outputs[0].shape[2], size[1].to_dim())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let size = if let Some(ref size) = target.outlet_fact(inputs[3])?.konst {
+            size.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|&s| s as usize).collect()
+        } else {
+            bail!("CropAndResize crop size must be a constant")
+        };
+        let image_fact = target.outlet_fact(inputs[0])?.clone();
+        let (h, w) = (image_fact.shape[1].to_usize()?, image_fact.shape[2].to_usize()?);
+        let mut image = inputs[0];
+        if image_fact.datum_type != f32::datum_type() {
+            image = target.wire_node(
+                format!("{}.cast", prefix),
+                tract_hir::ops::cast(f32::datum_type()),
+                &[image],
+            )?[0];
+        }
+        let image = target.wire_node(format!("{}.nchw", prefix), AxisOp::Move(3, 1), &[image])?[0];
+        let mut boxes = inputs[1];
+        if target.outlet_fact(boxes)?.datum_type != f32::datum_type() {
+            boxes = target.wire_node(
+                format!("{}.boxes.cast", prefix),
+                tract_hir::ops::cast(f32::datum_type()),
+                &[boxes],
+            )?[0];
+        }
+        let (h, w) = (h.saturating_sub(1) as f32, w.saturating_sub(1) as f32);
+        let scale = tensor2(&[[h, w, h, w]]);
+        let boxes = target.wire_node(
+            format!("{}.boxes", prefix),
+            tract_hir::ops::math::mul::unary(scale.into_arc_tensor()),
+            &[boxes],
+        )?[0];
+        let resample =
+            RoiResample::new(size, SamplingMethod::Aligned, Some(self.extrapolation_value));
+        let resampled = target.wire_node(
+            format!("{}.resample", prefix),
+            resample,
+            &[image, boxes, inputs[2]],
+        )?[0];
+        target.wire_node(prefix, AxisOp::Move(1, 3), &[resampled])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crop_and_resize() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let image =
+            model.add_source("image", InferenceFact::dt_shape(f32::datum_type(), &[1, 3, 3, 1]))?;
+        let boxes =
+            model.add_source("boxes", InferenceFact::dt_shape(f32::datum_type(), &[2, 4]))?;
+        let box_ind = model.add_const("box_ind", rctensor1(&[0i32, 0]))?;
+        let size = model.add_const("size", rctensor1(&[1i32, 2]))?;
+        let output = model.wire_node(
+            "crop",
+            expand(CropAndResize::new(-1.0)),
+            &[image, boxes, box_ind, size],
+        )?;
+        model.set_output_outlets(&output)?;
+        let image = tensor1(&[0f32, 1., 2., 3., 4., 5., 6., 7., 8.]).into_shape(&[1, 3, 3, 1])?;
+        let boxes = tensor2(&[[0f32, 0., 1., 1.], [0.5, 0.5, 0.5, 1.5]]);
+        let outputs = model.into_optimized()?.into_runnable()?.run(tvec!(image, boxes))?;
+        let expected = tensor1(&[3f32, 5., 4., -1.]).into_shape(&[2, 1, 2, 1])?;
+        assert_eq!(*outputs[0], expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use tract_hir::internal::*;
+use tract_hir::ops::cnn::{DeconvUnary, KernelFormat, PaddingSpec};
+use tract_hir::ops::nn::DataFormat;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn conv2d_backprop_input(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let data_format = super::data_format(pb)?;
+    let spatial = if data_format == DataFormat::NHWC { 1..3 } else { 2..4 };
+    let strides = super::strides(pb)?;
+    let dilations: Vec<usize> =
+        pb.get_attr_opt_list_int("dilations")?.unwrap_or_else(|| vec![1; 4]);
+    let padding = if pb.get_attr_raw_str("padding")? == b"EXPLICIT" {
+        let pads: Vec<usize> = pb.get_attr_list_int("explicit_paddings")?;
+        let before = spatial.clone().map(|ax| pads[2 * ax]).collect();
+        let after = spatial.clone().map(|ax| pads[2 * ax + 1]).collect();
+        PaddingSpec::Explicit(before, after, false)
+    } else {
+        super::padding(pb)?
+    };
+    Ok(expand(Conv2DBackpropInput::new(
+        data_format,
+        padding,
+        strides[spatial.clone()].into(),
+        dilations[spatial].into(),
+    )))
+}
+
+/// Gradient of Conv2D wrt its input, aka transposed convolution.
+///
+/// Inputs are the output shape, the (HWIO) forward convolution filter and the
+/// data.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Conv2DBackpropInput {
+    data_format: DataFormat,
+    padding: PaddingSpec,
+    strides: TVec<usize>,
+    dilations: TVec<usize>,
+}
+
+impl_dyn_hash!(Conv2DBackpropInput);
+
+impl Expansion for Conv2DBackpropInput {
+    fn name(&self) -> Cow<str> {
+        "Conv2DBackpropInput".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].rank, 1)?;
+        s.equals(&inputs[0].shape[0], 4.to_dim())?;
+        s.equals(&inputs[1].rank, 4)?;
+        s.equals(&inputs[2].rank, 4)?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&inputs[1].datum_type, &inputs[2].datum_type)?;
+        s.equals(&outputs[0].datum_type, &inputs[2].datum_type)?;
+        s.given(&inputs[0].value, move |s, shape| {
+            let shape = shape.cast_to::<TDim>()?;
+            s.equals(
+                &outputs[0].shape,
+                shape.as_slice::<TDim>()?.iter().cloned().collect::<TVec<_>>(),
+            )
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let output_shape = target
+            .outlet_fact(inputs[0])?
+            .konst
+            .clone()
+            .context("Expect output shape to be a constant")?;
+        let output_shape = output_shape.cast_to::<i64>()?;
+        let output_shape = self.data_format.shape(
+            output_shape.as_slice::<i64>()?.iter().map(|&d| d as usize).collect::<TVec<_>>(),
+        )?;
+        let kernel = target
+            .outlet_fact(inputs[1])?
+            .konst
+            .clone()
+            .context("Expect filter to be a constant")?;
+        let mut op = DeconvUnary::new(
+            self.data_format,
+            KernelFormat::HWIO,
+            self.padding.clone(),
+            self.strides.clone(),
+            self.dilations.clone(),
+            kernel,
+            None,
+            tvec!(0, 0),
+            1,
+        );
+        // pick the adjustments giving the expected output size
+        let input_shape = target.outlet_fact(inputs[2])?.shape.to_tvec();
+        let input_shape = self.data_format.shape(input_shape)?;
+        let computed = op.output_facts(&[target.outlet_fact(inputs[2])?])?.remove(0);
+        let computed = self.data_format.shape(computed.shape.to_tvec())?;
+        for (ax, (computed, wanted)) in
+            computed.hw_dims().iter().zip(output_shape.hw_dims().iter()).enumerate()
+        {
+            let computed = computed.to_usize()?;
+            if *wanted < computed || *wanted - computed >= self.strides[ax] {
+                bail!(
+                    "Can not produce output shape {:?} from input {:?} with {:?}",
+                    output_shape.shape,
+                    input_shape.shape,
+                    self
+                );
+            }
+            op.adjustments[ax] = *wanted - computed;
+        }
+        target.wire_node(prefix, op, &inputs[2..3])
+    }
+}
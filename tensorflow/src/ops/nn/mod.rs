@@ -1,4 +1,5 @@
 use tract_hir::internal::*;
+use tract_hir::ops::activations;
 use tract_hir::ops::cnn::PaddingSpec;
 use tract_hir::ops::nn::{DataFormat, LayerSoftmax};
 
@@ -6,25 +7,51 @@ use crate::model::TfOpRegister;
 use crate::tfpb::tensorflow::NodeDef;
 
 pub mod conv2d;
+pub mod conv3d;
+pub mod crop_and_resize;
+pub mod deconv;
 pub mod dw_conv2d;
 pub mod fused_batch_norm;
+pub mod non_max_suppression;
 pub mod pools;
+pub mod resize;
 pub mod s2b;
+pub mod top_k;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("AvgPool", pools::avgpool);
+    reg.insert("AvgPool3D", pools::avgpool);
     reg.insert("Conv2D", conv2d::conv2d);
+    reg.insert("Conv2DBackpropInput", deconv::conv2d_backprop_input);
+    reg.insert("Conv3D", conv3d::conv3d);
+    reg.insert("CropAndResize", crop_and_resize::crop_and_resize);
     reg.insert("DepthwiseConv2dNative", dw_conv2d::depthwise_conv2d);
+    reg.insert("Elu", |_, _| Ok(expand(activations::Elu::new(1.0))));
     reg.insert("FusedBatchNorm", fused_batch_norm::fused_batch_norm);
+    reg.insert("LeakyRelu", |_, pb| {
+        let alpha = pb.get_attr_opt_float("alpha")?.unwrap_or(0.2);
+        Ok(expand(activations::LeakyRelu::new(alpha)))
+    });
     reg.insert("MaxPool", pools::maxpool);
+    reg.insert("MaxPool3D", pools::maxpool);
+    reg.insert("NonMaxSuppressionV3", non_max_suppression::non_max_suppression_v3);
+    reg.insert("NonMaxSuppressionV4", non_max_suppression::non_max_suppression_v4);
+    reg.insert("NonMaxSuppressionV5", non_max_suppression::non_max_suppression_v5);
     reg.insert("Relu", |_, _| Ok(expand(tract_hir::ops::activations::Clip::new(Some(0.0), None))));
     reg.insert("Relu6", |_, _| {
         Ok(expand(tract_hir::ops::activations::Clip::new(Some(0.0), Some(6.0))))
     });
+    reg.insert("ResizeBilinear", resize::resize_bilinear);
+    reg.insert("ResizeNearestNeighbor", resize::resize_nearest_neighbor);
+    reg.insert("Selu", |_, _| {
+        Ok(expand(activations::Selu::new(1.6732632423543772, 1.0507009873554805)))
+    });
     reg.insert("Sigmoid", |_, _| Ok(Box::new(tract_hir::ops::nn::sigmoid())));
     reg.insert("Softmax", |_, _| Ok(expand(LayerSoftmax::new(1))));
+    reg.insert("Softplus", |_, _| Ok(expand(activations::Softplus)));
     reg.insert("SpaceToBatchND", s2b::space_to_batch_nd);
     reg.insert("BatchToSpaceND", s2b::batch_to_space_nd);
+    reg.insert("TopKV2", top_k::top_k_v2);
 }
 
 pub fn strides(pb: &NodeDef) -> TractResult<Vec<usize>> {
@@ -36,12 +63,11 @@ pub fn strides(pb: &NodeDef) -> TractResult<Vec<usize>> {
 }
 
 pub fn data_format(pb: &NodeDef) -> TractResult<DataFormat> {
-    let df = if pb.get_attr_opt_raw_str("data_format")?.unwrap_or(b"NHWC") == b"NHWC" {
-        DataFormat::NHWC
-    } else {
-        DataFormat::NCHW
-    };
-    Ok(df)
+    match pb.get_attr_opt_raw_str("data_format")?.unwrap_or(b"NHWC") {
+        b"NHWC" | b"NDHWC" => Ok(DataFormat::NHWC),
+        b"NCHW" | b"NCDHW" => Ok(DataFormat::NCHW),
+        df => bail!("unsupported data format {}", String::from_utf8_lossy(df)),
+    }
 }
 
 pub fn padding(pb: &NodeDef) -> TractResult<PaddingSpec> {
@@ -0,0 +1,234 @@
+use tract_hir::internal::*;
+use tract_hir::ops::nn::BoxRepr;
+use tract_ndarray::{ArrayView1, Ix2};
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn non_max_suppression_v3(
+    _ctx: &ParsingContext,
+    _pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    Ok(Box::new(NonMaxSuppression::new(false, false, false, None)))
+}
+
+pub fn non_max_suppression_v4(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let pad = pb.get_attr_opt_bool("pad_to_max_output_size")?.unwrap_or(false);
+    Ok(Box::new(NonMaxSuppression::new(pad, false, true, None)))
+}
+
+pub fn non_max_suppression_v5(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let pad = pb.get_attr_opt_bool("pad_to_max_output_size")?.unwrap_or(false);
+    Ok(Box::new(NonMaxSuppression::new(pad, true, true, None)))
+}
+
+/// Indices of the boxes selected by non maximum suppression, as i32.
+///
+/// Covers NonMaxSuppressionV3, V4 (which also outputs the number of valid
+/// indices) and V5 (which also outputs the selected scores, and takes a soft
+/// NMS sigma input that must be zero). Unless the output is padded to the
+/// maximum output size, its length depends on the input values and is
+/// expressed by the `num_selected` symbol, created when the op is typed.
+#[derive(Debug, Clone, new, Hash)]
+pub struct NonMaxSuppression {
+    pad_to_max_output_size: bool,
+    with_scores: bool,
+    with_valid_outputs: bool,
+    num_selected: Option<Symbol>,
+}
+
+impl_dyn_hash!(NonMaxSuppression);
+
+impl NonMaxSuppression {
+    fn inputs(&self) -> usize {
+        5 + self.with_scores as usize
+    }
+
+    fn outputs(&self) -> usize {
+        1 + self.with_scores as usize + self.with_valid_outputs as usize
+    }
+
+    fn output_len(&self, max_output_size: Option<&Tensor>) -> TractResult<TDim> {
+        if self.pad_to_max_output_size {
+            if let Some(max) = max_output_size {
+                return Ok(max.cast_to_scalar::<i64>()?.max(0).to_dim());
+            }
+        }
+        if let Some(num_selected) = self.num_selected {
+            Ok(num_selected.to_dim())
+        } else {
+            bail!("NonMaxSuppression output length symbol is only created at typing")
+        }
+    }
+}
+
+impl Op for NonMaxSuppression {
+    fn name(&self) -> Cow<str> {
+        "NonMaxSuppression".into()
+    }
+
+    op_tf!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for NonMaxSuppression {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        if self.with_scores && inputs[5].cast_to_scalar::<f32>()? != 0.0 {
+            bail!("Soft non maximum suppression is not supported");
+        }
+        let boxes = inputs[0].cast_to::<f32>()?;
+        let boxes = boxes.to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let scores = inputs[1].cast_to::<f32>()?;
+        let scores = scores.as_slice::<f32>()?;
+        let max_output = inputs[2].cast_to_scalar::<i64>()?.max(0) as usize;
+        let selected = tract_hir::ops::nn::non_max_suppression(
+            boxes,
+            ArrayView1::from(scores),
+            BoxRepr::TwoPoints,
+            max_output,
+            inputs[3].cast_to_scalar::<f32>()?,
+            inputs[4].cast_to_scalar::<f32>()?,
+        );
+        let len = if self.pad_to_max_output_size { max_output } else { selected.len() };
+        let mut indices = vec![0i32; len];
+        let mut selected_scores = vec![0f32; len];
+        for (ix, &selected) in selected.iter().enumerate() {
+            indices[ix] = selected as i32;
+            selected_scores[ix] = scores[selected];
+        }
+        let mut outputs = tvec!(tensor1(&indices).into_arc_tensor());
+        if self.with_scores {
+            outputs.push(
+                tensor1(&selected_scores)
+                    .cast_to_dt(inputs[1].datum_type())?
+                    .into_owned()
+                    .into_arc_tensor(),
+            );
+        }
+        if self.with_valid_outputs {
+            outputs.push(rctensor0(selected.len() as i32));
+        }
+        Ok(outputs)
+    }
+}
+
+impl InferenceRulesOp for NonMaxSuppression {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, self.inputs())?;
+        check_output_arity(&outputs, self.outputs())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], 4.to_dim())?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[0].shape[0], &inputs[1].shape[0])?;
+        for input in &inputs[2..] {
+            s.equals(&input.rank, 0)?;
+        }
+        s.equals(&outputs[0].datum_type, i32::datum_type())?;
+        s.equals(&outputs[0].rank, 1)?;
+        if self.pad_to_max_output_size {
+            s.given(&inputs[2].value, move |s, max| {
+                s.equals(&outputs[0].shape[0], self.output_len(Some(&*max))?)
+            })?;
+        }
+        if self.with_scores {
+            s.equals(&outputs[1].datum_type, &inputs[1].datum_type)?;
+            s.equals(&outputs[1].shape, &outputs[0].shape)?;
+        }
+        if self.with_valid_outputs {
+            let valid = &outputs[self.outputs() - 1];
+            s.equals(&valid.datum_type, i32::datum_type())?;
+            s.equals(&valid.rank, 0)?;
+        }
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.outputs())
+    }
+
+    as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let op = NonMaxSuppression {
+            num_selected: Some(target.symbol_table.new_symbol("selected")),
+            ..self.clone()
+        };
+        let inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+        target.wire_node(&*node.name, op, &inputs)
+    }
+}
+
+impl TypedOp for NonMaxSuppression {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let len = self.output_len(inputs[2].konst.as_deref())?;
+        let mut facts = tvec!(TypedFact::dt_shape(i32::datum_type(), &[len.clone()]));
+        if self.with_scores {
+            facts.push(TypedFact::dt_shape(inputs[1].datum_type, &[len]));
+        }
+        if self.with_valid_outputs {
+            facts.push(TypedFact::dt_shape(i32::datum_type(), &[0usize; 0]));
+        }
+        Ok(facts)
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(max: i32) -> TVec<Arc<Tensor>> {
+        tvec!(
+            rctensor2(&[[0f32, 0., 1., 1.], [0., 0.1, 1., 1.1], [0., 10., 1., 11.]]),
+            rctensor1(&[0.9f32, 0.95, 0.5]),
+            rctensor0(max),
+            rctensor0(0.5f32),
+            rctensor0(0f32),
+            rctensor0(0f32),
+        )
+    }
+
+    #[test]
+    fn v5() {
+        let op = NonMaxSuppression::new(false, true, true, Some(Symbol::new("selected")));
+        let outputs = op.eval(inputs(3)).unwrap();
+        assert_eq!(*outputs[0], tensor1(&[1i32, 2]));
+        assert_eq!(*outputs[1], tensor1(&[0.95f32, 0.5]));
+        assert_eq!(*outputs[2], tensor0(2i32));
+    }
+
+    #[test]
+    fn v4_padded() {
+        let op = NonMaxSuppression::new(true, false, true, Some(Symbol::new("selected")));
+        let mut inputs = inputs(4);
+        inputs.pop();
+        let facts: TVec<TypedFact> = inputs.iter().map(|t| TypedFact::from(t.clone())).collect();
+        let output_facts = op.output_facts(&*facts.iter().collect::<TVec<_>>()).unwrap();
+        assert_eq!(output_facts[0].shape.as_concrete(), Some(&[4usize] as &[usize]));
+        let outputs = op.eval(inputs).unwrap();
+        assert_eq!(*outputs[0], tensor1(&[1i32, 2, 0, 0]));
+        assert_eq!(*outputs[1], tensor0(2i32));
+    }
+}
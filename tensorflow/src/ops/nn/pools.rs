@@ -4,40 +4,33 @@ use tract_hir::internal::*;
 use tract_hir::ops::cnn::*;
 
 pub fn avgpool(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
-    let ksize: Vec<usize> = pb.get_attr_list_int("ksize")?;
-    let data_format = super::data_format(pb)?;
-    let kshape = data_format.shape(ksize)?;
-    let strides = super::strides(pb)?;
-    let padding = super::padding(pb)?;
-    Ok(Box::new(SumPool::new(
-        PoolSpec::new(
-            data_format,
-            kshape.hw_dims().into(),
-            padding,
-            None,
-            Some(strides[kshape.hw_axes()].into()),
-            None,
-        ),
-        false,
-        true,
-    )))
+    Ok(Box::new(SumPool::new(pool_spec(pb)?, false, true)))
 }
 
 pub fn maxpool(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    Ok(Box::new(MaxPool::new(pool_spec(pb)?, None)))
+}
+
+/// Pool geometry of 2D (`MaxPool`, `AvgPool`) and 3D (`MaxPool3D`,
+/// `AvgPool3D`) pools: `ksize` and `strides` have a value per input axis.
+fn pool_spec(pb: &NodeDef) -> TractResult<PoolSpec> {
     let ksize: Vec<usize> = pb.get_attr_list_int("ksize")?;
     let data_format = super::data_format(pb)?;
     let kshape = data_format.shape(ksize)?;
-    let strides = super::strides(pb)?;
+    let strides: Vec<usize> = pb.get_attr_list_int("strides")?;
+    if strides.len() != kshape.rank() {
+        bail!("strides must have {} values, found {:?}", kshape.rank(), strides)
+    }
+    if strides[kshape.n_axis().unwrap()] != 1 || strides[kshape.c_axis()] != 1 {
+        bail!("strides must be 1 on batch and channel axes, found {:?}", strides)
+    }
     let padding = super::padding(pb)?;
-    Ok(Box::new(MaxPool::new(
-        PoolSpec::new(
-            data_format,
-            kshape.hw_dims().into(),
-            padding,
-            None,
-            Some(strides[kshape.hw_axes()].into()),
-            None,
-        ),
+    Ok(PoolSpec::new(
+        data_format,
+        kshape.hw_dims().into(),
+        padding,
+        None,
+        Some(strides[kshape.hw_axes()].into()),
         None,
-    )))
+    ))
 }
@@ -0,0 +1,246 @@
+use tract_hir::internal::*;
+use tract_ndarray::{ArrayD, Axis};
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn resize_bilinear(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    resize(pb, Interpolation::Bilinear)
+}
+
+pub fn resize_nearest_neighbor(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    resize(pb, Interpolation::Nearest)
+}
+
+fn resize(pb: &NodeDef, interpolation: Interpolation) -> TractResult<Box<dyn InferenceOp>> {
+    let align_corners = pb.get_attr_opt_bool("align_corners")?.unwrap_or(false);
+    let half_pixel_centers = pb.get_attr_opt_bool("half_pixel_centers")?.unwrap_or(false);
+    Ok(Box::new(Resize::new(interpolation, align_corners, half_pixel_centers)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub enum Interpolation {
+    Bilinear,
+    Nearest,
+}
+
+/// ResizeBilinear and ResizeNearestNeighbor: resize the spatial axes of a
+/// NHWC image to the (constant) size given as second input.
+#[derive(Debug, Clone, new, Hash)]
+pub struct Resize {
+    interpolation: Interpolation,
+    align_corners: bool,
+    half_pixel_centers: bool,
+}
+
+impl_dyn_hash!(Resize);
+
+impl Resize {
+    fn scale(&self, input: usize, output: usize) -> f32 {
+        if self.align_corners && output > 1 {
+            (input - 1) as f32 / (output - 1) as f32
+        } else {
+            input as f32 / output as f32
+        }
+    }
+
+    fn output_dt(&self, input: DatumType) -> DatumType {
+        match self.interpolation {
+            Interpolation::Bilinear => DatumType::F32,
+            Interpolation::Nearest => input,
+        }
+    }
+
+    fn output_shape<D: DimLike>(&self, input: &[D], size: &Tensor) -> TractResult<TVec<D>> {
+        if input.len() != 4 || size.len() != 2 {
+            bail!("Expected a 4D image and a 2 values size, got {:?} and {:?}", input, size)
+        }
+        let size = size.cast_to::<i64>()?;
+        let size = size.as_slice::<i64>()?;
+        Ok(tvec!(
+            input[0].clone(),
+            D::from(size[0] as usize),
+            D::from(size[1] as usize),
+            input[3].clone()
+        ))
+    }
+
+    fn resize_axis_bilinear(&self, input: ArrayD<f32>, axis: usize, len: usize) -> ArrayD<f32> {
+        let len_in = input.shape()[axis];
+        let scale = self.scale(len_in, len);
+        let mut shape = input.shape().to_vec();
+        shape[axis] = len;
+        let mut output = ArrayD::<f32>::zeros(shape);
+        for x in 0..len {
+            let x_in = if self.half_pixel_centers {
+                (x as f32 + 0.5) * scale - 0.5
+            } else {
+                x as f32 * scale
+            };
+            let floor = x_in.floor();
+            let lower = (floor.max(0.0) as usize).min(len_in - 1);
+            let upper = (x_in.ceil().max(0.0) as usize).min(len_in - 1);
+            let lerp = x_in - floor;
+            let lower = input.index_axis(Axis(axis), lower);
+            let upper = input.index_axis(Axis(axis), upper);
+            output
+                .index_axis_mut(Axis(axis), x)
+                .assign(&(&lower.mapv(|v| v * (1.0 - lerp)) + &upper.mapv(|v| v * lerp)));
+        }
+        output
+    }
+
+    fn resize_axis_nearest<T: Datum>(
+        &self,
+        input: ArrayD<T>,
+        axis: usize,
+        len: usize,
+    ) -> ArrayD<T> {
+        let len_in = input.shape()[axis];
+        let scale = self.scale(len_in, len);
+        let mut shape = input.shape().to_vec();
+        shape[axis] = len;
+        let mut output = ArrayD::<T>::default(shape);
+        for x in 0..len {
+            let x_in =
+                if self.half_pixel_centers { (x as f32 + 0.5) * scale } else { x as f32 * scale };
+            let x_in = if self.align_corners { x_in.round() } else { x_in.floor() };
+            let x_in = (x_in.max(0.0) as usize).min(len_in - 1);
+            output.index_axis_mut(Axis(axis), x).assign(&input.index_axis(Axis(axis), x_in));
+        }
+        output
+    }
+
+    fn eval_nearest<T: Datum>(&self, input: &Tensor, size: &[usize]) -> TractResult<Tensor> {
+        let mut data = input.to_array_view::<T>()?.to_owned();
+        for (ix, &len) in size.iter().enumerate() {
+            data = self.resize_axis_nearest(data, ix + 1, len);
+        }
+        Ok(data.into_tensor())
+    }
+}
+
+impl Op for Resize {
+    fn name(&self) -> Cow<str> {
+        match self.interpolation {
+            Interpolation::Bilinear => "ResizeBilinear",
+            Interpolation::Nearest => "ResizeNearestNeighbor",
+        }
+        .into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "align_corners: {} half_pixel_centers: {}",
+            self.align_corners, self.half_pixel_centers
+        )])
+    }
+
+    op_tf!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for Resize {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, size) = args_2!(inputs);
+        let output_shape = self.output_shape(input.shape(), &size)?;
+        let size = &output_shape[1..3];
+        let output = match self.interpolation {
+            Interpolation::Bilinear => {
+                let mut data = input.cast_to::<f32>()?.into_owned().into_array::<f32>()?;
+                for (ix, &len) in size.iter().enumerate() {
+                    data = self.resize_axis_bilinear(data, ix + 1, len);
+                }
+                data.into_tensor()
+            }
+            Interpolation::Nearest => {
+                dispatch_datum!(Self::eval_nearest(input.datum_type())(self, &input, size))?
+            }
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for Resize {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[1].shape[0], 2.to_dim())?;
+        s.given(&inputs[0].datum_type, move |s, dt| {
+            s.equals(&outputs[0].datum_type, self.output_dt(dt))
+        })?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[3], &inputs[0].shape[3])?;
+        s.given(&inputs[1].value, move |s, size| {
+            let size = size.cast_to::<i64>()?;
+            let size = size.as_slice::<i64>()?;
+            s.equals(&outputs[0].shape[1], size[0].to_dim())?;
+            s.equals(&outputs[0].shape[2], size[1].to_dim())
+        })
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for Resize {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let size = inputs[1].konst.as_ref().context("Expect size to be a constant")?;
+        let shape = self.output_shape(&*inputs[0].shape.to_tvec(), size)?;
+        Ok(tvec!(TypedFact::dt_shape(self.output_dt(inputs[0].datum_type), &*shape)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(op: Resize, input: Tensor, size: &[i32]) -> Tensor {
+        op.eval(tvec!(input.into_arc_tensor(), rctensor1(size))).unwrap().remove(0).into_tensor()
+    }
+
+    #[test]
+    fn bilinear_upsample() {
+        let input = tensor4(&[[[[0f32], [2.]], [[4.], [6.]]]]);
+        let output = run(Resize::new(Interpolation::Bilinear, false, false), input, &[1, 4]);
+        assert_eq!(output, tensor4(&[[[[0f32], [1.], [2.], [2.]]]]));
+    }
+
+    #[test]
+    fn bilinear_align_corners() {
+        let input = tensor4(&[[[[0f32], [3.]]]]);
+        let output = run(Resize::new(Interpolation::Bilinear, true, false), input, &[1, 4]);
+        assert_eq!(output, tensor4(&[[[[0f32], [1.], [2.], [3.]]]]));
+    }
+
+    #[test]
+    fn bilinear_half_pixel_centers() {
+        let input = tensor4(&[[[[0f32], [4.]]]]);
+        let output = run(Resize::new(Interpolation::Bilinear, false, true), input, &[1, 4]);
+        assert_eq!(output, tensor4(&[[[[0f32], [1.], [3.], [4.]]]]));
+    }
+
+    #[test]
+    fn nearest_upsample() {
+        let input = tensor4(&[[[[1i32], [2]]]]);
+        let output = run(Resize::new(Interpolation::Nearest, false, false), input, &[1, 4]);
+        assert_eq!(output, tensor4(&[[[[1i32], [1], [2], [2]]]]));
+    }
+}
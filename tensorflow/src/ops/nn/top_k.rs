@@ -0,0 +1,74 @@
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::array::TopK;
+
+use crate::model::ParsingContext;
+use crate::tfpb::tensorflow::NodeDef;
+
+pub fn top_k_v2(_ctx: &ParsingContext, _pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    Ok(expand(TopKV2))
+}
+
+/// Largest values over the last axis, sorted. Indices are i32.
+#[derive(Debug, Clone, new, Hash)]
+pub struct TopKV2;
+
+impl_dyn_hash!(TopKV2);
+
+impl Expansion for TopKV2 {
+    fn name(&self) -> Cow<str> {
+        "TopKV2".into()
+    }
+
+    op_tf!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 2)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&outputs[1].datum_type, DatumType::I32)?;
+        s.equals(&inputs[1].rank, 0)?;
+        s.equals(&outputs[0].rank, &inputs[0].rank)?;
+        s.equals(&outputs[1].rank, &inputs[0].rank)?;
+        s.given_2(&inputs[0].shape, &inputs[1].value, move |s, shape, k| {
+            let mut shape = shape.clone();
+            *shape.last_mut().context("TopKV2 input must be at least 1D")? =
+                k.cast_to_scalar::<i64>()?.to_dim();
+            s.equals(&outputs[0].shape, shape.clone())?;
+            s.equals(&outputs[1].shape, shape)
+        })
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        if let Some(ref k) = target.outlet_fact(inputs[1])?.konst {
+            let k = k.cast_to_scalar::<i64>()? as usize;
+            let axis = target.outlet_fact(inputs[0])?.rank() - 1;
+            let topk = target.wire_node(
+                format!("{}.topk", prefix),
+                TopK::new(axis, k, true),
+                &inputs[0..1],
+            )?;
+            let indices = target.wire_node(
+                format!("{}.indices", prefix),
+                tract_hir::ops::cast(DatumType::I32),
+                &[topk[1]],
+            )?;
+            Ok(tvec!(topk[0], indices[0]))
+        } else {
+            bail!("Expect k to be a constant")
+        }
+    }
+}
@@ -92,6 +92,7 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
                     DataType::DtDouble => Self::from_raw::<f64>(&dims, content)?,
                     DataType::DtInt32 => Self::from_raw::<i32>(&dims, content)?,
                     DataType::DtInt64 => Self::from_raw::<i64>(&dims, content)?,
+                    DataType::DtBool => Self::from_raw::<bool>(&dims, content)?,
                     _ => unimplemented!("missing type (for get_tensor_content) {:?}", dtype),
                 }
             }
@@ -101,6 +102,7 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
                 DataType::DtInt64 => tensor_from_repeated_field(&*dims, t.int64_val.to_vec())?,
                 DataType::DtFloat => tensor_from_repeated_field(&*dims, t.float_val.to_vec())?,
                 DataType::DtDouble => tensor_from_repeated_field(&*dims, t.double_val.to_vec())?,
+                DataType::DtBool => tensor_from_repeated_field(&*dims, t.bool_val.to_vec())?,
                 DataType::DtString => {
                     let strings =
                         t.string_val.iter().map(|s| Blob(s.to_owned())).collect::<Vec<Blob>>();
@@ -160,6 +162,9 @@ impl<'a> TryFrom<&'a Tensor> for TensorProto {
             DatumType::I64 => {
                 tensor.int64_val = from.to_array_view::<i64>()?.iter().cloned().collect();
             }
+            DatumType::Bool => {
+                tensor.bool_val = from.to_array_view::<bool>()?.iter().cloned().collect();
+            }
             _ => unimplemented!("missing type {:?}", from.datum_type()),
         }
         Ok(tensor)
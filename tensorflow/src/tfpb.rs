@@ -14,7 +14,9 @@ pub mod tensorflow {
 
 use self::tensorflow::attr_value::ListValue;
 use self::tensorflow::attr_value::Value;
-use self::tensorflow::{AttrValue, DataType, GraphDef, NodeDef, TensorProto, TensorShapeProto};
+use self::tensorflow::{
+    AttrValue, DataType, GraphDef, NameAttrList, NodeDef, TensorProto, TensorShapeProto,
+};
 
 use std::convert::TryInto;
 
@@ -202,6 +204,21 @@ impl NodeDef {
         Ok(None)
     }
 
+    pub fn get_attr_func(&self, name: &str) -> TractResult<&str> {
+        Ok(self.get_attr_opt_func(name)?.with_context(|| {
+            format!("Node {} ({}) expected function attribute '{}'", self.name, self.op, name)
+        })?)
+    }
+
+    pub fn get_attr_opt_func(&self, name: &str) -> TractResult<Option<&str>> {
+        if let Some(a) = self.attr.get(name) {
+            if let Value::Func(f) = a.value.as_ref().unwrap() {
+                return Ok(Some(&*f.name));
+            }
+        };
+        Ok(None)
+    }
+
     pub fn get_attr_list_int<T: tract_num_traits::FromPrimitive>(
         &self,
         name: &str,
@@ -236,6 +253,12 @@ impl<'a> From<&'a str> for AttrValue {
     }
 }
 
+impl From<bool> for AttrValue {
+    fn from(t: bool) -> AttrValue {
+        AttrValue { value: Some(Value::B(t)) }
+    }
+}
+
 impl From<i32> for AttrValue {
     fn from(t: i32) -> AttrValue {
         AttrValue::from(t as i64)
@@ -277,6 +300,12 @@ impl From<TensorProto> for AttrValue {
     }
 }
 
+impl From<NameAttrList> for AttrValue {
+    fn from(t: NameAttrList) -> AttrValue {
+        AttrValue { value: Some(Value::Func(t)) }
+    }
+}
+
 impl From<TensorShapeProto> for AttrValue {
     fn from(t: TensorShapeProto) -> AttrValue {
         AttrValue { value: Some(Value::Shape(t.into())) }
@@ -0,0 +1,196 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use tract_ndarray::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::{DtBool, DtFloat, DtInt32};
+
+fn tensor_f32() -> BoxedStrategy<Tensor> {
+    vec(1usize..5, 1usize..4)
+        .prop_map(|shape| {
+            let len = shape.iter().product::<usize>();
+            ArrayD::from_shape_vec(shape, (0..len).map(|x| ((x * 7) % 11) as f32).collect())
+                .unwrap()
+                .into()
+        })
+        .boxed()
+}
+
+// checks the n-th output of "op" through an Identity node
+fn compare_output(
+    graph: tfpb::tensorflow::GraphDef,
+    inputs: Vec<(&str, Tensor)>,
+    output: usize,
+) -> std::result::Result<(), ::proptest::test_runner::TestCaseError> {
+    let graph =
+        graph.node(tfpb::node().name("result").op("Identity").input(&format!("op:{}", output)));
+    compare(&graph.write_to_bytes().unwrap(), inputs, "result")
+}
+
+proptest! {
+    #[test]
+    fn split(ref i in tensor_f32(), axis in 0usize..3, num_split in 1usize..4, output in 0usize..3) {
+        prop_assume!(axis < i.rank() && i.shape()[axis] % num_split == 0 && output < num_split);
+        let node = tfpb::node()
+            .name("op")
+            .op("Split")
+            .input("axis")
+            .input("data")
+            .attr("T", DtFloat)
+            .attr("num_split", num_split as i64);
+        let graph = tfpb::graph()
+            .node(const_i32("axis", &tensor0(axis as i32)))
+            .node(placeholder_f32("data"))
+            .node(node);
+        compare_output(graph, vec!(("data", i.clone())), output)?;
+    }
+
+    #[test]
+    fn split_v(ref i in tensor_f32(), axis in 0usize..3, first in 0usize..5, output in 0usize..2) {
+        prop_assume!(axis < i.rank() && first <= i.shape()[axis]);
+        let node = tfpb::node()
+            .name("op")
+            .op("SplitV")
+            .input("data")
+            .input("sizes")
+            .input("axis")
+            .attr("T", DtFloat)
+            .attr("Tlen", DtInt32)
+            .attr("num_split", 2i64);
+        let graph = tfpb::graph()
+            .node(placeholder_f32("data"))
+            .node(const_i32("sizes", &tensor1(&[first as i32, -1])))
+            .node(const_i32("axis", &tensor0(axis as i32)))
+            .node(node);
+        compare_output(graph, vec!(("data", i.clone())), output)?;
+    }
+
+    #[test]
+    fn unpack(ref i in tensor_f32(), axis in 0usize..3, output in 0usize..4) {
+        prop_assume!(axis < i.rank() && output < i.shape()[axis]);
+        let node = tfpb::node()
+            .name("op")
+            .op("Unpack")
+            .input("data")
+            .attr("T", DtFloat)
+            .attr("num", i.shape()[axis] as i64)
+            .attr("axis", axis as i64);
+        let graph = tfpb::graph().node(placeholder_f32("data")).node(node);
+        compare_output(graph, vec!(("data", i.clone())), output)?;
+    }
+
+    #[test]
+    fn top_k_v2(ref i in tensor_f32(), k in 1usize..5, output in 0usize..2) {
+        prop_assume!(k <= *i.shape().last().unwrap());
+        let node = tfpb::node()
+            .name("op")
+            .op("TopKV2")
+            .input("data")
+            .input("k")
+            .attr("T", DtFloat)
+            .attr("sorted", true);
+        let graph = tfpb::graph()
+            .node(placeholder_f32("data"))
+            .node(const_i32("k", &tensor0(k as i32)))
+            .node(node);
+        compare_output(graph, vec!(("data", i.clone())), output)?;
+    }
+
+    #[test]
+    fn mirror_pad(ref i in tensor_f32(), symmetric in any::<bool>(), pads in vec(0usize..3, 6)) {
+        let rank = i.rank();
+        let limit = |d: usize| if symmetric { i.shape()[d] } else { i.shape()[d] - 1 };
+        prop_assume!((0..rank).all(|d| pads[2 * d] <= limit(d) && pads[2 * d + 1] <= limit(d)));
+        let pads = Array2::from_shape_fn((rank, 2), |(d, s)| pads[2 * d + s] as i32);
+        let node = tfpb::node()
+            .name("op")
+            .op("MirrorPad")
+            .input("data")
+            .input("paddings")
+            .attr("T", DtFloat)
+            .attr("Tpaddings", DtInt32)
+            .attr("mode", if symmetric { "SYMMETRIC" } else { "REFLECT" });
+        let graph = tfpb::graph()
+            .node(placeholder_f32("data"))
+            .node(const_i32("paddings", &pads.into_tensor()))
+            .node(node);
+        compare(&graph.write_to_bytes().unwrap(), vec!(("data", i.clone())), "op")?;
+    }
+}
+
+#[test]
+fn one_hot() {
+    for axis in &[-1i64, 0, 1] {
+        let node = tfpb::node()
+            .name("op")
+            .op("OneHot")
+            .input("indices")
+            .input("depth")
+            .input("on")
+            .input("off")
+            .attr("T", DtFloat)
+            .attr("TI", DtInt32)
+            .attr("axis", *axis);
+        let graph = tfpb::graph()
+            .node(placeholder_i32("indices"))
+            .node(const_i32("depth", &tensor0(4i32)))
+            .node(const_f32("on", &tensor0(5.0f32)))
+            .node(const_f32("off", &tensor0(-1.0f32)))
+            .node(node);
+        let indices = tensor2(&[[0i32, 3, -1], [2, 4, 1]]);
+        compare(&graph.write_to_bytes().unwrap(), vec![("indices", indices)], "op").unwrap();
+    }
+}
+
+fn select(op: &str, cond: Tensor, a: Tensor, b: Tensor) {
+    let node =
+        tfpb::node().name("op").op(op).input("cond").input("a").input("b").attr("T", DtFloat);
+    let graph = tfpb::graph()
+        .node(placeholder("cond", DtBool, None))
+        .node(placeholder_f32("a"))
+        .node(placeholder_f32("b"))
+        .node(node);
+    compare(&graph.write_to_bytes().unwrap(), vec![("cond", cond), ("a", a), ("b", b)], "op")
+        .unwrap();
+}
+
+#[test]
+fn select_same_shape() {
+    let cond = tensor2(&[[true, false], [false, true]]);
+    select("Select", cond, tensor2(&[[1f32, 2.], [3., 4.]]), tensor2(&[[5f32, 6.], [7., 8.]]));
+}
+
+#[test]
+fn select_vector_condition() {
+    let cond = tensor1(&[false, true]);
+    select("Select", cond, tensor2(&[[1f32, 2.], [3., 4.]]), tensor2(&[[5f32, 6.], [7., 8.]]));
+}
+
+#[test]
+fn select_v2_broadcasting() {
+    let cond = tensor2(&[[true], [false]]);
+    select("SelectV2", cond, tensor1(&[1f32, 2., 3.]), tensor0(0f32));
+}
+
+#[test]
+fn where_op() {
+    let node = tfpb::node().name("op").op("Where").input("cond").attr("T", DtBool);
+    let graph = tfpb::graph().node(placeholder("cond", DtBool, None)).node(node);
+    let cond = tensor2(&[[true, false, true], [false, false, true]]);
+    // output shape depends on the input value, so the op can not be typed
+    compare_optim(&graph.write_to_bytes().unwrap(), &vec![("cond", cond)], "op", Mode::Infer)
+        .unwrap();
+}
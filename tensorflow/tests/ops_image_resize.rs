@@ -0,0 +1,66 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::prelude::*;
+use tract_ndarray::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::DtFloat;
+
+fn resize_pb(
+    op: &str,
+    size: (usize, usize),
+    align_corners: bool,
+    half_pixel_centers: bool,
+) -> TractResult<Vec<u8>> {
+    let resize = tfpb::node()
+        .name("resize")
+        .op(op)
+        .input("data")
+        .input("size")
+        .attr("align_corners", align_corners)
+        .attr("half_pixel_centers", half_pixel_centers)
+        .attr("T", DtFloat);
+    let graph = tfpb::graph()
+        .node(placeholder_f32("data"))
+        .node(const_i32("size", &tensor1(&[size.0 as i32, size.1 as i32])))
+        .node(resize);
+    Ok(graph.write_to_bytes()?)
+}
+
+fn problem() -> BoxedStrategy<(Tensor, (usize, usize), bool, bool)> {
+    (1usize..3, 1usize..5, 1usize..5, 1usize..3, 1usize..9, 1usize..9, 0usize..3)
+        .prop_map(|(n, h, w, c, oh, ow, mode)| {
+            let data = Array4::from_shape_fn((n, h, w, c), |(a, b, d, e)| {
+                (a * 17 + b * 7 + d * 3 + e) as f32
+            });
+            // TF forbids align_corners and half_pixel_centers together
+            (data.into(), (oh, ow), mode == 1, mode == 2)
+        })
+        .boxed()
+}
+
+proptest! {
+    #[test]
+    fn bilinear((ref i, size, align_corners, half_pixel_centers) in problem()) {
+        let model = resize_pb("ResizeBilinear", size, align_corners, half_pixel_centers).unwrap();
+        compare(&model, vec!(("data", i.clone())), "resize")?;
+    }
+
+    #[test]
+    fn nearest((ref i, size, align_corners, half_pixel_centers) in problem()) {
+        let model =
+            resize_pb("ResizeNearestNeighbor", size, align_corners, half_pixel_centers).unwrap();
+        compare(&model, vec!(("data", i.clone())), "resize")?;
+    }
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use tract_ndarray::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::{DtFloat, DtInt32, DtInt64};
+
+fn tensor_f32() -> BoxedStrategy<Tensor> {
+    vec(1usize..4, 1usize..4)
+        .prop_flat_map(|shape| {
+            let len = shape.iter().product::<usize>();
+            (Just(shape), vec(-50i32..50, len..len + 1))
+        })
+        .prop_map(|(shape, data)| {
+            ArrayD::from_shape_vec(shape, data.into_iter().map(|x| x as f32 / 10.0).collect())
+                .unwrap()
+                .into()
+        })
+        .boxed()
+}
+
+fn unary_pb(op: &str) -> TractResult<Vec<u8>> {
+    let node = tfpb::node().name("op").op(op).input("data").attr("T", DtFloat);
+    Ok(tfpb::graph().node(placeholder_f32("data")).node(node).write_to_bytes()?)
+}
+
+proptest! {
+    #[test]
+    fn unary(ref i in tensor_f32(), op in prop::sample::select(vec![
+            "Elu", "Erf", "Exp", "LeakyRelu", "Selu", "Softplus", "Square"])) {
+        compare(&unary_pb(op).unwrap(), vec!(("data", i.clone())), "op")?;
+    }
+
+    #[test]
+    fn sqrt(ref i in tensor_f32()) {
+        let i = i.to_array_view::<f32>().unwrap().mapv(|x| x.abs()).into_tensor();
+        compare(&unary_pb("Sqrt").unwrap(), vec!(("data", i)), "op")?;
+    }
+
+    #[test]
+    fn squared_difference(ref i in tensor_f32()) {
+        let b = Tensor::from(arr1(&[0.5f32]));
+        let node = tfpb::node()
+            .name("op")
+            .op("SquaredDifference")
+            .input("a")
+            .input("b")
+            .attr("T", DtFloat);
+        let graph = tfpb::graph().node(placeholder_f32("a")).node(const_f32("b", &b)).node(node);
+        compare(&graph.write_to_bytes().unwrap(), vec!(("a", i.clone())), "op")?;
+    }
+
+    #[test]
+    fn arg_max(ref i in tensor_f32(), axis in 0usize..4, i64_output in any::<bool>()) {
+        prop_assume!(axis < i.rank());
+        let node = tfpb::node()
+            .name("op")
+            .op("ArgMax")
+            .input("data")
+            .input("axis")
+            .attr("T", DtFloat)
+            .attr("Tidx", DtInt32)
+            .attr("output_type", if i64_output { DtInt64 } else { DtInt32 });
+        let graph = tfpb::graph()
+            .node(placeholder_f32("data"))
+            .node(const_i32("axis", &tensor0(axis as i32)))
+            .node(node);
+        compare(&graph.write_to_bytes().unwrap(), vec!(("data", i.clone())), "op")?;
+    }
+
+    #[test]
+    fn cumsum(ref i in tensor_f32(), axis in 0usize..4, exclusive in any::<bool>(), reverse in any::<bool>()) {
+        prop_assume!(axis < i.rank());
+        let node = tfpb::node()
+            .name("op")
+            .op("Cumsum")
+            .input("data")
+            .input("axis")
+            .attr("T", DtFloat)
+            .attr("Tidx", DtInt32)
+            .attr("exclusive", exclusive)
+            .attr("reverse", reverse);
+        let graph = tfpb::graph()
+            .node(placeholder_f32("data"))
+            .node(const_i32("axis", &tensor0(axis as i32)))
+            .node(node);
+        compare(&graph.write_to_bytes().unwrap(), vec!(("data", i.clone())), "op")?;
+    }
+}
+
+fn batch_mat_mul_pb(adj_x: bool, adj_y: bool) -> TractResult<Vec<u8>> {
+    let node = tfpb::node()
+        .name("op")
+        .op("BatchMatMulV2")
+        .input("a")
+        .input("b")
+        .attr("T", DtFloat)
+        .attr("adj_x", adj_x)
+        .attr("adj_y", adj_y);
+    Ok(tfpb::graph()
+        .node(placeholder_f32("a"))
+        .node(placeholder_f32("b"))
+        .node(node)
+        .write_to_bytes()?)
+}
+
+#[test]
+fn batch_mat_mul_v2_broadcasting() {
+    let a: Tensor =
+        Array::from_shape_fn((2, 1, 2, 3), |(a, b, c, d)| (a + b + c * 2 + d) as f32).into();
+    let b: Tensor =
+        Array::from_shape_fn((3, 3, 4), |(a, b, c)| (a * 3 + b) as f32 - c as f32).into();
+    compare(&batch_mat_mul_pb(false, false).unwrap(), vec![("a", a), ("b", b)], "op").unwrap();
+}
+
+#[test]
+fn batch_mat_mul_v2_adjoints() {
+    let a: Tensor = Array::from_shape_fn((2, 3, 2), |(a, b, c)| (a + b * 2 + c) as f32).into();
+    let b: Tensor =
+        Array::from_shape_fn((2, 4, 3), |(a, b, c)| (a * 3 + b) as f32 - c as f32).into();
+    compare(&batch_mat_mul_pb(true, true).unwrap(), vec![("a", a), ("b", b)], "op").unwrap();
+}
+
+fn einsum(equation: &str, inputs: Vec<Tensor>) {
+    let mut node = tfpb::node()
+        .name("op")
+        .op("Einsum")
+        .attr("T", DtFloat)
+        .attr("N", inputs.len() as i64)
+        .attr("equation", equation);
+    let mut graph = tfpb::graph();
+    let mut graph_inputs = vec![];
+    for (ix, input) in inputs.into_iter().enumerate() {
+        let name = format!("input-{}", ix);
+        graph = graph.node(placeholder_f32(&name));
+        node = node.input(&name);
+        graph_inputs.push((name, input));
+    }
+    let graph = graph.node(node).write_to_bytes().unwrap();
+    compare(&graph, graph_inputs, "op").unwrap();
+}
+
+#[test]
+fn einsum_matmul() {
+    let a: Tensor = Array::from_shape_fn((2, 3), |(a, b)| (a * 3 + b) as f32).into();
+    let b: Tensor = Array::from_shape_fn((3, 4), |(a, b)| a as f32 - b as f32).into();
+    einsum("ij,jk->ik", vec![a, b]);
+}
+
+#[test]
+fn einsum_batched_attention() {
+    let q: Tensor =
+        Array::from_shape_fn((2, 3, 4), |(a, b, c)| (a + b * 2) as f32 - c as f32).into();
+    let k: Tensor = Array::from_shape_fn((2, 5, 4), |(a, b, c)| (a * b + c) as f32).into();
+    einsum("bqd,bkd->bqk", vec![q, k]);
+}
+
+#[test]
+fn einsum_transpose_and_reduce() {
+    let a: Tensor = Array::from_shape_fn((2, 3, 4), |(a, b, c)| (a * 12 + b * 4 + c) as f32).into();
+    einsum("abc->ca", vec![a]);
+}
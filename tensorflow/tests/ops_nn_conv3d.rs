@@ -0,0 +1,58 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::prelude::*;
+use tract_ndarray::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::DtFloat;
+
+fn conv3d_pb(kernel: &Tensor, strides: usize, valid: bool) -> TractResult<Vec<u8>> {
+    let s = strides as i64;
+    let conv = tfpb::node()
+        .name("conv")
+        .op("Conv3D")
+        .input("data")
+        .input("kernel")
+        .attr("strides", vec![1, s, s, s, 1])
+        .attr("padding", if valid { "VALID" } else { "SAME" })
+        .attr("T", DtFloat);
+    let graph =
+        tfpb::graph().node(placeholder_f32("data")).node(const_f32("kernel", kernel)).node(conv);
+    Ok(graph.write_to_bytes()?)
+}
+
+fn img_and_ker() -> BoxedStrategy<(Tensor, Tensor, usize, bool)> {
+    (1usize..3, 1usize..3, 1usize..3, 1usize..3, any::<bool>())
+        .prop_flat_map(|(ic, oc, k, s, valid)| {
+            (Just((ic, oc, k, s, valid)), k..k + 4, k..k + 4, k..k + 4)
+        })
+        .prop_map(|((ic, oc, k, s, valid), d, h, w)| {
+            let img = Array::from_shape_fn((1, d, h, w, ic), |(_, a, b, c, e)| {
+                ((a * 7 + b * 5 + c * 3 + e) % 9) as f32 - 4.0
+            });
+            let ker = Array::from_shape_fn((k, k, k, ic, oc), |(a, b, c, e, f)| {
+                ((a + b * 2 + c * 3 + e * 5 + f) % 5) as f32 - 2.0
+            });
+            (img.into(), ker.into(), s, valid)
+        })
+        .boxed()
+}
+
+proptest! {
+    #[test]
+    fn conv3d_compare((ref i, ref k, strides, valid) in img_and_ker()) {
+        let model = conv3d_pb(k, strides, valid).unwrap();
+        compare(&model, vec!(("data", i.clone())), "conv")?;
+    }
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::prelude::*;
+use tract_ndarray::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::DataType::DtFloat;
+
+fn deconv_pb(
+    input_sizes: &[usize],
+    kernel: &Tensor,
+    strides: (usize, usize),
+    valid: bool,
+) -> TractResult<Vec<u8>> {
+    let sizes = tensor1(&input_sizes.iter().map(|d| *d as i32).collect::<Vec<_>>());
+    let deconv = tfpb::node()
+        .name("deconv")
+        .op("Conv2DBackpropInput")
+        .input("input_sizes")
+        .input("kernel")
+        .input("data")
+        .attr("strides", vec![1, strides.0 as i64, strides.1 as i64, 1])
+        .attr("padding", if valid { "VALID" } else { "SAME" })
+        .attr("T", DtFloat);
+    let graph = tfpb::graph()
+        .node(const_i32("input_sizes", &sizes))
+        .node(const_f32("kernel", kernel))
+        .node(placeholder_f32("data"))
+        .node(deconv);
+    Ok(graph.write_to_bytes()?)
+}
+
+// generates a forward convolution problem, and the input gradient of its output
+fn problem() -> BoxedStrategy<(Vec<usize>, Tensor, Tensor, (usize, usize), bool)> {
+    (1usize..3, 1usize..3, 1usize..3, 1usize..3, 1usize..3, 1usize..3, any::<bool>())
+        .prop_flat_map(|(ic, oc, kh, kw, sh, sw, valid)| {
+            (Just((ic, oc, kh, kw, sh, sw, valid)), kh..kh + 6, kw..kw + 6)
+        })
+        .prop_map(|((ic, oc, kh, kw, sh, sw, valid), h, w)| {
+            let (oh, ow) = if valid {
+                ((h - kh) / sh + 1, (w - kw) / sw + 1)
+            } else {
+                ((h + sh - 1) / sh, (w + sw - 1) / sw)
+            };
+            let kernel = Array4::from_shape_fn((kh, kw, ic, oc), |(a, b, c, d)| {
+                ((a * 7 + b * 5 + c * 3 + d) % 5) as f32 - 2.0
+            });
+            let data = Array4::from_shape_fn((1, oh, ow, oc), |(_, a, b, c)| {
+                ((a * 3 + b * 2 + c) % 7) as f32 - 3.0
+            });
+            (vec![1, h, w, ic], kernel.into(), data.into(), (sh, sw), valid)
+        })
+        .boxed()
+}
+
+proptest! {
+    #[test]
+    fn deconv_compare((ref sizes, ref k, ref i, strides, valid) in problem()) {
+        let model = deconv_pb(sizes, k, strides, valid).unwrap();
+        compare(&model, vec!(("data", i.clone())), "deconv")?;
+    }
+}
+
+#[test]
+fn deconv_same_stride_2_odd_output() {
+    let k: Tensor = Array4::<f32>::ones((3, 3, 1, 1)).into();
+    let i: Tensor = Array4::from_shape_fn((1, 3, 3, 1), |(_, a, b, _)| (a * 3 + b) as f32).into();
+    let model = deconv_pb(&[1, 5, 5, 1], &k, (2, 2), false).unwrap();
+    compare(&model, vec![("data", i)], "deconv").unwrap();
+}
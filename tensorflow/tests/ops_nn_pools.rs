@@ -60,7 +60,8 @@ fn pool(
                 .attr("ksize", vec![1, k.0 as i64, k.1 as i64, 1])
                 .attr("padding", padding),
         )
-        .write_to_bytes().unwrap();
+        .write_to_bytes()
+        .unwrap();
     compare(&graph, vec![("data", i.clone().into())], "pool")
 }
 
@@ -78,6 +79,60 @@ proptest! {
     }
 }
 
+fn vol_and_pool() -> BoxedStrategy<(Array5<f32>, usize, String, usize)> {
+    (1usize..4, 1usize..4, 1usize..4, 1usize..3, 1usize..3)
+        .prop_flat_map(|(id, ih, iw, ic, k)| {
+            let i_size = id * ih * iw * ic;
+            (
+                Just((1, id, ih, iw, ic)),
+                Just(k),
+                ::proptest::collection::vec((-10..10).prop_map(|a| a as f32), i_size..i_size + 1),
+                prop_oneof!("VALID", "SAME"),
+                1usize..3,
+            )
+        })
+        .prop_map(|(vol_shape, k, vol, padding, stride)| {
+            (Array::from(vol).into_shape(vol_shape).unwrap(), k, padding, stride)
+        })
+        .boxed()
+}
+
+fn pool3d(op: &str, i: &Array5<f32>, k: usize, padding: &str, stride: usize) -> TestCaseResult {
+    if padding == "VALID" {
+        prop_assume!(i.shape()[1..4].iter().all(|&d| d >= k));
+    }
+    let (k, s) = (k as i64, stride as i64);
+    let graph = tfpb::graph()
+        .node(placeholder_f32("data"))
+        .node(
+            tfpb::node()
+                .name("pool")
+                .op(op)
+                .input("data")
+                .attr("T", DtFloat)
+                .attr("strides", vec![1, s, s, s, 1])
+                .attr("ksize", vec![1, k, k, k, 1])
+                .attr("padding", padding),
+        )
+        .write_to_bytes()
+        .unwrap();
+    compare(&graph, vec![("data", i.clone().into())], "pool")
+}
+
+proptest! {
+    #[test]
+    fn proptest_maxpool3d((ref i, k, ref padding, stride) in vol_and_pool()) {
+        pool3d("MaxPool3D", i, k, padding, stride)?;
+    }
+}
+
+proptest! {
+    #[test]
+    fn proptest_avgpool3d((ref i, k, ref padding, stride) in vol_and_pool()) {
+        pool3d("AvgPool3D", i, k, padding, stride)?;
+    }
+}
+
 #[test]
 fn maxpool_1() {
     pool("MaxPool", &Array4::<f32>::zeros((1, 1, 4, 1)), (1, 2), "SAME", 1).unwrap();
@@ -0,0 +1,25 @@
+//! Loads a SavedModel written by TensorFlow 2 (Keras): object-based
+//! checkpoint, resource variables and `tf.function` calls.
+//!
+//! `models/keras_dense` is `examples/jupyter-keras-tract-tf2/my_model`. The
+//! expected outputs come from the ONNX export of the same model
+//! (`examples/jupyter-keras-tract-tf2/example.onnx`).
+use tract_tensorflow::prelude::*;
+
+fn input() -> Tensor {
+    tract_ndarray::Array2::from_shape_fn((2, 100), |(i, j)| ((i * 100 + j) as f32 / 100.0).sin())
+        .into()
+}
+
+#[test]
+fn keras_saved_model() -> TractResult<()> {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/models/keras_dense");
+    let model = tract_tensorflow::tensorflow()
+        .model_for_path(&dir)?
+        .with_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), tvec!(2, 100)))?
+        .into_optimized()?
+        .into_runnable()?;
+    let outputs = model.run(tvec!(input()))?;
+    outputs[0].close_enough(&tensor2(&[[0.57055527f32], [0.55773234]]), true)?;
+    Ok(())
+}
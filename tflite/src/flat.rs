@@ -0,0 +1,301 @@
+//! Minimal, bounds-checked flatbuffer reader.
+//!
+//! TFLite models are flatbuffers. We only need to read them, and only a
+//! handful of tables, so instead of pulling a code generator in, tables are
+//! accessed by field slot (the field position in the schema, unions taking
+//! two slots: the type, then the value).
+use std::convert::TryInto;
+use tract_hir::internal::*;
+
+pub trait Scalar: Copy + Sized {
+    const SIZE: usize;
+    fn read(bytes: &[u8]) -> Self;
+    fn write(&self, bytes: &mut Vec<u8>);
+}
+
+macro_rules! scalar {
+    ($($t: ty),*) => {
+        $(impl Scalar for $t {
+            const SIZE: usize = std::mem::size_of::<$t>();
+            fn read(bytes: &[u8]) -> $t {
+                <$t>::from_le_bytes(bytes[..Self::SIZE].try_into().unwrap())
+            }
+            fn write(&self, bytes: &mut Vec<u8>) {
+                bytes.extend_from_slice(&self.to_le_bytes())
+            }
+        })*
+    }
+}
+
+scalar!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+impl Scalar for bool {
+    const SIZE: usize = 1;
+    fn read(bytes: &[u8]) -> bool {
+        bytes[0] != 0
+    }
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self as u8)
+    }
+}
+
+fn read<T: Scalar>(buf: &[u8], pos: usize) -> TractResult<T> {
+    if pos.checked_add(T::SIZE).map(|end| end > buf.len()).unwrap_or(true) {
+        bail!("Invalid flatbuffer: read out of bounds at {}", pos)
+    }
+    Ok(T::read(&buf[pos..]))
+}
+
+#[derive(Clone, Copy)]
+pub struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> std::fmt::Debug for Table<'a> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "Table@{}", self.pos)
+    }
+}
+
+impl<'a> Table<'a> {
+    /// The root table of a flatbuffer.
+    pub fn root(buf: &'a [u8]) -> TractResult<Table<'a>> {
+        let pos = read::<u32>(buf, 0)? as usize;
+        Table::at(buf, pos)
+    }
+
+    fn at(buf: &'a [u8], pos: usize) -> TractResult<Table<'a>> {
+        let table = Table { buf, pos };
+        table.vtable()?;
+        Ok(table)
+    }
+
+    fn vtable(&self) -> TractResult<(usize, usize)> {
+        let vtable = self.pos as i64 - read::<i32>(self.buf, self.pos)? as i64;
+        if vtable < 0 {
+            bail!("Invalid flatbuffer: vtable out of bounds for table at {}", self.pos)
+        }
+        let len = read::<u16>(self.buf, vtable as usize)? as usize;
+        Ok((vtable as usize, len))
+    }
+
+    /// Position of a field in the buffer, None if the field is absent.
+    fn field(&self, slot: usize) -> TractResult<Option<usize>> {
+        let (vtable, len) = self.vtable()?;
+        let entry = 4 + 2 * slot;
+        if entry + 2 > len {
+            return Ok(None);
+        }
+        let offset = read::<u16>(self.buf, vtable + entry)? as usize;
+        Ok(if offset == 0 { None } else { Some(self.pos + offset) })
+    }
+
+    /// Follow an offset field (table, vector or string).
+    fn indirect(&self, slot: usize) -> TractResult<Option<usize>> {
+        if let Some(pos) = self.field(slot)? {
+            Ok(Some(pos + read::<u32>(self.buf, pos)? as usize))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn scalar<T: Scalar>(&self, slot: usize, default: T) -> TractResult<T> {
+        if let Some(pos) = self.field(slot)? {
+            read::<T>(self.buf, pos)
+        } else {
+            Ok(default)
+        }
+    }
+
+    pub fn table(&self, slot: usize) -> TractResult<Option<Table<'a>>> {
+        self.indirect(slot)?.map(|pos| Table::at(self.buf, pos)).transpose()
+    }
+
+    fn vector_bounds(&self, slot: usize, size: usize) -> TractResult<Option<(usize, usize)>> {
+        if let Some(pos) = self.indirect(slot)? {
+            let len = read::<u32>(self.buf, pos)? as usize;
+            if pos + 4 + len * size > self.buf.len() {
+                bail!("Invalid flatbuffer: vector out of bounds at {}", pos)
+            }
+            Ok(Some((pos + 4, len)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// A vector of scalars, empty if the field is absent.
+    pub fn vector<T: Scalar>(&self, slot: usize) -> TractResult<Vec<T>> {
+        if let Some((start, len)) = self.vector_bounds(slot, T::SIZE)? {
+            Ok((0..len).map(|i| T::read(&self.buf[start + i * T::SIZE..])).collect())
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// A vector of bytes, borrowed from the buffer.
+    pub fn bytes(&self, slot: usize) -> TractResult<&'a [u8]> {
+        if let Some((start, len)) = self.vector_bounds(slot, 1)? {
+            Ok(&self.buf[start..][..len])
+        } else {
+            Ok(&[])
+        }
+    }
+
+    pub fn string(&self, slot: usize) -> TractResult<Option<&'a str>> {
+        if let Some((start, len)) = self.vector_bounds(slot, 1)? {
+            Ok(Some(std::str::from_utf8(&self.buf[start..][..len])?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// A vector of tables, empty if the field is absent.
+    pub fn tables(&self, slot: usize) -> TractResult<Vec<Table<'a>>> {
+        if let Some((start, len)) = self.vector_bounds(slot, 4)? {
+            (0..len)
+                .map(|i| {
+                    let pos = start + 4 * i;
+                    Table::at(self.buf, pos + read::<u32>(self.buf, pos)? as usize)
+                })
+                .collect()
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+/// Flatbuffer writer, for building test models.
+///
+/// Like regular flatbuffer builders, it writes back to front, so objects must
+/// be written before the tables referring to them. Objects are designated by
+/// their distance to the end of the buffer. No alignment is performed.
+#[cfg(test)]
+pub mod builder {
+    use super::Scalar;
+
+    pub enum Field {
+        Absent,
+        Scalar(Vec<u8>),
+        Offset(usize),
+    }
+
+    pub fn scalar<T: Scalar>(t: T) -> Field {
+        let mut bytes = vec![];
+        t.write(&mut bytes);
+        Field::Scalar(bytes)
+    }
+
+    #[derive(Default)]
+    pub struct Builder {
+        data: Vec<u8>,
+    }
+
+    impl Builder {
+        fn prepend(&mut self, bytes: &[u8]) -> usize {
+            self.data.splice(0..0, bytes.iter().cloned());
+            self.data.len()
+        }
+
+        pub fn vector<T: Scalar>(&mut self, items: &[T]) -> usize {
+            let mut bytes = vec![];
+            (items.len() as u32).write(&mut bytes);
+            items.iter().for_each(|it| it.write(&mut bytes));
+            self.prepend(&bytes)
+        }
+
+        pub fn string(&mut self, s: &str) -> usize {
+            self.vector(s.as_bytes())
+        }
+
+        pub fn tables(&mut self, tables: &[usize]) -> usize {
+            let start = self.data.len() + 4 + 4 * tables.len();
+            let mut bytes = vec![];
+            (tables.len() as u32).write(&mut bytes);
+            for (ix, t) in tables.iter().enumerate() {
+                ((start - 4 - 4 * ix - t) as u32).write(&mut bytes);
+            }
+            self.prepend(&bytes)
+        }
+
+        pub fn table(&mut self, fields: &[Field]) -> usize {
+            let size = 4 + fields
+                .iter()
+                .map(|f| match f {
+                    Field::Absent => 0,
+                    Field::Scalar(b) => b.len(),
+                    Field::Offset(_) => 4,
+                })
+                .sum::<usize>();
+            let start = self.data.len() + size;
+            let vtable_size = 4 + 2 * fields.len();
+            let mut table = vec![];
+            let mut vtable = vec![];
+            (vtable_size as u16).write(&mut vtable);
+            (size as u16).write(&mut vtable);
+            (vtable_size as i32).write(&mut table);
+            for f in fields {
+                let offset = if let Field::Absent = f { 0 } else { table.len() as u16 };
+                offset.write(&mut vtable);
+                match f {
+                    Field::Absent => (),
+                    Field::Scalar(b) => table.extend_from_slice(b),
+                    Field::Offset(o) => ((start - table.len() - o) as u32).write(&mut table),
+                }
+            }
+            self.prepend(&table);
+            self.prepend(&vtable);
+            start
+        }
+
+        pub fn finish(mut self, root: usize, identifier: &[u8; 4]) -> Vec<u8> {
+            let len = self.data.len() + 8;
+            let mut header = vec![];
+            ((len - root) as u32).write(&mut header);
+            header.extend_from_slice(identifier);
+            self.prepend(&header);
+            self.data
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::builder::*;
+    use super::*;
+
+    #[test]
+    fn roundtrip() -> TractResult<()> {
+        let mut b = Builder::default();
+        let name = b.string("leaf");
+        let leaf = b.table(&[scalar(12i32), Field::Offset(name)]);
+        let leaves = b.tables(&[leaf, leaf]);
+        let values = b.vector(&[1.5f32, 2.5]);
+        let root = b.table(&[
+            scalar(3u8),
+            Field::Absent,
+            Field::Offset(values),
+            Field::Offset(leaves),
+            Field::Offset(leaf),
+        ]);
+        let buf = b.finish(root, b"TEST");
+        assert_eq!(&buf[4..8], b"TEST");
+        let root = Table::root(&buf)?;
+        assert_eq!(root.scalar::<u8>(0, 0)?, 3);
+        assert_eq!(root.scalar::<u32>(1, 42)?, 42);
+        assert_eq!(root.scalar::<u32>(12, 42)?, 42);
+        assert_eq!(root.vector::<f32>(2)?, vec![1.5, 2.5]);
+        let leaves = root.tables(3)?;
+        assert_eq!(leaves.len(), 2);
+        assert_eq!(leaves[1].scalar::<i32>(0, 0)?, 12);
+        assert_eq!(leaves[1].string(1)?, Some("leaf"));
+        assert_eq!(root.table(4)?.unwrap().string(1)?, Some("leaf"));
+        assert!(root.table(1)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        assert!(Table::root(&[12, 0, 0, 0]).is_err());
+    }
+}
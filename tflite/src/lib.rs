@@ -0,0 +1,45 @@
+//! # Tract TFLite module
+//!
+//! Loads TensorFlow Lite flatbuffer models (`.tflite`) as tract inference
+//! models. Float and quantized (8-bit, per-tensor and per-axis) CNN
+//! operators are supported.
+//!
+//! ```no_run
+//! use tract_tflite::prelude::*;
+//!
+//! let model = tract_tflite::tflite()
+//!     .model_for_path("mobilenet_v2_1.0_224_quant.tflite")
+//!     .unwrap()
+//!     .into_optimized()
+//!     .unwrap();
+//! ```
+
+#[macro_use]
+extern crate derive_new;
+#[macro_use]
+extern crate educe;
+#[allow(unused_imports)]
+#[macro_use]
+extern crate log;
+pub extern crate tract_hir;
+
+pub mod flat;
+pub mod model;
+mod ops;
+pub mod schema;
+
+pub use model::Tflite;
+pub use model::TfliteProtoModel;
+
+pub fn tflite() -> Tflite {
+    let mut tflite = Tflite::default();
+    ops::register_all_ops(&mut tflite.op_register);
+    tflite
+}
+
+pub use tract_hir::tract_core;
+pub mod prelude {
+    pub use crate::tflite;
+    pub use tract_hir::prelude::*;
+    pub use tract_hir::tract_core;
+}
@@ -0,0 +1,605 @@
+use crate::schema::{self, Operator, TensorDef};
+use tract_hir::internal::*;
+
+/// A TFLite model, as its raw flatbuffer.
+#[derive(Clone)]
+pub struct TfliteProtoModel(pub Vec<u8>);
+
+impl std::fmt::Debug for TfliteProtoModel {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "TfliteProtoModel ({} bytes)", self.0.len())
+    }
+}
+
+impl TfliteProtoModel {
+    pub fn new(buf: Vec<u8>) -> TractResult<TfliteProtoModel> {
+        schema::Model::root(&buf)?;
+        Ok(TfliteProtoModel(buf))
+    }
+
+    pub fn model(&self) -> TractResult<schema::Model> {
+        schema::Model::root(&self.0)
+    }
+}
+
+/// Quantization parameters of a tensor. Scale and zero point have one
+/// element for per-tensor quantization, one per slice along `axis` for
+/// per-axis quantization.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TensorQuant {
+    pub scale: Vec<f32>,
+    pub zero_point: Vec<i64>,
+    pub axis: usize,
+}
+
+impl TensorQuant {
+    /// The common scale of all slices, if there is one.
+    pub fn uniform_scale(&self) -> Option<f32> {
+        if self.scale.iter().all(|s| *s == self.scale[0]) {
+            Some(self.scale[0])
+        } else {
+            None
+        }
+    }
+
+    /// Whether the scale or the zero point vary along `axis`.
+    pub fn is_per_axis(&self) -> bool {
+        self.uniform_scale().is_none() || self.zero_point.iter().any(|z| *z != self.zero_point[0])
+    }
+
+    /// Zero point as a tensor of `dt`: a scalar for per-tensor quantization,
+    /// a vector for per-axis.
+    pub fn zero_point_tensor(&self, dt: DatumType) -> TractResult<Tensor> {
+        let zp = if self.zero_point.len() == 1 {
+            tensor0(self.zero_point[0])
+        } else {
+            tensor1(&self.zero_point)
+        };
+        Ok(zp.cast_to_dt(dt)?.into_owned())
+    }
+
+    /// Scales, shaped to broadcast along `axis` of a tensor of rank `rank`.
+    pub fn scale_along_axis(&self, rank: usize) -> TractResult<Tensor> {
+        self.along_axis(tensor1(&self.scale), rank)
+    }
+
+    /// Zero points as floats, shaped to broadcast along `axis` of a tensor
+    /// of rank `rank`.
+    pub fn zero_point_along_axis(&self, rank: usize) -> TractResult<Tensor> {
+        let zp = self.zero_point.iter().map(|z| *z as f32).collect::<Vec<_>>();
+        self.along_axis(tensor1(&zp), rank)
+    }
+
+    fn along_axis(&self, values: Tensor, rank: usize) -> TractResult<Tensor> {
+        if values.len() == 1 {
+            return values.into_shape(&[]);
+        }
+        if self.axis >= rank {
+            bail!("Quantization axis {} out of range for rank {}", self.axis, rank)
+        }
+        let mut shape = vec![1; rank];
+        shape[self.axis] = values.len();
+        values.into_shape(&shape)
+    }
+}
+
+pub type TfliteOpBuilder =
+    fn(&mut ParsingContext, op: &Operator, inputs: &[OutletId]) -> TractResult<TVec<OutletId>>;
+
+#[derive(Clone, Default)]
+pub struct TfliteOpRegister(pub HashMap<i32, TfliteOpBuilder>);
+
+impl TfliteOpRegister {
+    pub fn insert(&mut self, code: i32, builder: TfliteOpBuilder) {
+        self.0.insert(code, builder);
+    }
+}
+
+#[derive(Default)]
+pub struct Tflite {
+    pub op_register: TfliteOpRegister,
+}
+
+/// State of the translation of a subgraph.
+pub struct ParsingContext<'a> {
+    pub model: InferenceModel,
+    pub tensors: Vec<TensorDef<'a>>,
+    pub buffers: Vec<schema::Buffer<'a>>,
+    /// name of the operator being translated (the name of its first output)
+    pub name: String,
+    outlets: HashMap<usize, OutletId>,
+}
+
+impl<'a> ParsingContext<'a> {
+    pub fn tensor(&self, ix: i32) -> TractResult<&TensorDef<'a>> {
+        self.tensors.get(ix as usize).with_context(|| format!("Invalid tensor index {}", ix))
+    }
+
+    pub fn tensor_name(&self, ix: i32) -> TractResult<String> {
+        Ok(self.tensor(ix)?.name()?.map(|s| s.to_string()).unwrap_or(format!("tensor_{}", ix)))
+    }
+
+    pub fn shape(&self, ix: i32) -> TractResult<TVec<usize>> {
+        Ok(self.tensor(ix)?.shape()?.iter().map(|d| *d as usize).collect())
+    }
+
+    /// Value of the tensor, if it is backed by a non empty buffer.
+    pub fn konst(&self, ix: i32) -> TractResult<Option<Tensor>> {
+        let tensor = self.tensor(ix)?;
+        let data = if let Some(buffer) = self.buffers.get(tensor.buffer()? as usize) {
+            buffer.data()?
+        } else {
+            return Ok(None);
+        };
+        if data.len() == 0 {
+            return Ok(None);
+        }
+        let dt = tensor.datum_type()?;
+        let shape = self.shape(ix)?;
+        if shape.iter().product::<usize>() * dt.size_of() != data.len() {
+            bail!("Buffer for tensor {} has the wrong size", self.tensor_name(ix)?)
+        }
+        unsafe { Ok(Some(Tensor::from_raw_dt(dt, &shape, data)?)) }
+    }
+
+    pub fn quant(&self, ix: i32) -> TractResult<Option<TensorQuant>> {
+        if let Some(q) = self.tensor(ix)?.quantization()? {
+            let scale = q.scale()?;
+            if scale.len() == 0 {
+                return Ok(None);
+            }
+            let mut zero_point = q.zero_point()?;
+            if zero_point.len() == 0 {
+                zero_point = vec![0];
+            }
+            Ok(Some(TensorQuant { scale, zero_point, axis: q.quantized_dimension()? as usize }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Wire a node, named after the current operator and suffix.
+    pub fn wire(
+        &mut self,
+        suffix: &str,
+        op: impl Into<Box<dyn InferenceOp>>,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let name =
+            if suffix.len() > 0 { format!("{}.{}", self.name, suffix) } else { self.name.clone() };
+        self.model.wire_node(name, op, inputs)
+    }
+
+    pub fn add_const(&mut self, suffix: &str, t: impl IntoArcTensor) -> TractResult<OutletId> {
+        self.model.add_const(format!("{}.{}", self.name, suffix), t)
+    }
+
+    fn outlet(&mut self, ix: i32) -> TractResult<OutletId> {
+        if let Some(outlet) = self.outlets.get(&(ix as usize)) {
+            return Ok(*outlet);
+        }
+        if let Some(konst) = self.konst(ix)? {
+            let outlet = self.model.add_const(self.tensor_name(ix)?, konst)?;
+            self.outlets.insert(ix as usize, outlet);
+            Ok(outlet)
+        } else {
+            bail!("Tensor {} is not computed by any operator", self.tensor_name(ix)?)
+        }
+    }
+
+    fn source_fact(&self, ix: i32) -> TractResult<InferenceFact> {
+        let tensor = self.tensor(ix)?;
+        let signature = tensor.shape_signature()?;
+        let shape = if signature.len() > 0 { signature } else { tensor.shape()? };
+        let shape = ShapeFactoid::closed(
+            shape
+                .iter()
+                .map(
+                    |d| if *d < 0 { GenericFactoid::Any } else { GenericFactoid::Only(d.to_dim()) },
+                )
+                .collect(),
+        );
+        Ok(InferenceFact::dt(tensor.datum_type()?).with_shape(shape))
+    }
+}
+
+impl Tflite {
+    pub fn parse(&self, proto: &TfliteProtoModel) -> TractResult<InferenceModel> {
+        let model = proto.model()?;
+        let subgraphs = model.subgraphs()?;
+        if subgraphs.len() != 1 {
+            bail!("Expected a single subgraph, found {}", subgraphs.len())
+        }
+        let graph = subgraphs[0];
+        let codes = model
+            .operator_codes()?
+            .iter()
+            .map(|c| c.builtin_code())
+            .collect::<TractResult<Vec<i32>>>()?;
+        let mut ctx = ParsingContext {
+            model: InferenceModel::default(),
+            tensors: graph.tensors()?,
+            buffers: model.buffers()?,
+            name: String::new(),
+            outlets: HashMap::new(),
+        };
+        let mut inputs = vec![];
+        for ix in graph.inputs()? {
+            let outlet = ctx.model.add_source(ctx.tensor_name(ix)?, ctx.source_fact(ix)?)?;
+            ctx.outlets.insert(ix as usize, outlet);
+            inputs.push(outlet);
+        }
+        for op in graph.operators()? {
+            let opcode_index = op.opcode_index()?;
+            let code = *codes
+                .get(opcode_index as usize)
+                .with_context(|| format!("Invalid opcode index {}", opcode_index))?;
+            let outputs = op.outputs()?;
+            ctx.name = ctx.tensor_name(outputs[0])?;
+            let builder = self
+                .op_register
+                .0
+                .get(&code)
+                .with_context(|| format!("Unsupported TFLite builtin operator {}", code))?;
+            let op_inputs = op
+                .inputs()?
+                .iter()
+                .filter(|ix| **ix >= 0)
+                .map(|ix| ctx.outlet(*ix))
+                .collect::<TractResult<TVec<OutletId>>>()?;
+            let wires = (builder)(&mut ctx, &op, &op_inputs)
+                .with_context(|| format!("Translating operator {}", ctx.name))?;
+            if wires.len() != outputs.len() {
+                bail!(
+                    "Operator {} has {} outputs, expected {}",
+                    ctx.name,
+                    wires.len(),
+                    outputs.len()
+                )
+            }
+            for (ix, wire) in outputs.iter().zip(wires.iter()) {
+                ctx.outlets.insert(*ix as usize, *wire);
+                ctx.model.set_outlet_label(*wire, ctx.tensor_name(*ix)?)?;
+            }
+        }
+        let outputs = graph
+            .outputs()?
+            .iter()
+            .map(|ix| ctx.outlet(*ix))
+            .collect::<TractResult<Vec<OutletId>>>()?;
+        ctx.model.set_input_outlets(&inputs)?;
+        ctx.model.set_output_outlets(&outputs)?;
+        Ok(ctx.model)
+    }
+}
+
+impl Framework<TfliteProtoModel, InferenceModel> for Tflite {
+    fn proto_model_for_read(&self, r: &mut dyn std::io::Read) -> TractResult<TfliteProtoModel> {
+        let mut v = vec![];
+        r.read_to_end(&mut v)?;
+        TfliteProtoModel::new(v)
+    }
+
+    fn model_for_proto_model(&self, proto: &TfliteProtoModel) -> TractResult<InferenceModel> {
+        self.parse(proto)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flat::builder::*;
+    use crate::schema::builtin::*;
+
+    struct TensorSpec {
+        shape: Vec<i32>,
+        dt: i8,
+        data: Vec<u8>,
+        quant: Option<(Vec<f32>, Vec<i64>, i32)>,
+    }
+
+    fn f32s(shape: &[i32], values: &[f32]) -> TensorSpec {
+        let data = values.iter().flat_map(|v| v.to_le_bytes().to_vec()).collect();
+        TensorSpec { shape: shape.to_vec(), dt: 0, data, quant: None }
+    }
+
+    fn var(shape: &[i32]) -> TensorSpec {
+        f32s(shape, &[])
+    }
+
+    fn build(
+        tensors: &[TensorSpec],
+        ops: &[(i32, Vec<i32>, Vec<i32>, Vec<Field>)],
+        inputs: &[i32],
+        outputs: &[i32],
+    ) -> TfliteProtoModel {
+        let mut b = Builder::default();
+        let empty = b.vector::<u8>(&[]);
+        let mut buffers = vec![b.table(&[Field::Offset(empty)])];
+        let mut tensor_tables = vec![];
+        for (ix, t) in tensors.iter().enumerate() {
+            let buffer = if t.data.len() > 0 {
+                let data = b.vector(&t.data);
+                buffers.push(b.table(&[Field::Offset(data)]));
+                buffers.len() - 1
+            } else {
+                0
+            };
+            let quant = if let Some((scale, zp, axis)) = &t.quant {
+                let scale = b.vector(scale);
+                let zp = b.vector(zp);
+                let q = b.table(&[
+                    Field::Absent,
+                    Field::Absent,
+                    Field::Offset(scale),
+                    Field::Offset(zp),
+                    Field::Absent,
+                    Field::Absent,
+                    scalar(*axis),
+                ]);
+                Field::Offset(q)
+            } else {
+                Field::Absent
+            };
+            let shape = b.vector(&t.shape);
+            let name = b.string(&format!("t{}", ix));
+            tensor_tables.push(b.table(&[
+                Field::Offset(shape),
+                scalar(t.dt),
+                scalar(buffer as u32),
+                Field::Offset(name),
+                quant,
+            ]));
+        }
+        let mut codes = vec![];
+        let mut op_tables = vec![];
+        for (code, inputs, outputs, options) in ops {
+            let index = codes.iter().position(|c| c == code).unwrap_or_else(|| {
+                codes.push(*code);
+                codes.len() - 1
+            });
+            let inputs = b.vector(inputs);
+            let outputs = b.vector(outputs);
+            let options = b.table(options);
+            op_tables.push(b.table(&[
+                scalar(index as u32),
+                Field::Offset(inputs),
+                Field::Offset(outputs),
+                scalar(1u8),
+                Field::Offset(options),
+            ]));
+        }
+        let code_tables = codes
+            .iter()
+            .map(|c| b.table(&[scalar(*c as i8), Field::Absent, Field::Absent, scalar(*c)]))
+            .collect::<Vec<_>>();
+        let tensors = b.tables(&tensor_tables);
+        let inputs = b.vector(inputs);
+        let outputs = b.vector(outputs);
+        let operators = b.tables(&op_tables);
+        let subgraph = b.table(&[
+            Field::Offset(tensors),
+            Field::Offset(inputs),
+            Field::Offset(outputs),
+            Field::Offset(operators),
+        ]);
+        let subgraphs = b.tables(&[subgraph]);
+        let codes = b.tables(&code_tables);
+        let buffers = b.tables(&buffers);
+        let model = b.table(&[
+            scalar(3u32),
+            Field::Offset(codes),
+            Field::Offset(subgraphs),
+            Field::Absent,
+            Field::Offset(buffers),
+        ]);
+        TfliteProtoModel::new(b.finish(model, schema::IDENTIFIER)).unwrap()
+    }
+
+    fn run(proto: &TfliteProtoModel, input: Tensor) -> TractResult<Arc<Tensor>> {
+        let model = crate::tflite().model_for_proto_model(proto)?.into_optimized()?;
+        Ok(SimplePlan::new(model)?.run(tvec!(input))?.remove(0))
+    }
+
+    #[test]
+    fn not_tflite() {
+        assert!(TfliteProtoModel::new(vec![0; 16]).is_err());
+    }
+
+    #[test]
+    fn conv_relu_pool_fc_softmax() -> TractResult<()> {
+        let proto = build(
+            &[
+                var(&[1, 2, 2, 2]),
+                // OHWI kernel: out 0 sums channels, out 1 subtracts them
+                f32s(&[2, 1, 1, 2], &[1.0, 1.0, 1.0, -1.0]),
+                f32s(&[2], &[0.0, 0.5]),
+                var(&[1, 2, 2, 2]),
+                var(&[1, 1, 1, 2]),
+                f32s(&[2, 2], &[1.0, 0.0, 0.0, 1.0]),
+                var(&[1, 2]),
+                var(&[1, 2]),
+            ],
+            &[
+                (
+                    CONV_2D,
+                    vec![0, 1, 2],
+                    vec![3],
+                    vec![scalar(1i8), scalar(1i32), scalar(1i32), scalar(1i8)],
+                ),
+                (
+                    AVERAGE_POOL_2D,
+                    vec![3],
+                    vec![4],
+                    vec![scalar(1i8), scalar(2i32), scalar(2i32), scalar(2i32), scalar(2i32)],
+                ),
+                (FULLY_CONNECTED, vec![4, 5, -1], vec![6], vec![]),
+                (SOFTMAX, vec![6], vec![7], vec![scalar(1.0f32)]),
+            ],
+            &[0],
+            &[7],
+        );
+        let input =
+            tensor1(&[1.0f32, 2.0, 3.0, 1.0, 0.0, 4.0, 2.0, 2.0]).into_shape(&[1, 2, 2, 2])?;
+        let output = run(&proto, input)?;
+        // conv+relu: [3, 0], [4, 2.5], [4, 0], [4, 0.5] -> pool: [3.75, 0.75]
+        let e = (3.0f32).exp();
+        let expected = tensor2(&[[e / (e + 1.0), 1.0 / (e + 1.0)]]);
+        output.close_enough(&expected, true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn quantized_conv() -> TractResult<()> {
+        let u8s = |shape: &[i32], data: &[u8], scale: f32, zp: i64| TensorSpec {
+            shape: shape.to_vec(),
+            dt: 3,
+            data: data.to_vec(),
+            quant: Some((vec![scale], vec![zp], 0)),
+        };
+        let proto = build(
+            &[
+                var(&[1, 1, 2, 1]),
+                u8s(&[1, 1, 2, 1], &[], 0.5, 10),
+                u8s(&[1, 1, 1, 1], &[12], 0.25, 4),
+                u8s(&[1, 1, 2, 1], &[], 1.0, 10),
+                var(&[1, 1, 2, 1]),
+            ],
+            &[
+                (QUANTIZE, vec![0], vec![1], vec![]),
+                (CONV_2D, vec![1, 2], vec![3], vec![scalar(1i8)]),
+                (DEQUANTIZE, vec![3], vec![4], vec![]),
+            ],
+            &[0],
+            &[4],
+        );
+        let input = tensor1(&[1.0f32, -2.0]).into_shape(&[1, 1, 2, 1])?;
+        let output = run(&proto, input)?;
+        // kernel is 2.0: outputs are 2.0 and -4.0
+        output.close_enough(&tensor1(&[2.0f32, -4.0]).into_shape(&[1, 1, 2, 1])?, true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn per_axis_quantized_conv() -> TractResult<()> {
+        let i8s = |shape: &[i32], data: &[i8], scale: &[f32], zp: i64, axis: i32| TensorSpec {
+            shape: shape.to_vec(),
+            dt: 9,
+            data: data.iter().map(|x| *x as u8).collect(),
+            quant: Some((scale.to_vec(), vec![zp; scale.len()], axis)),
+        };
+        let i32s = |shape: &[i32], data: &[i32], scale: &[f32]| TensorSpec {
+            shape: shape.to_vec(),
+            dt: 2,
+            data: data.iter().flat_map(|x| x.to_le_bytes().to_vec()).collect(),
+            quant: Some((scale.to_vec(), vec![0; scale.len()], 0)),
+        };
+        let input = tensor1(&[1.0f32, 0.5, -1.0, 2.0]).into_shape(&[1, 1, 2, 2])?;
+        // channel 0: 2*x0 + x1 + 1.0, channel 1: x1 - x0
+        let expected = [[3.5f32, -0.5, 1.0, 3.0], [3.5, 0.0, 1.0, 3.0]];
+        for (relu, expected) in [0i8, 1].iter().zip(expected.iter()) {
+            let proto = build(
+                &[
+                    var(&[1, 1, 2, 2]),
+                    i8s(&[1, 1, 2, 2], &[], &[0.5], -1, 0),
+                    // OHWI kernel, one scale per output channel: [[2, 1], [-1, 1]]
+                    i8s(&[2, 1, 1, 2], &[4, 2, -4, 4], &[0.5, 0.25], 0, 0),
+                    i32s(&[2], &[4, 0], &[0.25, 0.125]),
+                    i8s(&[1, 1, 2, 2], &[], &[0.25, 0.5], 2, 3),
+                    var(&[1, 1, 2, 2]),
+                ],
+                &[
+                    (QUANTIZE, vec![0], vec![1], vec![]),
+                    (
+                        CONV_2D,
+                        vec![1, 2, 3],
+                        vec![4],
+                        vec![scalar(1i8), scalar(1i32), scalar(1i32), scalar(*relu)],
+                    ),
+                    (DEQUANTIZE, vec![4], vec![5], vec![]),
+                ],
+                &[0],
+                &[5],
+            );
+            let output = run(&proto, input.clone())?;
+            output.close_enough(&tensor1(expected).into_shape(&[1, 1, 2, 2])?, true)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn per_axis_dequantize() -> TractResult<()> {
+        let input = TensorSpec {
+            shape: vec![2, 2],
+            dt: 9,
+            data: vec![],
+            quant: Some((vec![0.5, 0.25], vec![0, 4], 0)),
+        };
+        let proto =
+            build(&[input, var(&[2, 2])], &[(DEQUANTIZE, vec![0], vec![1], vec![])], &[0], &[1]);
+        let output = run(&proto, tensor2(&[[2i8, -2], [4, 8]]))?;
+        output.close_enough(&tensor2(&[[1.0f32, -1.0], [0.0, 1.0]]), true)?;
+        Ok(())
+    }
+
+    fn u8s(shape: &[i32], scale: f32, zp: i64) -> TensorSpec {
+        TensorSpec {
+            shape: shape.to_vec(),
+            dt: 3,
+            data: vec![],
+            quant: Some((vec![scale], vec![zp], 0)),
+        }
+    }
+
+    #[test]
+    fn quantized_add() -> TractResult<()> {
+        let proto = build(
+            &[
+                var(&[2]),
+                var(&[2]),
+                u8s(&[2], 0.5, 10),
+                u8s(&[2], 0.25, 0),
+                u8s(&[2], 1.0, 20),
+                var(&[2]),
+            ],
+            &[
+                (QUANTIZE, vec![0], vec![2], vec![]),
+                (QUANTIZE, vec![1], vec![3], vec![]),
+                (ADD, vec![2, 3], vec![4], vec![]),
+                (DEQUANTIZE, vec![4], vec![5], vec![]),
+            ],
+            &[0, 1],
+            &[5],
+        );
+        let model = crate::tflite().model_for_proto_model(&proto)?.into_optimized()?;
+        let output = SimplePlan::new(model)?
+            .run(tvec!(tensor1(&[1.0f32, -3.0]), tensor1(&[2.0f32, 1.0])))?
+            .remove(0);
+        output.close_enough(&tensor1(&[3.0f32, -2.0]), true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn quantized_concat_requantizes_inputs() -> TractResult<()> {
+        let proto = build(
+            &[
+                var(&[1, 2]),
+                u8s(&[1, 2], 0.5, 10),
+                u8s(&[1, 2], 0.25, 128),
+                u8s(&[1, 4], 0.5, 10),
+                var(&[1, 4]),
+            ],
+            &[
+                (QUANTIZE, vec![0], vec![1], vec![]),
+                (QUANTIZE, vec![0], vec![2], vec![]),
+                (CONCATENATION, vec![1, 2], vec![3], vec![scalar(1i32)]),
+                (DEQUANTIZE, vec![3], vec![4], vec![]),
+            ],
+            &[0],
+            &[4],
+        );
+        let output = run(&proto, tensor2(&[[1.0f32, -2.5]]))?;
+        output.close_enough(&tensor2(&[[1.0f32, -2.5, 1.0, -2.5]]), true)?;
+        Ok(())
+    }
+}
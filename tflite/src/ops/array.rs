@@ -0,0 +1,57 @@
+use super::quant::{dequantize_as, quantize_as};
+use crate::model::ParsingContext;
+use crate::schema::options;
+use crate::schema::Operator;
+use tract_hir::internal::*;
+use tract_hir::ops;
+
+pub fn concatenation(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let (axis, activation) = options::concatenation(op.builtin_options()?)?;
+    let output = op.outputs()?[0];
+    let output_quant = ctx.quant(output)?;
+    // quantized inputs are requantized to the output quantization
+    let mut wires: TVec<OutletId> = inputs.into();
+    for (ix, input) in op.inputs()?.iter().enumerate() {
+        if output_quant.is_some() && ctx.quant(*input)? != output_quant {
+            let wire = dequantize_as(ctx, &format!("dequantize.{}", ix), *input, wires[ix])?;
+            wires[ix] = quantize_as(ctx, &format!("requantize.{}", ix), output, wire)?;
+        }
+    }
+    let wire = ctx.wire("concat", expand(ops::array::Concat::new(axis as i64)), &wires)?;
+    super::activation(ctx, output, &wire, activation)
+}
+
+pub fn pad(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let op_inputs = op.inputs()?;
+    let paddings = ctx.konst(op_inputs[1])?.context("Pad paddings must be a constant")?;
+    let paddings = paddings.cast_to::<i64>()?;
+    let pads = paddings.as_slice::<i64>()?.chunks(2).map(|p| (p[0] as usize, p[1] as usize));
+    let dt = ctx.tensor(op_inputs[0])?.datum_type()?;
+    let zero = ctx.quant(op_inputs[0])?.map(|q| q.zero_point[0]).unwrap_or(0);
+    let value = tensor0(zero).cast_to_dt(dt)?.into_owned().into_arc_tensor();
+    let pad = ops::array::Pad::new(pads.collect(), ops::array::PadMode::Constant(value));
+    ctx.wire("", pad, &inputs[0..1])
+}
+
+pub fn reshape(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let shape = if inputs.len() > 1 {
+        inputs[1]
+    } else {
+        let shape = options::reshape_new_shape(op.builtin_options()?)?;
+        let shape = tensor1(&shape.iter().map(|d| *d as i64).collect::<Vec<_>>());
+        ctx.add_const("shape", shape)?
+    };
+    ctx.wire("", expand(ops::array::Reshape::new()), &[inputs[0], shape])
+}
@@ -0,0 +1,163 @@
+use crate::model::ParsingContext;
+use crate::schema::options::{Activation, Conv2D, DepthwiseConv2D, Pool2D};
+use crate::schema::Operator;
+use tract_hir::internal::*;
+use tract_hir::ops;
+use tract_hir::ops::cnn::PoolSpec;
+use tract_hir::ops::nn::DataFormat;
+
+pub fn conv_2d(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let options = Conv2D::parse(op.builtin_options()?)?;
+    let kernel = ctx.konst(op.inputs()?[1])?.context("Conv2D kernel must be a constant")?;
+    // TFLite kernels are OHWI
+    let kernel = kernel.permute_axes(&[0, 3, 1, 2])?;
+    let conv = ops::cnn::Conv::default()
+        .nhwc()
+        .padding(options.padding)
+        .strides(options.strides)
+        .dilations(options.dilations);
+    self::conv(ctx, op, inputs, conv, kernel, options.activation)
+}
+
+pub fn depthwise_conv_2d(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let options = DepthwiseConv2D::parse(op.builtin_options()?)?;
+    let kernel =
+        ctx.konst(op.inputs()?[1])?.context("DepthwiseConv2D kernel must be a constant")?;
+    // TFLite depthwise kernels are 1HW(C*multiplier)
+    let kernel = kernel.permute_axes(&[3, 0, 1, 2])?;
+    let conv = ops::cnn::Conv::default()
+        .nhwc()
+        .padding(options.padding)
+        .strides(options.strides)
+        .dilations(options.dilations)
+        .group(kernel.shape()[0] / options.depth_multiplier);
+    self::conv(ctx, op, inputs, conv, kernel, options.activation)
+}
+
+/// Wire a hir convolution from an OIHW kernel, with quantization parameters
+/// as constant inputs when the operator is quantized.
+///
+/// tract convolutions take a single scale factor, so kernels with per-axis
+/// scales are convolved to the i32 accumulator, then requantized per output
+/// channel.
+pub fn conv(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+    conv: ops::cnn::Conv,
+    kernel: Tensor,
+    activation: Activation,
+) -> TractResult<TVec<OutletId>> {
+    let op_inputs = op.inputs()?;
+    let output = op.outputs()?[0];
+    let mut conv = conv.kernel_shape(kernel.shape()[2..].into());
+    let output_channels = kernel.shape()[0];
+    let kernel = ctx.add_const("kernel", kernel)?;
+    let mut wires = tvec!(inputs[0], kernel);
+    if let Some(&bias) = inputs.get(2) {
+        conv = conv.bias_input(wires.len());
+        wires.push(bias);
+    }
+    let dt = ctx.tensor(op_inputs[0])?.datum_type()?;
+    let quant = (ctx.quant(op_inputs[0])?, ctx.quant(op_inputs[1])?, ctx.quant(output)?);
+    let (x, k, y) = match (dt.is_float(), quant.0, quant.1, quant.2) {
+        (false, Some(x), Some(k), Some(y)) => (x, k, y),
+        _ => {
+            let wire = ctx.wire("conv", expand(conv), &wires)?;
+            return super::activation(ctx, output, &wire, activation);
+        }
+    };
+    if x.is_per_axis() {
+        bail!("Per-axis quantization of convolution input is not supported")
+    }
+    let mut push = |ctx: &mut ParsingContext, suffix: &str, t: Tensor| -> TractResult<usize> {
+        wires.push(ctx.add_const(suffix, t)?);
+        Ok(wires.len() - 1)
+    };
+    conv.x_zero_point_input = Some(push(ctx, "x_zero_point", x.zero_point_tensor(dt)?)?);
+    conv.k_zero_point_input = Some(push(ctx, "k_zero_point", k.zero_point_tensor(dt)?)?);
+    let wire = match (k.uniform_scale(), y.is_per_axis()) {
+        (Some(k_scale), false) => {
+            conv.x_scale_input = Some(push(ctx, "x_scale", tensor0(x.scale[0]))?);
+            conv.k_scale_input = Some(push(ctx, "k_scale", tensor0(k_scale))?);
+            conv.y_scale_input = Some(push(ctx, "y_scale", tensor0(y.scale[0]))?);
+            conv.y_zero_point_input = Some(push(ctx, "y_zero_point", y.zero_point_tensor(dt)?)?);
+            ctx.wire("conv", expand(conv), &wires)?
+        }
+        _ => {
+            if k.scale.len() != 1 && k.scale.len() != output_channels {
+                bail!("Kernel has {} scales for {} output channels", k.scale.len(), output_channels)
+            }
+            conv.override_output_datum_type = Some(i32::datum_type());
+            let wire = ctx.wire("conv", expand(conv), &wires)?;
+            // outputs are channels-last: a vector of scales broadcasts per channel
+            let scales = k.scale.iter().map(|s| s * x.scale[0]).collect::<Vec<f32>>();
+            let scales = ctx.add_const("requantize.scale", tensor1(&scales))?;
+            let wire = ctx.wire("requantize.cast", ops::cast(f32::datum_type()), &wire)?;
+            let wire = ctx.wire("requantize", ops::math::Mul.into_hir(), &[wire[0], scales])?;
+            tvec!(super::quant::quantize_tensor(ctx, output, wire[0])?)
+        }
+    };
+    super::activation(ctx, output, &wire, activation)
+}
+
+fn pool_spec(options: &Pool2D) -> PoolSpec {
+    PoolSpec::new(
+        DataFormat::NHWC,
+        options.kernel_shape.clone(),
+        options.padding.clone(),
+        None,
+        Some(options.strides.clone()),
+        None,
+    )
+}
+
+/// Pools operate on floats: quantized pooling keeps the same quantization
+/// for input and output, so it is done on the values cast to f32.
+fn pool(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+    pool: Box<dyn InferenceOp>,
+    activation: Activation,
+) -> TractResult<TVec<OutletId>> {
+    let dt = ctx.tensor(op.inputs()?[0])?.datum_type()?;
+    let output = op.outputs()?[0];
+    let wire = if dt.is_float() {
+        ctx.wire("pool", pool, inputs)?
+    } else {
+        let wire = ctx.wire("cast_in", ops::cast(f32::datum_type()), inputs)?;
+        let wire = ctx.wire("pool", pool, &wire)?;
+        let wire = ctx.wire("round", ops::math::round(), &wire)?;
+        ctx.wire("cast_out", ops::cast(dt), &wire)?
+    };
+    super::activation(ctx, output, &wire, activation)
+}
+
+pub fn average_pool_2d(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let options = Pool2D::parse(op.builtin_options()?)?;
+    let sum_pool = ops::cnn::SumPool::new(pool_spec(&options), false, true);
+    pool(ctx, op, inputs, Box::new(sum_pool), options.activation)
+}
+
+pub fn max_pool_2d(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let options = Pool2D::parse(op.builtin_options()?)?;
+    let max_pool = ops::cnn::MaxPool::new(pool_spec(&options), None);
+    pool(ctx, op, inputs, Box::new(max_pool), options.activation)
+}
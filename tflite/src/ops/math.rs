@@ -0,0 +1,71 @@
+use super::quant::{dequantize_as, dequantize_tensor, quantize_tensor};
+use crate::model::ParsingContext;
+use crate::schema::options;
+use crate::schema::Operator;
+use tract_hir::internal::*;
+use tract_hir::ops;
+
+/// Element-wise arithmetic on quantized operands is computed on their
+/// dequantized values, the result being quantized to the output type.
+fn binary(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+    mini_op: Box<dyn InferenceOp>,
+) -> TractResult<TVec<OutletId>> {
+    let activation = options::binary_activation(op.builtin_options()?)?;
+    let mut wires: TVec<OutletId> = inputs.into();
+    for (ix, input) in op.inputs()?.iter().enumerate() {
+        if ctx.quant(*input)?.is_some() {
+            wires[ix] = dequantize_as(ctx, &format!("dequantize.{}", ix), *input, wires[ix])?;
+        }
+    }
+    let output = op.outputs()?[0];
+    let mut wire = ctx.wire("binary", mini_op, &wires)?[0];
+    if ctx.quant(output)?.is_some() {
+        wire = quantize_tensor(ctx, output, wire)?;
+    }
+    super::activation(ctx, output, &[wire], activation)
+}
+
+pub fn add(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    binary(ctx, op, inputs, ops::math::Add.into_hir())
+}
+
+pub fn sub(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    binary(ctx, op, inputs, ops::math::Sub.into_hir())
+}
+
+pub fn mul(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    binary(ctx, op, inputs, ops::math::Mul.into_hir())
+}
+
+pub fn mean(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let keep_dims = options::reducer_keep_dims(op.builtin_options()?)?;
+    let axes = ctx.konst(op.inputs()?[1])?.context("Mean axes must be a constant")?;
+    let axes = axes.cast_to::<i64>()?.as_slice::<i64>()?.to_vec();
+    let reduce = ops::nn::Reduce::new(Some(axes), keep_dims, ops::nn::Reducer::Mean);
+    let (input, output) = (op.inputs()?[0], op.outputs()?[0]);
+    if ctx.quant(input)?.is_none() {
+        return ctx.wire("", expand(reduce), &inputs[0..1]);
+    }
+    let wire = dequantize_tensor(ctx, input, inputs[0])?;
+    let wire = ctx.wire("mean", expand(reduce), &[wire])?;
+    Ok(tvec!(quantize_tensor(ctx, output, wire[0])?))
+}
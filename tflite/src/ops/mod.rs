@@ -0,0 +1,76 @@
+use crate::model::{ParsingContext, TfliteOpRegister};
+use crate::schema::builtin::*;
+use crate::schema::options::Activation;
+use tract_hir::internal::*;
+use tract_hir::ops;
+
+macro_rules! op_tflite {
+    () => {
+        fn op_families(&self) -> &'static [&'static str] {
+            &["tflite"]
+        }
+    };
+}
+
+mod array;
+mod cnn;
+mod math;
+mod nn;
+mod quant;
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    reg.insert(ADD, math::add);
+    reg.insert(SUB, math::sub);
+    reg.insert(MUL, math::mul);
+    reg.insert(MEAN, math::mean);
+    reg.insert(LOGISTIC, nn::logistic);
+    reg.insert(TANH, nn::tanh);
+    reg.insert(RELU, |ctx, op, inputs| activation(ctx, op.outputs()?[0], inputs, Activation::Relu));
+    reg.insert(RELU6, |ctx, op, inputs| {
+        activation(ctx, op.outputs()?[0], inputs, Activation::Relu6)
+    });
+    reg.insert(CONCATENATION, array::concatenation);
+    reg.insert(PAD, array::pad);
+    reg.insert(RESHAPE, array::reshape);
+    reg.insert(AVERAGE_POOL_2D, cnn::average_pool_2d);
+    reg.insert(MAX_POOL_2D, cnn::max_pool_2d);
+    reg.insert(CONV_2D, cnn::conv_2d);
+    reg.insert(DEPTHWISE_CONV_2D, cnn::depthwise_conv_2d);
+    reg.insert(FULLY_CONNECTED, nn::fully_connected);
+    reg.insert(SOFTMAX, nn::softmax);
+    reg.insert(QUANTIZE, quant::quantize);
+    reg.insert(DEQUANTIZE, quant::dequantize);
+}
+
+/// Wire a (fused) activation function. For quantized outputs, bounds are
+/// translated using the output tensor quantization.
+pub fn activation(
+    ctx: &mut ParsingContext,
+    output: i32,
+    inputs: &[OutletId],
+    activation: Activation,
+) -> TractResult<TVec<OutletId>> {
+    let (low, high) = match activation {
+        Activation::None => return Ok(inputs.into()),
+        Activation::Tanh => return ctx.wire("tanh", ops::math::tanh(), inputs),
+        Activation::Relu => (Some(0.0), None),
+        Activation::ReluN1To1 => (Some(-1.0), Some(1.0)),
+        Activation::Relu6 => (Some(0.0), Some(6.0)),
+    };
+    match ctx.quant(output)? {
+        Some(q) if q.is_per_axis() => {
+            // per-axis bounds: clip the dequantized values
+            let wire = quant::dequantize_as(ctx, "activation.dequantize", output, inputs[0])?;
+            let clip = expand(ops::activations::Clip::new(low, high));
+            let wire = ctx.wire("activation", clip, &[wire])?;
+            Ok(tvec!(quant::quantize_as(ctx, "activation.quantize", output, wire[0])?))
+        }
+        Some(q) => {
+            let (scale, zp) = (q.scale[0], q.zero_point[0] as f32);
+            let quantize = |x: f32| (x / scale).round() + zp;
+            let clip = ops::activations::Clip::new(low.map(quantize), high.map(quantize));
+            ctx.wire("activation", expand(clip), inputs)
+        }
+        None => ctx.wire("activation", expand(ops::activations::Clip::new(low, high)), inputs),
+    }
+}
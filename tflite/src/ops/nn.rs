@@ -0,0 +1,83 @@
+use super::quant::{dequantize_tensor, quantize_tensor};
+use crate::model::ParsingContext;
+use crate::schema::options;
+use crate::schema::Operator;
+use tract_hir::internal::*;
+use tract_hir::ops;
+
+/// FULLY_CONNECTED is translated to a convolution over a [batch, input]
+/// HWC-like tensor with a 1-sized kernel, so quantization is handled the
+/// same way as for convolutions.
+pub fn fully_connected(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let activation = options::fully_connected_activation(op.builtin_options()?)?;
+    let op_inputs = op.inputs()?;
+    let output = op.outputs()?[0];
+    let weights = ctx.konst(op_inputs[1])?.context("FullyConnected weights must be a constant")?;
+    let input_size = weights.shape()[1];
+    let mut wires: TVec<OutletId> = inputs.into();
+    if ctx.shape(op_inputs[0])?.len() != 2 {
+        let shape = ctx.add_const("input_shape", tensor1(&[-1i64, input_size as i64]))?;
+        wires[0] =
+            ctx.wire("reshape_input", expand(ops::array::Reshape::new()), &[wires[0], shape])?[0];
+    }
+    let mut kernel = weights;
+    kernel.insert_axis(2)?;
+    let conv = ops::cnn::Conv::default().hwc();
+    let mut wire = super::cnn::conv(ctx, op, &wires, conv, kernel, activation)?;
+    let output_shape = ctx.shape(output)?;
+    if output_shape.len() != 2 {
+        let shape = tensor1(&output_shape.iter().map(|d| *d as i64).collect::<Vec<_>>());
+        let shape = ctx.add_const("output_shape", shape)?;
+        wire = ctx.wire("reshape_output", expand(ops::array::Reshape::new()), &[wire[0], shape])?;
+    }
+    Ok(wire)
+}
+
+pub fn softmax(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let beta = options::softmax_beta(op.builtin_options()?)?;
+    let mut wire = dequantize_tensor(ctx, op.inputs()?[0], inputs[0])?;
+    if beta != 1.0 {
+        let beta = ctx.add_const("beta", tensor0(beta))?;
+        wire = ctx.wire("scale", ops::math::Mul.into_hir(), &[wire, beta])?[0];
+    }
+    wire = ctx.wire("softmax", expand(ops::nn::LayerSoftmax::new(-1)), &[wire])?[0];
+    Ok(tvec!(quantize_tensor(ctx, op.outputs()?[0], wire)?))
+}
+
+/// Evaluate a float function on the dequantized input, quantizing the
+/// result to the output type.
+fn float_unary(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+    name: &str,
+    unary: impl Into<Box<dyn InferenceOp>>,
+) -> TractResult<TVec<OutletId>> {
+    let wire = dequantize_tensor(ctx, op.inputs()?[0], inputs[0])?;
+    let wire = ctx.wire(name, unary, &[wire])?;
+    Ok(tvec!(quantize_tensor(ctx, op.outputs()?[0], wire[0])?))
+}
+
+pub fn logistic(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    float_unary(ctx, op, inputs, "logistic", ops::nn::sigmoid())
+}
+
+pub fn tanh(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    float_unary(ctx, op, inputs, "tanh", ops::math::tanh())
+}
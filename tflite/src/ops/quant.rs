@@ -0,0 +1,196 @@
+use crate::model::ParsingContext;
+use crate::schema::Operator;
+use tract_hir::internal::*;
+use tract_hir::ops;
+
+/// Quantize floats with a per-tensor scale and zero point.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct Quantize {
+    #[educe(Hash(method = "hash_f32"))]
+    scale: f32,
+    zero_point: i64,
+    dt: DatumType,
+}
+
+impl_dyn_hash!(Quantize);
+
+impl Expansion for Quantize {
+    fn name(&self) -> Cow<str> {
+        "TfliteQuantize".into()
+    }
+
+    op_tflite!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, self.dt)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_hir::ops::quant::*;
+        let op = match self.dt {
+            DatumType::U8 => quantize_linear_u8(self.scale.recip(), self.zero_point as u8),
+            DatumType::I8 => quantize_linear_i8(self.scale.recip(), self.zero_point as i8),
+            dt => bail!("Unsupported quantized type {:?}", dt),
+        };
+        target.wire_node(prefix, op, inputs)
+    }
+}
+
+/// Dequantize integers with a per-tensor scale and zero point.
+#[derive(Debug, Clone, new, Educe)]
+#[educe(Hash)]
+pub struct Dequantize {
+    #[educe(Hash(method = "hash_f32"))]
+    scale: f32,
+    zero_point: i64,
+}
+
+impl_dyn_hash!(Dequantize);
+
+impl Expansion for Dequantize {
+    fn name(&self) -> Cow<str> {
+        "TfliteDequantize".into()
+    }
+
+    op_tflite!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let op = ops::quant::DequantizeLinearF32::new(self.scale, self.zero_point as i32);
+        target.wire_node(prefix, op, inputs)
+    }
+}
+
+/// Wire the dequantization of a tensor, if it is quantized.
+pub fn dequantize_tensor(
+    ctx: &mut ParsingContext,
+    tensor: i32,
+    wire: OutletId,
+) -> TractResult<OutletId> {
+    dequantize_as(ctx, "dequantize", tensor, wire)
+}
+
+/// Wire the quantization of a float wire to the type and quantization of a
+/// tensor, if it is quantized.
+pub fn quantize_tensor(
+    ctx: &mut ParsingContext,
+    tensor: i32,
+    wire: OutletId,
+) -> TractResult<OutletId> {
+    quantize_as(ctx, "quantize", tensor, wire)
+}
+
+/// Per-axis quantization is expanded to float arithmetic, with scale and
+/// zero point broadcast along the quantization axis.
+pub fn dequantize_as(
+    ctx: &mut ParsingContext,
+    suffix: &str,
+    tensor: i32,
+    wire: OutletId,
+) -> TractResult<OutletId> {
+    if ctx.tensor(tensor)?.datum_type()?.is_float() {
+        return Ok(wire);
+    }
+    let q = ctx.quant(tensor)?.context("Integer tensor without quantization parameters")?;
+    if !q.is_per_axis() {
+        let op = Dequantize::new(q.scale[0], q.zero_point[0]);
+        return Ok(ctx.wire(suffix, expand(op), &[wire])?[0]);
+    }
+    let rank = ctx.shape(tensor)?.len();
+    let zero_point =
+        ctx.add_const(&format!("{}.zero_point", suffix), q.zero_point_along_axis(rank)?)?;
+    let scale = ctx.add_const(&format!("{}.scale", suffix), q.scale_along_axis(rank)?)?;
+    let wire = ctx.wire(&format!("{}.cast", suffix), ops::cast(f32::datum_type()), &[wire])?;
+    let wire =
+        ctx.wire(&format!("{}.sub", suffix), ops::math::Sub.into_hir(), &[wire[0], zero_point])?;
+    Ok(ctx.wire(suffix, ops::math::Mul.into_hir(), &[wire[0], scale])?[0])
+}
+
+pub fn quantize_as(
+    ctx: &mut ParsingContext,
+    suffix: &str,
+    tensor: i32,
+    wire: OutletId,
+) -> TractResult<OutletId> {
+    let dt = ctx.tensor(tensor)?.datum_type()?;
+    if dt.is_float() {
+        return Ok(wire);
+    }
+    let q = ctx.quant(tensor)?.context("Integer tensor without quantization parameters")?;
+    if !q.is_per_axis() {
+        let op = Quantize::new(q.scale[0], q.zero_point[0], dt);
+        return Ok(ctx.wire(suffix, expand(op), &[wire])?[0]);
+    }
+    let (min, max) = match dt {
+        DatumType::U8 => (u8::min_value() as f32, u8::max_value() as f32),
+        DatumType::I8 => (i8::min_value() as f32, i8::max_value() as f32),
+        dt => bail!("Unsupported quantized type {:?}", dt),
+    };
+    let rank = ctx.shape(tensor)?.len();
+    let scale = ctx.add_const(&format!("{}.scale", suffix), q.scale_along_axis(rank)?)?;
+    let zero_point =
+        ctx.add_const(&format!("{}.zero_point", suffix), q.zero_point_along_axis(rank)?)?;
+    let wire = ctx.wire(&format!("{}.div", suffix), ops::math::Div.into_hir(), &[wire, scale])?;
+    let wire = ctx.wire(&format!("{}.round", suffix), ops::math::round(), &wire)?;
+    let wire =
+        ctx.wire(&format!("{}.add", suffix), ops::math::Add.into_hir(), &[wire[0], zero_point])?;
+    let clip = ops::activations::Clip::new(Some(min), Some(max));
+    let wire = ctx.wire(&format!("{}.clip", suffix), expand(clip), &wire)?;
+    Ok(ctx.wire(suffix, ops::cast(dt), &wire)?[0])
+}
+
+/// QUANTIZE: float to integer, or requantization from integer to integer.
+pub fn quantize(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let wire = dequantize_tensor(ctx, op.inputs()?[0], inputs[0])?;
+    Ok(tvec!(quantize_tensor(ctx, op.outputs()?[0], wire)?))
+}
+
+/// DEQUANTIZE: integer to float. Also used for float16 constants.
+pub fn dequantize(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let input = op.inputs()?[0];
+    if ctx.tensor(input)?.datum_type()? == DatumType::F16 {
+        return ctx.wire("", ops::cast(f32::datum_type()), inputs);
+    }
+    Ok(tvec!(dequantize_tensor(ctx, input, inputs[0])?))
+}
@@ -0,0 +1,357 @@
+//! Typed accessors for the subset of the TFLite schema (schema.fbs) tract
+//! uses.
+use crate::flat::Table;
+use tract_hir::internal::*;
+
+pub const IDENTIFIER: &[u8; 4] = b"TFL3";
+
+#[derive(Clone, Copy, Debug)]
+pub struct Model<'a>(pub Table<'a>);
+
+impl<'a> Model<'a> {
+    pub fn root(buf: &'a [u8]) -> TractResult<Model<'a>> {
+        if buf.get(4..8) != Some(&IDENTIFIER[..]) {
+            bail!("Not a TFLite model (missing {:?} identifier)", std::str::from_utf8(IDENTIFIER)?)
+        }
+        Ok(Model(Table::root(buf)?))
+    }
+
+    pub fn version(&self) -> TractResult<u32> {
+        self.0.scalar(0, 0)
+    }
+
+    pub fn operator_codes(&self) -> TractResult<Vec<OperatorCode<'a>>> {
+        Ok(self.0.tables(1)?.into_iter().map(OperatorCode).collect())
+    }
+
+    pub fn subgraphs(&self) -> TractResult<Vec<SubGraph<'a>>> {
+        Ok(self.0.tables(2)?.into_iter().map(SubGraph).collect())
+    }
+
+    pub fn description(&self) -> TractResult<Option<&'a str>> {
+        self.0.string(3)
+    }
+
+    pub fn buffers(&self) -> TractResult<Vec<Buffer<'a>>> {
+        Ok(self.0.tables(4)?.into_iter().map(Buffer).collect())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct OperatorCode<'a>(pub Table<'a>);
+
+impl<'a> OperatorCode<'a> {
+    /// The builtin operator code. Older models only have the deprecated
+    /// (byte-sized) field, newer ones set both, keeping the largest is right
+    /// in both cases.
+    pub fn builtin_code(&self) -> TractResult<i32> {
+        let deprecated = self.0.scalar::<i8>(0, 0)? as i32;
+        let code = self.0.scalar::<i32>(3, 0)?;
+        Ok(deprecated.max(code))
+    }
+
+    pub fn custom_code(&self) -> TractResult<Option<&'a str>> {
+        self.0.string(1)
+    }
+
+    pub fn version(&self) -> TractResult<i32> {
+        self.0.scalar(2, 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SubGraph<'a>(pub Table<'a>);
+
+impl<'a> SubGraph<'a> {
+    pub fn tensors(&self) -> TractResult<Vec<TensorDef<'a>>> {
+        Ok(self.0.tables(0)?.into_iter().map(TensorDef).collect())
+    }
+
+    pub fn inputs(&self) -> TractResult<Vec<i32>> {
+        self.0.vector(1)
+    }
+
+    pub fn outputs(&self) -> TractResult<Vec<i32>> {
+        self.0.vector(2)
+    }
+
+    pub fn operators(&self) -> TractResult<Vec<Operator<'a>>> {
+        Ok(self.0.tables(3)?.into_iter().map(Operator).collect())
+    }
+
+    pub fn name(&self) -> TractResult<Option<&'a str>> {
+        self.0.string(4)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TensorDef<'a>(pub Table<'a>);
+
+impl<'a> TensorDef<'a> {
+    pub fn shape(&self) -> TractResult<Vec<i32>> {
+        self.0.vector(0)
+    }
+
+    pub fn datum_type(&self) -> TractResult<DatumType> {
+        let dt = match self.0.scalar::<i8>(1, 0)? {
+            0 => DatumType::F32,
+            1 => DatumType::F16,
+            2 => DatumType::I32,
+            3 => DatumType::U8,
+            4 => DatumType::I64,
+            5 => DatumType::String,
+            6 => DatumType::Bool,
+            7 => DatumType::I16,
+            9 => DatumType::I8,
+            10 => DatumType::F64,
+            other => bail!("Unsupported TFLite tensor type {}", other),
+        };
+        Ok(dt)
+    }
+
+    pub fn buffer(&self) -> TractResult<u32> {
+        self.0.scalar(2, 0)
+    }
+
+    pub fn name(&self) -> TractResult<Option<&'a str>> {
+        self.0.string(3)
+    }
+
+    pub fn quantization(&self) -> TractResult<Option<Quantization<'a>>> {
+        Ok(self.0.table(4)?.map(Quantization))
+    }
+
+    /// Shape with -1 for unknown dimensions, if present.
+    pub fn shape_signature(&self) -> TractResult<Vec<i32>> {
+        self.0.vector(7)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Quantization<'a>(pub Table<'a>);
+
+impl<'a> Quantization<'a> {
+    pub fn scale(&self) -> TractResult<Vec<f32>> {
+        self.0.vector(2)
+    }
+
+    pub fn zero_point(&self) -> TractResult<Vec<i64>> {
+        self.0.vector(3)
+    }
+
+    pub fn quantized_dimension(&self) -> TractResult<i32> {
+        self.0.scalar(6, 0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Operator<'a>(pub Table<'a>);
+
+impl<'a> Operator<'a> {
+    pub fn opcode_index(&self) -> TractResult<u32> {
+        self.0.scalar(0, 0)
+    }
+
+    /// Tensor indices, -1 for omitted optional inputs.
+    pub fn inputs(&self) -> TractResult<Vec<i32>> {
+        self.0.vector(1)
+    }
+
+    pub fn outputs(&self) -> TractResult<Vec<i32>> {
+        self.0.vector(2)
+    }
+
+    pub fn builtin_options_type(&self) -> TractResult<u8> {
+        self.0.scalar(3, 0)
+    }
+
+    /// The options table. Its layout depends on the operator, see the
+    /// accessors in `options`.
+    pub fn builtin_options(&self) -> TractResult<Option<Table<'a>>> {
+        self.0.table(4)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Buffer<'a>(pub Table<'a>);
+
+impl<'a> Buffer<'a> {
+    pub fn data(&self) -> TractResult<&'a [u8]> {
+        self.0.bytes(0)
+    }
+}
+
+/// BuiltinOperator codes.
+pub mod builtin {
+    pub const ADD: i32 = 0;
+    pub const AVERAGE_POOL_2D: i32 = 1;
+    pub const CONCATENATION: i32 = 2;
+    pub const CONV_2D: i32 = 3;
+    pub const DEPTHWISE_CONV_2D: i32 = 4;
+    pub const DEQUANTIZE: i32 = 6;
+    pub const FULLY_CONNECTED: i32 = 9;
+    pub const LOGISTIC: i32 = 14;
+    pub const MAX_POOL_2D: i32 = 17;
+    pub const MUL: i32 = 18;
+    pub const RELU: i32 = 19;
+    pub const RELU6: i32 = 21;
+    pub const RESHAPE: i32 = 22;
+    pub const SOFTMAX: i32 = 25;
+    pub const TANH: i32 = 28;
+    pub const PAD: i32 = 34;
+    pub const MEAN: i32 = 40;
+    pub const SUB: i32 = 41;
+    pub const QUANTIZE: i32 = 114;
+}
+
+/// Builtin options tables field slots.
+pub mod options {
+    use crate::flat::Table;
+    use tract_hir::internal::*;
+    use tract_hir::ops::cnn::PaddingSpec;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Activation {
+        None,
+        Relu,
+        ReluN1To1,
+        Relu6,
+        Tanh,
+    }
+
+    fn activation(table: &Option<Table>, slot: usize) -> TractResult<Activation> {
+        let code = if let Some(t) = table { t.scalar::<i8>(slot, 0)? } else { 0 };
+        Ok(match code {
+            0 => Activation::None,
+            1 => Activation::Relu,
+            2 => Activation::ReluN1To1,
+            3 => Activation::Relu6,
+            4 => Activation::Tanh,
+            other => bail!("Unsupported fused activation function {}", other),
+        })
+    }
+
+    fn padding(table: &Option<Table>, slot: usize) -> TractResult<PaddingSpec> {
+        let code = if let Some(t) = table { t.scalar::<i8>(slot, 0)? } else { 0 };
+        Ok(match code {
+            0 => PaddingSpec::SameUpper,
+            1 => PaddingSpec::Valid,
+            other => bail!("Unsupported padding {}", other),
+        })
+    }
+
+    fn int(table: &Option<Table>, slot: usize, default: i32) -> TractResult<i32> {
+        if let Some(t) = table {
+            t.scalar(slot, default)
+        } else {
+            Ok(default)
+        }
+    }
+
+    /// Conv2DOptions: padding, stride_w, stride_h, activation, dilation_w,
+    /// dilation_h.
+    #[derive(Clone, Debug)]
+    pub struct Conv2D {
+        pub padding: PaddingSpec,
+        pub strides: TVec<usize>,
+        pub dilations: TVec<usize>,
+        pub activation: Activation,
+    }
+
+    impl Conv2D {
+        pub fn parse(t: Option<Table>) -> TractResult<Conv2D> {
+            Ok(Conv2D {
+                padding: padding(&t, 0)?,
+                strides: tvec!(int(&t, 2, 1)? as usize, int(&t, 1, 1)? as usize),
+                dilations: tvec!(int(&t, 5, 1)? as usize, int(&t, 4, 1)? as usize),
+                activation: activation(&t, 3)?,
+            })
+        }
+    }
+
+    /// DepthwiseConv2DOptions: padding, stride_w, stride_h, depth_multiplier,
+    /// activation, dilation_w, dilation_h.
+    #[derive(Clone, Debug)]
+    pub struct DepthwiseConv2D {
+        pub padding: PaddingSpec,
+        pub strides: TVec<usize>,
+        pub dilations: TVec<usize>,
+        pub depth_multiplier: usize,
+        pub activation: Activation,
+    }
+
+    impl DepthwiseConv2D {
+        pub fn parse(t: Option<Table>) -> TractResult<DepthwiseConv2D> {
+            Ok(DepthwiseConv2D {
+                padding: padding(&t, 0)?,
+                strides: tvec!(int(&t, 2, 1)? as usize, int(&t, 1, 1)? as usize),
+                depth_multiplier: int(&t, 3, 1)? as usize,
+                activation: activation(&t, 4)?,
+                dilations: tvec!(int(&t, 6, 1)? as usize, int(&t, 5, 1)? as usize),
+            })
+        }
+    }
+
+    /// Pool2DOptions: padding, stride_w, stride_h, filter_width,
+    /// filter_height, activation.
+    #[derive(Clone, Debug)]
+    pub struct Pool2D {
+        pub padding: PaddingSpec,
+        pub strides: TVec<usize>,
+        pub kernel_shape: TVec<usize>,
+        pub activation: Activation,
+    }
+
+    impl Pool2D {
+        pub fn parse(t: Option<Table>) -> TractResult<Pool2D> {
+            Ok(Pool2D {
+                padding: padding(&t, 0)?,
+                strides: tvec!(int(&t, 2, 1)? as usize, int(&t, 1, 1)? as usize),
+                kernel_shape: tvec!(int(&t, 4, 1)? as usize, int(&t, 3, 1)? as usize),
+                activation: activation(&t, 5)?,
+            })
+        }
+    }
+
+    /// FullyConnectedOptions: activation, weights_format, keep_num_dims.
+    pub fn fully_connected_activation(t: Option<Table>) -> TractResult<Activation> {
+        activation(&t, 0)
+    }
+
+    /// AddOptions, SubOptions, MulOptions: activation.
+    pub fn binary_activation(t: Option<Table>) -> TractResult<Activation> {
+        activation(&t, 0)
+    }
+
+    /// ConcatenationOptions: axis, activation.
+    pub fn concatenation(t: Option<Table>) -> TractResult<(i32, Activation)> {
+        Ok((int(&t, 0, 0)?, activation(&t, 1)?))
+    }
+
+    /// ReshapeOptions: new_shape.
+    pub fn reshape_new_shape(t: Option<Table>) -> TractResult<Vec<i32>> {
+        if let Some(t) = t {
+            t.vector(0)
+        } else {
+            bail!("Reshape expects either a shape input or options")
+        }
+    }
+
+    /// SoftmaxOptions: beta.
+    pub fn softmax_beta(t: Option<Table>) -> TractResult<f32> {
+        if let Some(t) = t {
+            t.scalar(0, 0.0)
+        } else {
+            Ok(0.0)
+        }
+    }
+
+    /// ReducerOptions: keep_dims.
+    pub fn reducer_keep_dims(t: Option<Table>) -> TractResult<bool> {
+        if let Some(t) = t {
+            t.scalar(0, false)
+        } else {
+            Ok(false)
+        }
+    }
+}